@@ -0,0 +1,24 @@
+//! Draws every benchmark workload with the canvas of the platform and writes the reports as JSON,
+//! to the path given after the number of iterations or otherwise to standard output.
+//!
+//! ```sh
+//! cargo run --release --features benches --example benches -- 60 reports.json
+//! ```
+
+use std::{env, fs};
+
+use vessels::{benches, graphics::canvas};
+
+fn main() {
+    let mut args = env::args().skip(1);
+    let iterations = args
+        .next()
+        .map(|iterations| iterations.parse().expect("iterations should be a number"))
+        .unwrap_or(60);
+    let reports = benches::run_all(&*canvas::new(), iterations);
+    let json = serde_json::to_string_pretty(&reports).unwrap();
+    match args.next() {
+        Some(path) => fs::write(path, json).unwrap(),
+        None => println!("{}", json),
+    }
+}
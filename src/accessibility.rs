@@ -0,0 +1,113 @@
+/// Accessibility preferences set by the user at the platform level.
+///
+/// Preferences are detected from the platform where possible and can be overridden by the
+/// application, such as from its own settings, with [Provider::set_accessibility]. Reduced motion
+/// is applied by passing the preferences of a context to
+/// [Kinetics](crate::interaction::kinetics::Kinetics::with_preferences) and the
+/// [Interpolator](crate::graphics::canvas::Interpolator::with_preferences).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Preferences {
+    /// Whether the user has requested that non-essential motion be minimized.
+    pub reduced_motion: bool,
+    /// Whether the user has requested increased contrast between content and its background.
+    pub high_contrast: bool,
+}
+
+impl Preferences {
+    /// Overrides the reduced motion preference.
+    pub fn with_reduced_motion(mut self, reduced_motion: bool) -> Self {
+        self.reduced_motion = reduced_motion;
+        self
+    }
+    /// Overrides the high contrast preference.
+    pub fn with_high_contrast(mut self, high_contrast: bool) -> Self {
+        self.high_contrast = high_contrast;
+        self
+    }
+}
+
+/// The UI scales stepped between by [zoom_in] and [zoom_out], as with the page zoom of browsers.
+const UI_SCALES: [f64; 13] = [
+    0.5, 0.67, 0.75, 0.8, 0.9, 1., 1.1, 1.25, 1.5, 1.75, 2., 2.5, 3.,
+];
+
+/// Returns the provided UI scale limited to the range stepped through by [zoom_in] and
+/// [zoom_out], or [None] if it isn't finite.
+pub(crate) fn clamp_ui_scale(scale: f64) -> Option<f64> {
+    if scale.is_finite() {
+        Some(scale.clamp(UI_SCALES[0], UI_SCALES[UI_SCALES.len() - 1]))
+    } else {
+        None
+    }
+}
+
+/// Returns the next step of UI scale larger than the provided scale, or the largest step.
+pub fn zoom_in(scale: f64) -> f64 {
+    UI_SCALES
+        .iter()
+        .cloned()
+        .find(|step| *step > scale + 1e-9)
+        .unwrap_or(UI_SCALES[UI_SCALES.len() - 1])
+}
+
+/// Returns the next step of UI scale smaller than the provided scale, or the smallest step.
+pub fn zoom_out(scale: f64) -> f64 {
+    UI_SCALES
+        .iter()
+        .rev()
+        .cloned()
+        .find(|step| *step < scale - 1e-9)
+        .unwrap_or(UI_SCALES[0])
+}
+
+/// A context that provides the accessibility preferences of the platform.
+pub trait Provider {
+    /// Returns the current accessibility preferences, as detected from the platform unless they
+    /// have been overridden. These may change while the context is running.
+    fn accessibility(&self) -> Preferences;
+    /// Overrides the preferences detected from the platform, such as with those from the
+    /// application's own settings, or returns to detecting them if given [None].
+    fn set_accessibility(&self, preferences: Option<Preferences>);
+    /// Returns the UI scale of the context, which is one unless it has been changed.
+    fn ui_scale(&self) -> f64;
+    /// Sets the UI scale of the context, which enlarges all of its content independently of the
+    /// density of the display, such as for users with impaired vision. The scale is limited to
+    /// the range stepped through by [zoom_in] and [zoom_out], and scales that aren't finite are
+    /// ignored.
+    ///
+    /// The scale multiplies into the pixel ratio of the root
+    /// [Frame](crate::graphics::canvas::Frame), whose size in logical pixels shrinks to match, so
+    /// that text, content and layouts based on that size are all enlarged together, and into that
+    /// of frames created with [Canvas::frame](crate::graphics::canvas::Canvas::frame) so that
+    /// they're drawn as sharply. Pointer input is given in the same logical pixels.
+    ///
+    /// Native contexts also step the scale with [zoom_in] and [zoom_out] when `Ctrl` and `+` or
+    /// `-` are pressed, and reset it when `Ctrl` and `0` are pressed. Browsers already do so for
+    /// the page itself.
+    fn set_ui_scale(&self, scale: f64);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn zoom_steps_through_scales() {
+        assert_eq!(zoom_in(1.), 1.1);
+        assert_eq!(zoom_out(1.), 0.9);
+        assert_eq!(zoom_in(1.2), 1.25);
+        assert_eq!(zoom_out(1.2), 1.1);
+        assert_eq!(zoom_in(3.), 3.);
+        assert_eq!(zoom_out(0.5), 0.5);
+        assert_eq!(zoom_out(10.), 3.);
+    }
+
+    #[test]
+    fn ui_scales_are_clamped() {
+        assert_eq!(clamp_ui_scale(1.2), Some(1.2));
+        assert_eq!(clamp_ui_scale(0.), Some(0.5));
+        assert_eq!(clamp_ui_scale(100.), Some(3.));
+        assert_eq!(clamp_ui_scale(f64::INFINITY), None);
+        assert_eq!(clamp_ui_scale(f64::NAN), None);
+    }
+}
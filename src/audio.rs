@@ -0,0 +1,207 @@
+use std::{
+    collections::VecDeque,
+    f64::consts::PI,
+    fmt::{self, Debug, Formatter},
+    sync::{Arc, RwLock},
+};
+
+/// The lowest frequency, in hertz, covered by the bands of an [Analysis].
+const MINIMUM_FREQUENCY: f64 = 20.;
+
+/// The loudness and spectrum of the most recent window of an audio stream.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Analysis {
+    /// The root mean square amplitude of the window, between zero and one for samples in range.
+    pub amplitude: f64,
+    /// The magnitude of the loudest frequency in each band, between zero and one. Bands are spaced
+    /// logarithmically from 20 hertz to half the sample rate, lowest first.
+    pub bands: Vec<f64>,
+}
+
+struct AnalyzerState {
+    samples: VecDeque<f32>,
+    previous: Vec<f64>,
+}
+
+/// Analyzes an audio stream for audio-reactive content such as music visualizers.
+///
+/// Samples are pushed from the stream, typically on its own thread, into a clone of the
+/// [Analyzer], and each tick reads an [Analysis] of the most recent window of them. Clones share
+/// the same stream.
+#[derive(Clone)]
+pub struct Analyzer {
+    state: Arc<RwLock<AnalyzerState>>,
+    sample_rate: f64,
+    window: usize,
+    bands: usize,
+    smoothing: f64,
+}
+
+impl Analyzer {
+    /// Creates an [Analyzer] for a mono stream at the provided sample rate in hertz, analyzing
+    /// windows of at least the provided number of samples into the provided number of bands.
+    ///
+    /// Windows are rounded up to a power of two. Larger windows resolve lower frequencies but
+    /// respond more slowly, and bands too narrow to contain a frequency of the window stay at zero.
+    pub fn new(sample_rate: f64, window: usize, bands: usize) -> Analyzer {
+        let window = window.max(2).next_power_of_two();
+        Analyzer {
+            state: Arc::new(RwLock::new(AnalyzerState {
+                samples: VecDeque::with_capacity(window),
+                previous: vec![0.; bands],
+            })),
+            sample_rate,
+            window,
+            bands,
+            smoothing: 0.,
+        }
+    }
+    /// Sets the fraction, between zero and one, of the previous bands kept in each analysis, which
+    /// steadies the output at the expense of responsiveness. This is zero by default.
+    pub fn with_smoothing(mut self, smoothing: f64) -> Self {
+        self.smoothing = smoothing.clamp(0., 1.);
+        self
+    }
+    /// Adds samples from the stream. Only the most recent window of samples is kept.
+    pub fn push(&self, samples: &[f32]) {
+        let mut state = self.state.write().unwrap();
+        state.samples.extend(samples);
+        let excess = state.samples.len().saturating_sub(self.window);
+        state.samples.drain(..excess);
+    }
+    /// Analyzes the most recent window of samples. Missing samples are treated as silence.
+    pub fn analyze(&self) -> Analysis {
+        let mut state = self.state.write().unwrap();
+        let mut samples = vec![0.; self.window - state.samples.len()];
+        samples.extend(state.samples.iter().map(|sample| f64::from(*sample)));
+        let amplitude =
+            (samples.iter().map(|sample| sample * sample).sum::<f64>() / self.window as f64).sqrt();
+        let mut bands = self.spectrum(&samples);
+        for (band, previous) in bands.iter_mut().zip(&state.previous) {
+            *band = *band * (1. - self.smoothing) + previous * self.smoothing;
+        }
+        state.previous = bands.clone();
+        Analysis { amplitude, bands }
+    }
+    /// Wraps a tick handler such that it also receives an [Analysis] on every tick. The returned
+    /// handler can be bound to a [Ticker](crate::graphics::canvas::Ticker).
+    pub fn hook<F>(&self, mut handler: F) -> Box<dyn FnMut(f64) + 'static + Send + Sync>
+    where
+        F: FnMut(f64, Analysis) + 'static + Send + Sync,
+    {
+        let analyzer = self.clone();
+        Box::new(move |delta| handler(delta, analyzer.analyze()))
+    }
+    fn spectrum(&self, samples: &[f64]) -> Vec<f64> {
+        let size = self.window as f64;
+        let mut bins: Vec<(f64, f64)> = samples
+            .iter()
+            .enumerate()
+            .map(|(index, sample)| {
+                let hann = 0.5 - 0.5 * (2. * PI * index as f64 / size).cos();
+                (sample * hann, 0.)
+            })
+            .collect();
+        fft(&mut bins);
+        let mut bands = vec![0.; self.bands];
+        let nyquist = self.sample_rate / 2.;
+        let range = (nyquist / MINIMUM_FREQUENCY).ln();
+        for (index, (real, imaginary)) in bins.iter().enumerate().take(self.window / 2).skip(1) {
+            let frequency = index as f64 * self.sample_rate / size;
+            if frequency < MINIMUM_FREQUENCY || range <= 0. {
+                continue;
+            }
+            let band = ((frequency / MINIMUM_FREQUENCY).ln() / range * self.bands as f64) as usize;
+            if let Some(band) = bands.get_mut(band) {
+                // A full-scale sinusoid peaks at a quarter of the window size under a Hann window.
+                let magnitude = real.hypot(*imaginary) / (size / 4.);
+                *band = magnitude.min(1.).max(*band);
+            }
+        }
+        bands
+    }
+}
+
+impl Debug for Analyzer {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Analyzer")
+            .field("sample_rate", &self.sample_rate)
+            .field("window", &self.window)
+            .field("bands", &self.bands)
+            .field("smoothing", &self.smoothing)
+            .finish()
+    }
+}
+
+/// Transforms complex values, whose count is a power of two, into their discrete Fourier transform
+/// in place.
+fn fft(values: &mut [(f64, f64)]) {
+    let count = values.len();
+    let mut target = 0;
+    for index in 1..count {
+        let mut bit = count >> 1;
+        while target & bit != 0 {
+            target ^= bit;
+            bit >>= 1;
+        }
+        target |= bit;
+        if index < target {
+            values.swap(index, target);
+        }
+    }
+    let mut length = 2;
+    while length <= count {
+        let angle = -2. * PI / length as f64;
+        for start in (0..count).step_by(length) {
+            for offset in 0..length / 2 {
+                let (sin, cos) = (angle * offset as f64).sin_cos();
+                let (even, odd) = (values[start + offset], values[start + offset + length / 2]);
+                let twiddled = (odd.0 * cos - odd.1 * sin, odd.0 * sin + odd.1 * cos);
+                values[start + offset] = (even.0 + twiddled.0, even.1 + twiddled.1);
+                values[start + offset + length / 2] = (even.0 - twiddled.0, even.1 - twiddled.1);
+            }
+        }
+        length <<= 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sine(frequency: f64, sample_rate: f64, count: usize) -> Vec<f32> {
+        (0..count)
+            .map(|index| (2. * PI * frequency * index as f64 / sample_rate).sin() as f32)
+            .collect()
+    }
+
+    #[test]
+    fn silence_is_empty() {
+        let analysis = Analyzer::new(44_100., 1000, 8).analyze();
+        assert_eq!(analysis.amplitude, 0.);
+        assert_eq!(analysis.bands, vec![0.; 8]);
+    }
+
+    #[test]
+    fn sines_peak_in_their_band() {
+        let analyzer = Analyzer::new(1024., 256, 4);
+        analyzer.push(&sine(256., 1024., 300));
+        let analysis = analyzer.analyze();
+        assert!((analysis.amplitude - 0.5f64.sqrt()).abs() < 1e-3);
+        assert!(analysis.bands[3] > 0.99);
+        assert!(analysis.bands[..3].iter().all(|band| *band < 1e-3));
+        analyzer.push(&sine(40., 1024., 256));
+        let low = analyzer.analyze();
+        assert!(low.bands[0] > 0.5);
+        assert!(low.bands[3] < 0.01);
+    }
+
+    #[test]
+    fn smoothing_keeps_previous_bands() {
+        let analyzer = Analyzer::new(1024., 256, 4).with_smoothing(0.5);
+        analyzer.push(&sine(256., 1024., 256));
+        let first = analyzer.analyze().bands[3];
+        analyzer.push(&[0.; 256]);
+        assert!((analyzer.analyze().bands[3] - first / 2.).abs() < 1e-9);
+    }
+}
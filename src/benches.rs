@@ -0,0 +1,233 @@
+use crate::graphics::{
+    canvas::{Canvas, Content},
+    path::{
+        Builder, GradientStop, LinearGradient, Primitive, RadialGradient, Shadow, SpreadMode,
+        StrokeBuilder, Texture,
+    },
+    text::Text,
+    LDRColor, Rect, Transform2, Vector2,
+};
+use crate::util::now;
+
+use serde::Serialize;
+
+/// The size of the frame every workload draws into.
+const SIZE: (f64, f64) = (1024., 768.);
+
+const PASSAGE: &str = "Vessels draws the same scene through every backend, so a change in the \
+                       time it takes to draw that scene points at the backend or the renderer.";
+
+/// A canonical scene used to measure rendering performance.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Workload {
+    /// Ten thousand stroked lines and curves.
+    Strokes,
+    /// One thousand wrapped blocks of text.
+    TextBlocks,
+    /// One thousand rectangles filled with linear and radial gradients.
+    GradientStorm,
+    /// Two hundred rounded rectangles casting large blurred shadows.
+    BlurStress,
+}
+
+/// The timings of a single [Workload], in milliseconds.
+#[derive(Clone, Debug, Serialize)]
+pub struct Report {
+    /// The name of the workload.
+    pub workload: &'static str,
+    /// The number of objects in the scene.
+    pub objects: usize,
+    /// The number of frames drawn.
+    pub iterations: u32,
+    /// The time taken to add the scene to a new frame.
+    pub setup: f64,
+    /// The mean time taken to draw a frame.
+    pub mean: f64,
+    /// The shortest time taken to draw a frame.
+    pub minimum: f64,
+    /// The longest time taken to draw a frame.
+    pub maximum: f64,
+}
+
+/// A linear congruential generator so that every run lays out an identical scene.
+struct Sequence(u32);
+
+impl Sequence {
+    fn next(&mut self) -> f64 {
+        self.0 = self.0.wrapping_mul(1_103_515_245).wrapping_add(12345);
+        f64::from(self.0 >> 8) / f64::from(1u32 << 24)
+    }
+    fn point(&mut self) -> Vector2 {
+        (self.next() * SIZE.0, self.next() * SIZE.1).into()
+    }
+    fn color(&mut self) -> LDRColor {
+        LDRColor::rgb(
+            (self.next() * 255.) as u8,
+            (self.next() * 255.) as u8,
+            (self.next() * 255.) as u8,
+        )
+    }
+}
+
+impl Workload {
+    /// Returns every workload.
+    pub fn all() -> Vec<Workload> {
+        vec![
+            Workload::Strokes,
+            Workload::TextBlocks,
+            Workload::GradientStorm,
+            Workload::BlurStress,
+        ]
+    }
+    /// Returns the name of the workload as used in a [Report].
+    pub fn name(self) -> &'static str {
+        match self {
+            Workload::Strokes => "strokes",
+            Workload::TextBlocks => "text_blocks",
+            Workload::GradientStorm => "gradient_storm",
+            Workload::BlurStress => "blur_stress",
+        }
+    }
+    /// Returns the content of the scene.
+    pub fn scene(self) -> Vec<Content> {
+        let mut sequence = Sequence(1);
+        match self {
+            Workload::Strokes => (0..10_000)
+                .map(|index| {
+                    let builder = Builder::new().move_to(sequence.point());
+                    let builder = if index % 2 == 0 {
+                        builder.line_to(sequence.point())
+                    } else {
+                        builder.cubic_to(sequence.point(), sequence.point(), sequence.point())
+                    };
+                    builder
+                        .done()
+                        .stroke(
+                            StrokeBuilder::new(
+                                sequence.color().into(),
+                                1. + sequence.next() as f32 * 2.,
+                            )
+                            .cap_round()
+                            .finalize(),
+                        )
+                        .finalize()
+                        .into()
+                })
+                .collect(),
+            Workload::TextBlocks => (0..1_000)
+                .map(|_| {
+                    let content: Content = Text::new(PASSAGE)
+                        .with_size(10. + sequence.next() * 8.)
+                        .with_color(sequence.color())
+                        .with_max_width(120. + sequence.next() * 200.)
+                        .into();
+                    content.with_transform(Transform2::from(sequence.point()))
+                })
+                .collect(),
+            Workload::GradientStorm => (0..1_000)
+                .map(|index| {
+                    let size = Vector2::from((40., 40.)) + sequence.point() / 8.;
+                    let stops = vec![
+                        GradientStop::new(0., sequence.color()).unwrap(),
+                        GradientStop::new(0.5, sequence.color()).unwrap(),
+                        GradientStop::new(1., sequence.color()).unwrap(),
+                    ];
+                    let texture = if index % 2 == 0 {
+                        Texture::LinearGradient(LinearGradient {
+                            stops,
+                            start: Vector2::default(),
+                            end: size,
+                            spread: SpreadMode::Pad,
+                            transform: None,
+                            dither: None,
+                        })
+                    } else {
+                        Texture::RadialGradient(RadialGradient {
+                            stops,
+                            start: size / 2.,
+                            start_radius: 0.,
+                            end: size / 2.,
+                            end_radius: size.x.max(size.y) / 2.,
+                            spread: SpreadMode::Pad,
+                            transform: None,
+                            dither: None,
+                        })
+                    };
+                    let content: Content = Primitive::rectangle(size)
+                        .fill(texture.into())
+                        .finalize()
+                        .into();
+                    content.with_transform(Transform2::from(sequence.point()))
+                })
+                .collect(),
+            Workload::BlurStress => (0..200)
+                .map(|_| {
+                    let content: Content = Primitive::rounded_rectangle((120., 80.), 12.)
+                        .fill(LDRColor::white().into())
+                        .shadow(
+                            Shadow::new(LDRColor::black().with_alpha(128))
+                                .blur(16. + sequence.next() * 16.)
+                                .offset((0., 8.)),
+                        )
+                        .finalize()
+                        .into();
+                    content.with_transform(Transform2::from(sequence.point()))
+                })
+                .collect(),
+        }
+    }
+    /// Adds the scene to a new frame from the provided canvas and draws it the given number of
+    /// times, reporting how long each step took.
+    ///
+    /// Each frame is drawn through the hidden `Frame::draw` that contexts call as they render, so
+    /// the timings cover rasterizing into the surface of the frame but not presenting it, and
+    /// the benches need updating along with that method. Backends only draw what changed since
+    /// the previous draw, so the viewport is set again before each iteration to have the whole
+    /// scene drawn every time.
+    pub fn run<C>(self, canvas: &C, iterations: u32) -> Report
+    where
+        C: Canvas + ?Sized,
+    {
+        let start = now();
+        let mut frame = canvas.frame();
+        let viewport = Rect::new(Vector2::default(), SIZE);
+        frame.resize(SIZE.into());
+        frame.set_viewport(viewport);
+        let scene = self.scene();
+        let objects = scene.len();
+        for content in scene {
+            frame.add(content);
+        }
+        let setup = now() - start;
+        let mut durations = vec![];
+        for _ in 0..iterations {
+            frame.set_viewport(viewport);
+            let start = now();
+            frame.draw();
+            durations.push(now() - start);
+        }
+        Report {
+            workload: self.name(),
+            objects,
+            iterations,
+            setup,
+            mean: durations.iter().sum::<f64>() / f64::from(iterations.max(1)),
+            minimum: durations.iter().cloned().fold(std::f64::INFINITY, f64::min),
+            maximum: durations.iter().cloned().fold(0., f64::max),
+        }
+    }
+}
+
+/// Runs every [Workload] against the provided canvas, drawing each the given number of times.
+///
+/// The `benches` example runs this against the canvas of the platform and writes the reports as
+/// JSON.
+pub fn run_all<C>(canvas: &C, iterations: u32) -> Vec<Report>
+where
+    C: Canvas + ?Sized,
+{
+    Workload::all()
+        .into_iter()
+        .map(|workload| workload.run(canvas, iterations))
+        .collect()
+}
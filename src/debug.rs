@@ -0,0 +1,469 @@
+use crate::graphics::{
+    canvas::{scene::Scene, Canvas, Content, Frame, Layer, Object, Rasterizable},
+    path::{Builder, Primitive, StrokeBuilder},
+    text::{Font, Text},
+    LDRColor, Rect, Transform2, Vector2,
+};
+use crate::input::{pointer, Event};
+use crate::util::now;
+
+use std::{
+    collections::VecDeque,
+    fmt::{self, Debug, Formatter},
+};
+
+/// The number of latency samples the reported statistics are taken over.
+const SAMPLES: usize = 60;
+
+/// The tag of the objects of a [TimeTravel] overlay, which are left out of its snapshots.
+const TIME_TRAVEL_TAG: &str = "vessels::debug::TimeTravel";
+
+/// An overlay for tuning the input and rendering pipeline, which draws a trail through recent
+/// pointer positions and reports the latency between receiving input and presenting it.
+///
+/// Latency is measured from the moment an event is passed to [LatencyOverlay::record] to the
+/// second [LatencyOverlay::tick] after it. The frame drawn after the first tick is the earliest
+/// that can reflect the event, and it has been presented by the time the next tick runs, so the
+/// measurement covers handling, drawing and presentation. Where ticks follow vsync it overstates
+/// the true latency by at most the time between presentation and the next tick, which is
+/// consistent enough to compare changes to the pipeline.
+pub struct LatencyOverlay {
+    frame: Box<dyn Frame>,
+    layer: Box<dyn Layer>,
+    trail: Box<dyn Object>,
+    background: Box<dyn Object>,
+    label: Box<dyn Object>,
+    length: usize,
+    positions: VecDeque<Vector2>,
+    pending: Vec<(f64, u8)>,
+    samples: VecDeque<f64>,
+    dirty: bool,
+}
+
+impl LatencyOverlay {
+    /// Adds the overlay to the provided [Frame] as a new [Layer], which should be the topmost. The
+    /// trail defaults to the last 32 pointer positions.
+    pub fn new(frame: &mut dyn Frame) -> LatencyOverlay {
+        let mut layer = frame.add_layer();
+        let empty = || Content::from(Builder::new().done().finalize());
+        LatencyOverlay {
+            frame: frame.box_clone(),
+            trail: layer.add(empty()),
+            background: layer.add(
+                empty()
+                    .with_depth(1)
+                    .with_transform(Vector2::from(8.).into()),
+            ),
+            label: layer.add(
+                empty()
+                    .with_depth(2)
+                    .with_transform(Vector2::from(12.).into()),
+            ),
+            layer,
+            length: 32,
+            positions: VecDeque::new(),
+            pending: vec![],
+            samples: VecDeque::new(),
+            dirty: true,
+        }
+    }
+    /// Sets the number of recent pointer positions the trail is drawn through.
+    pub fn with_trail(mut self, length: usize) -> Self {
+        self.length = length;
+        self
+    }
+    /// Records an input event as it is received, which should be as soon as it is taken from the
+    /// input stream.
+    pub fn record(&mut self, event: &Event) {
+        if let Event::Pointer(event) = event {
+            if let pointer::Action::Leave | pointer::Action::Cancel = event.action {
+                self.positions.clear();
+            } else {
+                self.positions.push_back(event.position);
+                while self.positions.len() > self.length {
+                    self.positions.pop_front();
+                }
+            }
+            self.dirty = true;
+        }
+        self.pending.push((now(), 0));
+    }
+    /// Returns the most recent latency in milliseconds, if any has been measured.
+    pub fn latency(&self) -> Option<f64> {
+        self.samples.back().cloned()
+    }
+    /// Returns the mean and greatest latency, in milliseconds, over the most recent samples.
+    pub fn statistics(&self) -> Option<(f64, f64)> {
+        if self.samples.is_empty() {
+            return None;
+        }
+        let mean = self.samples.iter().sum::<f64>() / self.samples.len() as f64;
+        let max = self.samples.iter().cloned().fold(0., f64::max);
+        Some((mean, max))
+    }
+    /// Completes the latency measurement of events that have been presented and redraws the
+    /// overlay if the trail or latency changed. This should be bound as a tick handler.
+    pub fn tick(&mut self) {
+        let time = now();
+        let (samples, dirty) = (&mut self.samples, &mut self.dirty);
+        self.pending.retain(|&(received, ticks)| {
+            if ticks < 1 {
+                return true;
+            }
+            samples.push_back(time - received);
+            *dirty = true;
+            false
+        });
+        for (_, ticks) in &mut self.pending {
+            *ticks += 1;
+        }
+        while self.samples.len() > SAMPLES {
+            self.samples.pop_front();
+        }
+        if self.dirty {
+            self.dirty = false;
+            self.draw();
+        }
+    }
+    fn draw(&mut self) {
+        let color = LDRColor::rgba(255, 0, 128, 200);
+        let mut positions = self.positions.iter();
+        let trail = match positions.next() {
+            Some(first) => positions
+                .fold(Builder::new().move_to(*first), |builder, position| {
+                    builder.line_to(*position)
+                })
+                .done()
+                .stroke(
+                    StrokeBuilder::new(color.into(), 2.)
+                        .cap_round()
+                        .join_round()
+                        .finalize(),
+                ),
+            None => Builder::new().done(),
+        };
+        self.trail
+            .update(Rasterizable::Path(Box::new(trail.finalize())));
+        let text = match (self.latency(), self.statistics()) {
+            (Some(latency), Some((mean, max))) => format!(
+                "latency {:.1} ms, mean {:.1} ms, max {:.1} ms",
+                latency, mean, max
+            ),
+            _ => "latency -".to_owned(),
+        };
+        let text = Text::new(&text)
+            .with_font(Font::Monospace)
+            .with_size(12.)
+            .with_line_height(16.)
+            .with_color(LDRColor::white());
+        let size = self
+            .frame
+            .measure(Rasterizable::Text(Box::new(text.clone())));
+        self.background.update(Rasterizable::Path(Box::new(
+            Primitive::rectangle(size + 8.)
+                .fill(LDRColor::rgba(0, 0, 0, 160).into())
+                .finalize(),
+        )));
+        self.label.update(Rasterizable::Text(Box::new(text)));
+    }
+}
+
+impl Debug for LatencyOverlay {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LatencyOverlay")
+            .field("latency", &self.latency())
+            .field("statistics", &self.statistics())
+            .finish()
+    }
+}
+
+/// An overlay for stepping back through the recent states of a [Frame], which records a [Scene]
+/// at each simulation step and shows any of them in place of the live contents while a timeline
+/// along the bottom of the viewport is scrubbed.
+///
+/// Snapshots are kept in a ring buffer spanning a limited amount of simulation time, measured by
+/// the steps passed to [TimeTravel::record] rather than by the clock. Positions between two
+/// snapshots are shown with transforms interpolated between them, as an [Interpolator] draws
+/// them, so scrubbing stays smooth at low simulation rates. The recorded state is drawn into a
+/// separate [Frame] and shown as an image, so the live contents and the objects the application
+/// holds are left untouched.
+///
+/// [Interpolator]: crate::graphics::canvas::Interpolator
+pub struct TimeTravel {
+    frame: Box<dyn Frame>,
+    preview: Box<dyn Frame>,
+    layer: Box<dyn Layer>,
+    cover: Box<dyn Object>,
+    track: Box<dyn Object>,
+    recorded: Box<dyn Object>,
+    playhead: Box<dyn Object>,
+    label: Box<dyn Object>,
+    viewport: Rect,
+    span: f64,
+    time: f64,
+    snapshots: VecDeque<(f64, Scene)>,
+    position: Option<f64>,
+    dragging: bool,
+}
+
+impl TimeTravel {
+    /// Adds the overlay to the provided [Frame] as a new [Layer], which should be the topmost,
+    /// using the [Canvas] to create the [Frame] recorded states are drawn into. Snapshots default
+    /// to spanning the last five seconds.
+    pub fn new(canvas: &dyn Canvas, frame: &mut dyn Frame) -> TimeTravel {
+        let mut layer = frame.add_layer();
+        let mut empty = |depth| {
+            layer.add(
+                Content::from(Builder::new().done().finalize())
+                    .with_depth(depth)
+                    .with_tag(TIME_TRAVEL_TAG),
+            )
+        };
+        let (cover, track, recorded, playhead, label) =
+            (empty(0), empty(1), empty(2), empty(3), empty(4));
+        let mut overlay = TimeTravel {
+            preview: canvas.frame(),
+            viewport: Rect::new(Vector2::default(), frame.get_size()),
+            frame: frame.box_clone(),
+            layer,
+            cover,
+            track,
+            recorded,
+            playhead,
+            label,
+            span: 5000.,
+            time: 0.,
+            snapshots: VecDeque::new(),
+            position: None,
+            dragging: false,
+        };
+        overlay.draw();
+        overlay
+    }
+    /// Sets the number of milliseconds of simulation time that snapshots are kept for.
+    pub fn with_span(mut self, span: f64) -> Self {
+        self.span = span;
+        self
+    }
+    /// Sets the viewport the overlay is positioned within, which should be kept the same as that
+    /// of the [Frame], such as by passing on [Camera::viewport] whenever it changes.
+    ///
+    /// [Camera::viewport]: crate::interaction::camera::Camera::viewport
+    pub fn set_viewport(&mut self, viewport: Rect) {
+        self.viewport = viewport;
+        if self.position.is_some() {
+            self.show();
+        }
+        self.draw();
+    }
+    /// Records a snapshot of the [Frame] after a simulation step of the provided number of
+    /// milliseconds, dropping those that have fallen out of the span. This should be called at the
+    /// end of each step, such as from the step handler of an [Interpolator]. Nothing is recorded
+    /// while scrubbing, so that the timeline holds still.
+    ///
+    /// [Interpolator]: crate::graphics::canvas::Interpolator
+    pub fn record(&mut self, elapsed: f64) {
+        if self.position.is_some() {
+            return;
+        }
+        self.time += elapsed;
+        let mut scene = Scene::capture(self.frame.as_ref());
+        for layer in &mut scene.layers {
+            layer.objects.retain(|object| {
+                object.content.tag.as_ref().map(String::as_str) != Some(TIME_TRAVEL_TAG)
+            });
+        }
+        self.snapshots.push_back((self.time, scene));
+        while self
+            .snapshots
+            .front()
+            .map_or(false, |(time, _)| *time < self.time - self.span)
+        {
+            self.snapshots.pop_front();
+        }
+        self.draw();
+    }
+    /// Returns the number of milliseconds of simulation time covered by the recorded snapshots.
+    pub fn recorded(&self) -> f64 {
+        match (self.snapshots.front(), self.snapshots.back()) {
+            (Some((first, _)), Some((last, _))) => last - first,
+            _ => 0.,
+        }
+    }
+    /// Returns how many milliseconds before the latest snapshot the state being shown is, or
+    /// [None] if the live contents are shown.
+    pub fn offset(&self) -> Option<f64> {
+        let last = self.snapshots.back().map_or(self.time, |(time, _)| *time);
+        self.position.map(|position| last - position)
+    }
+    /// Returns whether a recorded state is being shown, during which the application may want to
+    /// pause its simulation.
+    pub fn is_scrubbing(&self) -> bool {
+        self.position.is_some()
+    }
+    /// Shows the recorded state the provided number of milliseconds before the latest snapshot,
+    /// clamped to those recorded, in place of the live contents.
+    pub fn scrub(&mut self, offset: f64) {
+        let (first, last) = match (self.snapshots.front(), self.snapshots.back()) {
+            (Some((first, _)), Some((last, _))) => (*first, *last),
+            _ => return,
+        };
+        self.position = Some((last - offset).max(first).min(last));
+        self.show();
+        self.draw();
+    }
+    /// Returns to showing the live contents and recording.
+    pub fn resume(&mut self) {
+        self.position = None;
+        self.dragging = false;
+        self.cover.update(Rasterizable::Path(Box::new(
+            Builder::new().done().finalize(),
+        )));
+        self.draw();
+    }
+    /// Scrubs in response to pointer input on the timeline, returning whether the event was used.
+    /// Pressing on the timeline begins scrubbing and dragging moves through the recorded states.
+    /// Releasing at the newest end of the timeline resumes the live contents.
+    pub fn handle(&mut self, event: &Event) -> bool {
+        let event = match event {
+            Event::Pointer(event) => event,
+            _ => return false,
+        };
+        let (track, fraction) = self.track_at(event.position);
+        match event.action {
+            pointer::Action::Down if track => {
+                self.dragging = true;
+            }
+            pointer::Action::Move if self.dragging => {}
+            pointer::Action::Up if self.dragging => {
+                self.dragging = false;
+                if fraction >= 1. {
+                    self.resume();
+                }
+                return true;
+            }
+            pointer::Action::Leave | pointer::Action::Cancel if self.dragging => {
+                self.dragging = false;
+                return true;
+            }
+            _ => return false,
+        }
+        self.scrub((1. - fraction) * self.span);
+        true
+    }
+    /// Returns the screen rectangle of the timeline.
+    fn track_rect(&self) -> Rect {
+        let size = self.frame.get_size();
+        Rect::new((8., size.y - 28.), ((size.x - 16.).max(0.), 20.))
+    }
+    /// Returns whether a screen position is over the timeline and how far along it the position
+    /// is, from the oldest end of the span at zero to the newest at one.
+    fn track_at(&self, position: Vector2) -> (bool, f64) {
+        let rect = self.track_rect();
+        let fraction = if rect.size.x > 0. {
+            ((position.x - rect.position.x) / rect.size.x)
+                .max(0.)
+                .min(1.)
+        } else {
+            1.
+        };
+        (rect.contains(position), fraction)
+    }
+    /// Returns a transform that places content given in screen coordinates within the viewport.
+    fn screen(&self, position: Vector2) -> Transform2 {
+        let size = self.frame.get_size();
+        if size.x <= 0. || size.y <= 0. {
+            return Transform2::default().with_position(position);
+        }
+        let scale = self.viewport.size / size;
+        Transform2::default()
+            .with_position(self.viewport.position + position * scale)
+            .with_scale(scale)
+    }
+    /// Draws the recorded state at the scrubbed position into the preview and shows it over the
+    /// live contents.
+    fn show(&mut self) {
+        let position = match self.position {
+            Some(position) => position,
+            None => return,
+        };
+        let after = self
+            .snapshots
+            .iter()
+            .position(|(time, _)| *time >= position)
+            .unwrap_or(self.snapshots.len() - 1);
+        let before = after.saturating_sub(1);
+        let (start, end) = (self.snapshots[before].0, self.snapshots[after].0);
+        let amount = if end > start {
+            (position - start) / (end - start)
+        } else {
+            1.
+        };
+        let size = self.frame.get_size();
+        self.preview.resize(size);
+        self.preview.set_viewport(self.viewport);
+        self.snapshots[before].1.restore(self.preview.as_mut());
+        self.preview.advance();
+        self.snapshots[after].1.restore(self.preview.as_mut());
+        self.preview.set_interpolation(Some(amount));
+        let image = self.preview.to_image_scaled(1.);
+        let image_size = image.get_size();
+        self.cover.update(Rasterizable::from(image));
+        if image_size.x > 0. && image_size.y > 0. {
+            self.cover.set_transform(
+                Transform2::default()
+                    .with_position(self.viewport.position)
+                    .with_scale(self.viewport.size / image_size),
+            );
+        }
+    }
+    fn draw(&mut self) {
+        let rect = self.track_rect();
+        self.track.set_transform(self.screen(rect.position));
+        self.track.update(Rasterizable::Path(Box::new(
+            Primitive::rectangle(rect.size)
+                .fill(LDRColor::rgba(0, 0, 0, 160).into())
+                .finalize(),
+        )));
+        let recorded = (self.recorded() / self.span).min(1.) * rect.size.x;
+        self.recorded.set_transform(
+            self.screen(rect.position + Vector2::from((rect.size.x - recorded, 0.))),
+        );
+        self.recorded.update(Rasterizable::Path(Box::new(
+            Primitive::rectangle((recorded, rect.size.y))
+                .fill(LDRColor::rgba(255, 255, 255, 60).into())
+                .finalize(),
+        )));
+        let offset = self.offset().unwrap_or(0.);
+        let playhead = rect.size.x * (1. - (offset / self.span).min(1.));
+        self.playhead
+            .set_transform(self.screen(rect.position + Vector2::from((playhead - 1., -2.))));
+        self.playhead.update(Rasterizable::Path(Box::new(
+            Primitive::rectangle((2., rect.size.y + 4.))
+                .fill(LDRColor::rgba(255, 0, 128, 255).into())
+                .finalize(),
+        )));
+        let text = match self.offset() {
+            Some(offset) => format!("-{:.0} ms of {:.0} ms", offset, self.recorded()),
+            None => format!("live, {:.0} ms recorded", self.recorded()),
+        };
+        self.label
+            .set_transform(self.screen(rect.position + Vector2::from((6., 2.))));
+        self.label.update(Rasterizable::Text(Box::new(
+            Text::new(&text)
+                .with_font(Font::Monospace)
+                .with_size(12.)
+                .with_line_height(16.)
+                .with_color(LDRColor::white()),
+        )));
+    }
+}
+
+impl Debug for TimeTravel {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TimeTravel")
+            .field("recorded", &self.recorded())
+            .field("offset", &self.offset())
+            .finish()
+    }
+}
@@ -0,0 +1,391 @@
+use crate::graphics::{
+    canvas::{Canvas, Content, Frame, Layer},
+    sprite::SpriteSheet,
+    ImageRepresentation, Rect, Vector2,
+};
+
+use std::fmt::{self, Debug, Formatter};
+
+/// How a property curve moves from a keyframe to the next.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Interpolation {
+    /// The value is held until the next keyframe.
+    Hold,
+    /// The value changes at a constant rate.
+    Linear,
+    /// The value starts changing slowly.
+    EaseIn,
+    /// The value finishes changing slowly.
+    EaseOut,
+    /// The value starts and finishes changing slowly.
+    EaseInOut,
+}
+
+impl Interpolation {
+    /// Maps the linear progress between two keyframes, from zero to one, to eased progress.
+    pub fn apply(self, progress: f64) -> f64 {
+        let t = progress.clamp(0., 1.);
+        match self {
+            Interpolation::Hold => 0.,
+            Interpolation::Linear => t,
+            Interpolation::EaseIn => t * t * t,
+            Interpolation::EaseOut => 1. - (1. - t).powi(3),
+            Interpolation::EaseInOut => t * t * (3. - 2. * t),
+        }
+    }
+}
+
+impl Default for Interpolation {
+    fn default() -> Self {
+        Interpolation::Linear
+    }
+}
+
+/// A value at a point in time, in seconds from the start of a [Timeline].
+#[derive(Clone, Debug)]
+pub struct Keyframe<T> {
+    /// The time of the keyframe.
+    pub time: f64,
+    /// The value at that time.
+    pub value: T,
+    /// How the value moves towards the next keyframe.
+    pub interpolation: Interpolation,
+}
+
+impl<T> Keyframe<T> {
+    /// Creates a keyframe that moves linearly towards the next.
+    pub fn new(time: f64, value: T) -> Keyframe<T> {
+        Keyframe {
+            time,
+            value,
+            interpolation: Interpolation::default(),
+        }
+    }
+    /// Sets how the value moves towards the next keyframe.
+    pub fn with_interpolation(mut self, interpolation: Interpolation) -> Self {
+        self.interpolation = interpolation;
+        self
+    }
+}
+
+/// What a property curve drives during playback.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Binding {
+    /// The curve is only read through [Timeline::value].
+    None,
+    /// The curve drives the opacity of the scene track with the provided name.
+    Opacity(String),
+}
+
+/// The keyframes of a [Track].
+#[derive(Clone, Debug)]
+pub enum TrackKind {
+    /// Snapshots of a scene, each shown from its keyframe until the next.
+    Scene(Vec<Keyframe<Vec<Content>>>),
+    /// A curve of a numeric property, and what it drives.
+    Property(Vec<Keyframe<f64>>, Binding),
+}
+
+/// A named sequence of keyframes.
+#[derive(Clone, Debug)]
+pub struct Track {
+    /// The name of the track.
+    pub name: String,
+    /// The keyframes of the track.
+    pub kind: TrackKind,
+}
+
+impl Track {
+    /// Creates an empty scene track.
+    pub fn scene<T: Into<String>>(name: T) -> Track {
+        Track {
+            name: name.into(),
+            kind: TrackKind::Scene(vec![]),
+        }
+    }
+    /// Creates an empty property track.
+    pub fn property<T: Into<String>>(name: T, binding: Binding) -> Track {
+        Track {
+            name: name.into(),
+            kind: TrackKind::Property(vec![], binding),
+        }
+    }
+    /// Adds a snapshot of a scene. Snapshots added to a property track are ignored.
+    pub fn with_scene(mut self, time: f64, scene: Vec<Content>) -> Self {
+        if let TrackKind::Scene(keyframes) = &mut self.kind {
+            insert(keyframes, Keyframe::new(time, scene));
+        }
+        self
+    }
+    /// Adds a keyframe to a property curve. Keyframes added to a scene track are ignored.
+    pub fn with_keyframe(mut self, keyframe: Keyframe<f64>) -> Self {
+        if let TrackKind::Property(keyframes, _) = &mut self.kind {
+            insert(keyframes, keyframe);
+        }
+        self
+    }
+    fn end(&self) -> f64 {
+        match &self.kind {
+            TrackKind::Scene(keyframes) => keyframes.last().map_or(0., |keyframe| keyframe.time),
+            TrackKind::Property(keyframes, _) => {
+                keyframes.last().map_or(0., |keyframe| keyframe.time)
+            }
+        }
+    }
+}
+
+/// Inserts a keyframe after any others at or before its time.
+fn insert<T>(keyframes: &mut Vec<Keyframe<T>>, keyframe: Keyframe<T>) {
+    let index = keyframes
+        .iter()
+        .position(|existing| existing.time > keyframe.time)
+        .unwrap_or_else(|| keyframes.len());
+    keyframes.insert(index, keyframe);
+}
+
+/// Returns the index of the keyframe in effect at the provided time.
+fn active<T>(keyframes: &[Keyframe<T>], time: f64) -> Option<usize> {
+    keyframes
+        .iter()
+        .rposition(|keyframe| keyframe.time <= time)
+        .or_else(|| if keyframes.is_empty() { None } else { Some(0) })
+}
+
+fn sample(keyframes: &[Keyframe<f64>], time: f64) -> Option<f64> {
+    let index = active(keyframes, time)?;
+    let from = &keyframes[index];
+    let to = match keyframes.get(index + 1) {
+        Some(to) if time > from.time => to,
+        _ => return Some(from.value),
+    };
+    let progress = from
+        .interpolation
+        .apply((time - from.time) / (to.time - from.time));
+    Some(from.value + (to.value - from.value) * progress)
+}
+
+/// The tracks of a multi-frame document along with the state of its playback.
+#[derive(Clone, Debug)]
+pub struct Timeline {
+    tracks: Vec<Track>,
+    duration: Option<f64>,
+    time: f64,
+    rate: f64,
+    playing: bool,
+    looping: bool,
+}
+
+impl Timeline {
+    /// Creates an empty, paused timeline.
+    pub fn new() -> Timeline {
+        Timeline {
+            tracks: vec![],
+            duration: None,
+            time: 0.,
+            rate: 1.,
+            playing: false,
+            looping: false,
+        }
+    }
+    /// Adds a track. Tracks are drawn in the order they're added.
+    pub fn with_track(mut self, track: Track) -> Self {
+        self.tracks.push(track);
+        self
+    }
+    /// Sets the duration of the timeline. By default it ends at its last keyframe.
+    pub fn with_duration(mut self, duration: f64) -> Self {
+        self.duration = Some(duration.max(0.));
+        self
+    }
+    /// Returns the tracks.
+    pub fn tracks(&self) -> &[Track] {
+        &self.tracks
+    }
+    /// Returns the duration in seconds.
+    pub fn duration(&self) -> f64 {
+        self.duration
+            .unwrap_or_else(|| self.tracks.iter().map(Track::end).fold(0., f64::max))
+    }
+    /// Returns the playhead position in seconds.
+    pub fn get_time(&self) -> f64 {
+        self.time
+    }
+    /// Moves the playhead, limited to the duration.
+    pub fn seek(&mut self, time: f64) {
+        self.time = time.clamp(0., self.duration());
+    }
+    /// Starts playback. Playback from the end of a timeline that doesn't loop restarts it.
+    pub fn play(&mut self) {
+        if !self.looping && self.time >= self.duration() && self.rate > 0. {
+            self.time = 0.;
+        }
+        self.playing = true;
+    }
+    /// Pauses playback.
+    pub fn pause(&mut self) {
+        self.playing = false;
+    }
+    /// Returns whether the timeline is playing.
+    pub fn is_playing(&self) -> bool {
+        self.playing
+    }
+    /// Sets the playback rate. Negative rates play in reverse.
+    pub fn set_rate(&mut self, rate: f64) {
+        self.rate = rate;
+    }
+    /// Sets whether playback wraps around at either end instead of pausing.
+    pub fn set_looping(&mut self, looping: bool) {
+        self.looping = looping;
+    }
+    /// Advances the playhead by the provided number of seconds of wall time if playing.
+    pub fn advance(&mut self, elapsed: f64) {
+        if !self.playing {
+            return;
+        }
+        let duration = self.duration();
+        let time = self.time + elapsed * self.rate;
+        if self.looping && duration > 0. {
+            self.time = time.rem_euclid(duration);
+        } else {
+            self.time = time.clamp(0., duration);
+            if time <= 0. || time >= duration {
+                self.playing = false;
+            }
+        }
+    }
+    /// Returns the value of the named property track at the playhead.
+    pub fn value(&self, name: &str) -> Option<f64> {
+        self.tracks.iter().find_map(|track| match &track.kind {
+            TrackKind::Property(keyframes, _) if track.name == name => sample(keyframes, self.time),
+            _ => None,
+        })
+    }
+    /// Returns the scene of the named scene track at the playhead.
+    pub fn scene(&self, name: &str) -> Option<&[Content]> {
+        self.tracks.iter().find_map(|track| match &track.kind {
+            TrackKind::Scene(keyframes) if track.name == name => {
+                active(keyframes, self.time).map(|index| keyframes[index].value.as_slice())
+            }
+            _ => None,
+        })
+    }
+    /// Adds the scenes of the timeline to [Layer]s of the provided [Frame], returning a [Playback]
+    /// that shows the scenes and applies the property curves in effect at the playhead.
+    pub fn attach(&self, frame: &mut dyn Frame) -> Playback {
+        let scenes = self
+            .tracks
+            .iter()
+            .filter_map(|track| match &track.kind {
+                TrackKind::Scene(keyframes) => Some((
+                    track.name.clone(),
+                    keyframes
+                        .iter()
+                        .map(|keyframe| {
+                            let mut layer = frame.add_layer();
+                            for content in &keyframe.value {
+                                layer.add(content.clone());
+                            }
+                            layer
+                        })
+                        .collect(),
+                )),
+                _ => None,
+            })
+            .collect();
+        let mut playback = Playback { scenes };
+        playback.update(self);
+        playback
+    }
+    /// Renders the timeline at the provided number of frames per second into a new [Frame] of the
+    /// provided size, returning an image of each frame from the start of the timeline to its end.
+    /// The frame is never shown and the playhead of this timeline isn't moved.
+    pub fn render_frames<T>(
+        &self,
+        canvas: &dyn Canvas,
+        fps: f64,
+        size: T,
+    ) -> impl Iterator<Item = Box<dyn ImageRepresentation>>
+    where
+        T: Into<Vector2>,
+    {
+        let size = size.into();
+        let mut frame = canvas.frame();
+        frame.resize(size);
+        frame.set_viewport(Rect::new(Vector2::default(), size));
+        let mut timeline = self.clone();
+        let mut playback = timeline.attach(frame.as_mut());
+        let count = if fps > 0. {
+            (self.duration() * fps).floor() as usize + 1
+        } else {
+            0
+        };
+        (0..count).map(move |index| {
+            timeline.seek(index as f64 / fps);
+            playback.update(&timeline);
+            frame.to_image()
+        })
+    }
+    /// Renders the timeline as [Timeline::render_frames] does and packs the frames into a
+    /// [SpriteSheet].
+    pub fn render_sprite_sheet<T>(&self, canvas: &dyn Canvas, fps: f64, size: T) -> SpriteSheet
+    where
+        T: Into<Vector2>,
+    {
+        let frames: Vec<_> = self
+            .render_frames(canvas, fps, size)
+            .map(|image| image.as_texture())
+            .collect();
+        SpriteSheet::pack(&frames)
+    }
+}
+
+impl Default for Timeline {
+    fn default() -> Self {
+        Timeline::new()
+    }
+}
+
+/// The scenes of a [Timeline] added to a [Frame].
+pub struct Playback {
+    scenes: Vec<(String, Vec<Box<dyn Layer>>)>,
+}
+
+impl Playback {
+    /// Shows the scenes and applies the bound property curves in effect at the playhead of the
+    /// timeline. The timeline should be the one the [Playback] was attached from.
+    pub fn update(&mut self, timeline: &Timeline) {
+        for (name, layers) in &mut self.scenes {
+            let name = &*name;
+            let keyframes = timeline.tracks.iter().find_map(|track| match &track.kind {
+                TrackKind::Scene(keyframes) if track.name == *name => Some(keyframes),
+                _ => None,
+            });
+            let current = keyframes.and_then(|keyframes| active(keyframes, timeline.time));
+            let opacity = timeline
+                .tracks
+                .iter()
+                .find_map(|track| match &track.kind {
+                    TrackKind::Property(keyframes, Binding::Opacity(target)) if target == name => {
+                        sample(keyframes, timeline.time)
+                    }
+                    _ => None,
+                })
+                .unwrap_or(1.);
+            for (index, layer) in layers.iter_mut().enumerate() {
+                layer.set_visible(Some(index) == current);
+                layer.set_opacity(opacity);
+            }
+        }
+    }
+}
+
+impl Debug for Playback {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Playback")
+            .field(
+                "scenes",
+                &self.scenes.iter().map(|(name, _)| name).collect::<Vec<_>>(),
+            )
+            .finish()
+    }
+}
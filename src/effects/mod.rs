@@ -0,0 +1,2 @@
+/// Provides recycling of the objects used by short-lived effects.
+pub mod pool;
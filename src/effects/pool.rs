@@ -0,0 +1,145 @@
+use crate::graphics::{
+    canvas::{Blend, Content, CursorIcon, Frame, Layer, Object, Rasterizable},
+    path::Builder,
+};
+
+use std::fmt::{self, Debug, Formatter};
+
+/// A function that animates an effect, called on every tick with its [Object] and its progress
+/// between zero and one.
+pub type Animation = Box<dyn FnMut(&mut dyn Object, f64) + Send + Sync>;
+
+struct Effect {
+    object: Box<dyn Object>,
+    elapsed: f64,
+    duration: f64,
+    animation: Option<Animation>,
+}
+
+fn empty() -> Rasterizable {
+    Rasterizable::Path(Box::new(Builder::new().done().finalize()))
+}
+
+/// A pool of objects for short-lived effects such as ripples, toasts and particles.
+///
+/// Objects are added to the pool's [Layer] as they are first needed and, once their effect ends,
+/// are emptied and kept for the next effect rather than being left behind. A burst of effects
+/// therefore grows the layer only as far as the most that were ever alive at once.
+pub struct Pool {
+    layer: Box<dyn Layer>,
+    idle: Vec<Box<dyn Object>>,
+    effects: Vec<Effect>,
+    limit: usize,
+}
+
+impl Pool {
+    /// Adds the pool to the provided [Frame] as a new [Layer]. At most 64 effects are alive at once
+    /// by default.
+    pub fn new(frame: &mut dyn Frame) -> Pool {
+        Pool {
+            layer: frame.add_layer(),
+            idle: vec![],
+            effects: vec![],
+            limit: 64,
+        }
+    }
+    /// Sets the most effects that are alive at once. Spawning an effect beyond the limit ends the
+    /// oldest early.
+    pub fn with_limit(mut self, limit: usize) -> Self {
+        self.limit = limit.max(1);
+        self
+    }
+    /// Returns the number of effects currently alive.
+    pub fn active(&self) -> usize {
+        self.effects.len()
+    }
+    /// Returns the number of objects waiting to be reused.
+    pub fn idle(&self) -> usize {
+        self.idle.len()
+    }
+    /// Shows content for the provided duration, in the units of the deltas passed to
+    /// [Pool::tick].
+    pub fn spawn(&mut self, content: Content, duration: f64) {
+        self.spawn_effect(content, duration, None);
+    }
+    /// Shows content for the provided duration, calling the animation on every tick.
+    pub fn spawn_animated(&mut self, content: Content, duration: f64, animation: Animation) {
+        self.spawn_effect(content, duration, Some(animation));
+    }
+    fn spawn_effect(&mut self, content: Content, duration: f64, animation: Option<Animation>) {
+        while self.effects.len() >= self.limit {
+            let effect = self.effects.remove(0);
+            self.recycle(effect.object);
+        }
+        let object = match self.idle.pop() {
+            Some(mut object) => {
+                object.set_transform(content.transform);
+                object.set_depth(content.depth);
+                object.set_clip(content.clip);
+                object.set_tag(content.tag);
+                object.update(content.content);
+                object
+            }
+            None => self.layer.add(content),
+        };
+        let mut effect = Effect {
+            object,
+            elapsed: 0.,
+            duration,
+            animation,
+        };
+        if let Some(animation) = &mut effect.animation {
+            animation(&mut *effect.object, 0.);
+        }
+        self.effects.push(effect);
+    }
+    /// Empties the object of an ended effect and returns it to the state of one newly added, so
+    /// that whatever its animation changed doesn't carry over to the next effect.
+    fn recycle(&mut self, mut object: Box<dyn Object>) {
+        object.update(empty());
+        object.set_clip(None);
+        object.set_tag(None);
+        object.set_blend(Blend::default());
+        object.set_cursor(CursorIcon::default());
+        object.set_visible(true);
+        self.idle.push(object);
+    }
+    /// Advances every effect by the provided delta, ending those whose duration has passed.
+    pub fn tick(&mut self, delta: f64) {
+        let mut ended = vec![];
+        for (index, effect) in self.effects.iter_mut().enumerate() {
+            effect.elapsed += delta;
+            let progress = if effect.duration > 0. {
+                (effect.elapsed / effect.duration).min(1.)
+            } else {
+                1.
+            };
+            if let Some(animation) = &mut effect.animation {
+                animation(&mut *effect.object, progress);
+            }
+            if progress >= 1. {
+                ended.push(index);
+            }
+        }
+        for index in ended.into_iter().rev() {
+            let effect = self.effects.remove(index);
+            self.recycle(effect.object);
+        }
+    }
+    /// Ends every effect immediately.
+    pub fn clear(&mut self) {
+        for effect in self.effects.split_off(0) {
+            self.recycle(effect.object);
+        }
+    }
+}
+
+impl Debug for Pool {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Pool")
+            .field("active", &self.effects.len())
+            .field("idle", &self.idle.len())
+            .field("limit", &self.limit)
+            .finish()
+    }
+}
@@ -15,6 +15,37 @@ impl Error {
     pub(crate) fn color_stop() -> Error {
         Error::from(ErrorKind::LDRColorStopOffsetError)
     }
+
+    pub(crate) fn color_parse() -> Error {
+        Error::from(ErrorKind::ColorParseError)
+    }
+
+    pub(crate) fn stop_order() -> Error {
+        Error::from(ErrorKind::GradientStopOrderError)
+    }
+
+    pub(crate) fn network() -> Error {
+        Error::from(ErrorKind::NetworkError)
+    }
+
+    pub(crate) fn storage() -> Error {
+        Error::from(ErrorKind::StorageError)
+    }
+
+    #[cfg(feature = "image")]
+    pub(crate) fn image_size() -> Error {
+        Error::from(ErrorKind::ImageSizeError)
+    }
+
+    #[cfg(feature = "rhai")]
+    pub(crate) fn script() -> Error {
+        Error::from(ErrorKind::ScriptError)
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "windows"))]
+    pub(crate) fn graphics_context() -> Error {
+        Error::from(ErrorKind::GraphicsContextError)
+    }
 }
 
 impl Fail for Error {
@@ -36,6 +67,13 @@ impl fmt::Display for Error {
 #[derive(Clone, Eq, PartialEq, Debug)]
 pub enum ErrorKind {
     LDRColorStopOffsetError,
+    ColorParseError,
+    GradientStopOrderError,
+    NetworkError,
+    StorageError,
+    ImageSizeError,
+    ScriptError,
+    GraphicsContextError,
 
     #[doc(hidden)]
     __Nonexhaustive,
@@ -47,6 +85,15 @@ impl fmt::Display for ErrorKind {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match *self {
             ErrorKind::LDRColorStopOffsetError => write!(f, "LDRColorstop offset out of bounds"),
+            ErrorKind::ColorParseError => write!(f, "Invalid color string"),
+            ErrorKind::GradientStopOrderError => write!(f, "Gradient stops out of order"),
+            ErrorKind::NetworkError => write!(f, "Network request failed"),
+            ErrorKind::StorageError => write!(f, "Persistent storage unavailable"),
+            ErrorKind::ImageSizeError => write!(f, "Image size doesn't match its pixel data"),
+            ErrorKind::ScriptError => write!(f, "Script failed to parse or run"),
+            ErrorKind::GraphicsContextError => {
+                write!(f, "Graphics context couldn't be created or drawn to")
+            }
             ErrorKind::__Nonexhaustive => panic!("Invalid Error!"),
         }
     }
@@ -0,0 +1,401 @@
+use crate::graphics::{
+    tiles::TiledImage, Image, ImageRepresentation, LDRColor, Texture2, Transform2, Vector2,
+};
+
+use std::fmt::{self, Debug, Formatter};
+
+/// The smallest distance between stamps, which keeps tiny brushes from producing unbounded
+/// numbers of them.
+const MINIMUM_SPACING: f64 = 0.5;
+
+/// A mapping from pointer pressure to a multiplier of a brush property, as a piecewise linear
+/// curve through points whose coordinates are between zero and one.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PressureCurve {
+    points: Vec<Vector2>,
+}
+
+impl PressureCurve {
+    /// Creates a curve through the provided points, given as pressure and multiplier pairs.
+    /// Pressures outside of the points are mapped to the multiplier of the nearest point.
+    pub fn new<T>(points: Vec<T>) -> PressureCurve
+    where
+        T: Into<Vector2>,
+    {
+        let mut points: Vec<Vector2> = points
+            .into_iter()
+            .map(|point| {
+                let point = point.into();
+                (point.x.clamp(0., 1.), point.y.clamp(0., 1.)).into()
+            })
+            .collect();
+        points.sort_by(|a, b| a.x.total_cmp(&b.x));
+        PressureCurve { points }
+    }
+    /// Creates a curve that ignores pressure. This is the default.
+    pub fn constant() -> PressureCurve {
+        PressureCurve::new(vec![(0., 1.)])
+    }
+    /// Creates a curve proportional to pressure.
+    pub fn linear() -> PressureCurve {
+        PressureCurve::new(vec![(0., 0.), (1., 1.)])
+    }
+    /// Returns the multiplier for the provided pressure.
+    pub fn apply(&self, pressure: f64) -> f64 {
+        let after = match self.points.iter().position(|point| point.x >= pressure) {
+            Some(0) => return self.points[0].y,
+            Some(index) => index,
+            None => return self.points.last().map(|point| point.y).unwrap_or(1.),
+        };
+        let (start, end) = (self.points[after - 1], self.points[after]);
+        start.y + (end.y - start.y) * (pressure - start.x) / (end.x - start.x)
+    }
+}
+
+impl Default for PressureCurve {
+    fn default() -> Self {
+        PressureCurve::constant()
+    }
+}
+
+/// A raster brush that paints by placing stamps at regular intervals along the path of a pointer.
+///
+/// Stamps are rasterized into a [Layer](crate::graphics::canvas::Layer) with
+/// [Layer::paint](crate::graphics::canvas::Layer::paint).
+#[derive(Clone)]
+pub struct Brush {
+    /// The color of the brush.
+    pub color: LDRColor,
+    /// The diameter of each stamp.
+    pub size: f64,
+    /// The opacity of each stamp, between zero and one.
+    pub opacity: f64,
+    /// The distance between stamps as a fraction of their size.
+    pub spacing: f64,
+    /// The greatest random variation of stamp size, as a fraction of it.
+    pub size_jitter: f64,
+    /// The greatest random reduction of stamp opacity, as a fraction of it.
+    pub opacity_jitter: f64,
+    /// How pressure affects stamp size.
+    pub size_pressure: PressureCurve,
+    /// How pressure affects stamp opacity.
+    pub opacity_pressure: PressureCurve,
+    /// An image whose alpha channel shapes each stamp. Round stamps are used if this is [None].
+    pub texture: Option<Box<dyn ImageRepresentation>>,
+}
+
+impl Brush {
+    /// Creates an opaque round brush with stamps spaced at a quarter of their size.
+    pub fn new(color: LDRColor, size: f64) -> Brush {
+        Brush {
+            color,
+            size,
+            opacity: 1.,
+            spacing: 0.25,
+            size_jitter: 0.,
+            opacity_jitter: 0.,
+            size_pressure: PressureCurve::default(),
+            opacity_pressure: PressureCurve::default(),
+            texture: None,
+        }
+    }
+    /// Sets the opacity.
+    pub fn with_opacity(mut self, opacity: f64) -> Self {
+        self.opacity = opacity.clamp(0., 1.);
+        self
+    }
+    /// Sets the spacing.
+    pub fn with_spacing(mut self, spacing: f64) -> Self {
+        self.spacing = spacing;
+        self
+    }
+    /// Sets the size and opacity jitter.
+    pub fn with_jitter(mut self, size: f64, opacity: f64) -> Self {
+        self.size_jitter = size.clamp(0., 1.);
+        self.opacity_jitter = opacity.clamp(0., 1.);
+        self
+    }
+    /// Sets how pressure affects stamp size.
+    pub fn with_size_pressure(mut self, curve: PressureCurve) -> Self {
+        self.size_pressure = curve;
+        self
+    }
+    /// Sets how pressure affects stamp opacity.
+    pub fn with_opacity_pressure(mut self, curve: PressureCurve) -> Self {
+        self.opacity_pressure = curve;
+        self
+    }
+    /// Sets the texture.
+    pub fn with_texture(mut self, texture: Box<dyn ImageRepresentation>) -> Self {
+        self.texture = Some(texture);
+        self
+    }
+    /// Begins a new stroke of the brush.
+    pub fn stroke(&self) -> BrushStroke {
+        BrushStroke {
+            brush: self.clone(),
+            last: None,
+            until_next: 0.,
+            seed: 1,
+        }
+    }
+    fn spacing_at(&self, pressure: f64) -> f64 {
+        (self.spacing * self.size * self.size_pressure.apply(pressure)).max(MINIMUM_SPACING)
+    }
+}
+
+impl Debug for Brush {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Brush")
+            .field("color", &self.color)
+            .field("size", &self.size)
+            .field("opacity", &self.opacity)
+            .field("spacing", &self.spacing)
+            .field("size_jitter", &self.size_jitter)
+            .field("opacity_jitter", &self.opacity_jitter)
+            .field("size_pressure", &self.size_pressure)
+            .field("opacity_pressure", &self.opacity_pressure)
+            .field(
+                "texture",
+                &self.texture.as_ref().map(|texture| texture.get_size()),
+            )
+            .finish()
+    }
+}
+
+/// A single placement of a [Brush].
+#[derive(Clone)]
+pub struct Stamp {
+    /// The center of the stamp.
+    pub position: Vector2,
+    /// The diameter of the stamp.
+    pub size: f64,
+    /// The color of the stamp, with its opacity applied to the alpha channel.
+    pub color: LDRColor,
+    /// The image shaping the stamp, if any.
+    pub texture: Option<Box<dyn ImageRepresentation>>,
+}
+
+impl Debug for Stamp {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Stamp")
+            .field("position", &self.position)
+            .field("size", &self.size)
+            .field("color", &self.color)
+            .finish()
+    }
+}
+
+impl Stamp {
+    /// Rasterizes the stamp at the provided number of pixels per unit, returning its pixels along
+    /// with the pixel at which their top left corner is placed, or [None] if it covers no pixels.
+    fn rasterize(&self, pixel_ratio: f64) -> Option<(Image<LDRColor, Texture2>, i64, i64)> {
+        let radius = self.size * pixel_ratio / 2.;
+        let center = self.position * pixel_ratio;
+        if radius <= 0. || !radius.is_finite() || !center.x.is_finite() || !center.y.is_finite() {
+            return None;
+        }
+        let (left, top) = ((center.x - radius).floor(), (center.y - radius).floor());
+        let width = ((center.x + radius).ceil() - left) as u32;
+        let height = ((center.y + radius).ceil() - top) as u32;
+        let texture = self.texture.as_ref().map(|texture| texture.as_texture());
+        let mut pixels = Vec::with_capacity((width * height) as usize);
+        for y in 0..height {
+            for x in 0..width {
+                let point = Vector2::from((left + f64::from(x) + 0.5, top + f64::from(y) + 0.5));
+                let coverage = match &texture {
+                    Some(texture) => {
+                        // Textures are stretched over the square the stamp is inscribed in.
+                        let (texture_width, texture_height) =
+                            (texture.format.width, texture.format.height);
+                        let u = (point.x - center.x + radius) / (radius * 2.);
+                        let v = (point.y - center.y + radius) / (radius * 2.);
+                        if u < 0. || v < 0. || u >= 1. || v >= 1. {
+                            0.
+                        } else {
+                            let (u, v) = (
+                                (u * f64::from(texture_width)) as u32,
+                                (v * f64::from(texture_height)) as u32,
+                            );
+                            let index = (v * texture_width + u) as usize;
+                            texture
+                                .pixels
+                                .get(index)
+                                .map_or(0., |pixel| f64::from(pixel.a) / 255.)
+                        }
+                    }
+                    None => {
+                        let offset = point - center;
+                        (radius - offset.x.hypot(offset.y) + 0.5).clamp(0., 1.)
+                    }
+                };
+                let alpha = (f64::from(self.color.a) * coverage).round() as u8;
+                pixels.push(self.color.with_alpha(alpha));
+            }
+        }
+        Some((
+            Image {
+                pixels,
+                format: Texture2 { width, height },
+            },
+            left as i64,
+            top as i64,
+        ))
+    }
+}
+
+/// Rasterizes stamps over each other at the provided number of pixels per unit, returning their
+/// pixels as sparse tiles along with the transformation from those pixels into the space of the
+/// stamps, or [None] if they cover no pixels.
+pub(crate) fn rasterize(stamps: &[Stamp], pixel_ratio: f64) -> Option<(TiledImage, Transform2)> {
+    let rasterized: Vec<_> = stamps
+        .iter()
+        .filter_map(|stamp| stamp.rasterize(pixel_ratio))
+        .collect();
+    let left = rasterized.iter().map(|(_, x, _)| *x).min()?;
+    let top = rasterized.iter().map(|(_, _, y)| *y).min()?;
+    let right = rasterized
+        .iter()
+        .map(|(image, x, _)| x + i64::from(image.format.width))
+        .max()?;
+    let bottom = rasterized
+        .iter()
+        .map(|(image, _, y)| y + i64::from(image.format.height))
+        .max()?;
+    let mut painted = TiledImage::new((right - left) as u32, (bottom - top) as u32);
+    for (image, x, y) in &rasterized {
+        painted.draw(image, x - left, y - top);
+    }
+    let origin = Vector2::from((left as f64, top as f64)) / pixel_ratio;
+    Some((
+        painted,
+        Transform2::from(origin).with_scale(Vector2::from(1. / pixel_ratio)),
+    ))
+}
+
+/// A stroke of a [Brush] in progress, which places stamps as the pointer moves.
+#[derive(Clone, Debug)]
+pub struct BrushStroke {
+    brush: Brush,
+    last: Option<(Vector2, f64)>,
+    until_next: f64,
+    seed: u32,
+}
+
+impl BrushStroke {
+    /// Sets the seed of the jitter. Strokes with the same seed and input place identical stamps.
+    pub fn with_seed(mut self, seed: u32) -> Self {
+        self.seed = seed;
+        self
+    }
+    /// Moves the stroke to the provided point at the provided pressure, returning the stamps
+    /// placed along the way. The first point of a stroke always places a stamp.
+    pub fn to<T>(&mut self, point: T, pressure: f64) -> Vec<Stamp>
+    where
+        T: Into<Vector2>,
+    {
+        let (point, pressure) = (point.into(), pressure.clamp(0., 1.));
+        let (from, from_pressure) = match self.last.replace((point, pressure)) {
+            Some(last) => last,
+            None => {
+                self.until_next = self.brush.spacing_at(pressure);
+                return vec![self.stamp(point, pressure)];
+            }
+        };
+        let offset = point - from;
+        let distance = offset.x.hypot(offset.y);
+        let mut stamps = vec![];
+        let mut travelled = self.until_next;
+        while travelled <= distance {
+            let amount = travelled / distance;
+            let pressure = from_pressure + (pressure - from_pressure) * amount;
+            stamps.push(self.stamp(from + offset * amount, pressure));
+            travelled += self.brush.spacing_at(pressure);
+        }
+        self.until_next = travelled - distance;
+        stamps
+    }
+    fn random(&mut self) -> f64 {
+        self.seed = self.seed.wrapping_mul(1_103_515_245).wrapping_add(12345);
+        f64::from(self.seed >> 8) / f64::from(1u32 << 24)
+    }
+    fn stamp(&mut self, position: Vector2, pressure: f64) -> Stamp {
+        let size_jitter = 1. + self.brush.size_jitter * (self.random() * 2. - 1.);
+        let opacity_jitter = 1. - self.brush.opacity_jitter * self.random();
+        let brush = &self.brush;
+        let opacity = brush.opacity * brush.opacity_pressure.apply(pressure) * opacity_jitter;
+        Stamp {
+            position,
+            size: brush.size * brush.size_pressure.apply(pressure) * size_jitter,
+            color: brush
+                .color
+                .with_alpha((f64::from(brush.color.a) * opacity).round() as u8),
+            texture: brush.texture.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pressure_curves() {
+        assert_eq!(PressureCurve::constant().apply(0.2), 1.);
+        assert_eq!(PressureCurve::linear().apply(0.25), 0.25);
+        let curve = PressureCurve::new(vec![(0.8, 1.), (0.2, 0.5)]);
+        assert_eq!(curve.apply(0.), 0.5);
+        assert_eq!(curve.apply(0.5), 0.75);
+        assert_eq!(curve.apply(1.), 1.);
+        let curve = PressureCurve::new(vec![(f64::NAN, 0.5), (0.5, 1.)]);
+        assert_eq!(curve.apply(0.25), 1.);
+    }
+
+    #[test]
+    fn stamps_are_rasterized_together() {
+        let mut stroke = Brush::new(LDRColor::black(), 4.).with_spacing(1.).stroke();
+        let mut stamps = stroke.to((2., 2.), 1.);
+        stamps.extend(stroke.to((10., 2.), 1.));
+        assert_eq!(stamps.len(), 3);
+        let (painted, transform) = rasterize(&stamps, 2.).unwrap();
+        assert_eq!(painted.get_size(), Vector2::from((24., 8.)));
+        assert_eq!(transform.position, Vector2::from((0., 0.)));
+        assert_eq!(transform.scale, Vector2::from(0.5));
+        assert_eq!(painted.pixel(4, 4).a, 255);
+        assert_eq!(painted.pixel(0, 0).a, 0);
+        assert!(rasterize(&[], 1.).is_none());
+    }
+
+    #[test]
+    fn stamps_are_evenly_spaced() {
+        let mut stroke = Brush::new(LDRColor::black(), 8.).with_spacing(0.5).stroke();
+        assert_eq!(stroke.to((0., 0.), 1.).len(), 1);
+        let stamps = stroke.to((10., 0.), 1.);
+        let positions: Vec<f64> = stamps.iter().map(|stamp| stamp.position.x).collect();
+        assert_eq!(positions, vec![4., 8.]);
+        let stamps = stroke.to((14., 0.), 1.);
+        assert_eq!(stamps[0].position.x, 12.);
+        assert!(stamps.iter().all(|stamp| stamp.size == 8.));
+    }
+
+    #[test]
+    fn pressure_and_jitter_vary_stamps() {
+        let brush = Brush::new(LDRColor::black(), 10.)
+            .with_size_pressure(PressureCurve::linear())
+            .with_opacity_pressure(PressureCurve::linear());
+        let stamp = &brush.stroke().to((0., 0.), 0.5)[0];
+        assert_eq!(stamp.size, 5.);
+        assert_eq!(stamp.color.a, 128);
+        let brush = Brush::new(LDRColor::black(), 10.).with_jitter(0.5, 0.5);
+        let mut stroke = brush.stroke();
+        stroke.to((0., 0.), 1.);
+        let stamps = stroke.to((100., 0.), 1.);
+        assert!(stamps
+            .iter()
+            .all(|stamp| stamp.size >= 5. && stamp.size <= 15. && stamp.color.a >= 127));
+        assert!(stamps.iter().any(|stamp| stamp.size != stamps[0].size));
+        let mut repeated = brush.stroke();
+        repeated.to((0., 0.), 1.);
+        assert_eq!(repeated.to((100., 0.), 1.)[3].position, stamps[3].position);
+    }
+}
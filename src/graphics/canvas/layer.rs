@@ -0,0 +1,575 @@
+use crate::graphics::{
+    brush::{self, Stamp},
+    canvas::{Blend, Content, CursorIcon, Extension, Frame, Layer, Object, Rasterizable},
+    fill,
+    path::Path,
+    selection::{Selection, SelectionArea},
+    tiles::TiledImage,
+    Image, LDRColor, Rect, Texture2, Transform2, Vector2,
+};
+
+use std::{
+    any::Any,
+    fmt::{self, Debug, Formatter},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, RwLock,
+    },
+};
+
+struct State {
+    frame: Box<dyn Frame>,
+    opacity: f64,
+    blend: Blend,
+    visible: bool,
+    locked: bool,
+    fixed: bool,
+    interpolation: Option<f64>,
+    rendered: Option<(Vector2, Rect, f64)>,
+    selection: Option<Selection>,
+    target: Option<Box<dyn Layer>>,
+    /// The selection that confines each nested layer of the frame, in order.
+    groups: Vec<Option<Selection>>,
+    /// The objects added by flood fills, oldest first, each an undo step.
+    fills: Vec<Box<dyn Object>>,
+}
+
+impl State {
+    fn is_interpolating(&self) -> bool {
+        !self.fixed && self.interpolation.is_some()
+    }
+}
+
+/// A [Layer] that rasterizes its objects into an offscreen [Frame] of the target backend, which
+/// the parent [Frame] composites. The offscreen frame is only drawn again once the layer is dirty.
+#[derive(Clone)]
+pub(crate) struct FrameLayer {
+    state: Arc<RwLock<State>>,
+    dirty: Arc<AtomicBool>,
+}
+
+impl FrameLayer {
+    /// Creates a layer rasterized into the provided frame, which should clear to transparency.
+    pub(crate) fn new(frame: Box<dyn Frame>) -> FrameLayer {
+        FrameLayer {
+            state: Arc::new(RwLock::new(State {
+                frame,
+                opacity: 1.,
+                blend: Blend::Normal,
+                visible: true,
+                locked: false,
+                fixed: false,
+                interpolation: None,
+                rendered: None,
+                selection: None,
+                target: None,
+                groups: vec![],
+                fills: vec![],
+            })),
+            dirty: Arc::new(AtomicBool::new(true)),
+        }
+    }
+    /// Brings the rasterization of the layer up to date for a parent frame of the given size,
+    /// viewport and pixel ratio, returning the offscreen frame along with the opacity and blend it
+    /// is composited with, or [None] if the layer is hidden.
+    pub(crate) fn render(
+        &self,
+        size: Vector2,
+        viewport: Rect,
+        pixel_ratio: f64,
+    ) -> Option<(Box<dyn Frame>, f64, Blend)> {
+        let mut state = self.state.write().unwrap();
+        if !state.visible {
+            // Hidden layers are drawn again when shown, which marks them dirty.
+            self.dirty.store(false, Ordering::Relaxed);
+            return None;
+        }
+        let unchanged = match state.rendered {
+            Some((rendered_size, rendered_viewport, rendered_ratio)) => {
+                rendered_size == size
+                    && rendered_viewport.position == viewport.position
+                    && rendered_viewport.size == viewport.size
+                    && rendered_ratio == pixel_ratio
+            }
+            None => false,
+        };
+        if !unchanged {
+            state.frame.set_pixel_ratio(pixel_ratio);
+            state.frame.resize(size);
+            state.frame.set_viewport(viewport);
+            state.rendered = Some((size, viewport, pixel_ratio));
+        }
+        if self.dirty.swap(false, Ordering::Relaxed) || !unchanged || state.is_interpolating() {
+            state.frame.draw();
+        }
+        Some((state.frame.clone(), state.opacity, state.blend))
+    }
+    /// Returns whether the layer needs to be composited again, because it has changed since it was
+    /// last rendered or is drawn at interpolated transforms.
+    pub(crate) fn is_dirty(&self) -> bool {
+        self.dirty.load(Ordering::Relaxed) || self.state.read().unwrap().is_interpolating()
+    }
+    pub(crate) fn advance(&self) {
+        let state = self.state.read().unwrap();
+        // Static layers are drawn at their current transforms, so they stay cached as time passes.
+        if state.fixed {
+            return;
+        }
+        state.frame.advance();
+        self.dirty.store(true, Ordering::Relaxed);
+    }
+    /// Returns the rasterization of the layer along with the transformation from its pixels into
+    /// the space of its content, or [None] if the layer hasn't yet been drawn.
+    fn rasterization(&self) -> Option<(Image<LDRColor, Texture2>, Transform2)> {
+        let state = self.state.read().unwrap();
+        let (_, viewport, _) = state.rendered?;
+        // The layer stays dirty so that its parent frame still composites it again.
+        if self.dirty.load(Ordering::Relaxed) {
+            state.frame.draw();
+        }
+        let image = state.frame.to_image().as_texture();
+        let size = Vector2::from((
+            f64::from(image.format.width),
+            f64::from(image.format.height),
+        ));
+        let transform = Transform2::from(viewport.position).with_scale(viewport.size / size);
+        Some((image, transform))
+    }
+    /// Creates a selection of an area of the rasterization of the layer, placed in the space of its
+    /// content.
+    fn selection(&self, area: SelectionArea) -> Option<Selection> {
+        let (image, transform) = match area {
+            // Masks are placed where they were made, so they need no rasterization.
+            SelectionArea::Mask(selection) => return Some(selection),
+            _ => self.rasterization()?,
+        };
+        let (width, height) = (image.format.width, image.format.height);
+        let to_pixels = |point: Vector2| (point - transform.position) / transform.scale;
+        let selection = match area {
+            SelectionArea::Rectangle(rect) => Selection::rectangle(
+                width,
+                height,
+                Rect::new(to_pixels(rect.position), rect.size / transform.scale),
+            ),
+            SelectionArea::Lasso(path) => {
+                let polygons: Vec<Vec<Vector2>> = path
+                    .flatten(0.25 * transform.scale.x.min(transform.scale.y))
+                    .into_iter()
+                    .map(|(points, _)| points.into_iter().map(to_pixels).collect())
+                    .collect();
+                Selection::lasso(width, height, &polygons)
+            }
+            SelectionArea::Color(point, tolerance) => {
+                let pixel = to_pixels(point);
+                if pixel.x < 0. || pixel.y < 0. {
+                    return None;
+                }
+                let region = fill::flood_fill(
+                    &image,
+                    pixel.x as u32,
+                    pixel.y as u32,
+                    LDRColor::black(),
+                    tolerance.clamp(0., 1.),
+                )?;
+                Selection::from_image(&region)
+            }
+            SelectionArea::Mask(selection) => return Some(selection),
+        };
+        Some(selection.with_transform(transform))
+    }
+    /// Returns the objects of the layer at the provided position in the pixels of its parent
+    /// frame, topmost first, or none if the layer is hidden.
+    pub(crate) fn objects_at(&self, position: Vector2) -> Vec<Box<dyn Object>> {
+        let state = self.state.read().unwrap();
+        if !state.visible {
+            return vec![];
+        }
+        state
+            .frame
+            .objects_at(position)
+            .into_iter()
+            .map(|object| {
+                Box::new(LayerObject {
+                    object,
+                    dirty: self.dirty.clone(),
+                }) as Box<dyn Object>
+            })
+            .collect()
+    }
+    pub(crate) fn set_fallback_fonts(&self, families: Vec<String>) {
+        self.state
+            .read()
+            .unwrap()
+            .frame
+            .set_fallback_fonts(families);
+        self.dirty.store(true, Ordering::Relaxed);
+    }
+    pub(crate) fn set_interpolation(&self, amount: Option<f64>) {
+        let mut state = self.state.write().unwrap();
+        state.interpolation = amount;
+        if !state.fixed {
+            state.frame.set_interpolation(amount);
+            self.dirty.store(true, Ordering::Relaxed);
+        }
+    }
+}
+
+impl Layer for FrameLayer {
+    fn add(&mut self, content: Content) -> Box<dyn Object> {
+        let object = {
+            let mut state = self.state.write().unwrap();
+            let state = &mut *state;
+            match &mut state.target {
+                Some(target) => target.add(content),
+                None => state.frame.add(content),
+            }
+        };
+        self.dirty.store(true, Ordering::Relaxed);
+        Box::new(LayerObject {
+            object,
+            dirty: self.dirty.clone(),
+        })
+    }
+    fn remove(&mut self, object: &dyn Object) -> bool {
+        let object = match object.as_any().downcast::<LayerObject>() {
+            // Only objects of this layer share its dirty flag.
+            Ok(object) if Arc::ptr_eq(&object.dirty, &self.dirty) => object,
+            _ => return false,
+        };
+        // Objects added within a selection are in nested layers of the frame, which it searches.
+        let removed = self.state.write().unwrap().frame.remove(&*object.object);
+        if removed {
+            self.dirty.store(true, Ordering::Relaxed);
+        }
+        removed
+    }
+    fn clear(&mut self) {
+        let mut state = self.state.write().unwrap();
+        state.frame.clear();
+        state.selection = None;
+        state.target = None;
+        // The nested layers are kept but emptied, masks included, so they no longer confine any
+        // content.
+        for group in &mut state.groups {
+            *group = None;
+        }
+        state.fills.clear();
+        self.dirty.store(true, Ordering::Relaxed);
+    }
+    fn get_opacity(&self) -> f64 {
+        self.state.read().unwrap().opacity
+    }
+    fn set_opacity(&mut self, opacity: f64) {
+        self.state.write().unwrap().opacity = opacity.clamp(0., 1.);
+        self.dirty.store(true, Ordering::Relaxed);
+    }
+    fn get_blend(&self) -> Blend {
+        self.state.read().unwrap().blend
+    }
+    fn set_blend(&mut self, blend: Blend) {
+        self.state.write().unwrap().blend = blend;
+        self.dirty.store(true, Ordering::Relaxed);
+    }
+    fn is_visible(&self) -> bool {
+        self.state.read().unwrap().visible
+    }
+    fn set_visible(&mut self, visible: bool) {
+        self.state.write().unwrap().visible = visible;
+        self.dirty.store(true, Ordering::Relaxed);
+    }
+    fn is_locked(&self) -> bool {
+        self.state.read().unwrap().locked
+    }
+    fn set_locked(&mut self, locked: bool) {
+        self.state.write().unwrap().locked = locked;
+    }
+    fn is_static(&self) -> bool {
+        self.state.read().unwrap().fixed
+    }
+    fn children(&self) -> Vec<Box<dyn Object>> {
+        self.groups()
+            .into_iter()
+            .flat_map(|(_, objects)| objects)
+            .collect()
+    }
+    fn groups(&self) -> Vec<(Option<Selection>, Vec<Box<dyn Object>>)> {
+        let state = self.state.read().unwrap();
+        let wrap = |objects: Vec<Box<dyn Object>>| -> Vec<Box<dyn Object>> {
+            objects
+                .into_iter()
+                .map(|object| {
+                    Box::new(LayerObject {
+                        object,
+                        dirty: self.dirty.clone(),
+                    }) as Box<dyn Object>
+                })
+                .collect()
+        };
+        // Objects added within a selection are in nested layers of the frame, whose children
+        // follow its own.
+        let nested = state.frame.layers();
+        let mut children = state.frame.children();
+        let layered: usize = nested.iter().map(|layer| layer.children().len()).sum();
+        children.truncate(children.len() - layered);
+        let mut groups = vec![(None, wrap(children))];
+        for (layer, selection) in nested.iter().zip(&state.groups) {
+            let mut objects = layer.children();
+            // The mask that confines a group to its selection was the first object added to it.
+            if selection.is_some() && !objects.is_empty() {
+                objects.remove(0);
+            }
+            groups.push((selection.clone(), wrap(objects)));
+        }
+        groups
+    }
+    fn set_static(&mut self, fixed: bool) {
+        let mut state = self.state.write().unwrap();
+        if state.fixed == fixed {
+            return;
+        }
+        state.fixed = fixed;
+        if fixed {
+            state.frame.set_interpolation(None);
+        } else {
+            // Objects moved while the layer was static start from where they are now.
+            state.frame.advance();
+            state.frame.set_interpolation(state.interpolation);
+        }
+        self.dirty.store(true, Ordering::Relaxed);
+    }
+    fn invalidate(&self) {
+        self.dirty.store(true, Ordering::Relaxed);
+    }
+    fn flood_fill(
+        &mut self,
+        point: Vector2,
+        color: LDRColor,
+        tolerance: f64,
+    ) -> Option<Box<dyn Object>> {
+        if self.is_locked() {
+            return None;
+        }
+        let (image, transform) = self.rasterization()?;
+        let pixel = (point - transform.position) / transform.scale;
+        if pixel.x < 0. || pixel.y < 0. {
+            return None;
+        }
+        let fill = fill::flood_fill(
+            &image,
+            pixel.x as u32,
+            pixel.y as u32,
+            color,
+            tolerance.clamp(0., 1.),
+        )?;
+        let mut painted = TiledImage::new(fill.format.width, fill.format.height);
+        painted.draw(&fill, 0, 0);
+        let content: Content = Rasterizable::Extension(Box::new(Painted(Arc::new(painted)))).into();
+        let object = self.add(content.with_transform(transform));
+        self.state.write().unwrap().fills.push(object.clone());
+        Some(object)
+    }
+    fn paint(&mut self, stamps: &[Stamp]) -> Option<Box<dyn Object>> {
+        if self.is_locked() {
+            return None;
+        }
+        let pixel_ratio = match self.state.read().unwrap().rendered {
+            // Stamps are rasterized at the resolution the layer is drawn at.
+            Some((size, viewport, pixel_ratio)) => {
+                let scale = size * pixel_ratio / viewport.size;
+                scale.x.max(scale.y)
+            }
+            None => 1.,
+        };
+        let (painted, transform) = brush::rasterize(stamps, pixel_ratio)?;
+        let content: Content = Rasterizable::Extension(Box::new(Painted(Arc::new(painted)))).into();
+        Some(self.add(content.with_transform(transform)))
+    }
+    fn undo_fill(&mut self) -> bool {
+        loop {
+            let fill = match self.state.write().unwrap().fills.pop() {
+                Some(fill) => fill,
+                None => return false,
+            };
+            // Fills whose objects were already removed have nothing left to undo.
+            if self.remove(&*fill) {
+                return true;
+            }
+        }
+    }
+    fn select(&mut self, area: SelectionArea) -> Option<Selection> {
+        let selection = self.selection(area)?;
+        let mut state = self.state.write().unwrap();
+        // Content added from here on goes to a nested layer from which everything outside of the
+        // selection is erased.
+        let mut target = state.frame.add_layer();
+        let mask: Content = Rasterizable::Extension(Box::new(Raster(selection.inverse()))).into();
+        target
+            .add(
+                mask.with_transform(selection.get_transform())
+                    .with_depth(u32::MAX),
+            )
+            .set_blend(Blend::Erase);
+        state.target = Some(target);
+        state.selection = Some(selection.clone());
+        state.groups.push(Some(selection.clone()));
+        Some(selection)
+    }
+    fn get_selection(&self) -> Option<Selection> {
+        self.state.read().unwrap().selection.clone()
+    }
+    fn clear_selection(&mut self) {
+        let mut state = self.state.write().unwrap();
+        if state.selection.take().is_some() {
+            // Later content is still drawn above content added within the selection.
+            state.target = Some(state.frame.add_layer());
+            state.groups.push(None);
+        }
+    }
+    fn box_clone(&self) -> Box<dyn Layer> {
+        Box::new(self.clone())
+    }
+}
+
+impl Debug for FrameLayer {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let state = self.state.read().unwrap();
+        f.debug_struct("FrameLayer")
+            .field("opacity", &state.opacity)
+            .field("blend", &state.blend)
+            .field("visible", &state.visible)
+            .field("locked", &state.locked)
+            .finish()
+    }
+}
+
+/// An [Object] in a [FrameLayer] that marks the layer dirty whenever it changes.
+#[derive(Clone)]
+struct LayerObject {
+    object: Box<dyn Object>,
+    dirty: Arc<AtomicBool>,
+}
+
+impl LayerObject {
+    fn changed(&self) {
+        self.dirty.store(true, Ordering::Relaxed);
+    }
+}
+
+impl Object for LayerObject {
+    fn apply_transform(&mut self, transform: Transform2) {
+        self.object.apply_transform(transform);
+        self.changed();
+    }
+    fn get_transform(&self) -> Transform2 {
+        self.object.get_transform()
+    }
+    fn set_transform(&mut self, transform: Transform2) {
+        self.object.set_transform(transform);
+        self.changed();
+    }
+    fn get_depth(&self) -> u32 {
+        self.object.get_depth()
+    }
+    fn set_depth(&mut self, depth: u32) {
+        self.object.set_depth(depth);
+        self.changed();
+    }
+    fn get_clip(&self) -> Option<Path> {
+        self.object.get_clip()
+    }
+    fn set_clip(&mut self, clip: Option<Path>) {
+        self.object.set_clip(clip);
+        self.changed();
+    }
+    fn get_blend(&self) -> Blend {
+        self.object.get_blend()
+    }
+    fn set_blend(&mut self, blend: Blend) {
+        self.object.set_blend(blend);
+        self.changed();
+    }
+    fn get_cursor(&self) -> CursorIcon {
+        self.object.get_cursor()
+    }
+    fn set_cursor(&mut self, cursor: CursorIcon) {
+        self.object.set_cursor(cursor);
+    }
+    fn is_visible(&self) -> bool {
+        self.object.is_visible()
+    }
+    fn set_visible(&mut self, visible: bool) {
+        self.object.set_visible(visible);
+        self.changed();
+    }
+    fn get_tag(&self) -> Option<String> {
+        self.object.get_tag()
+    }
+    fn set_tag(&mut self, tag: Option<String>) {
+        self.object.set_tag(tag);
+    }
+    fn get_content(&self) -> Content {
+        self.object.get_content()
+    }
+    fn update(&mut self, content: Rasterizable) {
+        self.object.update(content);
+        self.changed();
+    }
+    fn box_clone(&self) -> Box<dyn Object> {
+        Box::new(self.clone())
+    }
+    fn as_any(&self) -> Box<dyn Any> {
+        Box::new(self.clone())
+    }
+}
+
+/// An image drawn at its size in pixels, such as a tile of a flood fill or a selection mask.
+#[derive(Clone)]
+struct Raster(Image<LDRColor, Texture2>);
+
+impl Extension for Raster {
+    fn measure(&self) -> Vector2 {
+        (
+            f64::from(self.0.format.width),
+            f64::from(self.0.format.height),
+        )
+            .into()
+    }
+    fn expand(&self) -> Option<Vec<Content>> {
+        None
+    }
+    fn rasterize(&self) -> Image<LDRColor, Texture2> {
+        self.0.clone()
+    }
+    fn box_clone(&self) -> Box<dyn Extension> {
+        Box::new(self.clone())
+    }
+}
+
+/// The pixels painted by a flood fill or brush stamps, stored as sparse tiles so that only the area it paints takes
+/// memory, and drawn as a [Raster] for each of them.
+#[derive(Clone)]
+struct Painted(Arc<TiledImage>);
+
+impl Extension for Painted {
+    fn measure(&self) -> Vector2 {
+        self.0.get_size()
+    }
+    fn expand(&self) -> Option<Vec<Content>> {
+        Some(
+            self.0
+                .tiles()
+                .into_iter()
+                .map(|(position, tile)| {
+                    let content: Content = Rasterizable::Extension(Box::new(Raster(tile))).into();
+                    content.with_transform(Transform2::from(position))
+                })
+                .collect(),
+        )
+    }
+    fn rasterize(&self) -> Image<LDRColor, Texture2> {
+        self.0.export(Rect::new((0., 0.), self.0.get_size()))
+    }
+    fn box_clone(&self) -> Box<dyn Extension> {
+        Box::new(self.clone())
+    }
+}
@@ -1,14 +1,93 @@
 use crate::{
+    accessibility::{Preferences, Provider as AccessibilityProvider},
     graphics::{
+        brush::Stamp,
+        instances::Instances,
         path::{Path, Primitive},
-        text::Text,
-        ImageRepresentation, Rect, Transform2, Vector2,
+        quality::RasterOptions,
+        selection::{Selection, SelectionArea},
+        text::{search, Glyph, Text},
+        Image, ImageRepresentation, LDRColor, Rect, Texture2, Transform2, Vector2,
     },
+    haptics::Provider as HapticsProvider,
     input::Provider,
+    net::Provider as NetProvider,
+    storage::Provider as StorageProvider,
     targets,
 };
 
-use std::any::Any;
+use serde::{Deserialize, Serialize};
+
+use std::{
+    any::Any,
+    fmt::{self, Debug, Formatter},
+    ops::Range,
+};
+
+pub(crate) mod layer;
+/// Provides onion skinning for frame-by-frame animation.
+pub mod onion;
+/// Provides saving and loading of the contents of frames.
+pub mod scene;
+
+/// How an [Object] is combined with the content beneath it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Blend {
+    /// The [Object] is drawn over the content beneath it.
+    Normal,
+    /// The [Object] erases the content beneath it wherever it would have been drawn, in proportion
+    /// to its opacity, and is not itself visible. Erased areas of a [Frame] become transparent.
+    Erase,
+}
+
+impl Default for Blend {
+    fn default() -> Self {
+        Blend::Normal
+    }
+}
+
+/// The pointer cursor shown while the pointer is over an [Object].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum CursorIcon {
+    /// The platform's default cursor, usually an arrow.
+    Default,
+    /// A pointing hand, for links and buttons.
+    Pointer,
+    /// An I-beam, for selectable or editable text.
+    Text,
+    /// A crosshair, for precise selection.
+    Crosshair,
+    /// A four-way arrow, for content that can be moved.
+    Move,
+    /// An open hand, for content that can be dragged.
+    Grab,
+    /// A closed hand, for content being dragged.
+    Grabbing,
+    /// A cursor indicating that the action under it isn't allowed.
+    NotAllowed,
+    /// A cursor indicating that the application is busy and can't be interacted with.
+    Wait,
+    /// A cursor indicating that the application is busy but can still be interacted with.
+    Progress,
+    /// A cursor indicating that help is available.
+    Help,
+    /// A horizontal two-way arrow, for resizing columns and horizontal edges.
+    ResizeHorizontal,
+    /// A vertical two-way arrow, for resizing rows and vertical edges.
+    ResizeVertical,
+    /// A magnifying glass with a plus sign.
+    ZoomIn,
+    /// A magnifying glass with a minus sign.
+    ZoomOut,
+    /// No cursor is shown.
+    Hidden,
+}
+
+impl Default for CursorIcon {
+    fn default() -> Self {
+        CursorIcon::Default
+    }
+}
 
 /// Represents content optimized and cached for rendering.
 pub trait Object: Sync + Send {
@@ -22,10 +101,40 @@ pub trait Object: Sync + Send {
     fn get_depth(&self) -> u32;
     /// Sets the current z-depth of the [Object].
     fn set_depth(&mut self, depth: u32);
+    /// Gets the current clip path of the [Object].
+    fn get_clip(&self) -> Option<Path>;
+    /// Sets the clip path of the [Object], outside of which nothing is drawn. The clip path is
+    /// positioned in the same space as the content and moves with it. Passing [None] removes the clip.
+    fn set_clip(&mut self, clip: Option<Path>);
+    /// Gets how the [Object] is combined with the content beneath it.
+    fn get_blend(&self) -> Blend;
+    /// Sets how the [Object] is combined with the content beneath it.
+    fn set_blend(&mut self, blend: Blend);
+    /// Gets the cursor shown while the pointer is over the [Object].
+    fn get_cursor(&self) -> CursorIcon;
+    /// Sets the cursor shown while the pointer is over the [Object]. The cursor of the topmost
+    /// [Object] of the root [Frame] or its layers under the pointer is shown, as found from the
+    /// bounds of its content, and is updated every frame.
+    fn set_cursor(&mut self, cursor: CursorIcon);
+    /// Returns whether the [Object] is drawn.
+    fn is_visible(&self) -> bool;
+    /// Sets whether the [Object] is drawn. Hidden objects keep their place in the [Frame] and are
+    /// ignored when finding the cursor under the pointer.
+    fn set_visible(&mut self, visible: bool);
+    /// Gets the tag of the [Object], if any.
+    fn get_tag(&self) -> Option<String>;
+    /// Sets the tag of the [Object], by which it can be found with [Frame::find]. Passing [None]
+    /// removes the tag.
+    fn set_tag(&mut self, tag: Option<String>);
+    /// Returns the content of the [Object] with its current transform, depth, clip and tag, such
+    /// that adding it to a [Frame] creates a copy of the [Object].
+    fn get_content(&self) -> Content;
     /// Replaces the contents of the [Object] with new Rasterizable content. This may be costly.
     fn update(&mut self, content: Rasterizable);
     #[doc(hidden)]
     fn box_clone(&self) -> Box<dyn Object>;
+    #[doc(hidden)]
+    fn as_any(&self) -> Box<dyn Any>;
 }
 
 impl Clone for Box<dyn Object> {
@@ -34,20 +143,228 @@ impl Clone for Box<dyn Object> {
     }
 }
 
+/// Some [Text] laid out by a [Frame] and retained, so that text that is measured, hit tested or
+/// drawn often is only laid out once. Adding a [TextLayout] to the [Frame] that laid it out, as
+/// [Content], draws it with its existing layout.
+pub trait TextLayout: Sync + Send {
+    /// Returns the [Text] that was laid out.
+    fn text(&self) -> Text;
+    /// Returns the measured dimensions of the laid out text, as given by [Frame::measure].
+    fn measure(&self) -> Vector2;
+    /// Returns the caret before the grapheme cluster at the provided index, as given by
+    /// [Frame::caret].
+    fn caret(&self, index: usize) -> Rect;
+    /// Returns the index of the caret position nearest to a point, as given by
+    /// [Frame::hit_test_text].
+    fn hit_test(&self, point: Vector2) -> usize;
+    /// Returns the highlight rectangles covering a range of grapheme clusters, as given by
+    /// [Frame::selection_rects].
+    fn selection_rects(&self, range: Range<usize>) -> Vec<Rect>;
+    /// Returns the shaped glyphs of the laid out text, as given by [Frame::glyphs].
+    fn glyphs(&self) -> Vec<Glyph>;
+    #[doc(hidden)]
+    fn box_clone(&self) -> Box<dyn TextLayout>;
+    #[doc(hidden)]
+    fn as_any(&self) -> Box<dyn Any>;
+}
+
+impl Clone for Box<dyn TextLayout> {
+    fn clone(&self) -> Box<dyn TextLayout> {
+        self.box_clone()
+    }
+}
+
+impl Debug for dyn TextLayout {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "TextLayout ( {:?} )", self.text())
+    }
+}
+
+/// A group of objects within a [Frame] that is rasterized to its own surface and composited with
+/// an opacity and [Blend]. A [Layer] is only rasterized again after its objects change.
+pub trait Layer: Sync + Send {
+    /// Adds content to the [Layer].
+    fn add(&mut self, content: Content) -> Box<dyn Object>;
+    /// Removes an [Object] that was added to the [Layer], such that it is no longer drawn,
+    /// returning whether it was found.
+    fn remove(&mut self, object: &dyn Object) -> bool;
+    /// Removes every [Object] from the [Layer], along with its selection.
+    fn clear(&mut self);
+    /// Gets the opacity with which the [Layer] is composited.
+    fn get_opacity(&self) -> f64;
+    /// Sets the opacity, between zero and one, with which the [Layer] is composited.
+    fn set_opacity(&mut self, opacity: f64);
+    /// Gets how the [Layer] is combined with the content beneath it.
+    fn get_blend(&self) -> Blend;
+    /// Sets how the [Layer] is combined with the content beneath it.
+    fn set_blend(&mut self, blend: Blend);
+    /// Returns whether the [Layer] is drawn.
+    fn is_visible(&self) -> bool;
+    /// Sets whether the [Layer] is drawn. Hidden layers keep their rasterization.
+    fn set_visible(&mut self, visible: bool);
+    /// Returns whether the [Layer] is locked.
+    fn is_locked(&self) -> bool;
+    /// Sets whether the [Layer] is locked. Locking is advisory: it doesn't prevent changes made
+    /// through the [Layer] or its objects, but editing tools leave locked layers untouched.
+    fn set_locked(&mut self, locked: bool);
+    /// Returns whether the [Layer] is static.
+    fn is_static(&self) -> bool;
+    /// Sets whether the [Layer] is static, such as for a background grid or map. A static [Layer]
+    /// keeps its rasterization cached and is composited from it until it is changed or
+    /// invalidated, or the viewport of its [Frame] or the global rendering settings change. Its
+    /// objects are drawn at their current transforms rather than interpolated, so the cache isn't
+    /// redrawn on every tick.
+    fn set_static(&mut self, fixed: bool);
+    /// Returns the objects of the [Layer] in the order they were added.
+    fn children(&self) -> Vec<Box<dyn Object>>;
+    /// Returns the objects of the [Layer] in the order they were added, split at each change of
+    /// selection, with each group given along with the selection that confines it, if any. The
+    /// first group holds the objects added before any selection was made, such as for saving the
+    /// [Layer] along with its selections.
+    fn groups(&self) -> Vec<(Option<Selection>, Vec<Box<dyn Object>>)>;
+    /// Marks the [Layer] for rasterization on the next draw, for content such as an [Extension]
+    /// that changes without its [Object] being updated.
+    fn invalidate(&self);
+    /// Fills the region of the [Layer]'s rasterization that is contiguous with the provided point
+    /// and within a tolerance, between zero and one, of its color, like a paint bucket. The fill is
+    /// added to the [Layer] as a new [Object], which keeps only the tiles of the rasterization that
+    /// it paints, and is an undo step for [Layer::undo_fill].
+    ///
+    /// Returns [None] if the [Layer] is locked, hasn't yet been drawn, or the point is outside of
+    /// the viewport.
+    fn flood_fill(
+        &mut self,
+        point: Vector2,
+        color: LDRColor,
+        tolerance: f64,
+    ) -> Option<Box<dyn Object>>;
+    /// Paints brush stamps into the [Layer], rasterized over each other at the pixel ratio the
+    /// [Layer] was last drawn at. The stamps are added as a single new [Object], which keeps only
+    /// the tiles they paint, so a stroke is usually painted by passing the stamps from each move.
+    ///
+    /// Returns [None] if the [Layer] is locked or the stamps cover no pixels.
+    fn paint(&mut self, stamps: &[Stamp]) -> Option<Box<dyn Object>>;
+    /// Undoes the most recent flood fill that is still in the [Layer], removing its [Object].
+    /// Returns whether anything was undone.
+    fn undo_fill(&mut self) -> bool;
+    /// Selects an area of the [Layer]. Until the selection is cleared or replaced, content added to
+    /// the [Layer], including brush stamps and flood fills, is only drawn within the selection.
+    ///
+    /// Returns [None], leaving the current selection in place, if the [Layer] hasn't yet been
+    /// drawn or a [SelectionArea::Color] point is outside of the viewport.
+    fn select(&mut self, area: SelectionArea) -> Option<Selection>;
+    /// Returns the current selection, if any.
+    fn get_selection(&self) -> Option<Selection>;
+    /// Clears the selection, such that content added afterwards is drawn anywhere in the [Layer].
+    fn clear_selection(&mut self);
+    #[doc(hidden)]
+    fn box_clone(&self) -> Box<dyn Layer>;
+}
+
+impl Clone for Box<dyn Layer> {
+    fn clone(&self) -> Box<dyn Layer> {
+        self.box_clone()
+    }
+}
+
 /// An isolated rendering context.
 pub trait Frame: Sync + Send {
     /// Adds content to the [Frame].
     fn add(&mut self, content: Content) -> Box<dyn Object>;
+    /// Removes an [Object] that was added to the [Frame] or one of its layers, such that it is no
+    /// longer drawn, returning whether it was found. Changes made through the removed [Object]
+    /// afterwards have no effect.
+    fn remove(&mut self, object: &dyn Object) -> bool;
+    /// Removes every [Object] from the [Frame] and its layers. The layers themselves are kept.
+    fn clear(&mut self);
+    /// Adds a new, empty [Layer] to the [Frame]. Layers are composited in the order they were
+    /// added, above the content added directly to the [Frame].
+    fn add_layer(&mut self) -> Box<dyn Layer>;
+    /// Returns the layers of the [Frame] in the order they were added.
+    fn layers(&self) -> Vec<Box<dyn Layer>>;
     /// Resizes the [Frame]. This does not resize the viewport.
     fn resize(&self, size: Vector2);
     /// Sets the viewport.
     fn set_viewport(&self, viewport: Rect);
+    /// Gets the color the [Frame] is cleared to before its contents are drawn, which is white
+    /// unless it has been set.
+    fn get_background(&self) -> LDRColor;
+    /// Sets the color the [Frame] is cleared to before its contents are drawn. A transparent
+    /// background, as layers have, lets the [Frame] be composited over other content.
+    fn set_background(&self, color: LDRColor);
+    /// Gets the current clip path of the [Frame].
+    fn get_clip(&self) -> Option<Path>;
+    /// Sets the clip path of the [Frame], outside of which none of its contents or layers are
+    /// drawn or found under the pointer, such as the bounds of a scrollable panel. The clip path
+    /// is positioned in the same space as the contents. Passing [None] removes the clip.
+    fn set_clip(&self, clip: Option<Path>);
     /// Returns the size of the [Frame].
     fn get_size(&self) -> Vector2;
     /// Returns an image that is a still rasterization of any rendered content.
     fn to_image(&self) -> Box<dyn ImageRepresentation>;
+    /// Returns a rasterization of the viewport of the [Frame] at the provided pixel ratio rather
+    /// than its own, such as for a thumbnail or as the source of an effect. It is drawn offscreen
+    /// into a new image with an alpha channel, so it needs no window, stays as it is when the
+    /// [Frame] is drawn again, and is transparent wherever the background and content of the
+    /// [Frame] are. A pixel ratio that isn't finite and positive falls back to that of the
+    /// [Frame], and the ratio is reduced where the image would be too large to allocate.
+    fn to_image_scaled(&self, pixel_ratio: f64) -> Box<dyn ImageRepresentation>;
     /// Returns the measured dimensions of some provided content.
     fn measure(&self, input: Rasterizable) -> Vector2;
+    /// Returns the objects of the [Frame] followed by those of each of its layers, in the order
+    /// they were added.
+    fn children(&self) -> Vec<Box<dyn Object>>;
+    /// Returns the objects of the [Frame] and its layers with the provided tag, in the order given
+    /// by [Frame::children].
+    fn find(&self, tag: &str) -> Vec<Box<dyn Object>> {
+        self.children()
+            .into_iter()
+            .filter(|object| object.get_tag().as_deref() == Some(tag))
+            .collect()
+    }
+    /// Returns the objects of the [Frame] and its layers at the provided position in the pixels of
+    /// the [Frame], topmost first. Objects are found from the bounds of their content and clip, as
+    /// transformed, and hidden objects and those that erase are skipped.
+    fn objects_at(&self, position: Vector2) -> Vec<Box<dyn Object>>;
+    /// Finds the occurrences of the query in the text content of the [Frame].
+    fn find_text(&self, query: &str, options: search::Options) -> Vec<Match>;
+    /// Returns the caret before the grapheme cluster at the provided index of some [Text], as it
+    /// would be laid out in this [Frame]. An index equal to the number of grapheme clusters gives
+    /// the caret at the end of the text. The caret is a zero-width [Rect] spanning the height of
+    /// its line, in the local space of the text.
+    fn caret(&self, text: &Text, index: usize) -> Rect;
+    /// Returns the index of the caret position nearest to a point, in the local space of some
+    /// [Text] as it would be laid out in this [Frame], counted in grapheme clusters.
+    fn hit_test_text(&self, text: &Text, point: Vector2) -> usize;
+    /// Returns the highlight rectangles covering a range of grapheme clusters of some [Text], as
+    /// it would be laid out in this [Frame], one per line in the local space of the text. Drawing
+    /// these beneath the text renders a selection.
+    fn selection_rects(&self, text: &Text, range: Range<usize>) -> Vec<Rect>;
+    /// Returns the shaped glyphs of some [Text] as it would be laid out in this [Frame], in visual
+    /// order within each line and in the local space of the text.
+    fn glyphs(&self, text: &Text) -> Vec<Glyph>;
+    /// Lays out some [Text] once for this [Frame], returning a [TextLayout] that can be measured,
+    /// queried and drawn repeatedly without laying the text out again.
+    fn layout_text(&self, text: Text) -> Box<dyn TextLayout>;
+    /// Records the current transform of each [Object] as its previous transform, beginning a new
+    /// simulation step.
+    fn advance(&self);
+    /// Sets the fraction of the way from the previous to the current transform of each [Object] at
+    /// which it is drawn, or [None] to draw current transforms directly, which is the default.
+    ///
+    /// [Interpolator] manages this along with [Frame::advance] for fixed-rate simulation.
+    fn set_interpolation(&self, amount: Option<f64>);
+    /// Sets the options this [Frame] is rasterized with, such as the tolerance to which curves
+    /// are flattened. Backends apply whichever of them they have control over.
+    fn set_raster_options(&self, options: RasterOptions);
+    /// Sets the fallback font families of this [Frame] and its layers, which are tried in order
+    /// for characters that none of the families of a [Text] or its own fallback families have a
+    /// glyph for. They replace the defaults, the color emoji and CJK fonts that ship with each
+    /// major platform.
+    fn set_fallback_fonts(&self, families: Vec<String>);
+    /// Returns what was approximated or dropped the last time this [Frame] was drawn, each listed
+    /// once, so that applications can adapt their content to the backend.
+    fn warnings(&self) -> Vec<RenderWarning>;
     #[doc(hidden)]
     fn box_clone(&self) -> Box<dyn Frame>;
     #[doc(hidden)]
@@ -58,6 +375,10 @@ pub trait Frame: Sync + Send {
     fn set_pixel_ratio(&self, ratio: f64);
     #[doc(hidden)]
     fn as_any(&self) -> Box<dyn Any>;
+    /// Returns the cursor of the topmost [Object] at the provided position in the pixels of the
+    /// [Frame], or [None] if there is no [Object] there.
+    #[doc(hidden)]
+    fn cursor_at(&self, position: Vector2) -> Option<CursorIcon>;
 }
 
 impl Clone for Box<dyn Frame> {
@@ -66,12 +387,148 @@ impl Clone for Box<dyn Frame> {
     }
 }
 
+/// A feature of some content that the backend of a [Frame] approximated or dropped when drawing
+/// it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum RenderWarning {
+    /// A gradient was drawn without its dithering.
+    GradientDither,
+    /// A radial gradient was drawn with a transform that scales it unevenly or skews it, which
+    /// was approximated by an even scale.
+    GradientTransform,
+    /// Text was drawn without some of its OpenType features.
+    FontFeatures,
+    /// Text was drawn without its antialiasing or hinting options.
+    GlyphRasterization,
+    /// The curve flattening tolerance of the [RasterOptions] of the [Frame] was ignored.
+    RasterTolerance,
+    /// Text was wrapped without hyphenating its words.
+    Hyphenation,
+    /// Text was wrapped between words rather than at the line break opportunities of the Unicode
+    /// line breaking algorithm.
+    LineBreaking,
+}
+
+/// An occurrence of a search query in the text content of a [Frame].
+#[derive(Clone)]
+pub struct Match {
+    /// The [Object] containing the text.
+    pub object: Box<dyn Object>,
+    /// The range of grapheme clusters matched in the text.
+    pub range: Range<usize>,
+    /// The areas covered by the matched text, one for each line it spans, in the local space of
+    /// the [Object].
+    pub rects: Vec<Rect>,
+}
+
+impl Debug for Match {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Match {{ range: {:?}, rects: {:?} }}",
+            self.range, self.rects
+        )
+    }
+}
+
+/// The greatest number of simulation steps an [Interpolator] runs for a single tick. Time beyond
+/// this is dropped so that a slow simulation cannot fall ever further behind.
+const MAXIMUM_STEPS: u32 = 8;
+
+/// Runs a simulation at a fixed rate against a [Frame], drawing the transforms of its objects
+/// interpolated between the two most recent simulation steps. This keeps motion smooth when the
+/// simulation rate does not match the display rate.
+///
+/// Because drawing trails the simulation by up to one step, objects appear one step behind their
+/// current transforms. Given [Preferences] for reduced motion, objects are drawn at their current
+/// transforms instead, moving in whole steps.
+pub struct Interpolator {
+    frame: Box<dyn Frame>,
+    step: f64,
+    accumulated: f64,
+    reduced_motion: bool,
+}
+
+impl Interpolator {
+    /// Creates a new [Interpolator] that simulates the provided [Frame] at the given number of
+    /// steps per second.
+    pub fn new(frame: Box<dyn Frame>, rate: f64) -> Interpolator {
+        frame.advance();
+        frame.set_interpolation(Some(1.));
+        Interpolator {
+            frame,
+            step: 1000. / rate,
+            accumulated: 0.,
+            reduced_motion: false,
+        }
+    }
+    /// Follows the provided accessibility preferences, such as those given by
+    /// [accessibility](crate::accessibility::Provider::accessibility) on the context, drawing
+    /// current transforms directly where reduced motion is preferred.
+    pub fn with_preferences(mut self, preferences: Preferences) -> Self {
+        self.reduced_motion = preferences.reduced_motion;
+        self.frame.set_interpolation(self.interpolation());
+        self
+    }
+    /// Returns the interpolation the [Frame] is drawn at, which is none where reduced motion is
+    /// preferred.
+    fn interpolation(&self) -> Option<f64> {
+        if self.reduced_motion {
+            None
+        } else {
+            Some(self.accumulated / self.step)
+        }
+    }
+    /// Advances time by the provided number of milliseconds, calling the step handler once for
+    /// each simulation step that has become due. The handler should update object transforms
+    /// as of the end of its step. Intended to be called from a tick handler.
+    pub fn tick<F>(&mut self, elapsed: f64, mut step: F)
+    where
+        F: FnMut(),
+    {
+        self.accumulated += elapsed;
+        let mut steps = 0;
+        while self.accumulated >= self.step {
+            if steps == MAXIMUM_STEPS {
+                self.accumulated %= self.step;
+                break;
+            }
+            self.frame.advance();
+            step();
+            self.accumulated -= self.step;
+            steps += 1;
+        }
+        self.frame.set_interpolation(self.interpolation());
+    }
+}
+
+impl Drop for Interpolator {
+    fn drop(&mut self) {
+        self.frame.set_interpolation(None);
+    }
+}
+
+impl Debug for Interpolator {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Interpolator {{ step: {:?}, accumulated: {:?} }}",
+            self.step, self.accumulated
+        )
+    }
+}
+
 /// Renderable content.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Content {
     pub(crate) content: Rasterizable,
     pub(crate) depth: u32,
     pub(crate) transform: Transform2,
+    pub(crate) clip: Option<Path>,
+    // Retained layouts belong to the frame they were laid out by.
+    #[serde(skip)]
+    pub(crate) layout: Option<Box<dyn TextLayout>>,
+    pub(crate) tag: Option<String>,
 }
 
 impl Content {
@@ -89,6 +546,17 @@ impl Content {
         self.depth = depth;
         self
     }
+    /// Clips the content to the filled area of the provided [Path], which is positioned in the
+    /// same space as the content.
+    pub fn with_clip(mut self, clip: Path) -> Self {
+        self.clip = Some(clip);
+        self
+    }
+    /// Tags the content, such that the [Object] it is added as can be found with [Frame::find].
+    pub fn with_tag(mut self, tag: &str) -> Self {
+        self.tag = Some(tag.to_owned());
+        self
+    }
 }
 
 impl From<Path> for Content {
@@ -97,6 +565,9 @@ impl From<Path> for Content {
             content: input.into(),
             depth: 0,
             transform: Transform2::default(),
+            clip: None,
+            layout: None,
+            tag: None,
         }
     }
 }
@@ -107,6 +578,22 @@ impl From<Text> for Content {
             content: input.into(),
             depth: 0,
             transform: Transform2::default(),
+            clip: None,
+            layout: None,
+            tag: None,
+        }
+    }
+}
+
+impl From<Box<dyn TextLayout>> for Content {
+    fn from(input: Box<dyn TextLayout>) -> Content {
+        Content {
+            content: input.text().into(),
+            depth: 0,
+            transform: Transform2::default(),
+            clip: None,
+            layout: Some(input),
+            tag: None,
         }
     }
 }
@@ -117,6 +604,9 @@ impl From<Rasterizable> for Content {
             content: input,
             depth: 0,
             transform: Transform2::default(),
+            clip: None,
+            layout: None,
+            tag: None,
         }
     }
 }
@@ -128,12 +618,204 @@ impl From<Content> for Rasterizable {
 }
 
 /// A type that can be rasterized.
-#[derive(Debug, Clone)]
+///
+/// [Extension] content can't be deserialized, so it is serialized as the content it
+/// [expands](Extension::expand) into, or where it doesn't expand as a [Path] filled with its
+/// [rasterization](Extension::rasterize).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(from = "SerializedRasterizable", into = "SerializedRasterizable")]
 pub enum Rasterizable {
     /// Some [Text].
     Text(Box<Text>),
     /// Some [Path].
     Path(Box<Path>),
+    /// Some user-defined [Extension] content.
+    Extension(Box<dyn Extension>),
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename = "Rasterizable")]
+enum SerializedRasterizable {
+    Text(Box<Text>),
+    Path(Box<Path>),
+    Expanded(Vector2, Vec<Content>),
+}
+
+/// The content an [Extension] expanded into when it was serialized, along with its measured size.
+#[derive(Clone)]
+struct Expanded(Vector2, Vec<Content>);
+
+impl Extension for Expanded {
+    fn measure(&self) -> Vector2 {
+        self.0
+    }
+    fn expand(&self) -> Option<Vec<Content>> {
+        Some(self.1.clone())
+    }
+    fn rasterize(&self) -> Image<LDRColor, Texture2> {
+        // Expanded content is always drawn from its expansion, so this is never shown.
+        Image {
+            pixels: vec![],
+            format: Texture2 {
+                width: 0,
+                height: 0,
+            },
+        }
+    }
+    fn box_clone(&self) -> Box<dyn Extension> {
+        Box::new(self.clone())
+    }
+}
+
+impl From<Rasterizable> for SerializedRasterizable {
+    fn from(input: Rasterizable) -> SerializedRasterizable {
+        match input {
+            Rasterizable::Text(text) => SerializedRasterizable::Text(text),
+            Rasterizable::Path(path) => SerializedRasterizable::Path(path),
+            Rasterizable::Extension(extension) => match extension.expand() {
+                Some(contents) => SerializedRasterizable::Expanded(extension.measure(), contents),
+                None => {
+                    let image: Box<dyn ImageRepresentation> = Box::new(extension.rasterize());
+                    Rasterizable::from(image).into()
+                }
+            },
+        }
+    }
+}
+
+impl From<SerializedRasterizable> for Rasterizable {
+    fn from(input: SerializedRasterizable) -> Rasterizable {
+        match input {
+            SerializedRasterizable::Text(text) => Rasterizable::Text(text),
+            SerializedRasterizable::Path(path) => Rasterizable::Path(path),
+            SerializedRasterizable::Expanded(size, contents) => {
+                Rasterizable::Extension(Box::new(Expanded(size, contents)))
+            }
+        }
+    }
+}
+
+/// Returns whether a point, in the space an [Object] is positioned in, is within the bounds of
+/// its content and clip, with text covered by the rects the provided closure returns for it, such
+/// as those the [Object] retains from laying it out.
+pub(crate) fn hit_test<F>(
+    content: &Rasterizable,
+    orientation: Transform2,
+    clip: Option<&Path>,
+    point: Vector2,
+    text_rects: F,
+) -> bool
+where
+    F: FnOnce(&Text) -> Vec<Rect>,
+{
+    let point = match orientation.to_local(point) {
+        Some(point) => point,
+        None => return false,
+    };
+    if let Some(clip) = clip {
+        if !clip.bounds().contains(point) {
+            return false;
+        }
+    }
+    match content {
+        Rasterizable::Path(path) => path.bounds().contains(point),
+        Rasterizable::Text(text) => text_rects(text).iter().any(|rect| rect.contains(point)),
+        Rasterizable::Extension(extension) => {
+            Rect::new((0., 0.), extension.measure()).contains(point)
+        }
+    }
+}
+
+/// Returns the objects of a [Frame] at a position in its pixels, topmost first, as needed. The
+/// objects of its layers come first, from the last added, followed by those of its contents, given
+/// in the order they are drawn, that pass the provided hit test. Nothing is found where the point,
+/// in the space of the content of the [Frame], is outside of the bounds of its clip.
+pub(crate) fn objects_at<T, F>(
+    layers: Vec<layer::FrameLayer>,
+    contents: Vec<T>,
+    position: Vector2,
+    point: Vector2,
+    clip: Option<&Path>,
+    hit: F,
+) -> impl Iterator<Item = Box<dyn Object>>
+where
+    T: Object + 'static,
+    F: Fn(&T) -> bool,
+{
+    let (layers, contents) = match clip {
+        Some(clip) if !clip.bounds().contains(point) => (vec![], vec![]),
+        _ => (layers, contents),
+    };
+    layers
+        .into_iter()
+        .rev()
+        .flat_map(move |layer| layer.objects_at(position))
+        .chain(
+            contents
+                .into_iter()
+                .rev()
+                .filter(move |object| hit(object))
+                .map(|object| Box::new(object) as Box<dyn Object>),
+        )
+}
+
+/// The greatest width or height, in pixels, of an image given by [Frame::to_image_scaled].
+const MAXIMUM_IMAGE_SIZE: f64 = 16_384.;
+
+/// Returns the pixel ratio to rasterize an image of a [Frame] of the provided size at, given the
+/// requested ratio and the ratio of the [Frame] itself. A requested ratio that isn't finite and
+/// positive falls back to that of the [Frame], and either is reduced as needed to keep the image
+/// within [MAXIMUM_IMAGE_SIZE] pixels along each side.
+pub(crate) fn image_pixel_ratio(size: Vector2, requested: f64, own: f64) -> f64 {
+    let ratio = if requested.is_finite() && requested > 0. {
+        requested
+    } else {
+        own
+    };
+    let largest = size.x.max(size.y);
+    if largest * ratio > MAXIMUM_IMAGE_SIZE {
+        MAXIMUM_IMAGE_SIZE / largest
+    } else {
+        ratio
+    }
+}
+
+/// User-defined content that can be rendered alongside the built-in content types.
+pub trait Extension: Sync + Send {
+    /// Returns the dimensions of the content.
+    fn measure(&self) -> Vector2;
+    /// Expands the content into built-in content types where it can be represented by them.
+    ///
+    /// Returning [None] causes the content to be drawn using [Extension::rasterize] instead.
+    fn expand(&self) -> Option<Vec<Content>>;
+    /// Returns a rasterization of the content at its measured size. This is only used where the content can't be expanded.
+    fn rasterize(&self) -> Image<LDRColor, Texture2>;
+    /// Returns a rasterization of the content at the provided number of pixels per unit of its
+    /// measured size, which backends draw with so that it stays sharp on dense displays. The image
+    /// is stretched over the measured size, so the default of [Extension::rasterize] is drawn the
+    /// same, only less sharply.
+    fn rasterize_scaled(&self, _pixel_ratio: f64) -> Image<LDRColor, Texture2> {
+        self.rasterize()
+    }
+    /// Returns the [Instances] the content consists of, if any, which backends draw by placing the
+    /// shared path at each instance rather than by [expanding](Extension::expand) the content.
+    fn as_instances(&self) -> Option<&Instances> {
+        None
+    }
+    #[doc(hidden)]
+    fn box_clone(&self) -> Box<dyn Extension>;
+}
+
+impl Clone for Box<dyn Extension> {
+    fn clone(&self) -> Box<dyn Extension> {
+        self.box_clone()
+    }
+}
+
+impl Debug for dyn Extension {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "Extension ( {:?} )", self.measure())
+    }
 }
 
 impl From<Path> for Rasterizable {
@@ -148,6 +830,25 @@ impl From<Text> for Rasterizable {
     }
 }
 
+impl From<Box<dyn Extension>> for Rasterizable {
+    fn from(input: Box<dyn Extension>) -> Rasterizable {
+        Rasterizable::Extension(input)
+    }
+}
+
+impl From<Box<dyn Extension>> for Content {
+    fn from(input: Box<dyn Extension>) -> Content {
+        Content {
+            content: input.into(),
+            depth: 0,
+            transform: Transform2::default(),
+            clip: None,
+            layout: None,
+            tag: None,
+        }
+    }
+}
+
 impl From<Box<dyn ImageRepresentation>> for Rasterizable {
     fn from(input: Box<dyn ImageRepresentation>) -> Rasterizable {
         Rasterizable::Path(Box::new(
@@ -160,8 +861,13 @@ impl From<Box<dyn ImageRepresentation>> for Rasterizable {
 
 /// Provides an interface for the rasterization of content.
 pub trait Rasterizer: Sync + Send {
-    /// Returns a rasterization of the input.
-    fn rasterize(&self, input: Rasterizable, vector: Vector2) -> Box<dyn ImageRepresentation>;
+    /// Returns a rasterization of the input with the provided options.
+    fn rasterize(
+        &self,
+        input: Rasterizable,
+        vector: Vector2,
+        options: RasterOptions,
+    ) -> Box<dyn ImageRepresentation>;
 }
 
 /// Provides 2-dimensional euclidean rendering capabilities.
@@ -171,7 +877,17 @@ pub trait Canvas: Rasterizer {
 }
 
 /// An aggregated context with bound graphics.
-pub trait CanvasContext: Canvas + Provider + Ticker + Send {}
+pub trait CanvasContext:
+    Canvas
+    + Provider
+    + HapticsProvider
+    + AccessibilityProvider
+    + NetProvider
+    + StorageProvider
+    + Ticker
+    + Send
+{
+}
 
 impl Clone for Box<dyn ActiveCanvas> {
     fn clone(&self) -> Box<dyn ActiveCanvas> {
@@ -183,6 +899,10 @@ impl Clone for Box<dyn ActiveCanvas> {
 pub trait ActiveCanvas: CanvasContext {
     #[doc(hidden)]
     fn box_clone(&self) -> Box<dyn ActiveCanvas>;
+    /// Shuts the context down, releasing its root [Frame] and tick handlers. Its runloop stops
+    /// after the current frame and releases its window and GPU resources, then
+    /// [InactiveCanvas::run_with] returns once the callback provided to it has also returned.
+    fn shutdown(&self);
 }
 
 /// An inactive canvas.
@@ -206,6 +926,12 @@ pub trait InteractiveCanvas: Canvas {
 }
 
 /// Initializes a new graphics context.
+///
+/// Any number of independent contexts can exist in one process. Each native context started from
+/// here runs its own window and event loop, so contexts run together must each be run on their own
+/// thread, which not every platform allows. To share one event loop between several windows
+/// instead, [attach] a context to each window of it. On the web, only one context at a time can
+/// be started as the root of the page.
 pub fn new() -> Box<dyn InteractiveCanvas> {
     #[cfg(any(target_arch = "wasm32", target_arch = "asmjs"))]
     return targets::web::graphics::new();
@@ -213,3 +939,21 @@ pub fn new() -> Box<dyn InteractiveCanvas> {
     #[cfg(any(target_os = "linux", target_os = "macos", target_os = "windows"))]
     return targets::native::graphics::new();
 }
+
+#[cfg(any(target_os = "linux", target_os = "windows"))]
+use crate::errors::Error;
+#[cfg(any(target_os = "linux", target_os = "windows"))]
+pub use crate::targets::native::graphics::EmbeddedContext;
+
+/// Attaches a new graphics context to a window created by the application.
+///
+/// Rather than running its own event loop as a context started from [new] does, the context is
+/// passed the events of the window by the application and rendered as part of its event loop,
+/// which allows vessels to be embedded as a view of a larger application. Embedding isn't
+/// available on macOS, where GL contexts can't be created for existing windows.
+///
+/// Fails if a GL context with the functions vessels draws with can't be created for the window.
+#[cfg(any(target_os = "linux", target_os = "windows"))]
+pub fn attach(window: &winit::Window) -> Result<EmbeddedContext, Error> {
+    targets::native::graphics::attach(window)
+}
@@ -0,0 +1,210 @@
+use crate::graphics::{
+    canvas::{Blend, Content, CursorIcon, Frame, Layer, Object, Rasterizable},
+    path::Path,
+    Transform2,
+};
+
+use std::{
+    any::Any,
+    fmt::{self, Debug, Formatter},
+};
+
+/// The frames of a frame-by-frame animation, drawn as [Layer]s of a [Frame] such that only the
+/// current animation frame is shown, with its neighbours optionally ghosted behind it at reduced
+/// opacity.
+///
+/// Ghosts are kept on their own layers beneath every animation frame, so the number of animation
+/// frames is fixed when the [OnionSkin] is created.
+pub struct OnionSkin {
+    ghosts: Vec<Box<dyn Layer>>,
+    frames: Vec<Box<dyn Layer>>,
+    current: usize,
+    before: usize,
+    after: usize,
+    opacity: f64,
+    enabled: bool,
+}
+
+impl OnionSkin {
+    /// Adds layers for the provided number of animation frames to the [Frame]. The first animation
+    /// frame is current and a single ghost is shown on either side of it at an opacity of 0.3.
+    pub fn new(frame: &mut dyn Frame, count: usize) -> OnionSkin {
+        let ghosts = (0..count).map(|_| frame.add_layer()).collect();
+        let frames = (0..count).map(|_| frame.add_layer()).collect();
+        let mut skin = OnionSkin {
+            ghosts,
+            frames,
+            current: 0,
+            before: 1,
+            after: 1,
+            opacity: 0.3,
+            enabled: true,
+        };
+        skin.update();
+        skin
+    }
+    /// Sets the number of preceding and following animation frames that are ghosted.
+    pub fn with_range(mut self, before: usize, after: usize) -> Self {
+        self.before = before;
+        self.after = after;
+        self.update();
+        self
+    }
+    /// Sets the opacity of the nearest ghosts. Ghosts further away fade out linearly.
+    pub fn with_opacity(mut self, opacity: f64) -> Self {
+        self.opacity = opacity.clamp(0., 1.);
+        self.update();
+        self
+    }
+    /// Returns the number of animation frames.
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+    /// Returns whether there are no animation frames.
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+    /// Adds content to an animation frame, returning an [Object] that updates both the animation
+    /// frame and its ghost.
+    ///
+    /// # Panics
+    /// Panics if the index is out of bounds.
+    pub fn add(&mut self, index: usize, content: Content) -> Box<dyn Object> {
+        // Ghosts are left untagged so that finding by tag yields only the objects themselves.
+        Box::new(GhostedObject {
+            ghost: self.ghosts[index].add(Content {
+                tag: None,
+                ..content.clone()
+            }),
+            object: self.frames[index].add(content),
+        })
+    }
+    /// Returns the [Layer] of an animation frame.
+    pub fn layer(&self, index: usize) -> Option<Box<dyn Layer>> {
+        self.frames.get(index).cloned()
+    }
+    /// Returns the index of the current animation frame.
+    pub fn get_current(&self) -> usize {
+        self.current
+    }
+    /// Sets the current animation frame. Indices beyond the last animation frame select the last.
+    pub fn set_current(&mut self, index: usize) {
+        self.current = index.min(self.len().saturating_sub(1));
+        self.update();
+    }
+    /// Returns whether ghosts are shown.
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+    /// Sets whether ghosts are shown, for instance so that they can be hidden during playback.
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+        self.update();
+    }
+    fn update(&mut self) {
+        let current = self.current;
+        for (index, layer) in self.frames.iter_mut().enumerate() {
+            layer.set_visible(index == current);
+        }
+        for (index, ghost) in self.ghosts.iter_mut().enumerate() {
+            let (distance, range) = if index < current {
+                (current - index, self.before)
+            } else {
+                (index - current, self.after)
+            };
+            let visible = self.enabled && distance > 0 && distance <= range;
+            ghost.set_visible(visible);
+            if visible {
+                ghost.set_opacity(self.opacity * (1. - (distance - 1) as f64 / range as f64));
+            }
+        }
+    }
+}
+
+impl Debug for OnionSkin {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OnionSkin")
+            .field("frames", &self.frames.len())
+            .field("current", &self.current)
+            .field("before", &self.before)
+            .field("after", &self.after)
+            .field("opacity", &self.opacity)
+            .field("enabled", &self.enabled)
+            .finish()
+    }
+}
+
+/// An [Object] in an animation frame that keeps its ghost in step with it.
+#[derive(Clone)]
+struct GhostedObject {
+    object: Box<dyn Object>,
+    ghost: Box<dyn Object>,
+}
+
+impl Object for GhostedObject {
+    fn apply_transform(&mut self, transform: Transform2) {
+        self.object.apply_transform(transform);
+        self.ghost.apply_transform(transform);
+    }
+    fn get_transform(&self) -> Transform2 {
+        self.object.get_transform()
+    }
+    fn set_transform(&mut self, transform: Transform2) {
+        self.object.set_transform(transform);
+        self.ghost.set_transform(transform);
+    }
+    fn get_depth(&self) -> u32 {
+        self.object.get_depth()
+    }
+    fn set_depth(&mut self, depth: u32) {
+        self.object.set_depth(depth);
+        self.ghost.set_depth(depth);
+    }
+    fn get_clip(&self) -> Option<Path> {
+        self.object.get_clip()
+    }
+    fn set_clip(&mut self, clip: Option<Path>) {
+        self.object.set_clip(clip.clone());
+        self.ghost.set_clip(clip);
+    }
+    fn get_blend(&self) -> Blend {
+        self.object.get_blend()
+    }
+    fn set_blend(&mut self, blend: Blend) {
+        self.object.set_blend(blend);
+        self.ghost.set_blend(blend);
+    }
+    fn get_cursor(&self) -> CursorIcon {
+        self.object.get_cursor()
+    }
+    fn set_cursor(&mut self, cursor: CursorIcon) {
+        // Ghosts aren't interactive, so they keep the default cursor.
+        self.object.set_cursor(cursor);
+    }
+    fn is_visible(&self) -> bool {
+        self.object.is_visible()
+    }
+    fn set_visible(&mut self, visible: bool) {
+        self.object.set_visible(visible);
+        self.ghost.set_visible(visible);
+    }
+    fn get_tag(&self) -> Option<String> {
+        self.object.get_tag()
+    }
+    fn set_tag(&mut self, tag: Option<String>) {
+        self.object.set_tag(tag);
+    }
+    fn get_content(&self) -> Content {
+        self.object.get_content()
+    }
+    fn update(&mut self, content: Rasterizable) {
+        self.object.update(content.clone());
+        self.ghost.update(content);
+    }
+    fn box_clone(&self) -> Box<dyn Object> {
+        Box::new(self.clone())
+    }
+    fn as_any(&self) -> Box<dyn Any> {
+        Box::new(self.clone())
+    }
+}
@@ -0,0 +1,298 @@
+use crate::graphics::{
+    canvas::{Blend, Content, CursorIcon, Frame, Layer, Object},
+    path::Path,
+    selection::{Selection, SelectionArea},
+    LDRColor,
+};
+
+use serde::{Deserialize, Serialize};
+
+use std::iter;
+
+/// An [Object] of a [Scene], along with the state of the [Object] that isn't part of its
+/// [Content].
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SceneObject {
+    /// The content of the object, with its transform, depth, clip and tag.
+    pub content: Content,
+    /// How the object is combined with the content beneath it.
+    pub blend: Blend,
+    /// The cursor shown while the pointer is over the object.
+    pub cursor: CursorIcon,
+    /// Whether the object is drawn.
+    pub visible: bool,
+}
+
+impl SceneObject {
+    fn capture(object: &dyn Object) -> SceneObject {
+        SceneObject {
+            content: object.get_content(),
+            blend: object.get_blend(),
+            cursor: object.get_cursor(),
+            visible: object.is_visible(),
+        }
+    }
+    fn restore(&self, object: &mut dyn Object) {
+        let content = self.content.clone();
+        object.update(content.content);
+        object.set_transform(content.transform);
+        object.set_depth(content.depth);
+        object.set_clip(content.clip);
+        object.set_tag(content.tag);
+        object.set_blend(self.blend);
+        object.set_cursor(self.cursor);
+        object.set_visible(self.visible);
+    }
+    fn load(&self, mut object: Box<dyn Object>) -> Box<dyn Object> {
+        object.set_blend(self.blend);
+        object.set_cursor(self.cursor);
+        object.set_visible(self.visible);
+        object
+    }
+}
+
+/// A [Layer] of a [Scene].
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SceneLayer {
+    /// The opacity the layer is composited with.
+    pub opacity: f64,
+    /// How the layer is combined with the content beneath it.
+    pub blend: Blend,
+    /// Whether the layer is drawn.
+    pub visible: bool,
+    /// Whether the layer is locked.
+    pub locked: bool,
+    /// Whether the layer is [static](Layer::set_static).
+    pub fixed: bool,
+    /// The objects of the layer added before any selection was made, in the order they were
+    /// added.
+    pub objects: Vec<SceneObject>,
+    /// The objects added after each change of selection, as given by [Layer::groups].
+    #[serde(default)]
+    pub groups: Vec<SceneGroup>,
+}
+
+impl SceneLayer {
+    fn capture(layer: &dyn Layer) -> SceneLayer {
+        let capture = |objects: Vec<Box<dyn Object>>| -> Vec<SceneObject> {
+            objects
+                .iter()
+                .map(|object| SceneObject::capture(object.as_ref()))
+                .collect()
+        };
+        let mut groups = layer.groups().into_iter();
+        let objects = groups
+            .next()
+            .map(|(_, objects)| capture(objects))
+            .unwrap_or_default();
+        SceneLayer {
+            opacity: layer.get_opacity(),
+            blend: layer.get_blend(),
+            visible: layer.is_visible(),
+            locked: layer.is_locked(),
+            fixed: layer.is_static(),
+            objects,
+            groups: groups
+                .map(|(selection, objects)| SceneGroup {
+                    selection,
+                    objects: capture(objects),
+                })
+                .collect(),
+        }
+    }
+    /// Returns the number of objects of the layer.
+    fn count(&self) -> usize {
+        self.objects.len()
+            + self
+                .groups
+                .iter()
+                .map(|group| group.objects.len())
+                .sum::<usize>()
+    }
+    fn apply(&self, layer: &mut dyn Layer) {
+        layer.set_opacity(self.opacity);
+        layer.set_blend(self.blend);
+        layer.set_visible(self.visible);
+        layer.set_locked(self.locked);
+        layer.set_static(self.fixed);
+    }
+    fn load(&self, layer: &mut dyn Layer) -> Vec<Box<dyn Object>> {
+        self.apply(layer);
+        let mut objects: Vec<Box<dyn Object>> = self
+            .objects
+            .iter()
+            .map(|object| object.load(layer.add(object.content.clone())))
+            .collect();
+        for group in &self.groups {
+            // Masks are placed where they were made, so selecting one always succeeds.
+            match &group.selection {
+                Some(selection) => {
+                    layer.select(SelectionArea::Mask(selection.clone()));
+                }
+                None => layer.clear_selection(),
+            }
+            objects.extend(
+                group
+                    .objects
+                    .iter()
+                    .map(|object| object.load(layer.add(object.content.clone()))),
+            );
+        }
+        objects
+    }
+    fn restore(&self, layer: &mut dyn Layer) {
+        let existing = layer.groups();
+        // Objects can only be added to the last group, so the earlier ones must line up already.
+        let last = existing.len() - 1;
+        let aligned = existing.len() == self.groups.len() + 1
+            && existing
+                .iter()
+                .enumerate()
+                .all(|(index, (selection, objects))| {
+                    let (expected, scene_objects) = match index {
+                        0 => (&None, &self.objects),
+                        _ => {
+                            let group = &self.groups[index - 1];
+                            (&group.selection, &group.objects)
+                        }
+                    };
+                    selection == expected && (index == last || objects.len() == scene_objects.len())
+                });
+        if !aligned {
+            layer.clear();
+            self.load(layer);
+            return;
+        }
+        self.apply(layer);
+        let scene_groups =
+            iter::once(&self.objects).chain(self.groups.iter().map(|group| &group.objects));
+        for ((_, objects), scene_objects) in existing.into_iter().zip(scene_groups) {
+            let left = restore_objects(scene_objects, objects, |content| layer.add(content));
+            for object in left {
+                layer.remove(object.as_ref());
+            }
+        }
+    }
+}
+
+/// The objects of a [SceneLayer] added after a change of its selection.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SceneGroup {
+    /// The selection the objects are confined to, or [None] if the selection was cleared.
+    pub selection: Option<Selection>,
+    /// The objects in the order they were added.
+    pub objects: Vec<SceneObject>,
+}
+
+/// A description of the contents of a [Frame] that can be saved with serde, such as to a file
+/// for an asset pipeline or to compare against a snapshot in a test, and loaded into a [Frame]
+/// again.
+///
+/// [Extension](super::Extension) content is saved as a still image of its rasterization, and the
+/// size and viewport of the [Frame] are left to whoever shows it. The selections of layers are
+/// saved along with the objects added within them, and are applied again when loaded.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Scene {
+    /// The color the frame is cleared to.
+    pub background: LDRColor,
+    /// The clip path of the frame.
+    pub clip: Option<Path>,
+    /// The objects added directly to the frame in the order they were added.
+    pub objects: Vec<SceneObject>,
+    /// The layers of the frame in the order they were added.
+    pub layers: Vec<SceneLayer>,
+}
+
+impl Scene {
+    /// Captures the current contents of a [Frame] and its layers.
+    pub fn capture(frame: &dyn Frame) -> Scene {
+        let layers: Vec<SceneLayer> = frame
+            .layers()
+            .iter()
+            .map(|layer| SceneLayer::capture(layer.as_ref()))
+            .collect();
+        // The children of a frame are its own objects followed by those of its layers, which can
+        // outnumber them if the layers change while they're captured.
+        let layered: usize = layers.iter().map(SceneLayer::count).sum();
+        let mut children = frame.children();
+        children.truncate(children.len().saturating_sub(layered));
+        Scene {
+            background: frame.get_background(),
+            clip: frame.get_clip(),
+            objects: children
+                .iter()
+                .map(|object| SceneObject::capture(object.as_ref()))
+                .collect(),
+            layers,
+        }
+    }
+    /// Adds the objects of the scene to a [Frame], along with a new [Layer] for each of its layers,
+    /// and sets the background and clip of the [Frame]. Returns the objects added in the order of
+    /// [Frame::children], such as for finding those to animate.
+    pub fn load(&self, frame: &mut dyn Frame) -> Vec<Box<dyn Object>> {
+        frame.set_background(self.background);
+        frame.set_clip(self.clip.clone());
+        let mut objects: Vec<Box<dyn Object>> = self
+            .objects
+            .iter()
+            .map(|object| object.load(frame.add(object.content.clone())))
+            .collect();
+        for scene_layer in &self.layers {
+            let mut layer: Box<dyn Layer> = frame.add_layer();
+            objects.extend(scene_layer.load(layer.as_mut()));
+        }
+        objects
+    }
+    /// Makes the contents of a [Frame] match the scene, updating its existing objects and layers in
+    /// place where they line up with those of the scene, adding any more that are needed and
+    /// removing or hiding those left over. Because objects are kept rather than replaced, the
+    /// [Frame] can be [advanced](Frame::advance) between two scenes to interpolate from the
+    /// transforms of one to the other.
+    pub fn restore(&self, frame: &mut dyn Frame) {
+        frame.set_background(self.background);
+        frame.set_clip(self.clip.clone());
+        let mut layers = frame.layers();
+        while layers.len() < self.layers.len() {
+            layers.push(frame.add_layer());
+        }
+        let layered: usize = layers.iter().map(|layer| layer.children().len()).sum();
+        let mut children = frame.children();
+        children.truncate(children.len().saturating_sub(layered));
+        let left = restore_objects(&self.objects, children, |content| frame.add(content));
+        for object in left {
+            frame.remove(object.as_ref());
+        }
+        for (index, layer) in layers.iter_mut().enumerate() {
+            match self.layers.get(index) {
+                Some(scene_layer) => scene_layer.restore(layer.as_mut()),
+                // Layers can't be removed from a frame, so those left over are emptied and hidden.
+                None => {
+                    layer.clear();
+                    layer.set_visible(false);
+                }
+            }
+        }
+    }
+}
+
+/// Updates existing objects in order to match those of a scene, adding any more that are needed
+/// and returning those left over.
+fn restore_objects<F>(
+    objects: &[SceneObject],
+    existing: Vec<Box<dyn Object>>,
+    mut add: F,
+) -> Vec<Box<dyn Object>>
+where
+    F: FnMut(Content) -> Box<dyn Object>,
+{
+    let mut existing = existing.into_iter();
+    for object in objects {
+        match existing.next() {
+            Some(mut current) => object.restore(current.as_mut()),
+            None => {
+                object.load(add(object.content.clone()));
+            }
+        }
+    }
+    existing.collect()
+}
@@ -0,0 +1,635 @@
+use crate::errors::Error;
+use crate::graphics::LDRColor;
+
+use std::sync::atomic::{AtomicU8, Ordering};
+
+/// A color space in which colors are interpolated.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ColorSpace {
+    /// Gamma-encoded sRGB, as used by CSS and the rendering backends for gradients.
+    Srgb,
+    /// Linear-light sRGB, in which blends are physically accurate but midpoints appear light.
+    LinearLight,
+    /// The perceptually uniform Oklab space, which keeps lightness and hue even across a blend.
+    Oklab,
+}
+
+impl Default for ColorSpace {
+    fn default() -> Self {
+        ColorSpace::Srgb
+    }
+}
+
+static COMPOSITING: AtomicU8 = AtomicU8::new(0);
+
+/// The color space in which rendered content is blended and gradients are interpolated.
+///
+/// The setting is global, and backends apply it wherever they have control over compositing.
+/// Blending in [Compositing::LinearLight] avoids the dark bands that sRGB blending leaves in the
+/// middle of gradients and along antialiased edges.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Compositing {
+    /// Content is blended in gamma-encoded sRGB, as on the web. This is the default.
+    Srgb,
+    /// Content is blended in linear light and converted to sRGB for display.
+    LinearLight,
+}
+
+impl Compositing {
+    /// Returns the current global compositing space.
+    pub fn current() -> Compositing {
+        match COMPOSITING.load(Ordering::Relaxed) {
+            1 => Compositing::LinearLight,
+            _ => Compositing::Srgb,
+        }
+    }
+    /// Sets the global compositing space. Native windows choose their framebuffer when they are
+    /// created, so this should be set before a canvas is started.
+    pub fn set_current(compositing: Compositing) {
+        COMPOSITING.store(
+            match compositing {
+                Compositing::Srgb => 0,
+                Compositing::LinearLight => 1,
+            },
+            Ordering::Relaxed,
+        );
+        super::settings_changed();
+    }
+    /// Returns the [ColorSpace] in which colors are interpolated.
+    pub fn space(self) -> ColorSpace {
+        match self {
+            Compositing::Srgb => ColorSpace::Srgb,
+            Compositing::LinearLight => ColorSpace::LinearLight,
+        }
+    }
+}
+
+impl Default for Compositing {
+    fn default() -> Self {
+        Compositing::Srgb
+    }
+}
+
+/// The CSS named colors, sorted by name.
+const NAMED: [(&str, u32); 148] = [
+    ("aliceblue", 0x00f0_f8ff),
+    ("antiquewhite", 0x00fa_ebd7),
+    ("aqua", 0x0000_ffff),
+    ("aquamarine", 0x007f_ffd4),
+    ("azure", 0x00f0_ffff),
+    ("beige", 0x00f5_f5dc),
+    ("bisque", 0x00ff_e4c4),
+    ("black", 0x0000_0000),
+    ("blanchedalmond", 0x00ff_ebcd),
+    ("blue", 0x0000_00ff),
+    ("blueviolet", 0x008a_2be2),
+    ("brown", 0x00a5_2a2a),
+    ("burlywood", 0x00de_b887),
+    ("cadetblue", 0x005f_9ea0),
+    ("chartreuse", 0x007f_ff00),
+    ("chocolate", 0x00d2_691e),
+    ("coral", 0x00ff_7f50),
+    ("cornflowerblue", 0x0064_95ed),
+    ("cornsilk", 0x00ff_f8dc),
+    ("crimson", 0x00dc_143c),
+    ("cyan", 0x0000_ffff),
+    ("darkblue", 0x0000_008b),
+    ("darkcyan", 0x0000_8b8b),
+    ("darkgoldenrod", 0x00b8_860b),
+    ("darkgray", 0x00a9_a9a9),
+    ("darkgreen", 0x0000_6400),
+    ("darkgrey", 0x00a9_a9a9),
+    ("darkkhaki", 0x00bd_b76b),
+    ("darkmagenta", 0x008b_008b),
+    ("darkolivegreen", 0x0055_6b2f),
+    ("darkorange", 0x00ff_8c00),
+    ("darkorchid", 0x0099_32cc),
+    ("darkred", 0x008b_0000),
+    ("darksalmon", 0x00e9_967a),
+    ("darkseagreen", 0x008f_bc8f),
+    ("darkslateblue", 0x0048_3d8b),
+    ("darkslategray", 0x002f_4f4f),
+    ("darkslategrey", 0x002f_4f4f),
+    ("darkturquoise", 0x0000_ced1),
+    ("darkviolet", 0x0094_00d3),
+    ("deeppink", 0x00ff_1493),
+    ("deepskyblue", 0x0000_bfff),
+    ("dimgray", 0x0069_6969),
+    ("dimgrey", 0x0069_6969),
+    ("dodgerblue", 0x001e_90ff),
+    ("firebrick", 0x00b2_2222),
+    ("floralwhite", 0x00ff_faf0),
+    ("forestgreen", 0x0022_8b22),
+    ("fuchsia", 0x00ff_00ff),
+    ("gainsboro", 0x00dc_dcdc),
+    ("ghostwhite", 0x00f8_f8ff),
+    ("gold", 0x00ff_d700),
+    ("goldenrod", 0x00da_a520),
+    ("gray", 0x0080_8080),
+    ("green", 0x0000_8000),
+    ("greenyellow", 0x00ad_ff2f),
+    ("grey", 0x0080_8080),
+    ("honeydew", 0x00f0_fff0),
+    ("hotpink", 0x00ff_69b4),
+    ("indianred", 0x00cd_5c5c),
+    ("indigo", 0x004b_0082),
+    ("ivory", 0x00ff_fff0),
+    ("khaki", 0x00f0_e68c),
+    ("lavender", 0x00e6_e6fa),
+    ("lavenderblush", 0x00ff_f0f5),
+    ("lawngreen", 0x007c_fc00),
+    ("lemonchiffon", 0x00ff_facd),
+    ("lightblue", 0x00ad_d8e6),
+    ("lightcoral", 0x00f0_8080),
+    ("lightcyan", 0x00e0_ffff),
+    ("lightgoldenrodyellow", 0x00fa_fad2),
+    ("lightgray", 0x00d3_d3d3),
+    ("lightgreen", 0x0090_ee90),
+    ("lightgrey", 0x00d3_d3d3),
+    ("lightpink", 0x00ff_b6c1),
+    ("lightsalmon", 0x00ff_a07a),
+    ("lightseagreen", 0x0020_b2aa),
+    ("lightskyblue", 0x0087_cefa),
+    ("lightslategray", 0x0077_8899),
+    ("lightslategrey", 0x0077_8899),
+    ("lightsteelblue", 0x00b0_c4de),
+    ("lightyellow", 0x00ff_ffe0),
+    ("lime", 0x0000_ff00),
+    ("limegreen", 0x0032_cd32),
+    ("linen", 0x00fa_f0e6),
+    ("magenta", 0x00ff_00ff),
+    ("maroon", 0x0080_0000),
+    ("mediumaquamarine", 0x0066_cdaa),
+    ("mediumblue", 0x0000_00cd),
+    ("mediumorchid", 0x00ba_55d3),
+    ("mediumpurple", 0x0093_70db),
+    ("mediumseagreen", 0x003c_b371),
+    ("mediumslateblue", 0x007b_68ee),
+    ("mediumspringgreen", 0x0000_fa9a),
+    ("mediumturquoise", 0x0048_d1cc),
+    ("mediumvioletred", 0x00c7_1585),
+    ("midnightblue", 0x0019_1970),
+    ("mintcream", 0x00f5_fffa),
+    ("mistyrose", 0x00ff_e4e1),
+    ("moccasin", 0x00ff_e4b5),
+    ("navajowhite", 0x00ff_dead),
+    ("navy", 0x0000_0080),
+    ("oldlace", 0x00fd_f5e6),
+    ("olive", 0x0080_8000),
+    ("olivedrab", 0x006b_8e23),
+    ("orange", 0x00ff_a500),
+    ("orangered", 0x00ff_4500),
+    ("orchid", 0x00da_70d6),
+    ("palegoldenrod", 0x00ee_e8aa),
+    ("palegreen", 0x0098_fb98),
+    ("paleturquoise", 0x00af_eeee),
+    ("palevioletred", 0x00db_7093),
+    ("papayawhip", 0x00ff_efd5),
+    ("peachpuff", 0x00ff_dab9),
+    ("peru", 0x00cd_853f),
+    ("pink", 0x00ff_c0cb),
+    ("plum", 0x00dd_a0dd),
+    ("powderblue", 0x00b0_e0e6),
+    ("purple", 0x0080_0080),
+    ("rebeccapurple", 0x0066_3399),
+    ("red", 0x00ff_0000),
+    ("rosybrown", 0x00bc_8f8f),
+    ("royalblue", 0x0041_69e1),
+    ("saddlebrown", 0x008b_4513),
+    ("salmon", 0x00fa_8072),
+    ("sandybrown", 0x00f4_a460),
+    ("seagreen", 0x002e_8b57),
+    ("seashell", 0x00ff_f5ee),
+    ("sienna", 0x00a0_522d),
+    ("silver", 0x00c0_c0c0),
+    ("skyblue", 0x0087_ceeb),
+    ("slateblue", 0x006a_5acd),
+    ("slategray", 0x0070_8090),
+    ("slategrey", 0x0070_8090),
+    ("snow", 0x00ff_fafa),
+    ("springgreen", 0x0000_ff7f),
+    ("steelblue", 0x0046_82b4),
+    ("tan", 0x00d2_b48c),
+    ("teal", 0x0000_8080),
+    ("thistle", 0x00d8_bfd8),
+    ("tomato", 0x00ff_6347),
+    ("turquoise", 0x0040_e0d0),
+    ("violet", 0x00ee_82ee),
+    ("wheat", 0x00f5_deb3),
+    ("white", 0x00ff_ffff),
+    ("whitesmoke", 0x00f5_f5f5),
+    ("yellow", 0x00ff_ff00),
+    ("yellowgreen", 0x009a_cd32),
+];
+
+impl LDRColor {
+    /// Parses a color from a hex string in the form `#rrggbb` or `#rrggbbaa`, or the shorthand
+    /// `#rgb` or `#rgba`. The leading `#` is optional.
+    pub fn from_hex(input: &str) -> Result<LDRColor, Error> {
+        let digits = input.trim();
+        let digits = digits.strip_prefix('#').unwrap_or(digits);
+        if !digits.bytes().all(|digit| digit.is_ascii_hexdigit()) {
+            return Err(Error::color_parse());
+        }
+        let channel = |index: usize, width: usize| {
+            let value = u8::from_str_radix(&digits[index * width..(index + 1) * width], 16)
+                .map_err(|_| Error::color_parse())?;
+            Ok(if width == 1 { value * 17 } else { value })
+        };
+        let width = match digits.len() {
+            3 | 4 => 1,
+            6 | 8 => 2,
+            _ => return Err(Error::color_parse()),
+        };
+        Ok(LDRColor {
+            r: channel(0, width)?,
+            g: channel(1, width)?,
+            b: channel(2, width)?,
+            a: if digits.len() == 4 * width {
+                channel(3, width)?
+            } else {
+                255
+            },
+        })
+    }
+    /// Parses a color from a CSS color string: a hex color, a named color, `transparent`, or an
+    /// `rgb()`, `rgba()`, `hsl()` or `hsla()` function with either comma or space separated
+    /// arguments.
+    pub fn from_css(input: &str) -> Result<LDRColor, Error> {
+        let input = input.trim().to_ascii_lowercase();
+        if input.starts_with('#') {
+            return LDRColor::from_hex(&input);
+        }
+        if input == "transparent" {
+            return Ok(LDRColor::rgba(0, 0, 0, 0));
+        }
+        if let Ok(index) = NAMED.binary_search_by(|(name, _)| (*name).cmp(&input)) {
+            let value = NAMED[index].1;
+            return Ok(LDRColor::rgb(
+                (value >> 16) as u8,
+                (value >> 8) as u8,
+                value as u8,
+            ));
+        }
+        let open = input.find('(').ok_or_else(Error::color_parse)?;
+        if !input.ends_with(')') {
+            return Err(Error::color_parse());
+        }
+        let (function, arguments) = (input[..open].trim(), &input[open + 1..input.len() - 1]);
+        let (channels, alpha) = split_arguments(arguments)?;
+        let alpha = match alpha {
+            Some(alpha) => to_byte(unit_value(alpha)?),
+            None => 255,
+        };
+        match function {
+            "rgb" | "rgba" => {
+                let channel = |argument: &str| {
+                    Ok(to_byte(match argument.strip_suffix('%') {
+                        Some(percentage) => parse_number(percentage)? / 100.,
+                        None => parse_number(argument)? / 255.,
+                    }))
+                };
+                Ok(LDRColor::rgba(
+                    channel(channels[0])?,
+                    channel(channels[1])?,
+                    channel(channels[2])?,
+                    alpha,
+                ))
+            }
+            "hsl" | "hsla" => {
+                let (r, g, b) = hsl_to_rgb(
+                    parse_hue(channels[0])?,
+                    parse_percentage(channels[1])?,
+                    parse_percentage(channels[2])?,
+                );
+                Ok(LDRColor::rgba(to_byte(r), to_byte(g), to_byte(b), alpha))
+            }
+            _ => Err(Error::color_parse()),
+        }
+    }
+    /// Creates a fully opaque color from a hue in degrees and a saturation and lightness between
+    /// zero and one.
+    pub fn hsl(hue: f64, saturation: f64, lightness: f64) -> LDRColor {
+        let (r, g, b) = hsl_to_rgb(hue, saturation.clamp(0., 1.), lightness.clamp(0., 1.));
+        LDRColor::rgb(to_byte(r), to_byte(g), to_byte(b))
+    }
+    /// Creates a fully opaque color from a hue in degrees and a saturation and value between zero
+    /// and one.
+    pub fn hsv(hue: f64, saturation: f64, value: f64) -> LDRColor {
+        let (saturation, value) = (saturation.clamp(0., 1.), value.clamp(0., 1.));
+        let channel = |offset: f64| {
+            let k = (offset + hue / 60.).rem_euclid(6.);
+            value - value * saturation * k.min(4. - k).clamp(0., 1.)
+        };
+        LDRColor::rgb(
+            to_byte(channel(5.)),
+            to_byte(channel(3.)),
+            to_byte(channel(1.)),
+        )
+    }
+    /// Returns the hue in degrees between zero and 360 and the saturation and lightness between
+    /// zero and one of the color.
+    pub fn to_hsl(&self) -> (f64, f64, f64) {
+        let (hue, max, min) = self.hue();
+        let lightness = (max + min) / 2.;
+        let saturation = if max == min {
+            0.
+        } else {
+            (max - min) / (1. - (2. * lightness - 1.).abs())
+        };
+        (hue, saturation, lightness)
+    }
+    /// Returns the hue in degrees between zero and 360 and the saturation and value between zero
+    /// and one of the color.
+    pub fn to_hsv(&self) -> (f64, f64, f64) {
+        let (hue, max, min) = self.hue();
+        let saturation = if max == 0. { 0. } else { (max - min) / max };
+        (hue, saturation, max)
+    }
+    /// Increases the HSL lightness of the color by the provided amount between zero and one.
+    pub fn lighten(self, amount: f64) -> LDRColor {
+        let (hue, saturation, lightness) = self.to_hsl();
+        LDRColor::hsl(hue, saturation, lightness + amount).with_alpha(self.a)
+    }
+    /// Decreases the HSL lightness of the color by the provided amount between zero and one.
+    pub fn darken(self, amount: f64) -> LDRColor {
+        self.lighten(-amount)
+    }
+    /// Increases the HSL saturation of the color by the provided amount between zero and one.
+    /// Negative amounts desaturate the color.
+    pub fn saturate(self, amount: f64) -> LDRColor {
+        let (hue, saturation, lightness) = self.to_hsl();
+        LDRColor::hsl(hue, saturation + amount, lightness).with_alpha(self.a)
+    }
+    /// Interpolates between this color, at an amount of zero, and another, at an amount of one, in
+    /// sRGB. Alpha is interpolated linearly and the color channels are weighted by it, so blending
+    /// towards a transparent color doesn't darken.
+    pub fn lerp(&self, other: &LDRColor, amount: f64) -> LDRColor {
+        self.lerp_in(other, amount, ColorSpace::Srgb)
+    }
+    /// Interpolates between this color and another in the provided [ColorSpace].
+    pub fn lerp_in(&self, other: &LDRColor, amount: f64, space: ColorSpace) -> LDRColor {
+        let (from, to) = (self.channels_in(space), other.channels_in(space));
+        let (from_alpha, to_alpha) = (f64::from(self.a) / 255., f64::from(other.a) / 255.);
+        let alpha = from_alpha + (to_alpha - from_alpha) * amount;
+        let mut channels = [0.; 3];
+        for (index, channel) in channels.iter_mut().enumerate() {
+            let (from, to) = (from[index] * from_alpha, to[index] * to_alpha);
+            let premultiplied = from + (to - from) * amount;
+            *channel = if alpha > 0. {
+                premultiplied / alpha
+            } else {
+                0.
+            };
+        }
+        LDRColor::from_space(channels, space).with_alpha(to_byte(alpha))
+    }
+    /// Returns the color channels of the color in the provided [ColorSpace].
+    fn channels_in(&self, space: ColorSpace) -> [f64; 3] {
+        let srgb = [
+            f64::from(self.r) / 255.,
+            f64::from(self.g) / 255.,
+            f64::from(self.b) / 255.,
+        ];
+        match space {
+            ColorSpace::Srgb => srgb,
+            ColorSpace::LinearLight => [to_linear(srgb[0]), to_linear(srgb[1]), to_linear(srgb[2])],
+            ColorSpace::Oklab => {
+                let (r, g, b) = (to_linear(srgb[0]), to_linear(srgb[1]), to_linear(srgb[2]));
+                let l = (0.412_221_470_8 * r + 0.536_332_536_3 * g + 0.051_445_992_9 * b).cbrt();
+                let m = (0.211_903_498_2 * r + 0.680_699_545_1 * g + 0.107_396_956_6 * b).cbrt();
+                let s = (0.088_302_461_9 * r + 0.281_718_837_6 * g + 0.629_978_700_5 * b).cbrt();
+                [
+                    0.210_454_255_3 * l + 0.793_617_785 * m - 0.004_072_046_8 * s,
+                    1.977_998_495_1 * l - 2.428_592_205 * m + 0.450_593_709_9 * s,
+                    0.025_904_037_1 * l + 0.782_771_766_2 * m - 0.808_675_766 * s,
+                ]
+            }
+        }
+    }
+    /// Creates a fully opaque color from color channels in the provided [ColorSpace].
+    fn from_space(channels: [f64; 3], space: ColorSpace) -> LDRColor {
+        let [r, g, b] = match space {
+            ColorSpace::Srgb => channels,
+            ColorSpace::LinearLight => [
+                from_linear(channels[0]),
+                from_linear(channels[1]),
+                from_linear(channels[2]),
+            ],
+            ColorSpace::Oklab => {
+                let [lightness, a, b] = channels;
+                let l = (lightness + 0.396_337_777_4 * a + 0.215_803_757_3 * b).powi(3);
+                let m = (lightness - 0.105_561_345_8 * a - 0.063_854_172_8 * b).powi(3);
+                let s = (lightness - 0.089_484_177_5 * a - 1.291_485_548 * b).powi(3);
+                [
+                    from_linear(4.076_741_662_1 * l - 3.307_711_591_3 * m + 0.230_969_929_2 * s),
+                    from_linear(-1.268_438_004_6 * l + 2.609_757_401_1 * m - 0.341_319_396_5 * s),
+                    from_linear(-0.004_196_086_3 * l - 0.703_418_614_7 * m + 1.707_614_701 * s),
+                ]
+            }
+        };
+        LDRColor::rgb(to_byte(r), to_byte(g), to_byte(b))
+    }
+    /// Returns the hue in degrees and the largest and smallest channels between zero and one.
+    fn hue(&self) -> (f64, f64, f64) {
+        let (r, g, b) = (
+            f64::from(self.r) / 255.,
+            f64::from(self.g) / 255.,
+            f64::from(self.b) / 255.,
+        );
+        let (max, min) = (r.max(g).max(b), r.min(g).min(b));
+        let delta = max - min;
+        let hue = if delta == 0. {
+            0.
+        } else if max == r {
+            ((g - b) / delta).rem_euclid(6.)
+        } else if max == g {
+            (b - r) / delta + 2.
+        } else {
+            (r - g) / delta + 4.
+        };
+        (hue * 60., max, min)
+    }
+}
+
+/// Splits the arguments of a CSS color function into three channels and an optional alpha.
+fn split_arguments(arguments: &str) -> Result<([&str; 3], Option<&str>), Error> {
+    let (channels, alpha) = if arguments.contains(',') {
+        let mut arguments: Vec<&str> = arguments.split(',').map(str::trim).collect();
+        let alpha = if arguments.len() == 4 {
+            arguments.pop()
+        } else {
+            None
+        };
+        (arguments, alpha)
+    } else {
+        let mut parts = arguments.splitn(2, '/');
+        let channels = parts.next().unwrap().split_whitespace().collect();
+        (channels, parts.next().map(str::trim))
+    };
+    match channels.as_slice() {
+        [first, second, third] => Ok(([first, second, third], alpha)),
+        _ => Err(Error::color_parse()),
+    }
+}
+
+fn parse_number(input: &str) -> Result<f64, Error> {
+    input
+        .trim()
+        .parse::<f64>()
+        .ok()
+        .filter(|value| value.is_finite())
+        .ok_or_else(Error::color_parse)
+}
+
+fn parse_percentage(input: &str) -> Result<f64, Error> {
+    let percentage = input.strip_suffix('%').ok_or_else(Error::color_parse)?;
+    Ok((parse_number(percentage)? / 100.).clamp(0., 1.))
+}
+
+/// Parses an alpha value given either as a number between zero and one or as a percentage.
+fn unit_value(input: &str) -> Result<f64, Error> {
+    match input.strip_suffix('%') {
+        Some(percentage) => Ok(parse_number(percentage)? / 100.),
+        None => parse_number(input),
+    }
+}
+
+/// Parses a hue, returning it in degrees. Hues without a unit are in degrees.
+fn parse_hue(input: &str) -> Result<f64, Error> {
+    let units = [
+        ("deg", 1.),
+        ("grad", 0.9),
+        ("rad", 180. / std::f64::consts::PI),
+        ("turn", 360.),
+    ];
+    for (unit, scale) in units.iter() {
+        if let Some(value) = input.strip_suffix(unit) {
+            return Ok(parse_number(value)? * scale);
+        }
+    }
+    parse_number(input)
+}
+
+fn to_byte(value: f64) -> u8 {
+    (value.clamp(0., 1.) * 255.).round() as u8
+}
+
+/// Converts a gamma-encoded sRGB channel between zero and one to linear light.
+pub(crate) fn to_linear(value: f64) -> f64 {
+    if value <= 0.040_45 {
+        value / 12.92
+    } else {
+        ((value + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+/// Converts a linear-light channel between zero and one to gamma-encoded sRGB.
+pub(crate) fn from_linear(value: f64) -> f64 {
+    if value <= 0.003_130_8 {
+        value * 12.92
+    } else {
+        1.055 * value.powf(1. / 2.4) - 0.055
+    }
+}
+
+/// Converts a hue in degrees and saturation and lightness between zero and one into red, green
+/// and blue channels between zero and one.
+fn hsl_to_rgb(hue: f64, saturation: f64, lightness: f64) -> (f64, f64, f64) {
+    let channel = |offset: f64| {
+        let k = (offset + hue / 30.).rem_euclid(12.);
+        let a = saturation * lightness.min(1. - lightness);
+        lightness - a * (k - 3.).min(9. - k).clamp(-1., 1.)
+    };
+    (channel(0.), channel(8.), channel(4.))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ColorSpace;
+    use crate::graphics::LDRColor;
+
+    #[test]
+    fn parses_hex() {
+        assert_eq!(
+            LDRColor::from_hex("#ff8000").unwrap(),
+            LDRColor::rgb(255, 128, 0)
+        );
+        assert_eq!(
+            LDRColor::from_hex("ff800080").unwrap(),
+            LDRColor::rgba(255, 128, 0, 128)
+        );
+        assert_eq!(
+            LDRColor::from_hex("#f80").unwrap(),
+            LDRColor::rgb(255, 136, 0)
+        );
+        assert!(LDRColor::from_hex("#ff80").is_ok());
+        assert!(LDRColor::from_hex("#ff800").is_err());
+        assert!(LDRColor::from_hex("#gg8000").is_err());
+        assert!(LDRColor::from_hex("#+f+f+f").is_err());
+    }
+
+    #[test]
+    fn parses_css() {
+        let orange = LDRColor::rgb(255, 165, 0);
+        assert_eq!(LDRColor::from_css("Orange").unwrap(), orange);
+        assert_eq!(LDRColor::from_css("#ffa500").unwrap(), orange);
+        assert_eq!(LDRColor::from_css("rgb(255, 165, 0)").unwrap(), orange);
+        assert_eq!(LDRColor::from_css("rgb(100% 64.7% 0%)").unwrap(), orange);
+        assert_eq!(LDRColor::from_css("hsl(38.8, 100%, 50%)").unwrap(), orange);
+        assert_eq!(
+            LDRColor::from_css("rgba(255, 165, 0, 0.5)").unwrap(),
+            orange.with_alpha(128)
+        );
+        assert_eq!(
+            LDRColor::from_css("hsl(0.5turn 100% 25% / 50%)").unwrap(),
+            LDRColor::rgba(0, 128, 128, 128)
+        );
+        assert_eq!(
+            LDRColor::from_css("transparent").unwrap(),
+            LDRColor::rgba(0, 0, 0, 0)
+        );
+        assert!(LDRColor::from_css("rgb(255, 165)").is_err());
+        assert!(LDRColor::from_css("hsl(38, 100, 50)").is_err());
+        assert!(LDRColor::from_css("rgb(255, 165, 0").is_err());
+        assert!(LDRColor::from_css("notacolor").is_err());
+    }
+
+    #[test]
+    fn converts_hsl_and_hsv() {
+        let orange = LDRColor::rgb(255, 165, 0);
+        let (hue, saturation, lightness) = orange.to_hsl();
+        assert!((hue - 38.824).abs() < 1e-3);
+        assert_eq!((saturation, lightness), (1., 0.5));
+        assert_eq!(LDRColor::hsl(hue, saturation, lightness), orange);
+        let (hue, saturation, value) = orange.to_hsv();
+        assert_eq!((saturation, value), (1., 1.));
+        assert_eq!(LDRColor::hsv(hue, saturation, value), orange);
+        assert_eq!(LDRColor::hsv(240., 0.5, 0.5), LDRColor::rgb(64, 64, 128));
+        assert_eq!(
+            LDRColor::rgb(128, 0, 0).with_alpha(10).lighten(0.25),
+            LDRColor::rgba(255, 0, 0, 10)
+        );
+        assert_eq!(LDRColor::white().darken(1.5), LDRColor::black());
+        assert_eq!(
+            LDRColor::rgb(191, 64, 64).saturate(-1.),
+            LDRColor::rgb(128, 128, 128)
+        );
+    }
+
+    #[test]
+    fn interpolates_in_color_spaces() {
+        let (red, blue) = (LDRColor::rgb(255, 0, 0), LDRColor::rgb(0, 0, 255));
+        assert_eq!(red.lerp(&blue, 0.5), LDRColor::rgb(128, 0, 128));
+        assert_eq!(
+            red.lerp_in(&blue, 0.5, ColorSpace::LinearLight),
+            LDRColor::rgb(188, 0, 188)
+        );
+        assert_eq!(red.lerp_in(&blue, 0., ColorSpace::Oklab), red);
+        assert_eq!(red.lerp_in(&blue, 1., ColorSpace::Oklab), blue);
+        assert_eq!(
+            red.lerp(&LDRColor::rgba(0, 0, 0, 0), 0.5),
+            LDRColor::rgba(255, 0, 0, 128)
+        );
+    }
+}
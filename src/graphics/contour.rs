@@ -0,0 +1,331 @@
+use crate::graphics::{
+    path::{Builder, StyleHelper},
+    Vector2,
+};
+
+use std::collections::{HashMap, HashSet};
+
+/// Identifies a grid edge by the sample at its top or left end and whether it runs vertically.
+type Edge = (i64, i64, bool);
+
+/// A rectangular grid of scalar samples, such as the output of a simulation, from which isolines
+/// and filled bands between levels are traced using marching squares.
+///
+/// Samples are placed [ScalarField::with_spacing] pixels apart, with the first at the origin.
+#[derive(Clone, Debug)]
+pub struct ScalarField {
+    width: u32,
+    height: u32,
+    values: Vec<f64>,
+    spacing: f64,
+    smooth: bool,
+}
+
+impl ScalarField {
+    /// Creates a field from rows of samples of the provided width, in row-major order. A trailing
+    /// partial row is ignored.
+    pub fn new(width: u32, values: Vec<f64>) -> ScalarField {
+        let height = if width == 0 {
+            0
+        } else {
+            (values.len() / width as usize) as u32
+        };
+        ScalarField {
+            width,
+            height,
+            values,
+            spacing: 1.,
+            smooth: true,
+        }
+    }
+    /// Sets the distance, in pixels, between adjacent samples.
+    pub fn with_spacing(mut self, spacing: f64) -> Self {
+        self.spacing = spacing;
+        self
+    }
+    /// Sets whether traced contours are smoothed into curves. When disabled they are drawn as the
+    /// straight segments found by marching squares.
+    pub fn with_smoothing(mut self, smooth: bool) -> Self {
+        self.smooth = smooth;
+        self
+    }
+    /// Returns the size of the field in samples.
+    pub fn get_size(&self) -> Vector2 {
+        (f64::from(self.width), f64::from(self.height)).into()
+    }
+    /// Returns the sample at the provided position, or [None] outside of the field.
+    pub fn value(&self, x: u32, y: u32) -> Option<f64> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+        Some(self.values[(y * self.width + x) as usize])
+    }
+    /// Returns the isolines at which the field crosses a level as polylines, each with whether it
+    /// is closed. Lines are oriented consistently, with higher values to one side.
+    pub fn polylines(&self, level: f64) -> Vec<(Vec<Vector2>, bool)> {
+        self.trace(level, false)
+    }
+    /// Returns the isolines at which the field crosses a level, ready for stroking.
+    pub fn isoline(&self, level: f64) -> StyleHelper {
+        let mut builder = Builder::new();
+        for (points, closed) in self.polylines(level) {
+            builder = self.append(builder, &points, closed);
+        }
+        builder.done()
+    }
+    /// Returns the filled region in which the field is at least the lower level and below the
+    /// upper level, ready for filling. Pass [f64::INFINITY] as the upper level to fill everything
+    /// above the lower one, or [f64::NEG_INFINITY] as the lower level to fill everything below the
+    /// upper one. The band is empty unless the lower level is below the upper one.
+    pub fn band(&self, lower: f64, upper: f64) -> StyleHelper {
+        let mut builder = Builder::new();
+        if self.width == 0 || self.height == 0 || lower.is_nan() || upper.is_nan() || lower >= upper
+        {
+            return builder.done();
+        }
+        if lower == f64::NEG_INFINITY {
+            // Every sample is at least negative infinity, as is the padding that closes traced
+            // contours, so nothing would be traced and the region is instead the whole field.
+            let size = (self.get_size() - 1.) * self.spacing;
+            builder = builder
+                .move_to((0., 0.))
+                .line_to((size.x, 0.))
+                .line_to(size)
+                .line_to((0., size.y))
+                .close();
+        } else {
+            for (points, _) in self.trace(lower, true) {
+                builder = self.append(builder, &points, true);
+            }
+        }
+        // The region above the upper level is nested within the region above the lower one, so
+        // tracing its outline in the opposite direction cuts it out under either fill rule.
+        for (mut points, _) in self.trace(upper, true) {
+            points.reverse();
+            builder = self.append(builder, &points, true);
+        }
+        builder.done()
+    }
+    /// Returns a sample and its position, where padded fields surround the samples with a ring of
+    /// negative infinity coincident with their border so that every contour closes.
+    fn sample(&self, x: i64, y: i64, padded: bool) -> (f64, Vector2) {
+        let (x, y) = if padded { (x - 1, y - 1) } else { (x, y) };
+        let clamp = |value: i64, limit: u32| value.clamp(0, i64::from(limit) - 1);
+        let (clamped_x, clamped_y) = (clamp(x, self.width), clamp(y, self.height));
+        let value = if clamped_x == x && clamped_y == y {
+            self.values[(y * i64::from(self.width) + x) as usize]
+        } else {
+            f64::NEG_INFINITY
+        };
+        (
+            value,
+            Vector2::from((clamped_x as f64, clamped_y as f64)) * self.spacing,
+        )
+    }
+    fn trace(&self, level: f64, padded: bool) -> Vec<(Vec<Vector2>, bool)> {
+        if self.width == 0 || self.height == 0 || level.is_nan() || level == f64::INFINITY {
+            return vec![];
+        }
+        let padding = if padded { 2 } else { 0 };
+        let (width, height) = (
+            i64::from(self.width) + padding,
+            i64::from(self.height) + padding,
+        );
+        let mut points: HashMap<Edge, Vector2> = HashMap::new();
+        let mut next: HashMap<Edge, Edge> = HashMap::new();
+        for y in 0..height - 1 {
+            for x in 0..width - 1 {
+                // Corners and the edges leaving them, clockwise from the top left.
+                let corners = [(x, y), (x + 1, y), (x + 1, y + 1), (x, y + 1)];
+                let edges = [
+                    (x, y, false),
+                    (x + 1, y, true),
+                    (x, y + 1, false),
+                    (x, y, true),
+                ];
+                let samples: Vec<(f64, Vector2)> = corners
+                    .iter()
+                    .map(|&(x, y)| self.sample(x, y, padded))
+                    .collect();
+                let above: Vec<bool> = samples.iter().map(|(value, _)| *value >= level).collect();
+                let (mut falling, mut rising) = (vec![], vec![]);
+                for index in 0..4 {
+                    let following = (index + 1) % 4;
+                    if above[index] == above[following] {
+                        continue;
+                    }
+                    let ((from, start), (to, end)) = (samples[index], samples[following]);
+                    let amount = (level - from) / (to - from);
+                    let amount = if amount.is_finite() { amount } else { 0.5 };
+                    points.insert(edges[index], start + (end - start) * amount);
+                    if above[index] {
+                        falling.push(index);
+                    } else {
+                        rising.push(index);
+                    }
+                }
+                // Each segment runs from where the field falls below the level to where it
+                // rises above it, so that higher values are always on the same side. Saddles
+                // are resolved by the mean of the corners.
+                let pairs: Vec<(usize, usize)> = match (falling.len(), rising.len()) {
+                    (1, 1) => vec![(falling[0], rising[0])],
+                    (2, 2) => {
+                        let center = samples.iter().map(|(value, _)| value).sum::<f64>() / 4.;
+                        let step = if center >= level { 1 } else { 3 };
+                        falling
+                            .iter()
+                            .map(|&index| (index, (index + step) % 4))
+                            .collect()
+                    }
+                    _ => vec![],
+                };
+                for (from, to) in pairs {
+                    next.insert(edges[from], edges[to]);
+                }
+            }
+        }
+        let mut lines = vec![];
+        // Lines that begin at an edge no other line ends at are open, and the rest are loops.
+        let ends: HashSet<Edge> = next.values().cloned().collect();
+        let mut starts: Vec<Edge> = next
+            .keys()
+            .filter(|edge| !ends.contains(edge))
+            .cloned()
+            .collect();
+        starts.sort();
+        for start in starts {
+            lines.push((self.follow(&mut next, &points, start), false));
+        }
+        let mut remaining: Vec<Edge> = next.keys().cloned().collect();
+        remaining.sort();
+        for start in remaining {
+            if next.contains_key(&start) {
+                let mut line = self.follow(&mut next, &points, start);
+                line.pop();
+                lines.push((line, true));
+            }
+        }
+        lines
+            .into_iter()
+            .map(|(mut line, closed)| {
+                line.dedup_by(|a, b| (a.x - b.x).hypot(a.y - b.y) < 1e-9);
+                (line, closed)
+            })
+            .filter(|(line, _)| line.len() > 1)
+            .collect()
+    }
+    /// Removes and returns the points of the line beginning at an edge.
+    fn follow(
+        &self,
+        next: &mut HashMap<Edge, Edge>,
+        points: &HashMap<Edge, Vector2>,
+        start: Edge,
+    ) -> Vec<Vector2> {
+        let mut line = vec![points[&start]];
+        let mut edge = start;
+        while let Some(following) = next.remove(&edge) {
+            line.push(points[&following]);
+            edge = following;
+        }
+        line
+    }
+    /// Appends a polyline to a path, as Catmull-Rom curves when smoothing.
+    fn append(&self, mut builder: Builder, points: &[Vector2], closed: bool) -> Builder {
+        builder = builder.move_to(points[0]);
+        let count = points.len();
+        let segments = if closed { count } else { count - 1 };
+        for index in 0..segments {
+            let to = points[(index + 1) % count];
+            if !self.smooth {
+                builder = builder.line_to(to);
+                continue;
+            }
+            let point = |offset: i64| {
+                let index = index as i64 + offset;
+                if closed {
+                    points[index.rem_euclid(count as i64) as usize]
+                } else {
+                    points[index.clamp(0, count as i64 - 1) as usize]
+                }
+            };
+            let (before, from, after) = (point(-1), point(0), point(2));
+            builder = builder.cubic_to(to, from + (to - before) / 6., to - (after - from) / 6.);
+        }
+        if closed {
+            builder = builder.close();
+        }
+        builder
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graphics::path::Segment;
+
+    fn peak() -> ScalarField {
+        ScalarField::new(3, vec![0., 0., 0., 0., 1., 0., 0., 0., 0.]).with_smoothing(false)
+    }
+
+    #[test]
+    fn isolines_surround_peaks() {
+        let lines = peak().polylines(0.5);
+        assert_eq!(lines.len(), 1);
+        let (points, closed) = &lines[0];
+        assert!(closed);
+        assert_eq!(points.len(), 4);
+        for point in points {
+            assert!(((point.x - 1.).hypot(point.y - 1.) - 0.5).abs() < 1e-9);
+        }
+        assert!(peak().polylines(2.).is_empty());
+        let ramp = ScalarField::new(3, vec![0., 1., 2., 0., 1., 2.]).with_spacing(10.);
+        let lines = ramp.polylines(1.5);
+        assert_eq!(lines.len(), 1);
+        assert!(!lines[0].1);
+        assert_eq!(
+            lines[0].0,
+            vec![Vector2::from((15., 10.)), Vector2::from((15., 0.))]
+        );
+    }
+
+    #[test]
+    fn bands_cut_out_higher_levels() {
+        let field = peak();
+        let above = field.band(0.5, f64::INFINITY).finalize();
+        assert!((above.signed_area().abs() - 0.5).abs() < 1e-9);
+        let whole = field.band(-1., f64::INFINITY).finalize();
+        assert!((whole.signed_area().abs() - 4.).abs() < 1e-9);
+        let band = field.band(-1., 0.5).finalize();
+        assert!((band.signed_area().abs() - 3.5).abs() < 1e-9);
+        assert!(field.band(2., 3.).finalize().segments.is_empty());
+        let below = field.band(f64::NEG_INFINITY, 0.5).finalize();
+        assert!((below.signed_area() - band.signed_area()).abs() < 1e-9);
+        let everything = field.band(f64::NEG_INFINITY, f64::INFINITY).finalize();
+        assert!((everything.signed_area() - whole.signed_area()).abs() < 1e-9);
+        assert!(field
+            .band(0.5, f64::NEG_INFINITY)
+            .finalize()
+            .segments
+            .is_empty());
+        assert!(field.band(0.5, 0.5).finalize().segments.is_empty());
+        assert!(field.band(f64::NAN, 1.).finalize().segments.is_empty());
+    }
+
+    #[test]
+    fn smoothing_passes_through_samples() {
+        let path = peak().with_smoothing(true).isoline(0.5).finalize();
+        let ends: Vec<Vector2> = path
+            .segments
+            .iter()
+            .filter_map(|segment| match segment {
+                Segment::CubicTo(point, _, _) => Some(*point),
+                _ => None,
+            })
+            .collect();
+        let (points, _) = &peak().polylines(0.5)[0];
+        assert_eq!(ends.len(), points.len());
+        for (index, end) in ends.iter().enumerate() {
+            assert_eq!(*end, points[(index + 1) % points.len()]);
+        }
+    }
+}
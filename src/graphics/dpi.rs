@@ -0,0 +1,100 @@
+use crate::graphics::Vector2;
+
+/// A size or position in logical pixels, which are independent of display density.
+///
+/// Content, viewports and frame sizes are specified in logical pixels.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Logical(pub Vector2);
+
+/// A size or position in physical device pixels.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Physical(pub Vector2);
+
+impl Logical {
+    /// Converts to physical pixels at the provided pixel ratio.
+    pub fn to_physical(self, pixel_ratio: f64) -> Physical {
+        Physical(self.0 * pixel_ratio)
+    }
+    /// Moves each coordinate to the nearest boundary between physical pixels at the provided
+    /// pixel ratio, such that edges placed there are rendered without blurring.
+    pub fn snap(self, pixel_ratio: f64) -> Logical {
+        self.to_physical(pixel_ratio)
+            .round()
+            .to_logical(pixel_ratio)
+    }
+}
+
+impl Physical {
+    /// Converts to logical pixels at the provided pixel ratio.
+    pub fn to_logical(self, pixel_ratio: f64) -> Logical {
+        Logical(self.0 / pixel_ratio)
+    }
+    /// Rounds each coordinate to the nearest whole device pixel.
+    pub fn round(self) -> Physical {
+        Physical((self.0.x.round(), self.0.y.round()).into())
+    }
+    /// Returns the whole-pixel dimensions of a backing surface of this size, rounding to the
+    /// nearest pixel.
+    pub fn to_pixels(self) -> (u32, u32) {
+        let rounded = self.round().0;
+        (rounded.x.max(0.) as u32, rounded.y.max(0.) as u32)
+    }
+}
+
+impl<T> From<T> for Logical
+where
+    T: Into<Vector2>,
+{
+    fn from(input: T) -> Logical {
+        Logical(input.into())
+    }
+}
+
+impl<T> From<T> for Physical
+where
+    T: Into<Vector2>,
+{
+    fn from(input: T) -> Physical {
+        Physical(input.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Logical;
+
+    const RATIOS: [f64; 6] = [1., 1.25, 1.5, 1.75, 2., 2.25];
+
+    #[test]
+    fn surfaces_round_at_fractional_ratios() {
+        assert_eq!(
+            Logical::from((1001., 701.)).to_physical(1.25).to_pixels(),
+            (1251, 876)
+        );
+        assert_eq!(
+            Logical::from((333., 667.)).to_physical(1.5).to_pixels(),
+            (500, 1001)
+        );
+        for ratio in RATIOS.iter() {
+            for size in 0..2000 {
+                let size = f64::from(size);
+                let (width, _) = Logical::from((size, 0.)).to_physical(*ratio).to_pixels();
+                assert!((f64::from(width) - size * ratio).abs() <= 0.5);
+            }
+        }
+    }
+
+    #[test]
+    fn snapped_edges_fall_on_device_pixels() {
+        for ratio in RATIOS.iter() {
+            for step in 0..400 {
+                let position = f64::from(step) * 0.37;
+                let snapped = Logical::from((position, -position)).snap(*ratio);
+                let physical = snapped.to_physical(*ratio).0;
+                assert!((physical.x - physical.x.round()).abs() < 1e-9);
+                assert!((physical.y - physical.y.round()).abs() < 1e-9);
+                assert!((snapped.0.x - position).abs() <= 0.5 / ratio + 1e-9);
+            }
+        }
+    }
+}
@@ -0,0 +1,135 @@
+use crate::graphics::{Image, LDRColor, Texture2};
+
+fn matches(target: LDRColor, color: LDRColor, tolerance: f64) -> bool {
+    let difference = [
+        (target.r, color.r),
+        (target.g, color.g),
+        (target.b, color.b),
+        (target.a, color.a),
+    ]
+    .iter()
+    .map(|(a, b)| (i16::from(*a) - i16::from(*b)).abs())
+    .max()
+    .unwrap_or(0);
+    f64::from(difference) <= tolerance * 255.
+}
+
+/// Performs a scanline flood fill of the region of the image contiguous with the pixel at the
+/// provided coordinates, where pixels belong to the region if no channel differs from that pixel
+/// by more than the tolerance, a fraction between zero and one.
+///
+/// Returns an image of the same size that is the fill color within the region and transparent
+/// elsewhere, or [None] if the coordinates are outside of the image.
+pub(crate) fn flood_fill(
+    image: &Image<LDRColor, Texture2>,
+    x: u32,
+    y: u32,
+    color: LDRColor,
+    tolerance: f64,
+) -> Option<Image<LDRColor, Texture2>> {
+    let (width, height) = (image.format.width as usize, image.format.height as usize);
+    let (x, y) = (x as usize, y as usize);
+    if x >= width || y >= height || image.pixels.len() < width * height {
+        return None;
+    }
+    let target = image.pixels[y * width + x];
+    let mut filled = vec![false; width * height];
+    let fillable = |index: usize, filled: &[bool]| {
+        !filled[index] && matches(target, image.pixels[index], tolerance)
+    };
+    let mut seeds = vec![(x, y)];
+    while let Some((x, y)) = seeds.pop() {
+        let row = y * width;
+        if !fillable(row + x, &filled) {
+            continue;
+        }
+        let mut start = x;
+        while start > 0 && fillable(row + start - 1, &filled) {
+            start -= 1;
+        }
+        let mut end = x;
+        while end + 1 < width && fillable(row + end + 1, &filled) {
+            end += 1;
+        }
+        for cell in &mut filled[row + start..=row + end] {
+            *cell = true;
+        }
+        let adjacent = [y.checked_sub(1), Some(y + 1).filter(|y| *y < height)];
+        for y in adjacent.iter().filter_map(|y| *y) {
+            let row = y * width;
+            let mut spanning = false;
+            for x in start..=end {
+                let open = fillable(row + x, &filled);
+                if open && !spanning {
+                    seeds.push((x, y));
+                }
+                spanning = open;
+            }
+        }
+    }
+    let transparent = LDRColor::rgba(0, 0, 0, 0);
+    Some(Image {
+        pixels: filled
+            .into_iter()
+            .map(|filled| if filled { color } else { transparent })
+            .collect(),
+        format: image.format,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn image(rows: &[&str]) -> Image<LDRColor, Texture2> {
+        Image {
+            pixels: rows
+                .iter()
+                .flat_map(|row| row.chars())
+                .map(|cell| match cell {
+                    '#' => LDRColor::black(),
+                    '+' => LDRColor::rgb(20, 20, 20),
+                    _ => LDRColor::white(),
+                })
+                .collect(),
+            format: Texture2 {
+                width: rows[0].len() as u32,
+                height: rows.len() as u32,
+            },
+        }
+    }
+
+    fn mask(image: &Image<LDRColor, Texture2>) -> Vec<String> {
+        image
+            .pixels
+            .chunks(image.format.width as usize)
+            .map(|row| {
+                row.iter()
+                    .map(|pixel| if pixel.a == 0 { '.' } else { 'x' })
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn fills_contiguous_region() {
+        let source = image(&["  #   ", "  # # ", "### # ", "    # "]);
+        let red = LDRColor::rgb(255, 0, 0);
+        let filled = flood_fill(&source, 0, 0, red, 0.).unwrap();
+        assert_eq!(mask(&filled), vec!["xx....", "xx....", "......", "......"]);
+        assert_eq!(filled.pixels[0], red);
+        let filled = flood_fill(&source, 3, 0, red, 0.).unwrap();
+        assert_eq!(mask(&filled), vec!["...xxx", "...x.x", "...x.x", "xxxx.x"]);
+        assert!(flood_fill(&source, 6, 0, red, 0.).is_none());
+    }
+
+    #[test]
+    fn tolerance_widens_region() {
+        let source = image(&["#+ #", "++ +"]);
+        let red = LDRColor::rgb(255, 0, 0);
+        let filled = flood_fill(&source, 0, 0, red, 0.).unwrap();
+        assert_eq!(mask(&filled), vec!["x...", "...."]);
+        let filled = flood_fill(&source, 0, 0, red, 0.1).unwrap();
+        assert_eq!(mask(&filled), vec!["xx..", "xx.."]);
+    }
+}
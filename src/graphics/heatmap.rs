@@ -0,0 +1,264 @@
+use crate::graphics::{
+    path::{sample_stops, validate_stops, GradientStop},
+    Image, LDRColor, Texture2, Vector2,
+};
+
+use crate::errors::Error;
+
+/// The number of entries in the lookup table a color ramp is sampled into.
+const RAMP_ENTRIES: usize = 256;
+
+/// A density grid that accumulates weighted points and colors them through a ramp.
+///
+/// Points are spread over nearby cells with a smooth falloff. The resulting [Image] is updated
+/// incrementally, so only the cells changed since the last call to [Heatmap::image] are recolored
+/// unless the normalization changes.
+#[derive(Clone, Debug)]
+pub struct Heatmap {
+    width: u32,
+    height: u32,
+    radius: f64,
+    maximum: Option<f64>,
+    density: Vec<f64>,
+    peak: f64,
+    ramp: Vec<LDRColor>,
+    image: Image<LDRColor, Texture2>,
+    scale: f64,
+    dirty: Option<(u32, u32, u32, u32)>,
+}
+
+fn default_ramp() -> Vec<GradientStop> {
+    vec![
+        GradientStop {
+            offset: 0.,
+            color: LDRColor::rgba(0, 0, 255, 0),
+        },
+        GradientStop {
+            offset: 0.25,
+            color: LDRColor::rgb(0, 0, 255),
+        },
+        GradientStop {
+            offset: 0.55,
+            color: LDRColor::rgb(0, 255, 0),
+        },
+        GradientStop {
+            offset: 0.85,
+            color: LDRColor::rgb(255, 255, 0),
+        },
+        GradientStop {
+            offset: 1.,
+            color: LDRColor::rgb(255, 0, 0),
+        },
+    ]
+}
+
+fn sample_ramp(stops: &[GradientStop]) -> Vec<LDRColor> {
+    let byte = |channel: f64| (channel * 255.).round() as u8;
+    (0..RAMP_ENTRIES)
+        .map(|entry| {
+            let [r, g, b, a] = sample_stops(stops, entry as f64 / (RAMP_ENTRIES - 1) as f64);
+            LDRColor::rgba(byte(r), byte(g), byte(b), byte(a))
+        })
+        .collect()
+}
+
+impl Heatmap {
+    /// Creates an empty heatmap with the provided size in cells, each of which is one pixel of
+    /// its image.
+    pub fn new(width: u32, height: u32) -> Heatmap {
+        let ramp = sample_ramp(&default_ramp());
+        Heatmap {
+            width,
+            height,
+            radius: 8.,
+            maximum: None,
+            density: vec![0.; (width * height) as usize],
+            peak: 0.,
+            image: Image {
+                pixels: vec![ramp[0]; (width * height) as usize],
+                format: Texture2 { width, height },
+            },
+            ramp,
+            scale: 0.,
+            dirty: None,
+        }
+    }
+    /// Sets the radius, in cells, that each point is spread over.
+    pub fn with_radius(mut self, radius: f64) -> Self {
+        self.radius = radius.max(0.);
+        self
+    }
+    /// Sets the color ramp that densities are mapped through, from empty at offset zero to the
+    /// maximum density at offset one.
+    pub fn with_ramp(mut self, stops: Vec<GradientStop>) -> Result<Self, Error> {
+        validate_stops(&stops)?;
+        self.ramp = sample_ramp(&stops);
+        self.scale = -1.;
+        Ok(self)
+    }
+    /// Sets the density that maps to the end of the ramp. By default the greatest density
+    /// accumulated so far is used.
+    pub fn with_maximum(mut self, maximum: f64) -> Self {
+        self.maximum = Some(maximum);
+        self
+    }
+    /// Returns the size of the heatmap in cells.
+    pub fn get_size(&self) -> Vector2 {
+        (f64::from(self.width), f64::from(self.height)).into()
+    }
+    /// Returns the accumulated density of a cell, or zero outside of the heatmap.
+    pub fn density(&self, x: u32, y: u32) -> f64 {
+        if x >= self.width || y >= self.height {
+            return 0.;
+        }
+        self.density[(y * self.width + x) as usize]
+    }
+    /// Accumulates a weighted point, in cells, into the density grid.
+    pub fn add<T>(&mut self, point: T, weight: f64)
+    where
+        T: Into<Vector2>,
+    {
+        let point = point.into();
+        let radius = self.radius.max(0.5);
+        let clamp = |value: f64, limit: u32| value.clamp(0., f64::from(limit)) as u32;
+        let (left, right) = (
+            clamp((point.x - radius).floor(), self.width),
+            clamp((point.x + radius).ceil(), self.width),
+        );
+        let (top, bottom) = (
+            clamp((point.y - radius).floor(), self.height),
+            clamp((point.y + radius).ceil(), self.height),
+        );
+        if left >= right || top >= bottom {
+            return;
+        }
+        for y in top..bottom {
+            for x in left..right {
+                let offset = Vector2::from((f64::from(x) + 0.5, f64::from(y) + 0.5)) - point;
+                let distance = (offset.x * offset.x + offset.y * offset.y) / (radius * radius);
+                if distance >= 1. {
+                    continue;
+                }
+                let cell = &mut self.density[(y * self.width + x) as usize];
+                *cell += weight * (1. - distance) * (1. - distance);
+                self.peak = self.peak.max(*cell);
+            }
+        }
+        self.mark((left, top, right, bottom));
+    }
+    /// Scales every density by the provided factor, fading older points for live data.
+    pub fn decay(&mut self, factor: f64) {
+        for cell in &mut self.density {
+            *cell *= factor;
+        }
+        self.peak *= factor;
+        self.mark((0, 0, self.width, self.height));
+    }
+    /// Removes all accumulated points.
+    pub fn clear(&mut self) {
+        for cell in &mut self.density {
+            *cell = 0.;
+        }
+        self.peak = 0.;
+        self.mark((0, 0, self.width, self.height));
+    }
+    /// Returns the colored heatmap, recoloring the cells that changed since the last call.
+    pub fn image(&mut self) -> &Image<LDRColor, Texture2> {
+        let scale = self.maximum.unwrap_or(self.peak);
+        if scale != self.scale {
+            self.scale = scale;
+            self.dirty = Some((0, 0, self.width, self.height));
+        }
+        if let Some((left, top, right, bottom)) = self.dirty.take() {
+            for y in top..bottom {
+                for x in left..right {
+                    let index = (y * self.width + x) as usize;
+                    let value = if scale > 0. {
+                        (self.density[index] / scale).clamp(0., 1.)
+                    } else {
+                        0.
+                    };
+                    self.image.pixels[index] =
+                        self.ramp[(value * (RAMP_ENTRIES - 1) as f64).round() as usize];
+                }
+            }
+        }
+        &self.image
+    }
+    fn mark(&mut self, (left, top, right, bottom): (u32, u32, u32, u32)) {
+        self.dirty = Some(match self.dirty {
+            Some(dirty) => (
+                dirty.0.min(left),
+                dirty.1.min(top),
+                dirty.2.max(right),
+                dirty.3.max(bottom),
+            ),
+            None => (left, top, right, bottom),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn points_accumulate_with_falloff() {
+        let mut heatmap = Heatmap::new(32, 32).with_radius(4.);
+        heatmap.add((16., 16.), 1.);
+        heatmap.add((16., 16.), 1.);
+        assert!((heatmap.density(15, 15) - heatmap.density(16, 16)).abs() < 1e-9);
+        assert!(heatmap.density(16, 16) > heatmap.density(18, 16));
+        assert!(heatmap.density(18, 16) > 0.);
+        assert_eq!(heatmap.density(21, 16), 0.);
+        assert!(heatmap.density(16, 16) > 1.8);
+        heatmap.add((-100., 0.), 1.);
+        heatmap.add((0., 0.), 1.);
+        assert!(heatmap.density(0, 0) > 0.);
+    }
+
+    #[test]
+    fn image_maps_through_ramp() {
+        let mut heatmap = Heatmap::new(8, 8).with_radius(1.);
+        heatmap.add((4., 4.), 1.);
+        let image = heatmap.image();
+        assert_eq!(image.pixels[0], LDRColor::rgba(0, 0, 255, 0));
+        assert_eq!(image.pixels[3 * 8 + 3], LDRColor::rgb(255, 0, 0));
+        let mut heatmap = Heatmap::new(4, 4)
+            .with_maximum(2.)
+            .with_ramp(vec![
+                GradientStop {
+                    offset: 0.,
+                    color: LDRColor::black(),
+                },
+                GradientStop {
+                    offset: 1.,
+                    color: LDRColor::white(),
+                },
+            ])
+            .unwrap();
+        heatmap.add((0.5, 0.5), 1.);
+        assert_eq!(heatmap.image().pixels[0], LDRColor::rgb(128, 128, 128));
+        heatmap.clear();
+        assert_eq!(heatmap.image().pixels[0], LDRColor::black());
+        assert!(Heatmap::new(4, 4)
+            .with_ramp(vec![GradientStop {
+                offset: 2.,
+                color: LDRColor::black(),
+            }])
+            .is_err());
+    }
+
+    #[test]
+    fn image_updates_incrementally() {
+        let mut heatmap = Heatmap::new(16, 16).with_radius(2.).with_maximum(1.);
+        heatmap.add((2., 2.), 1.);
+        heatmap.image();
+        heatmap.image.pixels[15 * 16 + 15] = LDRColor::white();
+        heatmap.add((4., 4.), 1.);
+        assert_eq!(heatmap.dirty, Some((2, 2, 6, 6)));
+        assert_eq!(heatmap.image().pixels[15 * 16 + 15], LDRColor::white());
+        heatmap.decay(0.5);
+        assert_ne!(heatmap.image().pixels[15 * 16 + 15], LDRColor::white());
+    }
+}
@@ -0,0 +1,201 @@
+use crate::graphics::{
+    canvas::Rasterizable,
+    dpi::Logical,
+    path::{Segment, StyleHelper},
+    text::Text,
+    LDRColor, Rect, Vector2,
+};
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, RwLock},
+};
+
+mod svg;
+
+static CURRENT: RwLock<Option<IconSet>> = RwLock::new(None);
+
+#[derive(Clone, Debug)]
+enum Glyph {
+    Path {
+        view_box: Rect,
+        segments: Vec<Segment>,
+    },
+    Character {
+        family: String,
+        character: char,
+    },
+}
+
+#[derive(Debug, Default)]
+struct IconSetState {
+    glyphs: HashMap<String, Glyph>,
+    cache: HashMap<(String, u64), Vec<Segment>>,
+}
+
+/// A set of named icons, loaded once and shared by every [Icon] drawn from it.
+///
+/// Icons are read from SVG, which is parsed when it is added to the set, or from the glyphs of an
+/// icon font. Geometry scaled to each size an icon is drawn at is cached, so repeated icons cost
+/// no more than cloning their segments.
+#[derive(Clone, Debug, Default)]
+pub struct IconSet {
+    state: Arc<RwLock<IconSetState>>,
+}
+
+impl IconSet {
+    /// Creates an empty icon set.
+    pub fn new() -> IconSet {
+        IconSet::default()
+    }
+    /// Returns the global icon set used by [Icon::named], if one has been set.
+    pub fn current() -> Option<IconSet> {
+        CURRENT.read().unwrap().clone()
+    }
+    /// Sets the global icon set.
+    pub fn set_current(set: IconSet) {
+        *CURRENT.write().unwrap() = Some(set);
+    }
+    fn with_glyph(self, name: &str, glyph: Glyph) -> Self {
+        {
+            let mut state = self.state.write().unwrap();
+            state.cache.retain(|(cached, _), _| cached != name);
+            state.glyphs.insert(name.to_owned(), glyph);
+        }
+        self
+    }
+    /// Adds every `symbol` element of an SVG sprite sheet, each named by its id. Only the `d`
+    /// attribute of `path` elements is read, so icons drawn with other elements, transforms or
+    /// strokes are not supported.
+    pub fn with_sprite(self, source: &str) -> Self {
+        svg::symbols(source)
+            .into_iter()
+            .filter(|symbol| !symbol.id.is_empty())
+            .fold(self, |set, symbol| {
+                let glyph = Glyph::Path {
+                    view_box: symbol.view_box,
+                    segments: symbol.segments,
+                };
+                set.with_glyph(&symbol.id, glyph)
+            })
+    }
+    /// Adds the icon drawn by an SVG document under the provided name, with the same limitations
+    /// as [IconSet::with_sprite].
+    pub fn with_svg(self, name: &str, source: &str) -> Self {
+        match svg::symbols(source).into_iter().next() {
+            Some(symbol) => self.with_glyph(
+                name,
+                Glyph::Path {
+                    view_box: symbol.view_box,
+                    segments: symbol.segments,
+                },
+            ),
+            None => self,
+        }
+    }
+    /// Adds a character of an icon font under the provided name.
+    pub fn with_font_glyph(self, name: &str, family: &str, character: char) -> Self {
+        self.with_glyph(
+            name,
+            Glyph::Character {
+                family: family.to_owned(),
+                character,
+            },
+        )
+    }
+    /// Returns the names of the icons in the set.
+    pub fn names(&self) -> Vec<String> {
+        self.state.read().unwrap().glyphs.keys().cloned().collect()
+    }
+    /// Returns an icon from the set.
+    pub fn icon(&self, name: &str) -> Icon {
+        Icon {
+            set: Some(self.clone()),
+            ..Icon::named(name)
+        }
+    }
+    fn rasterize(&self, name: &str, size: f64, color: LDRColor) -> Option<Rasterizable> {
+        let mut state = self.state.write().unwrap();
+        let key = (name.to_owned(), size.to_bits());
+        if !state.cache.contains_key(&key) {
+            let scaled = match state.glyphs.get(name)? {
+                Glyph::Character { family, character } => {
+                    return Some(
+                        Text::new(&character.to_string())
+                            .with_family(family)
+                            .with_size(size)
+                            .with_line_height(size)
+                            .with_color(color)
+                            .into(),
+                    );
+                }
+                Glyph::Path { view_box, segments } => {
+                    // The view box is fit within a square of the icon's size, centering its
+                    // shorter side.
+                    let scale = size / view_box.size.x.max(view_box.size.y);
+                    let offset = (Vector2::from(size) - view_box.size * scale) / 2.;
+                    segments
+                        .iter()
+                        .map(|segment| {
+                            segment.map_points(|point| (point - view_box.position) * scale + offset)
+                        })
+                        .collect()
+                }
+            };
+            state.cache.insert(key.clone(), scaled);
+        }
+        Some(
+            StyleHelper::new(state.cache[&key].clone())
+                .fill(color.into())
+                .finalize()
+                .into(),
+        )
+    }
+}
+
+/// A named icon from an [IconSet], drawn as a square of the given size.
+#[derive(Clone, Debug)]
+pub struct Icon {
+    set: Option<IconSet>,
+    name: String,
+    size: f64,
+    color: LDRColor,
+    pixel_ratio: f64,
+}
+
+impl Icon {
+    /// Creates an icon from the global icon set, set with [IconSet::set_current]. The size
+    /// defaults to 24 and the color to black.
+    pub fn named(name: &str) -> Icon {
+        Icon {
+            set: IconSet::current(),
+            name: name.to_owned(),
+            size: 24.,
+            color: LDRColor::black(),
+            pixel_ratio: 1.,
+        }
+    }
+    /// Sets the size of the icon in logical pixels.
+    pub fn with_size(mut self, size: f64) -> Self {
+        self.size = size;
+        self
+    }
+    /// Sets the color of the icon.
+    pub fn with_color(mut self, color: LDRColor) -> Self {
+        self.color = color;
+        self
+    }
+    /// Sets the pixel ratio of the frame the icon is drawn in. The size of the icon is rounded to
+    /// whole physical pixels at this ratio so that an icon designed on a pixel grid stays aligned
+    /// to it, rather than being blurred across pixel boundaries.
+    pub fn with_pixel_ratio(mut self, pixel_ratio: f64) -> Self {
+        self.pixel_ratio = pixel_ratio;
+        self
+    }
+    /// Returns the content of the icon, or [None] if there is no icon set or the set has no icon
+    /// of this name.
+    pub fn finalize(self) -> Option<Rasterizable> {
+        let size = Logical::from(self.size).snap(self.pixel_ratio).0.x;
+        self.set?.rasterize(&self.name, size, self.color)
+    }
+}
@@ -0,0 +1,469 @@
+use crate::graphics::{path::Segment, Rect, Vector2};
+
+use std::f64::consts::{FRAC_PI_2, PI};
+
+/// The view box assumed for icons that specify neither a view box nor a size, which is the most
+/// common icon grid.
+const DEFAULT_VIEW_BOX: (f64, f64) = (24., 24.);
+
+/// An icon parsed from SVG source.
+#[derive(Clone, Debug)]
+pub(crate) struct Symbol {
+    /// The id of the element the icon was read from, which is empty for an unnamed root element.
+    pub(crate) id: String,
+    /// The area of the icon's coordinate space that is shown.
+    pub(crate) view_box: Rect,
+    /// The geometry of every path in the icon.
+    pub(crate) segments: Vec<Segment>,
+}
+
+/// Returns the value of the named attribute of the provided tag, if present.
+fn attribute<'a>(tag: &'a str, name: &str) -> Option<&'a str> {
+    let mut rest = tag;
+    while let Some(index) = rest.find(name) {
+        let preceded = rest[..index].ends_with(char::is_whitespace);
+        let after = rest[index + name.len()..].trim_start();
+        rest = &rest[index + name.len()..];
+        if !preceded || !after.starts_with('=') {
+            continue;
+        }
+        let value = after[1..].trim_start();
+        let quote = value.chars().next()?;
+        if quote != '"' && quote != '\'' {
+            continue;
+        }
+        let value = &value[1..];
+        return value.find(quote).map(|end| &value[..end]);
+    }
+    None
+}
+
+fn view_box(tag: &str) -> Rect {
+    if let Some(numbers) = attribute(tag, "viewBox").map(|value| Lexer::new(value).numbers()) {
+        if let [x, y, width, height] = numbers[..] {
+            return Rect::new((x, y), (width, height));
+        }
+    }
+    let size = |name| attribute(tag, name).and_then(|value| Lexer::new(value).number());
+    match (size("width"), size("height")) {
+        (Some(width), Some(height)) => Rect::new((0., 0.), (width, height)),
+        _ => Rect::new((0., 0.), DEFAULT_VIEW_BOX),
+    }
+}
+
+/// Reads the icons in the provided SVG source: each `symbol` element, as found in SVG sprite
+/// sheets, and the root `svg` element if it contains paths of its own. Only the `d` attribute of
+/// `path` elements is read, so icons drawn with other elements or with transforms are not
+/// supported.
+pub(crate) fn symbols(source: &str) -> Vec<Symbol> {
+    let mut symbols = vec![];
+    let mut root: Option<Symbol> = None;
+    let mut current: Option<Symbol> = None;
+    let mut rest = source;
+    while let Some(start) = rest.find('<') {
+        rest = &rest[start + 1..];
+        let end = match rest.find('>') {
+            Some(end) => end,
+            None => break,
+        };
+        let tag = &rest[..end];
+        rest = &rest[end + 1..];
+        let name = tag
+            .split(|c: char| c.is_whitespace() || c == '/')
+            .next()
+            .unwrap_or("");
+        match name {
+            "svg" if root.is_none() => {
+                root = Some(Symbol {
+                    id: attribute(tag, "id").unwrap_or("").to_owned(),
+                    view_box: view_box(tag),
+                    segments: vec![],
+                })
+            }
+            "symbol" => {
+                current = Some(Symbol {
+                    id: attribute(tag, "id").unwrap_or("").to_owned(),
+                    view_box: view_box(tag),
+                    segments: vec![],
+                })
+            }
+            "" if tag.trim_end() == "/symbol" => symbols.extend(current.take()),
+            "path" => {
+                if let (Some(symbol), Some(data)) =
+                    (current.as_mut().or(root.as_mut()), attribute(tag, "d"))
+                {
+                    symbol.segments.extend(path_data(data));
+                }
+            }
+            _ => {}
+        }
+    }
+    if let Some(root) = root {
+        if !root.segments.is_empty() {
+            symbols.insert(0, root);
+        }
+    }
+    symbols
+}
+
+struct Lexer<'a> {
+    input: &'a [u8],
+    index: usize,
+}
+
+impl<'a> Lexer<'a> {
+    fn new(input: &'a str) -> Lexer<'a> {
+        Lexer {
+            input: input.as_bytes(),
+            index: 0,
+        }
+    }
+    fn skip_separators(&mut self) {
+        while let Some(byte) = self.input.get(self.index) {
+            if !byte.is_ascii_whitespace() && *byte != b',' {
+                break;
+            }
+            self.index += 1;
+        }
+    }
+    fn command(&mut self) -> Option<u8> {
+        self.skip_separators();
+        let byte = *self.input.get(self.index)?;
+        if byte.is_ascii_alphabetic() && byte != b'e' && byte != b'E' {
+            self.index += 1;
+            Some(byte)
+        } else {
+            None
+        }
+    }
+    fn number(&mut self) -> Option<f64> {
+        self.skip_separators();
+        let start = self.index;
+        let digits = |lexer: &mut Lexer<'_>| {
+            let from = lexer.index;
+            while let Some(b'0'..=b'9') = lexer.input.get(lexer.index) {
+                lexer.index += 1;
+            }
+            lexer.index > from
+        };
+        if let Some(b'+') | Some(b'-') = self.input.get(self.index) {
+            self.index += 1;
+        }
+        let mut valid = digits(self);
+        if self.input.get(self.index) == Some(&b'.') {
+            self.index += 1;
+            valid |= digits(self);
+        }
+        if valid {
+            if let Some(b'e') | Some(b'E') = self.input.get(self.index) {
+                let mantissa = self.index;
+                self.index += 1;
+                if let Some(b'+') | Some(b'-') = self.input.get(self.index) {
+                    self.index += 1;
+                }
+                if !digits(self) {
+                    self.index = mantissa;
+                }
+            }
+        }
+        let number = std::str::from_utf8(&self.input[start..self.index])
+            .ok()
+            .filter(|_| valid)
+            .and_then(|number| number.parse().ok());
+        if number.is_none() {
+            self.index = start;
+        }
+        number
+    }
+    /// Reads an arc flag, which may be written without a separator before the next number.
+    fn flag(&mut self) -> Option<bool> {
+        self.skip_separators();
+        let flag = match self.input.get(self.index)? {
+            b'0' => false,
+            b'1' => true,
+            _ => return None,
+        };
+        self.index += 1;
+        Some(flag)
+    }
+    fn point(&mut self) -> Option<Vector2> {
+        let x = self.number()?;
+        Some((x, self.number()?).into())
+    }
+    fn numbers(&mut self) -> Vec<f64> {
+        let mut numbers = vec![];
+        while let Some(number) = self.number() {
+            numbers.push(number);
+        }
+        numbers
+    }
+}
+
+fn rotate(point: Vector2, cos: f64, sin: f64) -> Vector2 {
+    (point.x * cos - point.y * sin, point.x * sin + point.y * cos).into()
+}
+
+/// Approximates an elliptical arc, given in the endpoint parameterization SVG uses, with cubic
+/// curves, following the conversion in appendix B.2.4 of the SVG 2 specification.
+fn arc(
+    segments: &mut Vec<Segment>,
+    from: Vector2,
+    radii: Vector2,
+    rotation: f64,
+    large_arc: bool,
+    sweep: bool,
+    to: Vector2,
+) {
+    if from == to {
+        return;
+    }
+    let (mut rx, mut ry) = (radii.x.abs(), radii.y.abs());
+    if rx == 0. || ry == 0. {
+        segments.push(Segment::LineTo(to));
+        return;
+    }
+    let (sin, cos) = rotation.to_radians().sin_cos();
+    let half = rotate((from - to) / 2., cos, -sin);
+    let scale = (half.x / rx).powi(2) + (half.y / ry).powi(2);
+    if scale > 1. {
+        rx *= scale.sqrt();
+        ry *= scale.sqrt();
+    }
+    let numerator = (rx * ry).powi(2) - (rx * half.y).powi(2) - (ry * half.x).powi(2);
+    let denominator = (rx * half.y).powi(2) + (ry * half.x).powi(2);
+    let coefficient =
+        (numerator / denominator).max(0.).sqrt() * if large_arc == sweep { -1. } else { 1. };
+    let center = Vector2::from((rx * half.y / ry, -ry * half.x / rx)) * coefficient;
+    let angle = |vector: Vector2| vector.y.atan2(vector.x);
+    let start = angle((half - center) / (rx, ry));
+    let mut sweep_angle = angle((-half - center) / (rx, ry)) - start;
+    if sweep && sweep_angle < 0. {
+        sweep_angle += 2. * PI;
+    } else if !sweep && sweep_angle > 0. {
+        sweep_angle -= 2. * PI;
+    }
+    let center = rotate(center, cos, sin) + (from + to) / 2.;
+    let point = |theta: f64| center + rotate((rx * theta.cos(), ry * theta.sin()).into(), cos, sin);
+    let tangent = |theta: f64| rotate((-rx * theta.sin(), ry * theta.cos()).into(), cos, sin);
+    let pieces = (sweep_angle.abs() / FRAC_PI_2).ceil().max(1.) as u32;
+    let step = sweep_angle / f64::from(pieces);
+    let handle_length = 4. / 3. * (step / 4.).tan();
+    for piece in 0..pieces {
+        let a = start + step * f64::from(piece);
+        let b = a + step;
+        let end = if piece + 1 == pieces { to } else { point(b) };
+        segments.push(Segment::CubicTo(
+            end,
+            point(a) + tangent(a) * handle_length,
+            end - tangent(b) * handle_length,
+        ));
+    }
+}
+
+/// Converts SVG path data, as found in the `d` attribute of a `path` element, into segments.
+/// Parsing stops at the first error, keeping the segments before it as SVG renderers do.
+pub(crate) fn path_data(data: &str) -> Vec<Segment> {
+    let mut lexer = Lexer::new(data);
+    let mut segments = vec![];
+    let (mut current, mut start) = (Vector2::default(), Vector2::default());
+    // The handle reflected by smooth curve commands, with whether it belongs to a cubic curve.
+    let mut previous_handle: Option<(Vector2, bool)> = None;
+    let mut command = match lexer.command() {
+        Some(command) => command,
+        None => return segments,
+    };
+    loop {
+        let relative = command.is_ascii_lowercase();
+        let offset = if relative {
+            current
+        } else {
+            Vector2::default()
+        };
+        let mut handle = None;
+        let parsed = match command.to_ascii_uppercase() {
+            b'M' => lexer.point().map(|point| {
+                current = point + offset;
+                start = current;
+                segments.push(Segment::MoveTo(current));
+                // Further coordinate pairs after a move are implicit lines.
+                command = if relative { b'l' } else { b'L' };
+            }),
+            b'L' => lexer.point().map(|point| {
+                current = point + offset;
+                segments.push(Segment::LineTo(current));
+            }),
+            b'H' => lexer.number().map(|x| {
+                current.x = x + offset.x;
+                segments.push(Segment::LineTo(current));
+            }),
+            b'V' => lexer.number().map(|y| {
+                current.y = y + offset.y;
+                segments.push(Segment::LineTo(current));
+            }),
+            b'C' => match (lexer.point(), lexer.point(), lexer.point()) {
+                (Some(handle_1), Some(handle_2), Some(point)) => {
+                    current = point + offset;
+                    handle = Some((handle_2 + offset, true));
+                    segments.push(Segment::CubicTo(
+                        current,
+                        handle_1 + offset,
+                        handle_2 + offset,
+                    ));
+                    Some(())
+                }
+                _ => None,
+            },
+            b'S' => match (lexer.point(), lexer.point()) {
+                (Some(handle_2), Some(point)) => {
+                    let handle_1 = match previous_handle {
+                        Some((previous, true)) => current * 2. - previous,
+                        _ => current,
+                    };
+                    current = point + offset;
+                    handle = Some((handle_2 + offset, true));
+                    segments.push(Segment::CubicTo(current, handle_1, handle_2 + offset));
+                    Some(())
+                }
+                _ => None,
+            },
+            b'Q' => match (lexer.point(), lexer.point()) {
+                (Some(control), Some(point)) => {
+                    current = point + offset;
+                    handle = Some((control + offset, false));
+                    segments.push(Segment::QuadraticTo(current, control + offset));
+                    Some(())
+                }
+                _ => None,
+            },
+            b'T' => lexer.point().map(|point| {
+                let control = match previous_handle {
+                    Some((previous, false)) => current * 2. - previous,
+                    _ => current,
+                };
+                current = point + offset;
+                handle = Some((control, false));
+                segments.push(Segment::QuadraticTo(current, control));
+            }),
+            b'A' => match (
+                lexer.point(),
+                lexer.number(),
+                lexer.flag(),
+                lexer.flag(),
+                lexer.point(),
+            ) {
+                (Some(radii), Some(rotation), Some(large_arc), Some(sweep), Some(point)) => {
+                    let to = point + offset;
+                    arc(
+                        &mut segments,
+                        current,
+                        radii,
+                        rotation,
+                        large_arc,
+                        sweep,
+                        to,
+                    );
+                    current = to;
+                    Some(())
+                }
+                _ => None,
+            },
+            b'Z' => {
+                segments.push(Segment::ClosePath);
+                current = start;
+                previous_handle = None;
+                match lexer.command() {
+                    Some(next) => {
+                        command = next;
+                        continue;
+                    }
+                    None => break,
+                }
+            }
+            _ => None,
+        };
+        if parsed.is_none() {
+            break;
+        }
+        previous_handle = handle;
+        if let Some(next) = lexer.command() {
+            command = next;
+        } else if lexer.index >= lexer.input.len() {
+            break;
+        }
+    }
+    segments
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: Vector2, b: Vector2) -> bool {
+        (a - b).x.abs() < 1e-9 && (a - b).y.abs() < 1e-9
+    }
+
+    #[test]
+    fn parses_compact_path_data() {
+        assert_eq!(
+            path_data("M.5.5l1-1h2V3Z m1,1 2 2"),
+            vec![
+                Segment::MoveTo((0.5, 0.5).into()),
+                Segment::LineTo((1.5, -0.5).into()),
+                Segment::LineTo((3.5, -0.5).into()),
+                Segment::LineTo((3.5, 3.).into()),
+                Segment::ClosePath,
+                Segment::MoveTo((1.5, 1.5).into()),
+                Segment::LineTo((3.5, 3.5).into()),
+            ]
+        );
+        assert_eq!(
+            path_data("M0 0L1e1 0 10 10 x 5 5"),
+            vec![
+                Segment::MoveTo((0., 0.).into()),
+                Segment::LineTo((10., 0.).into()),
+                Segment::LineTo((10., 10.).into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn reflects_smooth_handles() {
+        let segments = path_data("M0 0c0 1 1 2 2 2s2 1 2 2");
+        assert_eq!(
+            segments[2],
+            Segment::CubicTo((4., 4.).into(), (3., 2.).into(), (4., 3.).into())
+        );
+    }
+
+    #[test]
+    fn converts_arcs() {
+        let segments = path_data("M0 0A1 1 0 0110 0");
+        assert_eq!(segments.len(), 3);
+        let mut top = f64::INFINITY;
+        for segment in &segments[1..] {
+            if let Segment::CubicTo(point, ..) = segment {
+                top = top.min(point.y);
+            }
+        }
+        assert!(close((0., top).into(), (0., -5.).into()));
+        match segments[2] {
+            Segment::CubicTo(point, ..) => assert!(close(point, (10., 0.).into())),
+            _ => panic!("expected a curve"),
+        }
+    }
+
+    #[test]
+    fn reads_sprite_symbols() {
+        let source = r#"<svg xmlns="http://www.w3.org/2000/svg">
+            <symbol id="search" viewBox="0 0 16 16"><path d="M0 0h16"/><path d='M0 8h8'/></symbol>
+            <symbol data-id="x" id="close" width="20" height="10"><path d="M0 0L1 1"/></symbol>
+        </svg>"#;
+        let symbols = symbols(source);
+        assert_eq!(symbols.len(), 2);
+        assert_eq!(symbols[0].id, "search");
+        assert_eq!(symbols[0].segments.len(), 4);
+        assert_eq!(symbols[0].view_box.size, (16., 16.).into());
+        assert_eq!(symbols[1].id, "close");
+        assert_eq!(symbols[1].view_box.size, (20., 10.).into());
+    }
+}
@@ -0,0 +1,290 @@
+use crate::graphics::{
+    canvas::{Content, Extension},
+    path::{Path, Texture},
+    Image, LDRColor, Texture2, Transform2, Vector2,
+};
+use std::borrow::Cow;
+
+/// Many copies of one [Path] drawn as a single piece of content, such as the markers of a
+/// scatter plot or the particles of a burst, each optionally varied in tint, scale and opacity.
+///
+/// Variations are given as arrays indexed like the positions of the instances. Instances past
+/// the end of an array are drawn without that variation. Backends draw the shared path once for
+/// each instance, so no content is created per instance.
+#[derive(Clone, Debug)]
+pub struct Instances {
+    path: Path,
+    positions: Vec<Vector2>,
+    tints: Vec<LDRColor>,
+    scales: Vec<f64>,
+    opacities: Vec<f64>,
+}
+
+fn multiply(channel: u8, factor: u8) -> u8 {
+    ((u16::from(channel) * u16::from(factor) + 127) / 255) as u8
+}
+
+/// Returns a color multiplied by a tint and faded by an opacity.
+fn vary(color: LDRColor, tint: Option<LDRColor>, opacity: f64) -> LDRColor {
+    let tint = tint.unwrap_or_else(LDRColor::white);
+    LDRColor::rgba(
+        multiply(color.r, tint.r),
+        multiply(color.g, tint.g),
+        multiply(color.b, tint.b),
+        (f64::from(multiply(color.a, tint.a)) * opacity.clamp(0., 1.)).round() as u8,
+    )
+}
+
+/// The tint and opacity of one of some [Instances], which backends apply to the colors of the
+/// shared path as they draw it.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct Variation {
+    tint: Option<LDRColor>,
+    opacity: f64,
+}
+
+impl Default for Variation {
+    fn default() -> Self {
+        Variation {
+            tint: None,
+            opacity: 1.,
+        }
+    }
+}
+
+impl Variation {
+    /// Returns a color as varied.
+    pub(crate) fn color(&self, color: LDRColor) -> LDRColor {
+        if *self == Variation::default() {
+            return color;
+        }
+        vary(color, self.tint, self.opacity)
+    }
+    /// Returns a texture as varied, borrowing it if the variation leaves it as it is.
+    pub(crate) fn texture<'a>(&self, texture: &'a Texture) -> Cow<'a, Texture> {
+        if *self == Variation::default() {
+            return Cow::Borrowed(texture);
+        }
+        let mut texture = texture.clone();
+        vary_texture(&mut texture, self.tint, self.opacity);
+        Cow::Owned(texture)
+    }
+}
+
+fn vary_texture(texture: &mut Texture, tint: Option<LDRColor>, opacity: f64) {
+    match texture {
+        Texture::Solid(color) => *color = vary(*color, tint, opacity),
+        Texture::LinearGradient(gradient) => {
+            for stop in &mut gradient.stops {
+                stop.color = vary(stop.color, tint, opacity);
+            }
+        }
+        Texture::RadialGradient(gradient) => {
+            for stop in &mut gradient.stops {
+                stop.color = vary(stop.color, tint, opacity);
+            }
+        }
+        Texture::Hatch(hatch) => {
+            hatch.color = vary(hatch.color, tint, opacity);
+            hatch.background = vary(hatch.background, tint, opacity);
+        }
+        // Image textures are drawn by the backend as they are, so they can't be varied.
+        Texture::Image(_) => {}
+    }
+}
+
+impl Instances {
+    /// Creates instances of the provided path, positioned at each of the provided points.
+    pub fn new<T>(path: Path, positions: Vec<T>) -> Instances
+    where
+        T: Into<Vector2>,
+    {
+        Instances {
+            path,
+            positions: positions.into_iter().map(Into::into).collect(),
+            tints: vec![],
+            scales: vec![],
+            opacities: vec![],
+        }
+    }
+    /// Sets the color each instance is multiplied by.
+    pub fn with_tints(mut self, tints: Vec<LDRColor>) -> Self {
+        self.tints = tints;
+        self
+    }
+    /// Sets the factor each instance is scaled by about its position.
+    pub fn with_scales(mut self, scales: Vec<f64>) -> Self {
+        self.scales = scales;
+        self
+    }
+    /// Sets the opacity, between zero and one, of each instance.
+    pub fn with_opacities(mut self, opacities: Vec<f64>) -> Self {
+        self.opacities = opacities;
+        self
+    }
+    /// Returns the number of instances.
+    pub fn len(&self) -> usize {
+        self.positions.len()
+    }
+    /// Returns whether there are no instances.
+    pub fn is_empty(&self) -> bool {
+        self.positions.is_empty()
+    }
+    /// Returns the path that each instance is a copy of.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+    fn scale(&self, index: usize) -> f64 {
+        self.scales.get(index).cloned().unwrap_or(1.)
+    }
+    /// Returns the transform and variation of each instance, in order.
+    pub(crate) fn instances(&self) -> impl Iterator<Item = (Transform2, Variation)> + '_ {
+        self.positions
+            .iter()
+            .enumerate()
+            .map(move |(index, position)| {
+                let variation = Variation {
+                    tint: self.tints.get(index).cloned(),
+                    opacity: self.opacities.get(index).cloned().unwrap_or(1.),
+                };
+                let transform =
+                    Transform2::from(*position).with_scale(Vector2::from(self.scale(index)));
+                (transform, variation)
+            })
+    }
+}
+
+impl Extension for Instances {
+    fn measure(&self) -> Vector2 {
+        let bounds = self.path.bounds();
+        self.positions
+            .iter()
+            .enumerate()
+            .fold(Vector2::default(), |size, (index, position)| {
+                let far = *position + (bounds.position + bounds.size) * self.scale(index);
+                (size.x.max(far.x), size.y.max(far.y)).into()
+            })
+    }
+    /// Expands into a copy of the path for each instance, which backends only draw from where
+    /// they can't draw the path directly, such as when the instances are serialized.
+    fn expand(&self) -> Option<Vec<Content>> {
+        Some(
+            self.instances()
+                .map(|(transform, variation)| {
+                    let mut path = self.path.clone();
+                    if let Some(fill) = &mut path.fill {
+                        if let Cow::Owned(content) = variation.texture(&fill.content) {
+                            fill.content = content;
+                        }
+                    }
+                    if let Some(stroke) = &mut path.stroke {
+                        if let Cow::Owned(content) = variation.texture(&stroke.content) {
+                            stroke.content = content;
+                        }
+                    }
+                    for shadow in &mut path.shadows {
+                        shadow.color = variation.color(shadow.color);
+                    }
+                    Content::from(path).with_transform(transform)
+                })
+                .collect(),
+        )
+    }
+    fn rasterize(&self) -> Image<LDRColor, Texture2> {
+        // Instances always expand into paths.
+        Image {
+            pixels: vec![],
+            format: Texture2 {
+                width: 0,
+                height: 0,
+            },
+        }
+    }
+    fn as_instances(&self) -> Option<&Instances> {
+        Some(self)
+    }
+    fn box_clone(&self) -> Box<dyn Extension> {
+        Box::new(self.clone())
+    }
+}
+
+impl From<Instances> for Content {
+    fn from(input: Instances) -> Content {
+        (Box::new(input) as Box<dyn Extension>).into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graphics::{canvas::Rasterizable, path::Primitive};
+
+    #[test]
+    fn instances_vary_by_index() {
+        let instances = Instances::new(
+            Primitive::rectangle((10., 10.))
+                .fill(LDRColor::rgba(255, 255, 255, 200).into())
+                .finalize(),
+            vec![(0., 0.), (20., 0.), (40., 5.)],
+        )
+        .with_tints(vec![LDRColor::rgb(255, 0, 0)])
+        .with_scales(vec![1., 2.])
+        .with_opacities(vec![1., 1., 0.5]);
+        assert_eq!(instances.measure(), (50., 20.).into());
+        let contents = instances.expand().unwrap();
+        assert_eq!(contents.len(), 3);
+        let colors: Vec<LDRColor> = contents
+            .iter()
+            .map(|content| match &content.content {
+                Rasterizable::Path(path) => match &path.fill.as_ref().unwrap().content {
+                    Texture::Solid(color) => *color,
+                    _ => panic!("unexpected texture"),
+                },
+                _ => panic!("unexpected content"),
+            })
+            .collect();
+        assert_eq!(colors[0], LDRColor::rgba(255, 0, 0, 200));
+        assert_eq!(colors[1], LDRColor::rgba(255, 255, 255, 200));
+        assert_eq!(colors[2], LDRColor::rgba(255, 255, 255, 100));
+        assert_eq!(contents[1].transform.scale, Vector2::from(2.));
+        assert_eq!(contents[2].transform.position, (40., 5.).into());
+    }
+
+    #[test]
+    fn unvaried_instances_borrow_their_textures() {
+        let texture: Texture = LDRColor::black().into();
+        let instances = Instances::new(Primitive::square(1.).finalize(), vec![(0., 0.), (1., 1.)])
+            .with_opacities(vec![0.5]);
+        let variations: Vec<Variation> = instances
+            .instances()
+            .map(|(_, variation)| variation)
+            .collect();
+        assert!(matches!(
+            variations[0].texture(&texture),
+            Cow::Owned(Texture::Solid(color)) if color.a == 128
+        ));
+        assert!(matches!(variations[1].texture(&texture), Cow::Borrowed(_)));
+        assert_eq!(variations[1].color(LDRColor::black()), LDRColor::black());
+    }
+
+    #[test]
+    fn instances_serialize_as_their_expansion() {
+        let instances = Instances::new(
+            Primitive::rectangle((10., 10.))
+                .fill(LDRColor::black().into())
+                .finalize(),
+            vec![(0., 0.), (20., 0.)],
+        );
+        let saved = serde_json::to_string(&Content::from(instances)).unwrap();
+        let loaded: Content = serde_json::from_str(&saved).unwrap();
+        match &loaded.content {
+            Rasterizable::Extension(extension) => {
+                assert_eq!(extension.measure(), (30., 10.).into());
+                let contents = extension.expand().unwrap();
+                assert_eq!(contents.len(), 2);
+                assert_eq!(contents[1].transform.position, (20., 0.).into());
+            }
+            _ => panic!("unexpected content"),
+        }
+    }
+}
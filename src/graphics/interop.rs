@@ -0,0 +1,85 @@
+use crate::{
+    errors::Error,
+    graphics::{Image, ImageRepresentation, LDRColor, Texture2},
+};
+
+use image::{DynamicImage, RgbaImage};
+
+use std::convert::TryFrom;
+
+impl From<DynamicImage> for Image<LDRColor, Texture2> {
+    fn from(image: DynamicImage) -> Self {
+        let image = image.to_rgba();
+        let (width, height) = image.dimensions();
+        Image {
+            pixels: image
+                .pixels()
+                .map(|pixel| {
+                    let [r, g, b, a] = pixel.0;
+                    LDRColor::rgba(r, g, b, a)
+                })
+                .collect(),
+            format: Texture2 { width, height },
+        }
+    }
+}
+
+impl TryFrom<Image<LDRColor, Texture2>> for DynamicImage {
+    type Error = Error;
+
+    fn try_from(image: Image<LDRColor, Texture2>) -> Result<Self, Error> {
+        let Texture2 { width, height } = image.format;
+        if image.pixels.len() as u64 != u64::from(width) * u64::from(height) {
+            return Err(Error::image_size());
+        }
+        let mut data = Vec::with_capacity(image.pixels.len() * 4);
+        for pixel in &image.pixels {
+            data.extend_from_slice(&[pixel.r, pixel.g, pixel.b, pixel.a]);
+        }
+        RgbaImage::from_raw(width, height, data)
+            .map(DynamicImage::ImageRgba8)
+            .ok_or_else(Error::image_size)
+    }
+}
+
+impl TryFrom<&dyn ImageRepresentation> for DynamicImage {
+    type Error = Error;
+
+    fn try_from(image: &dyn ImageRepresentation) -> Result<Self, Error> {
+        DynamicImage::try_from(image.as_texture())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn images_round_trip() {
+        let image = Image {
+            pixels: vec![
+                LDRColor::rgba(255, 0, 0, 255),
+                LDRColor::rgba(0, 255, 0, 128),
+                LDRColor::rgba(0, 0, 255, 0),
+                LDRColor::white(),
+            ],
+            format: Texture2 {
+                width: 2,
+                height: 2,
+            },
+        };
+        let converted = DynamicImage::try_from(image.clone()).unwrap();
+        assert_eq!(converted.to_rgba().get_pixel(1, 0).0, [0, 255, 0, 128]);
+        let restored = Image::<LDRColor, Texture2>::from(converted);
+        assert_eq!(restored.pixels, image.pixels);
+        assert_eq!(
+            (restored.format.width, restored.format.height),
+            (image.format.width, image.format.height)
+        );
+        let truncated = Image {
+            pixels: vec![LDRColor::black()],
+            format: image.format,
+        };
+        assert!(DynamicImage::try_from(truncated).is_err());
+    }
+}
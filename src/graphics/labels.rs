@@ -0,0 +1,275 @@
+use crate::graphics::{Rect, Vector2};
+
+use std::cmp::Reverse;
+
+/// A label to be placed, such as the measured bounds of some [Text](crate::graphics::text::Text).
+#[derive(Clone, Copy, Debug)]
+pub struct Label {
+    /// The preferred area of the label.
+    pub rect: Rect,
+    /// The point the label describes, which leader lines are drawn to.
+    pub anchor: Vector2,
+    /// The priority of the label. Labels of higher priority are placed first, and those of equal
+    /// priority in the order they are provided.
+    pub priority: i32,
+}
+
+impl Label {
+    /// Creates a label of default priority anchored at the center of its preferred area.
+    pub fn new(rect: Rect) -> Label {
+        Label {
+            rect,
+            anchor: rect.position + rect.size / 2.,
+            priority: 0,
+        }
+    }
+    /// Sets the point the label describes.
+    pub fn with_anchor<T>(mut self, anchor: T) -> Self
+    where
+        T: Into<Vector2>,
+    {
+        self.anchor = anchor.into();
+        self
+    }
+    /// Sets the priority of the label.
+    pub fn with_priority(mut self, priority: i32) -> Self {
+        self.priority = priority;
+        self
+    }
+}
+
+/// How a label that overlaps labels already placed is resolved.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Overlap {
+    /// The label is hidden.
+    Hide,
+    /// The label is moved clear of the others by at most the provided distance in pixels, or
+    /// hidden if it can't be.
+    Nudge(f64),
+    /// As with [Overlap::Nudge], but the moved label is connected to its anchor by a leader line.
+    Leader(f64),
+}
+
+/// Where a label is shown.
+#[derive(Clone, Copy, Debug)]
+pub enum Placement {
+    /// The label is shown in the provided area.
+    Shown(Rect),
+    /// The label is shown in the provided area, with a leader line from its anchor to the
+    /// provided point on its edge.
+    Leader(Rect, Vector2),
+    /// The label is hidden.
+    Hidden,
+}
+
+/// Places labels greedily so that they don't overlap, as for the labels of maps and dense charts.
+///
+/// Placement is stateless and cheap enough to repeat every frame as labels move.
+#[derive(Clone, Copy, Debug)]
+pub struct Labeler {
+    overlap: Overlap,
+    padding: f64,
+    bounds: Option<Rect>,
+}
+
+fn overlaps(a: &Rect, b: &Rect, padding: f64) -> bool {
+    a.position.x < b.position.x + b.size.x + padding
+        && b.position.x < a.position.x + a.size.x + padding
+        && a.position.y < b.position.y + b.size.y + padding
+        && b.position.y < a.position.y + a.size.y + padding
+}
+
+/// Returns the point within a [Rect] nearest to the provided point.
+fn nearest(rect: &Rect, point: Vector2) -> Vector2 {
+    (
+        point
+            .x
+            .max(rect.position.x)
+            .min(rect.position.x + rect.size.x),
+        point
+            .y
+            .max(rect.position.y)
+            .min(rect.position.y + rect.size.y),
+    )
+        .into()
+}
+
+fn distance(a: Vector2, b: Vector2) -> f64 {
+    (a.x - b.x).hypot(a.y - b.y)
+}
+
+impl Labeler {
+    /// Creates a labeler that resolves overlapping labels as provided.
+    pub fn new(overlap: Overlap) -> Labeler {
+        Labeler {
+            overlap,
+            padding: 0.,
+            bounds: None,
+        }
+    }
+    /// Sets the least space, in pixels, kept between labels.
+    pub fn with_padding(mut self, padding: f64) -> Self {
+        self.padding = padding;
+        self
+    }
+    /// Sets the area, such as the viewport, that labels must be shown within.
+    pub fn with_bounds(mut self, bounds: Rect) -> Self {
+        self.bounds = Some(bounds);
+        self
+    }
+    /// Places the provided labels, returning the placement of each in the same order.
+    pub fn place(&self, labels: &[Label]) -> Vec<Placement> {
+        let mut order: Vec<usize> = (0..labels.len()).collect();
+        order.sort_by_key(|index| Reverse(labels[*index].priority));
+        let mut placed: Vec<Rect> = vec![];
+        let mut placements = vec![Placement::Hidden; labels.len()];
+        for index in order {
+            let label = &labels[index];
+            let fits = |rect: &Rect| {
+                self.contains(rect)
+                    && !placed
+                        .iter()
+                        .any(|other| overlaps(rect, other, self.padding))
+            };
+            if fits(&label.rect) {
+                placements[index] = Placement::Shown(label.rect);
+                placed.push(label.rect);
+                continue;
+            }
+            let max_distance = match self.overlap {
+                Overlap::Hide => continue,
+                Overlap::Nudge(max_distance) | Overlap::Leader(max_distance) => max_distance,
+            };
+            let moved = |rect: &Rect| distance(rect.position, label.rect.position);
+            let mut candidates = self.candidates(&label.rect, &placed);
+            candidates.retain(|rect| moved(rect) <= max_distance);
+            candidates.sort_by(|a, b| moved(a).total_cmp(&moved(b)));
+            if let Some(rect) = candidates.into_iter().find(|rect| fits(rect)) {
+                placements[index] = match self.overlap {
+                    Overlap::Leader(_) => Placement::Leader(rect, nearest(&rect, label.anchor)),
+                    _ => Placement::Shown(rect),
+                };
+                placed.push(rect);
+            }
+        }
+        placements
+    }
+    fn contains(&self, rect: &Rect) -> bool {
+        match self.bounds {
+            Some(bounds) => {
+                rect.position.x >= bounds.position.x
+                    && rect.position.y >= bounds.position.y
+                    && rect.position.x + rect.size.x <= bounds.position.x + bounds.size.x
+                    && rect.position.y + rect.size.y <= bounds.position.y + bounds.size.y
+            }
+            None => true,
+        }
+    }
+    /// Returns the positions at which a label just clears each placed label, horizontally,
+    /// vertically or both, and where it is moved just within the bounds.
+    fn candidates(&self, rect: &Rect, placed: &[Rect]) -> Vec<Rect> {
+        let (position, size, padding) = (rect.position, rect.size, self.padding);
+        let mut candidates = vec![];
+        for other in placed {
+            let xs = [
+                position.x,
+                other.position.x - padding - size.x,
+                other.position.x + other.size.x + padding,
+            ];
+            let ys = [
+                position.y,
+                other.position.y - padding - size.y,
+                other.position.y + other.size.y + padding,
+            ];
+            for x in &xs {
+                for y in &ys {
+                    candidates.push(Rect::new((*x, *y), size));
+                }
+            }
+        }
+        if let Some(bounds) = self.bounds {
+            let clamp = |position: f64, size: f64, start: f64, extent: f64| {
+                position.min(start + extent - size).max(start)
+            };
+            candidates.push(Rect::new(
+                (
+                    clamp(position.x, size.x, bounds.position.x, bounds.size.x),
+                    clamp(position.y, size.y, bounds.position.y, bounds.size.y),
+                ),
+                size,
+            ));
+        }
+        candidates
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn shown(placement: Placement) -> Option<Vector2> {
+        match placement {
+            Placement::Shown(rect) | Placement::Leader(rect, _) => Some(rect.position),
+            Placement::Hidden => None,
+        }
+    }
+
+    #[test]
+    fn overlapping_labels_are_hidden_by_priority() {
+        let labels = [
+            Label::new(Rect::new((0., 0.), (40., 10.))),
+            Label::new(Rect::new((30., 5.), (40., 10.))).with_priority(1),
+            Label::new(Rect::new((100., 0.), (40., 10.))),
+        ];
+        let placements = Labeler::new(Overlap::Hide).place(&labels);
+        assert_eq!(shown(placements[0]), None);
+        assert_eq!(shown(placements[1]), Some((30., 5.).into()));
+        assert_eq!(shown(placements[2]), Some((100., 0.).into()));
+        let padded = Labeler::new(Overlap::Hide).with_padding(70.).place(&labels);
+        assert_eq!(shown(padded[2]), None);
+    }
+
+    #[test]
+    fn overlapping_labels_are_nudged_clear() {
+        let labels = [
+            Label::new(Rect::new((0., 0.), (40., 10.))),
+            Label::new(Rect::new((30., 2.), (40., 10.))),
+        ];
+        let placements = Labeler::new(Overlap::Nudge(20.))
+            .with_padding(2.)
+            .place(&labels);
+        assert_eq!(shown(placements[1]), Some((30., 12.).into()));
+        let placements = Labeler::new(Overlap::Nudge(5.)).place(&labels);
+        assert_eq!(shown(placements[1]), None);
+        let placements = Labeler::new(Overlap::Nudge(20.))
+            .with_bounds(Rect::new((0., 0.), (100., 12.)))
+            .place(&labels);
+        assert_eq!(shown(placements[1]), Some((40., 2.).into()));
+    }
+
+    #[test]
+    fn leaders_join_moved_labels_to_anchors() {
+        let labels = [
+            Label::new(Rect::new((0., 0.), (40., 10.))),
+            Label::new(Rect::new((0., 0.), (40., 10.))).with_anchor((20., 5.)),
+        ];
+        match Labeler::new(Overlap::Leader(40.)).place(&labels)[1] {
+            Placement::Leader(rect, point) => {
+                assert_eq!(rect.position, (0., -10.).into());
+                assert_eq!(point, (20., 0.).into());
+            }
+            placement => panic!("unexpected placement {:?}", placement),
+        }
+    }
+
+    #[test]
+    fn unbounded_labels_are_placed() {
+        let labels = [
+            Label::new(Rect::new((0., 0.), (f64::INFINITY, 10.))),
+            Label::new(Rect::new((f64::NAN, 0.), (40., 10.))),
+            Label::new(Rect::new((0., 0.), (40., 10.))),
+        ];
+        let placements = Labeler::new(Overlap::Nudge(f64::INFINITY)).place(&labels);
+        assert_eq!(placements.len(), 3);
+    }
+}
@@ -1,15 +1,61 @@
+use serde::{de::Error, Deserialize, Deserializer, Serialize, Serializer};
+
 use std::{
     any::Any,
     borrow::Cow,
     ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign},
+    sync::atomic::{AtomicUsize, Ordering},
 };
 
+/// Provides a raster brush engine for painting applications.
+pub mod brush;
 /// Provides bindings to 2D vector graphic rendering functionality.
 pub mod canvas;
+/// Provides color parsing, color space conversions and interpolation.
+pub mod color;
+/// Provides isolines and filled bands traced from grids of scalar samples.
+pub mod contour;
+/// Provides explicit logical and physical pixel types for handling display density.
+pub mod dpi;
+mod fill;
+/// Provides density heatmaps of weighted points colored through a ramp.
+pub mod heatmap;
+/// Provides named icons loaded from SVG sprite sheets and icon fonts.
+pub mod icons;
+/// Provides many copies of a path drawn as one piece of content, varied per copy.
+pub mod instances;
+#[cfg(feature = "image")]
+mod interop;
+/// Provides greedy placement of labels that avoids overlaps.
+pub mod labels;
 /// Provides helper types that allow ergonomic construction and styling of 2D vector graphics.
 pub mod path;
+/// Provides global rendering quality tiers.
+pub mod quality;
+/// Provides selection masks that constrain painting on layers.
+pub mod selection;
+/// Provides streaming line plots of time series.
+pub mod series;
+/// Provides packing of images into sprite sheets.
+pub mod sprite;
 /// Contains types to help represent and construct styled text.
 pub mod text;
+/// Provides sparse tiled storage for very large raster images.
+pub mod tiles;
+
+static SETTINGS: AtomicUsize = AtomicUsize::new(0);
+
+/// Returns the number of times the global rendering settings, such as the quality tier, have
+/// changed, which backends compare against to know when content they drew under earlier settings
+/// needs drawing again.
+pub(crate) fn settings_generation() -> usize {
+    SETTINGS.load(Ordering::Relaxed)
+}
+
+/// Records a change to the global rendering settings.
+pub(crate) fn settings_changed() {
+    SETTINGS.fetch_add(1, Ordering::Relaxed);
+}
 
 /// A conversion to an eight-character hex color string.
 pub trait ToHexLDRColor {
@@ -39,6 +85,28 @@ impl Clone for Box<dyn ImageRepresentation> {
     }
 }
 
+/// Images are serialized as their conversion to [Image<LDRColor, Texture2>], which is what they
+/// are deserialized as.
+impl Serialize for Box<dyn ImageRepresentation> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        self.as_texture().serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Box<dyn ImageRepresentation> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Ok(Box::new(Image::<LDRColor, Texture2>::deserialize(
+            deserializer,
+        )?))
+    }
+}
+
 impl ImageRepresentation for Image<LDRColor, Texture2> {
     fn as_any(&self) -> Box<dyn Any> {
         Box::new(self.clone())
@@ -61,7 +129,7 @@ impl ImageRepresentation for Image<LDRColor, Texture2> {
 pub trait PixelFormat {}
 
 /// A standard 24-bit-depth LDR sRGB color with 8-bit alpha channel.
-#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct LDRColor {
     /// Red channel data.
     pub r: u8,
@@ -156,7 +224,7 @@ impl PixelFormat for LDRColor {}
 pub trait ImageFormat {}
 
 /// A typical two-dimensional grid image format with square pixels.
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
 pub struct Texture2 {
     /// Width of the image in pixels.
     pub width: u32,
@@ -167,7 +235,7 @@ pub struct Texture2 {
 impl ImageFormat for Texture2 {}
 
 /// A concrete image composed of format data and a flat [Vec] of pixels
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize)]
 pub struct Image<T: PixelFormat, U: ImageFormat> {
     /// Pixel data.
     pub pixels: Vec<T>,
@@ -175,8 +243,43 @@ pub struct Image<T: PixelFormat, U: ImageFormat> {
     pub format: U,
 }
 
+#[derive(Deserialize)]
+#[serde(rename = "Image")]
+struct SerializedImage<T> {
+    pixels: Vec<T>,
+    format: Texture2,
+}
+
+/// Images are only deserialized with as many pixels as their format describes, so that they can
+/// be indexed by their size.
+impl<'de, T> Deserialize<'de> for Image<T, Texture2>
+where
+    T: PixelFormat + Deserialize<'de>,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let image = SerializedImage::deserialize(deserializer)?;
+        let length = u64::from(image.format.width) * u64::from(image.format.height);
+        if image.pixels.len() as u64 != length {
+            return Err(D::Error::custom(format!(
+                "expected {} pixels for a {}x{} image, found {}",
+                length,
+                image.format.width,
+                image.format.height,
+                image.pixels.len()
+            )));
+        }
+        Ok(Image {
+            pixels: image.pixels,
+            format: image.format,
+        })
+    }
+}
+
 /// A 2-dimensional cartesian vector or point
-#[derive(Clone, Copy, Default, Debug, PartialEq)]
+#[derive(Clone, Copy, Default, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Vector2 {
     /// X-axis position.
     pub x: f64,
@@ -319,7 +422,7 @@ where
 }
 
 /// A rectilinear area of 2-dimensional cartesian space
-#[derive(Clone, Copy, Default, Debug)]
+#[derive(Clone, Copy, Default, Debug, Serialize, Deserialize)]
 pub struct Rect {
     /// The size of the delineated space.
     pub size: Vector2,
@@ -339,10 +442,60 @@ impl Rect {
             position: position.into(),
         }
     }
+    /// Returns whether the provided point lies within the [Rect], including its edges.
+    pub fn contains<T>(&self, point: T) -> bool
+    where
+        T: Into<Vector2>,
+    {
+        let point = point.into();
+        point.x >= self.position.x
+            && point.y >= self.position.y
+            && point.x <= self.position.x + self.size.x
+            && point.y <= self.position.y + self.size.y
+    }
+    /// Returns whether the [Rect] overlaps the provided [Rect], including touching edges.
+    pub fn intersects(&self, other: Rect) -> bool {
+        self.position.x <= other.position.x + other.size.x
+            && other.position.x <= self.position.x + self.size.x
+            && self.position.y <= other.position.y + other.size.y
+            && other.position.y <= self.position.y + self.size.y
+    }
+    /// Returns the smallest [Rect] containing both the [Rect] and the provided [Rect].
+    pub fn union(&self, other: Rect) -> Rect {
+        let position = Vector2::from((
+            self.position.x.min(other.position.x),
+            self.position.y.min(other.position.y),
+        ));
+        let far = Vector2::from((
+            (self.position.x + self.size.x).max(other.position.x + other.size.x),
+            (self.position.y + self.size.y).max(other.position.y + other.size.y),
+        ));
+        Rect::new(position, far - position)
+    }
+    /// Returns the smallest [Rect] containing the [Rect] once transformed, such as the bounds of
+    /// some content in the space it is positioned in.
+    pub fn transform(&self, transform: Transform2) -> Rect {
+        let [a, b, c, d, e, f] = transform.to_matrix();
+        let (near, far) = (self.position, self.position + self.size);
+        let corners = [
+            (near.x, near.y),
+            (far.x, near.y),
+            (near.x, far.y),
+            (far.x, far.y),
+        ];
+        let mut min = Vector2::from((std::f64::INFINITY, std::f64::INFINITY));
+        let mut max = Vector2::from((std::f64::NEG_INFINITY, std::f64::NEG_INFINITY));
+        for (x, y) in corners.iter().cloned() {
+            let point = Vector2::from((a * x + c * y + e, b * x + d * y + f));
+            min = (min.x.min(point.x), min.y.min(point.y)).into();
+            max = (max.x.max(point.x), max.y.max(point.y)).into();
+        }
+        Rect::new(min, max - min)
+    }
 }
 
 /// A transformation or orientation in cartesian 2-space.
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Transform2 {
     /// Position data.
     pub position: Vector2,
@@ -386,6 +539,26 @@ impl Transform2 {
             self.position.y,
         ]
     }
+    /// Maps a point into the space the [Transform2] transforms from, or returns [None] if it
+    /// collapses that space, such as with a scale of zero.
+    pub fn to_local<T>(&self, point: T) -> Option<Vector2>
+    where
+        T: Into<Vector2>,
+    {
+        let [a, b, c, d, e, f] = self.to_matrix();
+        let determinant = a * d - b * c;
+        if determinant == 0. || !determinant.is_finite() {
+            return None;
+        }
+        let point = point.into() - (e, f);
+        Some(
+            (
+                (d * point.x - c * point.y) / determinant,
+                (a * point.y - b * point.x) / determinant,
+            )
+                .into(),
+        )
+    }
     /// Translates the position by the provided offset.
     pub fn translate<T>(&mut self, offset: T) -> &mut Self
     where
@@ -414,6 +587,21 @@ impl Transform2 {
         self.position += transform.position;
         self
     }
+    /// Linearly interpolates each component between this transform, at zero, and the provided
+    /// transform, at one. Rotation turns through the smaller angle between the two, so a turn from
+    /// just short of a full revolution to just past zero takes the short way round.
+    pub fn interpolate(&self, to: Transform2, amount: f64) -> Transform2 {
+        let turn = 2. * std::f64::consts::PI;
+        let mut rotation = (to.rotation - self.rotation).rem_euclid(turn);
+        if rotation > turn / 2. {
+            rotation -= turn;
+        }
+        Transform2 {
+            position: self.position + (to.position - self.position) * amount,
+            scale: self.scale + (to.scale - self.scale) * amount,
+            rotation: self.rotation + rotation * amount,
+        }
+    }
 }
 
 impl Default for Transform2 {
@@ -437,3 +625,68 @@ impl From<(f64, f64)> for Transform2 {
         Vector2::from(input).into()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn images_deserialize_with_as_many_pixels_as_their_size() {
+        let image = Image {
+            pixels: vec![LDRColor::white(); 6],
+            format: Texture2 {
+                width: 3,
+                height: 2,
+            },
+        };
+        let saved = serde_json::to_string(&image).unwrap();
+        let loaded: Image<LDRColor, Texture2> = serde_json::from_str(&saved).unwrap();
+        assert_eq!(loaded.pixels, image.pixels);
+        let truncated = saved.replace(r#""height":2"#, r#""height":3"#);
+        assert!(serde_json::from_str::<Image<LDRColor, Texture2>>(&truncated).is_err());
+    }
+
+    #[test]
+    fn rects_combine_and_transform() {
+        let a = Rect::new((0., 0.), (10., 10.));
+        let b = Rect::new((5., 20.), (10., 5.));
+        assert!(!a.intersects(b));
+        assert!(a.intersects(Rect::new((10., 10.), (1., 1.))));
+        let union = a.union(b);
+        assert_eq!(
+            (union.position, union.size),
+            ((0., 0.).into(), (15., 25.).into())
+        );
+        let rotated = a.transform(
+            Transform2::from((5., 0.))
+                .with_scale(Vector2::from(2.))
+                .with_rotation(std::f64::consts::FRAC_PI_2),
+        );
+        let close =
+            |a: Vector2, b: (f64, f64)| (a.x - b.0).abs() < 1e-9 && (a.y - b.1).abs() < 1e-9;
+        assert!(close(rotated.position, (-15., 0.)));
+        assert!(close(rotated.size, (20., 20.)));
+    }
+
+    #[test]
+    fn transforms_interpolate_through_the_smaller_angle() {
+        let degrees = |degrees: f64| Transform2::default().with_rotation(degrees.to_radians());
+        let close = |a: f64, b: f64| (a - b).abs() < 1e-9;
+        let rotation = |from: f64, to: f64, amount: f64| {
+            degrees(from)
+                .interpolate(degrees(to), amount)
+                .rotation
+                .to_degrees()
+        };
+        assert!(close(rotation(359., 1., 0.5), 360.));
+        assert!(close(rotation(1., 359., 0.5), 0.));
+        assert!(close(rotation(-170., 170., 0.25), -175.));
+        assert!(close(rotation(0., 90., 0.5), 45.));
+        assert!(close(rotation(10., 730., 1.), 10.));
+        let from = Transform2::from((0., 10.)).with_scale(Vector2::from(1.));
+        let to = Transform2::from((20., 30.)).with_scale(Vector2::from(3.));
+        let halfway = from.interpolate(to, 0.5);
+        assert_eq!(halfway.position, (10., 20.).into());
+        assert_eq!(halfway.scale, (2., 2.).into());
+    }
+}
@@ -1,14 +1,46 @@
-use crate::graphics::{ImageRepresentation, LDRColor, Rect, Vector2};
+use crate::graphics::{
+    color::{self, Compositing},
+    ImageRepresentation, LDRColor, Rect, Transform2, Vector2,
+};
 
 use crate::errors::Error;
 
+use serde::{Deserialize, Serialize};
+
+use std::collections::HashMap;
 use std::fmt;
 use std::fmt::{Debug, Formatter};
+use std::sync::atomic::{AtomicU8, Ordering};
 
 const CUBIC_BEZIER_CIRCLE_APPROXIMATION_RATIO: f64 = 0.552_228_474;
 
+const FLATTENING_TOLERANCE: f64 = 0.05;
+
+/// The finest tolerance curves are flattened to, so that tolerances at or near zero can't divide
+/// a curve into an unbounded number of lines.
+const MINIMUM_FLATTENING_TOLERANCE: f64 = 1e-4;
+
+/// The greatest number of lines a single curve is flattened into.
+const MAXIMUM_FLATTENING_STEPS: f64 = 65_536.;
+
+/// The greatest number of periods a gradient is unrolled into where a backend lacks native
+/// support for [SpreadMode].
+const MAXIMUM_SPREAD_PERIODS: i64 = 256;
+
+/// The number of stops each span of a gradient is divided into where a backend interpolates
+/// gradients in sRGB but [Compositing] calls for linear light.
+const LINEAR_LIGHT_SUBDIVISIONS: u32 = 16;
+
+/// The greatest number of lines or ticks placed across a grid or ruler, beyond which their spacing
+/// is widened to a multiple of itself.
+const MAXIMUM_GUIDE_LINES: f64 = 4_096.;
+
+/// The greatest number of squares a checkerboard is divided into, beyond which they are enlarged
+/// to a multiple of their size.
+const MAXIMUM_CHECKERBOARD_SQUARES: f64 = 65_536.;
+
 /// A path segment.
-#[derive(Clone, Copy, Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
 pub enum Segment {
     /// A line to the given point.
     LineTo(Vector2),
@@ -18,10 +50,70 @@ pub enum Segment {
     QuadraticTo(Vector2, Vector2),
     /// A cubic bezier curve to the given point with the given handles.
     CubicTo(Vector2, Vector2, Vector2),
+    /// A straight line back to the start of the current subpath, closing it.
+    ClosePath,
+}
+
+impl Segment {
+    pub(crate) fn map_points<F>(self, map: F) -> Segment
+    where
+        F: Fn(Vector2) -> Vector2,
+    {
+        match self {
+            Segment::CubicTo(point, handle_1, handle_2) => {
+                Segment::CubicTo(map(point), map(handle_1), map(handle_2))
+            }
+            Segment::QuadraticTo(point, handle) => Segment::QuadraticTo(map(point), map(handle)),
+            Segment::MoveTo(point) => Segment::MoveTo(map(point)),
+            Segment::LineTo(point) => Segment::LineTo(map(point)),
+            Segment::ClosePath => Segment::ClosePath,
+        }
+    }
+    fn is_finite(&self) -> bool {
+        let finite = |point: &Vector2| point.x.is_finite() && point.y.is_finite();
+        match self {
+            Segment::CubicTo(point, handle_1, handle_2) => {
+                finite(point) && finite(handle_1) && finite(handle_2)
+            }
+            Segment::QuadraticTo(point, handle) => finite(point) && finite(handle),
+            Segment::MoveTo(point) | Segment::LineTo(point) => finite(point),
+            Segment::ClosePath => true,
+        }
+    }
+    fn is_degenerate(&self, current: Vector2) -> bool {
+        match *self {
+            Segment::CubicTo(point, handle_1, handle_2) => {
+                point == current && handle_1 == current && handle_2 == current
+            }
+            Segment::QuadraticTo(point, handle) => point == current && handle == current,
+            Segment::LineTo(point) => point == current,
+            Segment::MoveTo(_) | Segment::ClosePath => false,
+        }
+    }
+}
+
+/// A problem with the geometry of a [Path], as found by [Path::validate].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Issue {
+    /// The segment at the given index has a coordinate that is NaN or infinite.
+    NonFinite(usize),
+    /// The segment at the given index does not move from the end of the previous segment.
+    ZeroLength(usize),
+    /// The outline crosses or touches itself at the given point.
+    SelfIntersection(Vector2),
+}
+
+/// The direction in which a path winds, as measured in a y-up coordinate space.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Winding {
+    /// Clockwise winding, indicated by a negative signed area.
+    Clockwise,
+    /// Counter-clockwise winding, indicated by a positive or zero signed area.
+    CounterClockwise,
 }
 
 /// A gradient color stop.
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
 pub struct GradientStop {
     /// The offset, as a floating point value between zero and one, of the gradient stop.
     /// Zero represents the start of the gradient; one represents the end.
@@ -41,8 +133,97 @@ impl GradientStop {
     }
 }
 
+/// How a gradient continues beyond its start and end.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SpreadMode {
+    /// The colors of the first and last stops extend indefinitely.
+    Pad,
+    /// The gradient repeats from its start.
+    Repeat,
+    /// The gradient repeats, alternating direction each time.
+    Reflect,
+}
+
+impl Default for SpreadMode {
+    fn default() -> Self {
+        SpreadMode::Pad
+    }
+}
+
+static DITHER: AtomicU8 = AtomicU8::new(0);
+
+/// The dithering applied when gradients and blurs are quantized to eight bits per channel, which
+/// hides the banding of large, subtle gradients.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Dither {
+    /// Colors are rounded to the nearest representable value.
+    None,
+    /// An 8x8 Bayer matrix, which is fast and stable but forms a faint regular pattern.
+    Ordered,
+    /// Interleaved gradient noise, which approximates blue noise and forms no visible pattern.
+    BlueNoise,
+}
+
+impl Dither {
+    /// Returns the global dithering, used wherever a texture doesn't specify its own.
+    pub fn current() -> Dither {
+        match DITHER.load(Ordering::Relaxed) {
+            1 => Dither::Ordered,
+            2 => Dither::BlueNoise,
+            _ => Dither::None,
+        }
+    }
+    /// Sets the global dithering.
+    pub fn set_current(dither: Dither) {
+        DITHER.store(
+            match dither {
+                Dither::None => 0,
+                Dither::Ordered => 1,
+                Dither::BlueNoise => 2,
+            },
+            Ordering::Relaxed,
+        );
+        super::settings_changed();
+    }
+    /// Returns the threshold between zero and one above which the fractional part of a value at
+    /// the given pixel is rounded up.
+    pub(crate) fn threshold(self, x: i32, y: i32) -> f64 {
+        match self {
+            Dither::None => 0.5,
+            Dither::Ordered => {
+                let (x, y) = (x & 7, y & 7);
+                let xor = x ^ y;
+                // Interleaves the bits of x ^ y and y, most significant first.
+                let index = ((xor & 1) << 5)
+                    | ((y & 1) << 4)
+                    | ((xor & 2) << 2)
+                    | ((y & 2) << 1)
+                    | ((xor & 4) >> 1)
+                    | ((y & 4) >> 2);
+                (f64::from(index) + 0.5) / 64.
+            }
+            Dither::BlueNoise => {
+                let value = 0.067_110_56 * f64::from(x) + 0.005_837_15 * f64::from(y);
+                (52.982_918_9 * value.fract()).fract()
+            }
+        }
+    }
+    /// Quantizes a value between zero and one to eight bits at the given pixel.
+    pub(crate) fn quantize(self, value: f64, x: i32, y: i32) -> u8 {
+        (value * 255. + 1. - self.threshold(x, y))
+            .floor()
+            .clamp(0., 255.) as u8
+    }
+}
+
+impl Default for Dither {
+    fn default() -> Self {
+        Dither::None
+    }
+}
+
 /// A linear gradient.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct LinearGradient {
     /// Associated color stops.
     pub stops: Vec<GradientStop>,
@@ -50,10 +231,104 @@ pub struct LinearGradient {
     pub start: Vector2,
     /// The end point.
     pub end: Vector2,
+    /// How the gradient continues beyond its start and end.
+    pub spread: SpreadMode,
+    /// A transformation from the space of the gradient into the space of the path it fills.
+    pub transform: Option<Transform2>,
+    /// The dithering of the gradient, or [None] to use the global [Dither].
+    pub dither: Option<Dither>,
+}
+
+impl LinearGradient {
+    /// Sets how the gradient continues beyond its start and end.
+    pub fn with_spread(mut self, spread: SpreadMode) -> Self {
+        self.spread = spread;
+        self
+    }
+    /// Sets the transformation from the space of the gradient into the space of the path.
+    pub fn with_transform(mut self, transform: Transform2) -> Self {
+        self.transform = Some(transform);
+        self
+    }
+    /// Sets the dithering of the gradient, overriding the global [Dither].
+    pub fn with_dither(mut self, dither: Dither) -> Self {
+        self.dither = Some(dither);
+        self
+    }
+    /// Returns the dithering that applies to the gradient.
+    pub(crate) fn dither(&self) -> Dither {
+        self.dither.unwrap_or_else(Dither::current)
+    }
+    /// Returns the color at an offset along the gradient, where zero is its start and one its end,
+    /// continuing beyond them according to its [SpreadMode].
+    pub fn sample(&self, offset: f64) -> LDRColor {
+        let [r, g, b, a] = sample_stops(&self.stops, spread_offset(self.spread, offset));
+        let byte = |channel: f64| (channel * 255.).round() as u8;
+        LDRColor::rgba(byte(r), byte(g), byte(b), byte(a))
+    }
+    /// Produces an equivalent padded gradient without a transform that is accurate within the
+    /// provided bounds, for backends without native spread and transform support.
+    pub(crate) fn resolve(&self, bounds: Rect) -> LinearGradient {
+        let (mut start, mut direction) = (self.start, self.end - self.start);
+        if let Some(transform) = self.transform {
+            let matrix = transform.to_matrix();
+            let determinant = matrix[0] * matrix[3] - matrix[1] * matrix[2];
+            start = transform_point(matrix, start);
+            let gradient = Vector2::from((
+                (matrix[3] * direction.x - matrix[1] * direction.y) / determinant,
+                (matrix[0] * direction.y - matrix[2] * direction.x) / determinant,
+            )) / (direction.x * direction.x + direction.y * direction.y);
+            direction = gradient / (gradient.x * gradient.x + gradient.y * gradient.y);
+        }
+        let length = direction.x * direction.x + direction.y * direction.y;
+        let (mut from, mut to) = (0, 1);
+        if self.spread != SpreadMode::Pad && length > 0. {
+            let (min, max) = (bounds.position, bounds.position + bounds.size);
+            let offsets: Vec<f64> = [
+                (min.x, min.y),
+                (max.x, min.y),
+                (max.x, max.y),
+                (min.x, max.y),
+            ]
+            .iter()
+            .map(|corner| {
+                let offset = Vector2::from(*corner) - start;
+                (offset.x * direction.x + offset.y * direction.y) / length
+            })
+            .collect();
+            from = offsets.iter().cloned().fold(0., f64::min).floor() as i64;
+            to = offsets.iter().cloned().fold(1., f64::max).ceil() as i64;
+        }
+        let (from, to) = spread_periods(from, to);
+        LinearGradient {
+            stops: spread_stops(&self.stops, self.spread, from, to),
+            start: start + direction * from as f64,
+            end: start + direction * to as f64,
+            spread: SpreadMode::Pad,
+            transform: None,
+            dither: self.dither,
+        }
+    }
+    /// Returns the color of the gradient at a point in the space of the path as red, green, blue
+    /// and alpha channels between zero and one, or [None] where the gradient isn't drawn.
+    pub(crate) fn color_at(&self, point: Vector2) -> Option<[f64; 4]> {
+        let point = gradient_point(self.transform, point)?;
+        let direction = self.end - self.start;
+        let length = direction.x * direction.x + direction.y * direction.y;
+        if length == 0. {
+            return None;
+        }
+        let offset = point - self.start;
+        let offset = (offset.x * direction.x + offset.y * direction.y) / length;
+        Some(sample_stops(
+            &self.stops,
+            spread_offset(self.spread, offset),
+        ))
+    }
 }
 
 /// A drop shadow.
-#[derive(Clone, Copy, Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Shadow {
     /// The color of the shadow.
     pub color: LDRColor,
@@ -95,8 +370,65 @@ impl Shadow {
     }
 }
 
+/// A filter effect composited beneath a path.
+///
+/// Filters are lowered to [Shadow]s, so they are rendered by each target's shadow pipeline.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Filter {
+    /// A glow around the outline of the path, as used for neon and highlight effects.
+    Glow {
+        /// The color of the glow.
+        color: LDRColor,
+        /// The radius, in fractional pixels, the glow extends beyond the path.
+        radius: f64,
+        /// The intensity of the glow, where values above one stack additional passes, up to
+        /// [MAX_GLOW_PASSES].
+        strength: f64,
+    },
+}
+
+/// The greatest number of shadows a [Filter::Glow] is stacked from, beyond which more strength
+/// makes no visible difference.
+pub const MAX_GLOW_PASSES: usize = 8;
+
+impl Filter {
+    /// Returns the shadows that render this filter, ordered from the bottom up.
+    pub fn shadows(&self) -> Vec<Shadow> {
+        match *self {
+            Filter::Glow {
+                color,
+                radius,
+                strength,
+            } => {
+                // Glows without a finite size or strength can't be drawn.
+                if !strength.is_finite() || strength <= 0. || !radius.is_finite() || color.a == 0 {
+                    return vec![];
+                }
+                let strength = strength.min(MAX_GLOW_PASSES as f64);
+                let radius = radius.max(0.);
+                let shadow = Shadow::new(color).spread(radius / 2.).blur(radius);
+                let passes = strength.ceil() as usize;
+                let remainder = strength - (passes - 1) as f64;
+                (0..passes)
+                    .map(|pass| {
+                        if pass + 1 == passes {
+                            let alpha = (f64::from(color.a) * remainder).round() as u8;
+                            Shadow {
+                                color: color.with_alpha(alpha),
+                                ..shadow
+                            }
+                        } else {
+                            shadow
+                        }
+                    })
+                    .collect()
+            }
+        }
+    }
+}
+
 /// A radial gradient.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct RadialGradient {
     /// Associated color stops.
     pub stops: Vec<GradientStop>,
@@ -108,10 +440,516 @@ pub struct RadialGradient {
     pub end: Vector2,
     /// The radius at the end.
     pub end_radius: f64,
+    /// How the gradient continues beyond its start and end.
+    pub spread: SpreadMode,
+    /// A transformation from the space of the gradient into the space of the path it fills.
+    pub transform: Option<Transform2>,
+    /// The dithering of the gradient, or [None] to use the global [Dither].
+    pub dither: Option<Dither>,
+}
+
+impl RadialGradient {
+    /// Sets how the gradient continues beyond its start and end.
+    pub fn with_spread(mut self, spread: SpreadMode) -> Self {
+        self.spread = spread;
+        self
+    }
+    /// Sets the transformation from the space of the gradient into the space of the path.
+    pub fn with_transform(mut self, transform: Transform2) -> Self {
+        self.transform = Some(transform);
+        self
+    }
+    /// Sets the dithering of the gradient, overriding the global [Dither].
+    pub fn with_dither(mut self, dither: Dither) -> Self {
+        self.dither = Some(dither);
+        self
+    }
+    /// Returns the dithering that applies to the gradient.
+    pub(crate) fn dither(&self) -> Dither {
+        self.dither.unwrap_or_else(Dither::current)
+    }
+    /// Returns the color at an offset along the gradient, where zero is its start and one its end,
+    /// continuing beyond them according to its [SpreadMode].
+    pub fn sample(&self, offset: f64) -> LDRColor {
+        let [r, g, b, a] = sample_stops(&self.stops, spread_offset(self.spread, offset));
+        let byte = |channel: f64| (channel * 255.).round() as u8;
+        LDRColor::rgba(byte(r), byte(g), byte(b), byte(a))
+    }
+    /// Produces an equivalent padded gradient without a transform that is accurate within the
+    /// provided bounds, for backends without native spread and transform support.
+    ///
+    /// A transform that scales unevenly would make the gradient elliptical, so its radii are
+    /// scaled by the mean of the scale factors instead. Gradients whose circles grow no faster than
+    /// they move never cover the bounds and are not unrolled beyond their end.
+    pub(crate) fn resolve(&self, bounds: Rect) -> RadialGradient {
+        let (mut start, mut end) = (self.start, self.end);
+        let (mut start_radius, mut end_radius) = (self.start_radius, self.end_radius);
+        if let Some(transform) = self.transform {
+            let matrix = transform.to_matrix();
+            let scale = (matrix[0] * matrix[3] - matrix[1] * matrix[2]).abs().sqrt();
+            start = transform_point(matrix, start);
+            end = transform_point(matrix, end);
+            start_radius *= scale;
+            end_radius *= scale;
+        }
+        let (growth, movement) = (end_radius - start_radius, end - start);
+        let movement_length = movement.x.hypot(movement.y);
+        let (mut from, mut to) = (0, 1);
+        if self.spread != SpreadMode::Pad && growth > movement_length {
+            let (min, max) = (bounds.position, bounds.position + bounds.size);
+            to = [
+                (min.x, min.y),
+                (max.x, min.y),
+                (max.x, max.y),
+                (min.x, max.y),
+            ]
+            .iter()
+            .map(|corner| {
+                let offset = Vector2::from(*corner) - start;
+                (offset.x.hypot(offset.y) - start_radius) / (growth - movement_length)
+            })
+            .fold(1., f64::max)
+            .ceil() as i64;
+            from = -(start_radius / growth).floor() as i64;
+        }
+        let (from, to) = spread_periods(from, to);
+        RadialGradient {
+            stops: spread_stops(&self.stops, self.spread, from, to),
+            start: start + movement * from as f64,
+            start_radius: start_radius + growth * from as f64,
+            end: start + movement * to as f64,
+            end_radius: start_radius + growth * to as f64,
+            spread: SpreadMode::Pad,
+            transform: None,
+            dither: self.dither,
+        }
+    }
+    /// Returns the color of the gradient at a point in the space of the path as red, green, blue
+    /// and alpha channels between zero and one, or [None] where the gradient isn't drawn.
+    ///
+    /// The offset of a point is that of the largest circle with a non-negative radius that passes
+    /// through it, matching the two-point conical gradients of Cairo and the canvas API.
+    pub(crate) fn color_at(&self, point: Vector2) -> Option<[f64; 4]> {
+        let point = gradient_point(self.transform, point)? - self.start;
+        let (movement, growth) = (self.end - self.start, self.end_radius - self.start_radius);
+        let a = movement.x * movement.x + movement.y * movement.y - growth * growth;
+        let b = point.x * movement.x + point.y * movement.y + self.start_radius * growth;
+        let c = point.x * point.x + point.y * point.y - self.start_radius * self.start_radius;
+        let candidates = if a.abs() < std::f64::EPSILON {
+            if b == 0. {
+                return None;
+            }
+            [Some(c / (2. * b)), None]
+        } else {
+            let discriminant = b * b - a * c;
+            if discriminant < 0. {
+                return None;
+            }
+            let root = discriminant.sqrt();
+            let (first, second) = ((b + root) / a, (b - root) / a);
+            [Some(first.max(second)), Some(first.min(second))]
+        };
+        let offset = candidates
+            .iter()
+            .flatten()
+            .cloned()
+            .find(|offset| self.start_radius + growth * offset >= 0.)?;
+        Some(sample_stops(
+            &self.stops,
+            spread_offset(self.spread, offset),
+        ))
+    }
+}
+
+/// Maps a point from the space of a path into the space of a gradient with the given transform.
+///
+/// The matrix of the transform is read by rows, as the native backend applies it to gradients,
+/// so that dithered gradients are oriented like those it draws without dithering.
+fn gradient_point(transform: Option<Transform2>, point: Vector2) -> Option<Vector2> {
+    let matrix = match transform {
+        Some(transform) => transform.to_matrix(),
+        None => return Some(point),
+    };
+    let determinant = matrix[0] * matrix[3] - matrix[1] * matrix[2];
+    if determinant == 0. {
+        return None;
+    }
+    let offset = point - Vector2::from((matrix[4], matrix[5]));
+    Some(
+        (
+            (matrix[3] * offset.x - matrix[1] * offset.y) / determinant,
+            (matrix[0] * offset.y - matrix[2] * offset.x) / determinant,
+        )
+            .into(),
+    )
+}
+
+/// Maps an offset along a gradient into its first period.
+fn spread_offset(spread: SpreadMode, offset: f64) -> f64 {
+    match spread {
+        SpreadMode::Pad => offset.clamp(0., 1.),
+        SpreadMode::Repeat => offset - offset.floor(),
+        SpreadMode::Reflect => {
+            let offset = offset.rem_euclid(2.);
+            if offset > 1. {
+                2. - offset
+            } else {
+                offset
+            }
+        }
+    }
+}
+
+/// Interpolates the color of a set of ordered stops at an offset between zero and one.
+pub(crate) fn sample_stops(stops: &[GradientStop], offset: f64) -> [f64; 4] {
+    sample_stops_in(stops, offset, Compositing::current())
+}
+
+fn sample_stops_in(stops: &[GradientStop], offset: f64, compositing: Compositing) -> [f64; 4] {
+    let channels = |color: LDRColor| {
+        [
+            f64::from(color.r) / 255.,
+            f64::from(color.g) / 255.,
+            f64::from(color.b) / 255.,
+            f64::from(color.a) / 255.,
+        ]
+    };
+    let index = match stops.iter().position(|stop| stop.offset >= offset) {
+        Some(index) => index,
+        None => return stops.last().map_or([0.; 4], |stop| channels(stop.color)),
+    };
+    let to = &stops[index];
+    let from = match index.checked_sub(1) {
+        Some(previous) if to.offset > stops[previous].offset => &stops[previous],
+        _ => return channels(to.color),
+    };
+    let amount = (offset - from.offset) / (to.offset - from.offset);
+    let (from, to) = (channels(from.color), channels(to.color));
+    let linear = compositing == Compositing::LinearLight;
+    let mut color = [0.; 4];
+    for channel in 0..4 {
+        color[channel] = if linear && channel < 3 {
+            let (from, to) = (
+                color::to_linear(from[channel]),
+                color::to_linear(to[channel]),
+            );
+            color::from_linear(from + (to - from) * amount)
+        } else {
+            from[channel] + (to[channel] - from[channel]) * amount
+        };
+    }
+    color
+}
+
+/// Returns stops that a backend interpolating in sRGB renders in the current [Compositing] space,
+/// by subdividing each span between stops when compositing in linear light.
+pub(crate) fn compositing_stops(stops: &[GradientStop]) -> Vec<GradientStop> {
+    match Compositing::current() {
+        Compositing::Srgb => stops.to_vec(),
+        Compositing::LinearLight => linear_light_stops(stops),
+    }
+}
+
+fn linear_light_stops(stops: &[GradientStop]) -> Vec<GradientStop> {
+    let byte = |channel: f64| (channel * 255.).round() as u8;
+    let mut subdivided = vec![];
+    for (index, stop) in stops.iter().enumerate() {
+        let previous = index.checked_sub(1).map(|previous| &stops[previous]);
+        if let Some(previous) = previous.filter(|previous| previous.offset < stop.offset) {
+            for step in 1..LINEAR_LIGHT_SUBDIVISIONS {
+                let offset = previous.offset
+                    + (stop.offset - previous.offset) * f64::from(step)
+                        / f64::from(LINEAR_LIGHT_SUBDIVISIONS);
+                let [r, g, b, a] =
+                    sample_stops_in(&stops[index - 1..=index], offset, Compositing::LinearLight);
+                subdivided.push(GradientStop {
+                    offset,
+                    color: LDRColor::rgba(byte(r), byte(g), byte(b), byte(a)),
+                });
+            }
+        }
+        subdivided.push(*stop);
+    }
+    subdivided
+}
+
+fn transform_point(matrix: [f64; 6], point: Vector2) -> Vector2 {
+    (
+        matrix[0] * point.x + matrix[2] * point.y + matrix[4],
+        matrix[1] * point.x + matrix[3] * point.y + matrix[5],
+    )
+        .into()
+}
+
+fn spread_periods(from: i64, to: i64) -> (i64, i64) {
+    let excess = (to - from - MAXIMUM_SPREAD_PERIODS).max(0);
+    (from + excess / 2, to - excess / 2 - excess % 2)
+}
+
+fn spread_stops(
+    stops: &[GradientStop],
+    spread: SpreadMode,
+    from: i64,
+    to: i64,
+) -> Vec<GradientStop> {
+    let periods = (to - from) as f64;
+    (from..to)
+        .flat_map(|period| {
+            let reflected = spread == SpreadMode::Reflect && period.rem_euclid(2) == 1;
+            let offset = (period - from) as f64;
+            let period_stops: Vec<GradientStop> = if reflected {
+                stops
+                    .iter()
+                    .rev()
+                    .map(|stop| GradientStop {
+                        offset: (offset + 1. - stop.offset) / periods,
+                        color: stop.color,
+                    })
+                    .collect()
+            } else {
+                stops
+                    .iter()
+                    .map(|stop| GradientStop {
+                        offset: (offset + stop.offset) / periods,
+                        color: stop.color,
+                    })
+                    .collect()
+            };
+            period_stops
+        })
+        .collect()
+}
+
+/// The arrangement of marks in a [Hatch] pattern.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum HatchStyle {
+    /// Parallel lines.
+    Lines,
+    /// Two sets of parallel lines crossing at right angles.
+    CrossHatch,
+    /// A square grid of round dots.
+    Dots,
+}
+
+/// A procedural pattern of lines or dots, generated at render time at the resolution of the display.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Hatch {
+    /// The arrangement of the marks.
+    pub style: HatchStyle,
+    /// The color of the marks.
+    pub color: LDRColor,
+    /// The color behind the marks.
+    pub background: LDRColor,
+    /// The distance between adjacent lines or dot centers.
+    pub spacing: f64,
+    /// The width of the lines or the diameter of the dots.
+    pub width: f64,
+    /// The rotation of the pattern in radians. At zero lines are horizontal.
+    pub angle: f64,
+}
+
+impl Hatch {
+    /// Creates a pattern of diagonal lines.
+    pub fn lines(color: LDRColor) -> Hatch {
+        Hatch {
+            style: HatchStyle::Lines,
+            color,
+            background: LDRColor::rgba(0, 0, 0, 0),
+            spacing: 8.,
+            width: 1.,
+            angle: std::f64::consts::FRAC_PI_4,
+        }
+    }
+    /// Creates a pattern of diagonal lines crossing at right angles.
+    pub fn cross_hatch(color: LDRColor) -> Hatch {
+        Hatch {
+            style: HatchStyle::CrossHatch,
+            ..Hatch::lines(color)
+        }
+    }
+    /// Creates a square grid of dots.
+    pub fn dots(color: LDRColor) -> Hatch {
+        Hatch {
+            style: HatchStyle::Dots,
+            width: 2.,
+            angle: 0.,
+            ..Hatch::lines(color)
+        }
+    }
+    /// Sets the distance between adjacent lines or dot centers.
+    pub fn with_spacing(mut self, spacing: f64) -> Self {
+        self.spacing = spacing;
+        self
+    }
+    /// Sets the width of the lines or the diameter of the dots.
+    pub fn with_width(mut self, width: f64) -> Self {
+        self.width = width;
+        self
+    }
+    /// Sets the rotation of the pattern in radians.
+    pub fn with_angle(mut self, angle: f64) -> Self {
+        self.angle = angle;
+        self
+    }
+    /// Sets the color behind the marks, which is transparent by default.
+    pub fn with_background(mut self, background: LDRColor) -> Self {
+        self.background = background;
+        self
+    }
+    /// Returns the marks within a single unrotated tile of the pattern. The tile is a square with
+    /// sides of length [Hatch::spacing] positioned at the origin, which repeated and then rotated by
+    /// [Hatch::angle] forms the complete pattern.
+    pub fn tile(&self) -> Path {
+        let fill = Fill {
+            content: self.color.into(),
+        };
+        let center = self.spacing / 2.;
+        let half_width = (self.width / 2.).min(center);
+        match self.style {
+            HatchStyle::Dots => Primitive::circle(half_width)
+                .fill(fill)
+                .finalize()
+                .with_offset((center - half_width, center - half_width)),
+            HatchStyle::Lines | HatchStyle::CrossHatch => {
+                let mut builder = Builder::new()
+                    .move_to((0., center - half_width))
+                    .line_to((self.spacing, center - half_width))
+                    .line_to((self.spacing, center + half_width))
+                    .line_to((0., center + half_width))
+                    .close();
+                if self.style == HatchStyle::CrossHatch {
+                    // Wound in the same direction as the first line so the crossing isn't cut out.
+                    builder = builder
+                        .move_to((center + half_width, 0.))
+                        .line_to((center + half_width, self.spacing))
+                        .line_to((center - half_width, self.spacing))
+                        .line_to((center - half_width, 0.))
+                        .close();
+                }
+                builder.done().fill(fill).finalize()
+            }
+        }
+    }
+}
+
+/// How an image texture covers the area outside of the image.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Tiling {
+    /// The area outside of the image is left transparent.
+    None,
+    /// The image repeats in both directions.
+    Repeat,
+    /// The image repeats in both directions, mirrored in every other row and column.
+    Mirror,
+    /// The pixels at the edges of the image extend indefinitely.
+    Clamp,
+}
+
+impl Default for Tiling {
+    fn default() -> Self {
+        Tiling::None
+    }
+}
+
+/// An image used as a texture, placed relative to the bounds of the path it is applied to.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ImageTexture {
+    /// The image.
+    pub image: Box<dyn ImageRepresentation>,
+    /// How the image covers the area outside of itself.
+    pub tiling: Tiling,
+    /// A transformation of the image about its anchor.
+    pub transform: Transform2,
+    /// The point, as a fraction of both the path bounds and the image size, at which the image is
+    /// aligned with the path. At the default of zero the top left corners are aligned.
+    pub anchor: Vector2,
+    /// The area of the image that is used, such as a single sprite packed into an atlas, in the
+    /// units of [ImageRepresentation::get_size]. The whole image is used if this is [None].
+    pub source: Option<Rect>,
+}
+
+impl ImageTexture {
+    /// Creates an untiled texture aligned with the top left of the path bounds.
+    pub fn new(image: Box<dyn ImageRepresentation>) -> ImageTexture {
+        ImageTexture {
+            image,
+            tiling: Tiling::default(),
+            transform: Transform2::default(),
+            anchor: Vector2::default(),
+            source: None,
+        }
+    }
+    /// Sets how the image covers the area outside of itself.
+    pub fn with_tiling(mut self, tiling: Tiling) -> Self {
+        self.tiling = tiling;
+        self
+    }
+    /// Sets the transformation of the image about its anchor.
+    pub fn with_transform(mut self, transform: Transform2) -> Self {
+        self.transform = transform;
+        self
+    }
+    /// Sets the point, as a fraction of both the path bounds and the image size, at which the
+    /// image is aligned with the path.
+    pub fn with_anchor<T>(mut self, anchor: T) -> Self
+    where
+        T: Into<Vector2>,
+    {
+        self.anchor = anchor.into();
+        self
+    }
+    /// Uses only an area of the image, such as a single sprite packed into an atlas, in place of
+    /// the whole image. Tiling repeats and extends the area rather than the image.
+    pub fn with_source(mut self, source: Rect) -> Self {
+        self.source = Some(source);
+        self
+    }
+    /// Returns the area of the image that is used, limited to the bounds of the image.
+    pub(crate) fn source(&self) -> Rect {
+        let size = self.image.get_size();
+        match self.source {
+            Some(source) => {
+                let position = Vector2::from((
+                    source.position.x.clamp(0., size.x),
+                    source.position.y.clamp(0., size.y),
+                ));
+                let end = source.position + source.size;
+                Rect::new(
+                    position,
+                    (
+                        end.x.clamp(position.x, size.x) - position.x,
+                        end.y.clamp(position.y, size.y) - position.y,
+                    ),
+                )
+            }
+            None => Rect::new(Vector2::default(), size),
+        }
+    }
+    /// Returns the points at which the image is aligned with the path, first in the space of the
+    /// path and then in the space of the source area of the image. The transform is applied about
+    /// the latter.
+    pub(crate) fn anchoring(&self, bounds: Rect) -> (Vector2, Vector2) {
+        (
+            bounds.position + self.anchor * bounds.size,
+            self.anchor * self.source().size,
+        )
+    }
+}
+
+impl Debug for ImageTexture {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ImageTexture")
+            .field("size", &self.image.get_size())
+            .field("tiling", &self.tiling)
+            .field("transform", &self.transform)
+            .field("anchor", &self.anchor)
+            .field("source", &self.source)
+            .finish()
+    }
 }
 
 /// A texture used as the content for a stroke or fill.
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
 pub enum Texture {
     /// A solid color texture.
     Solid(LDRColor),
@@ -120,7 +958,9 @@ pub enum Texture {
     /// A radial gradient texture.
     RadialGradient(RadialGradient),
     /// An image texture.
-    Image(Box<dyn ImageRepresentation>),
+    Image(ImageTexture),
+    /// A procedural hatching or dot pattern texture.
+    Hatch(Hatch),
 }
 
 impl From<LDRColor> for Texture {
@@ -138,20 +978,45 @@ impl Debug for Texture {
                 Texture::Solid(color) => format!("Solid {:?}", color),
                 Texture::LinearGradient(gradient) => format!("LinearGradient {:?}", gradient),
                 Texture::RadialGradient(gradient) => format!("RadialGradient {:?}", gradient),
-                Texture::Image(_) => "Image".to_owned(),
+                Texture::Image(texture) => format!("Image {:?}", texture),
+                Texture::Hatch(hatch) => format!("Hatch {:?}", hatch),
             }
         )
     }
 }
 
+impl From<LinearGradient> for Texture {
+    fn from(gradient: LinearGradient) -> Texture {
+        Texture::LinearGradient(gradient)
+    }
+}
+
+impl From<RadialGradient> for Texture {
+    fn from(gradient: RadialGradient) -> Texture {
+        Texture::RadialGradient(gradient)
+    }
+}
+
+impl From<Hatch> for Texture {
+    fn from(hatch: Hatch) -> Texture {
+        Texture::Hatch(hatch)
+    }
+}
+
+impl From<ImageTexture> for Texture {
+    fn from(texture: ImageTexture) -> Self {
+        Texture::Image(texture)
+    }
+}
+
 impl From<Box<dyn ImageRepresentation>> for Texture {
     fn from(input: Box<dyn ImageRepresentation>) -> Self {
-        Texture::Image(input)
+        Texture::Image(ImageTexture::new(input))
     }
 }
 
 /// A stroke around the exterior of a path.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Stroke {
     /// The texture of the stroke.
     pub content: Texture,
@@ -161,6 +1026,10 @@ pub struct Stroke {
     pub cap: StrokeCapType,
     /// The style of the joins between stroke segments.
     pub join: StrokeJoinType,
+    /// The marker drawn at the start of the first subpath.
+    pub start_marker: Option<Marker>,
+    /// The marker drawn at the end of the last subpath.
+    pub end_marker: Option<Marker>,
 }
 
 impl Default for Stroke {
@@ -170,12 +1039,61 @@ impl Default for Stroke {
             cap: StrokeCapType::Butt,
             join: StrokeJoinType::Miter,
             width: 1.,
+            start_marker: None,
+            end_marker: None,
+        }
+    }
+}
+
+/// A decoration drawn at an end of a stroked path and oriented along the path tangent.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum Marker {
+    /// A triangular arrowhead pointing away from the path.
+    Arrow,
+    /// A circle centered on the end of the path.
+    Circle,
+    /// A diamond centered on the end of the path.
+    Diamond,
+    /// A custom styled path. It is specified in a space where the origin is the end of the path,
+    /// the positive x axis points away from the path along its tangent and one unit is equal to
+    /// the stroke width.
+    Custom(Box<Path>),
+}
+
+impl Marker {
+    fn geometry(&self, stroke: &Stroke) -> Path {
+        let fill = Fill {
+            content: stroke.content.clone(),
+        };
+        match self {
+            Marker::Arrow => Builder::new()
+                .move_to((2., 0.))
+                .line_to((-2., 1.5))
+                .line_to((-2., -1.5))
+                .close()
+                .done()
+                .fill(fill)
+                .finalize(),
+            Marker::Circle => Primitive::circle(1.5)
+                .fill(fill)
+                .finalize()
+                .with_offset((-1.5, -1.5)),
+            Marker::Diamond => Builder::new()
+                .move_to((2., 0.))
+                .line_to((0., 1.5))
+                .line_to((-2., 0.))
+                .line_to((0., -1.5))
+                .close()
+                .done()
+                .fill(fill)
+                .finalize(),
+            Marker::Custom(path) => *path.clone(),
         }
     }
 }
 
 /// Specifies the style of free stroke ends.
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
 pub enum StrokeCapType {
     /// Flat stroke caps.
     Butt,
@@ -184,7 +1102,7 @@ pub enum StrokeCapType {
 }
 
 /// Specifies the style at the join of stroke segments.
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
 pub enum StrokeJoinType {
     /// A beveled stroke join.
     Bevel,
@@ -195,7 +1113,7 @@ pub enum StrokeJoinType {
 }
 
 /// A fill style within a closed path.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Fill {
     /// The contents of the fill.
     pub content: Texture,
@@ -213,7 +1131,7 @@ where
 }
 
 /// A vector graphics path in 2-dimensional euclidean space.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Path {
     /// The segments comprising the path.
     pub segments: Vec<Segment>,
@@ -228,9 +1146,42 @@ pub struct Path {
     /// The associated drop shadow.
     pub shadows: Vec<Shadow>,
     /// Whether the path is closed.
+    ///
+    /// This closes only the final subpath, use [Segment::ClosePath] to close subpaths individually.
     pub closed: bool,
 }
 
+/// An indexed triangle mesh approximating the filled area of a [Path].
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Mesh {
+    /// The distinct vertices of the mesh.
+    pub vertices: Vec<Vector2>,
+    /// Indices into the vertices, taken three at a time to form triangles.
+    pub indices: Vec<u32>,
+}
+
+impl Mesh {
+    fn vertex(&mut self, point: Vector2, lookup: &mut HashMap<(u64, u64), u32>) -> u32 {
+        let vertices = &mut self.vertices;
+        *lookup
+            .entry((point.x.to_bits(), point.y.to_bits()))
+            .or_insert_with(|| {
+                vertices.push(point);
+                vertices.len() as u32 - 1
+            })
+    }
+    fn triangle(&mut self, points: [Vector2; 3], lookup: &mut HashMap<(u64, u64), u32>) {
+        let [a, b, c] = points;
+        if ((b.x - a.x) * (c.y - a.y) - (c.x - a.x) * (b.y - a.y)).abs() <= f64::EPSILON {
+            return;
+        }
+        for point in &points {
+            let index = self.vertex(*point, lookup);
+            self.indices.push(index);
+        }
+    }
+}
+
 impl Path {
     /// Adjusts the origin of the path.
     pub fn with_offset<U>(mut self, offset: U) -> Self
@@ -250,6 +1201,7 @@ impl Path {
                 }
                 Segment::MoveTo(point) => Segment::MoveTo(*point + offset),
                 Segment::LineTo(point) => Segment::LineTo(*point + offset),
+                Segment::ClosePath => Segment::ClosePath,
             })
             .collect();
         self.clip_segments = self
@@ -264,6 +1216,7 @@ impl Path {
                 }
                 Segment::MoveTo(point) => Segment::MoveTo(*point + offset),
                 Segment::LineTo(point) => Segment::LineTo(*point + offset),
+                Segment::ClosePath => Segment::ClosePath,
             })
             .collect();
         self
@@ -293,22 +1246,686 @@ impl Path {
                     update(handle_1);
                     update(handle_2);
                 }
-                Segment::QuadraticTo(point, handle) => {
-                    update(point);
-                    update(handle);
+                Segment::QuadraticTo(point, handle) => {
+                    update(point);
+                    update(handle);
+                }
+                Segment::MoveTo(point) => {
+                    update(point);
+                }
+                Segment::LineTo(point) => {
+                    update(point);
+                }
+                Segment::ClosePath => {}
+            }
+        }
+        Rect::new(
+            (top_left.x, top_left.y),
+            (bottom_right.x - top_left.x, bottom_right.y - top_left.y),
+        )
+    }
+    /// Reverses the direction of each subpath while preserving its geometry.
+    pub fn reverse(mut self) -> Self {
+        let mut reversed = vec![];
+        let mut subpath = vec![];
+        let mut start = Vector2::default();
+        let mut current = start;
+        for segment in &self.segments {
+            match *segment {
+                Segment::MoveTo(point) => {
+                    reverse_subpath(&subpath, current, false, &mut reversed);
+                    subpath.clear();
+                    start = point;
+                    current = point;
+                }
+                Segment::ClosePath => {
+                    reverse_subpath(&subpath, current, true, &mut reversed);
+                    subpath.clear();
+                    current = start;
+                }
+                Segment::LineTo(point)
+                | Segment::QuadraticTo(point, _)
+                | Segment::CubicTo(point, _, _) => {
+                    subpath.push((current, *segment));
+                    current = point;
+                }
+            }
+        }
+        reverse_subpath(&subpath, current, false, &mut reversed);
+        self.segments = reversed;
+        self
+    }
+    /// Replaces each sharp corner between two straight segments with a circular arc of the
+    /// provided radius. The radius is reduced at corners where the adjoining segments are too short
+    /// to fit it, and corners adjoining curves are left unchanged.
+    ///
+    /// Subpaths that are closed or that end at their starting point are rounded at that point too.
+    pub fn round_corners(mut self, radius: f64) -> Self {
+        let mut rounded = vec![];
+        let mut subpath = vec![];
+        let mut start = Vector2::default();
+        for segment in &self.segments {
+            match *segment {
+                Segment::MoveTo(point) => {
+                    round_subpath(start, &subpath, false, radius, &mut rounded);
+                    subpath.clear();
+                    start = point;
+                }
+                Segment::ClosePath => {
+                    subpath.push(Segment::ClosePath);
+                    round_subpath(start, &subpath, true, radius, &mut rounded);
+                    subpath.clear();
+                }
+                _ => subpath.push(*segment),
+            }
+        }
+        round_subpath(start, &subpath, self.closed, radius, &mut rounded);
+        self.segments = rounded;
+        self
+    }
+    /// Computes the signed area enclosed by the path, treating each subpath as closed.
+    ///
+    /// The area is positive for counter-clockwise paths in a y-up coordinate space.
+    pub fn signed_area(&self) -> f64 {
+        self.flatten(FLATTENING_TOLERANCE)
+            .iter()
+            .map(|(points, _)| {
+                points
+                    .iter()
+                    .zip(points.iter().cycle().skip(1))
+                    .map(|(a, b)| a.x * b.y - b.x * a.y)
+                    .sum::<f64>()
+                    / 2.
+            })
+            .sum()
+    }
+    /// Returns the winding direction of the path as determined by its signed area.
+    pub fn winding(&self) -> Winding {
+        if self.signed_area() < 0. {
+            Winding::Clockwise
+        } else {
+            Winding::CounterClockwise
+        }
+    }
+    /// Returns the stroke markers of the path as paths positioned and oriented at its ends.
+    pub fn markers(&self) -> Vec<Path> {
+        let stroke = match &self.stroke {
+            Some(stroke) => stroke,
+            None => return vec![],
+        };
+        if stroke.start_marker.is_none() && stroke.end_marker.is_none() {
+            return vec![];
+        }
+        let subpaths = self.flatten(FLATTENING_TOLERANCE);
+        let mut markers = vec![];
+        if let (Some(marker), Some((points, _))) = (&stroke.start_marker, subpaths.first()) {
+            markers.extend(place_marker(marker, stroke, points.iter()));
+        }
+        if let (Some(marker), Some((points, closed))) = (&stroke.end_marker, subpaths.last()) {
+            let closing = if *closed { points.first() } else { None };
+            markers.extend(place_marker(
+                marker,
+                stroke,
+                closing.into_iter().chain(points.iter().rev()),
+            ));
+        }
+        markers
+    }
+    /// Computes the points at which the outline of the path crosses the outline of another path.
+    ///
+    /// Curves are flattened before intersection, so points on curves are approximate.
+    pub fn intersections(&self, other: &Path) -> Vec<Vector2> {
+        let edges = |path: &Path| {
+            path.flatten(FLATTENING_TOLERANCE)
+                .into_iter()
+                .flat_map(|(points, closed)| {
+                    let mut edges: Vec<(Vector2, Vector2)> = points
+                        .iter()
+                        .zip(points.iter().skip(1))
+                        .map(|(from, to)| (*from, *to))
+                        .collect();
+                    if closed {
+                        edges.push((*points.last().unwrap(), points[0]));
+                    }
+                    edges
+                })
+                .collect::<Vec<_>>()
+        };
+        let other_edges = edges(other);
+        let mut points: Vec<Vector2> = vec![];
+        for (start, end) in edges(self) {
+            for (other_start, other_end) in &other_edges {
+                if start.x.max(end.x) < other_start.x.min(other_end.x)
+                    || start.x.min(end.x) > other_start.x.max(other_end.x)
+                    || start.y.max(end.y) < other_start.y.min(other_end.y)
+                    || start.y.min(end.y) > other_start.y.max(other_end.y)
+                {
+                    continue;
+                }
+                if let Some(point) = segment_contact(start, end, *other_start, *other_end) {
+                    if !points.iter().any(|existing| {
+                        (existing.x - point.x).hypot(existing.y - point.y) < FLATTENING_TOLERANCE
+                    }) {
+                        points.push(point);
+                    }
+                }
+            }
+        }
+        points
+    }
+    /// Computes the distance from a point to the outline of the path. The distance is negative
+    /// where the point lies within the area of the closed subpaths, using the non-zero fill rule.
+    ///
+    /// Curves are flattened before measurement, so distances to curves are approximate.
+    pub fn distance_to<T>(&self, point: T) -> f64
+    where
+        T: Into<Vector2>,
+    {
+        let point = point.into();
+        let mut distance = std::f64::INFINITY;
+        let mut winding = 0;
+        for (points, closed) in self.flatten(FLATTENING_TOLERANCE) {
+            let edges = if closed {
+                points.len()
+            } else {
+                points.len() - 1
+            };
+            for index in 0..edges {
+                let (from, to) = (points[index], points[(index + 1) % points.len()]);
+                let direction = to - from;
+                let length = direction.x * direction.x + direction.y * direction.y;
+                let offset = point - from;
+                let t = if length == 0. {
+                    0.
+                } else {
+                    ((offset.x * direction.x + offset.y * direction.y) / length)
+                        .max(0.)
+                        .min(1.)
+                };
+                let nearest = point - (from + direction * t);
+                distance = distance.min(nearest.x.hypot(nearest.y));
+                if closed {
+                    let side = direction.x * offset.y - direction.y * offset.x;
+                    if from.y <= point.y && to.y > point.y && side > 0. {
+                        winding += 1;
+                    } else if from.y > point.y && to.y <= point.y && side < 0. {
+                        winding -= 1;
+                    }
+                }
+            }
+        }
+        if winding != 0 {
+            -distance
+        } else {
+            distance
+        }
+    }
+    /// Checks the segments of the path for geometry that is likely to render incorrectly, such as
+    /// imported data with missing coordinates.
+    ///
+    /// Self-intersections are only sought where every coordinate is finite, and are approximate
+    /// on curves.
+    pub fn validate(&self) -> Vec<Issue> {
+        let mut issues = vec![];
+        let mut start = Vector2::default();
+        let mut current = start;
+        for (index, segment) in self.segments.iter().enumerate() {
+            if !segment.is_finite() {
+                issues.push(Issue::NonFinite(index));
+                continue;
+            }
+            if segment.is_degenerate(current) {
+                issues.push(Issue::ZeroLength(index));
+            }
+            match *segment {
+                Segment::MoveTo(point) => {
+                    start = point;
+                    current = point;
+                }
+                Segment::ClosePath => current = start,
+                Segment::LineTo(point)
+                | Segment::QuadraticTo(point, _)
+                | Segment::CubicTo(point, _, _) => current = point,
+            }
+        }
+        if issues
+            .iter()
+            .any(|issue| matches!(issue, Issue::NonFinite(_)))
+        {
+            return issues;
+        }
+        let mut edges = vec![];
+        for (subpath, (mut points, mut closed)) in
+            self.flatten(FLATTENING_TOLERANCE).into_iter().enumerate()
+        {
+            points.dedup();
+            if points.len() > 2 && points.first() == points.last() {
+                points.pop();
+                closed = true;
+            }
+            let count = if closed {
+                points.len()
+            } else {
+                points.len() - 1
+            };
+            for index in 0..count {
+                let (from, to) = (points[index], points[(index + 1) % points.len()]);
+                let last = closed && (index == 0 || index == count - 1);
+                edges.push((subpath, index, last, from, to));
+            }
+        }
+        for (position, (subpath, index, last, start, end)) in edges.iter().enumerate() {
+            for (other_subpath, other_index, other_last, other_start, other_end) in
+                &edges[position + 1..]
+            {
+                if subpath == other_subpath
+                    && (other_index - index == 1 || (*last && *other_last && *index == 0))
+                {
+                    continue;
+                }
+                if let Some(point) = segment_contact(*start, *end, *other_start, *other_end) {
+                    if !issues.iter().any(|issue| match issue {
+                        Issue::SelfIntersection(existing) => {
+                            (existing.x - point.x).hypot(existing.y - point.y)
+                                < FLATTENING_TOLERANCE
+                        }
+                        _ => false,
+                    }) {
+                        issues.push(Issue::SelfIntersection(point));
+                    }
+                }
+            }
+        }
+        issues
+    }
+    /// Removes segments that have non-finite coordinates or zero length, along with movements of
+    /// the pen that are immediately superseded, from both the path and its clipping mask.
+    pub fn cleaned(mut self) -> Self {
+        self.segments = clean_segments(&self.segments);
+        self.clip_segments = clean_segments(&self.clip_segments);
+        self
+    }
+    /// Converts the filled area of the path into a triangle mesh, treating each subpath as closed
+    /// and using the non-zero fill rule. Curves are flattened to within the provided tolerance.
+    pub fn tessellate(&self, tolerance: f64) -> Mesh {
+        let mut edges = vec![];
+        for (points, _) in self.flatten(tolerance) {
+            for (from, to) in points.iter().zip(points.iter().cycle().skip(1)) {
+                if from.y < to.y {
+                    edges.push((*from, *to, 1));
+                } else if from.y > to.y {
+                    edges.push((*to, *from, -1));
+                }
+            }
+        }
+        let mut boundaries: Vec<f64> = edges
+            .iter()
+            .flat_map(|(top, bottom, _)| vec![top.y, bottom.y])
+            .collect();
+        for (index, (top, bottom, _)) in edges.iter().enumerate() {
+            for (other_top, other_bottom, _) in &edges[index + 1..] {
+                if let Some(point) = segment_intersection(*top, *bottom, *other_top, *other_bottom)
+                {
+                    boundaries.push(point.y);
+                }
+            }
+        }
+        // Non-finite coordinates are ordered at the ends rather than panicking, negative NaN and
+        // infinity first and positive last.
+        boundaries.sort_by(|a, b| a.total_cmp(b));
+        boundaries.dedup();
+        let mut mesh = Mesh::default();
+        let mut lookup = HashMap::new();
+        for band in boundaries.windows(2) {
+            let (upper, lower) = (band[0], band[1]);
+            let x_at = |top: Vector2, bottom: Vector2, y: f64| {
+                top.x + (bottom.x - top.x) * (y - top.y) / (bottom.y - top.y)
+            };
+            let mut crossings: Vec<(f64, f64, i32)> = edges
+                .iter()
+                .filter(|(top, bottom, _)| top.y <= upper && bottom.y >= lower)
+                .map(|(top, bottom, direction)| {
+                    (
+                        x_at(*top, *bottom, upper),
+                        x_at(*top, *bottom, lower),
+                        *direction,
+                    )
+                })
+                .collect();
+            crossings.sort_by(|a, b| (a.0 + a.1).total_cmp(&(b.0 + b.1)));
+            let mut winding = 0;
+            for pair in crossings.windows(2) {
+                let (left, right) = (pair[0], pair[1]);
+                winding += left.2;
+                if winding == 0 {
+                    continue;
+                }
+                let corners = [
+                    Vector2::from((left.0, upper)),
+                    (right.0, upper).into(),
+                    (right.1, lower).into(),
+                    (left.1, lower).into(),
+                ];
+                mesh.triangle([corners[0], corners[1], corners[2]], &mut lookup);
+                mesh.triangle([corners[0], corners[2], corners[3]], &mut lookup);
+            }
+        }
+        mesh
+    }
+    pub(crate) fn flatten(&self, tolerance: f64) -> Vec<(Vec<Vector2>, bool)> {
+        let tolerance = tolerance.max(MINIMUM_FLATTENING_TOLERANCE);
+        let mut subpaths = vec![];
+        let mut points = vec![Vector2::default()];
+        let mut start = Vector2::default();
+        for segment in &self.segments {
+            let current = *points.last().unwrap_or(&start);
+            match *segment {
+                Segment::MoveTo(point) => {
+                    if points.len() > 1 {
+                        subpaths.push((points, false));
+                    }
+                    points = vec![point];
+                    start = point;
+                }
+                Segment::ClosePath => {
+                    if points.len() > 1 {
+                        subpaths.push((points, true));
+                    }
+                    points = vec![start];
+                }
+                Segment::LineTo(point) => points.push(point),
+                Segment::QuadraticTo(point, handle) => {
+                    let deviation = current - handle * 2. + point;
+                    let steps = (deviation.x.hypot(deviation.y) / (4. * tolerance))
+                        .sqrt()
+                        .ceil()
+                        .clamp(1., MAXIMUM_FLATTENING_STEPS);
+                    for step in 1..=steps as u32 {
+                        let t = f64::from(step) / steps;
+                        let mt = 1. - t;
+                        points.push(current * (mt * mt) + handle * (2. * mt * t) + point * (t * t));
+                    }
+                }
+                Segment::CubicTo(point, handle_1, handle_2) => {
+                    let deviation_1 = current - handle_1 * 2. + handle_2;
+                    let deviation_2 = handle_1 - handle_2 * 2. + point;
+                    let deviation = deviation_1
+                        .x
+                        .hypot(deviation_1.y)
+                        .max(deviation_2.x.hypot(deviation_2.y));
+                    let steps = (0.75 * deviation / tolerance)
+                        .sqrt()
+                        .ceil()
+                        .clamp(1., MAXIMUM_FLATTENING_STEPS);
+                    for step in 1..=steps as u32 {
+                        let t = f64::from(step) / steps;
+                        let mt = 1. - t;
+                        points.push(
+                            current * (mt * mt * mt)
+                                + handle_1 * (3. * mt * mt * t)
+                                + handle_2 * (3. * mt * t * t)
+                                + point * (t * t * t),
+                        );
+                    }
+                }
+            }
+        }
+        if points.len() > 1 {
+            subpaths.push((points, self.closed));
+        }
+        subpaths
+    }
+}
+
+fn place_marker<'a, T>(marker: &Marker, stroke: &Stroke, mut points: T) -> Option<Path>
+where
+    T: Iterator<Item = &'a Vector2>,
+{
+    let end = *points.next()?;
+    let previous = points.find(|point| **point != end)?;
+    let direction = end - *previous;
+    let matrix = Transform2::default()
+        .with_position(end)
+        .with_scale(f64::from(stroke.width))
+        .with_rotation(direction.y.atan2(direction.x))
+        .to_matrix();
+    let map = |point: Vector2| -> Vector2 {
+        (
+            matrix[0] * point.x + matrix[2] * point.y + matrix[4],
+            matrix[1] * point.x + matrix[3] * point.y + matrix[5],
+        )
+            .into()
+    };
+    let mut path = marker.geometry(stroke);
+    if !matches!(path.segments.first(), Some(Segment::MoveTo(_))) {
+        path.segments.insert(0, Segment::MoveTo(Vector2::default()));
+    }
+    path.segments = path
+        .segments
+        .into_iter()
+        .map(|segment| segment.map_points(map))
+        .collect();
+    path.clip_segments = path
+        .clip_segments
+        .into_iter()
+        .map(|segment| segment.map_points(map))
+        .collect();
+    Some(path)
+}
+
+fn clean_segments(segments: &[Segment]) -> Vec<Segment> {
+    let mut cleaned = vec![];
+    let mut start = Vector2::default();
+    let mut current = start;
+    for segment in segments {
+        if !segment.is_finite() || segment.is_degenerate(current) {
+            continue;
+        }
+        match *segment {
+            Segment::MoveTo(point) => {
+                if let Some(Segment::MoveTo(_)) = cleaned.last() {
+                    cleaned.pop();
+                }
+                start = point;
+                current = point;
+            }
+            Segment::ClosePath => current = start,
+            Segment::LineTo(point)
+            | Segment::QuadraticTo(point, _)
+            | Segment::CubicTo(point, _, _) => current = point,
+        }
+        cleaned.push(*segment);
+    }
+    if let Some(Segment::MoveTo(_)) = cleaned.last() {
+        cleaned.pop();
+    }
+    cleaned
+}
+
+/// Returns the fractions of the way along two segments at which the lines through them cross, or
+/// [None] if they're parallel.
+fn line_intersection(
+    start: Vector2,
+    end: Vector2,
+    other_start: Vector2,
+    other_end: Vector2,
+) -> Option<(f64, f64)> {
+    let direction = end - start;
+    let other_direction = other_end - other_start;
+    let denominator = direction.x * other_direction.y - direction.y * other_direction.x;
+    if denominator == 0. {
+        return None;
+    }
+    let offset = other_start - start;
+    let t = (offset.x * other_direction.y - offset.y * other_direction.x) / denominator;
+    let u = (offset.x * direction.y - offset.y * direction.x) / denominator;
+    Some((t, u))
+}
+
+/// Returns the point at which two segments cross, excluding their endpoints, so that segments
+/// that only meet where one ends aren't split.
+fn segment_intersection(
+    start: Vector2,
+    end: Vector2,
+    other_start: Vector2,
+    other_end: Vector2,
+) -> Option<Vector2> {
+    let (t, u) = line_intersection(start, end, other_start, other_end)?;
+    if t > 0. && t < 1. && u > 0. && u < 1. {
+        Some(start + (end - start) * t)
+    } else {
+        None
+    }
+}
+
+/// Returns the point at which two segments meet, including where one ends on the other.
+fn segment_contact(
+    start: Vector2,
+    end: Vector2,
+    other_start: Vector2,
+    other_end: Vector2,
+) -> Option<Vector2> {
+    let (t, u) = line_intersection(start, end, other_start, other_end)?;
+    if (0. ..=1.).contains(&t) && (0. ..=1.).contains(&u) {
+        Some(start + (end - start) * t)
+    } else {
+        None
+    }
+}
+
+fn round_corner(
+    previous: Vector2,
+    corner: Vector2,
+    next: Vector2,
+    radius: f64,
+) -> Option<(Vector2, Vector2, Vector2, Vector2)> {
+    let (to_previous, to_next) = (previous - corner, next - corner);
+    let (previous_length, next_length) = (
+        to_previous.x.hypot(to_previous.y),
+        to_next.x.hypot(to_next.y),
+    );
+    if radius <= 0. || previous_length == 0. || next_length == 0. {
+        return None;
+    }
+    let (to_previous, to_next) = (to_previous / previous_length, to_next / next_length);
+    let angle = (to_previous.x * to_next.x + to_previous.y * to_next.y)
+        .max(-1.)
+        .min(1.)
+        .acos();
+    if angle < std::f64::EPSILON || std::f64::consts::PI - angle < 1e-9 {
+        return None;
+    }
+    let half_tangent = (angle / 2.).tan();
+    let distance = (radius / half_tangent).min(previous_length.min(next_length) / 2.);
+    let handle = 4. / 3. * ((std::f64::consts::PI - angle) / 4.).tan() * distance * half_tangent;
+    let start = corner + to_previous * distance;
+    let end = corner + to_next * distance;
+    Some((
+        start,
+        start - to_previous * handle,
+        end - to_next * handle,
+        end,
+    ))
+}
+
+fn round_subpath(
+    start: Vector2,
+    subpath: &[Segment],
+    closed: bool,
+    radius: f64,
+    rounded: &mut Vec<Segment>,
+) {
+    let close = subpath.last() == Some(&Segment::ClosePath);
+    let mut edges: Vec<Segment> = subpath
+        .iter()
+        .filter(|segment| **segment != Segment::ClosePath)
+        .cloned()
+        .collect();
+    if edges.is_empty() {
+        return;
+    }
+    let mut points = vec![start];
+    points.extend(edges.iter().map(|segment| match *segment {
+        Segment::LineTo(point)
+        | Segment::MoveTo(point)
+        | Segment::QuadraticTo(point, _)
+        | Segment::CubicTo(point, _, _) => point,
+        Segment::ClosePath => start,
+    }));
+    let end = *points.last().unwrap();
+    if closed && end != start {
+        edges.push(Segment::LineTo(start));
+        points.push(start);
+    }
+    let looped = closed || end == start;
+    let count = edges.len();
+    let corners: Vec<_> = (0..count)
+        .map(|index| {
+            let next = if index + 1 < count {
+                index + 1
+            } else if looped {
+                0
+            } else {
+                return None;
+            };
+            match (edges[index], edges[next]) {
+                (Segment::LineTo(_), Segment::LineTo(_)) => {
+                    round_corner(points[index], points[index + 1], points[next + 1], radius)
                 }
-                Segment::MoveTo(point) => {
-                    update(point);
+                _ => None,
+            }
+        })
+        .collect();
+    let first = match corners[count - 1] {
+        Some((_, _, _, end)) if looped => end,
+        _ => start,
+    };
+    rounded.push(Segment::MoveTo(first));
+    let mut current = first;
+    for (edge, corner) in edges.into_iter().zip(corners) {
+        match corner {
+            Some((start, handle_1, handle_2, end)) => {
+                if start != current {
+                    rounded.push(Segment::LineTo(start));
                 }
-                Segment::LineTo(point) => {
-                    update(point);
+                rounded.push(Segment::CubicTo(end, handle_1, handle_2));
+                current = end;
+            }
+            None => {
+                rounded.push(edge);
+                if let Segment::LineTo(point)
+                | Segment::QuadraticTo(point, _)
+                | Segment::CubicTo(point, _, _) = edge
+                {
+                    current = point;
                 }
             }
         }
-        Rect::new(
-            (top_left.x, top_left.y),
-            (bottom_right.x - top_left.x, bottom_right.y - top_left.y),
-        )
+    }
+    if close {
+        rounded.push(Segment::ClosePath);
+    }
+}
+
+fn reverse_subpath(
+    subpath: &[(Vector2, Segment)],
+    end: Vector2,
+    closed: bool,
+    reversed: &mut Vec<Segment>,
+) {
+    if subpath.is_empty() {
+        return;
+    }
+    reversed.push(Segment::MoveTo(end));
+    for (from, segment) in subpath.iter().rev() {
+        reversed.push(match *segment {
+            Segment::QuadraticTo(_, handle) => Segment::QuadraticTo(*from, handle),
+            Segment::CubicTo(_, handle_1, handle_2) => Segment::CubicTo(*from, handle_2, handle_1),
+            _ => Segment::LineTo(*from),
+        });
+    }
+    if closed {
+        reversed.push(Segment::ClosePath);
     }
 }
 
@@ -316,6 +1933,8 @@ impl Path {
 #[derive(Default, Debug)]
 pub struct Builder {
     segments: Vec<Segment>,
+    position: Vector2,
+    start: Vector2,
 }
 
 impl Builder {
@@ -328,36 +1947,81 @@ impl Builder {
     where
         T: Into<Vector2>,
     {
-        self.segments.push(Segment::LineTo(to.into()));
+        let to = to.into();
+        self.segments.push(Segment::LineTo(to));
+        self.position = to;
         self
     }
+    /// Draws a line to the point at the specified offset from the current pen position.
+    pub fn line_by<T>(self, offset: T) -> Self
+    where
+        T: Into<Vector2>,
+    {
+        let to = self.position + offset;
+        self.line_to(to)
+    }
     /// Moves the pen to the specified point.
     pub fn move_to<T>(mut self, to: T) -> Self
     where
         T: Into<Vector2>,
     {
-        self.segments.push(Segment::MoveTo(to.into()));
+        let to = to.into();
+        self.segments.push(Segment::MoveTo(to));
+        self.position = to;
+        self.start = to;
         self
     }
+    /// Moves the pen by the specified offset from its current position.
+    pub fn move_by<T>(self, offset: T) -> Self
+    where
+        T: Into<Vector2>,
+    {
+        let to = self.position + offset;
+        self.move_to(to)
+    }
     /// Draws a quadratic bezier curve to the specified point with the given handle.
     pub fn quadratic_to<T>(mut self, to: T, handle: T) -> Self
     where
         T: Into<Vector2>,
     {
-        self.segments
-            .push(Segment::QuadraticTo(to.into(), handle.into()));
+        let to = to.into();
+        self.segments.push(Segment::QuadraticTo(to, handle.into()));
+        self.position = to;
         self
     }
+    /// Draws a quadratic bezier curve with the end point and handle given as offsets from the current
+    /// pen position.
+    pub fn quadratic_by<T>(self, to: T, handle: T) -> Self
+    where
+        T: Into<Vector2>,
+    {
+        let origin = self.position;
+        self.quadratic_to(origin + to, origin + handle)
+    }
     /// Draws a cubic bezier curve to the specified point with the given handles.
     pub fn cubic_to<T>(mut self, to: T, handle_1: T, handle_2: T) -> Self
     where
         T: Into<Vector2>,
     {
-        self.segments.push(Segment::CubicTo(
-            to.into(),
-            handle_1.into(),
-            handle_2.into(),
-        ));
+        let to = to.into();
+        self.segments
+            .push(Segment::CubicTo(to, handle_1.into(), handle_2.into()));
+        self.position = to;
+        self
+    }
+    /// Draws a cubic bezier curve with the end point and handles given as offsets from the current
+    /// pen position.
+    pub fn cubic_by<T>(self, to: T, handle_1: T, handle_2: T) -> Self
+    where
+        T: Into<Vector2>,
+    {
+        let origin = self.position;
+        self.cubic_to(origin + to, origin + handle_1, origin + handle_2)
+    }
+    /// Closes the current subpath with a straight line back to its starting point.
+    pub fn close(mut self) -> Self {
+        self.segments.push(Segment::ClosePath);
+        self.position = self.start;
         self
     }
     /// Finishes the path and returns a style builder containing the generated segments.
@@ -517,6 +2181,157 @@ impl Primitive {
     pub fn continuous_curvature_square(radius: f64, k_factor: f64) -> StyleHelper {
         Primitive::continuous_curvature_rectangle((radius, radius), k_factor)
     }
+    /// Creates a circular wedge, or pie slice, with the provided radius that begins at the start
+    /// angle and sweeps through the provided angle, both in radians. The center of the circle lies
+    /// at `(radius, radius)`.
+    pub fn wedge(radius: f64, start_angle: f64, sweep: f64) -> StyleHelper {
+        let center = Vector2::from((radius, radius));
+        arc(
+            Builder::new()
+                .move_to(center)
+                .line_to(arc_point(center, radius, start_angle)),
+            center,
+            radius,
+            start_angle,
+            sweep,
+        )
+        .close()
+        .done()
+    }
+    /// Creates an annular sector, or donut slice, between the provided inner and outer radii that
+    /// begins at the start angle and sweeps through the provided angle, both in radians. The center
+    /// of the circles lies at `(outer_radius, outer_radius)`.
+    pub fn annular_sector(
+        inner_radius: f64,
+        outer_radius: f64,
+        start_angle: f64,
+        sweep: f64,
+    ) -> StyleHelper {
+        let center = Vector2::from((outer_radius, outer_radius));
+        let outer = arc(
+            Builder::new().move_to(arc_point(center, outer_radius, start_angle)),
+            center,
+            outer_radius,
+            start_angle,
+            sweep,
+        )
+        .line_to(arc_point(center, inner_radius, start_angle + sweep));
+        arc(outer, center, inner_radius, start_angle + sweep, -sweep)
+            .close()
+            .done()
+    }
+    /// Creates grid lines across the provided bounds, intended to be stroked. Lines fall on
+    /// multiples of the horizontal and vertical spacing so that neighbouring grids line up, and
+    /// spacing too fine for the bounds is widened to a multiple of itself.
+    pub fn grid<T>(bounds: Rect, spacing: T) -> StyleHelper
+    where
+        T: Into<Vector2>,
+    {
+        let spacing = spacing.into();
+        let (min, max) = (bounds.position, bounds.position + bounds.size);
+        let mut builder = Builder::new();
+        for x in multiples(min.x, max.x, spacing.x) {
+            builder = builder.move_to((x, min.y)).line_to((x, max.y));
+        }
+        for y in multiples(min.y, max.y, spacing.y) {
+            builder = builder.move_to((min.x, y)).line_to((max.x, y));
+        }
+        builder.done()
+    }
+    /// Creates a ruler along the x axis from zero to the provided length, intended to be stroked.
+    /// A tick extends towards positive y at each multiple of the spacing, reaching the full height
+    /// at every major interval of ticks and half of it otherwise. Spacing too fine for the length
+    /// is widened to a multiple of itself.
+    pub fn ruler(length: f64, spacing: f64, height: f64, major_interval: u32) -> StyleHelper {
+        let mut builder = Builder::new().move_to((0., 0.)).line_to((length, 0.));
+        for (index, x) in multiples(0., length, spacing).enumerate() {
+            let tick = if index as u32 % major_interval.max(1) == 0 {
+                height
+            } else {
+                height / 2.
+            };
+            builder = builder.move_to((x, 0.)).line_to((x, tick));
+        }
+        builder.done()
+    }
+    /// Creates the alternate squares of a checkerboard across the provided bounds, intended to be
+    /// filled over a background of the other color. Squares are aligned to multiples of their size
+    /// and cut off at the edges of the bounds. Squares too small for the bounds are enlarged to a
+    /// multiple of their size, and nothing is created for bounds that aren't finite.
+    pub fn checkerboard(bounds: Rect, size: f64) -> StyleHelper {
+        let (min, max) = (bounds.position, bounds.position + bounds.size);
+        let mut builder = Builder::new();
+        let squares = ((max.x - min.x) / size + 1.) * ((max.y - min.y) / size + 1.);
+        let enlargement = (squares / MAXIMUM_CHECKERBOARD_SQUARES).sqrt().ceil();
+        let size = size * enlargement.max(1.);
+        let finite = [min.x, min.y, max.x, max.y]
+            .iter()
+            .all(|value| value.is_finite());
+        if size > 0. && size.is_finite() && finite {
+            for row in (min.y / size).floor() as i64..(max.y / size).ceil() as i64 {
+                for column in (min.x / size).floor() as i64..(max.x / size).ceil() as i64 {
+                    if (row + column) % 2 != 0 {
+                        continue;
+                    }
+                    let start = Vector2::from((
+                        (column as f64 * size).max(min.x),
+                        (row as f64 * size).max(min.y),
+                    ));
+                    let end = Vector2::from((
+                        ((column + 1) as f64 * size).min(max.x),
+                        ((row + 1) as f64 * size).min(max.y),
+                    ));
+                    builder = builder
+                        .move_to(start)
+                        .line_to((end.x, start.y))
+                        .line_to(end)
+                        .line_to((start.x, end.y))
+                        .close();
+                }
+            }
+        }
+        builder.done()
+    }
+}
+
+/// Returns the multiples of the spacing from the minimum to the maximum, widening the spacing to a
+/// multiple of itself where it would place more than [MAXIMUM_GUIDE_LINES] of them. Nothing is
+/// returned for ranges that aren't finite.
+fn multiples(min: f64, max: f64, spacing: f64) -> impl Iterator<Item = f64> {
+    let spacing = spacing * ((max - min) / spacing / MAXIMUM_GUIDE_LINES).ceil().max(1.);
+    let range = if spacing > 0. && spacing.is_finite() && min.is_finite() && max.is_finite() {
+        (min / spacing).ceil() as i64..(max / spacing).floor() as i64 + 1
+    } else {
+        0..0
+    };
+    range.map(move |index| index as f64 * spacing)
+}
+
+fn arc_point(center: Vector2, radius: f64, angle: f64) -> Vector2 {
+    center + Vector2::from((angle.cos(), angle.sin())) * radius
+}
+
+fn arc(
+    mut builder: Builder,
+    center: Vector2,
+    radius: f64,
+    start_angle: f64,
+    sweep: f64,
+) -> Builder {
+    let pieces = (sweep.abs() / std::f64::consts::FRAC_PI_2).ceil().max(1.);
+    let step = sweep / pieces;
+    let handle_length = 4. / 3. * (step / 4.).tan() * radius;
+    for piece in 0..pieces as u32 {
+        let from = start_angle + step * f64::from(piece);
+        let to = from + step;
+        let tangent = |angle: f64| Vector2::from((-angle.sin(), angle.cos())) * handle_length;
+        builder = builder.cubic_to(
+            arc_point(center, radius, to),
+            arc_point(center, radius, from) + tangent(from),
+            arc_point(center, radius, to) - tangent(to),
+        );
+    }
+    builder
 }
 
 /// Provides an ergonomic interface for building styled paths.
@@ -582,6 +2397,11 @@ impl StyleHelper {
         self.shadows.push(shadow);
         self
     }
+    /// Applies the provided filter beneath the path.
+    pub fn filter(mut self, filter: Filter) -> Self {
+        self.shadows.extend(filter.shadows());
+        self
+    }
     /// Finalizes the styling and returns a styled [Path].
     pub fn finalize(self) -> Path {
         Path {
@@ -626,19 +2446,661 @@ impl StrokeBuilder {
         self.stroke.join = StrokeJoinType::Round;
         self
     }
+    /// Sets the marker drawn at the start of the stroke.
+    pub fn start_marker(mut self, marker: Marker) -> Self {
+        self.stroke.start_marker = Some(marker);
+        self
+    }
+    /// Sets the marker drawn at the end of the stroke.
+    pub fn end_marker(mut self, marker: Marker) -> Self {
+        self.stroke.end_marker = Some(marker);
+        self
+    }
     /// Finalizes the style and returns a completed [Stroke].
     pub fn finalize(self) -> Stroke {
         self.stroke
     }
 }
 
+/// Checks that gradient stops are within bounds and in order of offset.
+pub(crate) fn validate_stops(stops: &[GradientStop]) -> Result<(), Error> {
+    if stops
+        .iter()
+        .any(|stop| stop.offset < 0. || stop.offset > 1.)
+    {
+        return Err(Error::color_stop());
+    }
+    if stops.windows(2).any(|pair| pair[1].offset < pair[0].offset) {
+        return Err(Error::stop_order());
+    }
+    Ok(())
+}
+
+/// Provides an ergonomic interface for building a [LinearGradient].
+#[derive(Debug)]
+pub struct LinearGradientBuilder {
+    gradient: LinearGradient,
+}
+
+impl LinearGradientBuilder {
+    /// Creates a new [LinearGradientBuilder] for a gradient between the provided points.
+    pub fn new<T, U>(start: T, end: U) -> Self
+    where
+        T: Into<Vector2>,
+        U: Into<Vector2>,
+    {
+        LinearGradientBuilder {
+            gradient: LinearGradient {
+                stops: vec![],
+                start: start.into(),
+                end: end.into(),
+                spread: SpreadMode::default(),
+                transform: None,
+                dither: None,
+            },
+        }
+    }
+    /// Adds a color stop. Stops must be added in order of offset.
+    pub fn stop(mut self, offset: f64, color: LDRColor) -> Self {
+        self.gradient.stops.push(GradientStop { offset, color });
+        self
+    }
+    /// Sets how the gradient continues beyond its start and end.
+    pub fn spread(mut self, spread: SpreadMode) -> Self {
+        self.gradient.spread = spread;
+        self
+    }
+    /// Sets the transformation from the space of the gradient into the space of the path.
+    pub fn transform(mut self, transform: Transform2) -> Self {
+        self.gradient.transform = Some(transform);
+        self
+    }
+    /// Sets the dithering of the gradient, overriding the global [Dither].
+    pub fn dither(mut self, dither: Dither) -> Self {
+        self.gradient.dither = Some(dither);
+        self
+    }
+    /// Finalizes the gradient, failing if a stop offset is outside of zero to one or stops are
+    /// out of order.
+    pub fn finalize(self) -> Result<LinearGradient, Error> {
+        validate_stops(&self.gradient.stops)?;
+        Ok(self.gradient)
+    }
+}
+
+/// Provides an ergonomic interface for building a [RadialGradient].
+#[derive(Debug)]
+pub struct RadialGradientBuilder {
+    gradient: RadialGradient,
+}
+
+impl RadialGradientBuilder {
+    /// Creates a new [RadialGradientBuilder] for a gradient between the provided circles.
+    pub fn new<T, U>(start: T, start_radius: f64, end: U, end_radius: f64) -> Self
+    where
+        T: Into<Vector2>,
+        U: Into<Vector2>,
+    {
+        RadialGradientBuilder {
+            gradient: RadialGradient {
+                stops: vec![],
+                start: start.into(),
+                start_radius,
+                end: end.into(),
+                end_radius,
+                spread: SpreadMode::default(),
+                transform: None,
+                dither: None,
+            },
+        }
+    }
+    /// Adds a color stop. Stops must be added in order of offset.
+    pub fn stop(mut self, offset: f64, color: LDRColor) -> Self {
+        self.gradient.stops.push(GradientStop { offset, color });
+        self
+    }
+    /// Sets how the gradient continues beyond its start and end.
+    pub fn spread(mut self, spread: SpreadMode) -> Self {
+        self.gradient.spread = spread;
+        self
+    }
+    /// Sets the transformation from the space of the gradient into the space of the path.
+    pub fn transform(mut self, transform: Transform2) -> Self {
+        self.gradient.transform = Some(transform);
+        self
+    }
+    /// Sets the dithering of the gradient, overriding the global [Dither].
+    pub fn dither(mut self, dither: Dither) -> Self {
+        self.gradient.dither = Some(dither);
+        self
+    }
+    /// Finalizes the gradient, failing if a stop offset is outside of zero to one or stops are
+    /// out of order.
+    pub fn finalize(self) -> Result<RadialGradient, Error> {
+        validate_stops(&self.gradient.stops)?;
+        Ok(self.gradient)
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{GradientStop, LDRColor};
+    use super::{
+        linear_light_stops, sample_stops_in, Builder, Compositing, Dither, Filter, GradientStop,
+        Hatch, ImageTexture, Issue, LDRColor, LinearGradient, LinearGradientBuilder, Marker,
+        Primitive, RadialGradient, RadialGradientBuilder, Rect, Segment, SpreadMode, StrokeBuilder,
+        Texture, Transform2, Winding, LINEAR_LIGHT_SUBDIVISIONS, MAXIMUM_CHECKERBOARD_SQUARES,
+        MAXIMUM_FLATTENING_STEPS, MAXIMUM_GUIDE_LINES, MAX_GLOW_PASSES,
+    };
+    use crate::graphics::{Image, ImageRepresentation, Texture2};
 
     #[test]
     fn gradient_stop_fail() {
         assert!(GradientStop::new(5.0, LDRColor::white()).is_err());
         assert!(GradientStop::new(-5.0, LDRColor::white()).is_err());
     }
+
+    #[test]
+    fn reverse_flips_winding() {
+        let square = Primitive::square(10.).finalize();
+        assert_eq!(square.signed_area(), 100.);
+        assert_eq!(square.winding(), Winding::CounterClockwise);
+        let reversed = square.reverse();
+        assert_eq!(reversed.signed_area(), -100.);
+        assert_eq!(reversed.winding(), Winding::Clockwise);
+        let circle = Primitive::circle(10.).finalize().reverse();
+        assert!((circle.signed_area() + std::f64::consts::PI * 100.).abs() < 3.);
+    }
+
+    fn area(path: super::Path) -> f64 {
+        let mesh = path.tessellate(0.05);
+        mesh.indices
+            .chunks(3)
+            .map(|triangle| {
+                let [a, b, c] = [
+                    mesh.vertices[triangle[0] as usize],
+                    mesh.vertices[triangle[1] as usize],
+                    mesh.vertices[triangle[2] as usize],
+                ];
+                ((b.x - a.x) * (c.y - a.y) - (c.x - a.x) * (b.y - a.y)).abs() / 2.
+            })
+            .sum::<f64>()
+    }
+
+    #[test]
+    fn tessellate_respects_holes() {
+        assert!((area(Primitive::square(10.).finalize()) - 100.).abs() < 1e-9);
+        let framed = Builder::new()
+            .line_to((10., 0.))
+            .line_to((10., 10.))
+            .line_to((0., 10.))
+            .close()
+            .move_to((2., 2.))
+            .line_to((2., 7.))
+            .line_to((7., 7.))
+            .line_to((7., 2.))
+            .close()
+            .done()
+            .finalize();
+        assert!((area(framed) - 75.).abs() < 1e-9);
+    }
+
+    #[test]
+    fn tessellate_touching_and_crossing_edges() {
+        // Squares meeting at a corner, whose edges only touch where they end.
+        let touching = Builder::new()
+            .line_to((10., 0.))
+            .line_to((10., 10.))
+            .line_to((0., 10.))
+            .close()
+            .move_to((10., 10.))
+            .line_to((20., 10.))
+            .line_to((20., 20.))
+            .line_to((10., 20.))
+            .close()
+            .done()
+            .finalize();
+        assert!((area(touching) - 200.).abs() < 1e-9);
+        let bowtie = Builder::new()
+            .line_to((10., 10.))
+            .line_to((10., 0.))
+            .line_to((0., 10.))
+            .close()
+            .done()
+            .finalize();
+        assert!((area(bowtie) - 50.).abs() < 1e-9);
+    }
+
+    #[test]
+    fn tessellate_tolerates_non_finite_points() {
+        let path = Builder::new()
+            .line_to((10., 0.))
+            .line_to((std::f64::NAN, 10.))
+            .line_to((0., std::f64::INFINITY))
+            .close()
+            .done()
+            .finalize();
+        path.tessellate(0.05);
+    }
+
+    #[test]
+    fn flattening_bounds_its_tolerance() {
+        let path = Builder::new()
+            .quadratic_to((100., 0.), (50., 100.))
+            .cubic_to((0., 0.), (100., 100.), (0., 100.))
+            .close()
+            .done()
+            .finalize();
+        for tolerance in &[0., -1., f64::NAN, 1e-300] {
+            let points: usize = path
+                .flatten(*tolerance)
+                .iter()
+                .map(|(points, _)| points.len())
+                .sum();
+            assert!(points > 100 && points < 10_000);
+        }
+        assert!(!path.tessellate(0.).indices.is_empty());
+        let huge = Builder::new()
+            .quadratic_to((1e300, 0.), (f64::MAX, f64::MAX))
+            .done()
+            .finalize();
+        assert!(huge.flatten(0.05)[0].0.len() <= MAXIMUM_FLATTENING_STEPS as usize + 1);
+    }
+
+    #[test]
+    fn intersections_of_overlapping_squares() {
+        let square = Primitive::square(10.).finalize();
+        let offset = Primitive::square(10.).finalize().with_offset((5., 5.));
+        let mut points = square.intersections(&offset);
+        points.sort_by(|a, b| a.x.partial_cmp(&b.x).unwrap());
+        assert_eq!(points.len(), 2);
+        assert_eq!((points[0].x, points[0].y), (5., 10.));
+        assert_eq!((points[1].x, points[1].y), (10., 5.));
+    }
+
+    #[test]
+    fn relative_commands_track_pen() {
+        let relative = Builder::new()
+            .move_by((1., 1.))
+            .line_by((4., 0.))
+            .quadratic_by((0., 4.), (2., 2.))
+            .close()
+            .cubic_by((1., 1.), (0., 1.), (1., 0.))
+            .done()
+            .finalize();
+        let absolute = Builder::new()
+            .move_to((1., 1.))
+            .line_to((5., 1.))
+            .quadratic_to((5., 5.), (7., 3.))
+            .close()
+            .cubic_to((2., 2.), (1., 2.), (2., 1.))
+            .done()
+            .finalize();
+        assert_eq!(relative.segments, absolute.segments);
+    }
+
+    #[test]
+    fn hatch_tiles_fit_spacing() {
+        let lines = Hatch::lines(LDRColor::black())
+            .with_spacing(10.)
+            .with_width(2.);
+        assert_eq!(lines.tile().signed_area(), 20.);
+        let cross = Hatch::cross_hatch(LDRColor::black())
+            .with_spacing(10.)
+            .with_width(2.);
+        assert_eq!(cross.tile().signed_area(), 40.);
+        for hatch in [lines, cross, Hatch::dots(LDRColor::black()).with_width(30.)].iter() {
+            let bounds = hatch.tile().bounds();
+            assert!(bounds.position.x >= 0. && bounds.position.y >= 0.);
+            assert!(bounds.position.x + bounds.size.x <= hatch.spacing + 1e-9);
+            assert!(bounds.position.y + bounds.size.y <= hatch.spacing + 1e-9);
+        }
+    }
+
+    #[test]
+    fn round_corners_fits_radius() {
+        let area = |path: super::Path| path.signed_area();
+        let rounded = Primitive::square(10.).finalize().round_corners(2.);
+        let expected = 100. - 4. * (4. - std::f64::consts::PI);
+        assert!((area(rounded.clone()) - expected).abs() < 0.5);
+        assert_eq!(rounded.bounds().size, (10., 10.).into());
+        let circle = Primitive::square(10.).finalize().round_corners(100.);
+        assert!((area(circle) - area(Primitive::circle(5.).finalize())).abs() < 0.01);
+        let closed = Builder::new()
+            .move_to((0., 0.))
+            .line_to((10., 0.))
+            .line_to((10., 10.))
+            .close()
+            .done()
+            .finalize()
+            .round_corners(1.);
+        assert_eq!(closed.segments.last(), Some(&super::Segment::ClosePath));
+        assert_eq!(
+            closed
+                .segments
+                .iter()
+                .filter(|segment| matches!(segment, super::Segment::CubicTo(..)))
+                .count(),
+            3
+        );
+    }
+
+    #[test]
+    fn distance_is_signed_inside_closed_paths() {
+        let square = Primitive::square(10.).close().finalize();
+        assert_eq!(square.distance_to((5., 5.)), -5.);
+        assert_eq!(square.distance_to((13., 14.)), 5.);
+        assert_eq!(square.distance_to((2., -1.)), 1.);
+        let line = Builder::new()
+            .move_to((0., 0.))
+            .line_to((10., 0.))
+            .done()
+            .finalize();
+        assert_eq!(line.distance_to((5., 3.)), 3.);
+        assert_eq!(line.distance_to((5., -3.)), 3.);
+    }
+
+    #[test]
+    fn validate_and_clean() {
+        let path = Builder::new()
+            .move_to((0., 0.))
+            .line_to((10., 10.))
+            .line_to((10., 10.))
+            .line_to((0., 10.))
+            .line_to((10., 0.))
+            .close()
+            .done()
+            .finalize();
+        assert_eq!(
+            path.validate(),
+            vec![
+                Issue::ZeroLength(2),
+                Issue::SelfIntersection((5., 5.).into())
+            ]
+        );
+        let cleaned = path.cleaned();
+        assert_eq!(cleaned.segments.len(), 5);
+        assert_eq!(
+            cleaned.validate(),
+            vec![Issue::SelfIntersection((5., 5.).into())]
+        );
+        assert!(Primitive::circle(5.).finalize().validate().is_empty());
+        let broken = Builder::new()
+            .move_to((0., 0.))
+            .move_to((1., 1.))
+            .line_to((std::f64::NAN, 2.))
+            .line_to((3., 3.))
+            .done()
+            .finalize();
+        assert_eq!(broken.validate(), vec![Issue::NonFinite(2)]);
+        assert_eq!(
+            broken.cleaned().segments,
+            vec![
+                Segment::MoveTo((1., 1.).into()),
+                Segment::LineTo((3., 3.).into())
+            ]
+        );
+    }
+
+    #[test]
+    fn guides_cover_bounds() {
+        let bounds = Rect::new((-5., 0.), (20., 10.));
+        let grid = Primitive::grid(bounds, (10., 5.)).finalize();
+        assert_eq!(grid.segments.len(), 10);
+        assert_eq!(grid.bounds().position, bounds.position);
+        assert_eq!(grid.bounds().size, bounds.size);
+        let ruler = Primitive::ruler(10., 1., 4., 5).finalize();
+        assert_eq!(ruler.bounds().size, (10., 4.).into());
+        assert_eq!(ruler.segments[13], Segment::LineTo((5., 4.).into()));
+        assert_eq!(ruler.segments[15], Segment::LineTo((6., 2.).into()));
+        let board = Primitive::checkerboard(bounds, 4.).finalize();
+        assert_eq!(board.signed_area().abs(), 98.);
+    }
+
+    #[test]
+    fn guides_are_bounded() {
+        let bounds = Rect::new((0., 0.), (1e6, 1e6));
+        let grid = Primitive::grid(bounds, (1e-9, 1.)).finalize();
+        let lines = grid.segments.len() as f64 / 2.;
+        assert!(lines <= 2. * MAXIMUM_GUIDE_LINES + 2.);
+        let ruler = Primitive::ruler(1e6, f64::MIN_POSITIVE, 1., 10).finalize();
+        assert!(ruler.segments.len() as f64 <= 2. * MAXIMUM_GUIDE_LINES + 4.);
+        let board = Primitive::checkerboard(bounds, 1e-9).finalize();
+        assert!(board.segments.len() as f64 <= 5. * MAXIMUM_CHECKERBOARD_SQUARES);
+        assert!((board.signed_area().abs() - 5e11).abs() < 1e6);
+        let unbounded = Rect::new((f64::NEG_INFINITY, 0.), (f64::INFINITY, 10.));
+        assert!(Primitive::grid(unbounded, 1.).finalize().segments.len() <= 2 * 11);
+        assert!(Primitive::checkerboard(unbounded, 1.)
+            .finalize()
+            .segments
+            .is_empty());
+    }
+
+    #[test]
+    fn gradients_unroll_spread() {
+        let black = LDRColor::black();
+        let white = LDRColor::white();
+        let gradient = LinearGradient {
+            stops: vec![
+                GradientStop::new(0., black).unwrap(),
+                GradientStop::new(1., white).unwrap(),
+            ],
+            start: (0., 0.).into(),
+            end: (10., 0.).into(),
+            spread: SpreadMode::Reflect,
+            transform: Some(Transform2::default().with_scale((2., 1.))),
+            dither: None,
+        };
+        let resolved = gradient.resolve(Rect::new((-5., 0.), (40., 10.)));
+        assert!((resolved.start.x + 20.).abs() < 1e-9);
+        assert!((resolved.end.x - 40.).abs() < 1e-9);
+        let offsets = [0., 1. / 3., 1. / 3., 2. / 3., 2. / 3., 1.];
+        assert!(resolved
+            .stops
+            .iter()
+            .zip(offsets.iter())
+            .all(|(stop, offset)| (stop.offset - offset).abs() < 1e-9));
+        assert_eq!(resolved.stops[0].color, white);
+        assert_eq!(resolved.stops[2].color, black);
+    }
+
+    #[test]
+    fn gradients_sample_and_dither() {
+        let stops = vec![
+            GradientStop::new(0., LDRColor::black()).unwrap(),
+            GradientStop::new(1., LDRColor::white()).unwrap(),
+        ];
+        let linear = LinearGradient {
+            stops: stops.clone(),
+            start: (0., 0.).into(),
+            end: (10., 0.).into(),
+            spread: SpreadMode::Reflect,
+            transform: Some(Transform2::default().with_scale((2., 1.))),
+            dither: None,
+        };
+        assert!((linear.color_at((25., 3.).into()).unwrap()[0] - 0.75).abs() < 1e-9);
+        let radial = RadialGradient {
+            stops,
+            start: (0., 0.).into(),
+            start_radius: 0.,
+            end: (0., 0.).into(),
+            end_radius: 10.,
+            spread: SpreadMode::Pad,
+            transform: None,
+            dither: Some(Dither::Ordered),
+        };
+        assert!((radial.color_at((3., 4.).into()).unwrap()[1] - 0.5).abs() < 1e-9);
+        assert_eq!(radial.color_at((30., 40.).into()).unwrap()[2], 1.);
+        // Transforms are applied by rows, as the native backend applies them to gradients.
+        let rotated = LinearGradient {
+            spread: SpreadMode::Pad,
+            transform: Some(Transform2::default().with_rotation(std::f64::consts::FRAC_PI_2)),
+            ..linear.clone()
+        };
+        assert!((rotated.color_at((0., -10.).into()).unwrap()[0] - 1.).abs() < 1e-9);
+        assert!(rotated.color_at((0., 10.).into()).unwrap()[0].abs() < 1e-9);
+        let mut thresholds: Vec<f64> = (0..64)
+            .map(|index| radial.dither().threshold(index % 8, index / 8))
+            .collect();
+        thresholds.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        thresholds.dedup();
+        assert_eq!(thresholds.len(), 64);
+        assert_eq!(radial.sample(0.5), LDRColor::rgb(128, 128, 128));
+        assert_eq!(linear.sample(-0.25), LDRColor::rgb(64, 64, 64));
+        let total: u32 = (0..64)
+            .map(|index| u32::from(Dither::Ordered.quantize(100.25 / 255., index % 8, index / 8)))
+            .sum();
+        assert_eq!(total, 6416);
+        assert_eq!(Dither::None.quantize(100.25 / 255., 3, 5), 100);
+    }
+
+    #[test]
+    fn linear_light_stops_subdivide_spans() {
+        let stops = vec![
+            GradientStop::new(0., LDRColor::black()).unwrap(),
+            GradientStop::new(0.5, LDRColor::black()).unwrap(),
+            GradientStop::new(1., LDRColor::white()).unwrap(),
+        ];
+        let subdivided = linear_light_stops(&stops);
+        assert_eq!(
+            subdivided.len(),
+            3 + 2 * (LINEAR_LIGHT_SUBDIVISIONS as usize - 1)
+        );
+        assert_eq!(subdivided.last().unwrap().color, LDRColor::white());
+        let middle = subdivided
+            .iter()
+            .find(|stop| stop.offset == 0.75)
+            .unwrap()
+            .color;
+        assert_eq!(middle, LDRColor::rgb(188, 188, 188));
+        let srgb = sample_stops_in(&stops, 0.75, Compositing::Srgb);
+        assert_eq!(srgb[0], 0.5);
+    }
+
+    #[test]
+    fn image_texture_sources() {
+        let atlas = Image {
+            pixels: vec![LDRColor::white(); 64 * 32],
+            format: Texture2 {
+                width: 64,
+                height: 32,
+            },
+        };
+        let texture = ImageTexture::new(Box::new(atlas)).with_anchor((0.5, 0.5));
+        let bounds = Rect::new((0., 0.), (10., 10.));
+        assert_eq!(texture.anchoring(bounds).1, (32., 16.).into());
+        let sprite = texture.with_source(Rect::new((48., 16.), (32., 32.)));
+        let source = sprite.source();
+        assert_eq!(source.position, (48., 16.).into());
+        assert_eq!(source.size, (16., 16.).into());
+        assert_eq!(sprite.anchoring(bounds), ((5., 5.).into(), (8., 8.).into()));
+    }
+
+    #[test]
+    fn gradient_builders_validate_stops() {
+        let gradient = LinearGradientBuilder::new((0., 0.), (10., 0.))
+            .stop(0., LDRColor::black())
+            .stop(1., LDRColor::white())
+            .spread(SpreadMode::Reflect)
+            .finalize()
+            .unwrap();
+        assert_eq!(gradient.stops.len(), 2);
+        assert_eq!(gradient.spread, SpreadMode::Reflect);
+        assert_eq!(gradient.sample(0.5), LDRColor::rgb(128, 128, 128));
+        assert!(LinearGradientBuilder::new((0., 0.), (1., 1.))
+            .stop(0.5, LDRColor::black())
+            .stop(0.2, LDRColor::white())
+            .finalize()
+            .is_err());
+        let radial = RadialGradientBuilder::new((5., 5.), 0., (5., 5.), 5.)
+            .stop(0., LDRColor::white())
+            .stop(0., LDRColor::black())
+            .dither(Dither::Ordered)
+            .finalize()
+            .unwrap();
+        assert_eq!(radial.dither, Some(Dither::Ordered));
+        assert!(RadialGradientBuilder::new((0., 0.), 0., (0., 0.), 1.)
+            .stop(1.5, LDRColor::white())
+            .finalize()
+            .is_err());
+    }
+
+    #[test]
+    fn glow_lowers_to_stacked_shadows() {
+        let color = LDRColor::rgb(0, 255, 128).with_alpha(200);
+        let glow = Filter::Glow {
+            color,
+            radius: 8.,
+            strength: 2.5,
+        };
+        let shadows = glow.shadows();
+        assert_eq!(shadows.len(), 3);
+        for shadow in &shadows {
+            assert_eq!(shadow.blur, 8.);
+            assert_eq!(shadow.spread, 4.);
+            assert_eq!(shadow.offset, (0., 0.).into());
+        }
+        assert_eq!(shadows[0].color, color);
+        assert_eq!(shadows[2].color.a, 100);
+        let path = Primitive::square(10.).filter(glow).finalize();
+        assert_eq!(path.shadows, shadows);
+        assert!(Filter::Glow {
+            color,
+            radius: 8.,
+            strength: 0.,
+        }
+        .shadows()
+        .is_empty());
+    }
+
+    #[test]
+    fn glow_rejects_unbounded_strength() {
+        let glow = |radius: f64, strength: f64| Filter::Glow {
+            color: LDRColor::white(),
+            radius,
+            strength,
+        };
+        assert!(glow(8., f64::NAN).shadows().is_empty());
+        assert!(glow(8., f64::INFINITY).shadows().is_empty());
+        assert!(glow(f64::INFINITY, 1.).shadows().is_empty());
+        assert!(glow(f64::NAN, 1.).shadows().is_empty());
+        let shadows = glow(8., 1e300).shadows();
+        assert_eq!(shadows.len(), MAX_GLOW_PASSES);
+        assert!(shadows
+            .iter()
+            .all(|shadow| shadow.color == LDRColor::white()));
+    }
+
+    #[test]
+    fn paths_round_trip_through_serde() {
+        let image = Image {
+            pixels: vec![LDRColor::rgba(255, 0, 0, 128); 4],
+            format: Texture2 {
+                width: 2,
+                height: 2,
+            },
+        };
+        let path = Primitive::square(10.)
+            .fill(ImageTexture::new(Box::new(image)).into())
+            .stroke(
+                StrokeBuilder::new(Hatch::lines(LDRColor::black()).into(), 2.)
+                    .end_marker(Marker::Arrow)
+                    .finalize(),
+            )
+            .filter(Filter::Glow {
+                color: LDRColor::white(),
+                radius: 4.,
+                strength: 1.,
+            })
+            .finalize();
+        let saved = serde_json::to_string(&path).unwrap();
+        let loaded: super::Path = serde_json::from_str(&saved).unwrap();
+        assert_eq!(format!("{:?}", loaded), format!("{:?}", path));
+        match &loaded.fill.unwrap().content {
+            Texture::Image(texture) => assert_eq!(
+                texture.image.as_texture().pixels,
+                vec![LDRColor::rgba(255, 0, 0, 128); 4]
+            ),
+            _ => panic!("unexpected texture"),
+        }
+    }
 }
@@ -0,0 +1,93 @@
+use std::sync::atomic::{AtomicU8, Ordering};
+
+static CURRENT: AtomicU8 = AtomicU8::new(2);
+
+/// A rendering quality tier, trading visual fidelity for speed on constrained hardware.
+///
+/// The tier is global so that it can be lowered for a whole application at once. Backends apply
+/// whichever of its settings they have control over.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Quality {
+    /// The fastest rendering, for embedded and low-power targets.
+    Low,
+    /// A balance of fidelity and speed.
+    Medium,
+    /// The highest fidelity. This is the default.
+    High,
+}
+
+impl Quality {
+    /// Returns the current global quality tier.
+    pub fn current() -> Quality {
+        match CURRENT.load(Ordering::Relaxed) {
+            0 => Quality::Low,
+            1 => Quality::Medium,
+            _ => Quality::High,
+        }
+    }
+    /// Sets the global quality tier. Content that has already been cached for rendering, such as
+    /// blurred shadows, keeps the quality it was rendered at until it is updated.
+    pub fn set_current(quality: Quality) {
+        CURRENT.store(
+            match quality {
+                Quality::Low => 0,
+                Quality::Medium => 1,
+                Quality::High => 2,
+            },
+            Ordering::Relaxed,
+        );
+        super::settings_changed();
+    }
+    /// Returns the number of box blur passes used to approximate a gaussian blur.
+    pub fn blur_passes(self) -> u32 {
+        match self {
+            Quality::Low => 1,
+            Quality::Medium => 2,
+            Quality::High => 3,
+        }
+    }
+    /// Returns the greatest distance, in logical pixels, by which curves may deviate from their
+    /// true shape when flattened for rendering.
+    pub fn flattening_tolerance(self) -> f64 {
+        match self {
+            Quality::Low => 0.5,
+            Quality::Medium => 0.2,
+            Quality::High => 0.05,
+        }
+    }
+    /// Returns whether glyph edges are antialiased.
+    pub fn antialias_glyphs(self) -> bool {
+        self != Quality::Low
+    }
+}
+
+impl Default for Quality {
+    fn default() -> Self {
+        Quality::High
+    }
+}
+
+/// Options for rasterizing content that override the current [Quality] tier for a single
+/// [Frame](crate::graphics::canvas::Frame) or rasterization.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct RasterOptions {
+    /// The greatest distance, in logical pixels, by which curves may deviate from their true shape
+    /// when flattened, or [None] to use that of the current [Quality] tier. Greater tolerances
+    /// rasterize complex paths faster at the expense of their fidelity.
+    pub tolerance: Option<f64>,
+}
+
+impl RasterOptions {
+    /// Sets the greatest distance, in logical pixels, by which curves may deviate from their true
+    /// shape when flattened.
+    pub fn with_tolerance(mut self, tolerance: f64) -> Self {
+        self.tolerance = Some(tolerance);
+        self
+    }
+    /// Returns the flattening tolerance to rasterize with, falling back to that of the current
+    /// [Quality] tier.
+    pub fn flattening_tolerance(self) -> f64 {
+        self.tolerance
+            .unwrap_or_else(|| Quality::current().flattening_tolerance())
+    }
+}
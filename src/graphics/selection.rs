@@ -0,0 +1,249 @@
+use crate::graphics::{path::Path, Image, LDRColor, Rect, Texture2, Transform2, Vector2};
+
+use serde::{de::Error, Deserialize, Deserializer, Serialize};
+
+/// An area to select on a [Layer](crate::graphics::canvas::Layer), in the same space as its
+/// content.
+#[derive(Clone, Debug)]
+pub enum SelectionArea {
+    /// A rectangle.
+    Rectangle(Rect),
+    /// The area enclosed by a freehand path, which is closed if it isn't already.
+    Lasso(Box<Path>),
+    /// The region contiguous with a point and within a tolerance, between zero and one, of its
+    /// color in the rasterization of the layer, like a magic wand.
+    Color(Vector2, f64),
+    /// A selection made earlier, such as one saved in a
+    /// [Scene](crate::graphics::canvas::scene::Scene), placed where it was made.
+    Mask(Selection),
+}
+
+/// An alpha mask over the pixels of a layer's rasterization. While a layer has a selection, content
+/// added to it is only drawn within the selected area.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Selection {
+    width: u32,
+    height: u32,
+    mask: Vec<u8>,
+    position: Vector2,
+    scale: Vector2,
+}
+
+#[derive(Deserialize)]
+#[serde(rename = "Selection")]
+struct SerializedSelection {
+    width: u32,
+    height: u32,
+    mask: Vec<u8>,
+    position: Vector2,
+    scale: Vector2,
+}
+
+/// Selections are only deserialized with a mask of as many pixels as their size, so that
+/// [Selection::alpha] can't index past its end.
+impl<'de> Deserialize<'de> for Selection {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let selection = SerializedSelection::deserialize(deserializer)?;
+        let length = u64::from(selection.width) * u64::from(selection.height);
+        if selection.mask.len() as u64 != length {
+            return Err(D::Error::custom(format!(
+                "expected {} pixels for a {}x{} selection, found {}",
+                length,
+                selection.width,
+                selection.height,
+                selection.mask.len()
+            )));
+        }
+        Ok(Selection {
+            width: selection.width,
+            height: selection.height,
+            mask: selection.mask,
+            position: selection.position,
+            scale: selection.scale,
+        })
+    }
+}
+
+impl Selection {
+    /// Creates a selection of the pixels whose centers are within the provided rectangle, in
+    /// pixels.
+    pub(crate) fn rectangle(width: u32, height: u32, rect: Rect) -> Selection {
+        let (min, max) = (rect.position, rect.position + rect.size);
+        Selection::from_fn(width, height, |x, y| {
+            x >= min.x.min(max.x)
+                && x < min.x.max(max.x)
+                && y >= min.y.min(max.y)
+                && y < min.y.max(max.y)
+        })
+    }
+    /// Creates a selection of the pixels whose centers are enclosed by the provided polygons, in
+    /// pixels, under the nonzero winding rule.
+    pub(crate) fn lasso(width: u32, height: u32, polygons: &[Vec<Vector2>]) -> Selection {
+        let mut mask = vec![0; (width * height) as usize];
+        for row in 0..height {
+            let y = f64::from(row) + 0.5;
+            let mut crossings = vec![];
+            for polygon in polygons {
+                for (index, start) in polygon.iter().enumerate() {
+                    let end = polygon[(index + 1) % polygon.len()];
+                    if (start.y <= y) == (end.y <= y) {
+                        continue;
+                    }
+                    let x = start.x + (y - start.y) / (end.y - start.y) * (end.x - start.x);
+                    crossings.push((x, if end.y > start.y { 1 } else { -1 }));
+                }
+            }
+            crossings.sort_by(|a, b| a.0.total_cmp(&b.0));
+            let mut winding = 0;
+            for pair in crossings.windows(2) {
+                winding += pair[0].1;
+                if winding == 0 {
+                    continue;
+                }
+                let from = (pair[0].0 - 0.5).ceil().max(0.) as u32;
+                let to = ((pair[1].0 - 0.5).ceil().max(0.) as u32).min(width);
+                for column in from..to {
+                    mask[(row * width + column) as usize] = 255;
+                }
+            }
+        }
+        Selection {
+            width,
+            height,
+            mask,
+            position: Vector2::default(),
+            scale: Vector2::from(1.),
+        }
+    }
+    /// Creates a selection from the alpha channel of an image.
+    pub(crate) fn from_image(image: &Image<LDRColor, Texture2>) -> Selection {
+        Selection {
+            width: image.format.width,
+            height: image.format.height,
+            mask: image.pixels.iter().map(|pixel| pixel.a).collect(),
+            position: Vector2::default(),
+            scale: Vector2::from(1.),
+        }
+    }
+    fn from_fn(width: u32, height: u32, selected: impl Fn(f64, f64) -> bool) -> Selection {
+        let mut mask = Vec::with_capacity((width * height) as usize);
+        for y in 0..height {
+            for x in 0..width {
+                let center = (f64::from(x) + 0.5, f64::from(y) + 0.5);
+                mask.push(if selected(center.0, center.1) { 255 } else { 0 });
+            }
+        }
+        Selection {
+            width,
+            height,
+            mask,
+            position: Vector2::default(),
+            scale: Vector2::from(1.),
+        }
+    }
+    /// Places the pixels of the selection in the space of a layer's content.
+    pub(crate) fn with_transform(mut self, transform: Transform2) -> Self {
+        self.position = transform.position;
+        self.scale = transform.scale;
+        self
+    }
+    /// Returns the transformation from the pixels of the mask into the space of the content of
+    /// the layer it was made on.
+    pub fn get_transform(&self) -> Transform2 {
+        Transform2::from(self.position).with_scale(self.scale)
+    }
+    /// Returns the size of the mask in pixels.
+    pub fn get_size(&self) -> Vector2 {
+        (f64::from(self.width), f64::from(self.height)).into()
+    }
+    /// Returns the coverage of a pixel, where zero is unselected and 255 fully selected.
+    pub fn alpha(&self, x: u32, y: u32) -> u8 {
+        if x >= self.width || y >= self.height {
+            return 0;
+        }
+        self.mask[(y * self.width + x) as usize]
+    }
+    /// Returns whether no pixels are selected.
+    pub fn is_empty(&self) -> bool {
+        self.mask.iter().all(|alpha| *alpha == 0)
+    }
+    /// Returns an image that is opaque where pixels are unselected, for erasing content outside
+    /// of the selection.
+    pub(crate) fn inverse(&self) -> Image<LDRColor, Texture2> {
+        Image {
+            pixels: self
+                .mask
+                .iter()
+                .map(|alpha| LDRColor::black().with_alpha(255 - alpha))
+                .collect(),
+            format: Texture2 {
+                width: self.width,
+                height: self.height,
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rows(selection: &Selection) -> Vec<String> {
+        (0..selection.height)
+            .map(|y| {
+                (0..selection.width)
+                    .map(|x| if selection.alpha(x, y) > 0 { 'x' } else { '.' })
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn selections_deserialize_with_as_many_pixels_as_their_size() {
+        let selection = Selection::rectangle(3, 2, Rect::new((0., 0.), (2., 1.)));
+        let saved = serde_json::to_string(&selection).unwrap();
+        assert_eq!(
+            serde_json::from_str::<Selection>(&saved).unwrap(),
+            selection
+        );
+        let truncated = saved.replace(r#""width":3"#, r#""width":4"#);
+        assert!(serde_json::from_str::<Selection>(&truncated).is_err());
+    }
+
+    #[test]
+    fn rectangle_selects_covered_pixels() {
+        let selection = Selection::rectangle(5, 3, Rect::new((1., 0.6), (2.5, 1.)));
+        assert_eq!(rows(&selection), vec![".....", ".xx..", "....."]);
+        assert_eq!(selection.alpha(9, 9), 0);
+        assert_eq!(selection.inverse().pixels[6].a, 0);
+        assert_eq!(selection.inverse().pixels[0].a, 255);
+    }
+
+    #[test]
+    fn lasso_uses_nonzero_winding() {
+        let square = |from: f64, to: f64| {
+            vec![
+                Vector2::from((from, from)),
+                (to, from).into(),
+                (to, to).into(),
+                (from, to).into(),
+            ]
+        };
+        let selection = Selection::lasso(6, 6, &[square(1., 5.), square(2., 4.)]);
+        assert_eq!(
+            rows(&selection),
+            vec!["......", ".xxxx.", ".xxxx.", ".xxxx.", ".xxxx.", "......"]
+        );
+        let mut hole = square(2., 4.);
+        hole.reverse();
+        let selection = Selection::lasso(6, 6, &[square(1., 5.), hole]);
+        assert_eq!(
+            rows(&selection),
+            vec!["......", ".xxxx.", ".x..x.", ".x..x.", ".xxxx.", "......"]
+        );
+        assert!(Selection::lasso(6, 6, &[]).is_empty());
+    }
+}
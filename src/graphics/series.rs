@@ -0,0 +1,207 @@
+use crate::graphics::{
+    canvas::{Content, Frame, Layer, Object, Rasterizable},
+    path::{Builder, Path, Primitive, Stroke},
+    Vector2,
+};
+
+use std::{
+    collections::VecDeque,
+    fmt::{self, Debug, Formatter},
+};
+
+/// The number of points stroked as one path, so that appending points only rebuilds the most
+/// recent of them.
+const CHUNK_POINTS: usize = 256;
+
+fn empty() -> Path {
+    Builder::new().done().finalize()
+}
+
+struct Chunk {
+    object: Box<dyn Object>,
+    points: Vec<Vector2>,
+    stroked: usize,
+}
+
+/// A line plot of a stream of timestamped values, for dashboards that append many points every
+/// frame.
+///
+/// Points are kept in a ring of chunks that are each stroked as a separate [Object] of the plot's
+/// [Layer]. Appending points only rebuilds the path of the newest chunk, and chunks are recycled
+/// as they scroll out of the time window, so the cost of a frame follows the number of points
+/// added rather than the number shown.
+pub struct TimeSeriesPath {
+    layer: Box<dyn Layer>,
+    stroke: Stroke,
+    window: f64,
+    scale: Vector2,
+    origin: Vector2,
+    chunks: VecDeque<Chunk>,
+    idle: Vec<Box<dyn Object>>,
+    latest: Option<f64>,
+}
+
+impl TimeSeriesPath {
+    /// Adds the plot to the provided [Frame] as a new [Layer], showing the points within the
+    /// provided span of time before the latest one. By default, each unit of time and value spans
+    /// one pixel.
+    pub fn new(frame: &mut dyn Frame, window: f64) -> TimeSeriesPath {
+        TimeSeriesPath {
+            layer: frame.add_layer(),
+            stroke: Stroke::default(),
+            window,
+            scale: Vector2::from(1.),
+            origin: Vector2::default(),
+            chunks: VecDeque::new(),
+            idle: vec![],
+            latest: None,
+        }
+    }
+    /// Sets the stroke the plot is drawn with.
+    pub fn with_stroke(mut self, stroke: Stroke) -> Self {
+        self.stroke = stroke;
+        self
+    }
+    /// Sets the number of pixels spanned by each unit of time and of value.
+    pub fn with_scale<T>(mut self, scale: T) -> Self
+    where
+        T: Into<Vector2>,
+    {
+        self.scale = scale.into();
+        self
+    }
+    /// Sets the position of the start of the time window at a value of zero. Greater values are
+    /// plotted above it.
+    pub fn with_origin<T>(mut self, origin: T) -> Self
+    where
+        T: Into<Vector2>,
+    {
+        self.origin = origin.into();
+        self
+    }
+    /// Returns the number of points kept, including those partly outside the time window.
+    pub fn len(&self) -> usize {
+        self.chunks
+            .iter()
+            .enumerate()
+            .map(|(index, chunk)| {
+                // Every chunk after the first begins with the last point of the one before it.
+                chunk.points.len() - if index == 0 { 0 } else { 1 }
+            })
+            .sum()
+    }
+    /// Returns whether no points are kept.
+    pub fn is_empty(&self) -> bool {
+        self.chunks.is_empty()
+    }
+    /// Appends a point at the provided time. Points earlier than the latest point are ignored.
+    ///
+    /// The plot is updated on the next call to [TimeSeriesPath::tick].
+    pub fn push(&mut self, time: f64, value: f64) {
+        if let Some(latest) = self.latest {
+            if time < latest {
+                return;
+            }
+        }
+        self.latest = Some(time);
+        let point = Vector2::from((time, value));
+        if let Some(chunk) = self.chunks.back_mut() {
+            if chunk.points.len() < CHUNK_POINTS {
+                chunk.points.push(point);
+                return;
+            }
+        }
+        let mut points = Vec::with_capacity(CHUNK_POINTS);
+        if let Some(previous) = self.chunks.back().and_then(|chunk| chunk.points.last()) {
+            points.push(*previous);
+        }
+        points.push(point);
+        let object = match self.idle.pop() {
+            Some(object) => object,
+            None => self.layer.add(Content::from(empty())),
+        };
+        self.chunks.push_back(Chunk {
+            object,
+            points,
+            stroked: 0,
+        });
+    }
+    /// Removes every point.
+    pub fn clear(&mut self) {
+        for chunk in self.chunks.split_off(0) {
+            self.recycle(chunk.object);
+        }
+        self.latest = None;
+    }
+    /// Strokes the points appended since the last tick, scrolls the plot to the latest point and
+    /// recycles chunks that have left the time window. This should be called once per frame.
+    pub fn tick(&mut self) {
+        let latest = match self.latest {
+            Some(latest) => latest,
+            None => return,
+        };
+        let start = latest - self.window;
+        while self.chunks.len() > 1 && self.chunks[0].points.last().unwrap().x < start {
+            let chunk = self.chunks.pop_front().unwrap();
+            self.recycle(chunk.object);
+        }
+        let (scale, origin) = (self.scale, self.origin);
+        for (index, chunk) in self.chunks.iter_mut().enumerate() {
+            // Each chunk is built relative to its first point so that large timestamps keep their
+            // precision, and scrolling only moves it.
+            let first = chunk.points[0];
+            let local = |point: &Vector2| {
+                Vector2::from(((point.x - first.x) * scale.x, -point.y * scale.y))
+            };
+            if chunk.stroked != chunk.points.len() {
+                let mut builder = Builder::new().move_to(local(&first));
+                for point in &chunk.points[1..] {
+                    builder = builder.line_to(local(point));
+                }
+                chunk
+                    .object
+                    .update(builder.done().stroke(self.stroke.clone()).finalize().into());
+                chunk.stroked = chunk.points.len();
+            }
+            chunk
+                .object
+                .set_transform((origin + ((first.x - start) * scale.x, 0.)).into());
+            // The oldest chunk is cut off at the start of the window. Chunks only become the oldest
+            // as the one before them is recycled, which removes its clip.
+            if index == 0 && first.x < start {
+                let (top, bottom) = chunk.points.iter().map(local).fold(
+                    (f64::INFINITY, f64::NEG_INFINITY),
+                    |(top, bottom), point| (top.min(point.y), bottom.max(point.y)),
+                );
+                let (cut, end) = (
+                    (start - first.x) * scale.x,
+                    local(&chunk.points[chunk.points.len() - 1]).x,
+                );
+                let margin = f64::from(self.stroke.width);
+                chunk.object.set_clip(Some(
+                    Primitive::rectangle((
+                        (end - cut + margin).max(0.),
+                        bottom - top + margin * 2.,
+                    ))
+                    .finalize()
+                    .with_offset((cut, top - margin)),
+                ));
+            }
+        }
+    }
+    fn recycle(&mut self, mut object: Box<dyn Object>) {
+        object.update(Rasterizable::Path(Box::new(empty())));
+        object.set_clip(None);
+        self.idle.push(object);
+    }
+}
+
+impl Debug for TimeSeriesPath {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TimeSeriesPath")
+            .field("points", &self.len())
+            .field("window", &self.window)
+            .field("latest", &self.latest)
+            .finish()
+    }
+}
@@ -0,0 +1,116 @@
+use crate::graphics::{Image, LDRColor, Rect, Texture2};
+
+/// Images packed into a grid of equally sized cells of a single image, such as the frames of an
+/// animation exported for a game engine.
+///
+/// Each cell is the size of the largest packed image, and images are placed at the top left of
+/// their cells in rows. The areas of the packed images can be drawn individually with
+/// [ImageTexture::with_source](crate::graphics::path::ImageTexture::with_source).
+#[derive(Clone, Debug)]
+pub struct SpriteSheet {
+    /// The packed image.
+    pub image: Image<LDRColor, Texture2>,
+    /// The area of each packed image within the sheet, in pixels, in the order they were packed.
+    pub frames: Vec<Rect>,
+}
+
+impl SpriteSheet {
+    /// Packs images into a grid that is as close to square as possible.
+    pub fn pack(images: &[Image<LDRColor, Texture2>]) -> SpriteSheet {
+        let columns = (images.len() as f64).sqrt().ceil() as u32;
+        SpriteSheet::pack_columns(images, columns)
+    }
+    /// Packs images into a grid with the provided number of columns, and as many rows as needed.
+    pub fn pack_columns(images: &[Image<LDRColor, Texture2>], columns: u32) -> SpriteSheet {
+        let columns = columns.max(1);
+        let cell = images.iter().fold((0, 0), |(width, height), image| {
+            (
+                width.max(image.format.width),
+                height.max(image.format.height),
+            )
+        });
+        let rows = (images.len() as u32).div_ceil(columns);
+        let width = if images.is_empty() {
+            0
+        } else {
+            cell.0 * columns.min(images.len() as u32)
+        };
+        let height = cell.1 * rows;
+        let mut pixels = vec![LDRColor::rgba(0, 0, 0, 0); (width * height) as usize];
+        let mut frames = Vec::with_capacity(images.len());
+        for (index, image) in images.iter().enumerate() {
+            let (left, top) = (
+                index as u32 % columns * cell.0,
+                index as u32 / columns * cell.1,
+            );
+            for (row, line) in image
+                .pixels
+                .chunks(image.format.width.max(1) as usize)
+                .enumerate()
+            {
+                let start = ((top + row as u32) * width + left) as usize;
+                pixels[start..start + line.len()].copy_from_slice(line);
+            }
+            frames.push(Rect::new(
+                (f64::from(left), f64::from(top)),
+                (
+                    f64::from(image.format.width),
+                    f64::from(image.format.height),
+                ),
+            ));
+        }
+        SpriteSheet {
+            image: Image {
+                pixels,
+                format: Texture2 { width, height },
+            },
+            frames,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn solid(width: u32, height: u32, color: LDRColor) -> Image<LDRColor, Texture2> {
+        Image {
+            pixels: vec![color; (width * height) as usize],
+            format: Texture2 { width, height },
+        }
+    }
+
+    #[test]
+    fn images_are_packed_into_cells() {
+        let sheet = SpriteSheet::pack(&[
+            solid(2, 2, LDRColor::white()),
+            solid(1, 3, LDRColor::black()),
+            solid(2, 1, LDRColor::white()),
+        ]);
+        assert_eq!(
+            (sheet.image.format.width, sheet.image.format.height),
+            (4, 6)
+        );
+        let area = |index: usize| {
+            let frame = sheet.frames[index];
+            (
+                frame.position.x,
+                frame.position.y,
+                frame.size.x,
+                frame.size.y,
+            )
+        };
+        assert_eq!(area(1), (2., 0., 1., 3.));
+        assert_eq!(area(2), (0., 3., 2., 1.));
+        assert_eq!(sheet.image.pixels[2 * 4 + 2], LDRColor::black());
+        assert_eq!(sheet.image.pixels[2 * 4 + 3], LDRColor::rgba(0, 0, 0, 0));
+        assert_eq!(sheet.image.pixels[3 * 4 + 1], LDRColor::white());
+        assert_eq!(sheet.image.pixels[4 * 4], LDRColor::rgba(0, 0, 0, 0));
+        let strip = SpriteSheet::pack_columns(&[solid(1, 1, LDRColor::white())], 4);
+        assert_eq!(
+            (strip.image.format.width, strip.image.format.height),
+            (1, 1)
+        );
+        assert!(SpriteSheet::pack(&[]).frames.is_empty());
+    }
+}
@@ -1,10 +1,55 @@
-use crate::graphics::LDRColor;
+use crate::graphics::{LDRColor, Vector2};
 
-/// A font face.
-#[derive(Clone, Copy, Debug)]
+use serde::{Deserialize, Serialize};
+
+use std::ops::Range;
+
+/// Provides conversions between grapheme cluster, `char` and byte indices of text.
+pub mod grapheme;
+/// Provides pattern-based hyphenation of words in registered languages.
+pub mod hyphenation;
+/// Provides a simplified implementation of the Unicode line breaking algorithm (UAX #14).
+pub mod linebreak;
+/// Provides parsing of a small markup language into styled text.
+pub mod markup;
+/// Provides grapheme-aware searching of text.
+pub mod search;
+
+/// The fallback families frames use until they are replaced with
+/// [Frame::set_fallback_fonts](crate::graphics::canvas::Frame::set_fallback_fonts): the color
+/// emoji fonts and CJK fonts that ship with each major platform.
+const DEFAULT_FALLBACK: &[&str] = &[
+    "Apple Color Emoji",
+    "Segoe UI Emoji",
+    "Segoe UI Symbol",
+    "Noto Color Emoji",
+    "PingFang SC",
+    "Hiragino Sans",
+    "Microsoft YaHei",
+    "Yu Gothic",
+    "Malgun Gothic",
+    "Noto Sans CJK SC",
+];
+
+/// Returns the fallback font families frames start with.
+pub(crate) fn default_fallback() -> Vec<String> {
+    DEFAULT_FALLBACK
+        .iter()
+        .map(|family| (*family).to_owned())
+        .collect()
+}
+
+/// A generic font family, used for text when none of its named families are available.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
 pub enum Font {
     /// The default system font face used for native UI elements.
     SystemFont,
+    /// The platform's default sans-serif font face.
+    SansSerif,
+    /// The platform's default serif font face.
+    Serif,
+    /// The platform's default monospace font face.
+    Monospace,
 }
 
 impl Default for Font {
@@ -14,7 +59,7 @@ impl Default for Font {
 }
 
 /// Specifies the weight of a font.
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
 pub enum Weight {
     /// Hairline weight.
     Hairline,
@@ -43,16 +88,244 @@ impl Default for Weight {
 }
 
 /// Specifies the type of text wrap used.
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
 pub enum Wrap {
     /// No wrap.
     None,
     /// Standard word-level text wrap.
     Normal,
+    /// Text wrap at the line break opportunities given by the Unicode line breaking algorithm,
+    /// which allows wrapping within scripts such as Japanese and Thai that aren't separated by spaces.
+    Unicode,
+    /// Text wrap between any grapheme clusters.
+    Char,
+    /// Standard word-level text wrap that also wraps between the grapheme clusters of words too
+    /// long to fit on a line, such as URLs and hashes.
+    WordChar,
+    /// No wrap, with lines that overflow the max width cut off at it.
+    Clip,
+    /// No wrap, with each line that overflows the max width shortened to fit by replacing part of
+    /// it with an ellipsis.
+    Truncate(Ellipsize),
+}
+
+/// Specifies which part of an overflowing line is replaced with an ellipsis.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Ellipsize {
+    /// The start of the line is omitted.
+    Start,
+    /// The middle of the line is omitted.
+    Middle,
+    /// The end of the line is omitted.
+    End,
+}
+
+/// Specifies how text is underlined.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Underline {
+    /// No underline.
+    None,
+    /// A single underline.
+    Single,
+    /// Two parallel underlines.
+    Double,
+}
+
+impl Default for Underline {
+    fn default() -> Underline {
+        Underline::None
+    }
+}
+
+/// Specifies the order of the color components of the pixels of a display.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SubpixelOrder {
+    /// Red, green and blue components from left to right.
+    Rgb,
+    /// Blue, green and red components from left to right.
+    Bgr,
+    /// Red, green and blue components from top to bottom.
+    VerticalRgb,
+    /// Blue, green and red components from top to bottom.
+    VerticalBgr,
+}
+
+/// Specifies how the edges of glyphs are antialiased.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Antialias {
+    /// Glyphs are not antialiased.
+    None,
+    /// Glyphs are antialiased with shades of their color, which is correct on any surface.
+    Grayscale,
+    /// Glyphs are antialiased using the color components of the pixels of a display in the
+    /// provided order. This is sharper on opaque surfaces that are drawn unrotated to that
+    /// display, and produces color fringes anywhere else.
+    Subpixel(SubpixelOrder),
+}
+
+impl Default for Antialias {
+    fn default() -> Antialias {
+        Antialias::Grayscale
+    }
+}
+
+/// Specifies how strongly glyph outlines are fitted to the pixel grid.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Hinting {
+    /// Outlines are drawn as designed.
+    None,
+    /// Outlines are fitted vertically only, preserving their shapes.
+    Slight,
+    /// Outlines are fitted moderately.
+    Medium,
+    /// Outlines are fitted as closely as possible, for the greatest contrast.
+    Full,
+}
+
+impl Default for Hinting {
+    fn default() -> Hinting {
+        Hinting::Full
+    }
+}
+
+/// An OpenType feature that fonts may provide.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Feature {
+    /// Standard ligatures, such as "fi". Most fonts enable these by default.
+    Ligatures,
+    /// Contextual ligatures. Most fonts enable these by default.
+    ContextualLigatures,
+    /// Discretionary ligatures, which are decorative.
+    DiscretionaryLigatures,
+    /// Small capitals in place of lowercase letters.
+    SmallCaps,
+    /// Numerals of uniform width, so that columns of values align and updating values don't shift.
+    TabularNumerals,
+    /// Numerals of varying width, as for running text.
+    ProportionalNumerals,
+    /// Numerals that rise and fall with lowercase letters.
+    OldstyleNumerals,
+    /// A zero with a slash or dot, distinguishing it from the letter O.
+    SlashedZero,
+    /// Diagonal fractions in place of numerals separated by a slash.
+    Fractions,
+    /// One of the stylistic sets of the font, numbered from 1 to 20.
+    StylisticSet(u8),
+}
+
+impl Feature {
+    /// Returns the OpenType tag of the feature.
+    pub fn tag(self) -> String {
+        match self {
+            Feature::Ligatures => "liga".to_owned(),
+            Feature::ContextualLigatures => "clig".to_owned(),
+            Feature::DiscretionaryLigatures => "dlig".to_owned(),
+            Feature::SmallCaps => "smcp".to_owned(),
+            Feature::TabularNumerals => "tnum".to_owned(),
+            Feature::ProportionalNumerals => "pnum".to_owned(),
+            Feature::OldstyleNumerals => "onum".to_owned(),
+            Feature::SlashedZero => "zero".to_owned(),
+            Feature::Fractions => "frac".to_owned(),
+            Feature::StylisticSet(set) => format!("ss{:02}", set.clamp(1, 20)),
+        }
+    }
+}
+
+/// A positioned glyph of some laid out [Text], for animating or styling its characters
+/// individually.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Glyph {
+    /// The index of the glyph in its font, or [None] where the backend doesn't expose shaping, in
+    /// which case the glyph stands for its whole cluster.
+    pub id: Option<u32>,
+    /// The position of the glyph on the baseline, in the local space of the text.
+    pub position: Vector2,
+    /// The horizontal distance from this glyph to the next, in pixels.
+    pub advance: f64,
+    /// The offset at which the glyph is drawn from its position, as for combining marks.
+    pub offset: Vector2,
+    /// The range of grapheme clusters of the content that the glyph renders. Ligatures render
+    /// several clusters with one glyph, and some clusters are rendered with several glyphs.
+    pub cluster: Range<usize>,
+}
+
+/// A range of the content of some [Text] styled differently from the rest, as for inline
+/// emphasis and links. Styles that a span leaves unset are those of the text or of the spans
+/// it's nested in, and later spans take precedence over earlier ones.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Span {
+    /// The range of bytes of the content that is styled.
+    pub range: Range<usize>,
+    /// The font weight of the range.
+    pub weight: Option<Weight>,
+    /// Whether the range is italic.
+    pub italic: bool,
+    /// The color of the range.
+    pub color: Option<LDRColor>,
+    /// How the range is underlined.
+    pub underline: Option<Underline>,
+    /// Whether the range is struck through.
+    pub strikethrough: bool,
+    /// The target of the link the range stands for, which isn't followed by the text itself but
+    /// can be found with [Text::link_at].
+    pub link: Option<String>,
+}
+
+impl Span {
+    /// Creates a span over the provided byte range of content that doesn't change its style.
+    pub fn new(range: Range<usize>) -> Span {
+        Span {
+            range,
+            ..Span::default()
+        }
+    }
+    /// Sets the font weight of the range.
+    pub fn with_weight(mut self, weight: Weight) -> Self {
+        self.weight = Some(weight);
+        self
+    }
+    /// Makes the range italic.
+    pub fn italic(mut self) -> Self {
+        self.italic = true;
+        self
+    }
+    /// Sets the color of the range.
+    pub fn with_color(mut self, color: LDRColor) -> Self {
+        self.color = Some(color);
+        self
+    }
+    /// Underlines the range.
+    pub fn underline(mut self) -> Self {
+        self.underline = Some(Underline::Single);
+        self
+    }
+    /// Strikes through the range.
+    pub fn strikethrough(mut self) -> Self {
+        self.strikethrough = true;
+        self
+    }
+    /// Makes the range a link to the provided target.
+    pub fn with_link(mut self, target: &str) -> Self {
+        self.link = Some(target.to_owned());
+        self
+    }
+    fn apply(&self, text: &mut Text) {
+        if let Some(weight) = self.weight {
+            text.weight = weight;
+        }
+        if let Some(color) = self.color {
+            text.color = color;
+        }
+        if let Some(underline) = self.underline {
+            text.underline = underline;
+        }
+        text.italic |= self.italic;
+        text.strikethrough |= self.strikethrough;
+    }
 }
 
 /// Specifices the alignment of text.
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
 pub enum Align {
     /// Center-justification.
     Center,
@@ -63,7 +336,7 @@ pub enum Align {
 }
 
 /// Specifies the origin of text.
-#[derive(Clone, Copy, Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
 pub enum Origin {
     /// Places the origin at the start of the alphabetic baseline.
     Baseline,
@@ -74,10 +347,16 @@ pub enum Origin {
 }
 
 /// Provides an abstraction for representing text content.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Text {
-    /// The font face used.
+    /// The generic font family used if none of the named families are available.
     pub font: Font,
+    /// The names of the font families to use, in order of preference.
+    pub family: Vec<String>,
+    /// The names of font families to try, in order, for characters that neither the named
+    /// families nor the generic family have a glyph for. These are tried before the global
+    /// [fallback] families.
+    pub fallback: Vec<String>,
     /// The actual text content to render.
     pub content: String,
     /// The font size in pixels.
@@ -90,16 +369,58 @@ pub struct Text {
     pub max_width: Option<f64>,
     /// The justification or alignment style of the text.
     pub align: Align,
-    /// The line height in pixels.
+    /// The line height in pixels, which is the distance between the baselines of lines.
     pub line_height: f64,
+    /// The distance from the top of text with a top origin to the baseline of its first line, or
+    /// [None] to use the ascent of its font.
+    pub first_baseline: Option<f64>,
+    /// The spacing of a grid, from the top of text with a top origin, that the baselines of its
+    /// lines are snapped to so that they align with those of other text on the same grid. The
+    /// line height is rounded up to a multiple of the spacing, and the first baseline moved down
+    /// to the next line of the grid.
+    pub baseline_grid: Option<f64>,
     /// The type of text wrap used.
     pub wrap: Wrap,
+    /// The maximum number of lines each paragraph of wrapped text is laid out on, the last of
+    /// which ends in an ellipsis if the paragraph is cut short.
+    pub max_lines: Option<u32>,
+    /// The language tag whose [registered](hyphenation::register) patterns words may be
+    /// hyphenated with when the text is wrapped between words, or [None] to never hyphenate.
+    ///
+    /// No patterns are bundled, so until some are registered for the language words aren't
+    /// hyphenated on the web. The native backend hyphenates with the rules of Pango 1.44 and later
+    /// for the language instead of the registered patterns.
+    pub hyphenation: Option<String>,
     /// The font weight used.
     pub weight: Weight,
     /// The letter spacing of the text.
     pub letter_spacing: f64,
+    /// The additional spacing of word separators in the text, on top of its letter spacing.
+    pub word_spacing: f64,
+    /// Whether the font's kerning is applied to the text.
+    pub kerning: bool,
+    /// The OpenType features of the font that are explicitly enabled or disabled for the text.
+    /// Other features keep the font's defaults.
+    pub features: Vec<(Feature, bool)>,
+    /// The positions of tab stops, in pixels from the start of each line. Tabs past the last stop
+    /// advance by the distance between the last two stops, or by the only stop. When empty, the
+    /// default tab stops of the target are used.
+    pub tab_stops: Vec<f64>,
+    /// How the edges of glyphs are antialiased. Glyphs are never antialiased at the
+    /// [Low](crate::graphics::quality::Quality::Low) quality tier.
+    pub antialias: Antialias,
+    /// How strongly glyph outlines are fitted to the pixel grid.
+    pub hinting: Hinting,
     /// The origin of the rendered text.
     pub origin: Origin,
+    /// How the text is underlined.
+    pub underline: Underline,
+    /// Whether the text is struck through.
+    pub strikethrough: bool,
+    /// The color of underlines and strikethroughs, or [None] to use the color of the text.
+    pub decoration_color: Option<LDRColor>,
+    /// The ranges of the content styled differently from the rest.
+    pub spans: Vec<Span>,
 }
 
 impl Text {
@@ -111,6 +432,52 @@ impl Text {
         self.content = content.to_owned();
         self
     }
+    /// Sets the content of the text and the spans that style it from the provided
+    /// [markup](markup::parse), replacing any spans already added.
+    pub fn with_markup(mut self, input: &str) -> Self {
+        let (content, spans) = markup::parse(input);
+        self.content = content;
+        self.spans = spans;
+        self
+    }
+    /// Adds a span styling a range of the content, which takes precedence over those added before
+    /// it.
+    pub fn with_span(mut self, span: Span) -> Self {
+        self.spans.push(span);
+        self
+    }
+    /// Returns the target of the innermost link at the provided byte index of the content, as found
+    /// by hit testing the text.
+    pub fn link_at(&self, index: usize) -> Option<&str> {
+        self.spans
+            .iter()
+            .rev()
+            .filter(|span| span.range.contains(&index))
+            .find_map(|span| span.link.as_deref())
+    }
+    /// Sets the generic font family.
+    pub fn with_font(mut self, font: Font) -> Self {
+        self.font = font;
+        self
+    }
+    /// Adds a named font family, which is used in preference to any added after it.
+    pub fn with_family(mut self, family: &str) -> Self {
+        self.family.push(family.to_owned());
+        self
+    }
+    /// Adds a fallback font family, which is tried before any added after it.
+    pub fn with_fallback(mut self, family: &str) -> Self {
+        self.fallback.push(family.to_owned());
+        self
+    }
+    /// Returns every fallback family of the text in the order they are tried, followed by the
+    /// fallback families of the frame it's laid out by.
+    pub(crate) fn fallback_families<'a>(
+        &'a self,
+        frame: &'a [String],
+    ) -> impl Iterator<Item = &'a str> + 'a {
+        self.fallback.iter().chain(frame).map(String::as_str)
+    }
     /// Sets the color of the text.
     pub fn with_color(mut self, color: LDRColor) -> Self {
         self.color = color;
@@ -121,6 +488,26 @@ impl Text {
         self.italic = true;
         self
     }
+    /// Underlines the text.
+    pub fn underline(mut self) -> Self {
+        self.underline = Underline::Single;
+        self
+    }
+    /// Underlines the text twice.
+    pub fn double_underline(mut self) -> Self {
+        self.underline = Underline::Double;
+        self
+    }
+    /// Strikes through the text.
+    pub fn strikethrough(mut self) -> Self {
+        self.strikethrough = true;
+        self
+    }
+    /// Sets the color of underlines and strikethroughs.
+    pub fn with_decoration_color(mut self, color: LDRColor) -> Self {
+        self.decoration_color = Some(color);
+        self
+    }
     /// Sets the font size of the text.
     pub fn with_size(mut self, size: f64) -> Self {
         self.size = size;
@@ -131,11 +518,73 @@ impl Text {
         self.line_height = line_height;
         self
     }
+    /// Sets the distance from the top of the text to the baseline of its first line.
+    pub fn with_first_baseline(mut self, first_baseline: f64) -> Self {
+        self.first_baseline = Some(first_baseline);
+        self
+    }
+    /// Snaps the baselines of the text to a grid of the provided spacing.
+    pub fn with_baseline_grid(mut self, spacing: f64) -> Self {
+        self.baseline_grid = Some(spacing);
+        self
+    }
     /// Sets the letter spacing of the text.
     pub fn with_letter_spacing(mut self, letter_spacing: f64) -> Self {
         self.letter_spacing = letter_spacing;
         self
     }
+    /// Sets the additional spacing of word separators in the text.
+    pub fn with_word_spacing(mut self, word_spacing: f64) -> Self {
+        self.word_spacing = word_spacing;
+        self
+    }
+    /// Sets whether the font's kerning is applied to the text.
+    pub fn with_kerning(mut self, kerning: bool) -> Self {
+        self.kerning = kerning;
+        self
+    }
+    /// Enables or disables an OpenType feature of the font for the text.
+    pub fn with_feature(mut self, feature: Feature, enabled: bool) -> Self {
+        self.features.retain(|(existing, _)| *existing != feature);
+        self.features.push((feature, enabled));
+        self
+    }
+    /// Sets the positions of the tab stops of the text, which should be increasing.
+    pub fn with_tab_stops(mut self, tab_stops: Vec<f64>) -> Self {
+        self.tab_stops = tab_stops;
+        self
+    }
+    /// Sets evenly spaced tab stops of the provided width.
+    pub fn with_tab_width(mut self, width: f64) -> Self {
+        self.tab_stops = vec![width];
+        self
+    }
+    /// Returns the position of the first tab stop after the provided position, if there are any
+    /// tab stops.
+    pub(crate) fn next_tab_stop(&self, position: f64) -> Option<f64> {
+        if let Some(stop) = self.tab_stops.iter().find(|stop| **stop > position) {
+            return Some(*stop);
+        }
+        let last = *self.tab_stops.last()?;
+        let interval = match self.tab_stops.len() {
+            1 => last,
+            count => last - self.tab_stops[count - 2],
+        };
+        if interval <= 0. {
+            return Some(position);
+        }
+        Some(last + ((position - last) / interval).floor() * interval + interval)
+    }
+    /// Sets how the edges of glyphs are antialiased.
+    pub fn with_antialias(mut self, antialias: Antialias) -> Self {
+        self.antialias = antialias;
+        self
+    }
+    /// Sets how strongly glyph outlines are fitted to the pixel grid.
+    pub fn with_hinting(mut self, hinting: Hinting) -> Self {
+        self.hinting = hinting;
+        self
+    }
     /// Sets the max width of the text.
     pub fn with_max_width(mut self, max_width: f64) -> Self {
         self.max_width = Some(max_width);
@@ -146,6 +595,43 @@ impl Text {
         self.wrap = Wrap::Normal;
         self
     }
+    /// Enables text wrapping using Unicode line breaking rules.
+    pub fn wrap_unicode(mut self) -> Self {
+        self.wrap = Wrap::Unicode;
+        self
+    }
+    /// Enables text wrapping between any grapheme clusters.
+    pub fn wrap_char(mut self) -> Self {
+        self.wrap = Wrap::Char;
+        self
+    }
+    /// Enables text wrapping that also wraps within words too long to fit on a line.
+    pub fn wrap_word_char(mut self) -> Self {
+        self.wrap = Wrap::WordChar;
+        self
+    }
+    /// Disables text wrapping and cuts off lines that overflow the max width.
+    pub fn clip(mut self) -> Self {
+        self.wrap = Wrap::Clip;
+        self
+    }
+    /// Disables text wrapping and shortens lines that overflow the max width with an ellipsis.
+    pub fn truncate(mut self, ellipsize: Ellipsize) -> Self {
+        self.wrap = Wrap::Truncate(ellipsize);
+        self
+    }
+    /// Sets the maximum number of lines each paragraph of wrapped text is laid out on.
+    pub fn with_max_lines(mut self, max_lines: u32) -> Self {
+        self.max_lines = Some(max_lines);
+        self
+    }
+    /// Allows words to be hyphenated, with the patterns [registered](hyphenation::register) for the
+    /// provided language tag, where that lets wrapped text fill its lines more evenly. No patterns
+    /// are bundled, so they must be registered for words to be hyphenated on the web.
+    pub fn with_hyphenation(mut self, language: &str) -> Self {
+        self.hyphenation = Some(language.to_owned());
+        self
+    }
     /// Sets the text alignment to be centered.
     pub fn center(mut self) -> Self {
         self.align = Align::Center;
@@ -171,12 +657,88 @@ impl Text {
         self.origin = Origin::Middle;
         self
     }
+    /// Returns the text with each of its lengths scaled by the provided factor.
+    pub(crate) fn scaled(&self, factor: f64) -> Text {
+        let mut text = self.clone();
+        text.size *= factor;
+        text.line_height *= factor;
+        text.letter_spacing *= factor;
+        text.word_spacing *= factor;
+        text.max_width = text.max_width.map(|max_width| max_width * factor);
+        text.tab_stops = text.tab_stops.iter().map(|stop| stop * factor).collect();
+        text.first_baseline = text.first_baseline.map(|offset| offset * factor);
+        text.baseline_grid = text.baseline_grid.map(|spacing| spacing * factor);
+        text
+    }
+    /// Splits the provided byte range of the content where the spans that cover it change,
+    /// returning each part with the text as styled by its spans.
+    pub(crate) fn runs(&self, range: Range<usize>) -> Vec<(Range<usize>, Text)> {
+        let mut boundaries = vec![range.start, range.end];
+        for span in &self.spans {
+            for boundary in &[span.range.start, span.range.end] {
+                if range.start < *boundary && *boundary < range.end {
+                    boundaries.push(*boundary);
+                }
+            }
+        }
+        boundaries.sort();
+        boundaries.dedup();
+        boundaries
+            .windows(2)
+            .map(|run| {
+                let mut text = self.clone();
+                text.spans = vec![];
+                for span in &self.spans {
+                    if span.range.start <= run[0] && run[1] <= span.range.end {
+                        span.apply(&mut text);
+                    }
+                }
+                (run[0]..run[1], text)
+            })
+            .collect()
+    }
+    /// Returns the distance between the baselines of lines, snapped to the baseline grid.
+    pub(crate) fn line_pitch(&self) -> f64 {
+        self.snap_to_grid(self.line_height)
+    }
+    /// Returns how far the first line is moved down from where the font of the text, of the
+    /// provided ascent, places it.
+    pub(crate) fn first_line_shift(&self, ascent: f64) -> f64 {
+        match self.origin {
+            Origin::Top => self.snap_to_grid(self.first_baseline.unwrap_or(ascent)) - ascent,
+            _ => 0.,
+        }
+    }
+    fn snap_to_grid(&self, offset: f64) -> f64 {
+        match self.baseline_grid {
+            Some(spacing) if spacing > 0. => (offset / spacing - 1e-9).ceil() * spacing,
+            _ => offset,
+        }
+    }
+}
+
+/// The number of zoom breakpoints per doubling of scale.
+const ZOOM_BREAKPOINTS: f64 = 2.;
+
+/// Returns the zoom breakpoint nearest the scale of the provided transformation matrix.
+///
+/// Text is laid out at the scale of its breakpoint and drawn scaled down by it, so that zoomed
+/// text stays crisp and keeps the metrics of its drawn size while only being laid out again as
+/// its scale crosses a breakpoint.
+pub(crate) fn zoom_breakpoint(matrix: [f64; 6]) -> f64 {
+    let scale = (matrix[0] * matrix[3] - matrix[1] * matrix[2]).abs().sqrt();
+    if !scale.is_normal() {
+        return 1.;
+    }
+    2f64.powf((scale.log2() * ZOOM_BREAKPOINTS).round() / ZOOM_BREAKPOINTS)
 }
 
 impl Default for Text {
     fn default() -> Text {
         Text {
             font: Font::SystemFont,
+            family: vec![],
+            fallback: vec![],
             content: "".to_owned(),
             size: 15.,
             color: LDRColor::black(),
@@ -184,10 +746,91 @@ impl Default for Text {
             max_width: None,
             align: Align::Start,
             letter_spacing: 0.,
+            word_spacing: 0.,
+            kerning: true,
+            features: vec![],
+            tab_stops: vec![],
             line_height: 26.,
+            first_baseline: None,
+            baseline_grid: None,
             wrap: Wrap::None,
+            max_lines: None,
+            hyphenation: None,
+            antialias: Antialias::Grayscale,
+            hinting: Hinting::Full,
             origin: Origin::Top,
             weight: Weight::Normal,
+            underline: Underline::None,
+            strikethrough: false,
+            decoration_color: None,
+            spans: vec![],
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fallback_families_precede_those_of_the_frame() {
+        let text = Text::new("").with_fallback("First").with_fallback("Second");
+        let frame = default_fallback();
+        let families: Vec<&str> = text.fallback_families(&frame).collect();
+        assert_eq!(families.len(), 2 + DEFAULT_FALLBACK.len());
+        assert_eq!(families[..3], ["First", "Second", DEFAULT_FALLBACK[0]]);
+        let replaced = vec!["Frame".to_owned()];
+        assert_eq!(
+            text.fallback_families(&replaced).collect::<Vec<_>>(),
+            vec!["First", "Second", "Frame"]
+        );
+        assert_eq!(Text::new("").fallback_families(&[]).count(), 0);
+    }
+
+    #[test]
+    fn zoom_breakpoints_are_discrete() {
+        assert_eq!(zoom_breakpoint([1., 0., 0., 1., 10., 20.]), 1.);
+        assert_eq!(zoom_breakpoint([1.1, 0., 0., 1.1, 0., 0.]), 1.);
+        assert_eq!(zoom_breakpoint([0., 4., -4., 0., 0., 0.]), 4.);
+        assert_eq!(zoom_breakpoint([0.49, 0., 0., 0.51, 0., 0.]), 0.5);
+        assert_eq!(zoom_breakpoint([0., 0., 0., 0., 0., 0.]), 1.);
+        let breakpoint = zoom_breakpoint([1.4, 0., 0., 1.4, 0., 0.]);
+        assert!((breakpoint - 2f64.sqrt()).abs() < 1e-9);
+    }
+
+    #[test]
+    fn spans_split_runs() {
+        let text = Text::new("abcdef")
+            .with_span(Span::new(1..4).with_weight(Weight::Bold).with_link("x"))
+            .with_span(Span::new(2..3).with_color(LDRColor::white()));
+        let runs = text.runs(0..6);
+        let ranges: Vec<Range<usize>> = runs.iter().map(|(range, _)| range.clone()).collect();
+        assert_eq!(ranges, vec![0..1, 1..2, 2..3, 3..4, 4..6]);
+        assert_eq!(runs[0].1.weight, Weight::Normal);
+        assert_eq!(runs[2].1.weight, Weight::Bold);
+        assert_eq!(runs[2].1.color, LDRColor::white());
+        assert_eq!(runs[3].1.color, LDRColor::black());
+        assert_eq!(text.runs(2..2).len(), 0);
+        assert_eq!(text.link_at(3), Some("x"));
+        assert_eq!(text.link_at(4), None);
+    }
+
+    #[test]
+    fn baselines_snap_to_grid() {
+        let text = Text::new("").with_line_height(26.).with_baseline_grid(8.);
+        assert_eq!(text.line_pitch(), 32.);
+        assert_eq!(text.first_line_shift(12.), 4.);
+        assert_eq!(
+            text.clone().with_first_baseline(20.).first_line_shift(12.),
+            12.
+        );
+        assert_eq!(
+            text.clone().with_first_baseline(24.).first_line_shift(12.),
+            12.
+        );
+        assert_eq!(text.with_baseline_origin().first_line_shift(12.), 0.);
+        let text = Text::new("").with_first_baseline(20.);
+        assert_eq!(text.line_pitch(), 26.);
+        assert_eq!(text.first_line_shift(12.), 8.);
+    }
+}
@@ -0,0 +1,189 @@
+const ZERO_WIDTH_JOINER: char = '\u{200D}';
+
+fn is_extend(character: char) -> bool {
+    matches!(
+        character,
+        '\u{0300}'..='\u{036F}'
+        | '\u{0483}'..='\u{0489}'
+        | '\u{0591}'..='\u{05BD}'
+        | '\u{0610}'..='\u{061A}'
+        | '\u{064B}'..='\u{065F}'
+        | '\u{0670}'
+        | '\u{06D6}'..='\u{06DC}'
+        | '\u{0900}'..='\u{0903}'
+        | '\u{093A}'..='\u{094F}'
+        | '\u{0951}'..='\u{0957}'
+        | '\u{0E31}'
+        | '\u{0E34}'..='\u{0E3A}'
+        | '\u{0E47}'..='\u{0E4E}'
+        | '\u{0EB1}'
+        | '\u{0EB4}'..='\u{0EBC}'
+        | '\u{0EC8}'..='\u{0ECD}'
+        | '\u{1160}'..='\u{11FF}'
+        | '\u{1AB0}'..='\u{1AFF}'
+        | '\u{1DC0}'..='\u{1DFF}'
+        | '\u{200C}'
+        | '\u{20D0}'..='\u{20FF}'
+        | '\u{3099}'..='\u{309A}'
+        | '\u{FE00}'..='\u{FE0F}'
+        | '\u{FE20}'..='\u{FE2F}'
+        | '\u{1F3FB}'..='\u{1F3FF}'
+        | '\u{E0020}'..='\u{E007F}'
+        | '\u{E0100}'..='\u{E01EF}'
+    )
+}
+
+fn is_regional_indicator(character: char) -> bool {
+    ('\u{1F1E6}'..='\u{1F1FF}').contains(&character)
+}
+
+fn is_pictographic(character: char) -> bool {
+    matches!(
+        character,
+        '\u{00A9}'
+        | '\u{00AE}'
+        | '\u{203C}'
+        | '\u{2049}'
+        | '\u{2122}'
+        | '\u{2139}'
+        | '\u{2194}'..='\u{21AA}'
+        | '\u{231A}'..='\u{23FF}'
+        | '\u{24C2}'
+        | '\u{25AA}'..='\u{27BF}'
+        | '\u{2934}'..='\u{2935}'
+        | '\u{2B05}'..='\u{2B55}'
+        | '\u{3030}'
+        | '\u{303D}'
+        | '\u{3297}'
+        | '\u{3299}'
+        | '\u{1F000}'..='\u{1FAFF}'
+    )
+}
+
+/// Returns the byte offsets at which each grapheme cluster in the text begins.
+///
+/// Clusters follow a simplified form of the Unicode extended grapheme cluster rules (UAX #29)
+/// that keeps combining marks, variation selectors, emoji modifiers, ZWJ emoji sequences and
+/// regional indicator flag pairs together.
+pub fn boundaries(text: &str) -> Vec<usize> {
+    let mut boundaries = vec![];
+    let mut previous: Option<char> = None;
+    let mut pictographic = false;
+    let mut regional_indicators = 0;
+    for (index, character) in text.char_indices() {
+        let joined = match previous {
+            None => false,
+            Some('\r') => character == '\n',
+            Some('\n') => false,
+            Some(_) if character == '\r' || character == '\n' => false,
+            Some(_) if is_extend(character) || character == ZERO_WIDTH_JOINER => true,
+            Some(ZERO_WIDTH_JOINER) => pictographic && is_pictographic(character),
+            Some(before) => {
+                is_regional_indicator(before)
+                    && is_regional_indicator(character)
+                    && regional_indicators % 2 == 1
+            }
+        };
+        if !joined {
+            boundaries.push(index);
+            pictographic = false;
+            regional_indicators = 0;
+        }
+        if is_pictographic(character) {
+            pictographic = true;
+        }
+        if is_regional_indicator(character) {
+            regional_indicators += 1;
+        }
+        previous = Some(character);
+    }
+    boundaries
+}
+
+/// Splits the text into its grapheme clusters.
+pub fn graphemes(text: &str) -> Vec<&str> {
+    let boundaries = boundaries(text);
+    boundaries
+        .iter()
+        .zip(boundaries.iter().skip(1).chain(Some(&text.len())))
+        .map(|(start, end)| &text[*start..*end])
+        .collect()
+}
+
+/// Returns the number of grapheme clusters in the text.
+pub fn count(text: &str) -> usize {
+    boundaries(text).len()
+}
+
+/// Converts a grapheme cluster index into the byte offset at which that cluster begins. Indices
+/// at or past the end of the text give the length of the text.
+pub fn to_byte_index(text: &str, index: usize) -> usize {
+    boundaries(text).get(index).cloned().unwrap_or(text.len())
+}
+
+/// Converts a byte offset into the index of the grapheme cluster containing it. Offsets at or past
+/// the end of the text give the number of grapheme clusters.
+pub fn from_byte_index(text: &str, byte_index: usize) -> usize {
+    if byte_index >= text.len() {
+        return count(text);
+    }
+    boundaries(text)
+        .iter()
+        .take_while(|boundary| **boundary <= byte_index)
+        .count()
+        .saturating_sub(1)
+}
+
+/// Converts a grapheme cluster index into the index of the `char` at which that cluster begins.
+pub fn to_char_index(text: &str, index: usize) -> usize {
+    text[..to_byte_index(text, index)].chars().count()
+}
+
+/// Converts a `char` index into the index of the grapheme cluster containing it.
+pub fn from_char_index(text: &str, char_index: usize) -> usize {
+    let byte_index = text
+        .char_indices()
+        .nth(char_index)
+        .map(|(index, _)| index)
+        .unwrap_or(text.len());
+    from_byte_index(text, byte_index)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{count, from_char_index, graphemes, to_byte_index};
+
+    #[test]
+    fn clusters_stay_whole() {
+        let family = "\u{1F468}\u{200D}\u{1F469}\u{200D}\u{1F467}";
+        let text = format!(
+            "e\u{0301}{}\u{1F1EF}\u{1F1F5}\u{1F1FA}\u{1F1F8}\u{1F44D}\u{1F3FD}\r\nx",
+            family
+        );
+        assert_eq!(
+            graphemes(&text),
+            vec![
+                "e\u{0301}",
+                family,
+                "\u{1F1EF}\u{1F1F5}",
+                "\u{1F1FA}\u{1F1F8}",
+                "\u{1F44D}\u{1F3FD}",
+                "\r\n",
+                "x"
+            ]
+        );
+        assert_eq!(to_byte_index(&text, 2), 3 + family.len());
+        assert_eq!(from_char_index(&text, 3), 1);
+    }
+
+    #[test]
+    fn emoji_presentation_sequences_stay_whole() {
+        let keycap = "1\u{FE0F}\u{20E3}";
+        let heart = "\u{2764}\u{FE0F}";
+        let rainbow = "\u{1F3F3}\u{FE0F}\u{200D}\u{1F308}";
+        let england = "\u{1F3F4}\u{E0067}\u{E0062}\u{E0065}\u{E006E}\u{E0067}\u{E007F}";
+        let text = format!("{}{} {}{}", keycap, heart, rainbow, england);
+        assert_eq!(graphemes(&text), vec![keycap, heart, " ", rainbow, england]);
+        assert_eq!(count(&text), 5);
+    }
+}
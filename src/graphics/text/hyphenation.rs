@@ -0,0 +1,214 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, RwLock},
+};
+
+static REGISTERED: RwLock<Vec<Arc<Patterns>>> = RwLock::new(Vec::new());
+
+/// The hyphenation patterns of a language, from which the points at which its words may be
+/// hyphenated are found with the algorithm of Liang used by TeX.
+///
+/// Patterns are given in the plain format of the `hyph-*.pat.txt` files of the hyph-utf8
+/// project, such as `hy3ph he2n hena4 hen5at 1na n2at 1tio 2io o2n`, where the digits between
+/// letters are the priorities of the points between them and a `.` matches the edge of a word.
+#[derive(Clone, Debug)]
+pub struct Patterns {
+    language: String,
+    patterns: HashMap<String, Vec<u8>>,
+    exceptions: HashMap<String, Vec<usize>>,
+    left_min: usize,
+    right_min: usize,
+    max_length: usize,
+}
+
+impl Patterns {
+    /// Creates the patterns of the provided language, given as a language tag such as `en-US`,
+    /// from whitespace separated patterns. Words keep at least two letters before their first
+    /// hyphen and three after their last, as in English.
+    pub fn new(language: &str, patterns: &str) -> Patterns {
+        let mut parsed = HashMap::new();
+        let mut max_length = 0;
+        for pattern in patterns.split_whitespace() {
+            let mut letters = String::new();
+            let mut values = vec![0];
+            for character in pattern.chars() {
+                match character.to_digit(10) {
+                    Some(value) => *values.last_mut().unwrap() = value as u8,
+                    None => {
+                        letters.push(lowercase(character));
+                        values.push(0);
+                    }
+                }
+            }
+            max_length = max_length.max(values.len() - 1);
+            parsed.insert(letters, values);
+        }
+        Patterns {
+            language: language.to_owned(),
+            patterns: parsed,
+            exceptions: HashMap::new(),
+            left_min: 2,
+            right_min: 3,
+            max_length,
+        }
+    }
+    /// Adds whitespace separated words hyphenated explicitly, such as `as-so-ciate`, which are
+    /// hyphenated as given rather than by the patterns.
+    pub fn with_exceptions(mut self, exceptions: &str) -> Self {
+        for exception in exceptions.split_whitespace() {
+            let mut word = String::new();
+            let mut points = vec![];
+            for character in exception.chars() {
+                if character == '-' {
+                    points.push(word.chars().count());
+                } else {
+                    word.push(lowercase(character));
+                }
+            }
+            self.exceptions.insert(word, points);
+        }
+        self
+    }
+    /// Sets the least number of letters kept before the first hyphen and after the last hyphen
+    /// of a word.
+    pub fn with_minimums(mut self, left: usize, right: usize) -> Self {
+        self.left_min = left.max(1);
+        self.right_min = right.max(1);
+        self
+    }
+    /// Returns the language tag of the patterns.
+    pub fn language(&self) -> &str {
+        &self.language
+    }
+    /// Returns the byte offsets in a word at which it may be hyphenated.
+    pub fn hyphenate(&self, word: &str) -> Vec<usize> {
+        let characters: Vec<(usize, char)> = word.char_indices().collect();
+        let length = characters.len();
+        if length < self.left_min + self.right_min {
+            return vec![];
+        }
+        let lowered: String = characters
+            .iter()
+            .map(|(_, character)| lowercase(*character))
+            .collect();
+        let points: Vec<usize> = match self.exceptions.get(&lowered) {
+            Some(points) => points.clone(),
+            None => {
+                let padded: Vec<char> = format!(".{}.", lowered).chars().collect();
+                let mut values = vec![0; padded.len() + 1];
+                for start in 0..padded.len() {
+                    for end in start + 1..=padded.len().min(start + self.max_length) {
+                        let part: String = padded[start..end].iter().collect();
+                        if let Some(pattern) = self.patterns.get(&part) {
+                            for (offset, value) in pattern.iter().enumerate() {
+                                values[start + offset] = values[start + offset].max(*value);
+                            }
+                        }
+                    }
+                }
+                // The value before each letter of the padded word is offset by its leading dot.
+                (1..length)
+                    .filter(|index| values[index + 1] % 2 == 1)
+                    .collect()
+            }
+        };
+        points
+            .into_iter()
+            .filter(|index| *index >= self.left_min && length - *index >= self.right_min)
+            .map(|index| characters[index].0)
+            .collect()
+    }
+}
+
+/// Lowercases a character without changing the number of characters, so that indices into a
+/// lowercased word are indices into the original.
+fn lowercase(character: char) -> char {
+    let mut lowered = character.to_lowercase();
+    match (lowered.next(), lowered.next()) {
+        (Some(lowered), None) => lowered,
+        _ => character,
+    }
+}
+
+/// Registers hyphenation patterns for their language, replacing any registered for it before, so
+/// that [Text](crate::graphics::text::Text) in that language can be hyphenated as it is wrapped.
+///
+/// No patterns are bundled with the crate. Those of hyph-utf8 can be registered as they are.
+pub fn register(patterns: Patterns) {
+    let mut registered = REGISTERED.write().unwrap();
+    registered.retain(|other| !other.language.eq_ignore_ascii_case(&patterns.language));
+    registered.push(Arc::new(patterns));
+}
+
+/// Returns the hyphenation patterns registered for a language tag, or failing that for its
+/// primary language, so that `en-GB` uses patterns registered for `en` where there are none for
+/// `en-GB` itself.
+pub fn patterns(language: &str) -> Option<Arc<Patterns>> {
+    let registered = REGISTERED.read().unwrap();
+    let find = |language: &str| {
+        registered
+            .iter()
+            .find(|patterns| patterns.language.eq_ignore_ascii_case(language))
+            .cloned()
+    };
+    find(language).or_else(|| find(language.split(['-', '_']).next()?))
+}
+
+/// Returns the byte offsets in some text at which its words may be hyphenated with the patterns
+/// registered for the provided language, which are none if there are no such patterns.
+pub(crate) fn points(text: &str, language: &str) -> Vec<usize> {
+    let patterns = match patterns(language) {
+        Some(patterns) => patterns,
+        None => return vec![],
+    };
+    let mut points = vec![];
+    let mut start = None;
+    for (index, character) in text
+        .char_indices()
+        .chain(std::iter::once((text.len(), ' ')))
+    {
+        match (character.is_alphabetic(), start) {
+            (true, None) => start = Some(index),
+            (false, Some(word_start)) => {
+                points.extend(
+                    patterns
+                        .hyphenate(&text[word_start..index])
+                        .into_iter()
+                        .map(|point| word_start + point),
+                );
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    points
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LIANG: &str = "hy3ph he2n hena4 hen5at 1na n2at 1tio 2io o2n";
+
+    #[test]
+    fn words_hyphenate_at_odd_points() {
+        let patterns = Patterns::new("xx-liang", LIANG);
+        assert_eq!(patterns.hyphenate("hyphenation"), vec![2, 6]);
+        assert_eq!(patterns.hyphenate("Hyphenation"), vec![2, 6]);
+        assert_eq!(patterns.hyphenate("hyp"), Vec::<usize>::new());
+        let patterns = patterns.with_exceptions("hyphe-na-tion");
+        assert_eq!(patterns.hyphenate("hyphenation"), vec![5, 7]);
+        let patterns = patterns.with_minimums(6, 3);
+        assert_eq!(patterns.hyphenate("hyphenation"), vec![7]);
+    }
+
+    #[test]
+    fn text_hyphenates_with_registered_language() {
+        assert!(points("hyphenation", "xx-text").is_empty());
+        register(Patterns::new("xx", LIANG));
+        assert_eq!(
+            points("a hyphenation, hyphenation", "xx-text"),
+            vec![4, 8, 17, 21]
+        );
+    }
+}
@@ -0,0 +1,141 @@
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Class {
+    Alphabetic,
+    Space,
+    ZeroWidthSpace,
+    Glue,
+    Combining,
+    Hyphen,
+    Open,
+    Close,
+    NonStarter,
+    Ideographic,
+    Complex,
+    ComplexPrefix,
+}
+
+fn classify(character: char) -> Class {
+    match character {
+        ' ' | '\t' => Class::Space,
+        '\u{200B}' => Class::ZeroWidthSpace,
+        '\u{00A0}' | '\u{202F}' | '\u{2060}' | '\u{FEFF}' | '\u{2007}' | '\u{2011}' => Class::Glue,
+        '\u{0300}'..='\u{036F}'
+        | '\u{0483}'..='\u{0489}'
+        | '\u{0591}'..='\u{05BD}'
+        | '\u{0610}'..='\u{061A}'
+        | '\u{064B}'..='\u{065F}'
+        | '\u{0900}'..='\u{0903}'
+        | '\u{093A}'..='\u{094F}'
+        | '\u{1AB0}'..='\u{1AFF}'
+        | '\u{1DC0}'..='\u{1DFF}'
+        | '\u{200C}'..='\u{200D}'
+        | '\u{20D0}'..='\u{20FF}'
+        | '\u{3099}'..='\u{309A}'
+        | '\u{FE00}'..='\u{FE0F}'
+        | '\u{FE20}'..='\u{FE2F}'
+        | '\u{0E31}'
+        | '\u{0E34}'..='\u{0E3A}'
+        | '\u{0E47}'..='\u{0E4E}'
+        | '\u{0EB1}'
+        | '\u{0EB4}'..='\u{0EBC}'
+        | '\u{0EC8}'..='\u{0ECD}' => Class::Combining,
+        '-' | '\u{2010}' | '\u{2012}' | '\u{2013}' | '\u{058A}' => Class::Hyphen,
+        '(' | '[' | '{' | '\u{2018}' | '\u{201C}' | '\u{3008}' | '\u{300A}' | '\u{300C}'
+        | '\u{300E}' | '\u{3010}' | '\u{3014}' | '\u{3016}' | '\u{FF08}' | '\u{FF3B}'
+        | '\u{FF5B}' => Class::Open,
+        ')' | ']' | '}' | ',' | '.' | ':' | ';' | '!' | '?' | '\u{2019}' | '\u{201D}'
+        | '\u{3001}' | '\u{3002}' | '\u{3009}' | '\u{300B}' | '\u{300D}' | '\u{300F}'
+        | '\u{3011}' | '\u{3015}' | '\u{3017}' | '\u{FF09}' | '\u{FF0C}' | '\u{FF0E}'
+        | '\u{FF1A}' | '\u{FF1B}' | '\u{FF01}' | '\u{FF1F}' | '\u{FF3D}' | '\u{FF5D}' => {
+            Class::Close
+        }
+        '\u{3041}' | '\u{3043}' | '\u{3045}' | '\u{3047}' | '\u{3049}' | '\u{3063}'
+        | '\u{3083}' | '\u{3085}' | '\u{3087}' | '\u{308E}' | '\u{3095}' | '\u{3096}'
+        | '\u{30A1}' | '\u{30A3}' | '\u{30A5}' | '\u{30A7}' | '\u{30A9}' | '\u{30C3}'
+        | '\u{30E3}' | '\u{30E5}' | '\u{30E7}' | '\u{30EE}' | '\u{30F5}' | '\u{30F6}'
+        | '\u{30FB}' | '\u{30FC}' | '\u{309D}' | '\u{309E}' | '\u{30FD}' | '\u{30FE}'
+        | '\u{3005}' | '\u{FF70}' => Class::NonStarter,
+        '\u{0E40}'..='\u{0E44}' | '\u{0EC0}'..='\u{0EC4}' => Class::ComplexPrefix,
+        '\u{0E00}'..='\u{0E7F}'
+        | '\u{0E80}'..='\u{0EFF}'
+        | '\u{1000}'..='\u{109F}'
+        | '\u{1780}'..='\u{17FF}' => Class::Complex,
+        '\u{1100}'..='\u{115F}'
+        | '\u{2E80}'..='\u{2FFF}'
+        | '\u{3000}'..='\u{303F}'
+        | '\u{3040}'..='\u{30FF}'
+        | '\u{3100}'..='\u{31FF}'
+        | '\u{3200}'..='\u{4DBF}'
+        | '\u{4E00}'..='\u{9FFF}'
+        | '\u{A000}'..='\u{A4CF}'
+        | '\u{AC00}'..='\u{D7AF}'
+        | '\u{F900}'..='\u{FAFF}'
+        | '\u{FF00}'..='\u{FF60}'
+        | '\u{FFE0}'..='\u{FFE6}'
+        | '\u{1F300}'..='\u{1F64F}'
+        | '\u{1F900}'..='\u{1F9FF}'
+        | '\u{20000}'..='\u{3FFFD}' => Class::Ideographic,
+        _ => Class::Alphabetic,
+    }
+}
+
+fn allows_break(before: Class, after: Class) -> bool {
+    match (before, after) {
+        (_, Class::Combining) | (_, Class::Space) | (_, Class::ZeroWidthSpace) => false,
+        (Class::ZeroWidthSpace, _) | (Class::Space, _) => true,
+        (Class::Glue, _) | (_, Class::Glue) => false,
+        (Class::Open, _) | (Class::ComplexPrefix, _) => false,
+        (_, Class::Close) | (_, Class::NonStarter) => false,
+        (Class::Hyphen, Class::Alphabetic) | (Class::Hyphen, Class::Ideographic) => true,
+        (Class::Ideographic, _) | (_, Class::Ideographic) => true,
+        (Class::Close, Class::Complex)
+        | (Class::NonStarter, Class::Complex)
+        | (Class::Complex, Class::Complex)
+        | (Class::Complex, Class::ComplexPrefix) => true,
+        _ => false,
+    }
+}
+
+/// Splits text into the segments between its line break opportunities. Each segment includes
+/// any trailing whitespace, so the segments concatenate to the original text.
+///
+/// Scripts written without spaces between words, such as Thai, would require dictionary
+/// segmentation to break only between words, so they are broken between clusters instead.
+pub fn segments(text: &str) -> Vec<&str> {
+    let mut segments = vec![];
+    let mut start = 0;
+    let mut previous: Option<Class> = None;
+    for (index, character) in text.char_indices() {
+        let class = classify(character);
+        if let Some(before) = previous {
+            if allows_break(before, class) {
+                segments.push(&text[start..index]);
+                start = index;
+            }
+        }
+        // Combining marks take on the class of the character they attach to.
+        if class != Class::Combining || previous.is_none() {
+            previous = Some(class);
+        }
+    }
+    if start < text.len() {
+        segments.push(&text[start..]);
+    }
+    segments
+}
+
+#[cfg(test)]
+mod tests {
+    use super::segments;
+
+    #[test]
+    fn segments_at_opportunities() {
+        assert_eq!(segments("hello big-world"), vec!["hello ", "big-", "world"]);
+        assert_eq!(
+            segments("今日は「晴れ」です。"),
+            vec!["今", "日", "は", "「晴", "れ」", "で", "す。"]
+        );
+        assert_eq!(segments("ไม่มี"), vec!["ไม่", "มี"]);
+        assert_eq!(segments("a\u{00A0}b c"), vec!["a\u{00A0}b ", "c"]);
+    }
+}
@@ -0,0 +1,308 @@
+use crate::graphics::{
+    text::{Span, Weight},
+    LDRColor,
+};
+use std::ops::Range;
+
+/// Parses a small markup language into the content of some text and the spans that style it.
+///
+/// The markup supports:
+///
+/// - `**bold**`
+/// - `*italic*` or `_italic_`
+/// - `~~struck through~~`
+/// - `[a link](target)`, which is underlined and carries its target
+/// - `{color|colored}`, where the color is any CSS color accepted by
+///   [LDRColor::from_css]
+///
+/// Styles nest up to [MAX_MARKUP_DEPTH] deep, and a backslash escapes the character after it.
+/// Markup that isn't closed or doesn't parse is kept as literal text, so user input such as chat
+/// messages can be parsed without failing, in time linear in its length.
+pub fn parse(input: &str) -> (String, Vec<Span>) {
+    let tokens = tokenize(input);
+    let mut content = String::new();
+    let mut spans = vec![];
+    let mut opened = vec![0; tokens.len()];
+    for (index, token) in tokens.into_iter().enumerate() {
+        match token {
+            Token::Text(range) => content.push_str(&input[range]),
+            Token::Open(_, Some(mut span)) => {
+                span.range = content.len()..content.len();
+                opened[index] = spans.len();
+                spans.push(span);
+            }
+            Token::Open(literal, None) => content.push_str(&input[literal]),
+            Token::Close(open) => spans[opened[open]].range.end = content.len(),
+        }
+    }
+    (content, spans)
+}
+
+/// The most styles that can be open at once, beyond which markup is kept as literal text.
+pub const MAX_MARKUP_DEPTH: usize = 32;
+
+/// The longest color, in bytes, that `{color|colored}` markup is parsed with.
+const MAX_COLOR_LENGTH: usize = 64;
+
+#[derive(Clone, Copy, PartialEq)]
+enum Marker {
+    Bold,
+    Strikethrough,
+    Asterisk,
+    Underscore,
+    Link,
+    Color,
+}
+
+enum Token {
+    /// A range of the input kept as it is.
+    Text(Range<usize>),
+    /// The markup that opens a style, spanning the provided range of the input, with the span
+    /// the style is applied with if it's closed or kept as literal text otherwise.
+    Open(Range<usize>, Option<Span>),
+    /// The markup that closes the style opened by the token at the provided index.
+    Close(usize),
+}
+
+/// A style that's open while the input is tokenized.
+struct Open {
+    token: usize,
+    marker: Marker,
+    span: Span,
+    /// The offset in the input at which the style's content starts.
+    start: usize,
+}
+
+fn is_word(character: Option<char>) -> bool {
+    matches!(character, Some(character) if character.is_alphanumeric())
+}
+
+/// Splits the input into text and the markup that opens and closes styles in one pass, keeping
+/// the styles that are open on a stack. A closing marker closes the innermost open style it
+/// matches, and the styles opened within it that aren't closed are kept as literal text.
+fn tokenize(input: &str) -> Vec<Token> {
+    let parens: Vec<usize> = input.match_indices(')').map(|(index, _)| index).collect();
+    let mut tokens = vec![];
+    let mut stack: Vec<Open> = vec![];
+    let mut index = 0;
+    while let Some(character) = input[index..].chars().next() {
+        let rest = &input[index..];
+        let before = input[..index].chars().next_back();
+        if character == '\\' {
+            if let Some(escaped) = rest[1..].chars().next() {
+                tokens.push(Token::Text(index + 1..index + 1 + escaped.len_utf8()));
+                index += 1 + escaped.len_utf8();
+                continue;
+            }
+        }
+        let length = if let Some((marker, length, span)) = [
+            (
+                Marker::Bold,
+                "**",
+                Span::new(0..0).with_weight(Weight::Bold),
+            ),
+            (Marker::Strikethrough, "~~", Span::new(0..0).strikethrough()),
+            (Marker::Asterisk, "*", Span::new(0..0).italic()),
+            (Marker::Underscore, "_", Span::new(0..0).italic()),
+        ]
+        .iter()
+        .find(|(_, marker, _)| rest.starts_with(marker))
+        .map(|(marker, text, span)| (*marker, text.len(), span.clone()))
+        {
+            let after = rest[length..].chars().next();
+            let closes = match marker {
+                Marker::Asterisk => before != Some('*') && after != Some('*'),
+                Marker::Underscore => before != Some('_') && after != Some('_') && !is_word(after),
+                _ => true,
+            };
+            let closed = closes && close(&mut stack, &mut tokens, marker, index, true);
+            if !closed {
+                if marker != Marker::Underscore || !is_word(before) {
+                    open(&mut stack, &mut tokens, marker, span, index, length);
+                } else {
+                    tokens.push(Token::Text(index..index + length));
+                }
+            }
+            length
+        } else if character == '[' {
+            open(
+                &mut stack,
+                &mut tokens,
+                Marker::Link,
+                Span::new(0..0),
+                index,
+                1,
+            );
+            1
+        } else if character == ']' {
+            link(&mut stack, &mut tokens, &parens, input, index)
+        } else if character == '{' {
+            let color = rest[1..]
+                .char_indices()
+                .take_while(|(offset, _)| *offset <= MAX_COLOR_LENGTH)
+                .find(|(_, character)| *character == '|')
+                .and_then(|(bar, _)| Some((bar, LDRColor::from_css(&rest[1..bar + 1]).ok()?)));
+            if let Some((bar, color)) = color {
+                let span = Span::new(0..0).with_color(color);
+                open(&mut stack, &mut tokens, Marker::Color, span, index, bar + 2);
+                bar + 2
+            } else {
+                tokens.push(Token::Text(index..index + 1));
+                1
+            }
+        } else if character == '}' {
+            let after = rest[1..].chars().next();
+            let closes = before != Some('}') && after != Some('}');
+            if !(closes && close(&mut stack, &mut tokens, Marker::Color, index, false)) {
+                tokens.push(Token::Text(index..index + 1));
+            }
+            1
+        } else {
+            tokens.push(Token::Text(index..index + character.len_utf8()));
+            character.len_utf8()
+        };
+        index += length;
+    }
+    tokens
+}
+
+/// Opens a style with the markup of the provided length at the index, or keeps the markup as
+/// literal text if too many styles are open.
+fn open(
+    stack: &mut Vec<Open>,
+    tokens: &mut Vec<Token>,
+    marker: Marker,
+    span: Span,
+    index: usize,
+    length: usize,
+) {
+    if stack.len() < MAX_MARKUP_DEPTH {
+        stack.push(Open {
+            token: tokens.len(),
+            marker,
+            span,
+            start: index + length,
+        });
+        tokens.push(Token::Open(index..index + length, None));
+    } else {
+        tokens.push(Token::Text(index..index + length));
+    }
+}
+
+/// Closes the innermost open style with the marker using the markup at the index, returning
+/// whether there was one to close. Styles that must have content aren't closed by markup just
+/// after the markup that opened them.
+fn close(
+    stack: &mut Vec<Open>,
+    tokens: &mut Vec<Token>,
+    marker: Marker,
+    index: usize,
+    content: bool,
+) -> bool {
+    let position = match stack.iter().rposition(|open| open.marker == marker) {
+        Some(position) if !content || stack[position].start != index => position,
+        _ => return false,
+    };
+    stack.truncate(position + 1);
+    let open = stack.pop().unwrap();
+    if let Token::Open(_, span) = &mut tokens[open.token] {
+        *span = Some(open.span);
+    }
+    tokens.push(Token::Close(open.token));
+    true
+}
+
+/// Handles the `]` at the index, closing the innermost open link if it's followed by its
+/// target and otherwise keeping both brackets as literal text. Returns the length of the markup
+/// that was handled.
+fn link(
+    stack: &mut Vec<Open>,
+    tokens: &mut Vec<Token>,
+    parens: &[usize],
+    input: &str,
+    index: usize,
+) -> usize {
+    let position = match stack.iter().rposition(|open| open.marker == Marker::Link) {
+        Some(position) => position,
+        None => {
+            tokens.push(Token::Text(index..index + 1));
+            return 1;
+        }
+    };
+    let target = if input[index + 1..].starts_with('(') {
+        let start = index + 2;
+        parens
+            .get(parens.partition_point(|paren| *paren < start))
+            .map(|end| start..*end)
+    } else {
+        None
+    };
+    match target {
+        Some(target) => {
+            let length = target.end + 1 - index;
+            stack[position].span = Span::new(0..0).underline().with_link(&input[target]);
+            close(stack, tokens, Marker::Link, index, false);
+            length
+        }
+        None => {
+            stack.remove(position);
+            tokens.push(Token::Text(index..index + 1));
+            1
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn markup_parses_into_spans() {
+        let (content, spans) = parse("a **b *c* d** [e](f) {red|g} ~~h~~");
+        assert_eq!(content, "a b c d e g h");
+        assert_eq!(
+            spans,
+            vec![
+                Span::new(2..7).with_weight(Weight::Bold),
+                Span::new(4..5).italic(),
+                Span::new(8..9).underline().with_link("f"),
+                Span::new(10..11).with_color(LDRColor::rgb(255, 0, 0)),
+                Span::new(12..13).strikethrough(),
+            ]
+        );
+    }
+
+    #[test]
+    fn malformed_markup_is_literal() {
+        let (content, spans) = parse(r"**a snake_case_name \*b\* {nope|c} [d]");
+        assert_eq!(content, "**a snake_case_name *b* {nope|c} [d]");
+        assert!(spans.is_empty());
+        let (content, spans) = parse("_a_ b");
+        assert_eq!(content, "a b");
+        assert_eq!(spans, vec![Span::new(0..1).italic()]);
+    }
+
+    #[test]
+    fn unclosed_styles_in_closed_ones_are_literal() {
+        let (content, spans) = parse("[a *b](c) d* {red|e [f} g]");
+        assert_eq!(content, "a *b d* e [f g]");
+        assert_eq!(
+            spans,
+            vec![
+                Span::new(0..4).underline().with_link("c"),
+                Span::new(8..12).with_color(LDRColor::rgb(255, 0, 0)),
+            ]
+        );
+    }
+
+    #[test]
+    fn deeply_nested_markup_is_bounded() {
+        let input = "[".repeat(100_000) + &"*".repeat(100_000) + &"]".repeat(100_000);
+        let (content, spans) = parse(&input);
+        assert_eq!(content, input);
+        assert!(spans.is_empty());
+        let input = "**".repeat(MAX_MARKUP_DEPTH + 1) + "a";
+        let (_, spans) = parse(&input);
+        assert!(spans.len() <= MAX_MARKUP_DEPTH);
+    }
+}
@@ -0,0 +1,90 @@
+use crate::graphics::text::grapheme;
+
+use std::ops::Range;
+
+/// Options controlling how text is matched by [find].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Options {
+    /// Whether letters must match in case.
+    pub case_sensitive: bool,
+    /// Whether matches must be bounded by the ends of the text or by characters that aren't
+    /// letters or digits.
+    pub whole_word: bool,
+}
+
+impl Options {
+    /// Requires letters to match in case.
+    pub fn case_sensitive(mut self) -> Self {
+        self.case_sensitive = true;
+        self
+    }
+    /// Requires matches to be whole words.
+    pub fn whole_word(mut self) -> Self {
+        self.whole_word = true;
+        self
+    }
+}
+
+fn is_word(cluster: &str) -> bool {
+    cluster.chars().next().map_or(false, |character| {
+        character.is_alphanumeric() || character == '_'
+    })
+}
+
+/// Finds the non-overlapping occurrences of the query in the text, returned as ranges of grapheme
+/// cluster indices. Matches only begin and end on grapheme cluster boundaries.
+pub fn find(text: &str, query: &str, options: Options) -> Vec<Range<usize>> {
+    let clusters = grapheme::graphemes(text);
+    let query = grapheme::graphemes(query);
+    let equal = |a: &str, b: &str| {
+        if options.case_sensitive {
+            a == b
+        } else {
+            a == b || a.to_lowercase() == b.to_lowercase()
+        }
+    };
+    let mut matches = vec![];
+    if query.is_empty() {
+        return matches;
+    }
+    let mut index = 0;
+    while index + query.len() <= clusters.len() {
+        let end = index + query.len();
+        let found = clusters[index..end]
+            .iter()
+            .zip(query.iter())
+            .all(|(a, b)| equal(a, b))
+            && !(options.whole_word
+                && (index > 0 && is_word(clusters[index - 1])
+                    || end < clusters.len() && is_word(clusters[end])));
+        if found {
+            matches.push(index..end);
+            index = end;
+        } else {
+            index += 1;
+        }
+    }
+    matches
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{find, Options};
+
+    #[test]
+    fn finds_whole_clusters() {
+        let text = "Cafe\u{0301} cafe CAFE caféine";
+        assert_eq!(find(text, "cafe", Options::default()), vec![5..9, 10..14]);
+        assert_eq!(find(text, "Café", Options::default()), vec![15..19]);
+        assert_eq!(find(text, "cafe\u{0301}", Options::default()), vec![0..4]);
+        assert_eq!(
+            find(text, "café", Options::default().whole_word()),
+            Vec::<std::ops::Range<usize>>::new()
+        );
+        assert_eq!(
+            find(text, "CAFE", Options::default().case_sensitive()),
+            vec![10..14]
+        );
+        assert_eq!(find("aaaa", "aa", Options::default()), vec![0..2, 2..4]);
+    }
+}
@@ -0,0 +1,253 @@
+use crate::graphics::{Image, LDRColor, Rect, Texture2, Vector2};
+
+use std::collections::HashMap;
+
+/// The width and height, in pixels, of each tile of a [TiledImage].
+pub const TILE_SIZE: u32 = 256;
+
+type Tile = Vec<LDRColor>;
+
+/// A raster image stored as sparse square tiles that are only allocated once painted, so that very
+/// large canvases use memory in proportion to their painted area.
+///
+/// Changes are grouped into undo steps by [TiledImage::commit], and undoing a step only restores
+/// the tiles it changed.
+#[derive(Clone, Debug, Default)]
+pub struct TiledImage {
+    width: u32,
+    height: u32,
+    tiles: HashMap<(u32, u32), Tile>,
+    pending: HashMap<(u32, u32), Option<Tile>>,
+    history: Vec<HashMap<(u32, u32), Option<Tile>>>,
+}
+
+fn transparent() -> LDRColor {
+    LDRColor::rgba(0, 0, 0, 0)
+}
+
+/// Composites a color over another with straight alpha.
+fn over(source: LDRColor, destination: LDRColor) -> LDRColor {
+    if source.a == 255 || destination.a == 0 {
+        return source;
+    }
+    let (source_alpha, destination_alpha) =
+        (f64::from(source.a) / 255., f64::from(destination.a) / 255.);
+    let alpha = source_alpha + destination_alpha * (1. - source_alpha);
+    let channel = |source: u8, destination: u8| {
+        ((f64::from(source) * source_alpha
+            + f64::from(destination) * destination_alpha * (1. - source_alpha))
+            / alpha)
+            .round() as u8
+    };
+    LDRColor::rgba(
+        channel(source.r, destination.r),
+        channel(source.g, destination.g),
+        channel(source.b, destination.b),
+        (alpha * 255.).round() as u8,
+    )
+}
+
+impl TiledImage {
+    /// Creates a transparent image of the provided size in pixels, without allocating any tiles.
+    pub fn new(width: u32, height: u32) -> TiledImage {
+        TiledImage {
+            width,
+            height,
+            ..TiledImage::default()
+        }
+    }
+    /// Returns the size of the image in pixels.
+    pub fn get_size(&self) -> Vector2 {
+        (f64::from(self.width), f64::from(self.height)).into()
+    }
+    /// Returns the number of tiles that have been allocated.
+    pub fn allocated_tiles(&self) -> usize {
+        self.tiles.len()
+    }
+    /// Returns the position in pixels and the pixels of each allocated tile, limited to the bounds
+    /// of the image, so that only the painted parts of it need to be drawn.
+    pub fn tiles(&self) -> Vec<(Vector2, Image<LDRColor, Texture2>)> {
+        self.tiles
+            .keys()
+            .map(|(column, row)| {
+                let position =
+                    Vector2::from((f64::from(column * TILE_SIZE), f64::from(row * TILE_SIZE)));
+                let size = Vector2::from(f64::from(TILE_SIZE));
+                (position, self.export(Rect::new(position, size)))
+            })
+            .collect()
+    }
+    /// Returns the color of a pixel. Unpainted pixels are transparent.
+    pub fn pixel(&self, x: u32, y: u32) -> LDRColor {
+        self.tiles
+            .get(&(x / TILE_SIZE, y / TILE_SIZE))
+            .map_or_else(transparent, |tile| {
+                tile[((y % TILE_SIZE) * TILE_SIZE + x % TILE_SIZE) as usize]
+            })
+    }
+    /// Sets the color of a pixel, allocating its tile if necessary. Pixels outside of the image are
+    /// ignored.
+    pub fn set_pixel(&mut self, x: u32, y: u32, color: LDRColor) {
+        if x >= self.width || y >= self.height {
+            return;
+        }
+        self.tile_mut(x / TILE_SIZE, y / TILE_SIZE)
+            [((y % TILE_SIZE) * TILE_SIZE + x % TILE_SIZE) as usize] = color;
+    }
+    /// Composites an image over this one with its top left corner at the provided pixel. Fully
+    /// transparent pixels of the image leave tiles unallocated.
+    pub fn draw(&mut self, image: &Image<LDRColor, Texture2>, x: i64, y: i64) {
+        let width = image.format.width as usize;
+        for (index, color) in image.pixels.iter().enumerate() {
+            if color.a == 0 {
+                continue;
+            }
+            let (target_x, target_y) = (x + (index % width) as i64, y + (index / width) as i64);
+            if target_x < 0
+                || target_y < 0
+                || target_x >= i64::from(self.width)
+                || target_y >= i64::from(self.height)
+            {
+                continue;
+            }
+            let (target_x, target_y) = (target_x as u32, target_y as u32);
+            let destination = self.pixel(target_x, target_y);
+            self.set_pixel(target_x, target_y, over(*color, destination));
+        }
+    }
+    /// Ends the current undo step. Changes made since the previous commit are undone together.
+    pub fn commit(&mut self) {
+        if !self.pending.is_empty() {
+            let step = std::mem::take(&mut self.pending);
+            self.history.push(step);
+        }
+    }
+    /// Reverts the changes made since the previous commit, or if there are none the most recent
+    /// undo step. Returns whether anything was undone.
+    pub fn undo(&mut self) -> bool {
+        self.commit();
+        let step = match self.history.pop() {
+            Some(step) => step,
+            None => return false,
+        };
+        for (position, tile) in step {
+            match tile {
+                Some(tile) => {
+                    self.tiles.insert(position, tile);
+                }
+                None => {
+                    self.tiles.remove(&position);
+                }
+            }
+        }
+        true
+    }
+    /// Stitches the tiles covering the provided area, in pixels, into a single image. The area is
+    /// limited to the bounds of the image.
+    pub fn export(&self, area: Rect) -> Image<LDRColor, Texture2> {
+        let clamp = |value: f64, limit: u32| value.clamp(0., f64::from(limit)) as u32;
+        let (left, top) = (
+            clamp(area.position.x, self.width),
+            clamp(area.position.y, self.height),
+        );
+        let (right, bottom) = (
+            clamp(area.position.x + area.size.x, self.width).max(left),
+            clamp(area.position.y + area.size.y, self.height).max(top),
+        );
+        let mut pixels = Vec::with_capacity(((right - left) * (bottom - top)) as usize);
+        for y in top..bottom {
+            for x in left..right {
+                pixels.push(self.pixel(x, y));
+            }
+        }
+        Image {
+            pixels,
+            format: Texture2 {
+                width: right - left,
+                height: bottom - top,
+            },
+        }
+    }
+    fn tile_mut(&mut self, column: u32, row: u32) -> &mut Tile {
+        let position = (column, row);
+        if !self.pending.contains_key(&position) {
+            let previous = self.tiles.get(&position).cloned();
+            self.pending.insert(position, previous);
+        }
+        self.tiles
+            .entry(position)
+            .or_insert_with(|| vec![transparent(); (TILE_SIZE * TILE_SIZE) as usize])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tiles_are_allocated_on_demand() {
+        let mut image = TiledImage::new(32_768, 32_768);
+        assert_eq!(image.allocated_tiles(), 0);
+        assert_eq!(image.pixel(20_000, 20_000), transparent());
+        image.set_pixel(20_000, 20_000, LDRColor::white());
+        image.set_pixel(20_001, 20_000, LDRColor::white());
+        image.set_pixel(40_000, 0, LDRColor::white());
+        assert_eq!(image.allocated_tiles(), 1);
+        assert_eq!(image.pixel(20_001, 20_000), LDRColor::white());
+        let stamp = Image {
+            pixels: vec![transparent(), LDRColor::black().with_alpha(128)],
+            format: Texture2 {
+                width: 1,
+                height: 2,
+            },
+        };
+        image.draw(&stamp, 1023, 1023);
+        assert_eq!(image.allocated_tiles(), 2);
+        image.draw(&stamp, 20_000, 19_999);
+        assert_eq!(image.pixel(20_000, 20_000), LDRColor::rgb(127, 127, 127));
+    }
+
+    #[test]
+    fn undo_restores_changed_tiles() {
+        let mut image = TiledImage::new(1024, 1024);
+        image.set_pixel(0, 0, LDRColor::white());
+        image.commit();
+        image.set_pixel(0, 0, LDRColor::black());
+        image.set_pixel(600, 600, LDRColor::black());
+        assert_eq!(image.allocated_tiles(), 2);
+        assert!(image.undo());
+        assert_eq!(image.allocated_tiles(), 1);
+        assert_eq!(image.pixel(0, 0), LDRColor::white());
+        assert!(image.undo());
+        assert_eq!(image.allocated_tiles(), 0);
+        assert!(!image.undo());
+    }
+
+    #[test]
+    fn export_stitches_tiles() {
+        let mut image = TiledImage::new(1024, 1024);
+        image.set_pixel(255, 255, LDRColor::white());
+        image.set_pixel(256, 256, LDRColor::black());
+        let exported = image.export(Rect::new((255., 255.), (2., 3.)));
+        assert_eq!(exported.format.width, 2);
+        assert_eq!(exported.format.height, 3);
+        assert_eq!(exported.pixels[0], LDRColor::white());
+        assert_eq!(exported.pixels[3], LDRColor::black());
+        assert_eq!(exported.pixels[1], transparent());
+        let clipped = image.export(Rect::new((1000., -10.), (100., 20.)));
+        assert_eq!((clipped.format.width, clipped.format.height), (24, 10));
+    }
+
+    #[test]
+    fn only_allocated_tiles_are_listed() {
+        let mut image = TiledImage::new(300, 8);
+        assert!(image.tiles().is_empty());
+        image.set_pixel(280, 4, LDRColor::white());
+        let tiles = image.tiles();
+        assert_eq!(tiles.len(), 1);
+        let (position, tile) = &tiles[0];
+        assert_eq!(*position, (256., 0.).into());
+        assert_eq!((tile.format.width, tile.format.height), (44, 8));
+        assert_eq!(tile.pixels[4 * 44 + 24], LDRColor::white());
+    }
+}
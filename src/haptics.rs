@@ -0,0 +1,26 @@
+/// A handle to the haptic feedback capabilities of the platform.
+///
+/// On platforms without haptic hardware, or where it is not exposed, these operations do nothing.
+pub trait Haptics: Send + Sync {
+    /// Plays a vibration of the given duration in milliseconds at the given intensity, where
+    /// intensity is between zero and one. Intensity is ignored where the platform cannot vary it.
+    fn pulse(&self, duration: f64, intensity: f64);
+    /// Plays a short, light tick appropriate for acknowledging a change in selection.
+    fn selection(&self) {
+        self.pulse(10., 0.3);
+    }
+    #[doc(hidden)]
+    fn box_clone(&self) -> Box<dyn Haptics>;
+}
+
+impl Clone for Box<dyn Haptics> {
+    fn clone(&self) -> Box<dyn Haptics> {
+        self.box_clone()
+    }
+}
+
+/// A context that provides haptic feedback functionality.
+pub trait Provider {
+    /// Returns a haptic feedback handle for this context.
+    fn haptics(&self) -> Box<dyn Haptics>;
+}
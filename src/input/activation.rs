@@ -0,0 +1,72 @@
+/// An activation of the application through a URL, such as `myapp://open/doc/42` from a custom
+/// protocol handler or a change to the location of the page on the web.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Event {
+    /// The full URL the application was activated with.
+    pub url: String,
+}
+
+impl Event {
+    /// Returns the route the URL points to as its non-empty path segments, ignoring any query.
+    ///
+    /// The route is read from the fragment where one is present, so hash routing on the web
+    /// behaves as path routing does. For web URLs the host is not part of the route, while for
+    /// custom schemes it is, so `myapp://open/doc/42`, `https://example.com/open/doc/42` and
+    /// `https://example.com/#/open/doc/42` all have the route `["open", "doc", "42"]`.
+    pub fn route(&self) -> Vec<&str> {
+        let (scheme, rest) = match self.url.find(':') {
+            Some(index) => (&self.url[..index], &self.url[index + 1..]),
+            None => ("", &self.url[..]),
+        };
+        let (rest, fragment) = match rest.find('#') {
+            Some(index) => (&rest[..index], Some(&rest[index + 1..])),
+            None => (rest, None),
+        };
+        let route = match fragment {
+            Some(fragment) if !fragment.trim_start_matches('!').is_empty() => {
+                fragment.trim_start_matches('!')
+            }
+            _ => {
+                let rest = rest.split('?').next().unwrap_or("");
+                match (scheme, rest.starts_with("//")) {
+                    ("http", true) | ("https", true) | ("file", true) => {
+                        let rest = &rest[2..];
+                        rest.find('/').map(|index| &rest[index..]).unwrap_or("")
+                    }
+                    _ => rest,
+                }
+            }
+        };
+        route
+            .split('?')
+            .next()
+            .unwrap_or("")
+            .split('/')
+            .filter(|segment| !segment.is_empty())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn route(url: &str) -> Vec<String> {
+        let event = Event {
+            url: url.to_owned(),
+        };
+        event.route().into_iter().map(str::to_owned).collect()
+    }
+
+    #[test]
+    fn routes_agree_across_targets() {
+        let expected = vec!["open", "doc", "42"];
+        assert_eq!(route("myapp://open/doc/42"), expected);
+        assert_eq!(route("myapp:open/doc/42?from=mail"), expected);
+        assert_eq!(route("https://example.com/open/doc/42/"), expected);
+        assert_eq!(route("https://example.com/app?x=1#/open/doc/42"), expected);
+        assert_eq!(route("https://example.com/#!/open/doc/42?x=1"), expected);
+        assert!(route("https://example.com").is_empty());
+        assert!(route("https://example.com/#").is_empty());
+    }
+}
@@ -1,19 +1,29 @@
 use futures::Stream;
 
+/// Types to permit handling of activation through URLs.
+pub mod activation;
 /// Types to permit handling of keyboard input.
 pub mod keyboard;
 /// Types to permit handling of mouse input.
 pub mod mouse;
+/// Types to permit handling of pointer input from mice, touch surfaces and pens.
+pub mod pointer;
 /// Types to permit handling of windowing.
 pub mod windowing;
 
 /// Encapsulates a variety of possible input events.
 #[derive(Clone, Debug)]
 pub enum Event {
+    /// An activation through a URL.
+    Activation(activation::Event),
     /// A keyboard event.
     Keyboard(keyboard::Event),
     /// A mouse event.
     Mouse(mouse::Event),
+    /// A pointer event, identifying the kind of device that produced it. Every backend reports
+    /// input of the mouse as pointer events as well as mouse events, so handlers reading
+    /// both should ignore one of the two for [Device::Mouse](pointer::Device::Mouse).
+    Pointer(pointer::Event),
     /// A windowing event.
     Windowing(windowing::Event),
 }
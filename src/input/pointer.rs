@@ -0,0 +1,57 @@
+use crate::graphics::Vector2;
+
+/// The kind of device that produced a pointer event.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Device {
+    /// A mouse, trackpad or other indirect pointing device.
+    Mouse,
+    /// A finger or other contact on a touch surface.
+    Touch,
+    /// A stylus or pen on a digitizer.
+    Pen,
+}
+
+impl From<&str> for Device {
+    fn from(input: &str) -> Device {
+        match input {
+            "touch" => Device::Touch,
+            "pen" => Device::Pen,
+            _ => Device::Mouse,
+        }
+    }
+}
+
+/// A pointer action.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Action {
+    /// The pointer came into range of the surface. For a pen this is the start of hovering
+    /// proximity, before it makes contact.
+    Enter,
+    /// The pointer made contact or pressed its primary button.
+    Down,
+    /// The pointer moved.
+    Move,
+    /// The pointer lifted or released its primary button.
+    Up,
+    /// The pointer went out of range of the surface.
+    Leave,
+    /// The platform abandoned the pointer, for example because a system gesture took over.
+    Cancel,
+}
+
+/// A pointer event from a mouse, touch contact or pen.
+#[derive(Clone, Copy, Debug)]
+pub struct Event {
+    /// The action that occurred.
+    pub action: Action,
+    /// The kind of device that produced the event.
+    pub device: Device,
+    /// An identifier that is stable for the lifetime of a single pointer, distinguishing
+    /// simultaneous touch contacts.
+    pub id: i32,
+    /// The position of the pointer.
+    pub position: Vector2,
+    /// The normalized pressure of the pointer between zero and one. Devices without pressure
+    /// sensing report one half while in contact and zero otherwise.
+    pub pressure: f64,
+}
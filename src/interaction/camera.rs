@@ -0,0 +1,374 @@
+use crate::{
+    executor,
+    graphics::{
+        canvas::{Frame, Ticker},
+        Rect, Vector2,
+    },
+    input::{
+        keyboard::{self, Key},
+        mouse,
+        pointer::{self, Device},
+        Event, Provider,
+    },
+    interaction::kinetics::{Kinetics, Tuning},
+};
+
+use futures::Stream;
+
+use std::{
+    collections::HashMap,
+    fmt::{self, Debug, Formatter},
+    sync::{Arc, RwLock},
+};
+
+/// Returns a [Kinetics] that carries touch gestures on with momentum. It only integrates the
+/// distance panned or the logarithm of the factor zoomed from the origin, so its bounds are far
+/// enough away that they are never reached.
+fn inertia(tuning: Tuning) -> Kinetics {
+    let mut kinetics = Kinetics::new(Rect::default(), (1., 1.)).with_tuning(tuning);
+    kinetics.set_bounds(Rect::new(Vector2::from(-1e9), Vector2::from(2e9)));
+    kinetics
+}
+
+struct State {
+    frame: Box<dyn Frame>,
+    viewport: Rect,
+    size: Vector2,
+    pan_sensitivity: f64,
+    zoom_sensitivity: f64,
+    zoom_limits: (f64, f64),
+    focal_point: bool,
+    control: bool,
+    pointer_seen: bool,
+    cursor: Vector2,
+    touches: HashMap<i32, Vector2>,
+    panning: Kinetics,
+    zooming: Kinetics,
+    // The positions of the kinetics that have been applied to the viewport so far.
+    panned: Vector2,
+    zoomed: f64,
+    focus: Vector2,
+}
+
+impl State {
+    /// Returns the size of the viewport relative to that of the frame, or [None] while either is
+    /// empty.
+    fn scale(&self) -> Option<Vector2> {
+        let size = self.frame.get_size();
+        if size.x > 0. && size.y > 0. && self.viewport.size.x > 0. && self.viewport.size.y > 0. {
+            Some(self.viewport.size / size)
+        } else {
+            None
+        }
+    }
+
+    fn to_content(&self, position: Vector2) -> Vector2 {
+        match self.scale() {
+            Some(scale) => self.viewport.position + position * scale,
+            None => self.viewport.position,
+        }
+    }
+
+    fn pan(&mut self, delta: Vector2) {
+        if let Some(scale) = self.scale() {
+            self.viewport.position += delta * scale;
+        }
+    }
+
+    fn zoom(&mut self, factor: f64, focus: Vector2) {
+        if self.scale().is_none() || !factor.is_finite() || factor <= 0. {
+            return;
+        }
+        let size = self.frame.get_size();
+        let focus = if self.focal_point { focus } else { size / 2. };
+        let zoom = (size.x / self.viewport.size.x * factor)
+            .max(self.zoom_limits.0)
+            .min(self.zoom_limits.1);
+        let anchor = self.to_content(focus);
+        self.viewport.size = size / zoom;
+        self.viewport.position = anchor - focus / size * self.viewport.size;
+    }
+
+    /// Resizes the viewport along with the frame, keeping the zoom and the content at the center
+    /// of the viewport in place, and returns whether the frame had been resized.
+    fn resize(&mut self) -> bool {
+        let size = self.frame.get_size();
+        if size == self.size {
+            return false;
+        }
+        let zoom = match self.scale() {
+            Some(scale) => 1. / scale.x,
+            // A viewport that was empty, such as for a frame that hadn't been sized yet, starts
+            // out at a zoom of one.
+            None => 1.,
+        };
+        let center = self.viewport.position + self.viewport.size / 2.;
+        self.size = size;
+        self.viewport.size = size / zoom;
+        self.viewport.position = center - self.viewport.size / 2.;
+        true
+    }
+
+    /// Applies the motion of the kinetics since they were last applied, returning whether the
+    /// viewport changed.
+    fn follow(&mut self) -> bool {
+        let panned = self.panning.position();
+        let delta = panned - self.panned;
+        self.panned = panned;
+        let zoomed = self.zooming.position().x;
+        let factor = (zoomed - self.zoomed).exp();
+        self.zoomed = zoomed;
+        if factor != 1. {
+            self.zoom(factor, self.focus);
+        }
+        if delta.x != 0. || delta.y != 0. {
+            self.pan(delta);
+        }
+        factor != 1. || delta.x != 0. || delta.y != 0.
+    }
+
+    /// Brings any momentum to a stop where it is.
+    fn halt(&mut self) {
+        for kinetics in &mut [&mut self.panning, &mut self.zooming] {
+            kinetics.begin();
+            kinetics.release();
+        }
+    }
+
+    fn tick(&mut self, elapsed: f64) -> bool {
+        let resized = self.resize();
+        if !self.panning.is_active() && !self.zooming.is_active() {
+            return resized;
+        }
+        self.panning.tick(elapsed);
+        self.zooming.tick(elapsed);
+        self.follow() || resized
+    }
+
+    fn handle(&mut self, event: &Event) -> bool {
+        match event {
+            Event::Keyboard(keyboard::Event { action, .. }) => {
+                match action {
+                    keyboard::Action::Down(Key::Control(_)) => self.control = true,
+                    keyboard::Action::Up(Key::Control(_)) => self.control = false,
+                    _ => {}
+                }
+                false
+            }
+            Event::Mouse(mouse::Event::Move(position)) => {
+                if !self.pointer_seen {
+                    self.cursor = *position;
+                }
+                false
+            }
+            Event::Mouse(mouse::Event::Scroll(delta)) => {
+                if self.control {
+                    self.zoom((-delta.y * self.zoom_sensitivity).exp(), self.cursor);
+                } else {
+                    self.pan(*delta * self.pan_sensitivity);
+                }
+                true
+            }
+            Event::Pointer(event) => {
+                self.pointer_seen = true;
+                if event.device != Device::Touch {
+                    self.cursor = event.position;
+                    return false;
+                }
+                match event.action {
+                    pointer::Action::Down => {
+                        self.touches.insert(event.id, event.position);
+                        // A new touch catches the viewport, halting any momentum.
+                        self.panning.begin();
+                        self.zooming.begin();
+                        false
+                    }
+                    pointer::Action::Up | pointer::Action::Cancel | pointer::Action::Leave => {
+                        if self.touches.remove(&event.id).is_some() {
+                            if self.touches.len() < 2 && self.zooming.is_dragging() {
+                                self.zooming.release();
+                            }
+                            if self.touches.is_empty() {
+                                self.panning.release();
+                            }
+                        }
+                        false
+                    }
+                    pointer::Action::Move => self.touch_moved(event.id, event.position),
+                    pointer::Action::Enter => false,
+                }
+            }
+            _ => false,
+        }
+    }
+
+    fn touch_moved(&mut self, id: i32, position: Vector2) -> bool {
+        let previous = match self.touches.insert(id, position) {
+            Some(previous) => previous,
+            None => return false,
+        };
+        let other = self
+            .touches
+            .iter()
+            .find(|(other, _)| **other != id)
+            .map(|(_, position)| *position);
+        match other {
+            None => self.panning.drag(previous - position),
+            Some(other) => {
+                let (center, previous_center) = ((position + other) / 2., (previous + other) / 2.);
+                let separation = position - other;
+                let previous_separation = previous - other;
+                let previous_distance = previous_separation.x.hypot(previous_separation.y);
+                let distance = separation.x.hypot(separation.y);
+                if previous_distance > 0. && distance > 0. {
+                    self.focus = previous_center;
+                    self.zooming.drag(((distance / previous_distance).ln(), 0.));
+                }
+                self.panning.drag(previous_center - center);
+            }
+        }
+        self.follow()
+    }
+}
+
+/// Controls the viewport of a [Frame] in response to panning and zooming gestures.
+///
+/// Scrolling pans the viewport, and zooms it while Control is held, which is also how most
+/// platforms report trackpad pinches. Dragging a single touch pans and pinching with two touches
+/// zooms and pans together. Mouse and pen dragging are left to the application.
+///
+/// Released touch gestures carry the viewport on with momentum simulated by [Kinetics], which
+/// advances as [Camera::tick] is called, such as through [Camera::bind_ticks]. The viewport is
+/// also resized along with the [Frame] as it ticks or handles input.
+#[derive(Clone)]
+pub struct Camera {
+    state: Arc<RwLock<State>>,
+}
+
+impl Camera {
+    /// Creates a new [Camera] controlling the viewport of the provided [Frame]. The viewport starts
+    /// at the origin at a zoom of one.
+    pub fn new(frame: Box<dyn Frame>) -> Camera {
+        let size = frame.get_size();
+        let viewport = Rect::new(Vector2::default(), size);
+        frame.set_viewport(viewport);
+        Camera {
+            state: Arc::new(RwLock::new(State {
+                frame,
+                viewport,
+                size,
+                pan_sensitivity: 1.,
+                zoom_sensitivity: 0.01,
+                zoom_limits: (0.1, 10.),
+                focal_point: true,
+                control: false,
+                pointer_seen: false,
+                cursor: Vector2::default(),
+                touches: HashMap::new(),
+                panning: inertia(Tuning::default()),
+                zooming: inertia(Tuning::default()),
+                panned: Vector2::default(),
+                zoomed: 0.,
+                focus: Vector2::default(),
+            })),
+        }
+    }
+    /// Replaces the platform-default tuning of the momentum of released touch gestures.
+    pub fn with_tuning(self, tuning: Tuning) -> Self {
+        {
+            let mut state = self.state.write().unwrap();
+            state.panning = inertia(tuning);
+            state.zooming = inertia(tuning);
+            state.panned = Vector2::default();
+            state.zoomed = 0.;
+        }
+        self
+    }
+    /// Sets the multiplier applied to scroll distances when panning and the exponential rate at
+    /// which scroll distances zoom the viewport.
+    pub fn with_sensitivity(self, pan: f64, zoom: f64) -> Self {
+        {
+            let mut state = self.state.write().unwrap();
+            state.pan_sensitivity = pan;
+            state.zoom_sensitivity = zoom;
+        }
+        self
+    }
+    /// Sets the minimum and maximum zoom, where a zoom of one shows content at its natural size.
+    pub fn with_zoom_limits(self, minimum: f64, maximum: f64) -> Self {
+        self.state.write().unwrap().zoom_limits = (minimum, maximum);
+        self
+    }
+    /// Sets whether zooming keeps the content under the cursor or pinch fixed in place, rather than
+    /// the content at the center of the [Frame].
+    pub fn with_focal_point(self, focal_point: bool) -> Self {
+        self.state.write().unwrap().focal_point = focal_point;
+        self
+    }
+    /// Returns the current viewport.
+    pub fn viewport(&self) -> Rect {
+        self.state.read().unwrap().viewport
+    }
+    /// Sets the viewport.
+    pub fn set_viewport(&self, viewport: Rect) {
+        let mut state = self.state.write().unwrap();
+        state.halt();
+        state.viewport = viewport;
+        state.frame.set_viewport(viewport);
+    }
+    /// Returns the current zoom, where a zoom of one shows content at its natural size. The zoom
+    /// is one while the [Frame] or viewport is empty.
+    pub fn zoom(&self) -> f64 {
+        match self.state.read().unwrap().scale() {
+            Some(scale) => 1. / scale.x,
+            None => 1.,
+        }
+    }
+    /// Updates the viewport in response to an input event, returning whether it changed.
+    pub fn handle(&self, event: &Event) -> bool {
+        let mut state = self.state.write().unwrap();
+        let resized = state.resize();
+        let changed = state.handle(event) || resized;
+        if changed {
+            let viewport = state.viewport;
+            state.frame.set_viewport(viewport);
+        }
+        changed
+    }
+    /// Advances the momentum of released gestures by the provided elapsed time in milliseconds and
+    /// resizes the viewport if the [Frame] has been resized, returning whether the viewport
+    /// changed.
+    pub fn tick(&self, elapsed: f64) -> bool {
+        let mut state = self.state.write().unwrap();
+        let changed = state.tick(elapsed);
+        if changed {
+            let viewport = state.viewport;
+            state.frame.set_viewport(viewport);
+        }
+        changed
+    }
+    /// Binds [Camera::tick] as a tick handler of the provided context.
+    pub fn bind_ticks(&self, ticker: &mut dyn Ticker) {
+        let camera = self.clone();
+        ticker.bind(Box::new(move |elapsed| {
+            camera.tick(elapsed);
+        }));
+    }
+    /// Spawns a task on the current executor that passes the input of the provided context to
+    /// [Camera::handle] for as long as the input stream lasts.
+    pub fn bind_gestures(&self, context: &dyn Provider) {
+        let camera = self.clone();
+        executor::spawn(context.input().for_each(move |event| {
+            camera.handle(&event);
+            Ok(())
+        }));
+    }
+}
+
+impl Debug for Camera {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Camera")
+            .field("viewport", &self.viewport())
+            .finish()
+    }
+}
@@ -0,0 +1,426 @@
+use crate::{
+    accessibility::Preferences,
+    graphics::{Rect, Vector2},
+};
+
+/// The span of time, in milliseconds, over which drag samples contribute to release velocity.
+const VELOCITY_WINDOW: f64 = 100.;
+/// The distance from rest below which a spring is considered settled.
+const SETTLE_DISTANCE: f64 = 0.5;
+
+/// Tuning parameters for [Kinetics].
+#[derive(Clone, Copy, Debug)]
+pub struct Tuning {
+    /// The fraction of velocity retained after each millisecond of free momentum.
+    pub deceleration: f64,
+    /// The resistance of overscroll while dragging, where zero permits no overscroll and larger
+    /// values permit content to be dragged further past its bounds.
+    pub rubber_band: f64,
+    /// The angular frequency, in radians per millisecond, of the spring that returns overscrolled
+    /// content to its bounds and carries content to snap points.
+    pub spring_frequency: f64,
+    /// The speed, in units per millisecond, below which momentum comes to rest.
+    pub minimum_velocity: f64,
+}
+
+impl Tuning {
+    /// Long, gliding momentum and loose overscroll, following the conventions of touch-first
+    /// platforms and the web.
+    pub fn smooth() -> Tuning {
+        Tuning {
+            deceleration: 0.998,
+            rubber_band: 0.55,
+            spring_frequency: 0.012,
+            minimum_velocity: 0.01,
+        }
+    }
+    /// Short momentum and stiff overscroll, following the conventions of desktop platforms.
+    pub fn firm() -> Tuning {
+        Tuning {
+            deceleration: 0.995,
+            rubber_band: 0.3,
+            spring_frequency: 0.018,
+            minimum_velocity: 0.02,
+        }
+    }
+    /// Momentum that stops almost immediately and no overscroll, for users who prefer reduced motion.
+    pub fn reduced() -> Tuning {
+        Tuning {
+            deceleration: 0.95,
+            rubber_band: 0.,
+            spring_frequency: 0.05,
+            minimum_velocity: 0.05,
+        }
+    }
+}
+
+impl From<Preferences> for Tuning {
+    /// Returns the platform-default tuning, or [Tuning::reduced] where reduced motion is preferred.
+    fn from(preferences: Preferences) -> Tuning {
+        if preferences.reduced_motion {
+            Tuning::reduced()
+        } else {
+            Tuning::default()
+        }
+    }
+}
+
+impl Default for Tuning {
+    #[cfg(any(target_os = "macos", target_arch = "wasm32"))]
+    fn default() -> Tuning {
+        Tuning::smooth()
+    }
+    #[cfg(not(any(target_os = "macos", target_arch = "wasm32")))]
+    fn default() -> Tuning {
+        Tuning::firm()
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Motion {
+    Rest,
+    Dragging,
+    Momentum,
+    Spring(f64),
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Axis {
+    position: f64,
+    velocity: f64,
+    min: f64,
+    max: f64,
+    dimension: f64,
+    motion: Motion,
+}
+
+impl Axis {
+    fn new(min: f64, max: f64, dimension: f64) -> Axis {
+        Axis {
+            position: min,
+            velocity: 0.,
+            min,
+            max,
+            dimension,
+            motion: Motion::Rest,
+        }
+    }
+    fn clamp(&self, position: f64) -> f64 {
+        position.max(self.min).min(self.max)
+    }
+    fn overscroll(&self) -> f64 {
+        self.position - self.clamp(self.position)
+    }
+    fn set_bounds(&mut self, min: f64, max: f64) {
+        self.min = min;
+        self.max = max;
+        if let Motion::Rest | Motion::Momentum = self.motion {
+            if self.overscroll() != 0. {
+                self.motion = Motion::Spring(self.clamp(self.position));
+            }
+        }
+    }
+    fn begin(&mut self) {
+        self.velocity = 0.;
+        self.motion = Motion::Dragging;
+    }
+    fn drag(&mut self, delta: f64, tuning: &Tuning) {
+        let limit = self.dimension;
+        let resistance = tuning.rubber_band;
+        if resistance <= 0. || limit <= 0. {
+            self.position = self.clamp(self.position + delta);
+            return;
+        }
+        // Map the current overscroll back onto the unresisted drag distance that would have
+        // produced it so that resistance stays continuous across drags in either direction.
+        let overscroll = self.overscroll();
+        let distance = overscroll.abs().min(limit * 0.999);
+        let raw = self.clamp(self.position)
+            + overscroll.signum() * distance * limit / (resistance * (limit - distance))
+            + delta;
+        let overscroll = raw - self.clamp(raw);
+        let resisted = (1. - 1. / (overscroll.abs() * resistance / limit + 1.)) * limit;
+        self.position = self.clamp(raw) + overscroll.signum() * resisted;
+    }
+    fn release(&mut self, target: Option<f64>, tuning: &Tuning) {
+        self.motion = match target {
+            Some(target) => Motion::Spring(self.clamp(target)),
+            None if self.overscroll() != 0. => Motion::Spring(self.clamp(self.position)),
+            None => Motion::Momentum,
+        };
+        self.settle(tuning);
+    }
+    fn rest_position(&self, tuning: &Tuning) -> f64 {
+        self.position - self.velocity / tuning.deceleration.ln()
+    }
+    fn tick(&mut self, elapsed: f64, tuning: &Tuning) {
+        match self.motion {
+            Motion::Rest | Motion::Dragging => {}
+            Motion::Momentum => {
+                let decay = tuning.deceleration.powf(elapsed);
+                self.position += self.velocity * (decay - 1.) / tuning.deceleration.ln();
+                self.velocity *= decay;
+                let bound = self.clamp(self.position);
+                if bound != self.position {
+                    self.motion = Motion::Spring(bound);
+                }
+            }
+            Motion::Spring(target) => {
+                // A critically damped spring, stepped in short intervals for stability.
+                let omega = tuning.spring_frequency;
+                let mut remaining = elapsed;
+                while remaining > 0. {
+                    let step = remaining.min(4.);
+                    let acceleration =
+                        -omega * omega * (self.position - target) - 2. * omega * self.velocity;
+                    self.velocity += acceleration * step;
+                    self.position += self.velocity * step;
+                    remaining -= step;
+                }
+            }
+        }
+        self.settle(tuning);
+    }
+    fn settle(&mut self, tuning: &Tuning) {
+        match self.motion {
+            Motion::Momentum if self.velocity.abs() < tuning.minimum_velocity => {
+                self.velocity = 0.;
+                self.motion = Motion::Rest;
+            }
+            Motion::Spring(target)
+                if (self.position - target).abs() < SETTLE_DISTANCE
+                    && self.velocity.abs() < tuning.minimum_velocity =>
+            {
+                self.position = target;
+                self.velocity = 0.;
+                self.motion = Motion::Rest;
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Simulates the physics of gesture-driven scrolling, including release velocity tracking,
+/// momentum with deceleration, rubber-band overscroll and snap points.
+///
+/// Drags and releases are fed in as input arrives and [Kinetics::tick] is driven from a
+/// [Ticker](crate::graphics::canvas::Ticker) handler with the elapsed time in milliseconds, after
+/// which [Kinetics::position] gives the scroll offset to present.
+#[derive(Clone, Debug)]
+pub struct Kinetics {
+    x: Axis,
+    y: Axis,
+    tuning: Tuning,
+    snap_points: Vec<Vector2>,
+    samples: Vec<(f64, Vector2)>,
+    time: f64,
+}
+
+impl Kinetics {
+    /// Creates a new [Kinetics] constrained to the provided bounds of scroll position, with
+    /// overscroll scaled relative to the provided viewport size. The position starts at the origin of the bounds.
+    pub fn new<T>(bounds: Rect, viewport: T) -> Kinetics
+    where
+        T: Into<Vector2>,
+    {
+        let viewport = viewport.into();
+        let min = bounds.position;
+        let max = bounds.position + bounds.size;
+        Kinetics {
+            x: Axis::new(min.x, max.x, viewport.x),
+            y: Axis::new(min.y, max.y, viewport.y),
+            tuning: Tuning::default(),
+            snap_points: vec![],
+            samples: vec![],
+            time: 0.,
+        }
+    }
+    /// Replaces the platform-default tuning.
+    pub fn with_tuning(mut self, tuning: Tuning) -> Self {
+        self.tuning = tuning;
+        self
+    }
+    /// Uses the tuning appropriate to the provided accessibility preferences, such as those given
+    /// by [accessibility](crate::accessibility::Provider::accessibility) on the context.
+    pub fn with_preferences(self, preferences: Preferences) -> Self {
+        self.with_tuning(preferences.into())
+    }
+    /// Adds positions at which released content will come to rest.
+    pub fn with_snap_points<T>(mut self, snap_points: T) -> Self
+    where
+        T: IntoIterator<Item = Vector2>,
+    {
+        self.snap_points.extend(snap_points);
+        self
+    }
+    /// Changes the bounds of scroll position. Content outside the new bounds springs back into them.
+    pub fn set_bounds(&mut self, bounds: Rect) {
+        let max = bounds.position + bounds.size;
+        self.x.set_bounds(bounds.position.x, max.x);
+        self.y.set_bounds(bounds.position.y, max.y);
+    }
+    /// Begins a drag gesture, halting any motion in progress.
+    pub fn begin(&mut self) {
+        self.x.begin();
+        self.y.begin();
+        self.samples.clear();
+        self.samples.push((self.time, self.position()));
+    }
+    /// Moves the content by the provided delta as part of a drag gesture, resisting movement past the bounds.
+    pub fn drag<T>(&mut self, delta: T)
+    where
+        T: Into<Vector2>,
+    {
+        let delta = delta.into();
+        if !self.is_dragging() {
+            self.begin();
+        }
+        self.x.drag(delta.x, &self.tuning);
+        self.y.drag(delta.y, &self.tuning);
+        let time = self.time;
+        self.samples
+            .retain(|(sample_time, _)| time - sample_time <= VELOCITY_WINDOW);
+        self.samples.push((time, self.position()));
+    }
+    /// Ends a drag gesture, releasing the content with the velocity of the recent drag motion.
+    pub fn release(&mut self) {
+        let velocity = self.tracked_velocity();
+        self.fling(velocity);
+    }
+    /// Releases the content with the provided velocity in units per millisecond, such as from a
+    /// scroll wheel or programmatic fling.
+    pub fn fling<T>(&mut self, velocity: T)
+    where
+        T: Into<Vector2>,
+    {
+        let velocity = velocity.into();
+        self.samples.clear();
+        self.x.velocity = velocity.x;
+        self.y.velocity = velocity.y;
+        let target = self.snap_target();
+        self.x.release(target.map(|target| target.x), &self.tuning);
+        self.y.release(target.map(|target| target.y), &self.tuning);
+    }
+    /// Advances the simulation by the provided elapsed time in milliseconds.
+    pub fn tick(&mut self, elapsed: f64) {
+        self.time += elapsed;
+        self.x.tick(elapsed, &self.tuning);
+        self.y.tick(elapsed, &self.tuning);
+    }
+    /// Returns the current scroll position.
+    pub fn position(&self) -> Vector2 {
+        (self.x.position, self.y.position).into()
+    }
+    /// Returns the current velocity in units per millisecond.
+    pub fn velocity(&self) -> Vector2 {
+        (self.x.velocity, self.y.velocity).into()
+    }
+    /// Returns the distance the content is currently displaced past its bounds.
+    pub fn overscroll(&self) -> Vector2 {
+        (self.x.overscroll(), self.y.overscroll()).into()
+    }
+    /// Returns true if the content is being dragged or is in motion.
+    pub fn is_active(&self) -> bool {
+        !matches!((self.x.motion, self.y.motion), (Motion::Rest, Motion::Rest))
+    }
+    /// Returns true if a drag gesture is in progress.
+    pub fn is_dragging(&self) -> bool {
+        matches!(self.x.motion, Motion::Dragging)
+    }
+    fn tracked_velocity(&self) -> Vector2 {
+        match (self.samples.first(), self.samples.last()) {
+            (Some((start_time, start)), Some((end_time, end))) if end_time > start_time => {
+                (*end - *start) / (end_time - start_time)
+            }
+            _ => Vector2::default(),
+        }
+    }
+    fn snap_target(&self) -> Option<Vector2> {
+        let rest: Vector2 = (
+            self.x.rest_position(&self.tuning),
+            self.y.rest_position(&self.tuning),
+        )
+            .into();
+        self.snap_points
+            .iter()
+            .map(|point| {
+                let offset = *point - rest;
+                (offset.x * offset.x + offset.y * offset.y, *point)
+            })
+            .fold(
+                None,
+                |closest: Option<(f64, Vector2)>, candidate| match closest {
+                    Some(closest) if closest.0 <= candidate.0 => Some(closest),
+                    _ => Some(candidate),
+                },
+            )
+            .map(|(_, point)| point)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settle(kinetics: &mut Kinetics) {
+        for _ in 0..10_000 {
+            if !kinetics.is_active() {
+                return;
+            }
+            kinetics.tick(16.);
+        }
+        panic!("kinetics never came to rest");
+    }
+
+    #[test]
+    fn momentum_decays_to_rest() {
+        for tuning in &[Tuning::smooth(), Tuning::firm()] {
+            let mut kinetics =
+                Kinetics::new(Rect::new((0., 0.), (1000., 0.)), (100., 100.)).with_tuning(*tuning);
+            kinetics.fling((1., 0.));
+            let mut velocity = kinetics.velocity().x;
+            while kinetics.is_active() {
+                kinetics.tick(16.);
+                assert!(kinetics.velocity().x < velocity || kinetics.velocity().x == 0.);
+                velocity = kinetics.velocity().x;
+            }
+            // Momentum stops once it falls below the minimum velocity, short of where unbroken
+            // exponential decay would come to rest.
+            let decay = tuning.deceleration.ln();
+            let position = kinetics.position().x;
+            assert!(position <= -1. / decay);
+            assert!(position >= (tuning.minimum_velocity - 1.) / decay);
+            assert_eq!(kinetics.velocity().x, 0.);
+        }
+    }
+
+    #[test]
+    fn firm_momentum_stops_sooner() {
+        let travel = |tuning: Tuning| {
+            let mut kinetics =
+                Kinetics::new(Rect::new((0., 0.), (1000., 0.)), (100., 100.)).with_tuning(tuning);
+            kinetics.fling((1., 0.));
+            settle(&mut kinetics);
+            kinetics.position().x
+        };
+        assert!(travel(Tuning::firm()) < travel(Tuning::smooth()));
+    }
+
+    #[test]
+    fn released_content_snaps_to_nearest_point() {
+        // Smooth momentum would come to rest near 500 and firm momentum near 200, so each snaps to
+        // the point closest to where it would have stopped.
+        for (tuning, expected) in &[(Tuning::smooth(), 600.), (Tuning::firm(), 300.)] {
+            let mut kinetics = Kinetics::new(Rect::new((0., 0.), (1000., 0.)), (100., 100.))
+                .with_tuning(*tuning)
+                .with_snap_points(vec![
+                    Vector2::from((0., 0.)),
+                    (300., 0.).into(),
+                    (600., 0.).into(),
+                ]);
+            kinetics.fling((1., 0.));
+            settle(&mut kinetics);
+            assert_eq!(kinetics.position(), Vector2::from((*expected, 0.)));
+        }
+    }
+}
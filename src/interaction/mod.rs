@@ -0,0 +1,10 @@
+/// Provides gesture-driven panning and zooming of a frame's viewport.
+pub mod camera;
+/// Provides scroll physics for gesture-driven kinetic scrolling.
+pub mod kinetics;
+/// Provides keyboard equivalents for pointer-driven interactions.
+pub mod nudge;
+/// Provides palm rejection for pen and touch input.
+pub mod palm;
+/// Provides a frame that scrolls kinetically across content larger than itself.
+pub mod scroll;
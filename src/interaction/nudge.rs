@@ -0,0 +1,132 @@
+use crate::{
+    graphics::Vector2,
+    input::keyboard::{Action, Arrow, Event, Key},
+};
+
+/// An interaction requested from the keyboard as an alternative to a pointer gesture.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Command {
+    /// Moves the focused item by the given offset, where positive y is down the screen. This
+    /// stands in for dragging.
+    Nudge(Vector2),
+    /// Activates or commits the focused item. This stands in for clicking or dropping.
+    Activate,
+    /// Abandons the interaction in progress. This stands in for releasing outside a drop target.
+    Cancel,
+}
+
+/// Translates keyboard input into [Command]s so that pointer-driven interactions remain usable
+/// without a pointing device.
+///
+/// Arrow keys nudge by a standard step, which is coarse while Shift is held and fine while Alt is
+/// held. Enter and Space activate and Escape cancels.
+#[derive(Clone, Copy, Debug)]
+pub struct Nudger {
+    step: f64,
+    coarse_step: f64,
+    fine_step: f64,
+    shift: bool,
+    alt: bool,
+}
+
+impl Nudger {
+    /// Creates a new [Nudger] with steps of one, ten and one tenth of a unit.
+    pub fn new() -> Nudger {
+        Nudger {
+            step: 1.,
+            coarse_step: 10.,
+            fine_step: 0.1,
+            shift: false,
+            alt: false,
+        }
+    }
+    /// Sets the standard, coarse and fine step distances.
+    pub fn with_steps(mut self, step: f64, coarse_step: f64, fine_step: f64) -> Self {
+        self.step = step;
+        self.coarse_step = coarse_step;
+        self.fine_step = fine_step;
+        self
+    }
+    /// Handles a keyboard event, returning the requested [Command] if any. All keyboard events
+    /// should be passed in while the item has focus so that modifier state is tracked.
+    pub fn handle(&mut self, event: &Event) -> Option<Command> {
+        let (key, down) = match event.action {
+            Action::Down(key) => (key, true),
+            Action::Up(key) => (key, false),
+        };
+        match key {
+            Key::Shift(_) => self.shift = down,
+            Key::Alt(_) => self.alt = down,
+            _ => {}
+        }
+        if !down {
+            return None;
+        }
+        let step = if self.shift {
+            self.coarse_step
+        } else if self.alt {
+            self.fine_step
+        } else {
+            self.step
+        };
+        match key {
+            Key::Arrow(Arrow::Up) => Some(Command::Nudge((0., -step).into())),
+            Key::Arrow(Arrow::Down) => Some(Command::Nudge((0., step).into())),
+            Key::Arrow(Arrow::Left) => Some(Command::Nudge((-step, 0.).into())),
+            Key::Arrow(Arrow::Right) => Some(Command::Nudge((step, 0.).into())),
+            Key::Enter | Key::Space => Some(Command::Activate),
+            Key::Escape => Some(Command::Cancel),
+            _ => None,
+        }
+    }
+    /// Clears tracked modifier state, such as when focus moves elsewhere and key releases may be missed.
+    pub fn reset(&mut self) {
+        self.shift = false;
+        self.alt = false;
+    }
+}
+
+impl Default for Nudger {
+    fn default() -> Nudger {
+        Nudger::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Command, Nudger};
+    use crate::input::keyboard::{Action, Arrow, Event, Key, Location};
+
+    fn event(action: Action) -> Event {
+        Event {
+            action,
+            printable: None,
+        }
+    }
+
+    #[test]
+    fn modifiers_scale_nudges() {
+        let mut nudger = Nudger::new();
+        let left = event(Action::Down(Key::Arrow(Arrow::Left)));
+        assert_eq!(nudger.handle(&left), Some(Command::Nudge((-1., 0.).into())));
+        assert_eq!(
+            nudger.handle(&event(Action::Down(Key::Shift(Location::Left)))),
+            None
+        );
+        assert_eq!(
+            nudger.handle(&left),
+            Some(Command::Nudge((-10., 0.).into()))
+        );
+        nudger.handle(&event(Action::Up(Key::Shift(Location::Left))));
+        nudger.handle(&event(Action::Down(Key::Alt(Location::Right))));
+        assert_eq!(
+            nudger.handle(&event(Action::Down(Key::Arrow(Arrow::Down)))),
+            Some(Command::Nudge((0., 0.1).into()))
+        );
+        assert_eq!(
+            nudger.handle(&event(Action::Down(Key::Enter))),
+            Some(Command::Activate)
+        );
+        assert_eq!(nudger.handle(&event(Action::Up(Key::Enter))), None);
+    }
+}
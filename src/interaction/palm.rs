@@ -0,0 +1,129 @@
+use crate::input::pointer::{Action, Device, Event};
+use std::collections::{HashMap, HashSet};
+
+/// When touch input is treated as an unintended palm contact.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Policy {
+    /// Touch input is never rejected.
+    Off,
+    /// Touch input is rejected while a pen is in proximity of the surface.
+    WhilePen,
+    /// Touch input is rejected once any pen has been used, for applications that draw with the
+    /// pen and have no use for touch afterwards.
+    PenOnly,
+}
+
+/// Filters pointer events according to a palm rejection [Policy] so that a hand resting on the
+/// surface does not interfere with drawing.
+///
+/// A touch that begins while rejected stays rejected until it lifts, even if the pen leaves in the
+/// meantime. A touch that was accepted when rejection begins is ended with a cancellation so that
+/// any interaction it drove can be abandoned.
+#[derive(Clone, Debug)]
+pub struct PalmRejection {
+    policy: Policy,
+    pens: HashSet<i32>,
+    touches: HashMap<i32, bool>,
+    pen_seen: bool,
+}
+
+impl PalmRejection {
+    /// Creates a new [PalmRejection] with the given policy.
+    pub fn new(policy: Policy) -> PalmRejection {
+        PalmRejection {
+            policy,
+            pens: HashSet::new(),
+            touches: HashMap::new(),
+            pen_seen: false,
+        }
+    }
+    /// Returns whether touch input is currently rejected.
+    pub fn rejecting(&self) -> bool {
+        match self.policy {
+            Policy::Off => false,
+            Policy::WhilePen => !self.pens.is_empty(),
+            Policy::PenOnly => self.pen_seen,
+        }
+    }
+    /// Filters a pointer event, returning the event to act upon if any. All pointer events should
+    /// be passed in so that pen proximity and touch contacts are tracked.
+    pub fn filter(&mut self, event: Event) -> Option<Event> {
+        match event.device {
+            Device::Mouse => Some(event),
+            Device::Pen => {
+                match event.action {
+                    Action::Leave | Action::Cancel => {
+                        self.pens.remove(&event.id);
+                    }
+                    _ => {
+                        self.pens.insert(event.id);
+                        self.pen_seen = true;
+                    }
+                }
+                Some(event)
+            }
+            Device::Touch => {
+                let rejecting = self.rejecting();
+                let accepted = match event.action {
+                    Action::Down => {
+                        self.touches.insert(event.id, !rejecting);
+                        return if rejecting { None } else { Some(event) };
+                    }
+                    Action::Up | Action::Cancel => self.touches.remove(&event.id),
+                    _ => self.touches.get(&event.id).cloned(),
+                }
+                .unwrap_or(!rejecting);
+                if !accepted {
+                    None
+                } else if rejecting {
+                    if self.touches.contains_key(&event.id) {
+                        self.touches.insert(event.id, false);
+                    }
+                    Some(Event {
+                        action: Action::Cancel,
+                        pressure: 0.,
+                        ..event
+                    })
+                } else {
+                    Some(event)
+                }
+            }
+        }
+    }
+}
+
+impl Default for PalmRejection {
+    fn default() -> PalmRejection {
+        PalmRejection::new(Policy::WhilePen)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{PalmRejection, Policy};
+    use crate::input::pointer::{Action, Device, Event};
+
+    fn event(device: Device, id: i32, action: Action) -> Event {
+        Event {
+            action,
+            device,
+            id,
+            position: (0., 0.).into(),
+            pressure: 0.5,
+        }
+    }
+
+    #[test]
+    fn touch_rejected_near_pen() {
+        let mut palm = PalmRejection::new(Policy::WhilePen);
+        assert!(palm.filter(event(Device::Touch, 1, Action::Down)).is_some());
+        assert!(palm.filter(event(Device::Pen, 2, Action::Enter)).is_some());
+        let cancel = palm.filter(event(Device::Touch, 1, Action::Move)).unwrap();
+        assert_eq!(cancel.action, Action::Cancel);
+        assert!(palm.filter(event(Device::Touch, 1, Action::Up)).is_none());
+        assert!(palm.filter(event(Device::Touch, 3, Action::Down)).is_none());
+        palm.filter(event(Device::Pen, 2, Action::Leave));
+        assert!(palm.filter(event(Device::Touch, 3, Action::Move)).is_none());
+        assert!(palm.filter(event(Device::Touch, 4, Action::Down)).is_some());
+    }
+}
@@ -0,0 +1,329 @@
+use crate::{
+    executor,
+    graphics::{
+        canvas::{Frame, Ticker},
+        Rect, Vector2,
+    },
+    input::{
+        mouse::{self, Button},
+        Event, Provider,
+    },
+    interaction::kinetics::{Kinetics, Tuning},
+};
+
+use futures::Stream;
+
+use std::{
+    fmt::{self, Debug, Formatter},
+    sync::{Arc, RwLock},
+};
+
+/// The scroll position of content within a frame of some size, independent of the frame itself.
+struct Scroll {
+    kinetics: Kinetics,
+    content: Vector2,
+    size: Vector2,
+    sensitivity: f64,
+    cursor: Vector2,
+    dragging: bool,
+}
+
+impl Scroll {
+    fn new(content: Vector2, size: Vector2, tuning: Tuning) -> Scroll {
+        let mut scroll = Scroll {
+            kinetics: Kinetics::new(Rect::default(), size).with_tuning(tuning),
+            content,
+            size,
+            sensitivity: 1.,
+            cursor: Vector2::default(),
+            dragging: false,
+        };
+        scroll.kinetics.set_bounds(scroll.bounds());
+        scroll
+    }
+
+    /// Returns the range of offsets over which the content can be scrolled, which is empty along
+    /// any axis on which the content fits within the frame.
+    fn bounds(&self) -> Rect {
+        let overflow = self.content - self.size;
+        Rect::new(Vector2::default(), (overflow.x.max(0.), overflow.y.max(0.)))
+    }
+
+    fn offset(&self) -> Vector2 {
+        self.kinetics.position()
+    }
+
+    /// Resizes the content or the frame, returning whether either changed. Content that is
+    /// scrolled past its new bounds springs back into them.
+    fn resize(&mut self, content: Vector2, size: Vector2) -> bool {
+        if content == self.content && size == self.size {
+            return false;
+        }
+        self.content = content;
+        self.size = size;
+        let bounds = self.bounds();
+        self.kinetics.set_bounds(bounds);
+        true
+    }
+
+    fn handle(&mut self, event: &Event) -> bool {
+        let offset = self.offset();
+        match event {
+            Event::Mouse(mouse::Event::Scroll(delta)) => {
+                // A scroll moves the content directly, resisting past the bounds as a drag does,
+                // and springs back from any overscroll once released.
+                self.kinetics.drag(*delta * self.sensitivity);
+                self.kinetics.release();
+            }
+            Event::Mouse(mouse::Event::Down(Button::Left)) => {
+                self.dragging = true;
+                self.kinetics.begin();
+            }
+            Event::Mouse(mouse::Event::Move(position)) => {
+                if self.dragging {
+                    self.kinetics.drag(self.cursor - *position);
+                }
+                self.cursor = *position;
+            }
+            Event::Mouse(mouse::Event::Up(Button::Left)) if self.dragging => {
+                self.dragging = false;
+                self.kinetics.release();
+            }
+            _ => {}
+        }
+        self.offset() != offset
+    }
+
+    fn tick(&mut self, elapsed: f64) -> bool {
+        if !self.kinetics.is_active() {
+            return false;
+        }
+        let offset = self.offset();
+        self.kinetics.tick(elapsed);
+        self.offset() != offset
+    }
+}
+
+struct State {
+    frame: Box<dyn Frame>,
+    scroll: Scroll,
+}
+
+impl State {
+    /// Follows any resize of the [Frame], returning whether it had been resized.
+    fn resize(&mut self) -> bool {
+        let (content, size) = (self.scroll.content, self.frame.get_size());
+        self.scroll.resize(content, size)
+    }
+
+    fn present(&self) {
+        let viewport = Rect::new(self.scroll.offset(), self.scroll.size);
+        self.frame.set_viewport(viewport);
+    }
+}
+
+/// Scrolls the viewport of a [Frame] across content larger than the [Frame] itself.
+///
+/// Scrolling the mouse wheel moves the content directly, and dragging it with the left mouse
+/// button carries it on with momentum once released. Both are simulated by [Kinetics], so content
+/// dragged past its bounds resists and springs back, and momentum advances as
+/// [ScrollFrame::tick] is called, such as through [ScrollFrame::bind_ticks]. The viewport keeps a
+/// zoom of one and is resized along with the [Frame] as it ticks or handles input.
+#[derive(Clone)]
+pub struct ScrollFrame {
+    state: Arc<RwLock<State>>,
+}
+
+impl ScrollFrame {
+    /// Creates a new [ScrollFrame] scrolling the provided [Frame] across content of the provided
+    /// size, starting with the origin of the content at the top left of the [Frame].
+    pub fn new<T>(frame: Box<dyn Frame>, content: T) -> ScrollFrame
+    where
+        T: Into<Vector2>,
+    {
+        let scroll = Scroll::new(content.into(), frame.get_size(), Tuning::default());
+        let state = State { frame, scroll };
+        state.present();
+        ScrollFrame {
+            state: Arc::new(RwLock::new(state)),
+        }
+    }
+    /// Replaces the platform-default tuning of the momentum and overscroll of the content.
+    pub fn with_tuning(self, tuning: Tuning) -> Self {
+        {
+            let mut state = self.state.write().unwrap();
+            let kinetics = state.scroll.kinetics.clone();
+            state.scroll.kinetics = kinetics.with_tuning(tuning);
+        }
+        self
+    }
+    /// Sets the multiplier applied to scroll distances.
+    pub fn with_sensitivity(self, sensitivity: f64) -> Self {
+        self.state.write().unwrap().scroll.sensitivity = sensitivity;
+        self
+    }
+    /// Adds offsets at which released content will come to rest, such as the positions of pages
+    /// or list items.
+    pub fn with_snap_points<T>(self, snap_points: T) -> Self
+    where
+        T: IntoIterator<Item = Vector2>,
+    {
+        {
+            let mut state = self.state.write().unwrap();
+            let kinetics = state.scroll.kinetics.clone();
+            state.scroll.kinetics = kinetics.with_snap_points(snap_points);
+        }
+        self
+    }
+    /// Returns the size of the scrolled content.
+    pub fn content_size(&self) -> Vector2 {
+        self.state.read().unwrap().scroll.content
+    }
+    /// Sets the size of the scrolled content. Content that is scrolled past its new bounds springs
+    /// back into them as the [ScrollFrame] ticks.
+    pub fn set_content_size<T>(&self, content: T)
+    where
+        T: Into<Vector2>,
+    {
+        let mut state = self.state.write().unwrap();
+        let size = state.frame.get_size();
+        if state.scroll.resize(content.into(), size) {
+            state.present();
+        }
+    }
+    /// Returns the current offset of the viewport into the content, which may lie past the bounds
+    /// of the content while it is overscrolled.
+    pub fn offset(&self) -> Vector2 {
+        self.state.read().unwrap().scroll.offset()
+    }
+    /// Updates the offset in response to an input event, returning whether the viewport changed.
+    pub fn handle(&self, event: &Event) -> bool {
+        let mut state = self.state.write().unwrap();
+        let resized = state.resize();
+        let changed = state.scroll.handle(event) || resized;
+        if changed {
+            state.present();
+        }
+        changed
+    }
+    /// Advances the momentum of released content by the provided elapsed time in milliseconds and
+    /// resizes the viewport if the [Frame] has been resized, returning whether the viewport
+    /// changed.
+    pub fn tick(&self, elapsed: f64) -> bool {
+        let mut state = self.state.write().unwrap();
+        let resized = state.resize();
+        let changed = state.scroll.tick(elapsed) || resized;
+        if changed {
+            state.present();
+        }
+        changed
+    }
+    /// Binds [ScrollFrame::tick] as a tick handler of the provided context.
+    pub fn bind_ticks(&self, ticker: &mut dyn Ticker) {
+        let scroll_frame = self.clone();
+        ticker.bind(Box::new(move |elapsed| {
+            scroll_frame.tick(elapsed);
+        }));
+    }
+    /// Spawns a task on the current executor that passes the input of the provided context to
+    /// [ScrollFrame::handle] for as long as the input stream lasts.
+    pub fn bind_input(&self, context: &dyn Provider) {
+        let scroll_frame = self.clone();
+        executor::spawn(context.input().for_each(move |event| {
+            scroll_frame.handle(&event);
+            Ok(())
+        }));
+    }
+}
+
+impl Debug for ScrollFrame {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ScrollFrame")
+            .field("content_size", &self.content_size())
+            .field("offset", &self.offset())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scroll() -> Scroll {
+        Scroll::new((100., 1000.).into(), (100., 200.).into(), Tuning::firm())
+    }
+
+    fn settle(scroll: &mut Scroll) {
+        for _ in 0..10_000 {
+            if !scroll.tick(16.) && !scroll.kinetics.is_active() {
+                return;
+            }
+        }
+        panic!("scroll never came to rest");
+    }
+
+    fn mouse(event: mouse::Event) -> Event {
+        Event::Mouse(event)
+    }
+
+    #[test]
+    fn content_scrolls_only_where_it_overflows() {
+        let scroll = scroll();
+        let bounds = scroll.bounds();
+        assert_eq!(bounds.position, Vector2::default());
+        assert_eq!(bounds.size, (0., 800.).into());
+    }
+
+    #[test]
+    fn wheel_scrolls_move_the_content_directly() {
+        let mut scroll = scroll();
+        assert!(scroll.handle(&mouse(mouse::Event::Scroll((0., 30.).into()))));
+        assert_eq!(scroll.offset(), (0., 30.).into());
+        assert!(!scroll.kinetics.is_active());
+        scroll.sensitivity = 2.;
+        scroll.handle(&mouse(mouse::Event::Scroll((0., 10.).into())));
+        assert_eq!(scroll.offset(), (0., 50.).into());
+    }
+
+    #[test]
+    fn overscrolled_content_springs_back() {
+        let mut scroll = scroll();
+        assert!(scroll.handle(&mouse(mouse::Event::Scroll((0., -100.).into()))));
+        let offset = scroll.offset().y;
+        assert!(offset < 0. && offset > -100.);
+        assert!(scroll.kinetics.is_active());
+        settle(&mut scroll);
+        assert_eq!(scroll.offset(), Vector2::default());
+    }
+
+    #[test]
+    fn dragged_content_follows_the_pointer_and_carries_on() {
+        let mut scroll = scroll();
+        scroll.handle(&mouse(mouse::Event::Move((50., 150.).into())));
+        assert!(!scroll.handle(&mouse(mouse::Event::Down(Button::Left))));
+        for _ in 0..5 {
+            let position = scroll.cursor - Vector2::from((0., 10.));
+            assert!(scroll.handle(&mouse(mouse::Event::Move(position))));
+            assert!(!scroll.tick(16.));
+        }
+        assert_eq!(scroll.offset(), (0., 50.).into());
+        scroll.handle(&mouse(mouse::Event::Up(Button::Left)));
+        assert!(scroll.kinetics.velocity().y > 0.);
+        assert!(scroll.tick(16.));
+        assert!(scroll.offset().y > 50.);
+        settle(&mut scroll);
+        assert!(scroll.offset().y > 50. && scroll.offset().y <= 800.);
+        assert!(!scroll.handle(&mouse(mouse::Event::Move((50., 0.).into()))));
+    }
+
+    #[test]
+    fn shrinking_content_springs_back_into_bounds() {
+        let mut scroll = scroll();
+        scroll.handle(&mouse(mouse::Event::Scroll((0., 800.).into())));
+        assert_eq!(scroll.offset(), (0., 800.).into());
+        assert!(!scroll.resize((100., 1000.).into(), (100., 200.).into()));
+        assert!(scroll.resize((100., 500.).into(), (100., 200.).into()));
+        settle(&mut scroll);
+        assert_eq!(scroll.offset(), (0., 300.).into());
+    }
+}
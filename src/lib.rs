@@ -28,12 +28,38 @@ extern crate gl;
 #[cfg(any(target_arch = "linux", target_arch = "macos", target_arch = "linux"))]
 extern crate glutin;
 
+/// Provides access to platform accessibility preferences.
+pub mod accessibility;
+/// Provides analysis of audio streams for audio-reactive content.
+pub mod audio;
+/// Provides canonical rendering workloads for tracking performance across backends.
+#[cfg(feature = "benches")]
+pub mod benches;
+/// Provides overlays for debugging input, rendering performance and recent states of frames.
+pub mod debug;
+/// Provides data models for multi-frame documents.
+pub mod document;
+/// Provides helpers for transient visual effects.
+pub mod effects;
 mod errors;
 /// Provides a cross-platform abstracted asynchronous executor.
 pub mod executor;
 /// Provides interfaces to 2D graphics APIs.
 pub mod graphics;
+/// Provides interfaces to haptic feedback APIs.
+pub mod haptics;
 /// Provides interfaces to user input/HID APIs.
 pub mod input;
+/// Provides platform-independent helpers for implementing interactive content.
+pub mod interaction;
+/// Provides HTTP and WebSocket clients that behave alike on every target.
+pub mod net;
+/// Provides runtime scripting of frames for end users of vessels-based tools.
+#[cfg(feature = "rhai")]
+pub mod scripting;
+/// Provides persistent storage that behaves alike on every target.
+pub mod storage;
+/// Provides synchronization of scenes shared between clients.
+pub mod sync;
 mod targets;
 mod util;
@@ -0,0 +1,171 @@
+use crate::errors::Error;
+
+use futures::{sync::mpsc::UnboundedReceiver, Future, Poll, Stream};
+
+use std::fmt::{self, Debug, Formatter};
+
+/// An HTTP request method.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Method {
+    /// The GET method.
+    Get,
+    /// The HEAD method.
+    Head,
+    /// The POST method.
+    Post,
+    /// The PUT method.
+    Put,
+    /// The PATCH method.
+    Patch,
+    /// The DELETE method.
+    Delete,
+}
+
+impl Method {
+    pub(crate) fn as_str(self) -> &'static str {
+        match self {
+            Method::Get => "GET",
+            Method::Head => "HEAD",
+            Method::Post => "POST",
+            Method::Put => "PUT",
+            Method::Patch => "PATCH",
+            Method::Delete => "DELETE",
+        }
+    }
+}
+
+impl Default for Method {
+    fn default() -> Self {
+        Method::Get
+    }
+}
+
+/// An HTTP request.
+#[derive(Clone, Debug, Default)]
+pub struct Request {
+    /// The request method.
+    pub method: Method,
+    /// The URL requested.
+    pub url: String,
+    /// The request headers as name and value pairs.
+    pub headers: Vec<(String, String)>,
+    /// The request body.
+    pub body: Vec<u8>,
+}
+
+impl Request {
+    /// Creates a GET request for the provided URL with no headers or body.
+    pub fn new(url: &str) -> Request {
+        Request {
+            url: url.to_owned(),
+            ..Request::default()
+        }
+    }
+    /// Sets the request method.
+    pub fn with_method(mut self, method: Method) -> Self {
+        self.method = method;
+        self
+    }
+    /// Adds a request header.
+    pub fn with_header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_owned(), value.to_owned()));
+        self
+    }
+    /// Sets the request body.
+    pub fn with_body<T: Into<Vec<u8>>>(mut self, body: T) -> Self {
+        self.body = body.into();
+        self
+    }
+}
+
+/// The response to an HTTP request.
+#[derive(Clone, Debug)]
+pub struct Response {
+    /// The status code.
+    pub status: u16,
+    /// The response headers as name and value pairs.
+    pub headers: Vec<(String, String)>,
+    /// The response body.
+    pub body: Vec<u8>,
+}
+
+impl Response {
+    /// Returns the body decoded as UTF-8, with invalid sequences replaced.
+    pub fn text(&self) -> String {
+        String::from_utf8_lossy(&self.body).into_owned()
+    }
+}
+
+/// A WebSocket message.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Message {
+    /// A text message.
+    Text(String),
+    /// A binary message.
+    Binary(Vec<u8>),
+}
+
+pub(crate) trait Socket: Send + Sync {
+    fn send(&self, message: Message) -> Result<(), Error>;
+    fn close(&self);
+}
+
+/// An open WebSocket connection, which is a [Stream] of the messages it receives. The stream ends
+/// when the connection closes.
+pub struct WebSocket {
+    socket: Box<dyn Socket>,
+    incoming: UnboundedReceiver<Message>,
+}
+
+impl WebSocket {
+    pub(crate) fn new(socket: Box<dyn Socket>, incoming: UnboundedReceiver<Message>) -> WebSocket {
+        WebSocket { socket, incoming }
+    }
+    /// Sends a message, failing if the connection has closed.
+    pub fn send(&self, message: Message) -> Result<(), Error> {
+        self.socket.send(message)
+    }
+    /// Closes the connection.
+    pub fn close(&self) {
+        self.socket.close();
+    }
+}
+
+impl Stream for WebSocket {
+    type Item = Message;
+    type Error = ();
+
+    fn poll(&mut self) -> Poll<Option<Message>, ()> {
+        self.incoming.poll()
+    }
+}
+
+impl Debug for WebSocket {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WebSocket").finish()
+    }
+}
+
+/// A handle to the networking capabilities of the platform, which behave alike on every target.
+pub trait Net: Send + Sync {
+    /// Performs an HTTP request, resolving to its response once the body has been received.
+    /// Responses with error statuses resolve normally; only failures to complete the request are
+    /// errors.
+    fn fetch(&self, request: Request) -> Box<dyn Future<Item = Response, Error = Error> + Send>;
+    /// Opens a WebSocket connection to the provided `ws:` or `wss:` URL.
+    fn connect(&self, url: &str) -> Box<dyn Future<Item = WebSocket, Error = Error> + Send>;
+    #[doc(hidden)]
+    fn box_clone(&self) -> Box<dyn Net>;
+}
+
+impl Clone for Box<dyn Net> {
+    fn clone(&self) -> Box<dyn Net> {
+        self.box_clone()
+    }
+}
+
+/// A context that provides networking functionality.
+pub trait Provider {
+    /// Returns a networking handle for this context.
+    fn net(&self) -> Box<dyn Net>;
+}
@@ -0,0 +1,256 @@
+use crate::{
+    errors::Error,
+    graphics::{
+        canvas::{Content, Frame, Object, Rasterizable},
+        path::{Fill, Primitive},
+        text::Text,
+        LDRColor, Rect, Vector2,
+    },
+    interaction::camera::Camera,
+};
+
+use rhai::{Array, Dynamic, Engine, RegisterFn};
+
+use std::{
+    fmt::{self, Debug, Formatter},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
+
+/// Parses the CSS colors given by a script. Registered functions can't fail, so a color that
+/// doesn't parse is drawn transparent and recorded, and the script fails once it has run.
+#[derive(Clone, Debug, Default)]
+struct Colors {
+    invalid: Arc<AtomicBool>,
+}
+
+impl Colors {
+    fn parse(&self, input: String) -> LDRColor {
+        LDRColor::from_css(&input).unwrap_or_else(|_| {
+            self.invalid.store(true, Ordering::Relaxed);
+            LDRColor::rgba(0, 0, 0, 0)
+        })
+    }
+    /// Returns an error if a color that didn't parse was given since this was last called.
+    fn check(&self) -> Result<(), Error> {
+        if self.invalid.swap(false, Ordering::Relaxed) {
+            Err(Error::color_parse())
+        } else {
+            Ok(())
+        }
+    }
+}
+
+fn add(mut frame: Box<dyn Frame>, content: Content, x: f64, y: f64) -> Box<dyn Object> {
+    let mut object = frame.add(content);
+    let transform = object.get_transform().with_position((x, y));
+    object.set_transform(transform);
+    object
+}
+
+fn recolor(mut object: Box<dyn Object>, color: LDRColor) {
+    let content = match object.get_content().content {
+        Rasterizable::Path(mut path) => {
+            path.fill = Some(Fill::from(color));
+            Rasterizable::Path(path)
+        }
+        Rasterizable::Text(text) => Rasterizable::Text(Box::new((*text).with_color(color))),
+        Rasterizable::Extension(_) => return,
+    };
+    object.update(content);
+}
+
+fn array(objects: Vec<Box<dyn Object>>) -> Array {
+    objects
+        .into_iter()
+        .map(|object| Box::new(object) as Dynamic)
+        .collect()
+}
+
+/// Runs small [rhai](https://github.com/jonathandturner/rhai) scripts against a [Frame], so that
+/// end users of vessels-based tools can automate their work at runtime, such as by generating
+/// shapes or restyling objects in batches.
+///
+/// Scripts reach the frame through `frame()` and, if one is provided, its [Camera] through
+/// `camera()`. Numbers are given as floats, such as `10.0`, and colors as CSS colors, such as
+/// `"#ff8800"`. A script that gives a color that doesn't parse fails once it has run, with
+/// whatever it added in that color drawn transparent. The following functions are available:
+///
+/// - `rect(frame, x, y, width, height, color)`, `circle(frame, x, y, radius, color)` and
+///   `text(frame, content, x, y, color)` add content and return the object added
+/// - `children(frame)` returns an array of the objects of the frame and `find(frame, tag)` those
+///   with a tag
+/// - `remove(frame, object)` removes an object and `clear(frame)` every object
+/// - `move_to(object, x, y)`, `move_by(object, x, y)`, `scale(object, factor)` and
+///   `rotate(object, radians)` transform an object
+/// - `recolor(object, color)` changes the fill of a path or the color of text
+/// - `set_tag(object, tag)`, `set_visible(object, visible)` and `set_depth(object, depth)`
+/// - `zoom(camera)` returns the zoom of the camera, `pan(camera, x, y)` moves its viewport and
+///   `look_at(camera, x, y, zoom)` centers it on a point at a zoom
+///
+/// ```ignore
+/// let x = 0.0;
+/// while x < 200.0 {
+///     let square = rect(frame(), x, 0.0, 10.0, 10.0, "rebeccapurple");
+///     set_tag(square, "generated");
+///     x = x + 20.0;
+/// }
+/// for square in find(frame(), "generated") {
+///     recolor(square, "orange");
+/// }
+/// ```
+pub struct Script {
+    engine: Engine,
+    colors: Colors,
+}
+
+impl Script {
+    /// Creates a new [Script] runner manipulating the provided [Frame].
+    pub fn new(frame: Box<dyn Frame>) -> Script {
+        let mut engine = Engine::new();
+        let colors = Colors::default();
+        engine.register_type::<Box<dyn Frame>>();
+        engine.register_type::<Box<dyn Object>>();
+        engine.register_type::<Camera>();
+
+        engine.register_fn("frame", move || frame.clone());
+
+        let parser = colors.clone();
+        engine.register_fn(
+            "rect",
+            move |frame: Box<dyn Frame>, x: f64, y: f64, width: f64, height: f64, fill: String| {
+                let path = Primitive::rectangle((width, height))
+                    .fill(parser.parse(fill).into())
+                    .finalize();
+                add(frame, path.into(), x, y)
+            },
+        );
+        let parser = colors.clone();
+        engine.register_fn(
+            "circle",
+            move |frame: Box<dyn Frame>, x: f64, y: f64, radius: f64, fill: String| {
+                let path = Primitive::circle(radius)
+                    .fill(parser.parse(fill).into())
+                    .finalize();
+                add(frame, path.into(), x - radius, y - radius)
+            },
+        );
+        let parser = colors.clone();
+        engine.register_fn(
+            "text",
+            move |frame: Box<dyn Frame>, content: String, x: f64, y: f64, fill: String| {
+                let text = Text::new(&content).with_color(parser.parse(fill));
+                add(frame, text.into(), x, y)
+            },
+        );
+
+        engine.register_fn("children", |frame: Box<dyn Frame>| array(frame.children()));
+        engine.register_fn("find", |frame: Box<dyn Frame>, tag: String| {
+            array(frame.find(&tag))
+        });
+        engine.register_fn(
+            "remove",
+            |mut frame: Box<dyn Frame>, object: Box<dyn Object>| {
+                frame.remove(&*object);
+            },
+        );
+        engine.register_fn("clear", |mut frame: Box<dyn Frame>| frame.clear());
+
+        engine.register_fn("move_to", |mut object: Box<dyn Object>, x: f64, y: f64| {
+            let transform = object.get_transform().with_position((x, y));
+            object.set_transform(transform);
+        });
+        engine.register_fn("move_by", |mut object: Box<dyn Object>, x: f64, y: f64| {
+            let mut transform = object.get_transform();
+            transform.translate((x, y));
+            object.set_transform(transform);
+        });
+        engine.register_fn("scale", |mut object: Box<dyn Object>, factor: f64| {
+            let mut transform = object.get_transform();
+            transform.scale(Vector2::from(factor));
+            object.set_transform(transform);
+        });
+        engine.register_fn("rotate", |mut object: Box<dyn Object>, radians: f64| {
+            let mut transform = object.get_transform();
+            transform.rotate(radians);
+            object.set_transform(transform);
+        });
+        let parser = colors.clone();
+        engine.register_fn("recolor", move |object: Box<dyn Object>, fill: String| {
+            recolor(object, parser.parse(fill));
+        });
+        engine.register_fn("set_tag", |mut object: Box<dyn Object>, tag: String| {
+            object.set_tag(Some(tag));
+        });
+        engine.register_fn(
+            "set_visible",
+            |mut object: Box<dyn Object>, visible: bool| {
+                object.set_visible(visible);
+            },
+        );
+        engine.register_fn("set_depth", |mut object: Box<dyn Object>, depth: i64| {
+            object.set_depth(depth.max(0) as u32);
+        });
+
+        engine.register_fn("zoom", |camera: Camera| camera.zoom());
+        engine.register_fn("pan", |camera: Camera, x: f64, y: f64| {
+            let mut viewport = camera.viewport();
+            viewport.position += Vector2::from((x, y));
+            camera.set_viewport(viewport);
+        });
+        engine.register_fn("look_at", |camera: Camera, x: f64, y: f64, zoom: f64| {
+            let viewport = camera.viewport();
+            let size = viewport.size * camera.zoom() / zoom;
+            camera.set_viewport(Rect::new(Vector2::from((x, y)) - size / 2., size));
+        });
+
+        Script { engine, colors }
+    }
+    /// Makes the provided [Camera] available to scripts through `camera()`.
+    pub fn with_camera(mut self, camera: Camera) -> Self {
+        self.engine.register_fn("camera", move || camera.clone());
+        self
+    }
+    /// Runs a script, returning an error if it doesn't parse, fails as it runs, or gives a color
+    /// that doesn't parse.
+    pub fn run(&mut self, script: &str) -> Result<(), Error> {
+        // Colors left over from a run that failed part way don't fail this one.
+        self.colors.check().ok();
+        self.engine.consume(script).map_err(|_| Error::script())?;
+        self.colors.check()
+    }
+}
+
+impl Debug for Script {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Script").finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn invalid_colors_fail_the_script() {
+        let colors = Colors::default();
+        assert_eq!(
+            colors.parse("orange".to_owned()),
+            LDRColor::rgb(255, 165, 0)
+        );
+        assert!(colors.check().is_ok());
+        assert_eq!(colors.parse("not a color".to_owned()).a, 0);
+        assert!(colors.check().is_err());
+        // The failure is reported once.
+        assert!(colors.check().is_ok());
+    }
+
+    #[test]
+    fn clones_share_failures() {
+        let colors = Colors::default();
+        colors.clone().parse("#ggg".to_owned());
+        assert!(colors.check().is_err());
+    }
+}
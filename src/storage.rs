@@ -0,0 +1,31 @@
+use crate::errors::Error;
+
+/// A handle to persistent storage of small binary blobs, such as settings or documents, kept
+/// between runs of the application.
+///
+/// Blobs are grouped into namespaces so that unrelated parts of an application do not collide.
+/// Natively they are kept in the platform's configuration directory and on the web in local
+/// storage, so they are best kept small.
+pub trait Storage: Send + Sync {
+    /// Returns the blob stored under the provided key in the provided namespace, if any.
+    fn get(&self, namespace: &str, key: &str) -> Option<Vec<u8>>;
+    /// Stores a blob under the provided key in the provided namespace, replacing any previous one.
+    fn set(&self, namespace: &str, key: &str, value: &[u8]) -> Result<(), Error>;
+    /// Removes the blob stored under the provided key in the provided namespace, doing nothing if
+    /// there is none.
+    fn delete(&self, namespace: &str, key: &str) -> Result<(), Error>;
+    #[doc(hidden)]
+    fn box_clone(&self) -> Box<dyn Storage>;
+}
+
+impl Clone for Box<dyn Storage> {
+    fn clone(&self) -> Box<dyn Storage> {
+        self.box_clone()
+    }
+}
+
+/// A context that provides persistent storage functionality.
+pub trait Provider {
+    /// Returns a persistent storage handle for this context.
+    fn storage(&self) -> Box<dyn Storage>;
+}
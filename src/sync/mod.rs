@@ -0,0 +1,461 @@
+use crate::graphics::{
+    canvas::{Content, Frame, Object},
+    Transform2,
+};
+
+use serde::{Deserialize, Serialize};
+
+use std::{
+    collections::HashMap,
+    fmt::{self, Debug, Formatter},
+};
+
+/// Provides rendering of the cursors and selections of remote clients.
+pub mod presence;
+
+/// Identifies an object of a shared scene on every client, by the client that added it and the
+/// order in which that client added it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct ObjectId {
+    /// The client that added the object.
+    pub client: u32,
+    /// The number of objects the client had added before this one.
+    pub index: u64,
+}
+
+/// A Lamport timestamp that orders operations identically on every client. Operations with equal
+/// clocks are ordered by client.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct Stamp {
+    /// The logical clock of the client when the operation was made.
+    pub clock: u64,
+    /// The client that made the operation.
+    pub client: u32,
+}
+
+/// A mutation of a single object of a shared scene.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum Change<T> {
+    /// Sets the content of the object, adding the object if it isn't yet in the scene.
+    Content(T),
+    /// Sets the transform of the object.
+    Transform(Transform2),
+    /// Sets the depth of the object.
+    Depth(u32),
+    /// Removes the object. Changes to removed objects are ignored.
+    Remove,
+}
+
+/// A [Change] to an object, stamped with when it was made.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Operation<T> {
+    /// When the change was made.
+    pub stamp: Stamp,
+    /// The object that was changed.
+    pub object: ObjectId,
+    /// The change.
+    pub change: Change<T>,
+}
+
+/// Carries operations between the clients of a shared scene, in whichever serialization format
+/// and over whichever connection suits the application.
+///
+/// Operations may be delivered late, out of order or more than once, but each should eventually
+/// reach every other client.
+pub trait Transport<T>: Send {
+    /// Sends an operation made locally to every other client.
+    fn send(&mut self, operation: &Operation<T>);
+    /// Returns the operations received from other clients since the last call.
+    fn receive(&mut self) -> Vec<Operation<T>>;
+}
+
+/// Receives the changes that take effect in a shared scene.
+pub trait Scene<T> {
+    /// Adds an object with the provided content.
+    fn insert(&mut self, object: ObjectId, content: &T);
+    /// Replaces the content of an object.
+    fn update(&mut self, object: ObjectId, content: &T);
+    /// Sets the transform of an object.
+    fn set_transform(&mut self, object: ObjectId, transform: Transform2);
+    /// Sets the depth of an object.
+    fn set_depth(&mut self, object: ObjectId, depth: u32);
+    /// Removes an object.
+    fn remove(&mut self, object: ObjectId);
+}
+
+#[derive(Default)]
+struct Entry {
+    content: Option<Stamp>,
+    transform: Option<(Stamp, Transform2)>,
+    depth: Option<(Stamp, u32)>,
+    removed: Option<Stamp>,
+}
+
+fn newer(stamp: Stamp, current: Option<Stamp>) -> bool {
+    Some(stamp) > current
+}
+
+/// Removes the operation with the provided stamp from a log ordered by [Stamp], if it's there.
+fn forget<T>(log: &mut Vec<Operation<T>>, stamp: Option<Stamp>) {
+    if let Some(stamp) = stamp {
+        if let Ok(index) = log.binary_search_by(|logged| logged.stamp.cmp(&stamp)) {
+            log.remove(index);
+        }
+    }
+}
+
+/// One client's copy of a shared scene.
+///
+/// Local changes are applied immediately and sent to the other clients, and changes received from
+/// them are merged with last-writer-wins semantics: for each property of each object, the change
+/// with the greatest [Stamp] takes effect, so every client converges on the same scene regardless
+/// of the order in which operations arrive.
+pub struct Replica<T> {
+    client: u32,
+    clock: u64,
+    added: u64,
+    log: Vec<Operation<T>>,
+    entries: HashMap<ObjectId, Entry>,
+    transport: Box<dyn Transport<T>>,
+    scene: Box<dyn Scene<T> + Send>,
+}
+
+impl<T> Replica<T> {
+    /// Creates a replica for the provided client, which must be unique among the clients sharing
+    /// the scene.
+    pub fn new(
+        client: u32,
+        transport: Box<dyn Transport<T>>,
+        scene: Box<dyn Scene<T> + Send>,
+    ) -> Replica<T> {
+        Replica {
+            client,
+            clock: 0,
+            added: 0,
+            log: vec![],
+            entries: HashMap::new(),
+            transport,
+            scene,
+        }
+    }
+    /// Adds an object with the provided content, returning its identifier.
+    pub fn add(&mut self, content: T) -> ObjectId {
+        let object = ObjectId {
+            client: self.client,
+            index: self.added,
+        };
+        self.added += 1;
+        self.change(object, Change::Content(content));
+        object
+    }
+    /// Makes a change to an object.
+    pub fn change(&mut self, object: ObjectId, change: Change<T>) {
+        self.clock += 1;
+        let operation = Operation {
+            stamp: Stamp {
+                clock: self.clock,
+                client: self.client,
+            },
+            object,
+            change,
+        };
+        self.transport.send(&operation);
+        self.integrate(operation);
+    }
+    /// Applies the operations received from other clients, returning how many took effect.
+    /// Operations already received, and those superseded by ones that were, have no effect.
+    pub fn poll(&mut self) -> usize {
+        self.transport
+            .receive()
+            .into_iter()
+            .map(|operation| self.integrate(operation))
+            .filter(|new| *new)
+            .count()
+    }
+    /// Returns the operations made or received so far that determine the scene, ordered by
+    /// [Stamp]. Operations are dropped from the log once superseded, such as by a later change to
+    /// the same property or by the removal of their object, so it grows with the scene rather than
+    /// with its history. Replaying the log into a new replica reproduces the scene.
+    pub fn log(&self) -> &[Operation<T>] {
+        &self.log
+    }
+    /// Applies an operation if it takes effect, returning whether it did. Operations already
+    /// applied never do, as they're no newer than themselves.
+    fn integrate(&mut self, operation: Operation<T>) -> bool {
+        self.clock = self.clock.max(operation.stamp.clock);
+        let (stamp, object) = (operation.stamp, operation.object);
+        let entry = self.entries.entry(object).or_default();
+        if entry.removed.is_some() {
+            return false;
+        }
+        let scene = &mut self.scene;
+        let log = &mut self.log;
+        match &operation.change {
+            Change::Content(content) if newer(stamp, entry.content) => {
+                let previous = entry.content.replace(stamp);
+                if previous.is_some() {
+                    scene.update(object, content);
+                } else {
+                    scene.insert(object, content);
+                    if let Some((_, transform)) = entry.transform {
+                        scene.set_transform(object, transform);
+                    }
+                    if let Some((_, depth)) = entry.depth {
+                        scene.set_depth(object, depth);
+                    }
+                }
+                forget(log, previous);
+            }
+            Change::Transform(transform)
+                if newer(stamp, entry.transform.map(|(stamp, _)| stamp)) =>
+            {
+                let previous = entry.transform.replace((stamp, *transform));
+                if entry.content.is_some() {
+                    scene.set_transform(object, *transform);
+                }
+                forget(log, previous.map(|(stamp, _)| stamp));
+            }
+            Change::Depth(depth) if newer(stamp, entry.depth.map(|(stamp, _)| stamp)) => {
+                let previous = entry.depth.replace((stamp, *depth));
+                if entry.content.is_some() {
+                    scene.set_depth(object, *depth);
+                }
+                forget(log, previous.map(|(stamp, _)| stamp));
+            }
+            Change::Remove => {
+                entry.removed = Some(stamp);
+                if entry.content.is_some() {
+                    scene.remove(object);
+                }
+                // Only the removal is needed to replay the object, which ignores any other change.
+                forget(log, entry.content.take());
+                forget(log, entry.transform.take().map(|(stamp, _)| stamp));
+                forget(log, entry.depth.take().map(|(stamp, _)| stamp));
+            }
+            _ => return false,
+        }
+        let index = match log.binary_search_by(|logged| logged.stamp.cmp(&stamp)) {
+            Ok(index) | Err(index) => index,
+        };
+        log.insert(index, operation);
+        true
+    }
+}
+
+impl<T> Debug for Replica<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Replica")
+            .field("client", &self.client)
+            .field("clock", &self.clock)
+            .field("operations", &self.log.len())
+            .field("objects", &self.entries.len())
+            .finish()
+    }
+}
+
+/// A [Scene] drawn into a [Frame], with content rendered from the application's own serializable
+/// description of it.
+pub struct FrameScene<T> {
+    frame: Box<dyn Frame>,
+    objects: HashMap<ObjectId, Box<dyn Object>>,
+    render: Box<dyn Fn(&T) -> Content + Send + Sync>,
+}
+
+impl<T> FrameScene<T> {
+    /// Creates a scene that adds objects to the provided [Frame], rendering their content with the
+    /// provided function.
+    pub fn new<F>(frame: Box<dyn Frame>, render: F) -> FrameScene<T>
+    where
+        F: Fn(&T) -> Content + Send + Sync + 'static,
+    {
+        FrameScene {
+            frame,
+            objects: HashMap::new(),
+            render: Box::new(render),
+        }
+    }
+}
+
+impl<T> Scene<T> for FrameScene<T> {
+    fn insert(&mut self, object: ObjectId, content: &T) {
+        let added = self.frame.add((self.render)(content));
+        self.objects.insert(object, added);
+    }
+    fn update(&mut self, object: ObjectId, content: &T) {
+        if let Some(added) = self.objects.get_mut(&object) {
+            added.update((self.render)(content).content);
+        }
+    }
+    fn set_transform(&mut self, object: ObjectId, transform: Transform2) {
+        if let Some(added) = self.objects.get_mut(&object) {
+            added.set_transform(transform);
+        }
+    }
+    fn set_depth(&mut self, object: ObjectId, depth: u32) {
+        if let Some(added) = self.objects.get_mut(&object) {
+            added.set_depth(depth);
+        }
+    }
+    fn remove(&mut self, object: ObjectId) {
+        if let Some(added) = self.objects.remove(&object) {
+            self.frame.remove(added.as_ref());
+        }
+    }
+}
+
+impl<T> Debug for FrameScene<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FrameScene")
+            .field("objects", &self.objects.len())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    type Wire = Arc<Mutex<Vec<Operation<String>>>>;
+
+    /// Delivers everything sent by any client to every client, including the sender.
+    struct Broadcast {
+        wire: Wire,
+        read: usize,
+    }
+
+    impl Transport<String> for Broadcast {
+        fn send(&mut self, operation: &Operation<String>) {
+            self.wire.lock().unwrap().push(operation.clone());
+        }
+        fn receive(&mut self) -> Vec<Operation<String>> {
+            let wire = self.wire.lock().unwrap();
+            let received = wire[self.read..].to_vec();
+            self.read = wire.len();
+            received
+        }
+    }
+
+    /// The content and horizontal position of each object.
+    type Objects = HashMap<ObjectId, (String, Option<f64>)>;
+
+    #[derive(Clone, Default)]
+    struct Recorder(Arc<Mutex<Objects>>);
+
+    impl Scene<String> for Recorder {
+        fn insert(&mut self, object: ObjectId, content: &String) {
+            self.0
+                .lock()
+                .unwrap()
+                .insert(object, (content.clone(), None));
+        }
+        fn update(&mut self, object: ObjectId, content: &String) {
+            self.0.lock().unwrap().get_mut(&object).unwrap().0 = content.clone();
+        }
+        fn set_transform(&mut self, object: ObjectId, transform: Transform2) {
+            self.0.lock().unwrap().get_mut(&object).unwrap().1 = Some(transform.position.x);
+        }
+        fn set_depth(&mut self, _: ObjectId, _: u32) {}
+        fn remove(&mut self, object: ObjectId) {
+            self.0.lock().unwrap().remove(&object);
+        }
+    }
+
+    fn replica(client: u32, wire: &Wire) -> (Replica<String>, Recorder) {
+        let scene = Recorder::default();
+        let transport = Broadcast {
+            wire: wire.clone(),
+            read: 0,
+        };
+        (
+            Replica::new(client, Box::new(transport), Box::new(scene.clone())),
+            scene,
+        )
+    }
+
+    fn moved(x: f64) -> Change<String> {
+        Change::Transform(Transform2::default().with_position((x, 0.)))
+    }
+
+    #[test]
+    fn concurrent_changes_converge() {
+        let wire = Wire::default();
+        let (mut first, first_scene) = replica(1, &wire);
+        let (mut second, second_scene) = replica(2, &wire);
+        let shape = first.add("square".to_owned());
+        second.poll();
+        first.change(shape, moved(1.));
+        second.change(shape, moved(2.));
+        second.change(shape, Change::Content("circle".to_owned()));
+        let removed = second.add("line".to_owned());
+        second.change(removed, Change::Remove);
+        first.change(removed, moved(3.));
+        assert_eq!(first.poll(), 4);
+        second.poll();
+        let expected = Some(&("circle".to_owned(), Some(2.)));
+        assert_eq!(first_scene.0.lock().unwrap().get(&shape), expected);
+        assert_eq!(second_scene.0.lock().unwrap().get(&shape), expected);
+        assert_eq!(first_scene.0.lock().unwrap().len(), 1);
+        assert_eq!(second_scene.0.lock().unwrap().len(), 1);
+        let stamps = |replica: &Replica<String>| {
+            replica
+                .log()
+                .iter()
+                .map(|operation| operation.stamp)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(stamps(&first), stamps(&second));
+    }
+
+    #[test]
+    fn superseded_operations_are_compacted() {
+        let wire = Wire::default();
+        let (mut first, _) = replica(1, &wire);
+        let (mut second, second_scene) = replica(2, &wire);
+        let shape = first.add("square".to_owned());
+        for x in 0..100 {
+            first.change(shape, moved(f64::from(x)));
+        }
+        let removed = first.add("line".to_owned());
+        first.change(removed, moved(1.));
+        first.change(removed, Change::Remove);
+        assert_eq!(first.log().len(), 3);
+        assert_eq!(second.poll(), 104);
+        assert_eq!(second.log().len(), 3);
+        assert_eq!(
+            second_scene.0.lock().unwrap().get(&shape),
+            Some(&("square".to_owned(), Some(99.)))
+        );
+        // Operations that arrive again, even once dropped from the log, have no effect.
+        let (mut third, third_scene) = replica(3, &wire);
+        assert_eq!(third.poll(), 104);
+        wire.lock().unwrap().extend(first.log().to_vec());
+        assert_eq!(third.poll(), 0);
+        assert_eq!(third_scene.0.lock().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn late_content_receives_earlier_properties() {
+        let wire = Wire::default();
+        let (mut replica, scene) = replica(1, &wire);
+        let object = ObjectId {
+            client: 2,
+            index: 0,
+        };
+        let operation = |clock, change| Operation {
+            stamp: Stamp { clock, client: 2 },
+            object,
+            change,
+        };
+        wire.lock().unwrap().push(operation(2, moved(5.)));
+        wire.lock()
+            .unwrap()
+            .push(operation(1, Change::Content("dot".to_owned())));
+        wire.lock().unwrap().push(operation(2, moved(5.)));
+        assert_eq!(replica.poll(), 2);
+        assert_eq!(
+            scene.0.lock().unwrap().get(&object),
+            Some(&("dot".to_owned(), Some(5.)))
+        );
+    }
+}
@@ -0,0 +1,254 @@
+use crate::graphics::{
+    canvas::{Content, Frame, Layer, Object, Rasterizable},
+    path::{Builder, Path, Primitive, StrokeBuilder},
+    text::Text,
+    LDRColor, Rect, Transform2, Vector2,
+};
+
+use serde::{Deserialize, Serialize};
+
+use std::{
+    collections::HashMap,
+    fmt::{self, Debug, Formatter},
+};
+
+/// The offset of a name tag from the tip of its cursor.
+const TAG_OFFSET: (f64, f64) = (12., 18.);
+/// The padding between a name tag's text and its edges.
+const TAG_PADDING: (f64, f64) = (6., 3.);
+
+/// The cursor and selection of a client, as shown to the other clients of a shared scene.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PresenceMessage {
+    /// The client, as identified to [Replica](super::Replica).
+    pub client: u32,
+    /// The name shown next to the client's cursor.
+    pub name: String,
+    /// The color of the client's cursor, name tag and selection.
+    pub color: LDRColor,
+    /// The position of the client's cursor, or [None] if it is outside of the scene.
+    pub cursor: Option<Vector2>,
+    /// The bounds of the client's selection, if any.
+    pub selection: Option<Rect>,
+}
+
+struct Remote {
+    message: PresenceMessage,
+    from: Vector2,
+    elapsed: f64,
+    seen: f64,
+    cursor: Box<dyn Object>,
+    tag: Box<dyn Object>,
+    label: Box<dyn Object>,
+    outline: Box<dyn Object>,
+}
+
+impl Remote {
+    fn position(&self, interval: f64) -> Vector2 {
+        let to = self.message.cursor.unwrap_or(self.from);
+        let progress = if interval > 0. {
+            (self.elapsed / interval).min(1.)
+        } else {
+            1.
+        };
+        self.from + (to - self.from) * progress
+    }
+    fn place(&mut self, interval: f64) {
+        let position = self.position(interval);
+        let tag = position + TAG_OFFSET;
+        self.cursor.set_transform(Transform2::from(position));
+        self.tag.set_transform(Transform2::from(tag));
+        self.label
+            .set_transform(Transform2::from(tag + TAG_PADDING));
+    }
+    /// Removes the objects of the remote from the overlay.
+    fn remove(&self, layer: &mut dyn Layer) {
+        for object in &[&self.cursor, &self.tag, &self.label, &self.outline] {
+            layer.remove(object.as_ref());
+        }
+    }
+}
+
+fn empty() -> Rasterizable {
+    Rasterizable::Path(Box::new(Builder::new().done().finalize()))
+}
+
+fn label(message: &PresenceMessage) -> Text {
+    Text::new(&message.name)
+        .with_size(12.)
+        .with_line_height(12.)
+        .with_color(LDRColor::white())
+}
+
+fn cursor(color: LDRColor) -> Path {
+    Builder::new()
+        .move_to((0., 0.))
+        .line_to((0., 17.))
+        .line_to((4.5, 13.))
+        .line_to((11., 12.5))
+        .close()
+        .done()
+        .fill(color.into())
+        .stroke(StrokeBuilder::new(LDRColor::white().into(), 1.).finalize())
+        .finalize()
+}
+
+fn outline(color: LDRColor, size: Vector2) -> Path {
+    Primitive::rectangle(size)
+        .stroke(StrokeBuilder::new(color.into(), 1.5).finalize())
+        .finalize()
+}
+
+/// An overlay showing the cursors and selections of the other clients of a shared scene, each
+/// with a name tag in the client's color.
+///
+/// The local client's presence is throttled to one message per interval, and remote cursors move
+/// smoothly to each new position over the same interval, so every client should use the same one.
+/// Clients that send nothing for longer than the timeout are removed.
+pub struct Presence {
+    frame: Box<dyn Frame>,
+    layer: Box<dyn Layer>,
+    interval: f64,
+    timeout: f64,
+    time: f64,
+    sent: Option<f64>,
+    pending: Option<PresenceMessage>,
+    remotes: HashMap<u32, Remote>,
+}
+
+impl Presence {
+    /// Adds the overlay to the provided [Frame] as a new [Layer], which should be the topmost. The
+    /// interval defaults to a twentieth of a second and the timeout to ten seconds.
+    pub fn new(frame: &mut dyn Frame) -> Presence {
+        Presence {
+            layer: frame.add_layer(),
+            frame: frame.box_clone(),
+            interval: 0.05,
+            timeout: 10.,
+            time: 0.,
+            sent: None,
+            pending: None,
+            remotes: HashMap::new(),
+        }
+    }
+    /// Sets the shortest time, in seconds, between messages about the local client.
+    pub fn with_interval(mut self, interval: f64) -> Self {
+        self.interval = interval.max(0.);
+        self
+    }
+    /// Sets how long, in seconds, a silent client is shown for.
+    pub fn with_timeout(mut self, timeout: f64) -> Self {
+        self.timeout = timeout;
+        self
+    }
+    /// Returns the clients currently shown.
+    pub fn clients(&self) -> Vec<u32> {
+        self.remotes.keys().cloned().collect()
+    }
+    /// Records the presence of the local client, to be returned by [Presence::tick] once the
+    /// interval since the previous message has passed. Only the latest unsent presence is kept.
+    pub fn set_local(&mut self, message: PresenceMessage) {
+        self.pending = Some(message);
+    }
+    /// Shows the presence of a remote client.
+    pub fn receive(&mut self, message: PresenceMessage) {
+        let (time, interval) = (self.time, self.interval);
+        let tag_size = self
+            .frame
+            .measure(Rasterizable::Text(Box::new(label(&message))))
+            + Vector2::from(TAG_PADDING) * 2.;
+        let layer = &mut self.layer;
+        let remote = self.remotes.entry(message.client).or_insert_with(|| {
+            let mut add = |depth| layer.add(Content::from(empty()).with_depth(depth));
+            Remote {
+                message: PresenceMessage {
+                    cursor: None,
+                    ..message.clone()
+                },
+                from: Vector2::default(),
+                elapsed: 0.,
+                seen: time,
+                outline: add(0),
+                tag: add(1),
+                label: add(2),
+                cursor: add(3),
+            }
+        });
+        remote.from = match remote.message.cursor {
+            Some(_) => remote.position(interval),
+            None => message.cursor.unwrap_or_default(),
+        };
+        remote.elapsed = 0.;
+        remote.seen = time;
+        if message.cursor.is_some() {
+            remote
+                .cursor
+                .update(Rasterizable::Path(Box::new(cursor(message.color))));
+            remote.tag.update(Rasterizable::Path(Box::new(
+                Primitive::rounded_rectangle(tag_size, tag_size.y / 2.)
+                    .fill(message.color.into())
+                    .finalize(),
+            )));
+            remote
+                .label
+                .update(Rasterizable::Text(Box::new(label(&message))));
+        } else {
+            remote.cursor.update(empty());
+            remote.tag.update(empty());
+            remote.label.update(empty());
+        }
+        match message.selection {
+            Some(selection) => {
+                remote.outline.update(Rasterizable::Path(Box::new(outline(
+                    message.color,
+                    selection.size,
+                ))));
+                remote
+                    .outline
+                    .set_transform(Transform2::from(selection.position));
+            }
+            None => remote.outline.update(empty()),
+        }
+        remote.message = message;
+        remote.place(interval);
+    }
+    /// Advances the overlay by the provided number of seconds, moving remote cursors and removing
+    /// clients that have timed out. Returns the presence of the local client if it is due to be
+    /// sent.
+    pub fn tick(&mut self, delta: f64) -> Option<PresenceMessage> {
+        self.time += delta;
+        let (time, interval, timeout) = (self.time, self.interval, self.timeout);
+        let layer = &mut self.layer;
+        self.remotes.retain(|_, remote| {
+            if time - remote.seen > timeout {
+                remote.remove(layer.as_mut());
+                return false;
+            }
+            if remote.elapsed < interval {
+                remote.elapsed += delta;
+                remote.place(interval);
+            }
+            true
+        });
+        match self.sent {
+            Some(sent) if time - sent < interval => None,
+            _ => {
+                let message = self.pending.take();
+                if message.is_some() {
+                    self.sent = Some(time);
+                }
+                message
+            }
+        }
+    }
+}
+
+impl Debug for Presence {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Presence")
+            .field("interval", &self.interval)
+            .field("timeout", &self.timeout)
+            .field("clients", &self.remotes.len())
+            .finish()
+    }
+}
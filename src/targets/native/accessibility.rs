@@ -0,0 +1,12 @@
+use crate::accessibility::Preferences;
+
+use std::env;
+
+pub(crate) fn preferences() -> Preferences {
+    // glutin exposes no accessibility settings, so only the GTK theme override is consulted,
+    // which is how high contrast is selected on most Linux desktops.
+    let high_contrast = env::var("GTK_THEME")
+        .map(|theme| theme.starts_with("HighContrast"))
+        .unwrap_or(false);
+    Preferences::default().with_high_contrast(high_contrast)
+}
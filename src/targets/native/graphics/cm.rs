@@ -1,7 +1,7 @@
 use crate::graphics::{
     canvas::Rasterizable,
     path::{Fill, GradientStop, Path, Texture},
-    LDRColor,
+    Image, LDRColor, Texture2,
 };
 use lcms2::{Intent, PixelFormat, Transform};
 use std::collections::{HashMap, VecDeque};
@@ -162,6 +162,32 @@ impl Profile {
         state.color_cache.insert(color, transformed_color);
         transformed_color
     }
+    /// Transforms every pixel of an image, such as the rasterization of an
+    /// [Extension](crate::graphics::canvas::Extension).
+    pub(crate) fn transform_image(
+        &self,
+        mut image: Image<LDRColor, Texture2>,
+    ) -> Image<LDRColor, Texture2> {
+        let state = self.state.read().unwrap();
+        let t = Transform::new(
+            &state.srgb_profile,
+            PixelFormat::RGBA_8,
+            &state.display_profile,
+            PixelFormat::RGBA_8,
+            Intent::Perceptual,
+        )
+        .unwrap();
+        let mut pixels: Vec<[u8; 4]> = image
+            .pixels
+            .iter()
+            .map(|color| [color.r, color.g, color.b, color.a])
+            .collect();
+        t.transform_in_place(&mut pixels[..]);
+        for (color, pixel) in image.pixels.iter_mut().zip(pixels) {
+            *color = LDRColor::rgba(pixel[0], pixel[1], pixel[2], pixel[3]);
+        }
+        image
+    }
     pub(crate) fn transform_texture(&self, texture: Texture) -> Texture {
         match texture {
             Texture::Solid(color) => Texture::Solid(self.transform(color)),
@@ -187,6 +213,11 @@ impl Profile {
                     .collect();
                 Texture::RadialGradient(gradient)
             }
+            Texture::Hatch(mut hatch) => {
+                hatch.color = self.transform(hatch.color);
+                hatch.background = self.transform(hatch.background);
+                Texture::Hatch(hatch)
+            }
             texture => texture,
         }
     }
@@ -217,6 +248,8 @@ impl Profile {
                     .collect(),
                 closed: path.closed,
             })),
+            // Extensions are transformed as they're drawn, whether expanded or rasterized.
+            content => content,
         }
     }
 }
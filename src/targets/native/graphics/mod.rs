@@ -3,6 +3,9 @@ use crate::graphics::canvas::InteractiveCanvas;
 mod cm;
 mod pure2d;
 
+#[cfg(any(target_os = "linux", target_os = "windows"))]
+pub(crate) use pure2d::{attach, EmbeddedContext};
+
 pub(crate) fn new() -> Box<dyn InteractiveCanvas> {
     pure2d::new()
 }
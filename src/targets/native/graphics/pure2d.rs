@@ -1,19 +1,38 @@
 use super::cm::Profile;
-use crate::graphics::path::{Path, Segment, StrokeCapType, StrokeJoinType, Texture};
-use crate::graphics::text::{Origin, Text, Weight, Wrap};
+use crate::accessibility::{self, Preferences, Provider as AccessibilityProvider};
+#[cfg(any(target_os = "linux", target_os = "windows"))]
+use crate::errors::Error;
+use crate::graphics::instances::Variation;
+use crate::graphics::path::{
+    self, compositing_stops, Dither, Hatch, ImageTexture, Path, Segment, SpreadMode, StrokeCapType,
+    StrokeJoinType, Texture, Tiling,
+};
+use crate::graphics::text::{
+    self, grapheme, search, zoom_breakpoint, Antialias as TextAntialias, Ellipsize, Font, Glyph,
+    Hinting, Origin, SubpixelOrder as TextSubpixelOrder, Text, Underline, Weight, Wrap,
+};
 use crate::graphics::{
+    self,
     canvas::{
-        ActiveCanvas, Canvas, CanvasContext, Content, Frame, InactiveCanvas, InteractiveCanvas,
-        Object, Rasterizable, Rasterizer, Ticker,
+        hit_test, image_pixel_ratio, layer::FrameLayer, objects_at, ActiveCanvas, Blend, Canvas,
+        CanvasContext, Content, CursorIcon, Extension, Frame, InactiveCanvas, InteractiveCanvas,
+        Layer, Match, Object, Rasterizable, Rasterizer, RenderWarning, TextLayout, Ticker,
     },
+    color::Compositing,
+    dpi::Logical,
+    quality::{Quality, RasterOptions},
     Image, ImageRepresentation, LDRColor, Rect, Texture2, Transform2, Vector2,
 };
+use crate::haptics::{Haptics, Provider as HapticsProvider};
 use crate::input::{
     keyboard::{self, Event as KeyboardEvent},
     mouse::{self, Event as MouseEvent},
+    pointer::{self, Event as PointerEvent},
     windowing::Event as WindowingEvent,
     Event, Input, Provider,
 };
+use crate::net::{Net, Provider as NetProvider};
+use crate::storage::{Provider as StorageProvider, Storage};
 use crate::targets::native;
 use crate::util::ObserverCell;
 use crossbeam_channel::{unbounded, Receiver, Sender, TryRecvError};
@@ -21,17 +40,23 @@ use futures::{task::AtomicTask, Async, Poll, Stream};
 
 use std::{
     any::Any,
+    borrow::Cow,
     ffi::{c_void, CString},
-    ops::Deref,
-    sync::{Arc, Mutex, RwLock},
+    ops::{Deref, Range},
+    slice,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex, RwLock,
+    },
     time::SystemTime,
 };
 
 use glutin::{dpi::LogicalSize, ContextTrait};
 
 use cairo::{
-    Antialias, FontOptions, Format, Gradient, HintStyle, ImageSurface, LineCap, LineJoin,
-    LinearGradient, Matrix, Operator, Pattern, RadialGradient, Status, SubpixelOrder,
+    Antialias, Extend, FontOptions, Format, Gradient, HintStyle, ImageSurface, LineCap, LineJoin,
+    LinearGradient, Matrix, MatrixTrait, Operator, Pattern, PatternTrait, RadialGradient, Status,
+    SubpixelOrder, Surface, SurfacePattern,
 };
 
 use pango::{FontDescription, Layout, LayoutExt};
@@ -40,6 +65,8 @@ use gl::types::*;
 
 use cairo_sys;
 
+use glib::translate::ToGlibPtr;
+
 #[cfg(target_os = "windows")]
 static SYSTEM_FONT: &str = "Segoe UI";
 #[cfg(target_os = "macos")]
@@ -63,6 +90,10 @@ impl Deref for CairoSurface {
 
 unsafe impl Send for CairoContext {}
 
+struct CairoLayout(Layout);
+
+unsafe impl Send for CairoLayout {}
+
 impl Deref for CairoContext {
     type Target = cairo::Context;
 
@@ -94,7 +125,15 @@ impl CairoImage {
     fn new(surface: CairoSurface) -> CairoImage {
         CairoImage(Arc::new(Mutex::new(surface)))
     }
-    fn box_blur(&self, data: &mut [[u8; 4]], width: u32, height: u32, radius: u32, channel: usize) {
+    fn box_blur(
+        &self,
+        data: &mut [[u8; 4]],
+        width: u32,
+        height: u32,
+        radius: u32,
+        channel: usize,
+        dither: Dither,
+    ) {
         let mut target = vec![[0, 0, 0, 0]; data.len()];
         target.copy_from_slice(data);
         self.box_blur_h(
@@ -112,6 +151,7 @@ impl CairoImage {
             height as i32,
             radius as i32,
             channel,
+            dither,
         );
     }
     fn box_blur_h(
@@ -164,6 +204,7 @@ impl CairoImage {
         height: i32,
         radius: i32,
         channel: usize,
+        dither: Dither,
     ) {
         let iarr = 1. / f64::from(radius + radius + 1);
         for i in 0..width {
@@ -178,21 +219,24 @@ impl CairoImage {
             }
             for _ in 0..=radius {
                 val += i32::from(source[ri as usize][channel]) - fv;
-                target[ti as usize][channel] = (f64::from(val) * iarr).round() as u8;
+                target[ti as usize][channel] =
+                    dither.quantize(f64::from(val) * iarr / 255., ti % width, ti / width);
                 ri += width;
                 ti += width;
             }
             for _ in radius + 1..height - radius {
                 val += i32::from(source[ri as usize][channel])
                     - i32::from(source[li as usize][channel]);
-                target[ti as usize][channel] = (f64::from(val) * iarr).round() as u8;
+                target[ti as usize][channel] =
+                    dither.quantize(f64::from(val) * iarr / 255., ti % width, ti / width);
                 li += width;
                 ti += width;
                 ri += width;
             }
             for _ in height - radius..height {
                 val += lv - i32::from(source[li as usize][channel]);
-                target[ti as usize][channel] = (f64::from(val) * iarr).round() as u8;
+                target[ti as usize][channel] =
+                    dither.quantize(f64::from(val) * iarr / 255., ti % width, ti / width);
                 li += width;
                 ti += width;
             }
@@ -209,9 +253,26 @@ impl CairoImage {
                 (width * height) as usize,
             )
         };
-        let boxes = boxes_for_gauss(radius, 3);
-        for b in 0..=2 {
-            self.box_blur(data, width, height, (boxes[b] - 1) / 2, 3);
+        let passes = Quality::current().blur_passes();
+        // Pixels are premultiplied, so every channel including alpha is blurred alike.
+        for channel in 0..4 {
+            // Only the final pass is dithered, as earlier passes would blur the dithering away.
+            for (pass, size) in boxes_for_gauss(radius, passes).into_iter().enumerate() {
+                let dither = if pass as u32 + 1 == passes {
+                    Dither::current()
+                } else {
+                    Dither::None
+                };
+                self.box_blur(data, width, height, (size - 1) / 2, channel, dither);
+            }
+        }
+        // Rounding and dithering each channel separately can leave a color channel above the
+        // alpha it is premultiplied by.
+        for pixel in data.iter_mut() {
+            let alpha = pixel[3];
+            for channel in &mut pixel[..3] {
+                *channel = (*channel).min(alpha);
+            }
         }
         unsafe { cairo_sys::cairo_surface_mark_dirty(self.0.lock().unwrap().0.to_raw_none()) };
     }
@@ -237,6 +298,20 @@ impl Clone for CairoImage {
     }
 }
 
+/// Multiplies a color channel by alpha, as stored in Cairo's ARGB32 surfaces.
+fn premultiply(channel: u8, alpha: u8) -> u8 {
+    ((u16::from(channel) * u16::from(alpha) + 127) / 255) as u8
+}
+
+/// Recovers a color channel from its premultiplied value.
+fn unpremultiply(channel: u8, alpha: u8) -> u8 {
+    if alpha == 0 {
+        0
+    } else {
+        ((u16::from(channel.min(alpha)) * 255 + u16::from(alpha) / 2) / u16::from(alpha)) as u8
+    }
+}
+
 fn pixels_to_pango_points(pixels: f64) -> i32 {
     (pixels * 0.75 * f64::from(pango::SCALE)) as i32
 }
@@ -245,6 +320,55 @@ fn pixels_to_pango_pixels(pixels: f64) -> i32 {
     (pixels * f64::from(pango::SCALE)) as i32
 }
 
+/// Returns the comma-separated family list Pango tries in order. The generic family follows the
+/// named families so that some face is always found, and the fallback families come last so that
+/// Pango only uses them for characters the others lack.
+fn font_family(entity: &Text, fallback: &[String]) -> String {
+    let mut families: Vec<&str> = entity.family.iter().map(String::as_str).collect();
+    if let Font::SystemFont = entity.font {
+        families.push(SYSTEM_FONT);
+    }
+    families.push(match entity.font {
+        Font::SystemFont | Font::SansSerif => "Sans",
+        Font::Serif => "Serif",
+        Font::Monospace => "Monospace",
+    });
+    families.extend(entity.fallback_families(fallback));
+    families.join(",")
+}
+
+/// Returns the OpenType feature settings of some [Text] in the syntax of pango, or [None] if the
+/// font's defaults are all kept.
+fn pango_weight(weight: Weight) -> pango::Weight {
+    match weight {
+        Weight::Bold => pango::Weight::Bold,
+        Weight::Hairline => pango::Weight::Ultralight,
+        Weight::Normal => pango::Weight::Normal,
+        Weight::Heavy => pango::Weight::Heavy,
+        Weight::Thin => pango::Weight::Semilight,
+        Weight::Light => pango::Weight::Light,
+        Weight::Medium => pango::Weight::Medium,
+        Weight::ExtraBold => pango::Weight::Ultrabold,
+        Weight::SemiBold => pango::Weight::Semibold,
+    }
+}
+
+fn font_features(entity: &Text) -> Option<String> {
+    let mut features: Vec<String> = entity
+        .features
+        .iter()
+        .map(|(feature, enabled)| format!("{}{}", if *enabled { "+" } else { "-" }, feature.tag()))
+        .collect();
+    if !entity.kerning {
+        features.push("-kern".to_owned());
+    }
+    if features.is_empty() {
+        None
+    } else {
+        Some(features.join(","))
+    }
+}
+
 impl ImageRepresentation for CairoImage {
     fn get_size(&self) -> Vector2 {
         (
@@ -259,24 +383,58 @@ impl ImageRepresentation for CairoImage {
     }
 
     fn as_texture(&self) -> Image<LDRColor, Texture2> {
+        let (width, height) = {
+            let surface = &self.0.lock().unwrap().0;
+            (surface.get_width() as u32, surface.get_height() as u32)
+        };
+        let data: &[[u8; 4]] = unsafe {
+            std::slice::from_raw_parts(
+                self.get_data_ptr() as *const [u8; 4],
+                (width * height) as usize,
+            )
+        };
         Image {
-            pixels: vec![],
-            format: Texture2 {
-                height: 0,
-                width: 0,
-            },
+            pixels: data
+                .iter()
+                .map(|pixel| {
+                    let alpha = pixel[3];
+                    LDRColor::rgba(
+                        unpremultiply(pixel[2], alpha),
+                        unpremultiply(pixel[1], alpha),
+                        unpremultiply(pixel[0], alpha),
+                        alpha,
+                    )
+                })
+                .collect(),
+            format: Texture2 { width, height },
         }
     }
 
     fn from_texture(texture: Image<LDRColor, Texture2>) -> CairoImage {
-        CairoImage::new(CairoSurface(
+        let image = CairoImage::new(CairoSurface(
             ImageSurface::create(
                 Format::ARgb32,
                 texture.format.width as i32,
                 texture.format.height as i32,
             )
             .unwrap(),
-        ))
+        ));
+        let data: &mut [[u8; 4]] = unsafe {
+            std::slice::from_raw_parts_mut(
+                image.get_data_ptr() as *mut [u8; 4],
+                (texture.format.width * texture.format.height) as usize,
+            )
+        };
+        for (pixel, color) in data.iter_mut().zip(texture.pixels.iter()) {
+            *pixel = [
+                premultiply(color.b, color.a),
+                premultiply(color.g, color.a),
+                premultiply(color.r, color.a),
+                color.a,
+            ];
+        }
+        unsafe { cairo_sys::cairo_surface_mark_dirty(image.0.lock().unwrap().0.to_raw_none()) };
+        image
     }
 
     fn as_any(&self) -> Box<dyn Any> {
@@ -284,13 +442,46 @@ impl ImageRepresentation for CairoImage {
     }
 }
 
+/// The regions of a frame that need to be drawn again, in the space of its contents.
+struct Damage {
+    /// Whether the whole viewport needs to be drawn again.
+    full: bool,
+    regions: Vec<Rect>,
+    /// The generation of the global rendering settings the frame was last drawn under.
+    settings: usize,
+}
+
 struct CairoFrameState {
     context: Mutex<CairoContext>,
     contents: Vec<CairoObject>,
+    layers: Vec<FrameLayer>,
+    background: LDRColor,
     viewport: Rect,
+    clip: Option<Path>,
     color_profile: Option<Profile>,
     size: Vector2,
     pixel_ratio: f64,
+    interpolation: Option<f64>,
+    raster_options: RasterOptions,
+    damage: Mutex<Damage>,
+    /// Whether the last draw changed the surface.
+    painted: Mutex<bool>,
+    warnings: Mutex<Vec<RenderWarning>>,
+    patterns: Patterns,
+    fallback: Vec<String>,
+}
+
+impl CairoFrameState {
+    /// Marks the whole frame to be drawn again.
+    fn invalidate(&self) {
+        self.damage.lock().unwrap().full = true;
+    }
+    fn warn(&self, warning: RenderWarning) {
+        let mut warnings = self.warnings.lock().unwrap();
+        if !warnings.contains(&warning) {
+            warnings.push(warning);
+        }
+    }
 }
 
 struct CairoFrame {
@@ -318,6 +509,9 @@ fn composite_clip(context: &CairoContext, entity: &Path) {
                 Segment::QuadraticTo(point, handle) => {
                     context.curve_to(handle.x, handle.y, handle.x, handle.y, point.x, point.y);
                 }
+                Segment::ClosePath => {
+                    context.close_path();
+                }
             });
         context.set_source_rgb(0., 0., 0.);
         context.set_operator(Operator::DestIn);
@@ -326,7 +520,444 @@ fn composite_clip(context: &CairoContext, entity: &Path) {
     }
 }
 
-fn draw_path(context: &CairoContext, entity: &Path, pixel_ratio: f64) {
+fn to_cairo_matrix(matrix: [f64; 6]) -> Matrix {
+    Matrix {
+        xx: matrix[0],
+        yx: matrix[2],
+        xy: matrix[1],
+        yy: matrix[3],
+        x0: matrix[4],
+        y0: matrix[5],
+    }
+}
+
+/// Returns bounds containing everything drawn for a path, including its stroke, markers and
+/// shadows.
+fn path_bounds(path: &Path) -> Rect {
+    let bounds = path.bounds();
+    // Miter joins reach beyond the stroke width, which is allowed for twice over.
+    let stroke = path
+        .stroke
+        .as_ref()
+        .map_or(0., |stroke| f64::from(stroke.width) * 2.);
+    let shadow = path
+        .shadows
+        .iter()
+        .map(|shadow| {
+            shadow.offset.x.abs().max(shadow.offset.y.abs()) + shadow.blur * 2. + shadow.spread
+        })
+        .fold(0., f64::max);
+    let pad = stroke + shadow + 1.;
+    path.markers().iter().fold(
+        Rect::new(bounds.position - pad, bounds.size + pad * 2.),
+        |bounds, marker| bounds.union(path_bounds(marker)),
+    )
+}
+
+/// Expands a region of the contents of a frame to whole pixels of its surface, so that clipping
+/// to it doesn't blend what is drawn again with what is kept at its edges.
+fn align_region(context: &CairoContext, region: Rect) -> Rect {
+    let a = context.user_to_device(region.position.x, region.position.y);
+    let b = context.user_to_device(
+        region.position.x + region.size.x,
+        region.position.y + region.size.y,
+    );
+    let (near, far) = (
+        context.device_to_user(a.0.min(b.0).floor(), a.1.min(b.1).floor()),
+        context.device_to_user(a.0.max(b.0).ceil(), a.1.max(b.1).ceil()),
+    );
+    Rect::new(
+        (near.0.min(far.0), near.1.min(far.1)),
+        ((near.0 - far.0).abs(), (near.1 - far.1).abs()),
+    )
+}
+
+fn compose_matrices(parent: [f64; 6], child: [f64; 6]) -> [f64; 6] {
+    let matrix = Matrix::multiply(&to_cairo_matrix(child), &to_cairo_matrix(parent));
+    [
+        matrix.xx, matrix.xy, matrix.yx, matrix.yy, matrix.x0, matrix.y0,
+    ]
+}
+
+/// Styles a gradient pattern, returning false if its transform can't be inverted and nothing
+/// should be painted with it.
+fn style_gradient<T: PatternTrait>(
+    gradient: &T,
+    spread: SpreadMode,
+    transform: Option<Transform2>,
+) -> bool {
+    gradient.set_extend(match spread {
+        SpreadMode::Pad => Extend::Pad,
+        SpreadMode::Repeat => Extend::Repeat,
+        SpreadMode::Reflect => Extend::Reflect,
+    });
+    if let Some(transform) = transform {
+        let matrix = transform.to_matrix();
+        // Pattern matrices map from user space into pattern space, the inverse of the transform.
+        let matrix = Matrix {
+            xx: matrix[0],
+            yx: matrix[2],
+            xy: matrix[1],
+            yy: matrix[3],
+            x0: matrix[4],
+            y0: matrix[5],
+        };
+        match matrix.try_invert() {
+            Ok(matrix) => gradient.set_matrix(matrix),
+            Err(_) => return false,
+        }
+    }
+    true
+}
+
+/// A gradient that's rasterized to be dithered, as Cairo can't dither gradients.
+#[derive(Clone, PartialEq)]
+enum DitheredGradient {
+    Linear(path::LinearGradient),
+    Radial(path::RadialGradient),
+}
+
+impl DitheredGradient {
+    fn dither(&self) -> Dither {
+        match self {
+            DitheredGradient::Linear(gradient) => gradient.dither(),
+            DitheredGradient::Radial(gradient) => gradient.dither(),
+        }
+    }
+    fn color_at(&self, point: Vector2) -> Option<[f64; 4]> {
+        match self {
+            DitheredGradient::Linear(gradient) => gradient.color_at(point),
+            DitheredGradient::Radial(gradient) => gradient.color_at(point),
+        }
+    }
+}
+
+/// A dithered gradient rasterized over a region of device space, along with the settings it
+/// was rasterized with.
+struct GradientRaster {
+    gradient: DitheredGradient,
+    dither: Dither,
+    compositing: Compositing,
+    /// The transformation from user space into device space.
+    matrix: [f64; 6],
+    origin: (f64, f64),
+    size: (i32, i32),
+}
+
+/// Rasterizes a gradient with dithering, covering the provided extents in user space at the
+/// resolution of the target surface, or reuses the raster of a previous draw that covered the
+/// same pixels.
+fn dithered_gradient(
+    context: &CairoContext,
+    extents: (f64, f64, f64, f64),
+    gradient: &DitheredGradient,
+    patterns: &Patterns,
+) -> Pattern {
+    let clip = context.clip_extents();
+    let (left, top) = (extents.0.max(clip.0), extents.1.max(clip.1));
+    let (right, bottom) = (extents.2.min(clip.2), extents.3.min(clip.3));
+    let (mut min, mut max) = (
+        Vector2::from(std::f64::INFINITY),
+        Vector2::from(std::f64::NEG_INFINITY),
+    );
+    for (x, y) in &[(left, top), (right, top), (right, bottom), (left, bottom)] {
+        let corner = context.user_to_device(*x, *y);
+        min = (min.x.min(corner.0), min.y.min(corner.1)).into();
+        max = (max.x.max(corner.0), max.y.max(corner.1)).into();
+    }
+    let origin = (min.x.floor(), min.y.floor());
+    let size = (
+        (max.x.ceil() - origin.0).max(1.) as i32,
+        (max.y.ceil() - origin.1).max(1.) as i32,
+    );
+    let matrix = context.get_matrix();
+    let (dither, compositing) = (gradient.dither(), Compositing::current());
+    let device = [
+        matrix.xx, matrix.yx, matrix.xy, matrix.yy, matrix.x0, matrix.y0,
+    ];
+    let cached = patterns
+        .gradients
+        .lock()
+        .unwrap()
+        .iter()
+        .find(|(raster, _)| {
+            raster.gradient == *gradient
+                && raster.dither == dither
+                && raster.compositing == compositing
+                && raster.matrix == device
+                && raster.origin == origin
+                && raster.size == size
+        })
+        .map(|(_, surface)| surface.0.clone());
+    let surface = match cached {
+        Some(surface) => surface,
+        None => {
+            let surface = rasterize_gradient(context, gradient, dither, origin, size);
+            let mut rasters = patterns.gradients.lock().unwrap();
+            if rasters.len() == GRADIENT_RASTER_CAPACITY {
+                rasters.remove(0);
+            }
+            let raster = GradientRaster {
+                gradient: gradient.clone(),
+                dither,
+                compositing,
+                matrix: device,
+                origin,
+                size,
+            };
+            rasters.push((raster, CairoSurface(surface.clone())));
+            surface
+        }
+    };
+    let pattern = SurfacePattern::create(&surface);
+    // The surface is in device space, offset to the extents, rather than in user space.
+    let matrix = Matrix::multiply(
+        &matrix,
+        &Matrix {
+            xx: 1.,
+            yx: 0.,
+            xy: 0.,
+            yy: 1.,
+            x0: -origin.0,
+            y0: -origin.1,
+        },
+    );
+    pattern.set_matrix(matrix);
+    Pattern::SurfacePattern(pattern)
+}
+
+/// Rasterizes a gradient with dithering over a region of the device space of a context.
+fn rasterize_gradient(
+    context: &CairoContext,
+    gradient: &DitheredGradient,
+    dither: Dither,
+    origin: (f64, f64),
+    size: (i32, i32),
+) -> ImageSurface {
+    let mut surface = ImageSurface::create(Format::ARgb32, size.0, size.1).unwrap();
+    let stride = surface.get_stride() as usize;
+    {
+        let mut data = surface.get_data().unwrap();
+        for y in 0..size.1 {
+            for x in 0..size.0 {
+                let point = context
+                    .device_to_user(origin.0 + f64::from(x) + 0.5, origin.1 + f64::from(y) + 0.5);
+                let color = match gradient.color_at(point.into()) {
+                    Some(color) => color,
+                    None => continue,
+                };
+                let (device_x, device_y) = (origin.0 as i32 + x, origin.1 as i32 + y);
+                let quantize = |value: f64| dither.quantize(value, device_x, device_y);
+                let alpha = quantize(color[3]);
+                // Channels are premultiplied and can't exceed the alpha they're multiplied by.
+                let channel = |value: f64| u32::from(quantize(value * color[3]).min(alpha));
+                let pixel = u32::from(alpha) << 24
+                    | channel(color[0]) << 16
+                    | channel(color[1]) << 8
+                    | channel(color[2]);
+                let offset = y as usize * stride + x as usize * 4;
+                data[offset..offset + 4].copy_from_slice(&pixel.to_ne_bytes());
+            }
+        }
+    }
+    surface
+}
+
+fn image_pattern(texture: &ImageTexture, bounds: Rect) -> Option<Pattern> {
+    let image = texture.image.as_any().downcast::<CairoImage>().unwrap();
+    let surface = &image.0.lock().unwrap().0;
+    let pattern = match texture.source {
+        // A subsurface lets the pattern repeat and extend the source area alone.
+        Some(_) => {
+            let source = texture.source();
+            let subsurface = unsafe {
+                Surface::from_raw_full(cairo_sys::cairo_surface_create_for_rectangle(
+                    surface.to_raw_none(),
+                    source.position.x,
+                    source.position.y,
+                    source.size.x,
+                    source.size.y,
+                ))
+            };
+            SurfacePattern::create(&subsurface)
+        }
+        None => SurfacePattern::create(surface),
+    };
+    pattern.set_extend(match texture.tiling {
+        Tiling::None => Extend::None,
+        Tiling::Repeat => Extend::Repeat,
+        Tiling::Mirror => Extend::Reflect,
+        Tiling::Clamp => Extend::Pad,
+    });
+    let (anchor, origin) = texture.anchoring(bounds);
+    let transform = texture.transform.to_matrix();
+    let mut matrix = Matrix::identity();
+    matrix.translate(anchor.x, anchor.y);
+    let mut matrix = Matrix::multiply(
+        &Matrix {
+            xx: transform[0],
+            yx: transform[2],
+            xy: transform[1],
+            yy: transform[3],
+            x0: transform[4],
+            y0: transform[5],
+        },
+        &matrix,
+    );
+    matrix.translate(-origin.x, -origin.y);
+    // A degenerate transform has no inverse and leaves nothing to paint.
+    pattern.set_matrix(matrix.try_invert().ok()?);
+    Some(Pattern::SurfacePattern(pattern))
+}
+
+/// The patterns a frame renders itself, kept for reuse by later draws.
+#[derive(Default)]
+struct Patterns {
+    /// The tiles most recently rendered for hatches, oldest first, along with the pixel ratio
+    /// each was rendered at.
+    hatches: Mutex<Vec<(Hatch, f64, CairoSurface)>>,
+    /// The dithered gradients most recently rasterized, oldest first.
+    gradients: Mutex<Vec<(GradientRaster, CairoSurface)>>,
+}
+
+/// The greatest number of hatch tiles a frame keeps for reuse.
+const HATCH_TILE_CAPACITY: usize = 16;
+
+/// The greatest number of dithered gradients a frame keeps for reuse.
+const GRADIENT_RASTER_CAPACITY: usize = 16;
+
+/// Returns the tile of a hatch rendered at a pixel ratio, rendering it only if it isn't cached.
+fn hatch_tile(hatch: &Hatch, pixel_ratio: f64, patterns: &Patterns) -> ImageSurface {
+    let cached = patterns
+        .hatches
+        .lock()
+        .unwrap()
+        .iter()
+        .find(|(cached, ratio, _)| cached == hatch && *ratio == pixel_ratio)
+        .map(|(_, _, tile)| tile.0.clone());
+    if let Some(tile) = cached {
+        return tile;
+    }
+    let pixels = (hatch.spacing * pixel_ratio).ceil().max(1.);
+    let surface = ImageSurface::create(Format::ARgb32, pixels as i32, pixels as i32).unwrap();
+    let context = CairoContext(cairo::Context::new(&surface));
+    context.scale(pixels / hatch.spacing, pixels / hatch.spacing);
+    context.set_source_rgba(
+        f64::from(hatch.background.r) / 255.,
+        f64::from(hatch.background.g) / 255.,
+        f64::from(hatch.background.b) / 255.,
+        f64::from(hatch.background.a) / 255.,
+    );
+    context.paint();
+    draw_path(&context, &hatch.tile(), None, pixel_ratio, patterns);
+    let mut tiles = patterns.hatches.lock().unwrap();
+    if tiles.len() == HATCH_TILE_CAPACITY {
+        tiles.remove(0);
+    }
+    tiles.push((*hatch, pixel_ratio, CairoSurface(surface.clone())));
+    surface
+}
+
+fn hatch_pattern(hatch: &Hatch, pixel_ratio: f64, patterns: &Patterns) -> Option<Pattern> {
+    let pixels = (hatch.spacing * pixel_ratio).ceil().max(1.);
+    let surface = hatch_tile(hatch, pixel_ratio, patterns);
+    let pattern = SurfacePattern::create(&surface);
+    pattern.set_extend(Extend::Repeat);
+    // The tile is rendered at a whole number of pixels, so it's scaled back down to span exactly
+    // one spacing interval in user space.
+    let mut matrix = Matrix::identity();
+    matrix.rotate(hatch.angle);
+    matrix.scale(hatch.spacing / pixels, hatch.spacing / pixels);
+    pattern.set_matrix(matrix.try_invert().ok()?);
+    Some(Pattern::SurfacePattern(pattern))
+}
+
+/// Returns whether the version of Pango that's loaded hyphenates the words it breaks, which it
+/// does from version 1.44.
+fn pango_hyphenates() -> bool {
+    unsafe { pango_sys::pango_version() >= 14400 }
+}
+
+/// The extents of a line of a [Layout], in Pango units, and how far it is moved down from where
+/// Pango lays it out so that the baselines of its text are the line pitch apart.
+struct PlacedLine {
+    shift: i32,
+    ink: pango::Rectangle,
+    logical: pango::Rectangle,
+}
+
+impl PlacedLine {
+    /// Returns the top and bottom of the logical extents of the line as placed.
+    fn span(&self) -> (i32, i32) {
+        let top = self.logical.y + self.shift;
+        (top, top + self.logical.height)
+    }
+}
+
+/// Places the lines of a layout of some [Text] scaled by the provided factor. Pango spaces lines
+/// apart uniformly by adding to their heights, which differ where fonts are mixed, so each line
+/// is moved on its own to put its baseline the line pitch below that of the line before.
+fn place_lines(entity: &Text, layout: &Layout, scale: f64) -> Vec<PlacedLine> {
+    let pitch = pixels_to_pango_pixels(entity.line_pitch() * scale);
+    let mut lines = vec![];
+    let mut iter = match layout.get_iter() {
+        Some(iter) => iter,
+        None => return lines,
+    };
+    let first = iter.get_baseline();
+    loop {
+        let (ink, logical) = iter.get_line_extents();
+        lines.push(PlacedLine {
+            shift: first + pitch * lines.len() as i32 - iter.get_baseline(),
+            ink,
+            logical,
+        });
+        if !iter.next_line() {
+            break;
+        }
+    }
+    lines
+}
+
+/// Returns the height, in Pango units, of placed lines.
+fn placed_height(lines: &[PlacedLine]) -> i32 {
+    lines.last().map_or(0, |line| line.span().1)
+}
+
+/// Returns how far the line of a layout holding the provided byte index is moved down.
+fn shift_at(layout: &Layout, lines: &[PlacedLine], index: i32) -> i32 {
+    let (line, _) = layout.index_to_line_x(index, false);
+    lines.get(line as usize).map_or(0, |line| line.shift)
+}
+
+/// The variation of one of some [Instances](crate::graphics::instances::Instances) and the color
+/// profile of the display, which are applied to the textures of the shared path as it's drawn.
+struct InstancePaint<'a> {
+    variation: Variation,
+    color_profile: Option<&'a Profile>,
+}
+
+impl InstancePaint<'_> {
+    fn texture<'a>(&self, texture: &'a Texture) -> Cow<'a, Texture> {
+        let texture = self.variation.texture(texture);
+        match self.color_profile {
+            Some(color_profile) => {
+                Cow::Owned(color_profile.transform_texture(texture.into_owned()))
+            }
+            None => texture,
+        }
+    }
+}
+
+/// Draws a path in user space, with its textures painted as for an instance of some
+/// [Instances](crate::graphics::instances::Instances) if provided.
+fn draw_path(
+    context: &CairoContext,
+    entity: &Path,
+    paint: Option<&InstancePaint<'_>>,
+    pixel_ratio: f64,
+    patterns: &Patterns,
+) {
     context.move_to(0., 0.);
     entity.segments.iter().for_each(|segment| match segment {
         Segment::LineTo(point) => {
@@ -343,6 +974,9 @@ fn draw_path(context: &CairoContext, entity: &Path, pixel_ratio: f64) {
         Segment::QuadraticTo(point, handle) => {
             context.curve_to(handle.x, handle.y, handle.x, handle.y, point.x, point.y);
         }
+        Segment::ClosePath => {
+            context.close_path();
+        }
     });
     if entity.closed {
         context.close_path();
@@ -358,7 +992,12 @@ fn draw_path(context: &CairoContext, entity: &Path, pixel_ratio: f64) {
                 StrokeJoinType::Round => LineJoin::Round,
                 StrokeJoinType::Bevel => LineJoin::Bevel,
             });
-            match &stroke.content {
+            context.set_line_width(f64::from(stroke.width));
+            let content = match paint {
+                Some(paint) => paint.texture(&stroke.content),
+                None => Cow::Borrowed(&stroke.content),
+            };
+            let painted = match &*content {
                 Texture::Solid(color) => {
                     context.set_source_rgba(
                         f64::from(color.r) / 255.,
@@ -366,67 +1005,100 @@ fn draw_path(context: &CairoContext, entity: &Path, pixel_ratio: f64) {
                         f64::from(color.b) / 255.,
                         f64::from(color.a) / 255.,
                     );
+                    true
                 }
                 Texture::LinearGradient(gradient) => {
-                    let canvas_gradient = LinearGradient::new(
-                        gradient.start.x,
-                        gradient.start.y,
-                        gradient.end.x,
-                        gradient.end.y,
-                    );
-                    gradient.stops.iter().for_each(|stop| {
-                        canvas_gradient.add_color_stop_rgba(
-                            stop.offset,
-                            f64::from(stop.color.r) / 255.,
-                            f64::from(stop.color.g) / 255.,
-                            f64::from(stop.color.b) / 255.,
-                            f64::from(stop.color.a) / 255.,
-                        )
-                    });
-                    context.set_source(&Pattern::LinearGradient(canvas_gradient));
+                    if gradient.dither() != Dither::None {
+                        let gradient = DitheredGradient::Linear(gradient.clone());
+                        let extents = context.stroke_extents();
+                        let pattern = dithered_gradient(context, extents, &gradient, patterns);
+                        context.set_source(&pattern);
+                        true
+                    } else {
+                        let canvas_gradient = LinearGradient::new(
+                            gradient.start.x,
+                            gradient.start.y,
+                            gradient.end.x,
+                            gradient.end.y,
+                        );
+                        compositing_stops(&gradient.stops).iter().for_each(|stop| {
+                            canvas_gradient.add_color_stop_rgba(
+                                stop.offset,
+                                f64::from(stop.color.r) / 255.,
+                                f64::from(stop.color.g) / 255.,
+                                f64::from(stop.color.b) / 255.,
+                                f64::from(stop.color.a) / 255.,
+                            )
+                        });
+                        let styled =
+                            style_gradient(&canvas_gradient, gradient.spread, gradient.transform);
+                        context.set_source(&Pattern::LinearGradient(canvas_gradient));
+                        styled
+                    }
                 }
-                Texture::Image(image) => {
-                    let pattern = image.as_any().downcast::<CairoImage>().unwrap();
-                    let surface = &pattern.0.lock().unwrap().0;
-                    //TODO: coordinates here probd shouldn't be 0, 0
-                    context.set_source_surface(surface, 0.0, 0.0);
+                Texture::Image(texture) => {
+                    let width = f64::from(stroke.width);
+                    let bounds = entity.bounds();
+                    let bounds = Rect::new(bounds.position - width / 2., bounds.size + width);
+                    image_pattern(texture, bounds)
+                        .map(|pattern| context.set_source(&pattern))
+                        .is_some()
                 }
                 Texture::RadialGradient(gradient) => {
-                    let canvas_gradient = RadialGradient::new(
-                        gradient.start.x,
-                        gradient.start.y,
-                        gradient.start_radius,
-                        gradient.end.x,
-                        gradient.end.y,
-                        gradient.end_radius,
-                    );
-                    gradient.stops.iter().for_each(|stop| {
-                        canvas_gradient.add_color_stop_rgba(
-                            stop.offset,
-                            f64::from(stop.color.r) / 255.,
-                            f64::from(stop.color.g) / 255.,
-                            f64::from(stop.color.b) / 255.,
-                            f64::from(stop.color.a) / 255.,
+                    if gradient.dither() != Dither::None {
+                        let gradient = DitheredGradient::Radial(gradient.clone());
+                        let extents = context.stroke_extents();
+                        let pattern = dithered_gradient(context, extents, &gradient, patterns);
+                        context.set_source(&pattern);
+                        true
+                    } else {
+                        let canvas_gradient = RadialGradient::new(
+                            gradient.start.x,
+                            gradient.start.y,
+                            gradient.start_radius,
+                            gradient.end.x,
+                            gradient.end.y,
+                            gradient.end_radius,
                         );
-                    });;
-                    context.set_source(&Pattern::RadialGradient(canvas_gradient));
+                        compositing_stops(&gradient.stops).iter().for_each(|stop| {
+                            canvas_gradient.add_color_stop_rgba(
+                                stop.offset,
+                                f64::from(stop.color.r) / 255.,
+                                f64::from(stop.color.g) / 255.,
+                                f64::from(stop.color.b) / 255.,
+                                f64::from(stop.color.a) / 255.,
+                            );
+                        });;
+                        let styled =
+                            style_gradient(&canvas_gradient, gradient.spread, gradient.transform);
+                        context.set_source(&Pattern::RadialGradient(canvas_gradient));
+                        styled
+                    }
                 }
-            }
-            context.set_line_width(f64::from(stroke.width));
-            if entity.fill.is_some() {
+                Texture::Hatch(hatch) => hatch_pattern(hatch, pixel_ratio, patterns)
+                    .map(|pattern| context.set_source(&pattern))
+                    .is_some(),
+            };
+            if !painted {
+                // Skipping the stroke leaves the path for the fill, or clears it if there's none.
+                if entity.fill.is_none() {
+                    context.new_path();
+                }
+            } else if entity.fill.is_some() {
                 context.stroke_preserve();
             } else {
                 context.stroke();
             }
-            if let Texture::Image(_image) = &stroke.content {
-                context.scale(pixel_ratio, pixel_ratio);
-            }
         }
         None => {}
     }
     match &entity.fill {
         Some(fill) => {
-            match &fill.content {
+            let content = match paint {
+                Some(paint) => paint.texture(&fill.content),
+                None => Cow::Borrowed(&fill.content),
+            };
+            let painted = match &*content {
                 Texture::Solid(color) => {
                     context.set_source_rgba(
                         f64::from(color.r) / 255.,
@@ -434,59 +1106,87 @@ fn draw_path(context: &CairoContext, entity: &Path, pixel_ratio: f64) {
                         f64::from(color.b) / 255.,
                         f64::from(color.a) / 255.,
                     );
+                    true
                 }
-                Texture::Image(image) => {
-                    let pattern = image.as_any().downcast::<CairoImage>().unwrap();
-                    let surface = &pattern.0.lock().unwrap().0;
-                    //TODO: coordinates here probd shouldn't be 0, 0
-                    context.set_source_surface(surface, 0.0, 0.0);
-                }
+                Texture::Image(texture) => image_pattern(texture, entity.bounds())
+                    .map(|pattern| context.set_source(&pattern))
+                    .is_some(),
                 Texture::LinearGradient(gradient) => {
-                    let canvas_gradient = LinearGradient::new(
-                        gradient.start.x,
-                        gradient.start.y,
-                        gradient.end.x,
-                        gradient.end.y,
-                    );
-                    gradient.stops.iter().for_each(|stop| {
-                        canvas_gradient.add_color_stop_rgba(
-                            stop.offset,
-                            f64::from(stop.color.r) / 255.,
-                            f64::from(stop.color.g) / 255.,
-                            f64::from(stop.color.b) / 255.,
-                            f64::from(stop.color.a) / 255.,
-                        )
-                    });
-                    context.set_source(&Pattern::LinearGradient(canvas_gradient));
+                    if gradient.dither() != Dither::None {
+                        let gradient = DitheredGradient::Linear(gradient.clone());
+                        let extents = context.fill_extents();
+                        let pattern = dithered_gradient(context, extents, &gradient, patterns);
+                        context.set_source(&pattern);
+                        true
+                    } else {
+                        let canvas_gradient = LinearGradient::new(
+                            gradient.start.x,
+                            gradient.start.y,
+                            gradient.end.x,
+                            gradient.end.y,
+                        );
+                        compositing_stops(&gradient.stops).iter().for_each(|stop| {
+                            canvas_gradient.add_color_stop_rgba(
+                                stop.offset,
+                                f64::from(stop.color.r) / 255.,
+                                f64::from(stop.color.g) / 255.,
+                                f64::from(stop.color.b) / 255.,
+                                f64::from(stop.color.a) / 255.,
+                            )
+                        });
+                        let styled =
+                            style_gradient(&canvas_gradient, gradient.spread, gradient.transform);
+                        context.set_source(&Pattern::LinearGradient(canvas_gradient));
+                        styled
+                    }
                 }
                 Texture::RadialGradient(gradient) => {
-                    let canvas_gradient = RadialGradient::new(
-                        gradient.start.x,
-                        gradient.start.y,
-                        gradient.start_radius,
-                        gradient.end.x,
-                        gradient.end.y,
-                        gradient.end_radius,
-                    );
-                    gradient.stops.iter().for_each(|stop| {
-                        canvas_gradient.add_color_stop_rgba(
-                            stop.offset,
-                            f64::from(stop.color.r) / 255.,
-                            f64::from(stop.color.g) / 255.,
-                            f64::from(stop.color.b) / 255.,
-                            f64::from(stop.color.a) / 255.,
+                    if gradient.dither() != Dither::None {
+                        let gradient = DitheredGradient::Radial(gradient.clone());
+                        let extents = context.fill_extents();
+                        let pattern = dithered_gradient(context, extents, &gradient, patterns);
+                        context.set_source(&pattern);
+                        true
+                    } else {
+                        let canvas_gradient = RadialGradient::new(
+                            gradient.start.x,
+                            gradient.start.y,
+                            gradient.start_radius,
+                            gradient.end.x,
+                            gradient.end.y,
+                            gradient.end_radius,
                         );
-                    });
-                    context.set_source(&Pattern::RadialGradient(canvas_gradient));
+                        compositing_stops(&gradient.stops).iter().for_each(|stop| {
+                            canvas_gradient.add_color_stop_rgba(
+                                stop.offset,
+                                f64::from(stop.color.r) / 255.,
+                                f64::from(stop.color.g) / 255.,
+                                f64::from(stop.color.b) / 255.,
+                                f64::from(stop.color.a) / 255.,
+                            );
+                        });
+                        let styled =
+                            style_gradient(&canvas_gradient, gradient.spread, gradient.transform);
+                        context.set_source(&Pattern::RadialGradient(canvas_gradient));
+                        styled
+                    }
                 }
-            }
-            context.fill();
-            if let Texture::Image(_image) = &fill.content {
-                context.scale(pixel_ratio, pixel_ratio);
+                Texture::Hatch(hatch) => hatch_pattern(hatch, pixel_ratio, patterns)
+                    .map(|pattern| context.set_source(&pattern))
+                    .is_some(),
+            };
+            if painted {
+                context.fill();
+            } else {
+                context.new_path();
             }
         }
         None => {}
     }
+    entity
+        .markers()
+        .iter()
+        .for_each(|marker| draw_path(context, marker, paint, pixel_ratio, patterns));
 }
 
 impl CairoFrame {
@@ -497,6 +1197,9 @@ impl CairoFrame {
             state: Arc::new(RwLock::new(CairoFrameState {
                 context: Mutex::new(CairoContext(cairo::Context::new(&surface))),
                 contents: vec![],
+                layers: vec![],
+                background: LDRColor::white(),
+                clip: None,
                 size,
                 color_profile: None,
                 viewport: Rect {
@@ -504,6 +1207,17 @@ impl CairoFrame {
                     position: (0., 0.).into(),
                 },
                 pixel_ratio: 1.,
+                interpolation: None,
+                raster_options: RasterOptions::default(),
+                damage: Mutex::new(Damage {
+                    full: true,
+                    regions: vec![],
+                    settings: graphics::settings_generation(),
+                }),
+                painted: Mutex::new(false),
+                warnings: Mutex::new(vec![]),
+                patterns: Patterns::default(),
+                fallback: text::default_fallback(),
             })),
         })
     }
@@ -522,9 +1236,157 @@ impl CairoFrame {
             .unwrap(),
         )))
     }
+    /// Returns whether the last draw changed the surface of the frame.
+    fn painted(&self) -> bool {
+        *self.state.read().unwrap().painted.lock().unwrap()
+    }
+    fn pixels(state: &CairoFrameState) -> (u32, u32) {
+        Logical(state.size)
+            .to_physical(state.pixel_ratio)
+            .to_pixels()
+    }
+    fn allocate(state: &mut CairoFrameState) {
+        let (width, height) = CairoFrame::pixels(state);
+        let surface = ImageSurface::create(Format::ARgb32, width as i32, height as i32).unwrap();
+        state.context = Mutex::new(CairoContext(cairo::Context::new(&surface)));
+        state.invalidate();
+    }
+    /// Returns bounds containing everything drawn for an object, in the space of the contents of
+    /// the frame, at its transform and at the previous transform it is interpolated from.
+    fn object_bounds(&self, state: &CairoFrameState, object: &CairoObjectState) -> Rect {
+        let bounds = match &object.content {
+            Rasterizable::Path(path) => path_bounds(path),
+            Rasterizable::Text(text) => {
+                let layout = self.layout_text(text);
+                let offset = CairoFrame::origin_offset(text, &layout, 1.);
+                let scale = f64::from(pango::SCALE);
+                let extents = |rect: &pango::Rectangle, shift: i32| {
+                    Rect::new(
+                        (
+                            f64::from(rect.x) / scale,
+                            f64::from(rect.y + shift) / scale + offset,
+                        ),
+                        (
+                            f64::from(rect.width) / scale,
+                            f64::from(rect.height) / scale,
+                        ),
+                    )
+                };
+                let bounds = place_lines(text, &layout, 1.)
+                    .iter()
+                    .map(|line| {
+                        extents(&line.ink, line.shift).union(extents(&line.logical, line.shift))
+                    })
+                    .fold(None, |bounds: Option<Rect>, line| {
+                        Some(bounds.map_or(line, |bounds| bounds.union(line)))
+                    })
+                    .unwrap_or_default();
+                // Text is laid out again at the scale it's drawn at, where hinting can move its
+                // glyphs by up to a pixel.
+                Rect::new(bounds.position - 1., bounds.size + 2.)
+            }
+            Rasterizable::Extension(extension) => Rect::new((0., 0.), extension.measure()),
+        };
+        let bounds = match (state.interpolation, object.previous) {
+            (Some(_), Some(previous)) => bounds
+                .transform(object.orientation)
+                .union(bounds.transform(previous)),
+            _ => bounds.transform(object.orientation),
+        };
+        if bounds.position.x.is_finite()
+            && bounds.position.y.is_finite()
+            && bounds.size.x.is_finite()
+            && bounds.size.y.is_finite()
+        {
+            bounds
+        } else {
+            state.viewport
+        }
+    }
+    /// Returns the regions of the frame that have changed since it was last drawn, in the space of
+    /// its contents, recording the bounds each changed object is now drawn within. Changes to
+    /// layers or to the global rendering settings, and drawing at interpolated transforms, damage
+    /// the whole viewport.
+    fn take_damage(&self, state: &CairoFrameState) -> Vec<Rect> {
+        let (full, stale, mut regions) = {
+            let mut damage = state.damage.lock().unwrap();
+            let settings = graphics::settings_generation();
+            let stale = std::mem::replace(&mut damage.settings, settings) != settings;
+            (
+                std::mem::take(&mut damage.full) || stale,
+                stale,
+                std::mem::take(&mut damage.regions),
+            )
+        };
+        for layer in &state.layers {
+            // Layers keep their rasterization until they are dirty, so they are drawn again too,
+            // except for static layers, which are only drawn again once the settings they were
+            // rasterized under change.
+            if stale || (full && !layer.is_static()) {
+                layer.invalidate();
+            }
+        }
+        for object in &state.contents {
+            let mut object = object.state.write().unwrap();
+            // Extensions can change without their objects being updated, though instances can't.
+            let extension = matches!(
+                &object.content,
+                Rasterizable::Extension(extension) if extension.as_instances().is_none()
+            );
+            if !object.changed && !extension {
+                continue;
+            }
+            object.changed = false;
+            regions.extend(object.drawn.take());
+            if object.visible {
+                let bounds = self.object_bounds(state, &object);
+                regions.push(bounds);
+                object.drawn = Some(bounds);
+            }
+        }
+        if full || state.interpolation.is_some() || state.layers.iter().any(FrameLayer::is_dirty) {
+            // Partial draws only reach some of the content, so warnings are only gathered afresh
+            // when all of it is drawn again.
+            state.warnings.lock().unwrap().clear();
+            vec![state.viewport]
+        } else {
+            // Changes outside of the viewport aren't visible, so they don't need drawing.
+            regions
+                .into_iter()
+                .filter(|region| region.intersects(state.viewport))
+                .collect()
+        }
+    }
+    /// Returns the objects of the frame at the provided position in its pixels, topmost first.
+    fn hits(&self, position: Vector2) -> impl Iterator<Item = Box<dyn Object>> + '_ {
+        let (contents, layers, point, clip) = {
+            let state = self.state.read().unwrap();
+            (
+                state.contents.clone(),
+                state.layers.clone(),
+                state.viewport.position + position / state.size * state.viewport.size,
+                state.clip.clone(),
+            )
+        };
+        // Layers are composited above the contents, and later contents above earlier ones.
+        let hit = move |object: &CairoObject| {
+            let state = object.state.read().unwrap();
+            state.visible
+                && state.blend != Blend::Erase
+                && hit_test(
+                    &state.content,
+                    state.orientation,
+                    state.clip.as_ref(),
+                    point,
+                    |text| object.text_rects(self, text),
+                )
+        };
+        objects_at(layers, contents, position, point, clip.as_ref(), hit)
+    }
     fn set_color_profile(&self, profile: Profile) {
         let mut state = self.state.write().unwrap();
         state.color_profile = Some(profile);
+        state.invalidate();
     }
     fn layout_text(&self, entity: &Text) -> Layout {
         let state = self.state.read().unwrap();
@@ -532,56 +1394,201 @@ impl CairoFrame {
         let layout = pangocairo::functions::create_layout(&context).unwrap();
         layout.set_text(&entity.content);
         let mut font_options = FontOptions::new();
-        font_options.set_antialias(Antialias::Gray);
-        font_options.set_hint_style(HintStyle::Full);
-        font_options.set_subpixel_order(SubpixelOrder::Rgb);
+        match entity.antialias {
+            _ if !Quality::current().antialias_glyphs() => {
+                font_options.set_antialias(Antialias::None)
+            }
+            TextAntialias::None => font_options.set_antialias(Antialias::None),
+            TextAntialias::Grayscale => font_options.set_antialias(Antialias::Gray),
+            TextAntialias::Subpixel(order) => {
+                font_options.set_antialias(Antialias::Subpixel);
+                font_options.set_subpixel_order(match order {
+                    TextSubpixelOrder::Rgb => SubpixelOrder::Rgb,
+                    TextSubpixelOrder::Bgr => SubpixelOrder::Bgr,
+                    TextSubpixelOrder::VerticalRgb => SubpixelOrder::Vrgb,
+                    TextSubpixelOrder::VerticalBgr => SubpixelOrder::Vbgr,
+                });
+            }
+        }
+        font_options.set_hint_style(match entity.hinting {
+            Hinting::None => HintStyle::None,
+            Hinting::Slight => HintStyle::Slight,
+            Hinting::Medium => HintStyle::Medium,
+            Hinting::Full => HintStyle::Full,
+        });
         context.set_font_options(&font_options);
-        context.set_antialias(Antialias::Best);
         let mut font = FontDescription::new();
         font.set_absolute_size(f64::from(pixels_to_pango_pixels(entity.size)));
-        font.set_family(SYSTEM_FONT);
-        font.set_weight(match entity.weight {
-            Weight::Bold => pango::Weight::Bold,
-            Weight::Hairline => pango::Weight::Ultralight,
-            Weight::Normal => pango::Weight::Normal,
-            Weight::Heavy => pango::Weight::Heavy,
-            Weight::Thin => pango::Weight::Semilight,
-            Weight::Light => pango::Weight::Light,
-            Weight::Medium => pango::Weight::Medium,
-            Weight::ExtraBold => pango::Weight::Ultrabold,
-            Weight::SemiBold => pango::Weight::Semibold,
-        });
+        font.set_family(&font_family(entity, &state.fallback));
+        font.set_weight(pango_weight(entity.weight));
         layout.set_font_description(&font);
-        if entity.max_width.is_some() {
-            layout.set_width(pixels_to_pango_pixels(entity.max_width.unwrap()));
+        match (entity.wrap, entity.max_width) {
+            // Pango wraps any layout given a width, so clipped text is laid out unbounded and cut
+            // off as it's drawn.
+            (Wrap::Clip, _) | (_, None) => {}
+            (_, Some(max_width)) => layout.set_width(pixels_to_pango_pixels(max_width)),
+        }
+        match entity.wrap {
+            Wrap::Normal | Wrap::Unicode | Wrap::Char | Wrap::WordChar => {
+                layout.set_wrap(match entity.wrap {
+                    Wrap::Char => pango::WrapMode::Char,
+                    Wrap::WordChar => pango::WrapMode::WordChar,
+                    _ => pango::WrapMode::Word,
+                });
+                if let Some(max_lines) = entity.max_lines {
+                    layout.set_height(-(max_lines.max(1) as i32));
+                    layout.set_ellipsize(pango::EllipsizeMode::End);
+                }
+            }
+            Wrap::Truncate(ellipsize) => layout.set_ellipsize(match ellipsize {
+                Ellipsize::Start => pango::EllipsizeMode::Start,
+                Ellipsize::Middle => pango::EllipsizeMode::Middle,
+                Ellipsize::End => pango::EllipsizeMode::End,
+            }),
+            Wrap::None | Wrap::Clip => {}
         }
-        if let Wrap::Normal = entity.wrap {
-            layout.set_wrap(pango::WrapMode::Word);
+        if !entity.tab_stops.is_empty() {
+            let mut tabs = pango::TabArray::new(entity.tab_stops.len() as i32, false);
+            for (index, stop) in entity.tab_stops.iter().enumerate() {
+                tabs.set_tab(
+                    index as i32,
+                    pango::TabAlign::Left,
+                    pixels_to_pango_pixels(*stop),
+                );
+            }
+            layout.set_tabs(Some(&mut tabs));
         }
-        layout.set_spacing(pixels_to_pango_pixels(entity.line_height - entity.size));
         let attribute_list = pango::AttrList::new();
         attribute_list.insert(
             pango::Attribute::new_letter_spacing(pixels_to_pango_points(entity.letter_spacing))
                 .unwrap(),
         );
+        if entity.word_spacing != 0. {
+            // Pango has no word spacing, so word separators are given wider letter spacing, which
+            // it splits evenly on either side of them.
+            for (index, separator) in entity
+                .content
+                .match_indices(|character| character == ' ' || character == '\u{a0}')
+            {
+                let mut attribute = pango::Attribute::new_letter_spacing(pixels_to_pango_points(
+                    entity.letter_spacing + entity.word_spacing,
+                ))
+                .unwrap();
+                attribute.set_start_index(index as u32);
+                attribute.set_end_index((index + separator.len()) as u32);
+                attribute_list.insert(attribute);
+            }
+        }
+        if let Some(features) = font_features(entity) {
+            attribute_list.insert(pango::Attribute::new_font_features(&features).unwrap());
+        }
+        if let Some(language) = &entity.hyphenation {
+            // Pango hyphenates words that it breaks by the rules of their language.
+            let language = pango::Language::from_string(language);
+            attribute_list.insert(pango::Attribute::new_language(&language).unwrap());
+        }
+        match entity.underline {
+            Underline::None => {}
+            Underline::Single => attribute_list
+                .insert(pango::Attribute::new_underline(pango::Underline::Single).unwrap()),
+            Underline::Double => attribute_list
+                .insert(pango::Attribute::new_underline(pango::Underline::Double).unwrap()),
+        }
+        if entity.strikethrough {
+            attribute_list.insert(pango::Attribute::new_strikethrough(true).unwrap());
+        }
+        if let Some(color) = entity.decoration_color {
+            let (red, green, blue) = (
+                u16::from(color.r) * 257,
+                u16::from(color.g) * 257,
+                u16::from(color.b) * 257,
+            );
+            attribute_list.insert(pango::Attribute::new_underline_color(red, green, blue).unwrap());
+            attribute_list
+                .insert(pango::Attribute::new_strikethrough_color(red, green, blue).unwrap());
+        }
+        for span in &entity.spans {
+            let mut attributes = vec![];
+            if let Some(weight) = span.weight {
+                attributes.push(pango::Attribute::new_weight(pango_weight(weight)));
+            }
+            if span.italic {
+                attributes.push(pango::Attribute::new_style(pango::Style::Italic));
+            }
+            if let Some(color) = span.color {
+                attributes.push(pango::Attribute::new_foreground(
+                    u16::from(color.r) * 257,
+                    u16::from(color.g) * 257,
+                    u16::from(color.b) * 257,
+                ));
+            }
+            match span.underline {
+                None => {}
+                Some(Underline::None) => {
+                    attributes.push(pango::Attribute::new_underline(pango::Underline::None))
+                }
+                Some(Underline::Single) => {
+                    attributes.push(pango::Attribute::new_underline(pango::Underline::Single))
+                }
+                Some(Underline::Double) => {
+                    attributes.push(pango::Attribute::new_underline(pango::Underline::Double))
+                }
+            }
+            if span.strikethrough {
+                attributes.push(pango::Attribute::new_strikethrough(true));
+            }
+            for mut attribute in attributes.into_iter().flatten() {
+                attribute.set_start_index(span.range.start as u32);
+                attribute.set_end_index(span.range.end as u32);
+                attribute_list.insert(attribute);
+            }
+        }
         layout.set_attributes(&attribute_list);
-        context.set_source_rgba(
-            f64::from(entity.color.r) / 255.,
-            f64::from(entity.color.g) / 255.,
-            f64::from(entity.color.b) / 255.,
-            f64::from(entity.color.a) / 255.,
-        );
         pangocairo::functions::update_layout(&context, &layout);
         layout
     }
-    fn measure_text(&self, entity: &Text) -> Vector2 {
-        let layout = self.layout_text(entity);
-        let size = layout.get_pixel_size();
-        (f64::from(size.0), f64::from(size.1)).into()
+    fn text_layout(&self, entity: &Text) -> CairoTextLayout {
+        CairoTextLayout {
+            layout: Arc::new(Mutex::new(CairoLayout(self.layout_text(entity)))),
+            text: entity.clone(),
+        }
+    }
+    /// Returns the vertical offset of a layout of the text scaled by the provided factor from the
+    /// origin of the text.
+    fn origin_offset(entity: &Text, layout: &Layout, scale: f64) -> f64 {
+        match entity.origin {
+            Origin::Baseline => f64::from(-layout.get_baseline() / pango::SCALE),
+            Origin::Middle => {
+                let height = placed_height(&place_lines(entity, layout, scale));
+                -(f64::from(height) / f64::from(pango::SCALE) / 2.)
+            }
+            Origin::Top => {
+                let ascent = f64::from(layout.get_baseline()) / f64::from(pango::SCALE);
+                entity.first_line_shift(ascent / scale) * scale
+            }
+        }
     }
-    fn draw_text(&self, matrix: [f64; 6], entity: &Text) {
+    fn draw_text(
+        &self,
+        matrix: [f64; 6],
+        entity: &Text,
+        cache: &Mutex<Option<(f64, CairoLayout)>>,
+    ) {
+        let breakpoint = zoom_breakpoint(matrix);
         {
             let state = self.state.read().unwrap();
+            // Pango wraps with its own rules, which only approximate Unicode line breaking and
+            // don't hyphenate before Pango 1.44.
+            if entity.max_width.is_some() {
+                let hyphenated =
+                    matches!(entity.wrap, Wrap::Normal | Wrap::Unicode | Wrap::WordChar);
+                if hyphenated && entity.hyphenation.is_some() && !pango_hyphenates() {
+                    state.warn(RenderWarning::Hyphenation);
+                }
+                if matches!(entity.wrap, Wrap::Unicode) {
+                    state.warn(RenderWarning::LineBreaking);
+                }
+            }
             let context = state.context.lock().unwrap();
             context.restore();
             context.save();
@@ -593,22 +1600,46 @@ impl CairoFrame {
                 x0: matrix[4],
                 y0: matrix[5],
             });
+            context.scale(1. / breakpoint, 1. / breakpoint);
         }
-        let layout = self.layout_text(&entity);
+        let mut cache = cache.lock().unwrap();
+        let layout = match &*cache {
+            Some((cached, layout)) if *cached == breakpoint => layout.0.clone(),
+            _ => {
+                let layout = self.layout_text(&entity.scaled(breakpoint));
+                *cache = Some((breakpoint, CairoLayout(layout.clone())));
+                layout
+            }
+        };
         let state = self.state.read().unwrap();
         let context = state.context.lock().unwrap();
-        match entity.origin {
-            Origin::Baseline => {
-                let baseline = layout.get_baseline();
-                context.translate(0., f64::from(-baseline / pango::SCALE));
-            }
-            Origin::Middle => {
-                let size = layout.get_pixel_size();
-                context.translate(0., -(f64::from(size.1) / 2.));
-            }
-            _ => {}
-        }
-        pangocairo::functions::show_layout(&context, &layout);
+        context.set_source_rgba(
+            f64::from(entity.color.r) / 255.,
+            f64::from(entity.color.g) / 255.,
+            f64::from(entity.color.b) / 255.,
+            f64::from(entity.color.a) / 255.,
+        );
+        context.translate(0., CairoFrame::origin_offset(entity, &layout, breakpoint));
+        let lines = place_lines(entity, &layout, breakpoint);
+        let scale = f64::from(pango::SCALE);
+        if let (Wrap::Clip, Some(max_width)) = (entity.wrap, entity.max_width) {
+            let height = f64::from(placed_height(&lines)) / scale;
+            context.rectangle(0., 0., max_width * breakpoint, height);
+            context.clip();
+        }
+        // Lines are drawn one at a time, each at the baseline it's placed on.
+        if let Some(mut iter) = layout.get_iter() {
+            for line in &lines {
+                if let Some(layout_line) = iter.get_line_readonly() {
+                    context.move_to(
+                        f64::from(line.logical.x) / scale,
+                        f64::from(iter.get_baseline() + line.shift) / scale,
+                    );
+                    pangocairo::functions::show_layout_line(&context, &layout_line);
+                }
+                iter.next_line();
+            }
+        }
     }
 
     fn draw_path(&self, matrix: [f64; 6], entity: &Path) {
@@ -628,7 +1659,191 @@ impl CairoFrame {
         }
         let context = state.context.lock().unwrap();
         if entity.shadows.is_empty() && entity.clip_segments.is_empty() {
-            draw_path(&context, entity, state.pixel_ratio);
+            draw_path(&context, entity, None, state.pixel_ratio, &state.patterns);
+        }
+    }
+
+    fn push_clip(&self, matrix: [f64; 6], clip: &Path) {
+        let state = self.state.read().unwrap();
+        let context = state.context.lock().unwrap();
+        context.restore();
+        context.save();
+        context.save();
+        context.transform(to_cairo_matrix(matrix));
+        context.new_path();
+        context.move_to(0., 0.);
+        clip.segments.iter().for_each(|segment| match segment {
+            Segment::LineTo(point) => {
+                context.line_to(point.x, point.y);
+            }
+            Segment::MoveTo(point) => {
+                context.move_to(point.x, point.y);
+            }
+            Segment::CubicTo(point, handle_1, handle_2) => {
+                context.curve_to(
+                    handle_1.x, handle_1.y, handle_2.x, handle_2.y, point.x, point.y,
+                );
+            }
+            Segment::QuadraticTo(point, handle) => {
+                context.curve_to(handle.x, handle.y, handle.x, handle.y, point.x, point.y);
+            }
+            Segment::ClosePath => {
+                context.close_path();
+            }
+        });
+        // The path is kept in device space, so restoring the transformation doesn't affect it.
+        context.restore();
+        context.clip();
+        context.save();
+    }
+
+    fn pop_clip(&self) {
+        let state = self.state.read().unwrap();
+        let context = state.context.lock().unwrap();
+        context.restore();
+        context.restore();
+        context.save();
+    }
+
+    fn draw_object(&self, parent: [f64; 6], object: &CairoObject, pixel_ratio: f64) {
+        let state = self.state.read().unwrap();
+        let object_state = object.state.read().unwrap();
+        let orientation = match (state.interpolation, object_state.previous) {
+            (Some(amount), Some(previous)) => {
+                previous.interpolate(object_state.orientation, amount)
+            }
+            _ => object_state.orientation,
+        };
+        let matrix = compose_matrices(parent, orientation.to_matrix());
+        let operator = match object_state.blend {
+            Blend::Normal => None,
+            Blend::Erase => Some(self.set_operator(Operator::DestOut)),
+        };
+        if let Some(clip) = &object_state.clip {
+            self.push_clip(matrix, clip);
+        }
+        object.redraw(pixel_ratio, &state.patterns);
+        (*object.cache_surface.lock().unwrap())
+            .iter()
+            .for_each(|surface| {
+                let context = state.context.lock().unwrap();
+                context.restore();
+                context.save();
+                context.transform(Matrix {
+                    xx: matrix[0],
+                    yx: matrix[2],
+                    xy: matrix[1],
+                    yy: matrix[3],
+                    x0: matrix[4],
+                    y0: matrix[5],
+                });
+                context.scale(1. / state.pixel_ratio, 1. / state.pixel_ratio);
+                context.set_source_surface(&surface.0.get_target(), surface.1.x, surface.1.y);
+                context.paint();
+            });
+        match &object_state.content {
+            Rasterizable::Path(path) => self.draw_path(matrix, &path),
+            Rasterizable::Text(input) => self.draw_text(matrix, &input, &object.text_layout),
+            Rasterizable::Extension(extension) => {
+                self.draw_extension(matrix, extension.as_ref(), &object.expanded)
+            }
+        };
+        if object_state.clip.is_some() {
+            self.pop_clip();
+        }
+        if let Some(operator) = operator {
+            self.set_operator(operator);
+        }
+    }
+
+    /// Sets the operator of the saved state that each drawing operation restores, so that it
+    /// applies to everything drawn until it is set again, and returns the previous operator.
+    fn set_operator(&self, operator: Operator) -> Operator {
+        let state = self.state.read().unwrap();
+        let context = state.context.lock().unwrap();
+        context.restore();
+        let previous = context.get_operator();
+        context.set_operator(operator);
+        context.save();
+        previous
+    }
+
+    fn draw_extension(
+        &self,
+        matrix: [f64; 6],
+        extension: &dyn Extension,
+        expanded: &Mutex<Vec<CairoObject>>,
+    ) {
+        let (color_profile, pixel_ratio) = {
+            let state = self.state.read().unwrap();
+            (state.color_profile.clone(), state.pixel_ratio)
+        };
+        if let Some(instances) = extension.as_instances() {
+            let path = instances.path();
+            // Shadows and clips are rasterized into the cache of an object, so paths with them are
+            // drawn from the objects of their expansion instead.
+            if path.shadows.is_empty() && path.clip_segments.is_empty() {
+                let state = self.state.read().unwrap();
+                let context = state.context.lock().unwrap();
+                for (transform, variation) in instances.instances() {
+                    let paint = InstancePaint {
+                        variation,
+                        color_profile: color_profile.as_ref(),
+                    };
+                    context.restore();
+                    context.save();
+                    context.transform(to_cairo_matrix(compose_matrices(
+                        matrix,
+                        transform.to_matrix(),
+                    )));
+                    draw_path(&context, path, Some(&paint), pixel_ratio, &state.patterns);
+                }
+                return;
+            }
+        }
+        match extension.expand() {
+            Some(mut contents) => {
+                contents.sort_by_key(|content| content.depth);
+                // The objects of the previous expansion are reused, along with what they cached.
+                let mut expanded = expanded.lock().unwrap();
+                expanded.truncate(contents.len());
+                for (index, content) in contents.into_iter().enumerate() {
+                    match expanded.get(index) {
+                        Some(object) => object.replace(content),
+                        None => expanded.push(CairoObject::new(
+                            content.content,
+                            content.transform,
+                            content.depth,
+                            content.clip,
+                            color_profile.clone(),
+                        )),
+                    }
+                }
+                expanded
+                    .iter()
+                    .for_each(|object| self.draw_object(matrix, object, pixel_ratio));
+            }
+            None => {
+                let texture = extension.rasterize_scaled(pixel_ratio);
+                let (width, height) = (texture.format.width, texture.format.height);
+                if width == 0 || height == 0 {
+                    return;
+                }
+                let texture = match &color_profile {
+                    Some(color_profile) => color_profile.transform_image(texture),
+                    None => texture,
+                };
+                let image = CairoImage::from_texture(texture);
+                let size = extension.measure();
+                let state = self.state.read().unwrap();
+                let context = state.context.lock().unwrap();
+                context.restore();
+                context.save();
+                context.transform(to_cairo_matrix(matrix));
+                context.scale(size.x / f64::from(width), size.y / f64::from(height));
+                context.set_source_surface(&image.0.lock().unwrap().0, 0., 0.);
+                context.paint();
+            }
         }
     }
 }
@@ -644,57 +1859,289 @@ impl Clone for CairoFrame {
 impl Frame for CairoFrame {
     fn set_pixel_ratio(&self, ratio: f64) {
         let mut state = self.state.write().unwrap();
-        state.pixel_ratio = ratio;
+        if state.pixel_ratio != ratio {
+            state.pixel_ratio = ratio;
+            CairoFrame::allocate(&mut state);
+        }
     }
 
     fn as_any(&self) -> Box<dyn Any> {
         Box::new(self.clone())
     }
 
+    fn cursor_at(&self, position: Vector2) -> Option<CursorIcon> {
+        self.hits(position).next().map(|object| object.get_cursor())
+    }
+
+    fn children(&self) -> Vec<Box<dyn Object>> {
+        let state = self.state.read().unwrap();
+        let mut objects: Vec<Box<dyn Object>> = state
+            .contents
+            .iter()
+            .map(|object| Box::new(object.clone()) as Box<dyn Object>)
+            .collect();
+        objects.extend(state.layers.iter().flat_map(|layer| layer.children()));
+        objects
+    }
+
+    fn objects_at(&self, position: Vector2) -> Vec<Box<dyn Object>> {
+        self.hits(position).collect()
+    }
+
     fn add(&mut self, content: Content) -> Box<dyn Object> {
         let object = CairoObject::new(
             content.content,
             content.transform,
             content.depth,
+            content.clip,
             self.state.read().unwrap().color_profile.clone(),
         );
+        // Retained layouts are drawn as they were laid out until the object is zoomed or updated.
+        // Pango layouts aren't thread safe, so the object draws a copy rather than sharing the
+        // layout that is still queried through the retained one.
+        if let Some(Ok(layout)) = content
+            .layout
+            .map(|layout| layout.as_any().downcast::<CairoTextLayout>())
+        {
+            if let Some(layout) = layout.layout.lock().unwrap().0.copy() {
+                *object.text_layout.lock().unwrap() = Some((1., CairoLayout(layout)));
+            }
+        }
+        object.state.write().unwrap().tag = content.tag;
         let mut state = self.state.write().unwrap();
         state.contents.push(object.clone());
         Box::new(object)
     }
 
+    fn remove(&mut self, object: &dyn Object) -> bool {
+        let mut state = self.state.write().unwrap();
+        if let Ok(object) = object.as_any().downcast::<CairoObject>() {
+            if let Some(index) = state
+                .contents
+                .iter()
+                .position(|other| Arc::ptr_eq(&other.state, &object.state))
+            {
+                let removed = state.contents.remove(index);
+                let drawn = removed.state.read().unwrap().drawn;
+                state.damage.lock().unwrap().regions.extend(drawn);
+                return true;
+            }
+        }
+        state.layers.iter_mut().any(|layer| layer.remove(object))
+    }
+
+    fn clear(&mut self) {
+        let mut state = self.state.write().unwrap();
+        state.contents.clear();
+        state.layers.iter_mut().for_each(Layer::clear);
+        state.invalidate();
+    }
+
+    fn add_layer(&mut self) -> Box<dyn Layer> {
+        let frame = CairoFrame::new();
+        {
+            let mut layer_state = frame.state.write().unwrap();
+            layer_state.background = LDRColor::rgba(0, 0, 0, 0);
+            let state = self.state.read().unwrap();
+            layer_state.color_profile = state.color_profile.clone();
+            layer_state.fallback = state.fallback.clone();
+        }
+        let layer = FrameLayer::new(frame);
+        self.state.write().unwrap().layers.push(layer.clone());
+        Box::new(layer)
+    }
+
+    fn layers(&self) -> Vec<Box<dyn Layer>> {
+        let state = self.state.read().unwrap();
+        state
+            .layers
+            .iter()
+            .map(|layer| Box::new(layer.clone()) as Box<dyn Layer>)
+            .collect()
+    }
+
     fn set_viewport(&self, viewport: Rect) {
         let mut state = self.state.write().unwrap();
         state.viewport = viewport;
+        state.invalidate();
+    }
+
+    fn get_background(&self) -> LDRColor {
+        self.state.read().unwrap().background
+    }
+
+    fn set_background(&self, color: LDRColor) {
+        let mut state = self.state.write().unwrap();
+        state.background = color;
+        state.invalidate();
+    }
+
+    fn get_clip(&self) -> Option<Path> {
+        self.state.read().unwrap().clip.clone()
+    }
+
+    fn set_clip(&self, clip: Option<Path>) {
+        let mut state = self.state.write().unwrap();
+        state.clip = clip;
+        state.invalidate();
     }
 
     fn resize(&self, size: Vector2) {
         let mut state = self.state.write().unwrap();
         state.size = size;
-        let surface = ImageSurface::create(Format::ARgb32, size.x as i32, size.y as i32).unwrap();
-        state.context = Mutex::new(CairoContext(cairo::Context::new(&surface)));
+        CairoFrame::allocate(&mut state);
     }
 
     fn get_size(&self) -> Vector2 {
         let state = self.state.read().unwrap();
-        state.size / state.pixel_ratio
+        state.size
     }
 
     fn to_image(&self) -> Box<dyn ImageRepresentation> {
         self.surface()
     }
 
+    fn to_image_scaled(&self, pixel_ratio: f64) -> Box<dyn ImageRepresentation> {
+        // The image is drawn by a separate frame sharing the contents and layers of this one, so
+        // that drawing this frame meanwhile can't paint into the image.
+        let offscreen = {
+            let state = self.state.read().unwrap();
+            let mut offscreen = CairoFrameState {
+                context: Mutex::new(CairoContext(cairo::Context::new(
+                    &ImageSurface::create(Format::ARgb32, 0, 0).unwrap(),
+                ))),
+                contents: state.contents.clone(),
+                layers: state.layers.clone(),
+                background: state.background,
+                viewport: state.viewport,
+                clip: state.clip.clone(),
+                color_profile: state.color_profile.clone(),
+                size: state.size,
+                pixel_ratio: image_pixel_ratio(state.size, pixel_ratio, state.pixel_ratio),
+                interpolation: state.interpolation,
+                raster_options: state.raster_options,
+                damage: Mutex::new(Damage {
+                    full: true,
+                    regions: vec![],
+                    settings: graphics::settings_generation(),
+                }),
+                painted: Mutex::new(false),
+                warnings: Mutex::new(vec![]),
+                patterns: Patterns::default(),
+                fallback: state.fallback.clone(),
+            };
+            CairoFrame::allocate(&mut offscreen);
+            CairoFrame {
+                state: Arc::new(RwLock::new(offscreen)),
+            }
+        };
+        let image = offscreen.surface();
+        // The surface of the frame was left as it was, but objects drawn into the image no longer
+        // know that they've changed since it was last drawn.
+        self.state.read().unwrap().invalidate();
+        image
+    }
+
     fn measure(&self, input: Rasterizable) -> Vector2 {
         match input {
-            Rasterizable::Text(input) => {
-                let mut size = self.measure_text(input.deref());
-                if input.origin == Origin::Middle {
-                    size.y = 0.;
-                }
-                size
-            }
+            Rasterizable::Text(input) => self.text_layout(&input).measure(),
             Rasterizable::Path(input) => input.bounds().size,
+            Rasterizable::Extension(input) => input.measure(),
+        }
+    }
+
+    fn advance(&self) {
+        let state = self.state.read().unwrap();
+        state.contents.iter().for_each(|object| {
+            let mut object = object.state.write().unwrap();
+            object.previous = Some(object.orientation);
+        });
+        state.layers.iter().for_each(FrameLayer::advance);
+    }
+
+    fn set_interpolation(&self, amount: Option<f64>) {
+        let mut state = self.state.write().unwrap();
+        // Interpolated frames are drawn across the whole viewport regardless, so only the frame
+        // that stops interpolating needs damaging, and layers are marked dirty by their own.
+        if state.interpolation.is_some() && amount.is_none() {
+            let viewport = state.viewport;
+            state.damage.lock().unwrap().regions.push(viewport);
+        }
+        state.interpolation = amount;
+        state
+            .layers
+            .iter()
+            .for_each(|layer| layer.set_interpolation(amount));
+    }
+
+    fn set_raster_options(&self, options: RasterOptions) {
+        let mut state = self.state.write().unwrap();
+        state.raster_options = options;
+        state.invalidate();
+    }
+
+    fn set_fallback_fonts(&self, families: Vec<String>) {
+        let mut state = self.state.write().unwrap();
+        state.fallback = families.clone();
+        // Text is laid out again with the new families.
+        for object in &state.contents {
+            *object.text_layout.lock().unwrap() = None;
+            *object.text_rects.lock().unwrap() = None;
         }
+        state
+            .layers
+            .iter()
+            .for_each(|layer| layer.set_fallback_fonts(families.clone()));
+        state.invalidate();
+    }
+
+    fn warnings(&self) -> Vec<RenderWarning> {
+        self.state.read().unwrap().warnings.lock().unwrap().clone()
+    }
+
+    fn find_text(&self, query: &str, options: search::Options) -> Vec<Match> {
+        let contents = self.state.read().unwrap().contents.clone();
+        contents
+            .into_iter()
+            .flat_map(|object| {
+                let text = match &object.state.read().unwrap().content {
+                    Rasterizable::Text(text) => text.clone(),
+                    _ => return vec![],
+                };
+                search::find(&text.content, query, options)
+                    .into_iter()
+                    .map(|range| {
+                        let bytes = grapheme::to_byte_index(&text.content, range.start)
+                            ..grapheme::to_byte_index(&text.content, range.end);
+                        Match {
+                            object: Box::new(object.clone()),
+                            rects: self.text_layout(&text).rects(bytes),
+                            range,
+                        }
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn caret(&self, text: &Text, index: usize) -> Rect {
+        self.text_layout(text).caret(index)
+    }
+
+    fn hit_test_text(&self, text: &Text, point: Vector2) -> usize {
+        self.text_layout(text).hit_test(point)
+    }
+
+    fn selection_rects(&self, text: &Text, range: Range<usize>) -> Vec<Rect> {
+        self.text_layout(text).selection_rects(range)
+    }
+
+    fn glyphs(&self, text: &Text) -> Vec<Glyph> {
+        self.text_layout(text).glyphs()
+    }
+
+    fn layout_text(&self, text: Text) -> Box<dyn TextLayout> {
+        Box::new(self.text_layout(&text))
     }
 
     fn box_clone(&self) -> Box<dyn Frame> {
@@ -707,63 +2154,311 @@ impl Frame for CairoFrame {
 
     fn draw(&self) {
         let state = self.state.read().unwrap();
+        let regions = self.take_damage(&state);
+        *state.painted.lock().unwrap() = !regions.is_empty();
+        if regions.is_empty() {
+            return;
+        }
         {
             let context = state.context.lock().unwrap();
-            context.set_source_rgb(1., 1., 1.);
+            let quality = Quality::current();
+            context.set_tolerance(state.raster_options.flattening_tolerance());
+            context.set_antialias(match quality {
+                Quality::Low => Antialias::Fast,
+                Quality::Medium => Antialias::Good,
+                Quality::High => Antialias::Best,
+            });
+            let background = state.background;
+            context.set_source_rgba(
+                f64::from(background.r) / 255.,
+                f64::from(background.g) / 255.,
+                f64::from(background.b) / 255.,
+                f64::from(background.a) / 255.,
+            );
+            context.set_operator(Operator::Source);
             let viewport = state.viewport;
-            let size = state.size;
+            let (width, height) = CairoFrame::pixels(&state);
+            let scale = Vector2::from((f64::from(width), f64::from(height))) / viewport.size;
             context.set_matrix(Matrix {
-                xx: (size.x / viewport.size.x) * state.pixel_ratio,
+                xx: scale.x,
                 yx: 0.,
                 xy: 0.,
-                yy: -(size.y / viewport.size.y) * state.pixel_ratio,
-                x0: -viewport.position.x * state.pixel_ratio,
-                y0: -viewport.position.y * state.pixel_ratio + viewport.size.y,
+                yy: -scale.y,
+                x0: -viewport.position.x * scale.x,
+                y0: viewport.position.y * scale.y + f64::from(height),
             });
-            context.rectangle(
-                viewport.position.x,
-                viewport.position.y,
-                viewport.size.x,
-                viewport.size.y,
-            );
-            context.fill();
+            // Only the damaged regions are cleared and drawn again. The rest of the surface keeps
+            // what was drawn before.
+            context.save();
+            context.new_path();
+            for region in &regions {
+                let region = align_region(&context, *region);
+                context.rectangle(
+                    region.position.x,
+                    region.position.y,
+                    region.size.x,
+                    region.size.y,
+                );
+            }
+            context.fill_preserve();
+            context.clip();
+            context.set_operator(Operator::Over);
             context.save();
         }
-        state.contents.iter().for_each(|object| {
-            let object_state = object.state.read().unwrap();
-            let matrix = object_state.orientation.to_matrix();
-            object.redraw(state.pixel_ratio);
-            (*object.cache_surface.lock().unwrap())
-                .iter()
-                .for_each(|surface| {
-                    let context = state.context.lock().unwrap();
-                    context.restore();
-                    context.save();
-                    context.transform(Matrix {
-                        xx: matrix[0],
-                        yx: matrix[2],
-                        xy: matrix[1],
-                        yy: matrix[3],
-                        x0: matrix[4],
-                        y0: matrix[5],
-                    });
-                    context.scale(1. / state.pixel_ratio, 1. / state.pixel_ratio);
-                    context.set_source_surface(&surface.0.get_target(), surface.1.x, surface.1.y);
-                    context.paint();
-                });
-            match &object_state.content {
-                Rasterizable::Path(path) => self.draw_path(matrix, &path),
-                Rasterizable::Text(input) => self.draw_text(matrix, &input),
+        let root = Transform2::default().to_matrix();
+        // The clip is kept beneath the state that objects and layers restore to, and removed once
+        // drawing finishes so that the next background is filled everywhere.
+        if let Some(clip) = &state.clip {
+            self.push_clip(root, clip);
+        }
+        state
+            .contents
+            .iter()
+            .filter(|object| {
+                let object = object.state.read().unwrap();
+                // Objects outside of the viewport aren't rasterized, and those outside of the
+                // damaged regions are left as they were drawn.
+                object.visible
+                    && match object.drawn {
+                        Some(drawn) => {
+                            drawn.intersects(state.viewport)
+                                && regions.iter().any(|region| region.intersects(drawn))
+                        }
+                        None => true,
+                    }
+            })
+            .for_each(|object| self.draw_object(root, object, state.pixel_ratio));
+        for layer in &state.layers {
+            let (frame, opacity, blend) =
+                match layer.render(state.size, state.viewport, state.pixel_ratio) {
+                    Some(rendered) => rendered,
+                    None => continue,
+                };
+            let frame = frame.as_any().downcast::<CairoFrame>().unwrap();
+            let target = {
+                let layer_state = frame.state.read().unwrap();
+                let context = layer_state.context.lock().unwrap();
+                context.get_target()
             };
-        });
+            let context = state.context.lock().unwrap();
+            context.restore();
+            context.save();
+            context.identity_matrix();
+            context.set_operator(match blend {
+                Blend::Normal => Operator::Over,
+                Blend::Erase => Operator::DestOut,
+            });
+            context.set_source_surface(&target, 0., 0.);
+            context.paint_with_alpha(opacity);
+        }
+        if state.clip.is_some() {
+            self.pop_clip();
+        }
+        let context = state.context.lock().unwrap();
+        context.restore();
+        context.restore();
     }
 }
 
 struct CairoObjectState {
     orientation: Transform2,
+    previous: Option<Transform2>,
     content: Rasterizable,
     depth: u32,
+    clip: Option<Path>,
+    blend: Blend,
+    cursor: CursorIcon,
+    visible: bool,
+    tag: Option<String>,
     redraw: Mutex<bool>,
+    /// Whether the object has changed since its frame was last drawn.
+    changed: bool,
+    /// The bounds the object was last drawn within, in the space of its frame's contents.
+    drawn: Option<Rect>,
+}
+
+/// Some [Text] laid out by a [CairoFrame], with its queries answered from the retained layout.
+#[derive(Clone)]
+struct CairoTextLayout {
+    text: Text,
+    layout: Arc<Mutex<CairoLayout>>,
+}
+
+impl CairoTextLayout {
+    /// Returns the rects covering a range of bytes of the content, one for each line.
+    fn rects(&self, range: Range<usize>) -> Vec<Rect> {
+        let layout = self.layout.lock().unwrap();
+        let layout = &layout.0;
+        let offset = CairoFrame::origin_offset(&self.text, layout, 1.);
+        let lines = place_lines(&self.text, layout, 1.);
+        let scale = f64::from(pango::SCALE);
+        let mut rects: Vec<Rect> = vec![];
+        for index in grapheme::boundaries(&self.text.content)
+            .into_iter()
+            .filter(|index| range.start <= *index && *index < range.end)
+        {
+            let position = layout.index_to_pos(index as i32);
+            let y = position.y + shift_at(layout, &lines, index as i32);
+            let (x, width) = if position.width < 0 {
+                (position.x + position.width, -position.width)
+            } else {
+                (position.x, position.width)
+            };
+            let rect = Rect::new(
+                (f64::from(x) / scale, f64::from(y) / scale + offset),
+                (f64::from(width) / scale, f64::from(position.height) / scale),
+            );
+            match rects.last_mut() {
+                Some(line) if line.position.y == rect.position.y => {
+                    let end = (line.position.x + line.size.x).max(rect.position.x + rect.size.x);
+                    line.position.x = line.position.x.min(rect.position.x);
+                    line.size.x = end - line.position.x;
+                }
+                _ => rects.push(rect),
+            }
+        }
+        rects
+    }
+}
+
+impl TextLayout for CairoTextLayout {
+    fn text(&self) -> Text {
+        self.text.clone()
+    }
+
+    fn measure(&self) -> Vector2 {
+        let layout = self.layout.lock().unwrap();
+        let layout = &layout.0;
+        let size = layout.get_pixel_size();
+        let scale = f64::from(pango::SCALE);
+        let ascent = f64::from(layout.get_baseline()) / scale;
+        let height = match self.text.origin {
+            Origin::Middle => 0.,
+            _ => {
+                let height = placed_height(&place_lines(&self.text, layout, 1.));
+                f64::from(height) / scale + self.text.first_line_shift(ascent)
+            }
+        };
+        (f64::from(size.0), height).into()
+    }
+
+    fn caret(&self, index: usize) -> Rect {
+        let text = &self.text;
+        let layout = self.layout.lock().unwrap();
+        let layout = &layout.0;
+        let offset = CairoFrame::origin_offset(text, layout, 1.);
+        let scale = f64::from(pango::SCALE);
+        let index = grapheme::to_byte_index(&text.content, index) as i32;
+        let position = layout.index_to_pos(index);
+        let y = position.y + shift_at(layout, &place_lines(text, layout, 1.), index);
+        Rect::new(
+            (f64::from(position.x) / scale, f64::from(y) / scale + offset),
+            (0., f64::from(position.height) / scale),
+        )
+    }
+
+    fn hit_test(&self, point: Vector2) -> usize {
+        let text = &self.text;
+        let layout = self.layout.lock().unwrap();
+        let layout = &layout.0;
+        let offset = CairoFrame::origin_offset(text, layout, 1.);
+        let y = pixels_to_pango_pixels(point.y - offset);
+        // The point is moved onto the line it's over as Pango lays the line out.
+        let lines = place_lines(text, layout, 1.);
+        let shift = lines
+            .iter()
+            .take_while(|line| line.span().0 <= y)
+            .last()
+            .or_else(|| lines.first())
+            .map_or(0, |line| line.shift);
+        let (_, index, trailing) = layout.xy_to_index(pixels_to_pango_pixels(point.x), y - shift);
+        // Pango reports the grapheme cluster under the point, with a nonzero trailing value if
+        // the point is nearer its end than its start.
+        let index = grapheme::from_byte_index(&text.content, index.max(0) as usize);
+        if trailing > 0 {
+            index + 1
+        } else {
+            index
+        }
+    }
+
+    fn selection_rects(&self, range: Range<usize>) -> Vec<Rect> {
+        self.rects(
+            grapheme::to_byte_index(&self.text.content, range.start)
+                ..grapheme::to_byte_index(&self.text.content, range.end),
+        )
+    }
+
+    fn glyphs(&self) -> Vec<Glyph> {
+        let text = &self.text;
+        let layout = self.layout.lock().unwrap();
+        let layout = &layout.0;
+        let offset = CairoFrame::origin_offset(text, layout, 1.);
+        let lines = place_lines(text, layout, 1.);
+        let scale = f64::from(pango::SCALE);
+        let content = &text.content;
+        let mut glyphs = vec![];
+        let mut iter = match layout.get_iter() {
+            Some(iter) => iter,
+            None => return glyphs,
+        };
+        loop {
+            if let Some(run) = iter.get_run_readonly() {
+                let shift = shift_at(layout, &lines, iter.get_index());
+                let baseline = f64::from(iter.get_baseline() + shift) / scale + offset;
+                let mut x = f64::from(iter.get_run_extents().1.x) / scale;
+                // The bindings don't expose the fields of glyph items, so they're read directly.
+                let run: *const pango_sys::PangoGlyphItem = run.to_glib_none().0;
+                let (item, string) = unsafe { (&*(*run).item, &*(*run).glyphs) };
+                let (start, end) = (item.offset as usize, (item.offset + item.length) as usize);
+                let count = string.num_glyphs as usize;
+                let (infos, clusters) = unsafe {
+                    (
+                        slice::from_raw_parts(string.glyphs, count),
+                        slice::from_raw_parts(string.log_clusters, count),
+                    )
+                };
+                for (info, cluster) in infos.iter().zip(clusters) {
+                    // A cluster ends where the next one in logical order begins, whichever
+                    // direction the run is laid out in.
+                    let from = start + *cluster as usize;
+                    let to = clusters
+                        .iter()
+                        .map(|other| start + *other as usize)
+                        .filter(|other| *other > from)
+                        .min()
+                        .unwrap_or(end);
+                    let first = grapheme::from_byte_index(content, from);
+                    let last = grapheme::from_byte_index(content, to.max(from + 1) - 1);
+                    let advance = f64::from(info.geometry.width) / scale;
+                    glyphs.push(Glyph {
+                        id: Some(info.glyph),
+                        position: (x, baseline).into(),
+                        advance,
+                        offset: (
+                            f64::from(info.geometry.x_offset) / scale,
+                            f64::from(info.geometry.y_offset) / scale,
+                        )
+                            .into(),
+                        cluster: first..last + 1,
+                    });
+                    x += advance;
+                }
+            }
+            if !iter.next_run() {
+                break;
+            }
+        }
+        glyphs
+    }
+
+    fn box_clone(&self) -> Box<dyn TextLayout> {
+        Box::new(self.clone())
+    }
+
+    fn as_any(&self) -> Box<dyn Any> {
+        Box::new(self.clone())
+    }
 }
 
 #[derive(Clone)]
@@ -771,6 +2466,12 @@ struct CairoObject {
     state: Arc<RwLock<CairoObjectState>>,
     color_profile: Option<Profile>,
     cache_surface: Arc<Mutex<Option<(CairoContext, Vector2)>>>,
+    text_layout: Arc<Mutex<Option<(f64, CairoLayout)>>>,
+    /// The rects covering the text content as laid out at its own size, which hit testing reuses
+    /// until the content changes.
+    text_rects: Arc<Mutex<Option<Vec<Rect>>>>,
+    /// The objects last drawn for the expansion of an [Extension].
+    expanded: Arc<Mutex<Vec<CairoObject>>>,
 }
 
 impl CairoObject {
@@ -778,23 +2479,76 @@ impl CairoObject {
         content: Rasterizable,
         orientation: Transform2,
         depth: u32,
+        clip: Option<Path>,
         color_profile: Option<Profile>,
     ) -> CairoObject {
         CairoObject {
             state: Arc::new(RwLock::new(CairoObjectState {
                 orientation,
+                previous: None,
                 content: match color_profile.clone() {
                     Some(color_profile) => color_profile.transform_content(content),
                     None => content,
                 },
                 depth,
+                clip,
+                blend: Blend::Normal,
+                cursor: CursorIcon::Default,
+                visible: true,
+                tag: None,
                 redraw: Mutex::new(true),
+                changed: true,
+                drawn: None,
             })),
             color_profile,
             cache_surface: Arc::new(Mutex::new(None)),
+            text_layout: Arc::new(Mutex::new(None)),
+            text_rects: Arc::new(Mutex::new(None)),
+            expanded: Arc::new(Mutex::new(vec![])),
+        }
+    }
+    /// Replaces the content of an object drawn for the expansion of an [Extension], keeping the
+    /// shadows it cached unless they have changed.
+    fn replace(&self, content: Content) {
+        let mut state = self.state.write().unwrap();
+        self.set_content(&mut state, content.content);
+        state.orientation = content.transform;
+        state.depth = content.depth;
+        state.clip = content.clip;
+    }
+    /// Sets the content of the object through the lock on its state that the caller holds,
+    /// marking the shadows it cached to be drawn again if they have changed.
+    fn set_content(&self, state: &mut CairoObjectState, input: Rasterizable) {
+        let input = match &self.color_profile {
+            Some(color_profile) => color_profile.transform_content(input),
+            None => input,
+        };
+        let redraw = match (&state.content, &input) {
+            (Rasterizable::Path(current), Rasterizable::Path(path)) => {
+                current.shadows != path.shadows
+                    || current.segments != path.segments
+                    || current.clip_segments != path.clip_segments
+            }
+            (Rasterizable::Path(_), _) | (_, Rasterizable::Path(_)) => true,
+            _ => false,
+        };
+        if redraw {
+            *state.redraw.lock().unwrap() = true;
         }
+        *self.text_layout.lock().unwrap() = None;
+        *self.text_rects.lock().unwrap() = None;
+        state.content = input;
+    }
+    /// Returns the rects covering some text content of the object as laid out by a frame, laying
+    /// it out only if it has changed since they were last returned.
+    fn text_rects(&self, frame: &CairoFrame, text: &Text) -> Vec<Rect> {
+        self.text_rects
+            .lock()
+            .unwrap()
+            .get_or_insert_with(|| frame.selection_rects(text, 0..grapheme::count(&text.content)))
+            .clone()
     }
-    fn redraw(&self, pixel_ratio: f64) {
+    fn redraw(&self, pixel_ratio: f64, patterns: &Patterns) {
         let state = self.state.read().unwrap();
         let mut redraw = state.redraw.lock().unwrap();
         if !*redraw {
@@ -836,8 +2590,12 @@ impl CairoObject {
                     (corners.1.x - corners.0.x).abs(),
                     (corners.1.y - corners.0.y).abs(),
                 )) * pixel_ratio;
-                let base_surface =
-                    ImageSurface::create(Format::ARgb32, size.x as i32, size.y as i32).unwrap();
+                let base_surface = ImageSurface::create(
+                    Format::ARgb32,
+                    size.x.ceil() as i32,
+                    size.y.ceil() as i32,
+                )
+                .unwrap();
                 let base_context = CairoContext(cairo::Context::new(&base_surface));
                 for shadow in &path.shadows {
                     let spread = shadow.spread * 2.;
@@ -848,8 +2606,8 @@ impl CairoObject {
                     let new_size = size + spread;
                     let surface = ImageSurface::create(
                         Format::ARgb32,
-                        ((new_size.x + (shadow.blur * 4.)) * pixel_ratio) as i32,
-                        ((new_size.y + (shadow.blur * 4.)) * pixel_ratio) as i32,
+                        ((new_size.x + (shadow.blur * 4.)) * pixel_ratio).ceil() as i32,
+                        ((new_size.y + (shadow.blur * 4.)) * pixel_ratio).ceil() as i32,
                     )
                     .unwrap();
                     let context = CairoContext(cairo::Context::new(&surface));
@@ -874,6 +2632,9 @@ impl CairoObject {
                             context
                                 .curve_to(handle.x, handle.y, handle.x, handle.y, point.x, point.y);
                         }
+                        Segment::ClosePath => {
+                            context.close_path();
+                        }
                     });
                     if path.closed {
                         context.close_path();
@@ -902,7 +2663,7 @@ impl CairoObject {
                 }
                 base_context.scale(pixel_ratio, pixel_ratio);
                 let path = path.clone().with_offset(-corners.0);
-                draw_path(&base_context, &path, pixel_ratio);
+                draw_path(&base_context, &path, None, pixel_ratio, patterns);
                 composite_clip(&base_context, &path);
                 *self.cache_surface.lock().unwrap() = Some((
                     base_context,
@@ -918,56 +2679,338 @@ impl Object for CairoObject {
         self.state.read().unwrap().orientation
     }
     fn apply_transform(&mut self, transform: Transform2) {
-        self.state.write().unwrap().orientation.transform(transform);
+        let mut state = self.state.write().unwrap();
+        state.orientation.transform(transform);
+        state.changed = true;
     }
     fn set_transform(&mut self, transform: Transform2) {
-        self.state.write().unwrap().orientation = transform;
+        let mut state = self.state.write().unwrap();
+        state.orientation = transform;
+        state.changed = true;
     }
     fn update(&mut self, input: Rasterizable) {
         let mut state = self.state.write().unwrap();
-        *state.redraw.lock().unwrap() = if let Rasterizable::Path(path) = &input {
-            if let Rasterizable::Path(current_path) = &self.state.read().unwrap().content {
-                current_path.shadows != path.shadows
-                    || current_path.segments != path.segments
-                    || !(current_path.clip_segments.is_empty()
-                        && current_path.clip_segments.is_empty())
-            } else {
-                false
-            }
-        } else {
-            false
-        };
-        state.content = match self.color_profile.clone() {
-            Some(color_profile) => color_profile.transform_content(input),
-            None => input,
-        };
+        state.changed = true;
+        self.set_content(&mut state, input);
     }
     fn get_depth(&self) -> u32 {
         self.state.read().unwrap().depth
     }
     fn set_depth(&mut self, depth: u32) {
-        self.state.write().unwrap().depth = depth;
+        let mut state = self.state.write().unwrap();
+        state.depth = depth;
+        state.changed = true;
     }
-    fn box_clone(&self) -> Box<dyn Object> {
-        Box::new(self.clone())
+    fn get_clip(&self) -> Option<Path> {
+        self.state.read().unwrap().clip.clone()
     }
-}
+    fn set_clip(&mut self, clip: Option<Path>) {
+        let mut state = self.state.write().unwrap();
+        state.clip = clip;
+        state.changed = true;
+    }
+    fn get_blend(&self) -> Blend {
+        self.state.read().unwrap().blend
+    }
+    fn set_blend(&mut self, blend: Blend) {
+        let mut state = self.state.write().unwrap();
+        state.blend = blend;
+        state.changed = true;
+    }
+    fn get_cursor(&self) -> CursorIcon {
+        self.state.read().unwrap().cursor
+    }
+    fn set_cursor(&mut self, cursor: CursorIcon) {
+        self.state.write().unwrap().cursor = cursor;
+    }
+    fn is_visible(&self) -> bool {
+        self.state.read().unwrap().visible
+    }
+    fn set_visible(&mut self, visible: bool) {
+        let mut state = self.state.write().unwrap();
+        state.visible = visible;
+        state.changed = true;
+    }
+    fn get_tag(&self) -> Option<String> {
+        self.state.read().unwrap().tag.clone()
+    }
+    fn set_tag(&mut self, tag: Option<String>) {
+        self.state.write().unwrap().tag = tag;
+    }
+    fn get_content(&self) -> Content {
+        let state = self.state.read().unwrap();
+        Content {
+            content: state.content.clone(),
+            depth: state.depth,
+            transform: state.orientation,
+            clip: state.clip.clone(),
+            layout: None,
+            tag: state.tag.clone(),
+        }
+    }
+    fn box_clone(&self) -> Box<dyn Object> {
+        Box::new(self.clone())
+    }
+    fn as_any(&self) -> Box<dyn Any> {
+        Box::new(self.clone())
+    }
+}
 
 #[derive(Clone)]
 struct Cairo {
     state: Arc<RwLock<CairoState>>,
+    shutdown: Arc<AtomicBool>,
+}
+
+/// Declares the GL functions used to present frames as a table of function pointers loaded from
+/// a single context.
+macro_rules! gl_functions {
+    ($($name:ident = $symbol:expr => fn($($argument:ty),*) $(-> $output:ty)?;)*) => {
+        /// The GL functions of a context. Function pointers can differ between contexts, so each
+        /// context loads its own rather than sharing a process-wide set.
+        struct Gl {
+            $($name: unsafe extern "system" fn($($argument),*) $(-> $output)?,)*
+        }
+
+        impl Gl {
+            /// Loads the functions from a context, or returns [None] if it lacks any of them.
+            fn load(context: &dyn ContextTrait) -> Option<Gl> {
+                Some(Gl {
+                    $($name: {
+                        let address = context.get_proc_address($symbol);
+                        if address.is_null() {
+                            return None;
+                        }
+                        unsafe {
+                            std::mem::transmute::<
+                                *const (),
+                                unsafe extern "system" fn($($argument),*) $(-> $output)?,
+                            >(address)
+                        }
+                    },)*
+                })
+            }
+        }
+    };
+}
+
+gl_functions! {
+    attach_shader = "glAttachShader" => fn(GLuint, GLuint);
+    bind_buffer = "glBindBuffer" => fn(GLenum, GLuint);
+    bind_texture = "glBindTexture" => fn(GLenum, GLuint);
+    bind_vertex_array = "glBindVertexArray" => fn(GLuint);
+    blend_func = "glBlendFunc" => fn(GLenum, GLenum);
+    buffer_data = "glBufferData" => fn(GLenum, GLsizeiptr, *const GLvoid, GLenum);
+    clear = "glClear" => fn(GLbitfield);
+    compile_shader = "glCompileShader" => fn(GLuint);
+    create_program = "glCreateProgram" => fn() -> GLuint;
+    create_shader = "glCreateShader" => fn(GLenum) -> GLuint;
+    delete_buffers = "glDeleteBuffers" => fn(GLsizei, *const GLuint);
+    delete_program = "glDeleteProgram" => fn(GLuint);
+    delete_shader = "glDeleteShader" => fn(GLuint);
+    delete_textures = "glDeleteTextures" => fn(GLsizei, *const GLuint);
+    delete_vertex_arrays = "glDeleteVertexArrays" => fn(GLsizei, *const GLuint);
+    draw_arrays = "glDrawArrays" => fn(GLenum, GLint, GLsizei);
+    enable = "glEnable" => fn(GLenum);
+    enable_vertex_attrib_array = "glEnableVertexAttribArray" => fn(GLuint);
+    gen_buffers = "glGenBuffers" => fn(GLsizei, *mut GLuint);
+    gen_textures = "glGenTextures" => fn(GLsizei, *mut GLuint);
+    gen_vertex_arrays = "glGenVertexArrays" => fn(GLsizei, *mut GLuint);
+    link_program = "glLinkProgram" => fn(GLuint);
+    shader_source = "glShaderSource" => fn(GLuint, GLsizei, *const *const GLchar, *const GLint);
+    tex_image_2d = "glTexImage2D" => fn(
+        GLenum, GLint, GLint, GLsizei, GLsizei, GLint, GLenum, GLenum, *const GLvoid
+    );
+    tex_parameter_i = "glTexParameteri" => fn(GLenum, GLenum, GLint);
+    use_program = "glUseProgram" => fn(GLuint);
+    vertex_attrib_pointer = "glVertexAttribPointer" => fn(
+        GLuint, GLint, GLenum, GLboolean, GLsizei, *const GLvoid
+    );
+    viewport = "glViewport" => fn(GLint, GLint, GLsizei, GLsizei);
+}
+
+/// The GL objects used to present the root frame, which are deleted when dropped.
+struct Presenter {
+    gl: Gl,
+    texture: GLuint,
+    shaders: [GLuint; 2],
+    program: GLuint,
+    buffer: GLuint,
+    vertex_array: GLuint,
+    linear_light: bool,
+}
+
+impl Presenter {
+    /// Creates the GL objects in the current context, which the functions are loaded from.
+    fn new(gl: Gl) -> Presenter {
+        let mut texture_id: GLuint = 0;
+        unsafe {
+            (gl.gen_textures)(1, &mut texture_id);
+        }
+
+        let vert_id = new_shader(
+            &gl,
+            r#"#version 330 core
+layout (location = 0) in vec3 pos;
+
+out vec2 coord;
+
+void main()
+{
+    gl_Position = vec4(pos, 1.0);
+    coord = (pos.xy + vec2(1, 1)) / 2;
+}"#,
+            gl::VERTEX_SHADER,
+        );
+
+        let frag_id = new_shader(
+            &gl,
+            r#"#version 330 core
+out vec4 FragLDRColor;
+  
+in vec2 coord;
+
+uniform sampler2D tex;
+
+void main()
+{
+    FragLDRColor = texture(tex, coord);
+}"#,
+            gl::FRAGMENT_SHADER,
+        );
+
+        // The uploaded surface is premultiplied, so it is blended as such rather than multiplied
+        // by its alpha a second time.
+        unsafe {
+            (gl.enable)(gl::BLEND);
+            (gl.blend_func)(gl::ONE, gl::ONE_MINUS_SRC_ALPHA);
+        }
+        // In linear light the surface is decoded from sRGB as it is sampled, blended linearly and
+        // encoded to sRGB again as it is written to the framebuffer.
+        let linear_light = Compositing::current() == Compositing::LinearLight;
+        if linear_light {
+            unsafe { (gl.enable)(gl::FRAMEBUFFER_SRGB) };
+        }
+
+        let program = unsafe {
+            let id = (gl.create_program)();
+            (gl.attach_shader)(id, vert_id);
+            (gl.attach_shader)(id, frag_id);
+            (gl.link_program)(id);
+            id
+        };
+
+        let vertices: Vec<f32> = vec![1., -1., 0., 1., 1., 0., -1., -1., 0., -1., 1., 0.];
+        let mut vbo: GLuint = 0;
+        unsafe {
+            (gl.gen_buffers)(1, &mut vbo);
+            (gl.bind_buffer)(gl::ARRAY_BUFFER, vbo);
+            (gl.buffer_data)(
+                gl::ARRAY_BUFFER,
+                (vertices.len() * std::mem::size_of::<f32>()) as GLsizeiptr,
+                vertices.as_ptr() as *const GLvoid,
+                gl::STATIC_DRAW,
+            );
+            (gl.bind_buffer)(gl::ARRAY_BUFFER, 0);
+        }
+
+        let mut vao: GLuint = 0;
+        unsafe {
+            (gl.gen_vertex_arrays)(1, &mut vao);
+            (gl.bind_vertex_array)(vao);
+            (gl.bind_buffer)(gl::ARRAY_BUFFER, vbo);
+            (gl.enable_vertex_attrib_array)(0);
+            (gl.vertex_attrib_pointer)(
+                0,
+                3,
+                gl::FLOAT,
+                gl::FALSE,
+                (3 * std::mem::size_of::<f32>()) as GLint,
+                std::ptr::null(),
+            );
+            (gl.bind_buffer)(gl::ARRAY_BUFFER, 0);
+            (gl.bind_vertex_array)(0);
+        }
+
+        Presenter {
+            gl,
+            texture: texture_id,
+            shaders: [vert_id, frag_id],
+            program,
+            buffer: vbo,
+            vertex_array: vao,
+            linear_light,
+        }
+    }
+    /// Draws a surface of the provided size in pixels over the viewport of the current context,
+    /// uploading it first if it is stale.
+    fn present(&self, surface: *const c_void, (width, height): (u32, u32), stale: bool) {
+        let gl = &self.gl;
+        unsafe {
+            (gl.viewport)(0, 0, width as i32, height as i32);
+            (gl.clear)(gl::COLOR_BUFFER_BIT);
+            (gl.bind_texture)(gl::TEXTURE_2D, self.texture);
+            if stale {
+                (gl.tex_parameter_i)(gl::TEXTURE_2D, gl::TEXTURE_BASE_LEVEL, 0);
+                (gl.tex_parameter_i)(gl::TEXTURE_2D, gl::TEXTURE_MAX_LEVEL, 0);
+                (gl.tex_image_2d)(
+                    gl::TEXTURE_2D,
+                    0,
+                    if self.linear_light {
+                        gl::SRGB8_ALPHA8 as i32
+                    } else {
+                        gl::RGBA as i32
+                    },
+                    width as i32,
+                    height as i32,
+                    0,
+                    gl::BGRA,
+                    gl::UNSIGNED_BYTE,
+                    surface,
+                );
+            }
+            (gl.use_program)(self.program);
+            (gl.bind_vertex_array)(self.vertex_array);
+            (gl.draw_arrays)(gl::TRIANGLE_STRIP, 0, 4);
+        }
+    }
+}
+
+impl Drop for Presenter {
+    fn drop(&mut self) {
+        let gl = &self.gl;
+        unsafe {
+            (gl.delete_vertex_arrays)(1, &self.vertex_array);
+            (gl.delete_buffers)(1, &self.buffer);
+            (gl.delete_program)(self.program);
+            for shader in &self.shaders {
+                (gl.delete_shader)(*shader);
+            }
+            (gl.delete_textures)(1, &self.texture);
+        }
+    }
 }
 
-fn new_shader(source: &str, kind: GLenum) -> GLuint {
+fn new_shader(gl: &Gl, source: &str, kind: GLenum) -> GLuint {
     unsafe {
-        let id = gl::CreateShader(kind);
+        let id = (gl.create_shader)(kind);
         let source_string = CString::new(source).unwrap();
-        gl::ShaderSource(id, 1, &(source_string).as_ptr(), std::ptr::null());
-        gl::CompileShader(id);
+        (gl.shader_source)(id, 1, &(source_string).as_ptr(), std::ptr::null());
+        (gl.compile_shader)(id);
         id
     }
 }
 
+/// Returns the pixels of the surface a root frame is drawn to.
+fn surface_pointer(frame: &dyn Frame) -> *const c_void {
+    frame
+        .to_image()
+        .as_any()
+        .downcast::<CairoImage>()
+        .unwrap()
+        .get_data_ptr()
+}
+
 struct CairoState {
     root_frame: Option<Box<dyn Frame>>,
     event_sender: Sender<Event>,
@@ -975,6 +3018,10 @@ struct CairoState {
     event_task: Arc<AtomicTask>,
     tick_handlers: Vec<Box<dyn FnMut(f64) + Send + Sync>>,
     size: ObserverCell<Vector2>,
+    ui_scale: f64,
+    // The density of the display the root frame was last fitted to.
+    dpi_factor: f64,
+    accessibility: Option<Preferences>,
 }
 
 #[derive(Clone)]
@@ -996,9 +3043,15 @@ impl Ticker for Cairo {
 }
 
 impl Rasterizer for Cairo {
-    fn rasterize(&self, input: Rasterizable, size: Vector2) -> Box<dyn ImageRepresentation> {
+    fn rasterize(
+        &self,
+        input: Rasterizable,
+        size: Vector2,
+        options: RasterOptions,
+    ) -> Box<dyn ImageRepresentation> {
         //this is probably wrong, just temp
         let mut frame = CairoFrame::new();
+        frame.set_raster_options(options);
         frame.resize(size);
         frame.set_viewport(Rect::new(Vector2::default(), size));
         frame.add(input.into());
@@ -1035,12 +3088,309 @@ impl Provider for Cairo {
     }
 }
 
+impl NetProvider for Cairo {
+    fn net(&self) -> Box<dyn Net> {
+        Box::new(native::net::Net::new())
+    }
+}
+
+impl StorageProvider for Cairo {
+    fn storage(&self) -> Box<dyn Storage> {
+        Box::new(native::storage::Storage::new())
+    }
+}
+
+impl HapticsProvider for Cairo {
+    fn haptics(&self) -> Box<dyn Haptics> {
+        Box::new(native::haptics::Haptics)
+    }
+}
+
+impl AccessibilityProvider for Cairo {
+    fn accessibility(&self) -> Preferences {
+        self.state
+            .read()
+            .unwrap()
+            .accessibility
+            .unwrap_or_else(native::accessibility::preferences)
+    }
+    fn set_accessibility(&self, preferences: Option<Preferences>) {
+        self.state.write().unwrap().accessibility = preferences;
+    }
+    fn ui_scale(&self) -> f64 {
+        self.state.read().unwrap().ui_scale
+    }
+    fn set_ui_scale(&self, scale: f64) {
+        if let Some(scale) = accessibility::clamp_ui_scale(scale) {
+            let mut state = self.state.write().unwrap();
+            state.ui_scale = scale;
+            state.size.invalidate();
+        }
+    }
+}
+
 impl CanvasContext for Cairo {}
 
+impl Cairo {
+    fn new() -> Cairo {
+        let (event_sender, event_stream) = unbounded();
+        for event in native::input::activations() {
+            event_sender.send(event).unwrap();
+        }
+        Cairo {
+            state: Arc::new(RwLock::new(CairoState {
+                //need to figure out how to select size, temp default
+                size: ObserverCell::new((700., 700.).into()),
+                root_frame: None,
+                event_task: Arc::new(AtomicTask::new()),
+                event_stream,
+                event_sender,
+                tick_handlers: vec![],
+                ui_scale: 1.,
+                dpi_factor: 1.,
+                accessibility: None,
+            })),
+            shutdown: Arc::new(AtomicBool::new(false)),
+        }
+    }
+    /// Releases the root frame and tick handlers, unless they're in use by a tick handler calling
+    /// this, in which case the runloop releases them as it exits.
+    fn release(&self) {
+        if let Ok(mut state) = self.state.try_write() {
+            state.root_frame = None;
+            state.tick_handlers.clear();
+        }
+    }
+    /// Sets the logical size of the window, returning the event that reports it.
+    fn resized(&self, size: LogicalSize) -> Event {
+        self.state
+            .read()
+            .unwrap()
+            .size
+            .set((size.width, size.height).into());
+        Event::Windowing(WindowingEvent::Resize)
+    }
+    /// Sends an event to the input of the context, if anything is listening for it.
+    fn dispatch(&self, event: Event) {
+        let state = self.state.read().unwrap();
+        if Arc::strong_count(&state.event_task) != 1 {
+            state.event_sender.send(event).unwrap();
+            state.event_task.notify()
+        }
+    }
+    /// Calls the tick handlers with the time elapsed since the last tick.
+    fn tick(&self, last_time: &mut SystemTime) {
+        let mut state = self.state.write().unwrap();
+        let now = SystemTime::now();
+        state.tick_handlers.iter_mut().for_each(|handler| {
+            (handler)(now.duration_since(*last_time).unwrap().as_nanos() as f64 / 1_000_000.)
+        });
+        *last_time = now;
+    }
+    /// Draws the root frame, first fitting it to the size and UI scale of the context if either has
+    /// changed, in which case the surface it is drawn to is reallocated and returned.
+    fn draw_root(&self, frame: &dyn Frame, dpi_factor: f64) -> Option<*const c_void> {
+        let surface = {
+            let mut state = self.state.write().unwrap();
+            if state.size.is_dirty() {
+                state.dpi_factor = dpi_factor;
+                // The UI scale enlarges the root frame by raising its pixel ratio, leaving it as
+                // many logical pixels smaller.
+                frame.set_pixel_ratio(dpi_factor * state.ui_scale);
+                let size = state.size.get() / state.ui_scale;
+                frame.set_viewport(Rect::new((0., 0.), size));
+                frame.resize(size);
+                Some(surface_pointer(frame))
+            } else {
+                None
+            }
+        };
+        frame.draw();
+        surface
+    }
+    /// Returns the size of the window in physical pixels.
+    fn pixels(&self, dpi_factor: f64) -> (u32, u32) {
+        Logical(self.state.read().unwrap().size.get())
+            .to_physical(dpi_factor)
+            .to_pixels()
+    }
+}
+
 impl ActiveCanvas for Cairo {
     fn box_clone(&self) -> Box<dyn ActiveCanvas> {
         Box::new(self.clone())
     }
+    fn shutdown(&self) {
+        self.shutdown.store(true, Ordering::SeqCst);
+        self.release();
+    }
+}
+
+fn mouse_cursor(cursor: CursorIcon) -> glutin::MouseCursor {
+    match cursor {
+        CursorIcon::Default | CursorIcon::Hidden => glutin::MouseCursor::Default,
+        CursorIcon::Pointer => glutin::MouseCursor::Hand,
+        CursorIcon::Text => glutin::MouseCursor::Text,
+        CursorIcon::Crosshair => glutin::MouseCursor::Crosshair,
+        CursorIcon::Move => glutin::MouseCursor::Move,
+        CursorIcon::Grab => glutin::MouseCursor::Grab,
+        CursorIcon::Grabbing => glutin::MouseCursor::Grabbing,
+        CursorIcon::NotAllowed => glutin::MouseCursor::NotAllowed,
+        CursorIcon::Wait => glutin::MouseCursor::Wait,
+        CursorIcon::Progress => glutin::MouseCursor::Progress,
+        CursorIcon::Help => glutin::MouseCursor::Help,
+        CursorIcon::ResizeHorizontal => glutin::MouseCursor::EwResize,
+        CursorIcon::ResizeVertical => glutin::MouseCursor::NsResize,
+        CursorIcon::ZoomIn => glutin::MouseCursor::ZoomIn,
+        CursorIcon::ZoomOut => glutin::MouseCursor::ZoomOut,
+    }
+}
+
+/// Returns the pointer event reported alongside an input event of the mouse, as browsers do, so
+/// that pointer events cover the mouse on every platform. Whether the primary button is held is
+/// tracked so that pressure is reported as browsers report it.
+fn mouse_pointer_event(
+    event: &glutin::WindowEvent,
+    ui_scale: f64,
+    pointer: Option<Vector2>,
+    pressed: &mut bool,
+) -> Option<Event> {
+    let (action, position) = match event {
+        glutin::WindowEvent::CursorMoved { position, .. } => {
+            let position = Vector2::from((position.x, position.y)) / ui_scale;
+            // The position isn't known when the cursor enters, so entering is reported with the
+            // first movement.
+            match pointer {
+                Some(_) => (pointer::Action::Move, position),
+                None => (pointer::Action::Enter, position),
+            }
+        }
+        glutin::WindowEvent::CursorLeft { .. } => {
+            *pressed = false;
+            (pointer::Action::Leave, pointer?)
+        }
+        glutin::WindowEvent::MouseInput {
+            state,
+            button: glutin::MouseButton::Left,
+            ..
+        } => {
+            *pressed = *state == glutin::ElementState::Pressed;
+            let action = if *pressed {
+                pointer::Action::Down
+            } else {
+                pointer::Action::Up
+            };
+            (action, pointer?)
+        }
+        _ => return None,
+    };
+    Some(Event::Pointer(PointerEvent {
+        action,
+        device: pointer::Device::Mouse,
+        // Browsers identify the mouse as the first pointer.
+        id: 1,
+        position,
+        pressure: if *pressed { 0.5 } else { 0. },
+    }))
+}
+
+/// Translates an input event of a window, tracking the position of the pointer and any UI scale
+/// requested from the keyboard.
+fn input_event(
+    event: glutin::WindowEvent,
+    ui_scale: f64,
+    pointer: &mut Option<Vector2>,
+    zoom: &mut Option<f64>,
+) -> Option<Event> {
+    match event {
+        glutin::WindowEvent::Moved(p) => {
+            Some(Event::Windowing(WindowingEvent::Move((p.x, p.y).into())))
+        }
+        glutin::WindowEvent::CursorMoved { position, .. } => {
+            let position = Vector2::from((position.x, position.y)) / ui_scale;
+            *pointer = Some(position);
+            Some(Event::Mouse(MouseEvent::Move(position)))
+        }
+        glutin::WindowEvent::CursorLeft { .. } => {
+            *pointer = None;
+            None
+        }
+        glutin::WindowEvent::MouseInput {
+            state: element_state,
+            button,
+            ..
+        } => Some(Event::Mouse(match element_state {
+            glutin::ElementState::Pressed => MouseEvent::Down(match button {
+                glutin::MouseButton::Left => mouse::Button::Left,
+                glutin::MouseButton::Right => mouse::Button::Right,
+                glutin::MouseButton::Middle => mouse::Button::Middle,
+                glutin::MouseButton::Other(x) => mouse::Button::Auxiliary(x),
+            }),
+            glutin::ElementState::Released => MouseEvent::Up(match button {
+                glutin::MouseButton::Left => mouse::Button::Left,
+                glutin::MouseButton::Right => mouse::Button::Right,
+                glutin::MouseButton::Middle => mouse::Button::Middle,
+                glutin::MouseButton::Other(x) => mouse::Button::Auxiliary(x),
+            }),
+        })),
+        glutin::WindowEvent::MouseWheel { delta, .. } => {
+            let pixel_delta: Vector2 = match delta {
+                glutin::MouseScrollDelta::LineDelta(_x, _y) => {
+                    println!("LineDelta is not handled");
+                    (0., 0.).into()
+                }
+                glutin::MouseScrollDelta::PixelDelta(p) => (p.x, p.y).into(),
+            };
+            Some(Event::Mouse(MouseEvent::Scroll(pixel_delta / ui_scale)))
+        }
+        glutin::WindowEvent::Touch(touch) => {
+            let action = match touch.phase {
+                glutin::TouchPhase::Started => pointer::Action::Down,
+                glutin::TouchPhase::Moved => pointer::Action::Move,
+                glutin::TouchPhase::Ended => pointer::Action::Up,
+                glutin::TouchPhase::Cancelled => pointer::Action::Cancel,
+            };
+            Some(Event::Pointer(PointerEvent {
+                action,
+                device: pointer::Device::Touch,
+                id: touch.id as i32,
+                position: Vector2::from((touch.location.x, touch.location.y)) / ui_scale,
+                pressure: match action {
+                    pointer::Action::Up | pointer::Action::Cancel => 0.,
+                    _ => 0.5,
+                },
+            }))
+        }
+        glutin::WindowEvent::KeyboardInput { input, .. } => {
+            if input.state == glutin::ElementState::Pressed
+                && (input.modifiers.ctrl || input.modifiers.logo)
+            {
+                *zoom = match input.virtual_keycode {
+                    Some(glutin::VirtualKeyCode::Equals) | Some(glutin::VirtualKeyCode::Add) => {
+                        Some(accessibility::zoom_in(ui_scale))
+                    }
+                    Some(glutin::VirtualKeyCode::Minus)
+                    | Some(glutin::VirtualKeyCode::Subtract) => {
+                        Some(accessibility::zoom_out(ui_scale))
+                    }
+                    Some(glutin::VirtualKeyCode::Key0) | Some(glutin::VirtualKeyCode::Numpad0) => {
+                        Some(1.)
+                    }
+                    _ => *zoom,
+                };
+            }
+            let key = native::input::keyboard::parse_code(input.scancode);
+            Some(Event::Keyboard(KeyboardEvent {
+                action: match input.state {
+                    glutin::ElementState::Pressed => keyboard::Action::Down(key),
+                    glutin::ElementState::Released => keyboard::Action::Up(key),
+                },
+                // TODO
+                printable: None,
+            }))
+        }
+        _ => None,
+    }
 }
 
 impl InactiveCanvas for Cairo {
@@ -1049,20 +3399,21 @@ impl InactiveCanvas for Cairo {
     }
     fn run_with(self: Box<Self>, mut cb: Box<dyn FnMut(Box<dyn ActiveCanvas>) + Send + 'static>) {
         let (mut el, frame, size, windowed_context) = {
-            let state = self.state.read().unwrap();
+            let mut state = self.state.write().unwrap();
             let size = state.size.get();
             let size = LogicalSize::new(size.x, size.y);
             let el = glutin::EventsLoop::new();
             let wb = glutin::WindowBuilder::new().with_dimensions(size);
             let windowed_context = glutin::ContextBuilder::new()
                 .with_vsync(true)
+                .with_srgb(Compositing::current() == Compositing::LinearLight)
                 .build_windowed(wb, &el)
                 .unwrap();
             let dpi_factor = windowed_context.get_hidpi_factor();
+            state.dpi_factor = dpi_factor;
             let frame = state.root_frame.clone().unwrap();
-            frame.set_pixel_ratio(dpi_factor);
-            let size = size.to_physical(dpi_factor);
-            let size = (size.width, size.height).into();
+            frame.set_pixel_ratio(dpi_factor * state.ui_scale);
+            let size = Vector2::from((size.width, size.height)) / state.ui_scale;
             (el, frame, size, windowed_context)
         };
 
@@ -1079,253 +3430,1221 @@ impl InactiveCanvas for Cairo {
         frame.resize(size);
         frame.set_viewport(Rect::new((0., 0.), size));
 
-        unsafe {
-            windowed_context.make_current().unwrap();
-            gl::load_with(|symbol| windowed_context.get_proc_address(symbol) as *const _);
-        }
+        // A context that can't be drawn with ends the runloop as closing its window does.
+        let gl = unsafe { windowed_context.make_current() }
+            .ok()
+            .and_then(|_| Gl::load(&windowed_context));
+        let presenter = match gl {
+            Some(gl) => Presenter::new(gl),
+            None => {
+                drop(windowed_context);
+                drop(frame);
+                self.release();
+                return;
+            }
+        };
+        let mut surface = surface_pointer(&*frame);
+        // The surface is only uploaded again once drawing changes it or it is reallocated.
+        let root = frame.as_any().downcast::<CairoFrame>().unwrap();
+        let mut stale = true;
 
-        let mut texture_id: GLuint = 0;
-        unsafe {
-            gl::GenTextures(1, &mut texture_id);
-        }
+        let mut running = true;
+        let mut pointer: Option<Vector2> = None;
+        let mut pressed = false;
+        let mut cursor = CursorIcon::Default;
+        let mut last_time = SystemTime::now();
+        let ctx = self.clone();
+        let callback = std::thread::spawn(move || cb(ctx));
+        while running && !self.shutdown.load(Ordering::SeqCst) {
+            let ui_scale = self.ui_scale();
+            let mut zoom = None;
+            el.poll_events(|event| {
+                let event = match event {
+                    glutin::Event::WindowEvent { event, .. } => event,
+                    _ => return,
+                };
+                let e = match event {
+                    glutin::WindowEvent::CloseRequested => {
+                        running = false;
+                        None
+                    }
+                    glutin::WindowEvent::Resized(logical_size) => {
+                        let dpi_factor = windowed_context.get_hidpi_factor();
+                        windowed_context.resize(logical_size.to_physical(dpi_factor));
+                        Some(self.resized(logical_size))
+                    }
+                    glutin::WindowEvent::HiDpiFactorChanged(dpi_factor) => {
+                        // A window that has already closed has no size to fit.
+                        windowed_context.get_inner_size().map(|logical_size| {
+                            windowed_context.resize(logical_size.to_physical(dpi_factor));
+                            self.resized(logical_size)
+                        })
+                    }
+                    event => {
+                        if let Some(e) =
+                            mouse_pointer_event(&event, ui_scale, pointer, &mut pressed)
+                        {
+                            self.dispatch(e);
+                        }
+                        input_event(event, ui_scale, &mut pointer, &mut zoom)
+                    }
+                };
+                if let Some(e) = e {
+                    self.dispatch(e);
+                }
+            });
+            if let Some(scale) = zoom {
+                self.set_ui_scale(scale);
+            }
 
-        let mut surface_pointer: *const c_void;
+            self.tick(&mut last_time);
 
-        surface_pointer = frame
-            .to_image()
-            .as_any()
-            .downcast::<CairoImage>()
-            .unwrap()
-            .get_data_ptr();
+            let dpi_factor = windowed_context.get_hidpi_factor();
+            if let Some(resized) = self.draw_root(&*frame, dpi_factor) {
+                surface = resized;
+                stale = true;
+            }
+            stale |= root.painted();
 
-        let vert_id = new_shader(
-            r#"#version 330 core
-layout (location = 0) in vec3 pos;
+            let hovered = pointer
+                .and_then(|position| frame.cursor_at(position))
+                .unwrap_or_default();
+            if hovered != cursor {
+                let window = windowed_context.window();
+                window.hide_cursor(hovered == CursorIcon::Hidden);
+                window.set_cursor(mouse_cursor(hovered));
+                cursor = hovered;
+            }
 
-out vec2 coord;
+            let pixels = self.pixels(dpi_factor);
+            presenter.present(surface, pixels, stale);
+            stale = false;
+            if windowed_context.swap_buffers().is_err() {
+                running = false;
+            }
+        }
 
-void main()
-{
-    gl_Position = vec4(pos, 1.0);
-    coord = (pos.xy + vec2(1, 1)) / 2;
-}"#,
-            gl::VERTEX_SHADER,
-        );
+        // GL objects are deleted while their context is still current, before the window closes.
+        drop(presenter);
+        drop(windowed_context);
+        drop(frame);
+        self.release();
+        // The callback is only waited for after an explicit shutdown, as closing the window
+        // doesn't otherwise end it.
+        if self.shutdown.load(Ordering::SeqCst) {
+            let _ = callback.join();
+        }
+    }
+}
 
-        let frag_id = new_shader(
-            r#"#version 330 core
-out vec4 FragLDRColor;
-  
-in vec2 coord;
+impl InteractiveCanvas for Cairo {
+    fn start(self: Box<Self>, root: Box<dyn Frame>) -> Box<dyn InactiveCanvas> {
+        {
+            let mut state = self.state.write().unwrap();
+            state.root_frame = Some(root);
+        }
+        self
+    }
+}
 
-uniform sampler2D tex;
+impl Canvas for Cairo {
+    fn frame(&self) -> Box<dyn Frame> {
+        let frame = CairoFrame::new();
+        let state = self.state.read().unwrap();
+        frame.set_pixel_ratio(state.dpi_factor * state.ui_scale);
+        frame
+    }
+}
 
-void main()
-{
-    FragLDRColor = texture(tex, coord);
-}"#,
-            gl::FRAGMENT_SHADER,
-        );
+pub(crate) fn new() -> Box<dyn InteractiveCanvas> {
+    Box::new(Cairo::new())
+}
 
-        let program = unsafe {
-            let id = gl::CreateProgram();
-            gl::AttachShader(id, vert_id);
-            gl::AttachShader(id, frag_id);
-            gl::LinkProgram(id);
-            id
-        };
+/// Creates a GL context drawing to a window created by the application.
+#[cfg(target_os = "linux")]
+fn raw_context(window: &winit::Window) -> Result<glutin::RawContext, Error> {
+    use glutin::os::unix::{RawContextExt, WindowExt};
+    let builder = glutin::ContextBuilder::new()
+        .with_vsync(true)
+        .with_srgb(Compositing::current() == Compositing::LinearLight);
+    unsafe {
+        match (window.get_wayland_display(), window.get_wayland_surface()) {
+            (Some(display), Some(surface)) => {
+                let (width, height): (u32, u32) = window
+                    .get_inner_size()
+                    .ok_or_else(Error::graphics_context)?
+                    .to_physical(window.get_hidpi_factor())
+                    .into();
+                builder.build_raw_wayland_context(display as *mut _, surface, width, height)
+            }
+            _ => builder.build_raw_x11_context(
+                window
+                    .get_xlib_xconnection()
+                    .ok_or_else(Error::graphics_context)?,
+                window
+                    .get_xlib_window()
+                    .ok_or_else(Error::graphics_context)?,
+            ),
+        }
+        .map_err(|_| Error::graphics_context())
+    }
+}
 
-        let vertices: Vec<f32> = vec![1., -1., 0., 1., 1., 0., -1., -1., 0., -1., 1., 0.];
-        let mut vbo: GLuint = 0;
+/// Creates a GL context drawing to a window created by the application.
+#[cfg(target_os = "windows")]
+fn raw_context(window: &winit::Window) -> Result<glutin::RawContext, Error> {
+    use glutin::os::windows::{RawContextExt, WindowExt};
+    unsafe {
+        glutin::ContextBuilder::new()
+            .with_vsync(true)
+            .with_srgb(Compositing::current() == Compositing::LinearLight)
+            .build_raw_context(window.get_hwnd())
+            .map_err(|_| Error::graphics_context())
+    }
+}
+
+/// A graphics context embedded as a view of a window owned by the application, which runs its own
+/// event loop and passes the context the events of the window.
+///
+/// The context draws through a GL context of its own for the window, which is made current as the
+/// view is rendered.
+#[cfg(any(target_os = "linux", target_os = "windows"))]
+pub struct EmbeddedContext {
+    canvas: Cairo,
+    frame: Option<Box<CairoFrame>>,
+    profile: Option<Profile>,
+    // The GL objects are deleted before the context they were created in.
+    presenter: Presenter,
+    context: glutin::RawContext,
+    window: winit::WindowId,
+    surface: *const c_void,
+    stale: bool,
+    dpi_factor: f64,
+    pointer: Option<Vector2>,
+    pressed: bool,
+    cursor: CursorIcon,
+    last_time: SystemTime,
+}
+
+#[cfg(any(target_os = "linux", target_os = "windows"))]
+impl EmbeddedContext {
+    /// Returns the context, for creating frames, binding tick handlers and reading input.
+    pub fn canvas(&self) -> Box<dyn ActiveCanvas> {
+        Box::new(self.canvas.clone())
+    }
+    /// Sets the [Frame] shown as the document root of the view.
+    pub fn set_root(&mut self, root: Box<dyn Frame>) {
+        let frame = root.as_any().downcast::<CairoFrame>().unwrap();
+        if let Some(profile) = &self.profile {
+            frame.set_color_profile(profile.clone());
+        }
+        self.surface = surface_pointer(&*root);
+        self.stale = true;
+        self.frame = Some(frame);
+        let mut state = self.canvas.state.write().unwrap();
+        // The root frame is fitted to the window as it is next rendered.
+        state.size.invalidate();
+        state.root_frame = Some(root);
+    }
+    /// Handles an event of the event loop of the application. Events of windows other than the
+    /// one the context is attached to are ignored, so contexts attached to several windows of one
+    /// event loop can each be passed every event. Closing the window is left to the application.
+    pub fn handle_event(&mut self, event: &winit::Event) {
+        let event = match event {
+            winit::Event::WindowEvent { window_id, event } if *window_id == self.window => {
+                event.clone()
+            }
+            _ => return,
+        };
+        let mut zoom = None;
+        let event = match event {
+            winit::WindowEvent::Resized(logical_size) => {
+                self.context
+                    .resize(logical_size.to_physical(self.dpi_factor));
+                Some(self.canvas.resized(logical_size))
+            }
+            winit::WindowEvent::HiDpiFactorChanged(dpi_factor) => {
+                self.dpi_factor = dpi_factor;
+                self.canvas.state.read().unwrap().size.invalidate();
+                Some(Event::Windowing(WindowingEvent::Resize))
+            }
+            event => {
+                let ui_scale = self.canvas.ui_scale();
+                let pointer_event =
+                    mouse_pointer_event(&event, ui_scale, self.pointer, &mut self.pressed);
+                if let Some(event) = pointer_event {
+                    self.canvas.dispatch(event);
+                }
+                input_event(event, ui_scale, &mut self.pointer, &mut zoom)
+            }
+        };
+        if let Some(scale) = zoom {
+            self.canvas.set_ui_scale(scale);
+        }
+        if let Some(event) = event {
+            self.canvas.dispatch(event);
+        }
+    }
+    /// Calls the tick handlers, then draws the root frame and presents it to the window. Does
+    /// nothing before a root frame is set or once the context has been shut down. Fails if the GL
+    /// context can't be made current or presented with, such as once it has been lost.
+    pub fn render(&mut self) -> Result<(), Error> {
+        let frame = match &self.frame {
+            Some(frame) if !self.canvas.shutdown.load(Ordering::SeqCst) => frame,
+            _ => return Ok(()),
+        };
         unsafe {
-            gl::GenBuffers(1, &mut vbo);
-            gl::BindBuffer(gl::ARRAY_BUFFER, vbo);
-            gl::BufferData(
-                gl::ARRAY_BUFFER,
-                (vertices.len() * std::mem::size_of::<f32>()) as GLsizeiptr,
-                vertices.as_ptr() as *const GLvoid,
-                gl::STATIC_DRAW,
-            );
-            gl::BindBuffer(gl::ARRAY_BUFFER, 0);
+            self.context
+                .make_current()
+                .map_err(|_| Error::graphics_context())?;
+        }
+        self.canvas.tick(&mut self.last_time);
+        if let Some(resized) = self.canvas.draw_root(&**frame, self.dpi_factor) {
+            self.surface = resized;
+            self.stale = true;
         }
+        self.stale |= frame.painted();
+        self.cursor = self
+            .pointer
+            .and_then(|position| frame.cursor_at(position))
+            .unwrap_or_default();
+        let pixels = self.canvas.pixels(self.dpi_factor);
+        self.presenter.present(self.surface, pixels, self.stale);
+        self.stale = false;
+        self.context
+            .swap_buffers()
+            .map_err(|_| Error::graphics_context())
+    }
+    /// Returns the cursor of the object under the pointer as of the last render, for the
+    /// application to set on the window.
+    pub fn cursor(&self) -> CursorIcon {
+        self.cursor
+    }
+}
 
-        let mut vao: GLuint = 0;
+#[cfg(any(target_os = "linux", target_os = "windows"))]
+impl Drop for EmbeddedContext {
+    fn drop(&mut self) {
         unsafe {
-            gl::GenVertexArrays(1, &mut vao);
-            gl::BindVertexArray(vao);
-            gl::BindBuffer(gl::ARRAY_BUFFER, vbo);
-            gl::EnableVertexAttribArray(0);
-            gl::VertexAttribPointer(
-                0,
-                3,
-                gl::FLOAT,
-                gl::FALSE,
-                (3 * std::mem::size_of::<f32>()) as GLint,
-                std::ptr::null(),
-            );
-            gl::BindBuffer(gl::ARRAY_BUFFER, 0);
-            gl::BindVertexArray(0);
+            let _ = self.context.make_current();
         }
+        self.frame = None;
+        self.canvas.release();
+    }
+}
 
-        let mut running = true;
-        let mut last_time = SystemTime::now();
-        let ctx = self.clone();
-        std::thread::spawn(move || cb(ctx));
-        while running {
-            el.poll_events(|event| {
-                let state = self.state.read().unwrap();
-                let e = if let glutin::Event::WindowEvent { event, .. } = event.clone() {
-                    match event {
-                        glutin::WindowEvent::CloseRequested => {
-                            running = false;
-                            None
-                        }
-                        glutin::WindowEvent::Resized(logical_size) => {
-                            let dpi_factor = windowed_context.get_hidpi_factor();
-                            let true_size = logical_size.to_physical(dpi_factor);
-                            windowed_context.resize(true_size);
-                            state.size.set((true_size.width, true_size.height).into());
-                            Some(Event::Windowing(WindowingEvent::Resize))
-                        }
-                        glutin::WindowEvent::Moved(p) => {
-                            Some(Event::Windowing(WindowingEvent::Move((p.x, p.y).into())))
-                        }
-                        glutin::WindowEvent::CursorMoved { position, .. } => Some(Event::Mouse(
-                            MouseEvent::Move((position.x, position.y).into()),
-                        )),
-                        glutin::WindowEvent::MouseInput {
-                            state: element_state,
-                            button,
-                            ..
-                        } => Some(Event::Mouse(match element_state {
-                            glutin::ElementState::Pressed => MouseEvent::Down(match button {
-                                glutin::MouseButton::Left => mouse::Button::Left,
-                                glutin::MouseButton::Right => mouse::Button::Right,
-                                glutin::MouseButton::Middle => mouse::Button::Middle,
-                                glutin::MouseButton::Other(x) => mouse::Button::Auxiliary(x),
-                            }),
-                            glutin::ElementState::Released => MouseEvent::Up(match button {
-                                glutin::MouseButton::Left => mouse::Button::Left,
-                                glutin::MouseButton::Right => mouse::Button::Right,
-                                glutin::MouseButton::Middle => mouse::Button::Middle,
-                                glutin::MouseButton::Other(x) => mouse::Button::Auxiliary(x),
-                            }),
-                        })),
-                        glutin::WindowEvent::MouseWheel { delta, .. } => {
-                            let pixel_delta: Vector2 = match delta {
-                                glutin::MouseScrollDelta::LineDelta(_x, _y) => {
-                                    println!("LineDelta is not handled");
-                                    (0., 0.).into()
-                                }
-                                glutin::MouseScrollDelta::PixelDelta(p) => (p.x, p.y).into(),
-                            };
-                            Some(Event::Mouse(MouseEvent::Scroll(pixel_delta)))
-                        }
-                        glutin::WindowEvent::KeyboardInput { input, .. } => {
-                            let key = native::input::keyboard::parse_code(input.scancode);
-                            Some(Event::Keyboard(KeyboardEvent {
-                                action: match input.state {
-                                    glutin::ElementState::Pressed => keyboard::Action::Down(key),
-                                    glutin::ElementState::Released => keyboard::Action::Up(key),
-                                },
-                                // TODO
-                                printable: None,
-                            }))
-                        }
-                        _ => None,
-                    }
-                } else {
-                    None
-                };
-                e.map(|e| {
-                    if Arc::strong_count(&state.event_task) != 1 {
-                        state.event_sender.send(e).unwrap();
-                        state.event_task.notify()
-                    }
-                });
-            });
+#[cfg(any(target_os = "linux", target_os = "windows"))]
+pub(crate) fn attach(window: &winit::Window) -> Result<EmbeddedContext, Error> {
+    let context = raw_context(window)?;
+    unsafe {
+        context
+            .make_current()
+            .map_err(|_| Error::graphics_context())?;
+    }
+    let gl = Gl::load(&context).ok_or_else(Error::graphics_context)?;
+    let canvas = Cairo::new();
+    if let Some(size) = window.get_inner_size() {
+        canvas.resized(size);
+    }
+    Ok(EmbeddedContext {
+        canvas,
+        frame: None,
+        profile: Profile::from_window(window).ok(),
+        presenter: Presenter::new(gl),
+        context,
+        window: window.id(),
+        surface: std::ptr::null(),
+        stale: true,
+        dpi_factor: window.get_hidpi_factor(),
+        pointer: None,
+        pressed: false,
+        cursor: CursorIcon::Default,
+        last_time: SystemTime::now(),
+    })
+}
 
-            {
-                let mut state = self.state.write().unwrap();
-                let now = SystemTime::now();
-                state.tick_handlers.iter_mut().for_each(|handler| {
-                    (handler)(now.duration_since(last_time).unwrap().as_nanos() as f64 / 1_000_000.)
-                });
-                last_time = now;
-            }
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::debug::{LatencyOverlay, TimeTravel};
+    use crate::effects::pool::Pool;
+    use crate::graphics::{
+        brush::Brush,
+        canvas::{scene::Scene, Interpolator},
+        instances::Instances,
+        path::{LinearGradientBuilder, Primitive, Shadow},
+        selection::SelectionArea,
+    };
+    use crate::sync::presence::{Presence, PresenceMessage};
+    use std::sync::atomic::AtomicUsize;
 
-            let state = self.state.read().unwrap();
-            if state.size.is_dirty() {
-                let size = state.size.get();
-                frame.set_viewport(Rect::new((0., 0.), size));
-                frame.resize(size);
-                surface_pointer = frame
-                    .to_image()
-                    .as_any()
-                    .downcast::<CairoImage>()
-                    .unwrap()
-                    .get_data_ptr();
-            }
+    #[test]
+    fn latency_is_measured_after_the_second_tick() {
+        let mut frame = CairoFrame::new();
+        let mut overlay = LatencyOverlay::new(frame.as_mut());
+        let event = Event::Pointer(PointerEvent {
+            action: pointer::Action::Move,
+            device: pointer::Device::Mouse,
+            id: 1,
+            position: (2., 3.).into(),
+            pressure: 0.,
+        });
+        overlay.record(&event);
+        overlay.tick();
+        assert!(overlay.latency().is_none());
+        std::thread::sleep(std::time::Duration::from_millis(2));
+        overlay.tick();
+        let latency = overlay.latency().unwrap();
+        assert!(latency >= 2.);
+        assert_eq!(overlay.statistics(), Some((latency, latency)));
+        overlay.tick();
+        assert_eq!(overlay.statistics(), Some((latency, latency)));
+    }
 
-            frame.draw();
+    #[test]
+    fn idle_latency_overlays_are_not_redrawn() {
+        let mut frame = CairoFrame::new();
+        frame.resize((64., 64.).into());
+        frame.set_viewport(Rect::new((0., 0.), (64., 64.)));
+        let mut overlay = LatencyOverlay::new(frame.as_mut());
+        overlay.tick();
+        frame.draw();
+        assert!(frame.painted());
+        overlay.tick();
+        frame.draw();
+        assert!(!frame.painted());
+    }
 
-            let size = state.size.get();
+    fn presence_message(cursor: Vector2) -> PresenceMessage {
+        PresenceMessage {
+            client: 2,
+            name: "Ada".to_owned(),
+            color: LDRColor::rgb(255, 0, 0),
+            cursor: Some(cursor),
+            selection: None,
+        }
+    }
 
-            unsafe {
-                gl::Viewport(0, 0, size.x as i32, size.y as i32);
-                gl::Clear(gl::COLOR_BUFFER_BIT);
-                gl::BindTexture(gl::TEXTURE_2D, texture_id);
-                gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_BASE_LEVEL, 0);
-                gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAX_LEVEL, 0);
-                gl::TexImage2D(
-                    gl::TEXTURE_2D,
-                    0,
-                    gl::RGBA as i32,
-                    size.x as i32,
-                    size.y as i32,
-                    0,
-                    gl::BGRA,
-                    gl::UNSIGNED_BYTE,
-                    surface_pointer,
-                );
-                gl::UseProgram(program);
-                gl::BindVertexArray(vao);
-                gl::DrawArrays(gl::TRIANGLE_STRIP, 0, 4);
+    #[test]
+    fn local_presence_is_throttled() {
+        let mut frame = CairoFrame::new();
+        let mut presence = Presence::new(frame.as_mut()).with_interval(1.);
+        assert!(presence.tick(0.25).is_none());
+        presence.set_local(presence_message((1., 0.).into()));
+        assert!(presence.tick(0.25).is_some());
+        presence.set_local(presence_message((2., 0.).into()));
+        presence.set_local(presence_message((3., 0.).into()));
+        assert!(presence.tick(0.5).is_none());
+        let sent = presence.tick(0.5).unwrap();
+        assert_eq!(sent.cursor, Some((3., 0.).into()));
+        assert!(presence.tick(1.).is_none());
+    }
+
+    #[test]
+    fn remote_cursors_are_interpolated() {
+        let mut frame = CairoFrame::new();
+        let mut presence = Presence::new(frame.as_mut()).with_interval(1.);
+        presence.receive(presence_message((0., 0.).into()));
+        presence.receive(presence_message((10., 20.).into()));
+        // Objects are added as the selection outline, name tag, label and then cursor.
+        let cursor = || frame.layers()[0].children()[3].get_transform().position;
+        assert_eq!(cursor(), (0., 0.).into());
+        presence.tick(0.5);
+        assert_eq!(cursor(), (5., 10.).into());
+        presence.tick(1.);
+        assert_eq!(cursor(), (10., 20.).into());
+    }
+
+    #[test]
+    fn silent_remotes_are_removed() {
+        let mut frame = CairoFrame::new();
+        let mut presence = Presence::new(frame.as_mut()).with_timeout(1.);
+        presence.receive(presence_message((0., 0.).into()));
+        assert_eq!(presence.clients(), vec![2]);
+        assert_eq!(frame.layers()[0].children().len(), 4);
+        presence.tick(0.5);
+        assert_eq!(presence.clients(), vec![2]);
+        presence.tick(1.);
+        assert!(presence.clients().is_empty());
+        assert!(frame.layers()[0].children().is_empty());
+    }
+
+    #[test]
+    fn pooled_objects_are_reset_when_reused() {
+        let mut frame = CairoFrame::new();
+        let mut pool = Pool::new(frame.as_mut());
+        let square = || -> Content {
+            Primitive::rectangle((4., 4.))
+                .fill(LDRColor::black().into())
+                .finalize()
+                .into()
+        };
+        pool.spawn_animated(
+            square().with_tag("ripple"),
+            1.,
+            Box::new(|object, _| {
+                object.set_blend(Blend::Erase);
+                object.set_cursor(CursorIcon::Pointer);
+                object.set_visible(false);
+            }),
+        );
+        assert_eq!(frame.find("ripple").len(), 1);
+        pool.tick(1.);
+        assert_eq!((pool.active(), pool.idle()), (0, 1));
+        assert!(frame.find("ripple").is_empty());
+
+        pool.spawn(square().with_tag("toast"), 1.);
+        assert_eq!(pool.idle(), 0);
+        let objects = frame.find("toast");
+        assert_eq!(objects.len(), 1);
+        assert_eq!(objects[0].get_blend(), Blend::Normal);
+        assert_eq!(objects[0].get_cursor(), CursorIcon::Default);
+        assert!(objects[0].is_visible());
+        assert_eq!(frame.layers()[0].children().len(), 1);
+    }
+
+    #[test]
+    fn shutdown_releases_resources() {
+        for _ in 0..8 {
+            let frame = CairoFrame::new();
+            let surface = Arc::downgrade(&frame.state);
+            let handled = Arc::new(());
+            let mut context = Cairo::new();
+            let captured = handled.clone();
+            context.bind(Box::new(move |_| {
+                let _ = &captured;
+            }));
+            let handle = context.clone();
+            let inactive = Box::new(context).start(frame);
+            assert!(surface.upgrade().is_some());
+            handle.shutdown();
+            assert!(surface.upgrade().is_none());
+            assert_eq!(Arc::strong_count(&handled), 1);
+            drop(inactive);
+        }
+    }
+
+    #[test]
+    fn scenes_keep_selections() {
+        let new_frame = || -> Box<dyn Frame> {
+            let frame = CairoFrame::new();
+            frame.resize((8., 8.).into());
+            frame.set_viewport(Rect::new((0., 0.), (8., 8.)));
+            frame
+        };
+        let square = |size: f64| -> Content {
+            Primitive::rectangle((size, size))
+                .fill(LDRColor::black().into())
+                .finalize()
+                .into()
+        };
+        let mut frame = new_frame();
+        let mut layer = frame.add_layer();
+        frame.draw();
+        let selection = layer
+            .select(SelectionArea::Rectangle(Rect::new((0., 0.), (4., 8.))))
+            .unwrap();
+        layer.add(square(8.));
+        layer.clear_selection();
+        layer.add(square(2.).with_transform((6., 6.).into()));
+        assert_eq!(layer.children().len(), 2);
+
+        let saved = serde_json::to_string(&Scene::capture(frame.as_ref())).unwrap();
+        let scene: Scene = serde_json::from_str(&saved).unwrap();
+        let mut loaded = new_frame();
+        scene.load(loaded.as_mut());
+        let groups = loaded.layers()[0].groups();
+        assert_eq!(groups.len(), 3);
+        assert_eq!(groups[1].0, Some(selection));
+        assert!(groups[2].0.is_none());
+        assert_eq!(
+            groups
+                .iter()
+                .map(|(_, objects)| objects.len())
+                .collect::<Vec<_>>(),
+            vec![0, 1, 1]
+        );
+
+        frame.draw();
+        loaded.draw();
+        let drawn = frame.to_image().as_texture();
+        let reloaded = loaded.to_image().as_texture();
+        assert_eq!(drawn.pixels, reloaded.pixels);
+        // Only the selected half of the large square is drawn.
+        assert_eq!(reloaded.pixels[8 + 1].r, 0);
+        assert_eq!(reloaded.pixels[8 + 5].r, 255);
+    }
+
+    #[test]
+    fn updated_paths_are_drawn_again() {
+        let mut frame = CairoFrame::new();
+        frame.resize((8., 8.).into());
+        frame.set_viewport(Rect::new((0., 0.), (8., 8.)));
+        let square = |size: f64| -> Rasterizable {
+            Primitive::rectangle((size, size))
+                .fill(LDRColor::black().into())
+                .shadow(Shadow::new(LDRColor::black()).blur(size / 4.))
+                .finalize()
+                .into()
+        };
+        let mut object = frame.add(square(8.).into());
+        frame.draw();
+        assert_eq!(frame.to_image().as_texture().pixels[5 * 8 + 5].r, 0);
+        object.update(square(2.));
+        frame.draw();
+        assert_eq!(frame.to_image().as_texture().pixels[5 * 8 + 5].r, 255);
+    }
+
+    #[test]
+    fn instances_are_drawn_without_expanding() {
+        let mut frame = CairoFrame::new();
+        frame.resize((8., 8.).into());
+        frame.set_viewport(Rect::new((0., 0.), (8., 8.)));
+        let instances = Instances::new(
+            Primitive::square(2.)
+                .fill(LDRColor::rgb(0, 0, 255).into())
+                .finalize(),
+            vec![(0., 0.), (4., 4.)],
+        )
+        .with_tints(vec![LDRColor::rgb(255, 0, 0)]);
+        frame.add(Content::from(instances));
+        frame.draw();
+        assert!(frame.painted());
+        // Instances can't change once created, so unlike other extensions they aren't drawn again.
+        frame.draw();
+        assert!(!frame.painted());
+        let pixels = frame.to_image().as_texture().pixels;
+        assert_eq!(pixels[8 + 1], LDRColor::rgb(0, 0, 0));
+        assert_eq!(pixels[5 * 8 + 5], LDRColor::rgb(0, 0, 255));
+        assert_eq!(pixels[3 * 8 + 3], LDRColor::white());
+    }
+
+    #[test]
+    fn flood_fills_keep_only_the_tiles_they_paint() {
+        let mut frame = CairoFrame::new();
+        frame.resize((300., 8.).into());
+        frame.set_viewport(Rect::new((0., 0.), (300., 8.)));
+        let mut layer = frame.add_layer();
+        layer.add(
+            Primitive::rectangle((260., 8.))
+                .fill(LDRColor::black().into())
+                .finalize()
+                .into(),
+        );
+        frame.draw();
+        let red = LDRColor::rgb(255, 0, 0);
+        let fill = layer.flood_fill((280., 4.).into(), red, 0.).unwrap();
+        let tiles = match fill.get_content().content {
+            Rasterizable::Extension(extension) => extension.expand().unwrap(),
+            _ => panic!("unexpected content"),
+        };
+        assert_eq!(tiles.len(), 1);
+        assert_eq!(tiles[0].transform.position, (256., 0.).into());
+        frame.draw();
+        assert_eq!(frame.to_image().as_texture().pixels[4 * 300 + 280], red);
+    }
+
+    #[test]
+    fn objects_are_found_topmost_first() {
+        let mut frame = CairoFrame::new();
+        frame.resize((20., 20.).into());
+        frame.set_viewport(Rect::new((0., 0.), (20., 20.)));
+        let square = || -> Content {
+            Primitive::rectangle((10., 10.))
+                .fill(LDRColor::black().into())
+                .finalize()
+                .into()
+        };
+        let mut below = frame.add(square());
+        below.set_cursor(CursorIcon::Move);
+        let mut hidden = frame.add(square());
+        hidden.set_visible(false);
+        let mut layer = frame.add_layer();
+        let mut above = layer.add(square());
+        above.set_cursor(CursorIcon::Pointer);
+        let cursors = |frame: &CairoFrame, position: Vector2| -> Vec<CursorIcon> {
+            frame
+                .objects_at(position)
+                .iter()
+                .map(|object| object.get_cursor())
+                .collect()
+        };
+        let position = Vector2::from((5., 5.));
+        assert_eq!(
+            cursors(&frame, position),
+            vec![CursorIcon::Pointer, CursorIcon::Move]
+        );
+        assert_eq!(frame.cursor_at(position), Some(CursorIcon::Pointer));
+        above.set_visible(false);
+        assert_eq!(frame.cursor_at(position), Some(CursorIcon::Move));
+        assert_eq!(frame.cursor_at((15., 15.).into()), None);
+        frame.set_clip(Some(Primitive::rectangle((2., 2.)).finalize()));
+        assert!(cursors(&frame, position).is_empty());
+        assert_eq!(frame.cursor_at(position), None);
+    }
+
+    #[cfg(feature = "rhai")]
+    #[test]
+    fn scripts_manipulate_frames() {
+        use crate::scripting::Script;
+
+        let frame = CairoFrame::new();
+        let mut script = Script::new(frame.clone());
+        script
+            .run(
+                r#"
+                let x = 0.0;
+                while x < 40.0 {
+                    let square = rect(frame(), x, 0.0, 10.0, 10.0, "black");
+                    set_tag(square, "generated");
+                    x = x + 20.0;
+                }
+                text(frame(), "Label", 0.0, 20.0, "black");
+                for square in find(frame(), "generated") {
+                    recolor(square, "orange");
+                    move_by(square, 0.0, 5.0);
+                }
+                "#,
+            )
+            .unwrap();
+        assert_eq!(frame.children().len(), 3);
+        let squares = frame.find("generated");
+        assert_eq!(squares.len(), 2);
+        assert_eq!(squares[1].get_transform().position, (20., 5.).into());
+        match squares[0].get_content().content {
+            Rasterizable::Path(path) => match path.fill.unwrap().content {
+                Texture::Solid(color) => assert_eq!(color, LDRColor::rgb(255, 165, 0)),
+                _ => panic!("unexpected fill"),
+            },
+            _ => panic!("unexpected content"),
+        }
+        assert!(script
+            .run(r#"rect(frame(), 0.0, 0.0, 1.0, 1.0, "nope");"#)
+            .is_err());
+        assert!(script.run("clear(frame());").is_ok());
+        assert!(frame.children().is_empty());
+        assert!(script.run("rect(frame(), ").is_err());
+    }
+
+    #[test]
+    fn brush_strokes_are_painted_as_one_object() {
+        let mut frame = CairoFrame::new();
+        frame.resize((16., 8.).into());
+        frame.set_viewport(Rect::new((0., 0.), (16., 8.)));
+        let mut layer = frame.add_layer();
+        frame.draw();
+        let red = LDRColor::rgb(255, 0, 0);
+        let mut stroke = Brush::new(red, 4.).stroke();
+        let mut stamps = stroke.to((2., 4.), 1.);
+        stamps.extend(stroke.to((14., 4.), 1.));
+        assert!(stamps.len() > 1);
+        layer.paint(&stamps).unwrap();
+        assert_eq!(layer.children().len(), 1);
+        frame.draw();
+        let pixels = frame.to_image().as_texture().pixels;
+        assert_eq!(pixels[4 * 16 + 8], red);
+        assert_eq!(pixels[0], LDRColor::white());
+    }
+
+    /// Content that counts how many times it is drawn.
+    #[derive(Clone)]
+    struct Counted(Arc<AtomicUsize>);
+
+    impl Extension for Counted {
+        fn measure(&self) -> Vector2 {
+            (4., 4.).into()
+        }
+        fn expand(&self) -> Option<Vec<Content>> {
+            self.0.fetch_add(1, Ordering::SeqCst);
+            Some(vec![])
+        }
+        fn rasterize(&self) -> Image<LDRColor, Texture2> {
+            Image {
+                pixels: vec![],
+                format: Texture2 {
+                    width: 0,
+                    height: 0,
+                },
             }
-            windowed_context.swap_buffers().unwrap();
+        }
+        fn box_clone(&self) -> Box<dyn Extension> {
+            Box::new(self.clone())
         }
     }
-}
 
-impl InteractiveCanvas for Cairo {
-    fn start(self: Box<Self>, root: Box<dyn Frame>) -> Box<dyn InactiveCanvas> {
+    #[test]
+    fn static_layers_stay_cached() {
+        let mut frame = CairoFrame::new();
+        frame.resize((8., 8.).into());
+        frame.set_viewport(Rect::new((0., 0.), (8., 8.)));
+        let mut layer = frame.add_layer();
+        layer.set_static(true);
+        let drawn = Arc::new(AtomicUsize::new(0));
+        layer.add(Rasterizable::Extension(Box::new(Counted(drawn.clone()))).into());
+        frame.draw();
+        assert_eq!(drawn.load(Ordering::SeqCst), 1);
+        // Neither interpolation nor full redraws of the frame draw the layer again.
+        frame.advance();
+        frame.set_interpolation(Some(0.5));
+        frame.draw();
+        frame.set_interpolation(None);
+        frame.draw();
+        frame.set_raster_options(RasterOptions::default());
+        frame.draw();
+        assert_eq!(drawn.load(Ordering::SeqCst), 1);
+        layer.invalidate();
+        frame.draw();
+        assert_eq!(drawn.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn static_layers_are_drawn_where_their_objects_are() {
+        let mut frame = CairoFrame::new();
+        frame.resize((8., 8.).into());
+        frame.set_viewport(Rect::new((0., 0.), (8., 8.)));
+        frame.set_background(LDRColor::white());
+        let mut layer = frame.add_layer();
+        layer.set_static(true);
+        assert!(layer.is_static());
+        let drawn = Arc::new(AtomicUsize::new(0));
+        layer.add(Rasterizable::Extension(Box::new(Counted(drawn.clone()))).into());
+        let square = Primitive::square(4.)
+            .fill(LDRColor::black().into())
+            .finalize();
+        let mut object = layer.add(Rasterizable::Path(square).into());
+        frame.draw();
+        assert_eq!(drawn.load(Ordering::SeqCst), 1);
+        // A moved object is drawn where it is now rather than interpolated from where it was.
+        frame.advance();
+        object.set_transform((4., 4.).into());
+        frame.set_interpolation(Some(0.));
+        frame.draw();
+        assert_eq!(drawn.load(Ordering::SeqCst), 2);
+        let pixels = frame.to_image().as_texture().pixels;
+        assert_eq!((pixels[8 + 1].r, pixels[5 * 8 + 5].r), (255, 0));
+        frame.set_interpolation(None);
+        // Moving the viewport draws the layer again.
+        frame.set_viewport(Rect::new((4., 4.), (8., 8.)));
+        frame.draw();
+        assert_eq!(drawn.load(Ordering::SeqCst), 3);
+        assert_eq!(frame.to_image().as_texture().pixels[8 + 1].r, 0);
+    }
+
+    #[test]
+    fn frames_clear_to_white_beneath_transparent_layers() {
+        let mut frame = CairoFrame::new();
+        frame.resize((2., 2.).into());
+        frame.set_viewport(Rect::new((0., 0.), (2., 2.)));
+        assert_eq!(frame.get_background(), LDRColor::white());
+        frame.add_layer();
+        frame.draw();
+        let pixels = frame.to_image().as_texture().pixels;
+        assert!(pixels.iter().all(|pixel| *pixel == LDRColor::white()));
+    }
+
+    #[test]
+    fn scaled_images_are_drawn_apart_from_the_frame() {
+        let mut frame = CairoFrame::new();
+        frame.resize((8., 2.).into());
+        frame.set_viewport(Rect::new((0., 0.), (8., 2.)));
+        frame.set_background(LDRColor::white());
+        let square = Primitive::square(1.)
+            .fill(LDRColor::black().into())
+            .finalize();
+        frame.add(Rasterizable::Path(square).into());
+        frame.draw();
+        let drawn = frame.to_image().as_texture();
+        let image = frame.to_image_scaled(2.);
+        assert_eq!(image.get_size(), (16., 4.).into());
+        let black = |image: Image<LDRColor, Texture2>| {
+            image.pixels.iter().filter(|pixel| pixel.r == 0).count()
+        };
+        assert_eq!(black(image.as_texture()), 4);
+        frame.draw();
+        assert_eq!(frame.to_image().as_texture().pixels, drawn.pixels);
+        // Ratios that aren't finite and positive fall back to that of the frame, and those that
+        // are too large to allocate are reduced.
+        for ratio in &[std::f64::NAN, std::f64::INFINITY, 0., -1.] {
+            assert_eq!(frame.to_image_scaled(*ratio).get_size(), (8., 2.).into());
+        }
+        let wide = CairoFrame::new();
+        wide.resize((64., 1.).into());
+        wide.set_viewport(Rect::new((0., 0.), (64., 1.)));
+        let bounded = wide.to_image_scaled(1e12).get_size();
+        assert_eq!(bounded, (16_384., 256.).into());
+    }
+
+    #[test]
+    fn objects_outside_the_viewport_are_culled() {
+        let mut frame = CairoFrame::new();
+        frame.resize((8., 8.).into());
+        frame.set_viewport(Rect::new((0., 0.), (8., 8.)));
+        let drawn = Arc::new(AtomicUsize::new(0));
+        let counted = Rasterizable::Extension(Box::new(Counted(drawn.clone())));
+        let mut object = frame.add(Content::from(counted).with_transform((100., 100.).into()));
+        frame.draw();
+        assert!(frame.painted());
+        assert_eq!(drawn.load(Ordering::SeqCst), 0);
+        // Changes outside of the viewport paint nothing, and full redraws still skip the object.
+        object.set_transform((200., 100.).into());
+        frame.draw();
+        assert!(!frame.painted());
+        frame.set_raster_options(RasterOptions::default());
+        frame.draw();
+        assert!(frame.painted());
+        assert_eq!(drawn.load(Ordering::SeqCst), 0);
+        frame.set_viewport(Rect::new((198., 98.), (8., 8.)));
+        frame.draw();
+        assert_eq!(drawn.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn interpolation_damages_only_when_it_ends() {
+        let frame = CairoFrame::new();
+        frame.resize((8., 8.).into());
+        frame.set_viewport(Rect::new((0., 0.), (8., 8.)));
+        frame.draw();
+        let damage = |frame: &CairoFrame| {
+            let state = frame.state.read().unwrap();
+            let damage = state.damage.lock().unwrap();
+            (damage.full, damage.regions.len())
+        };
+        frame.set_interpolation(Some(0.25));
+        frame.set_interpolation(Some(0.75));
+        assert_eq!(damage(&frame), (false, 0));
+        frame.set_interpolation(None);
+        assert_eq!(damage(&frame), (false, 1));
+    }
+
+    #[test]
+    fn objects_are_enumerated_and_found_by_tag() {
+        let path = || {
+            Content::from(Rasterizable::Path(
+                Primitive::square(4.)
+                    .fill(LDRColor::black().into())
+                    .finalize(),
+            ))
+        };
+        let mut frame = CairoFrame::new();
+        let tagged = frame.add(path().with_tag("tagged"));
+        let mut untagged = frame.add(path().with_transform((1., 0.).into()));
+        let mut layer = frame.add_layer();
+        layer.add(path().with_tag("tagged").with_transform((2., 0.).into()));
+        assert_eq!(tagged.get_tag().as_deref(), Some("tagged"));
+        assert_eq!(untagged.get_tag(), None);
+        let positions = |objects: Vec<Box<dyn Object>>| {
+            objects
+                .iter()
+                .map(|object| object.get_transform().position.x)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(positions(frame.children()), vec![0., 1., 2.]);
+        assert_eq!(positions(frame.find("tagged")), vec![0., 2.]);
+        assert!(frame.find("missing").is_empty());
+
+        // Found objects are handles to those in the frame, so changes made through them stick.
+        frame.find("tagged")[1].set_tag(None);
+        untagged.set_tag(Some("tagged".to_owned()));
+        assert_eq!(positions(frame.find("tagged")), vec![0., 1.]);
+        frame.find("tagged")[1].set_transform((3., 0.).into());
+        assert_eq!(untagged.get_transform().position.x, 3.);
+        assert!(frame.remove(tagged.as_ref()));
+        assert_eq!(positions(frame.children()), vec![3., 2.]);
+        assert_eq!(positions(layer.children()), vec![2.]);
+    }
+
+    #[test]
+    fn scenes_are_restored_in_place() {
+        let square = |side: f64, position: (f64, f64)| -> Content {
+            Content::from(Rasterizable::Path(
+                Primitive::square(side)
+                    .fill(LDRColor::black().into())
+                    .finalize(),
+            ))
+            .with_transform(position.into())
+        };
+        let mut frame = CairoFrame::new();
+        frame.resize((16., 16.).into());
+        frame.set_viewport(Rect::new((0., 0.), (16., 16.)));
+        frame.set_background(LDRColor::white());
+        let mut moved = frame.add(square(4., (0., 0.)));
+        let mut updated = frame.add(square(4., (8., 0.)));
+        frame.add_layer().add(square(4., (0., 8.)));
+        frame.draw();
+        let drawn = frame.to_image().as_texture();
+        let scene = Scene::capture(frame.as_ref());
+
+        moved.set_transform((4., 4.).into());
+        updated.update(Rasterizable::Path(
+            Primitive::square(8.)
+                .fill(LDRColor::black().into())
+                .finalize(),
+        ));
+        frame.add(square(4., (12., 12.)));
+        frame.add_layer().add(square(4., (8., 8.)));
+        frame.set_background(LDRColor::black());
+        frame.draw();
+        assert_ne!(frame.to_image().as_texture().pixels, drawn.pixels);
+
+        scene.restore(frame.as_mut());
+        // The objects held before the scene was captured are kept and changed back.
+        assert_eq!(moved.get_transform().position, Vector2::default());
+        assert_eq!(frame.children().len(), 3);
+        assert!(frame.layers()[1].children().is_empty());
+        frame.draw();
+        assert_eq!(frame.to_image().as_texture().pixels, drawn.pixels);
+    }
+
+    #[test]
+    fn time_travel_shows_recorded_states() {
+        let canvas = Cairo::new();
+        let mut frame = canvas.frame();
+        frame.resize((64., 64.).into());
+        frame.set_viewport(Rect::new((0., 0.), (64., 64.)));
+        frame.set_background(LDRColor::white());
+        let square = Primitive::square(8.)
+            .fill(LDRColor::black().into())
+            .finalize();
+        let mut object = frame.add(Rasterizable::Path(square).into());
+        let mut overlay = TimeTravel::new(&canvas, frame.as_mut()).with_span(100.);
+        for step in 1..=5 {
+            object.set_transform((f64::from(step) * 8., 0.).into());
+            overlay.record(10.);
+        }
+        assert_eq!(overlay.recorded(), 40.);
+        assert_eq!(overlay.offset(), None);
+        // The red channel of a pixel left of the newest position of the object, and of one on it.
+        let pixels = |frame: &dyn Frame| {
+            frame.draw();
+            let pixels = frame.to_image().as_texture().pixels;
+            (pixels[4 * 64 + 10].r, pixels[4 * 64 + 42].r)
+        };
+        assert_eq!(pixels(frame.as_ref()), (255, 0));
+
+        overlay.scrub(1000.);
+        assert!(overlay.is_scrubbing());
+        assert_eq!(overlay.offset(), Some(40.));
+        // The oldest state is shown over the live contents, which are left as they are.
+        assert_eq!(pixels(frame.as_ref()), (0, 255));
+        assert_eq!(object.get_transform().position, (40., 0.).into());
+        overlay.record(10.);
+        assert_eq!(overlay.recorded(), 40.);
+
+        let press = |action, x| {
+            Event::Pointer(PointerEvent {
+                action,
+                device: pointer::Device::Mouse,
+                id: 1,
+                position: (x, 46.).into(),
+                pressure: 0.,
+            })
+        };
+        overlay.resume();
+        assert!(!overlay.handle(&press(pointer::Action::Down, 4.)));
+        assert!(overlay.handle(&press(pointer::Action::Down, 32.)));
+        assert_eq!(overlay.offset(), Some(40.));
+        assert!(overlay.handle(&press(pointer::Action::Move, 56.)));
+        assert_eq!(overlay.offset(), Some(0.));
+        assert!(overlay.handle(&press(pointer::Action::Up, 64.)));
+        assert!(!overlay.is_scrubbing());
+        assert_eq!(pixels(frame.as_ref()), (255, 0));
+    }
+
+    #[test]
+    fn interpolators_step_at_a_fixed_rate() {
+        let mut frame = CairoFrame::new();
+        let square = Primitive::square(4.)
+            .fill(LDRColor::black().into())
+            .finalize();
+        let mut object = frame.add(Rasterizable::Path(square).into());
+        let interpolation = |frame: &CairoFrame| frame.state.read().unwrap().interpolation;
+        let previous = |frame: &CairoFrame| {
+            let state = frame.state.read().unwrap();
+            let object = state.contents[0].state.read().unwrap();
+            object.previous.map(|previous| previous.position)
+        };
+        let mut interpolator = Interpolator::new(frame.box_clone(), 100.);
+        assert_eq!(interpolation(&frame), Some(1.));
+        let mut steps = 0;
+        interpolator.tick(25., || {
+            steps += 1;
+            object.apply_transform((10., 0.).into());
+        });
+        assert_eq!(steps, 2);
+        assert_eq!(interpolation(&frame), Some(0.5));
+        assert_eq!(previous(&frame), Some((10., 0.).into()));
+        // Time beyond the most steps run for a single tick is dropped.
+        interpolator.tick(1000., || steps += 1);
+        assert_eq!(steps, 10);
+        assert_eq!(interpolation(&frame), Some(0.5));
+        let interpolator =
+            interpolator.with_preferences(Preferences::default().with_reduced_motion(true));
+        assert_eq!(interpolation(&frame), None);
+        let interpolator = interpolator.with_preferences(Preferences::default());
+        assert_eq!(interpolation(&frame), Some(0.5));
+        drop(interpolator);
+        assert_eq!(interpolation(&frame), None);
+    }
+
+    #[test]
+    fn text_is_hit_tested_without_laying_it_out_again() {
+        let mut frame = CairoFrame::new();
+        frame.resize((64., 64.).into());
+        frame.set_viewport(Rect::new((0., 0.), (64., 64.)));
+        let mut object = frame.add(Text::new("Hit").into());
+        object.set_cursor(CursorIcon::Text);
+        let rects = |frame: &CairoFrame| {
+            let state = frame.state.read().unwrap();
+            let rects = state.contents[0].text_rects.lock().unwrap();
+            rects.as_ref().map(|rects| rects.len())
+        };
+        assert_eq!(rects(&frame), None);
+        let rect = frame.selection_rects(&Text::new("Hit"), 0..1)[0];
+        let point = rect.position + rect.size / 2.;
+        assert_eq!(frame.cursor_at(point), Some(CursorIcon::Text));
+        assert_eq!(rects(&frame), Some(1));
+        object.update(Text::new("Hit again").into());
+        assert_eq!(rects(&frame), None);
+    }
+
+    #[test]
+    fn added_layouts_are_copied() {
+        let mut frame = CairoFrame::new();
+        frame.resize((64., 64.).into());
+        frame.set_viewport(Rect::new((0., 0.), (64., 64.)));
+        let layout = Frame::layout_text(&*frame, Text::new("Retained"));
+        let size = layout.measure();
+        frame.add(layout.clone().into());
+        let retained = layout.as_any().downcast::<CairoTextLayout>().unwrap();
         {
-            let mut state = self.state.write().unwrap();
-            state.root_frame = Some(root);
+            let state = frame.state.read().unwrap();
+            let cached = state.contents[0].text_layout.lock().unwrap();
+            let (scale, cached) = cached.as_ref().unwrap();
+            let retained = retained.layout.lock().unwrap();
+            assert_eq!(*scale, 1.);
+            assert!(cached.0 != retained.0);
+            assert_eq!(cached.0.get_pixel_size(), retained.0.get_pixel_size());
         }
-        self
+        frame.draw();
+        assert_eq!(layout.measure(), size);
+        assert_eq!(layout.text().content, "Retained");
     }
-}
 
-impl Canvas for Cairo {
-    fn frame(&self) -> Box<dyn Frame> {
-        CairoFrame::new()
+    #[test]
+    fn centered_text_is_bounded_by_its_lines() {
+        let mut frame = CairoFrame::new();
+        let mut text = Text::new("One\nTwo\nThree").with_size(20.);
+        text.origin = Origin::Middle;
+        frame.add(Rasterizable::Text(text).into());
+        let state = frame.state.read().unwrap();
+        let object = state.contents[0].state.read().unwrap();
+        let bounds = frame.object_bounds(&state, &object);
+        // Three lines centered on the origin reach further from it than one line's height.
+        assert!(bounds.contains((1., -25.)));
+        assert!(bounds.contains((1., 25.)));
+        assert!(bounds.size.y < 120.);
     }
-}
 
-pub(crate) fn new() -> Box<dyn InteractiveCanvas> {
-    let (event_sender, event_stream) = unbounded();
-    let window = Cairo {
-        state: Arc::new(RwLock::new(CairoState {
-            //need to figure out how to select size, temp default
-            size: ObserverCell::new((700., 700.).into()),
-            root_frame: None,
-            event_task: Arc::new(AtomicTask::new()),
-            event_stream,
-            event_sender,
-            tick_handlers: vec![],
-        })),
-    };
+    #[test]
+    fn baselines_are_the_line_pitch_apart() {
+        let frame = CairoFrame::new();
+        // Fallback fonts for other scripts give lines different heights.
+        let text = Text::new("Latin\n日本語\nمرحبا\nLatin")
+            .with_size(16.)
+            .with_line_height(30.);
+        let layout = Frame::layout_text(&*frame, text.clone());
+        let mut baselines: Vec<f64> = vec![];
+        for glyph in layout.glyphs() {
+            if baselines.last() != Some(&glyph.position.y) {
+                baselines.push(glyph.position.y);
+            }
+        }
+        assert_eq!(baselines.len(), 4);
+        for (index, baseline) in baselines.iter().enumerate() {
+            assert!((baseline - baselines[0] - 30. * index as f64).abs() < 0.01);
+        }
+        let last = text.content.rfind('L').unwrap();
+        let caret = layout.caret(grapheme::from_byte_index(&text.content, last));
+        assert!(caret.position.y < baselines[3] && baselines[3] < caret.position.y + caret.size.y);
+        let point = Vector2::from((caret.position.x + 1., baselines[3] - 1.));
+        assert_eq!(
+            layout.hit_test(point),
+            grapheme::from_byte_index(&text.content, last)
+        );
+    }
+
+    #[test]
+    fn hatch_tiles_are_cached_by_their_frame() {
+        let mut frame = CairoFrame::new();
+        frame.resize((8., 8.).into());
+        frame.set_viewport(Rect::new((0., 0.), (8., 8.)));
+        let hatched = Primitive::square(8.)
+            .fill(Hatch::lines(LDRColor::black()).into())
+            .finalize();
+        frame.add(Rasterizable::Path(hatched.clone()).into());
+        frame.add(Rasterizable::Path(hatched.with_offset((2., 2.))).into());
+        frame.draw();
+        let tiles = |frame: &CairoFrame| {
+            let state = frame.state.read().unwrap();
+            let hatches = state.patterns.hatches.lock().unwrap();
+            hatches.len()
+        };
+        assert_eq!(tiles(&frame), 1);
+        assert_eq!(tiles(&CairoFrame::new()), 0);
+    }
+
+    #[test]
+    fn dithered_gradients_are_cached_by_their_frame() {
+        let mut frame = CairoFrame::new();
+        frame.resize((8., 8.).into());
+        frame.set_viewport(Rect::new((0., 0.), (8., 8.)));
+        let gradient = LinearGradientBuilder::new((0., 0.), (8., 0.))
+            .stop(0., LDRColor::black())
+            .stop(1., LDRColor::white())
+            .dither(Dither::Ordered)
+            .finalize()
+            .unwrap();
+        let filled = Primitive::square(8.).fill(gradient.into()).finalize();
+        frame.add(Rasterizable::Path(filled.clone()).into());
+        frame.add(Rasterizable::Path(filled).into());
+        frame.draw();
+        let rasters = |frame: &CairoFrame| {
+            let state = frame.state.read().unwrap();
+            let gradients = state.patterns.gradients.lock().unwrap();
+            gradients.len()
+        };
+        assert_eq!(rasters(&frame), 1);
+        assert_eq!(rasters(&CairoFrame::new()), 0);
+    }
 
-    Box::new(window)
+    #[test]
+    fn fallback_fonts_are_set_per_frame() {
+        let mut text = Text::new("").with_family("Named").with_fallback("Own");
+        text.font = Font::SansSerif;
+        let frame = CairoFrame::new();
+        frame.set_fallback_fonts(vec!["Frame".to_owned()]);
+        assert_eq!(
+            font_family(&text, &frame.state.read().unwrap().fallback),
+            "Named,Sans,Own,Frame"
+        );
+        let families = font_family(&text, &CairoFrame::new().state.read().unwrap().fallback);
+        assert_eq!(
+            families,
+            format!("Named,Sans,Own,{}", text::default_fallback().join(","))
+        );
+    }
+
+    #[test]
+    fn emoji_stay_whole() {
+        let frame = CairoFrame::new();
+        // A family, joined from three people into a single grapheme cluster.
+        let layout = Frame::layout_text(
+            &*frame,
+            Text::new("\u{1F468}\u{200D}\u{1F469}\u{200D}\u{1F467}"),
+        );
+        let glyphs = layout.glyphs();
+        assert!(!glyphs.is_empty());
+        assert!(glyphs.iter().all(|glyph| glyph.cluster == (0..1)));
+        assert!(layout.measure().x > 0.);
+    }
 }
@@ -0,0 +1,11 @@
+use crate::haptics::Haptics as IHaptics;
+
+#[derive(Clone, Copy)]
+pub(crate) struct Haptics;
+
+impl IHaptics for Haptics {
+    fn pulse(&self, _: f64, _: f64) {}
+    fn box_clone(&self) -> Box<dyn IHaptics> {
+        Box::new(*self)
+    }
+}
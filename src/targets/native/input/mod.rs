@@ -1 +1,14 @@
+use crate::input::{activation, Event};
+
+use std::env;
+
 pub(crate) mod keyboard;
+
+/// Returns the URLs passed on the command line, which is how the platform hands a custom protocol
+/// URL to the handler it launches.
+pub(crate) fn activations() -> impl Iterator<Item = Event> {
+    env::args()
+        .skip(1)
+        .filter(|argument| argument.contains("://"))
+        .map(|url| Event::Activation(activation::Event { url }))
+}
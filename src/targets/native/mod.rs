@@ -1,2 +1,6 @@
+pub(crate) mod accessibility;
 pub(crate) mod graphics;
+pub(crate) mod haptics;
 pub(crate) mod input;
+pub(crate) mod net;
+pub(crate) mod storage;
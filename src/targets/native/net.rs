@@ -0,0 +1,105 @@
+use crate::errors::Error;
+use crate::net::{Message, Net as INet, Request, Response, Socket, WebSocket};
+
+use futures::{future, sync::mpsc, Future, Sink, Stream};
+use reqwest::r#async::Client;
+use tokio_tungstenite::{connect_async, tungstenite::Message as SocketMessage};
+use url::Url;
+
+#[derive(Clone)]
+pub(crate) struct Net {
+    client: Client,
+}
+
+impl Net {
+    pub(crate) fn new() -> Net {
+        Net {
+            client: Client::new(),
+        }
+    }
+}
+
+struct NativeSocket(mpsc::UnboundedSender<SocketMessage>);
+
+impl Socket for NativeSocket {
+    fn send(&self, message: Message) -> Result<(), Error> {
+        self.0
+            .unbounded_send(match message {
+                Message::Text(text) => SocketMessage::Text(text),
+                Message::Binary(data) => SocketMessage::Binary(data),
+            })
+            .map_err(|_| Error::network())
+    }
+    fn close(&self) {
+        let _ = self.0.unbounded_send(SocketMessage::Close(None));
+    }
+}
+
+impl INet for Net {
+    fn fetch(&self, request: Request) -> Box<dyn Future<Item = Response, Error = Error> + Send> {
+        let method = reqwest::Method::from_bytes(request.method.as_str().as_bytes()).unwrap();
+        let mut builder = self.client.request(method, &request.url);
+        for (name, value) in &request.headers {
+            builder = builder.header(name.as_str(), value.as_str());
+        }
+        Box::new(
+            builder
+                .body(request.body)
+                .send()
+                .and_then(|response| {
+                    let status = response.status().as_u16();
+                    let headers = response
+                        .headers()
+                        .iter()
+                        .map(|(name, value)| {
+                            (
+                                name.as_str().to_owned(),
+                                String::from_utf8_lossy(value.as_bytes()).into_owned(),
+                            )
+                        })
+                        .collect();
+                    response.into_body().concat2().map(move |body| Response {
+                        status,
+                        headers,
+                        body: body.to_vec(),
+                    })
+                })
+                .map_err(|_| Error::network()),
+        )
+    }
+    fn connect(&self, url: &str) -> Box<dyn Future<Item = WebSocket, Error = Error> + Send> {
+        let url = match Url::parse(url) {
+            Ok(url) => url,
+            Err(_) => return Box::new(future::err(Error::network())),
+        };
+        Box::new(
+            connect_async(url)
+                .map_err(|_| Error::network())
+                .map(|(stream, _)| {
+                    let (sink, source) = stream.split();
+                    let (outgoing, outgoing_receiver) = mpsc::unbounded();
+                    let (incoming, incoming_receiver) = mpsc::unbounded();
+                    tokio::spawn(
+                        outgoing_receiver
+                            .forward(sink.sink_map_err(|_| ()))
+                            .map(|_| ()),
+                    );
+                    tokio::spawn(
+                        source
+                            .filter_map(|message| match message {
+                                SocketMessage::Text(text) => Some(Message::Text(text)),
+                                SocketMessage::Binary(data) => Some(Message::Binary(data)),
+                                _ => None,
+                            })
+                            .map_err(|_| ())
+                            .forward(incoming.sink_map_err(|_| ()))
+                            .map(|_| ()),
+                    );
+                    WebSocket::new(Box::new(NativeSocket(outgoing)), incoming_receiver)
+                }),
+        )
+    }
+    fn box_clone(&self) -> Box<dyn INet> {
+        Box::new(self.clone())
+    }
+}
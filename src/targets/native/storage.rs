@@ -0,0 +1,103 @@
+use crate::errors::Error;
+use crate::storage::Storage as IStorage;
+
+use std::{
+    env, fs,
+    io::ErrorKind,
+    path::{Path, PathBuf},
+};
+
+#[derive(Clone)]
+pub(crate) struct Storage {
+    root: Option<PathBuf>,
+}
+
+impl Storage {
+    pub(crate) fn new() -> Storage {
+        let name = env::current_exe()
+            .ok()
+            .and_then(|path| path.file_stem().map(|stem| escape(&stem.to_string_lossy())));
+        Storage {
+            root: dirs::config_dir().and_then(|root| name.map(|name| root.join(name))),
+        }
+    }
+    fn path(&self, namespace: &str, key: &str) -> Result<PathBuf, Error> {
+        self.root
+            .as_ref()
+            .map(|root| root.join(escape(namespace)).join(escape(key)))
+            .ok_or_else(Error::storage)
+    }
+}
+
+/// Names of devices that can't be used as file names on Windows, in any case.
+const RESERVED: [&str; 24] = [
+    "con", "prn", "aux", "nul", "com0", "com1", "com2", "com3", "com4", "com5", "com6", "com7",
+    "com8", "com9", "lpt0", "lpt1", "lpt2", "lpt3", "lpt4", "lpt5", "lpt6", "lpt7", "lpt8", "lpt9",
+];
+
+/// Percent-encodes everything but lowercase ASCII letters, digits, hyphens and underscores so that
+/// arbitrary names are valid, distinct and free of separators as file names on every platform,
+/// including those whose file systems ignore case. Names that would be empty or reserved by
+/// Windows are suffixed with a lone `%`, which no other escaped name ends with.
+fn escape(name: &str) -> String {
+    let mut escaped = String::with_capacity(name.len());
+    for byte in name.bytes() {
+        match byte {
+            b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' => escaped.push(byte as char),
+            _ => escaped.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    if escaped.is_empty() || RESERVED.contains(&escaped.as_str()) {
+        escaped.push('%');
+    }
+    escaped
+}
+
+fn write(path: &Path, value: &[u8]) -> Result<(), Error> {
+    let parent = path.parent().ok_or_else(Error::storage)?;
+    fs::create_dir_all(parent).map_err(|_| Error::storage())?;
+    let temporary = path.with_extension("tmp");
+    fs::write(&temporary, value).map_err(|_| Error::storage())?;
+    fs::rename(&temporary, path).map_err(|_| Error::storage())
+}
+
+impl IStorage for Storage {
+    fn get(&self, namespace: &str, key: &str) -> Option<Vec<u8>> {
+        fs::read(self.path(namespace, key).ok()?).ok()
+    }
+    fn set(&self, namespace: &str, key: &str, value: &[u8]) -> Result<(), Error> {
+        write(&self.path(namespace, key)?, value)
+    }
+    fn delete(&self, namespace: &str, key: &str) -> Result<(), Error> {
+        match fs::remove_file(self.path(namespace, key)?) {
+            Err(ref error) if error.kind() != ErrorKind::NotFound => Err(Error::storage()),
+            _ => Ok(()),
+        }
+    }
+    fn box_clone(&self) -> Box<dyn IStorage> {
+        Box::new(self.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names_are_escaped_distinctly() {
+        assert_eq!(escape("settings_v2-b"), "settings_v2-b");
+        assert_eq!(escape("a/b.c"), "a%2Fb%2Ec");
+        assert_eq!(escape("é"), "%C3%A9");
+        assert_ne!(escape("Key"), escape("key"));
+        assert_eq!(escape("Key"), "%4Bey");
+    }
+
+    #[test]
+    fn reserved_names_are_escaped() {
+        assert_eq!(escape(""), "%");
+        assert_eq!(escape("con"), "con%");
+        assert_eq!(escape("lpt1"), "lpt1%");
+        assert_eq!(escape("NUL"), "%4E%55%4C");
+        assert_eq!(escape("console"), "console");
+    }
+}
@@ -0,0 +1,19 @@
+use crate::accessibility::Preferences;
+
+use stdweb::unstable::TryInto;
+
+fn matches(query: &str) -> bool {
+    js! {
+        return !!(window.matchMedia && window.matchMedia(@{query}).matches);
+    }
+    .try_into()
+    .unwrap_or(false)
+}
+
+pub(crate) fn preferences() -> Preferences {
+    Preferences::default()
+        .with_reduced_motion(matches("(prefers-reduced-motion: reduce)"))
+        .with_high_contrast(
+            matches("(prefers-contrast: more)") || matches("(forced-colors: active)"),
+        )
+}
@@ -1,13 +1,28 @@
-use crate::graphics::path::{Path, Segment, StrokeCapType, StrokeJoinType, Texture};
-use crate::graphics::text::{Align, Font, Origin, Text, Weight, Wrap};
+use crate::accessibility::{self, Preferences, Provider as AccessibilityProvider};
+use crate::graphics::instances::Variation;
+use crate::graphics::path::{
+    compositing_stops, Dither, Hatch, ImageTexture, Path, Primitive, Segment, StrokeCapType,
+    StrokeJoinType, Texture, Tiling,
+};
+use crate::graphics::text::{
+    self, grapheme, hyphenation, linebreak, search, zoom_breakpoint, Align, Antialias, Ellipsize,
+    Feature, Font, Glyph, Hinting, Origin, Text, Underline, Weight, Wrap,
+};
 use crate::graphics::{
     canvas::{
-        ActiveCanvas, Canvas as VesselsCanvas, CanvasContext, Content, Frame, InactiveCanvas,
-        InteractiveCanvas, Object, Rasterizable, Rasterizer, Ticker,
+        hit_test, image_pixel_ratio, layer::FrameLayer, objects_at, ActiveCanvas, Blend,
+        Canvas as VesselsCanvas, CanvasContext, Content, CursorIcon, Extension, Frame,
+        InactiveCanvas, InteractiveCanvas, Layer, Match, Object, Rasterizable, Rasterizer,
+        RenderWarning, TextLayout, Ticker,
     },
+    dpi::Logical,
+    quality::{Quality, RasterOptions},
     Image, ImageRepresentation, LDRColor, Rect, Texture2, Transform2, Vector2,
 };
+use crate::haptics::{Haptics, Provider as HapticsProvider};
 use crate::input::{Input, Provider};
+use crate::net::{Net, Provider as NetProvider};
+use crate::storage::{Provider as StorageProvider, Storage};
 use crate::targets::web;
 use crate::util::ObserverCell;
 
@@ -17,16 +32,19 @@ use stdweb::traits::{IChildNode, IElement, IEvent, IEventTarget, IHtmlElement, I
 use stdweb::unstable::TryInto;
 use stdweb::web::{
     document,
-    event::{ContextMenuEvent, ResizeEvent},
+    event::{ContextMenuEvent, IMouseEvent, MouseMoveEvent, MouseOutEvent, ResizeEvent},
     window, CanvasPattern, CanvasRenderingContext2d, FillRule, LineCap, LineJoin, TextAlign,
-    TextBaseline,
+    TextBaseline, TypedArray,
 };
 
 use stdweb::web::html_element::CanvasElement;
 
-use std::sync::{Arc, RwLock};
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc, Mutex, RwLock,
+};
 
-use std::ops::Deref;
+use std::ops::Range;
 
 use std::any::Any;
 
@@ -48,12 +66,26 @@ impl ImageRepresentation for CanvasImage {
         Box::new(self.clone())
     }
     fn as_texture(&self) -> Image<LDRColor, Texture2> {
+        let (width, height) = (self.width(), self.height());
+        let data: TypedArray<u8> = if width == 0 || height == 0 {
+            TypedArray::from(&[][..])
+        } else {
+            js! {
+                let image = @{self}
+                    .getContext("2d")
+                    .getImageData(0, 0, @{width}, @{height});
+                return new Uint8Array(image.data.buffer);
+            }
+            .try_into()
+            .unwrap()
+        };
         Image {
-            pixels: vec![],
-            format: Texture2 {
-                height: 0,
-                width: 0,
-            },
+            pixels: data
+                .to_vec()
+                .chunks(4)
+                .map(|pixel| LDRColor::rgba(pixel[0], pixel[1], pixel[2], pixel[3]))
+                .collect(),
+            format: Texture2 { width, height },
         }
     }
     fn from_texture(texture: Image<LDRColor, Texture2>) -> CanvasImage {
@@ -65,21 +97,260 @@ impl ImageRepresentation for CanvasImage {
         canvas.set_width(texture.format.width);
         canvas.set_height(texture.format.height);
         let context: CanvasRenderingContext2d = canvas.get_context().unwrap();
-        let image = context
-            .create_image_data(
-                f64::from(texture.format.width),
-                f64::from(texture.format.height),
-            )
-            .unwrap();
-        context.put_image_data(image, 0., 0.).unwrap();
+        let pixels: Vec<u8> = texture
+            .pixels
+            .iter()
+            .flat_map(|color| vec![color.r, color.g, color.b, color.a])
+            .collect();
+        js! {
+            let context = @{&context};
+            let image = context.createImageData(@{texture.format.width}, @{texture.format.height});
+            image.data.set(@{pixels});
+            context.putImageData(image, 0, 0);
+        };
         canvas
     }
 }
 
+fn compose_matrices(parent: [f64; 6], child: [f64; 6]) -> [f64; 6] {
+    [
+        parent[0] * child[0] + parent[2] * child[1],
+        parent[1] * child[0] + parent[3] * child[1],
+        parent[0] * child[2] + parent[2] * child[3],
+        parent[1] * child[2] + parent[3] * child[3],
+        parent[0] * child[4] + parent[2] * child[5] + parent[4],
+        parent[1] * child[4] + parent[3] * child[5] + parent[5],
+    ]
+}
+
+fn stroke_bounds(entity: &Path, width: f32) -> Rect {
+    let bounds = entity.bounds();
+    let width = f64::from(width);
+    Rect::new(bounds.position - width / 2., bounds.size + width)
+}
+
+fn image_pattern(
+    context: &CanvasRenderingContext2d,
+    texture: &ImageTexture,
+    bounds: Rect,
+) -> CanvasPattern {
+    let image = match texture.image.as_any().downcast::<CanvasImage>() {
+        Ok(image) => *image,
+        Err(_) => CanvasImage::from_texture(texture.image.as_texture()),
+    };
+    let (anchor, origin) = texture.anchoring(bounds);
+    let transform = texture.transform.to_matrix();
+    // Images are backed by device pixels, so the pattern is scaled down to their logical size.
+    let scale = texture.image.get_size().x / f64::from(image.width().max(1));
+    let source = texture.source.map(|_| {
+        let source = texture.source();
+        let position = source.position / scale;
+        let size = source.size / scale;
+        vec![
+            position.x,
+            position.y,
+            size.x.round().max(1.),
+            size.y.round().max(1.),
+        ]
+    });
+    // Canvas patterns can't extend their edges, so clamped images are drawn as though untiled.
+    let repetition = match texture.tiling {
+        Tiling::None | Tiling::Clamp => "no-repeat",
+        Tiling::Repeat | Tiling::Mirror => "repeat",
+    };
+    js! {
+        let image = @{&image};
+        let context = @{context};
+        let source = @{source};
+        if (source) {
+            let tile = document.createElement("canvas");
+            tile.width = source[2];
+            tile.height = source[3];
+            tile.getContext("2d").drawImage(image, source[0], source[1], source[2], source[3], 0, 0, source[2], source[3]);
+            image = tile;
+        }
+        if (@{texture.tiling == Tiling::Mirror}) {
+            let tile = document.createElement("canvas");
+            tile.width = image.width * 2;
+            tile.height = image.height * 2;
+            let tile_context = tile.getContext("2d");
+            [[1, 1], [-1, 1], [1, -1], [-1, -1]].forEach(function(flip) {
+                tile_context.setTransform(flip[0], 0, 0, flip[1], flip[0] < 0 ? tile.width : 0, flip[1] < 0 ? tile.height : 0);
+                tile_context.drawImage(image, 0, 0);
+            });
+            image = tile;
+        }
+        let pattern = context.createPattern(image, @{repetition});
+        if (pattern.setTransform) {
+            pattern.setTransform(
+                new DOMMatrix()
+                    .translate(@{anchor.x}, @{anchor.y})
+                    .multiply(new DOMMatrix(@{transform.to_vec()}))
+                    .translate(@{-origin.x}, @{-origin.y})
+                    .scale(@{scale})
+            );
+        }
+        return pattern;
+    }
+    .try_into()
+    .unwrap()
+}
+
+/// Returns the offset from the origin of text to the top of its first line. Canvas text metrics
+/// don't give vertical extents, so lines are taken to span the font size.
+fn text_top(input: &Text) -> f64 {
+    match input.origin {
+        Origin::Top => input.first_line_shift(input.size * 0.8),
+        Origin::Middle => -input.size / 2.,
+        Origin::Baseline => -input.size * 0.8,
+    }
+}
+
+/// Returns the CSS font shorthand for text, listing its named families, then its generic family,
+/// then its fallback families followed by those of the frame.
+fn css_font(input: &Text, fallback: &[String]) -> String {
+    let generic = match input.font {
+        Font::SystemFont => {
+            r#"-apple-system, BlinkMacSystemFont, "Segoe UI", Roboto, Helvetica, Arial, sans-serif"#
+        }
+        Font::SansSerif => "sans-serif",
+        Font::Serif => "serif",
+        Font::Monospace => "monospace",
+    };
+    let quote = |family: &str| format!("\"{}\"", family.replace('"', "\\\""));
+    let families = input
+        .family
+        .iter()
+        .map(|family| format!("{}, ", quote(family.as_str())))
+        .collect::<String>();
+    // Browsers fall back per character through the whole list, so families after the generic
+    // family are only used for characters it lacks.
+    let fallback = input
+        .fallback_families(fallback)
+        .map(|family| format!(", {}", quote(family)))
+        .collect::<String>();
+    format!(
+        "{}{} {}px {}{}{}",
+        if input.italic { "italic " } else { "" },
+        match input.weight {
+            Weight::Normal => "400",
+            Weight::Medium => "500",
+            Weight::SemiBold => "600",
+            Weight::Bold => "700",
+            Weight::ExtraBold => "800",
+            Weight::Heavy => "900",
+            Weight::Thin => "200",
+            Weight::Light => "300",
+            Weight::Hairline => "100",
+        },
+        input.size,
+        families,
+        generic,
+        fallback
+    )
+}
+
+/// Splits segments of a line of text at the points its words may be hyphenated, marking the
+/// pieces that end in such a point.
+fn hyphenate<'a>(input: &Text, segments: Vec<&'a str>) -> Vec<(&'a str, bool)> {
+    let language = match &input.hyphenation {
+        Some(language) => language,
+        None => {
+            return segments
+                .into_iter()
+                .map(|segment| (segment, false))
+                .collect()
+        }
+    };
+    segments
+        .into_iter()
+        .flat_map(|segment| {
+            let mut pieces = vec![];
+            let mut start = 0;
+            for point in hyphenation::points(segment, language) {
+                pieces.push((&segment[start..point], true));
+                start = point;
+            }
+            pieces.push((&segment[start..], false));
+            pieces
+        })
+        .collect()
+}
+
+/// Returns the range of content covered by a wrapped line found after the provided offset. Lines
+/// broken at a hyphenation point end in a hyphen that isn't part of the content.
+fn find_line(content: &str, cursor: usize, line: &str) -> Option<Range<usize>> {
+    let find = |line: &str| {
+        content[cursor..]
+            .find(line)
+            .map(|offset| cursor + offset..cursor + offset + line.len())
+    };
+    find(line).or_else(|| find(line.strip_suffix('-')?))
+}
+
+/// The greatest number of hatch patterns a frame keeps for reuse.
+const HATCH_PATTERN_CAPACITY: usize = 16;
+
+/// Returns the pattern of a hatch at the pixel ratio of a frame, rendering its tile only if the
+/// frame hasn't cached it.
+fn hatch_pattern(state: &CanvasFrameState, hatch: &Hatch) -> CanvasPattern {
+    let pixel_ratio = state.pixel_ratio;
+    let cached = state
+        .hatches
+        .lock()
+        .unwrap()
+        .iter()
+        .find(|(cached, ratio, _)| cached == hatch && *ratio == pixel_ratio)
+        .map(|(_, _, pattern)| pattern.clone());
+    if let Some(pattern) = cached {
+        return pattern;
+    }
+    let context = &state.context;
+    let mut frame = CanvasFrame::new_raw(pixel_ratio);
+    let size = Vector2::from(hatch.spacing);
+    frame.set_viewport(Rect::new(Vector2::default(), size));
+    frame.resize(size);
+    frame.add(
+        Primitive::square(hatch.spacing)
+            .fill(hatch.background.into())
+            .finalize()
+            .into(),
+    );
+    frame.add(hatch.tile().into());
+    frame.draw();
+    let tile = frame.element();
+    // The tile is rendered at a whole number of pixels, so it's scaled back down to span exactly
+    // one spacing interval in user space.
+    let scale = hatch.spacing / f64::from(tile.width().max(1));
+    let pattern: CanvasPattern = js! {
+        let pattern = @{context}.createPattern(@{&tile}, "repeat");
+        if (pattern.setTransform) {
+            pattern.setTransform(new DOMMatrix().rotate(@{hatch.angle.to_degrees()}).scale(@{scale}));
+        }
+        return pattern;
+    }
+    .try_into()
+    .unwrap();
+    let mut hatches = state.hatches.lock().unwrap();
+    if hatches.len() == HATCH_PATTERN_CAPACITY {
+        hatches.remove(0);
+    }
+    hatches.push((*hatch, pixel_ratio, pattern.clone()));
+    pattern
+}
+
 struct CanvasObjectState {
     orientation: Transform2,
+    previous: Option<Transform2>,
     content: Rasterizable,
     depth: u32,
+    clip: Option<Path>,
+    blend: Blend,
+    cursor: CursorIcon,
+    visible: bool,
+    tag: Option<String>,
+    text_lines: Mutex<Option<(f64, Vec<String>)>>,
+    text_rects: Mutex<Option<Vec<Rect>>>,
 }
 
 #[derive(Clone)]
@@ -88,12 +359,25 @@ struct CanvasObject {
 }
 
 impl CanvasObject {
-    fn new(content: Rasterizable, orientation: Transform2, depth: u32) -> CanvasObject {
+    fn new(
+        content: Rasterizable,
+        orientation: Transform2,
+        depth: u32,
+        clip: Option<Path>,
+    ) -> CanvasObject {
         CanvasObject {
             state: Arc::new(RwLock::new(CanvasObjectState {
                 orientation,
+                previous: None,
                 content,
                 depth,
+                clip,
+                blend: Blend::Normal,
+                cursor: CursorIcon::Default,
+                visible: true,
+                tag: None,
+                text_lines: Mutex::new(None),
+                text_rects: Mutex::new(None),
             })),
         }
     }
@@ -116,21 +400,107 @@ impl Object for CanvasObject {
         self.state.read().unwrap().depth
     }
     fn update(&mut self, input: Rasterizable) {
-        self.state.write().unwrap().content = input;
+        let mut state = self.state.write().unwrap();
+        state.content = input;
+        state.text_lines = Mutex::new(None);
+        state.text_rects = Mutex::new(None);
+    }
+    fn get_clip(&self) -> Option<Path> {
+        self.state.read().unwrap().clip.clone()
+    }
+    fn set_clip(&mut self, clip: Option<Path>) {
+        self.state.write().unwrap().clip = clip;
+    }
+    fn get_blend(&self) -> Blend {
+        self.state.read().unwrap().blend
+    }
+    fn set_blend(&mut self, blend: Blend) {
+        self.state.write().unwrap().blend = blend;
+    }
+    fn get_cursor(&self) -> CursorIcon {
+        self.state.read().unwrap().cursor
+    }
+    fn set_cursor(&mut self, cursor: CursorIcon) {
+        self.state.write().unwrap().cursor = cursor;
+    }
+    fn is_visible(&self) -> bool {
+        self.state.read().unwrap().visible
+    }
+    fn set_visible(&mut self, visible: bool) {
+        self.state.write().unwrap().visible = visible;
+    }
+    fn get_tag(&self) -> Option<String> {
+        self.state.read().unwrap().tag.clone()
+    }
+    fn set_tag(&mut self, tag: Option<String>) {
+        self.state.write().unwrap().tag = tag;
+    }
+    fn get_content(&self) -> Content {
+        let state = self.state.read().unwrap();
+        Content {
+            content: state.content.clone(),
+            depth: state.depth,
+            transform: state.orientation,
+            clip: state.clip.clone(),
+            layout: None,
+            tag: state.tag.clone(),
+        }
     }
     fn box_clone(&self) -> Box<dyn Object> {
         Box::new(self.clone())
     }
+    fn as_any(&self) -> Box<dyn Any> {
+        Box::new(self.clone())
+    }
 }
 
 struct CanvasFrameState {
     context: CanvasRenderingContext2d,
     canvas: CanvasElement,
     contents: Vec<CanvasObject>,
+    layers: Vec<FrameLayer>,
     pixel_ratio: f64,
     viewport: Rect,
     size: Vector2,
+    background: LDRColor,
+    clip: Option<Path>,
     clip_frame: Option<CanvasFrame>,
+    interpolation: Option<f64>,
+    raster_options: RasterOptions,
+    warnings: Mutex<Vec<RenderWarning>>,
+    /// The patterns most recently created for hatches, oldest first, along with the pixel ratio
+    /// each was rendered at.
+    hatches: Mutex<Vec<(Hatch, f64, CanvasPattern)>>,
+    fallback: Vec<String>,
+}
+
+impl CanvasFrameState {
+    fn warn(&self, warning: RenderWarning) {
+        let mut warnings = self.warnings.lock().unwrap();
+        if !warnings.contains(&warning) {
+            warnings.push(warning);
+        }
+    }
+    /// Records the features of a gradient that canvas gradients can't express.
+    fn warn_gradient(&self, dither: Dither, transform: Option<Transform2>) {
+        if dither != Dither::None {
+            self.warn(RenderWarning::GradientDither);
+        }
+        if let Some(transform) = transform {
+            let matrix = transform.to_matrix();
+            let (x, y) = (
+                Vector2::from((matrix[0], matrix[1])),
+                Vector2::from((matrix[2], matrix[3])),
+            );
+            // Radial gradients are resolved with an even scale, which is only exact for transforms
+            // that keep circles circular.
+            if (x.x.hypot(x.y) - y.x.hypot(y.y)).abs() > 1e-9
+                || (x.x * y.x + x.y * y.y).abs() > 1e-9
+            {
+                self.warn(RenderWarning::GradientTransform);
+            }
+        }
+    }
 }
 
 impl Drop for CanvasFrameState {
@@ -143,6 +513,52 @@ struct CanvasFrame {
     state: Arc<RwLock<CanvasFrameState>>,
 }
 
+/// Some [Text] laid out by a [CanvasFrame]. Canvas text has no layout to retain, so the lines the
+/// text is broken onto and its measured size are retained instead, and other queries are
+/// answered by the frame.
+#[derive(Clone)]
+struct CanvasTextLayout {
+    text: Text,
+    lines: Vec<String>,
+    size: Vector2,
+    frame: Arc<RwLock<CanvasFrameState>>,
+}
+
+impl CanvasTextLayout {
+    fn frame(&self) -> CanvasFrame {
+        CanvasFrame {
+            state: self.frame.clone(),
+        }
+    }
+}
+
+impl TextLayout for CanvasTextLayout {
+    fn text(&self) -> Text {
+        self.text.clone()
+    }
+    fn measure(&self) -> Vector2 {
+        self.size
+    }
+    fn caret(&self, index: usize) -> Rect {
+        self.frame().caret(&self.text, index)
+    }
+    fn hit_test(&self, point: Vector2) -> usize {
+        self.frame().hit_test_text(&self.text, point)
+    }
+    fn selection_rects(&self, range: Range<usize>) -> Vec<Rect> {
+        self.frame().selection_rects(&self.text, range)
+    }
+    fn glyphs(&self) -> Vec<Glyph> {
+        self.frame().glyphs(&self.text)
+    }
+    fn box_clone(&self) -> Box<dyn TextLayout> {
+        Box::new(self.clone())
+    }
+    fn as_any(&self) -> Box<dyn Any> {
+        Box::new(self.clone())
+    }
+}
+
 impl CanvasFrame {
     fn new_raw(pixel_ratio: f64) -> CanvasFrame {
         let canvas: CanvasElement = document()
@@ -157,9 +573,17 @@ impl CanvasFrame {
                 pixel_ratio,
                 context,
                 contents: vec![],
+                layers: vec![],
                 size: (1., 1.).into(),
                 viewport: Rect::default(),
+                background: LDRColor::rgba(0, 0, 0, 0),
+                clip: None,
                 clip_frame: None,
+                interpolation: None,
+                raster_options: RasterOptions::default(),
+                warnings: Mutex::new(vec![]),
+                hatches: Mutex::new(vec![]),
+                fallback: text::default_fallback(),
             })),
         }
     }
@@ -177,9 +601,17 @@ impl CanvasFrame {
                 pixel_ratio: 0.,
                 context,
                 contents: vec![],
+                layers: vec![],
                 size: Vector2::default(),
                 viewport: Rect::default(),
+                background: LDRColor::white(),
+                clip: None,
                 clip_frame,
+                interpolation: None,
+                raster_options: RasterOptions::default(),
+                warnings: Mutex::new(vec![]),
+                hatches: Mutex::new(vec![]),
+                fallback: text::default_fallback(),
             })),
         })
     }
@@ -193,7 +625,125 @@ impl CanvasFrame {
         };
         state.canvas.class_list().add("root").unwrap();
     }
-    fn draw_shadows(&self, matrix: [f64; 6], entity: &Path) {
+    /// Sets the composite operation of the saved state that each drawing operation restores, so
+    /// that it applies to everything drawn until it is set again, and returns the previous one.
+    fn set_composite_operation(&self, operation: &str) -> String {
+        let state = self.state.read().unwrap();
+        state.context.restore();
+        let previous = js! {
+            let context = @{&state.context};
+            let previous = context.globalCompositeOperation;
+            context.globalCompositeOperation = @{operation};
+            return previous;
+        }
+        .try_into()
+        .unwrap();
+        state.context.save();
+        previous
+    }
+    fn draw_content(
+        &self,
+        matrix: [f64; 6],
+        content: &Rasterizable,
+        clip: Option<&Path>,
+        text_lines: Option<&Mutex<Option<(f64, Vec<String>)>>>,
+    ) {
+        if let Some(clip) = clip {
+            self.push_clip(matrix, clip);
+        }
+        match content {
+            Rasterizable::Path(path) => self.draw_path_clipped(matrix, &path),
+            Rasterizable::Text(input) => self.draw_text(matrix, &input, text_lines),
+            Rasterizable::Extension(extension) => self.draw_extension(matrix, extension.as_ref()),
+        };
+        if clip.is_some() {
+            self.pop_clip();
+        }
+    }
+    fn push_clip(&self, matrix: [f64; 6], clip: &Path) {
+        let state = self.state.read().unwrap();
+        state.context.restore();
+        state.context.save();
+        state.context.save();
+        state.context.transform(
+            matrix[0], matrix[1], matrix[2], matrix[3], matrix[4], matrix[5],
+        );
+        state.context.begin_path();
+        state.context.move_to(0., 0.);
+        clip.segments.iter().for_each(|segment| match segment {
+            Segment::LineTo(point) => {
+                state.context.line_to(point.x, point.y);
+            }
+            Segment::MoveTo(point) => {
+                state.context.move_to(point.x, point.y);
+            }
+            Segment::CubicTo(point, handle_1, handle_2) => {
+                state.context.bezier_curve_to(
+                    handle_1.x, handle_1.y, handle_2.x, handle_2.y, point.x, point.y,
+                );
+            }
+            Segment::QuadraticTo(point, handle) => {
+                state
+                    .context
+                    .quadratic_curve_to(handle.x, handle.y, point.x, point.y);
+            }
+            Segment::ClosePath => {
+                state.context.close_path();
+            }
+        });
+        // The current path isn't part of the drawing state, so restoring doesn't affect it.
+        state.context.restore();
+        state.context.clip(FillRule::NonZero);
+        state.context.save();
+    }
+    fn pop_clip(&self) {
+        let state = self.state.read().unwrap();
+        state.context.restore();
+        state.context.restore();
+        state.context.save();
+    }
+    fn draw_extension(&self, matrix: [f64; 6], extension: &dyn Extension) {
+        if let Some(instances) = extension.as_instances() {
+            let path = instances.path();
+            // Clips are composited through the clip frame, so paths with them are drawn from their
+            // expansion instead.
+            if path.clip_segments.is_empty() {
+                for (transform, variation) in instances.instances() {
+                    let matrix = compose_matrices(matrix, transform.to_matrix());
+                    self.draw_path(matrix, path, variation);
+                }
+                return;
+            }
+        }
+        match extension.expand() {
+            Some(contents) => contents
+                .iter()
+                .sorted_by(|a, b| a.depth.cmp(&b.depth))
+                .for_each(|content| {
+                    let matrix = compose_matrices(matrix, content.transform.to_matrix());
+                    self.draw_content(matrix, &content.content, content.clip.as_ref(), None);
+                }),
+            None => {
+                let pixel_ratio = self.state.read().unwrap().pixel_ratio;
+                let texture = extension.rasterize_scaled(pixel_ratio);
+                if texture.format.width == 0 || texture.format.height == 0 {
+                    return;
+                }
+                let image = CanvasImage::from_texture(texture);
+                let size = extension.measure();
+                let state = self.state.read().unwrap();
+                state.context.restore();
+                state.context.save();
+                state.context.transform(
+                    matrix[0], matrix[1], matrix[2], matrix[3], matrix[4], matrix[5],
+                );
+                js! {
+                    @{&state.context}.drawImage(@{&image}, 0, 0, @{size.x}, @{size.y});
+                }
+            }
+        }
+    }
+    fn draw_shadows(&self, matrix: [f64; 6], entity: &Path, variation: Variation) {
         let state = self.state.read().unwrap();
         for shadow in &entity.shadows {
             state.context.restore();
@@ -236,6 +786,9 @@ impl CanvasFrame {
                         .context
                         .quadratic_curve_to(handle.x, handle.y, point.x, point.y);
                 }
+                Segment::ClosePath => {
+                    state.context.close_path();
+                }
             });
             if entity.closed {
                 state.context.close_path();
@@ -245,7 +798,7 @@ impl CanvasFrame {
                 .set_shadow_blur(shadow.blur * state.pixel_ratio);
             state
                 .context
-                .set_shadow_color(&shadow.color.to_rgba_color());
+                .set_shadow_color(&variation.color(shadow.color).to_rgba_color());
             state
                 .context
                 .set_shadow_offset_x((shadow.offset.x + offset.x) * state.pixel_ratio);
@@ -279,7 +832,7 @@ impl CanvasFrame {
             matrix[5] *= state.pixel_ratio;
             matrix[3] = state.pixel_ratio;
             matrix[0] = state.pixel_ratio;
-            frame.draw_path(matrix, entity);
+            frame.draw_path(matrix, entity, Variation::default());
             frame.composite_clip(matrix, entity);
             let el = frame.element();
             js! {
@@ -288,7 +841,7 @@ impl CanvasFrame {
             }
             frame.clear();
         } else {
-            self.draw_path(matrix, entity);
+            self.draw_path(matrix, entity, Variation::default());
         }
     }
     fn clear(&self) {
@@ -323,20 +876,25 @@ impl CanvasFrame {
                         .context
                         .quadratic_curve_to(handle.x, handle.y, point.x, point.y);
                 }
+                Segment::ClosePath => {
+                    state.context.close_path();
+                }
             });
         js! {
             @{&state.context}.globalCompositeOperation = "destination-in";
         };
         state.context.fill(FillRule::NonZero);
     }
-    fn draw_path(&self, matrix: [f64; 6], entity: &Path) {
+    /// Draws a path, with its colors varied as for an instance of some
+    /// [Instances](crate::graphics::instances::Instances).
+    fn draw_path(&self, matrix: [f64; 6], entity: &Path, variation: Variation) {
         let state = self.state.read().unwrap();
         state.context.restore();
         state.context.save();
         state.context.transform(
             matrix[0], matrix[1], matrix[2], matrix[3], matrix[4], matrix[5],
         );
-        self.draw_shadows(matrix, &entity);
+        self.draw_shadows(matrix, &entity, variation);
         state.context.begin_path();
         let segments = entity.segments.iter();
         state.context.move_to(0., 0.);
@@ -357,6 +915,9 @@ impl CanvasFrame {
                     .context
                     .quadratic_curve_to(handle.x, handle.y, point.x, point.y);
             }
+            Segment::ClosePath => {
+                state.context.close_path();
+            }
         });
         if entity.closed {
             state.context.close_path();
@@ -372,42 +933,37 @@ impl CanvasFrame {
                     StrokeJoinType::Round => LineJoin::Round,
                     StrokeJoinType::Bevel => LineJoin::Bevel,
                 });
-                match &stroke.content {
+                match &*variation.texture(&stroke.content) {
                     Texture::Solid(color) => {
                         state.context.set_stroke_style_color(&color.to_rgba_color());
                     }
                     Texture::LinearGradient(gradient) => {
+                        state.warn_gradient(gradient.dither(), None);
+                        let gradient = gradient.resolve(stroke_bounds(entity, stroke.width));
                         let canvas_gradient = state.context.create_linear_gradient(
                             gradient.start.x,
                             gradient.start.y,
                             gradient.end.x,
                             gradient.end.y,
                         );
-                        gradient.stops.iter().for_each(|stop| {
+                        compositing_stops(&gradient.stops).iter().for_each(|stop| {
                             canvas_gradient
                                 .add_color_stop(stop.offset, &stop.color.to_rgba_color())
                                 .unwrap();
                         });
                         state.context.set_stroke_style_gradient(&canvas_gradient);
                     }
-                    Texture::Image(image) => {
-                        let pattern: CanvasPattern = match image.as_any().downcast::<CanvasImage>() {
-                            Ok(as_image) => js! {
-                                return @{&state.context}.createPattern(@{as_image.deref()}, "no-repeat");
-                            }.try_into().unwrap(),
-                            Err(_) => {
-                                let as_image = CanvasImage::from_texture(image.box_clone().as_texture());
-                                return js! {
-                                    return @{&state.context}.createPattern(@{as_image}, "no-repeat");
-                                }.try_into().unwrap();
-                            }
-                        };
-                        state
-                            .context
-                            .scale(1. / state.pixel_ratio, 1. / state.pixel_ratio);
+                    Texture::Image(texture) => {
+                        let pattern = image_pattern(
+                            &state.context,
+                            texture,
+                            stroke_bounds(entity, stroke.width),
+                        );
                         state.context.set_stroke_style_pattern(&pattern);
                     }
                     Texture::RadialGradient(gradient) => {
+                        state.warn_gradient(gradient.dither(), gradient.transform);
+                        let gradient = gradient.resolve(stroke_bounds(entity, stroke.width));
                         let canvas_gradient = state
                             .context
                             .create_radial_gradient(
@@ -419,53 +975,43 @@ impl CanvasFrame {
                                 gradient.end_radius,
                             )
                             .unwrap();
-                        gradient.stops.iter().for_each(|stop| {
+                        compositing_stops(&gradient.stops).iter().for_each(|stop| {
                             canvas_gradient
                                 .add_color_stop(stop.offset, &stop.color.to_rgba_color())
                                 .unwrap();
                         });
                         state.context.set_stroke_style_gradient(&canvas_gradient);
                     }
+                    Texture::Hatch(hatch) => {
+                        let pattern = hatch_pattern(&state, hatch);
+                        state.context.set_stroke_style_pattern(&pattern);
+                    }
                 }
                 state.context.set_line_width(f64::from(stroke.width));
                 state.context.stroke();
-                if let Texture::Image(_image) = &stroke.content {
-                    state.context.scale(state.pixel_ratio, state.pixel_ratio);
-                }
             }
             None => {}
         }
         match &entity.fill {
             Some(fill) => {
-                match &fill.content {
+                match &*variation.texture(&fill.content) {
                     Texture::Solid(color) => {
                         state.context.set_fill_style_color(&color.to_rgba_color());
                     }
-                    Texture::Image(image) => {
-                        let pattern: CanvasPattern = match image.as_any().downcast::<CanvasImage>() {
-                            Ok(as_image) => js! {
-                                return @{&state.context}.createPattern(@{as_image.deref()}, "no-repeat");
-                            }.try_into().unwrap(),
-                            Err(_) => {
-                                let as_image = CanvasImage::from_texture(image.box_clone().as_texture());
-                                return js! {
-                                    return @{&state.context}.createPattern(@{as_image}, "no-repeat");
-                                }.try_into().unwrap();
-                            }
-                        };
-                        state
-                            .context
-                            .scale(1. / state.pixel_ratio, 1. / state.pixel_ratio);
+                    Texture::Image(texture) => {
+                        let pattern = image_pattern(&state.context, texture, entity.bounds());
                         state.context.set_fill_style_pattern(&pattern);
                     }
                     Texture::LinearGradient(gradient) => {
+                        state.warn_gradient(gradient.dither(), None);
+                        let gradient = gradient.resolve(entity.bounds());
                         let canvas_gradient = state.context.create_linear_gradient(
                             gradient.start.x,
                             gradient.start.y,
                             gradient.end.x,
                             gradient.end.y,
                         );
-                        gradient.stops.iter().for_each(|stop| {
+                        compositing_stops(&gradient.stops).iter().for_each(|stop| {
                             canvas_gradient
                                 .add_color_stop(stop.offset, &stop.color.to_rgba_color())
                                 .unwrap();
@@ -473,6 +1019,8 @@ impl CanvasFrame {
                         state.context.set_fill_style_gradient(&canvas_gradient);
                     }
                     Texture::RadialGradient(gradient) => {
+                        state.warn_gradient(gradient.dither(), gradient.transform);
+                        let gradient = gradient.resolve(entity.bounds());
                         let canvas_gradient = state
                             .context
                             .create_radial_gradient(
@@ -484,39 +1032,30 @@ impl CanvasFrame {
                                 gradient.end_radius,
                             )
                             .unwrap();
-                        gradient.stops.iter().for_each(|stop| {
+                        compositing_stops(&gradient.stops).iter().for_each(|stop| {
                             canvas_gradient
                                 .add_color_stop(stop.offset, &stop.color.to_rgba_color())
                                 .unwrap();
                         });
                         state.context.set_fill_style_gradient(&canvas_gradient);
                     }
+                    Texture::Hatch(hatch) => {
+                        let pattern = hatch_pattern(&state, hatch);
+                        state.context.set_fill_style_pattern(&pattern);
+                    }
                 }
                 state.context.fill(FillRule::NonZero);
-                if let Texture::Image(_image) = &fill.content {
-                    state.context.scale(state.pixel_ratio, state.pixel_ratio);
-                }
             }
             None => {}
         }
+        entity
+            .markers()
+            .iter()
+            .for_each(|marker| self.draw_path(matrix, marker, variation));
     }
     fn update_text_style(&self, input: &Text) {
         let state = self.state.read().unwrap();
-        state.context.set_font((match input.font {
-                Font::SystemFont => {
-                    format!(r#"{} {} {}px -apple-system, BlinkMacSystemFont, "Segoe UI", Roboto, Helvetica, Arial, sans-serif, "Apple LDRColor Emoji", "Segoe UI Emoji", "Segoe UI Symbol""#, if input.italic { "italic " } else { "" }, match input.weight {
-                        Weight::Normal => "400",
-                        Weight::Medium => "500",
-                        Weight::SemiBold => "600",
-                        Weight::Bold => "700",
-                        Weight::ExtraBold => "800",
-                        Weight::Heavy => "900",
-                        Weight::Thin => "200",
-                        Weight::Light => "300",
-                        Weight::Hairline => "100"
-                    }, input.size)
-                }
-            }).as_str());
+        state.context.set_font(&css_font(input, &state.fallback));
         state.context.set_text_align(match input.align {
             Align::Center => TextAlign::Center,
             Align::End => TextAlign::End,
@@ -527,131 +1066,450 @@ impl CanvasFrame {
             Origin::Baseline => TextBaseline::Alphabetic,
             Origin::Middle => TextBaseline::Middle,
         });
+        let (word_spacing, kerning) = (
+            format!("{}px", input.word_spacing),
+            if input.kerning { "auto" } else { "none" },
+        );
+        // Canvas has no general OpenType feature settings, only a property for small capitals.
+        let caps = if input.features.contains(&(Feature::SmallCaps, true)) {
+            "small-caps"
+        } else {
+            "normal"
+        };
+        // Canvas measurement follows these properties, so spaced lines wrap and hit test as drawn.
+        js! {
+            let context = @{&state.context};
+            context.wordSpacing = @{word_spacing};
+            context.fontKerning = @{kerning};
+            context.fontVariantCaps = @{caps};
+        };
         state
             .context
             .set_fill_style_color(&input.color.to_rgba_color());
     }
     fn fill_text_with_spacing(&self, text: &'_ str, position: Vector2, spacing: f64) {
-        if text == "" {
-            return;
-        }
         let state = self.state.read().unwrap();
-        let mut full_width = state.context.measure_text(&text).unwrap().get_width();
+        let width = |start: usize| {
+            if start == text.len() {
+                0.
+            } else {
+                state
+                    .context
+                    .measure_text(&text[start..])
+                    .unwrap()
+                    .get_width()
+            }
+        };
         let mut position = position;
-        let mut text = text.to_owned();
-        let mut text_iter = text.chars();
-        while {
-            let head = text_iter.next().unwrap();
-            text = text_iter
-                .map(|character| character.to_string())
-                .collect::<Vec<String>>()
-                .join("");
-            text_iter = text.chars();
+        let boundaries = grapheme::boundaries(text);
+        let mut full_width = width(0);
+        // Each grapheme cluster is drawn whole so that emoji sequences and combining marks keep
+        // their joined glyph, and is advanced by the width it adds to the rest of the line.
+        for (index, start) in boundaries.iter().enumerate() {
+            let end = boundaries.get(index + 1).cloned().unwrap_or(text.len());
             state
                 .context
-                .fill_text(&head.to_string(), position.x, position.y, None);
-
-            let shorter_width = if text == "" {
-                0.
-            } else {
-                state.context.measure_text(&text).unwrap().get_width()
-            };
-            let character_width = full_width - shorter_width;
-            position.x += character_width + spacing;
+                .fill_text(&text[*start..end], position.x, position.y, None);
+            let shorter_width = width(end);
+            position.x += full_width - shorter_width + spacing;
             full_width = shorter_width;
-            text != ""
-        } {}
+        }
     }
     fn measure_text_with_spacing(&self, text: &'_ str, spacing: f64) -> f64 {
         if text == "" {
             return 0.;
         }
         let state = self.state.read().unwrap();
-        let mut full_width = state.context.measure_text(&text).unwrap().get_width();
-        if spacing == 0. {
-            return full_width;
-        }
-        let mut spaced_width = 0.;
-        let mut text = text.to_owned();
-        let mut text_iter = text.chars();
-        while {
-            text_iter.next().unwrap();
-            text = text_iter
-                .map(|character| character.to_string())
-                .collect::<Vec<String>>()
-                .join("");
-            text_iter = text.chars();
-            let shorter_width = if text == "" {
-                0.
-            } else {
-                state.context.measure_text(&text).unwrap().get_width()
-            };
-            let character_width = full_width - shorter_width;
-            spaced_width += character_width + spacing;
-            full_width = shorter_width;
-            text != ""
-        } {}
-        spaced_width - spacing
+        let width = state.context.measure_text(&text).unwrap().get_width();
+        // The advances of the clusters sum to the width of the whole text, so spacing only adds
+        // the gaps between them.
+        width + spacing * f64::from((grapheme::count(text) - 1) as u32)
     }
-    fn draw_text(&self, matrix: [f64; 6], input: &Text) {
+    fn draw_text(
+        &self,
+        matrix: [f64; 6],
+        input: &Text,
+        text_lines: Option<&Mutex<Option<(f64, Vec<String>)>>>,
+    ) {
         let state = self.state.read().unwrap();
+        if input
+            .features
+            .iter()
+            .any(|(feature, _)| *feature != Feature::SmallCaps)
+        {
+            state.warn(RenderWarning::FontFeatures);
+        }
+        if input.antialias != Antialias::default() || input.hinting != Hinting::default() {
+            state.warn(RenderWarning::GlyphRasterization);
+        }
         state.context.restore();
         state.context.save();
         state.context.transform(
             matrix[0], matrix[1], matrix[2], matrix[3], matrix[4], matrix[5],
         );
-        let mut lines: Vec<String> = input
-            .content
-            .split('\n')
-            .map(std::borrow::ToOwned::to_owned)
+        let breakpoint = zoom_breakpoint(matrix);
+        state.context.scale(1. / breakpoint, 1. / breakpoint);
+        let input = &input.scaled(breakpoint);
+        self.update_text_style(&input);
+        let cached = text_lines.and_then(|text_lines| match &*text_lines.lock().unwrap() {
+            Some((cached, lines)) if *cached == breakpoint => Some(lines.clone()),
+            _ => None,
+        });
+        let lines = cached.unwrap_or_else(|| {
+            let lines = self.break_lines(input);
+            if let Some(text_lines) = text_lines {
+                *text_lines.lock().unwrap() = Some((breakpoint, lines.clone()));
+            }
+            lines
+        });
+        if let (Wrap::Clip, Some(max_width)) = (input.wrap, input.max_width) {
+            let left = match input.align {
+                Align::Start => 0.,
+                Align::Center => -max_width / 2.,
+                Align::End => -max_width,
+            };
+            state.context.begin_path();
+            state.context.rect(
+                left,
+                text_top(input),
+                max_width,
+                input.line_pitch() * f64::from((lines.len() - 1) as u32) + input.size,
+            );
+            state.context.clip(FillRule::NonZero);
+        }
+        for (index, (start, line)) in self.locate_lines(input, lines).iter().enumerate() {
+            let y = input.line_pitch() * f64::from(index as u32)
+                + input.first_line_shift(input.size * 0.8);
+            let styled = !input.spans.is_empty()
+                && input.content.get(*start..start + line.len()) == Some(line.as_str());
+            if styled && !line.contains('\t') {
+                self.fill_runs(input, *start, line, index);
+                continue;
+            }
+            if !input.tab_stops.is_empty() && line.contains('\t') {
+                // Canvas text has no tab stops, so tabbed lines are drawn a segment at a time.
+                let start = self.line_start(input, line);
+                state.context.set_text_align(TextAlign::Start);
+                for (offset, segment) in self.tab_segments(input, line) {
+                    self.fill_line(input, segment, (start + offset, y).into());
+                }
+                self.update_text_style(&input);
+            } else {
+                self.fill_line(input, line, (0., y).into());
+            }
+            self.decorate_line(input, line, input.line_pitch() * f64::from(index as u32));
+        }
+    }
+    /// Draws and decorates a line of text one run at a time, each in the style of the spans that
+    /// cover it, since canvas text takes a single style per call. The line begins at the provided
+    /// byte offset in the content and is drawn as the line of the provided index.
+    fn fill_runs(&self, input: &Text, start: usize, line: &str, index: usize) {
+        let top = input.line_pitch() * f64::from(index as u32);
+        let y = top + input.first_line_shift(input.size * 0.8);
+        let runs: Vec<(&str, Text)> = input
+            .runs(start..start + line.len())
+            .into_iter()
+            .map(|(range, run)| {
+                (
+                    &line[range.start - start..range.end - start],
+                    Text {
+                        align: Align::Start,
+                        ..run
+                    },
+                )
+            })
+            .collect();
+        let widths: Vec<f64> = runs
+            .iter()
+            .map(|(segment, run)| {
+                self.update_text_style(run);
+                self.prefix_width(run, segment)
+            })
             .collect();
+        let width = widths.iter().sum::<f64>() - input.letter_spacing;
+        let mut x = match input.align {
+            Align::Start => 0.,
+            Align::Center => -width / 2.,
+            Align::End => -width,
+        };
+        for ((segment, run), width) in runs.iter().zip(widths) {
+            self.update_text_style(run);
+            self.fill_line(run, segment, (x, y).into());
+            {
+                let state = self.state.read().unwrap();
+                state.context.save();
+                state.context.translate(x, 0.);
+            }
+            self.decorate_line(run, segment, top);
+            self.state.read().unwrap().context.restore();
+            x += width;
+        }
+        self.update_text_style(input);
+    }
+    fn fill_line(&self, input: &Text, line: &'_ str, position: Vector2) {
+        if input.letter_spacing != 0. {
+            self.fill_text_with_spacing(line, position, input.letter_spacing);
+        } else {
+            let state = self.state.read().unwrap();
+            state.context.fill_text(line, position.x, position.y, None);
+        }
+    }
+    /// Splits a line of text at its tabs, returning each segment with its offset from the start of
+    /// the line.
+    fn tab_segments<'a>(&self, input: &Text, line: &'a str) -> Vec<(f64, &'a str)> {
+        let mut offset = 0.;
+        let mut segments = vec![];
+        for (index, segment) in line.split('\t').enumerate() {
+            if index > 0 {
+                offset = input.next_tab_stop(offset).unwrap_or(offset);
+            }
+            segments.push((offset, segment));
+            offset += self.prefix_width(input, segment);
+        }
+        segments
+    }
+    /// Returns the width of a line of text, with its tabs advanced to the tab stops.
+    fn line_width(&self, input: &Text, line: &str) -> f64 {
+        if input.tab_stops.is_empty() || !line.contains('\t') {
+            return self.measure_text_with_spacing(line, input.letter_spacing);
+        }
+        let (offset, segment) = *self.tab_segments(input, line).last().unwrap();
+        offset + self.measure_text_with_spacing(segment, input.letter_spacing)
+    }
+    fn decorate_line(&self, input: &Text, line: &'_ str, y: f64) {
+        if input.underline == Underline::None && !input.strikethrough {
+            return;
+        }
+        // Measuring reads the state again, so it's done before the state is held.
+        let width = self.line_width(input, line);
+        let state = self.state.read().unwrap();
+        let x = match input.align {
+            Align::Start => 0.,
+            Align::Center => -width / 2.,
+            Align::End => -width,
+        };
+        // Canvas text has no decorations, so they're drawn as rectangles placed relative to the top
+        // of the line.
+        let top = y + text_top(input);
+        let thickness = (input.size / 15.).max(1.);
+        let mut offsets = vec![];
+        match input.underline {
+            Underline::None => {}
+            Underline::Single => offsets.push(input.size * 0.9),
+            Underline::Double => {
+                offsets.push(input.size * 0.9);
+                offsets.push(input.size * 0.9 + thickness * 2.);
+            }
+        }
+        if input.strikethrough {
+            offsets.push(input.size * 0.55);
+        }
+        state.context.set_fill_style_color(
+            &input
+                .decoration_color
+                .unwrap_or(input.color)
+                .to_rgba_color(),
+        );
+        for offset in offsets {
+            state
+                .context
+                .fill_rect(x, top + offset - thickness / 2., width, thickness);
+        }
+        state
+            .context
+            .set_fill_style_color(&input.color.to_rgba_color());
+    }
+    fn text_rects(&self, input: &Text, range: Range<usize>) -> Vec<Rect> {
         self.update_text_style(&input);
+        let top = text_top(input);
+        self.text_lines(input)
+            .into_iter()
+            .enumerate()
+            .filter_map(|(index, (start, line))| {
+                let (from, to) = (range.start.max(start), range.end.min(start + line.len()));
+                if from >= to {
+                    return None;
+                }
+                let x = self.prefix_width(input, &line[..from - start]);
+                Some(Rect::new(
+                    (
+                        self.line_start(input, &line) + x,
+                        input.line_pitch() * f64::from(index as u32) + top,
+                    ),
+                    (
+                        self.prefix_width(input, &line[..to - start]) - x,
+                        input.size,
+                    ),
+                ))
+            })
+            .collect()
+    }
+    /// Returns the lines some text is drawn on, each with the byte offset in the content at which
+    /// it begins.
+    fn text_lines(&self, input: &Text) -> Vec<(usize, String)> {
+        self.locate_lines(input, self.break_lines(input))
+    }
+    /// Returns the lines some text is drawn on, wrapped if it has a maximum width.
+    fn break_lines(&self, input: &Text) -> Vec<String> {
         if input.max_width.is_some() {
-            lines = self.wrap_text(&input);
-        }
-        for (index, line) in lines.iter().enumerate() {
-            if input.letter_spacing != 0. {
-                self.fill_text_with_spacing(
-                    line,
-                    (0., input.line_height * f64::from(index as u32)).into(),
-                    input.letter_spacing,
-                );
-            } else {
-                state.context.fill_text(
-                    line,
-                    0.,
-                    input.line_height * f64::from(index as u32),
-                    None,
-                );
+            self.wrap_text(input)
+        } else {
+            input
+                .content
+                .split('\n')
+                .map(std::borrow::ToOwned::to_owned)
+                .collect()
+        }
+    }
+    /// Returns each of the lines some text is drawn on with the byte offset in the content at
+    /// which it begins.
+    fn locate_lines(&self, input: &Text, lines: Vec<String>) -> Vec<(usize, String)> {
+        let mut cursor = 0;
+        lines
+            .into_iter()
+            .map(|line| {
+                // Wrapped lines omit the whitespace they were broken at, so each is located in
+                // the content following the previous line.
+                let range = find_line(&input.content, cursor, &line)
+                    .unwrap_or(cursor..(cursor + line.len()).min(input.content.len()));
+                cursor = range.end;
+                (range.start, line)
+            })
+            .collect()
+    }
+    /// Returns the width of the start of a line of text, including the letter spacing after it.
+    fn prefix_width(&self, input: &Text, prefix: &str) -> f64 {
+        if prefix.is_empty() {
+            0.
+        } else {
+            self.line_width(input, prefix) + input.letter_spacing
+        }
+    }
+    /// Returns the horizontal position at which a line of text starts for its alignment.
+    fn line_start(&self, input: &Text, line: &str) -> f64 {
+        let width = self.line_width(input, line);
+        match input.align {
+            Align::Start => 0.,
+            Align::Center => -width / 2.,
+            Align::End => -width,
+        }
+    }
+    /// Returns the glyphs of some text. Canvas exposes no shaping, so each grapheme cluster is
+    /// given as a single glyph.
+    fn text_glyphs(&self, input: &Text) -> Vec<Glyph> {
+        self.update_text_style(&input);
+        let baseline = text_top(input) + input.size * 0.8;
+        let mut glyphs = vec![];
+        for (index, (start, line)) in self.text_lines(input).into_iter().enumerate() {
+            let (x, y) = (
+                self.line_start(input, &line),
+                input.line_pitch() * f64::from(index as u32) + baseline,
+            );
+            let mut boundaries = grapheme::boundaries(&line);
+            boundaries.push(line.len());
+            for pair in boundaries.windows(2) {
+                let (from, to) = (self.prefix_width(input, &line[..pair[0]]), pair[1]);
+                let cluster = grapheme::from_byte_index(&input.content, start + pair[0]);
+                glyphs.push(Glyph {
+                    id: None,
+                    position: (x + from, y).into(),
+                    advance: self.prefix_width(input, &line[..to]) - from,
+                    offset: Vector2::default(),
+                    cluster: cluster..cluster + 1,
+                });
             }
         }
+        glyphs
+    }
+    fn caret_rect(&self, input: &Text, index: usize) -> Rect {
+        self.update_text_style(&input);
+        let byte = grapheme::to_byte_index(&input.content, index);
+        let lines = self.text_lines(input);
+        let line_index = lines
+            .iter()
+            .rposition(|(start, _)| *start <= byte)
+            .unwrap_or(0);
+        let (start, line) = &lines[line_index];
+        let prefix = line.get(..byte.saturating_sub(*start)).unwrap_or(line);
+        Rect::new(
+            (
+                self.line_start(input, line) + self.prefix_width(input, prefix),
+                input.line_pitch() * f64::from(line_index as u32) + text_top(input),
+            ),
+            (0., input.size),
+        )
+    }
+    fn text_index(&self, input: &Text, point: Vector2) -> usize {
+        self.update_text_style(&input);
+        let lines = self.text_lines(input);
+        let line_index = ((point.y - text_top(input)) / input.line_pitch())
+            .floor()
+            .max(0.) as usize;
+        let (start, line) = &lines[line_index.min(lines.len() - 1)];
+        let x = point.x - self.line_start(input, line);
+        let distance = |boundary: &usize| (self.prefix_width(input, &line[..*boundary]) - x).abs();
+        let nearest = grapheme::boundaries(line)
+            .into_iter()
+            .chain(Some(line.len()))
+            .min_by(|a, b| distance(a).partial_cmp(&distance(b)).unwrap())
+            .unwrap_or(0);
+        grapheme::from_byte_index(&input.content, start + nearest)
+    }
+    /// Returns the objects of the frame at the provided position in its pixels, topmost first.
+    fn hits(&self, position: Vector2) -> impl Iterator<Item = Box<dyn Object>> + '_ {
+        let (contents, layers, point, clip) = {
+            let state = self.state.read().unwrap();
+            (
+                state
+                    .contents
+                    .iter()
+                    .cloned()
+                    .sorted_by_key(|object| object.state.read().unwrap().depth)
+                    .collect(),
+                state.layers.clone(),
+                state.viewport.position + position / state.size * state.viewport.size,
+                state.clip.clone(),
+            )
+        };
+        // Layers are composited above the contents, which are drawn in order of depth.
+        let hit = move |object: &CanvasObject| {
+            let state = object.state.read().unwrap();
+            state.visible
+                && state.blend != Blend::Erase
+                && hit_test(
+                    &state.content,
+                    state.orientation,
+                    state.clip.as_ref(),
+                    point,
+                    |text| {
+                        // Hit testing reuses the rects until the content changes.
+                        let mut rects = state.text_rects.lock().unwrap();
+                        rects
+                            .get_or_insert_with(|| {
+                                self.selection_rects(text, 0..grapheme::count(&text.content))
+                            })
+                            .clone()
+                    },
+                )
+        };
+        objects_at(layers, contents, position, point, clip.as_ref(), hit)
     }
     fn element(&self) -> CanvasElement {
         let state = self.state.read().unwrap();
         state.canvas.clone()
     }
     fn measure_text_height(&self, input: Text) -> f64 {
-        let font = match input.font {
-            Font::SystemFont => {
-                format!(r#"{} {} {}px -apple-system, BlinkMacSystemFont, "Segoe UI", Roboto, Helvetica, Arial, sans-serif, "Apple LDRColor Emoji", "Segoe UI Emoji", "Segoe UI Symbol""#, if input.italic { "italic " } else { "" }, match input.weight {
-                    Weight::Normal => "400",
-                    Weight::Medium => "500",
-                    Weight::SemiBold => "600",
-                    Weight::Bold => "700",
-                    Weight::ExtraBold => "800",
-                    Weight::Heavy => "900",
-                    Weight::Thin => "200",
-                    Weight::Light => "300",
-                    Weight::Hairline => "100"
-                }, input.size)
-            }
-        };
+        let font = css_font(&input, &self.state.read().unwrap().fallback);
+        // The content is measured alongside the sample letters since fallback fonts, color emoji
+        // fonts especially, can have taller lines than the font of the text.
+        let content = input.content.replace('\n', " ");
         (js! {
             let el = document.createElement("span");
             el.style.position = "fixed";
             el.style.left = "-5000px";
             el.style.top = "-5000px";
-            el.textContent = "abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ";
+            el.style.whiteSpace = "pre";
+            el.textContent = "abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ" + @{content};
             el.style.font = @{font};
             document.body.appendChild(el);
             let offsetHeight = el.offsetHeight;
@@ -661,13 +1519,86 @@ impl CanvasFrame {
         .try_into()
         .unwrap()
     }
+    fn ellipsize(&self, input: &Text, line: &str, ellipsize: Ellipsize) -> String {
+        let max_width = input.max_width.unwrap();
+        if self.measure_text_with_spacing(line, input.letter_spacing) <= max_width {
+            return line.to_owned();
+        }
+        let graphemes = grapheme::graphemes(line);
+        let shorten = |kept: usize| {
+            let (head, tail) = match ellipsize {
+                Ellipsize::Start => (0, kept),
+                Ellipsize::Middle => (kept - kept / 2, kept / 2),
+                Ellipsize::End => (kept, 0),
+            };
+            format!(
+                "{}\u{2026}{}",
+                graphemes[..head].concat().trim_end(),
+                graphemes[graphemes.len() - tail..].concat().trim_start()
+            )
+        };
+        // Shortening is monotonic, so the most graphemes that still fit are found by bisection.
+        let (mut fits, mut overflows) = (0, graphemes.len());
+        while overflows - fits > 1 {
+            let kept = (fits + overflows) / 2;
+            if self.measure_text_with_spacing(&shorten(kept), input.letter_spacing) <= max_width {
+                fits = kept;
+            } else {
+                overflows = kept;
+            }
+        }
+        shorten(fits)
+    }
     fn wrap_text(&self, input: &Text) -> Vec<String> {
+        if let (Wrap::Normal, Some(max_lines))
+        | (Wrap::Unicode, Some(max_lines))
+        | (Wrap::Char, Some(max_lines))
+        | (Wrap::WordChar, Some(max_lines)) = (input.wrap, input.max_lines)
+        {
+            let max_lines = max_lines.max(1) as usize;
+            return input
+                .content
+                .split('\n')
+                .flat_map(|paragraph| {
+                    let mut lines = self.wrap_text(&Text {
+                        content: paragraph.to_owned(),
+                        max_lines: None,
+                        ..input.clone()
+                    });
+                    if lines.len() > max_lines {
+                        // The last line shown takes the rest of the paragraph, which overflows
+                        // and so is ellipsized as Pango does.
+                        let mut cursor = 0;
+                        for line in &lines[..max_lines - 1] {
+                            if let Some(range) = find_line(paragraph, cursor, line) {
+                                cursor = range.end;
+                            }
+                        }
+                        lines.truncate(max_lines - 1);
+                        lines.push(self.ellipsize(
+                            input,
+                            paragraph[cursor..].trim_start(),
+                            Ellipsize::End,
+                        ));
+                    }
+                    lines
+                })
+                .collect();
+        }
         let mut lines: Vec<String> = input
             .content
             .split('\n')
             .map(std::borrow::ToOwned::to_owned)
             .collect();
         match input.wrap {
+            // Hyphenated words are wrapped as segments between spaces, which break as Normal does.
+            Wrap::Normal if input.hyphenation.is_some() => lines
+                .iter()
+                .flat_map(|line| {
+                    let segments = line.split_inclusive(' ').collect();
+                    self.wrap_segments(input, hyphenate(input, segments))
+                })
+                .collect(),
             Wrap::Normal => {
                 let mut test_string = "".to_owned();
                 lines.reverse();
@@ -710,27 +1641,113 @@ impl CanvasFrame {
                 }
                 wrapped_lines
             }
-            _ => lines,
+            Wrap::Unicode => lines
+                .iter()
+                .flat_map(|line| {
+                    self.wrap_segments(input, hyphenate(input, linebreak::segments(line)))
+                })
+                .collect(),
+            Wrap::Char => lines
+                .iter()
+                .flat_map(|line| {
+                    let segments = grapheme::graphemes(line);
+                    self.wrap_segments(input, segments.into_iter().map(|s| (s, false)).collect())
+                })
+                .collect(),
+            Wrap::WordChar => lines
+                .iter()
+                .flat_map(|line| {
+                    let max_width = input.max_width.unwrap();
+                    // Pieces too long for a line of their own, even once hyphenated, are broken
+                    // into grapheme clusters.
+                    let segments = hyphenate(input, linebreak::segments(line))
+                        .into_iter()
+                        .flat_map(|(segment, hyphen)| {
+                            if self
+                                .measure_text_with_spacing(segment.trim_end(), input.letter_spacing)
+                                > max_width
+                            {
+                                grapheme::graphemes(segment)
+                                    .into_iter()
+                                    .map(|grapheme| (grapheme, false))
+                                    .collect()
+                            } else {
+                                vec![(segment, hyphen)]
+                            }
+                        })
+                        .collect();
+                    self.wrap_segments(input, segments)
+                })
+                .collect(),
+            Wrap::Truncate(ellipsize) => lines
+                .iter()
+                .map(|line| self.ellipsize(input, line, ellipsize))
+                .collect(),
+            Wrap::None | Wrap::Clip => lines,
         }
     }
+    /// Greedily fills lines of the max width of some text with segments of a line of it, breaking
+    /// only between segments. Lines broken after a segment marked as hyphenated end in a hyphen.
+    fn wrap_segments(&self, input: &Text, segments: Vec<(&str, bool)>) -> Vec<String> {
+        let max_width = input.max_width.unwrap();
+        let mut wrapped_lines = vec![];
+        let mut current = "".to_owned();
+        let mut hyphenated = false;
+        for (segment, hyphen) in segments {
+            let candidate = current.clone() + segment;
+            if !current.is_empty()
+                && self.measure_text_with_spacing(candidate.trim_end(), input.letter_spacing)
+                    > max_width
+            {
+                let mut line = current.trim_end().to_owned();
+                if hyphenated {
+                    line.push('-');
+                }
+                wrapped_lines.push(line);
+                current = segment.to_owned();
+            } else {
+                current = candidate;
+            }
+            hyphenated = hyphen;
+        }
+        wrapped_lines.push(current.trim_end().to_owned());
+        wrapped_lines
+    }
 }
 
 impl Frame for CanvasFrame {
     fn set_pixel_ratio(&self, ratio: f64) {
-        let mut state = self.state.write().unwrap();
-        state.pixel_ratio = ratio;
+        let size = {
+            let mut state = self.state.write().unwrap();
+            if state.pixel_ratio == ratio {
+                return;
+            }
+            state.pixel_ratio = ratio;
+            if let Some(frame) = &state.clip_frame {
+                frame.set_pixel_ratio(ratio);
+            }
+            state.size
+        };
+        self.resize(size);
     }
     fn draw(&self) {
         let state = self.state.read().unwrap();
+        state.warnings.lock().unwrap().clear();
+        if state.raster_options.tolerance.is_some() {
+            state.warn(RenderWarning::RasterTolerance);
+        }
         let viewport = state.viewport;
-        let size = state.size;
+        let scale = Vector2::from((
+            f64::from(state.canvas.width()),
+            f64::from(state.canvas.height()),
+        )) / viewport.size;
         state.context.set_transform(
-            (size.x / viewport.size.x) * state.pixel_ratio,
+            scale.x,
             0.,
             0.,
-            (size.y / viewport.size.y) * state.pixel_ratio,
-            -viewport.position.x * state.pixel_ratio,
-            -viewport.position.y * state.pixel_ratio,
+            scale.y,
+            -viewport.position.x * scale.x,
+            -viewport.position.y * scale.y,
         );
         state.context.clear_rect(
             viewport.position.x,
@@ -738,10 +1755,35 @@ impl Frame for CanvasFrame {
             viewport.size.x,
             viewport.size.y,
         );
+        if state.background.a > 0 {
+            state
+                .context
+                .set_fill_style_color(&state.background.to_rgba_color());
+            state.context.fill_rect(
+                viewport.position.x,
+                viewport.position.y,
+                viewport.size.x,
+                viewport.size.y,
+            );
+        }
+        let smoothing = match Quality::current() {
+            Quality::Low => "low",
+            Quality::Medium => "medium",
+            Quality::High => "high",
+        };
+        js! {
+            @{&state.context}.imageSmoothingQuality = @{smoothing};
+        }
         state.context.save();
+        // The clip is kept beneath the state that objects and layers restore to, and removed once
+        // drawing finishes so that the next frame is cleared everywhere.
+        if let Some(clip) = &state.clip {
+            self.push_clip(Transform2::default().to_matrix(), clip);
+        }
         state
             .contents
             .iter()
+            .filter(|object| object.state.read().unwrap().visible)
             .sorted_by(|a, b| {
                 let a = a.state.read().unwrap();
                 let b = b.state.read().unwrap();
@@ -749,12 +1791,50 @@ impl Frame for CanvasFrame {
             })
             .for_each(|object| {
                 let object = object.state.read().unwrap();
-                let matrix = object.orientation.to_matrix();
-                match &object.content {
-                    Rasterizable::Path(path) => self.draw_path_clipped(matrix, &path),
-                    Rasterizable::Text(input) => self.draw_text(matrix, &input),
+                let orientation = match (state.interpolation, object.previous) {
+                    (Some(amount), Some(previous)) => {
+                        previous.interpolate(object.orientation, amount)
+                    }
+                    _ => object.orientation,
                 };
+                let operation = match object.blend {
+                    Blend::Normal => None,
+                    Blend::Erase => Some(self.set_composite_operation("destination-out")),
+                };
+                self.draw_content(
+                    orientation.to_matrix(),
+                    &object.content,
+                    object.clip.as_ref(),
+                    Some(&object.text_lines),
+                );
+                if let Some(operation) = operation {
+                    self.set_composite_operation(&operation);
+                }
             });
+        for layer in &state.layers {
+            let (frame, opacity, blend) =
+                match layer.render(state.size, state.viewport, state.pixel_ratio) {
+                    Some(rendered) => rendered,
+                    None => continue,
+                };
+            let canvas = frame.as_any().downcast::<CanvasFrame>().unwrap().element();
+            let operation = match blend {
+                Blend::Normal => "source-over",
+                Blend::Erase => "destination-out",
+            };
+            state.context.restore();
+            state.context.save();
+            js! {
+                let context = @{&state.context};
+                context.setTransform(1, 0, 0, 1, 0, 0);
+                context.globalAlpha = @{opacity};
+                context.globalCompositeOperation = @{operation};
+                context.drawImage(@{&canvas}, 0, 0);
+            }
+        }
+        if state.clip.is_some() {
+            self.pop_clip();
+        }
     }
     fn show(&self) {
         let state = self.state.read().unwrap();
@@ -765,11 +1845,59 @@ impl Frame for CanvasFrame {
         document().body().unwrap().append_child(&state.canvas);
     }
     fn add(&mut self, content: Content) -> Box<dyn Object> {
-        let object = CanvasObject::new(content.content, content.transform, content.depth);
+        let object = CanvasObject::new(
+            content.content,
+            content.transform,
+            content.depth,
+            content.clip,
+        );
+        // Retained layouts are drawn on the lines they were broken onto until the object is
+        // zoomed or updated.
+        if let Some(Ok(layout)) = content
+            .layout
+            .map(|layout| layout.as_any().downcast::<CanvasTextLayout>())
+        {
+            *object.state.read().unwrap().text_lines.lock().unwrap() = Some((1., layout.lines));
+        }
+        object.state.write().unwrap().tag = content.tag;
         let mut state = self.state.write().unwrap();
         state.contents.push(object.clone());
         Box::new(object)
     }
+    fn remove(&mut self, object: &dyn Object) -> bool {
+        let mut state = self.state.write().unwrap();
+        if let Ok(object) = object.as_any().downcast::<CanvasObject>() {
+            let count = state.contents.len();
+            state
+                .contents
+                .retain(|other| !Arc::ptr_eq(&other.state, &object.state));
+            if state.contents.len() != count {
+                return true;
+            }
+        }
+        state.layers.iter_mut().any(|layer| layer.remove(object))
+    }
+    fn clear(&mut self) {
+        let mut state = self.state.write().unwrap();
+        state.contents.clear();
+        state.layers.iter_mut().for_each(Layer::clear);
+    }
+    fn add_layer(&mut self) -> Box<dyn Layer> {
+        let pixel_ratio = self.state.read().unwrap().pixel_ratio;
+        let frame = CanvasFrame::new_raw(pixel_ratio);
+        frame.state.write().unwrap().clip_frame = Some(CanvasFrame::new_raw(pixel_ratio));
+        let layer = FrameLayer::new(Box::new(frame));
+        self.state.write().unwrap().layers.push(layer.clone());
+        Box::new(layer)
+    }
+    fn layers(&self) -> Vec<Box<dyn Layer>> {
+        let state = self.state.read().unwrap();
+        state
+            .layers
+            .iter()
+            .map(|layer| Box::new(layer.clone()) as Box<dyn Layer>)
+            .collect()
+    }
     fn set_viewport(&self, viewport: Rect) {
         let mut state = self.state.write().unwrap();
         state.viewport = viewport;
@@ -777,11 +1905,24 @@ impl Frame for CanvasFrame {
             frame.set_viewport(viewport);
         }
     }
+    fn get_background(&self) -> LDRColor {
+        self.state.read().unwrap().background
+    }
+    fn set_background(&self, color: LDRColor) {
+        self.state.write().unwrap().background = color;
+    }
+    fn get_clip(&self) -> Option<Path> {
+        self.state.read().unwrap().clip.clone()
+    }
+    fn set_clip(&self, clip: Option<Path>) {
+        self.state.write().unwrap().clip = clip;
+    }
     fn resize(&self, size: Vector2) {
         let mut state = self.state.write().unwrap();
         state.size = size;
-        state.canvas.set_height((size.y * state.pixel_ratio) as u32);
-        state.canvas.set_width((size.x * state.pixel_ratio) as u32);
+        let (width, height) = Logical(size).to_physical(state.pixel_ratio).to_pixels();
+        state.canvas.set_width(width);
+        state.canvas.set_height(height);
         if let Some(frame) = &state.clip_frame {
             frame.resize(size);
         }
@@ -795,33 +1936,174 @@ impl Frame for CanvasFrame {
         self.draw();
         Box::new(state.canvas.clone())
     }
+    fn to_image_scaled(&self, pixel_ratio: f64) -> Box<dyn ImageRepresentation> {
+        let canvas: CanvasElement = document()
+            .create_element("canvas")
+            .unwrap()
+            .try_into()
+            .unwrap();
+        let context: CanvasRenderingContext2d = canvas.get_context().unwrap();
+        let (previous_ratio, previous_canvas, previous_context, clip_ratio) = {
+            let mut state = self.state.write().unwrap();
+            let pixel_ratio = image_pixel_ratio(state.size, pixel_ratio, state.pixel_ratio);
+            let (width, height) = Logical(state.size).to_physical(pixel_ratio).to_pixels();
+            canvas.set_width(width);
+            canvas.set_height(height);
+            let clip_ratio = state.clip_frame.as_ref().map(|frame| {
+                let ratio = frame.state.read().unwrap().pixel_ratio;
+                frame.set_pixel_ratio(pixel_ratio);
+                ratio
+            });
+            (
+                std::mem::replace(&mut state.pixel_ratio, pixel_ratio),
+                std::mem::replace(&mut state.canvas, canvas.clone()),
+                std::mem::replace(&mut state.context, context),
+                clip_ratio,
+            )
+        };
+        self.draw();
+        let mut state = self.state.write().unwrap();
+        state.pixel_ratio = previous_ratio;
+        state.canvas = previous_canvas;
+        state.context = previous_context;
+        if let (Some(frame), Some(ratio)) = (&state.clip_frame, clip_ratio) {
+            frame.set_pixel_ratio(ratio);
+        }
+        Box::new(canvas)
+    }
     fn measure(&self, input: Rasterizable) -> Vector2 {
         match input {
             Rasterizable::Text(input) => {
                 self.update_text_style(&input);
                 let origin = input.origin;
+                let shift = input.first_line_shift(input.size * 0.8);
                 let mut size: Vector2 = if input.max_width.is_some() {
                     let lines = self.wrap_text(&input);
                     (
                         input.max_width.unwrap(),
-                        (f64::from((lines.len() - 1).max(0) as u32) * input.line_height)
+                        (f64::from((lines.len() - 1).max(0) as u32) * input.line_pitch())
                             + input.size,
                     )
                         .into()
                 } else {
                     (
-                        self.measure_text_with_spacing(&input.content, input.letter_spacing),
+                        self.line_width(&input, &input.content),
                         self.measure_text_height(*input),
                     )
                         .into()
                 };
+                size.y += shift;
                 if origin == Origin::Middle {
                     size.y = 0.;
                 }
                 size
             }
             Rasterizable::Path(input) => input.bounds().size,
+            Rasterizable::Extension(input) => input.measure(),
+        }
+    }
+    fn advance(&self) {
+        let state = self.state.read().unwrap();
+        state.contents.iter().for_each(|object| {
+            let mut object = object.state.write().unwrap();
+            object.previous = Some(object.orientation);
+        });
+        state.layers.iter().for_each(FrameLayer::advance);
+    }
+    fn set_interpolation(&self, amount: Option<f64>) {
+        let mut state = self.state.write().unwrap();
+        state.interpolation = amount;
+        state
+            .layers
+            .iter()
+            .for_each(|layer| layer.set_interpolation(amount));
+    }
+    fn set_raster_options(&self, options: RasterOptions) {
+        // Canvas flattens curves itself and exposes no control over its tolerance, so the options
+        // are only kept to report that.
+        self.state.write().unwrap().raster_options = options;
+    }
+    fn set_fallback_fonts(&self, families: Vec<String>) {
+        let mut state = self.state.write().unwrap();
+        state.fallback = families.clone();
+        // Text is broken into lines again with the new families.
+        for object in &state.contents {
+            let object_state = object.state.read().unwrap();
+            *object_state.text_lines.lock().unwrap() = None;
+            *object_state.text_rects.lock().unwrap() = None;
         }
+        state
+            .layers
+            .iter()
+            .for_each(|layer| layer.set_fallback_fonts(families.clone()));
+    }
+    fn warnings(&self) -> Vec<RenderWarning> {
+        self.state.read().unwrap().warnings.lock().unwrap().clone()
+    }
+    fn cursor_at(&self, position: Vector2) -> Option<CursorIcon> {
+        self.hits(position).next().map(|object| object.get_cursor())
+    }
+    fn children(&self) -> Vec<Box<dyn Object>> {
+        let state = self.state.read().unwrap();
+        let mut objects: Vec<Box<dyn Object>> = state
+            .contents
+            .iter()
+            .map(|object| Box::new(object.clone()) as Box<dyn Object>)
+            .collect();
+        objects.extend(state.layers.iter().flat_map(|layer| layer.children()));
+        objects
+    }
+    fn objects_at(&self, position: Vector2) -> Vec<Box<dyn Object>> {
+        self.hits(position).collect()
+    }
+    fn find_text(&self, query: &str, options: search::Options) -> Vec<Match> {
+        let contents = self.state.read().unwrap().contents.clone();
+        contents
+            .into_iter()
+            .flat_map(|object| {
+                let text = match &object.state.read().unwrap().content {
+                    Rasterizable::Text(text) => text.clone(),
+                    _ => return vec![],
+                };
+                search::find(&text.content, query, options)
+                    .into_iter()
+                    .map(|range| {
+                        let bytes = grapheme::to_byte_index(&text.content, range.start)
+                            ..grapheme::to_byte_index(&text.content, range.end);
+                        Match {
+                            object: Box::new(object.clone()),
+                            rects: self.text_rects(&text, bytes),
+                            range,
+                        }
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+    fn caret(&self, text: &Text, index: usize) -> Rect {
+        self.caret_rect(text, index)
+    }
+    fn hit_test_text(&self, text: &Text, point: Vector2) -> usize {
+        self.text_index(text, point)
+    }
+    fn selection_rects(&self, text: &Text, range: Range<usize>) -> Vec<Rect> {
+        self.text_rects(
+            text,
+            grapheme::to_byte_index(&text.content, range.start)
+                ..grapheme::to_byte_index(&text.content, range.end),
+        )
+    }
+    fn glyphs(&self, text: &Text) -> Vec<Glyph> {
+        self.text_glyphs(text)
+    }
+    fn layout_text(&self, text: Text) -> Box<dyn TextLayout> {
+        self.update_text_style(&text);
+        Box::new(CanvasTextLayout {
+            lines: self.break_lines(&text),
+            size: self.measure(text.clone().into()),
+            text,
+            frame: self.state.clone(),
+        })
     }
     fn box_clone(&self) -> Box<dyn Frame> {
         Box::new(CanvasFrame {
@@ -837,17 +2119,28 @@ impl Frame for CanvasFrame {
 
 struct Canvas {
     state: Arc<RwLock<CanvasState>>,
+    shutdown: Arc<AtomicBool>,
 }
 
 struct CanvasState {
     root_frame: Option<Box<dyn Frame>>,
     size: ObserverCell<Vector2>,
     tick_handlers: Vec<Box<dyn FnMut(f64) + Send + Sync>>,
+    pointer: Option<Vector2>,
+    cursor: CursorIcon,
+    ui_scale: Arc<RwLock<f64>>,
+    accessibility: Option<Preferences>,
 }
 
 impl Rasterizer for Canvas {
-    fn rasterize(&self, input: Rasterizable, size: Vector2) -> Box<dyn ImageRepresentation> {
+    fn rasterize(
+        &self,
+        input: Rasterizable,
+        size: Vector2,
+        options: RasterOptions,
+    ) -> Box<dyn ImageRepresentation> {
         let mut frame = CanvasFrame::new();
+        frame.set_raster_options(options);
         if let Rasterizable::Text(text) = &input {
             match &text.origin {
                 Origin::Top => {
@@ -870,7 +2163,7 @@ impl Rasterizer for Canvas {
 
 impl Provider for Canvas {
     fn input(&self) -> Box<dyn Input> {
-        web::input::Input::new()
+        web::input::Input::new(self.state.read().unwrap().ui_scale.clone())
     }
 }
 
@@ -880,12 +2173,57 @@ impl Ticker for Canvas {
     }
 }
 
+impl NetProvider for Canvas {
+    fn net(&self) -> Box<dyn Net> {
+        Box::new(web::net::Net)
+    }
+}
+
+impl StorageProvider for Canvas {
+    fn storage(&self) -> Box<dyn Storage> {
+        Box::new(web::storage::Storage)
+    }
+}
+
+impl HapticsProvider for Canvas {
+    fn haptics(&self) -> Box<dyn Haptics> {
+        Box::new(web::haptics::Haptics)
+    }
+}
+
+impl AccessibilityProvider for Canvas {
+    fn accessibility(&self) -> Preferences {
+        self.state
+            .read()
+            .unwrap()
+            .accessibility
+            .unwrap_or_else(web::accessibility::preferences)
+    }
+    fn set_accessibility(&self, preferences: Option<Preferences>) {
+        self.state.write().unwrap().accessibility = preferences;
+    }
+    fn ui_scale(&self) -> f64 {
+        *self.state.read().unwrap().ui_scale.read().unwrap()
+    }
+    fn set_ui_scale(&self, scale: f64) {
+        if let Some(scale) = accessibility::clamp_ui_scale(scale) {
+            let state = self.state.read().unwrap();
+            *state.ui_scale.write().unwrap() = scale;
+            state.size.invalidate();
+        }
+    }
+}
+
 impl CanvasContext for Canvas {}
 
 impl ActiveCanvas for Canvas {
     fn box_clone(&self) -> Box<dyn ActiveCanvas> {
         Box::new(self.clone())
     }
+    fn shutdown(&self) {
+        self.shutdown.store(true, Ordering::SeqCst);
+        self.release();
+    }
 }
 
 impl InactiveCanvas for Canvas {
@@ -909,6 +2247,7 @@ impl Clone for Canvas {
     fn clone(&self) -> Canvas {
         Canvas {
             state: self.state.clone(),
+            shutdown: self.shutdown.clone(),
         }
     }
 }
@@ -917,7 +2256,7 @@ impl InteractiveCanvas for Canvas {
     fn start(self: Box<Self>, root: Box<dyn Frame>) -> Box<dyn InactiveCanvas> {
         {
             let mut state = self.state.write().unwrap();
-            let size = state.size.get();
+            let size = state.size.get() / *state.ui_scale.read().unwrap();
             let frame = root.as_any().downcast::<CanvasFrame>().unwrap();
             frame.set_root();
             root.resize(size);
@@ -931,13 +2270,26 @@ impl InteractiveCanvas for Canvas {
 impl VesselsCanvas for Canvas {
     fn frame(&self) -> Box<dyn Frame> {
         let frame = CanvasFrame::new();
-        frame.set_pixel_ratio(window().device_pixel_ratio());
+        frame.set_pixel_ratio(window().device_pixel_ratio() * self.ui_scale());
         frame
     }
 }
 
 impl Canvas {
+    /// Releases the root frame, which removes its canvas from the page, and tick handlers, unless
+    /// they're in use by a tick handler calling this, in which case the next animation frame
+    /// releases them instead.
+    fn release(&self) {
+        if let Ok(mut state) = self.state.try_write() {
+            state.root_frame = None;
+            state.tick_handlers.clear();
+        }
+    }
     fn animate(&self, start_time: f64, last_start_time: f64) {
+        if self.shutdown.load(Ordering::SeqCst) {
+            self.release();
+            return;
+        }
         let mut state = self.state.write().unwrap();
         state
             .tick_handlers
@@ -945,8 +2297,12 @@ impl Canvas {
             .for_each(|handler| (handler)(start_time - last_start_time));
         match &state.root_frame {
             Some(frame) => {
+                // The UI scale enlarges the root frame by raising its pixel ratio, leaving it as
+                // many logical pixels smaller.
+                let ui_scale = *state.ui_scale.read().unwrap();
+                frame.set_pixel_ratio(window().device_pixel_ratio() * ui_scale);
                 if state.size.is_dirty() {
-                    let size = state.size.get();
+                    let size = state.size.get() / ui_scale;
                     frame.resize(size);
                     frame.set_viewport(Rect::new((0., 0.), size));
                 }
@@ -954,6 +2310,17 @@ impl Canvas {
             }
             None => {}
         }
+        let cursor = match (&state.root_frame, state.pointer) {
+            (Some(frame), Some(position)) => frame.cursor_at(position),
+            _ => None,
+        }
+        .unwrap_or_default();
+        if cursor != state.cursor {
+            js! {
+                document.body.style.cursor = @{css_cursor(cursor)};
+            }
+            state.cursor = cursor;
+        }
         let cloned = self.clone();
         window().request_animation_frame(move |new_start_time| {
             cloned.animate(new_start_time, start_time);
@@ -961,20 +2328,43 @@ impl Canvas {
     }
 }
 
+fn css_cursor(cursor: CursorIcon) -> &'static str {
+    match cursor {
+        CursorIcon::Default => "default",
+        CursorIcon::Pointer => "pointer",
+        CursorIcon::Text => "text",
+        CursorIcon::Crosshair => "crosshair",
+        CursorIcon::Move => "move",
+        CursorIcon::Grab => "grab",
+        CursorIcon::Grabbing => "grabbing",
+        CursorIcon::NotAllowed => "not-allowed",
+        CursorIcon::Wait => "wait",
+        CursorIcon::Progress => "progress",
+        CursorIcon::Help => "help",
+        CursorIcon::ResizeHorizontal => "ew-resize",
+        CursorIcon::ResizeVertical => "ns-resize",
+        CursorIcon::ZoomIn => "zoom-in",
+        CursorIcon::ZoomOut => "zoom-out",
+        CursorIcon::Hidden => "none",
+    }
+}
+
 pub(crate) fn new() -> Box<dyn InteractiveCanvas> {
-    js! {
-        let elem = document.querySelector(".root");
-        if (elem !== null) {
-            @{|| {panic!("A graphics context has already been started")}}();
-        }
-    };
-    document()
-        .head()
-        .unwrap()
-        .append_html(
-            r#"
+    // Contexts share the page, so only the first adds its styles. Only one context can be started
+    // as the root of the page, but any number can create and rasterize frames.
+    let styled: bool = js! {
+        return document.querySelector("style.vessels") !== null;
+    }
+    .try_into()
+    .unwrap();
+    if !styled {
+        document()
+            .head()
+            .unwrap()
+            .append_html(
+                r#"
 <title></title>
-<style>
+<style class="vessels">
 body, html, canvas.root {
     height: 100%;
 }
@@ -990,9 +2380,10 @@ canvas.root {
     display: initial;
 }
 </style>
-            "#,
-        )
-        .unwrap();
+                "#,
+            )
+            .unwrap();
+    }
 
     let body = document().body().unwrap();
 
@@ -1003,10 +2394,29 @@ canvas.root {
             ),
             root_frame: None,
             tick_handlers: vec![],
+            pointer: None,
+            cursor: CursorIcon::Default,
+            ui_scale: Arc::new(RwLock::new(1.)),
+            accessibility: None,
         })),
+        shutdown: Arc::new(AtomicBool::new(false)),
     };
 
     let gfx_resize = gfx.clone();
+    let (gfx_move, gfx_out) = (gfx.clone(), gfx.clone());
+
+    // The root canvas fills the page, so positions in the page are positions in the root frame.
+    window().add_event_listener(move |event: MouseMoveEvent| {
+        let mut state = gfx_move.state.write().unwrap();
+        let ui_scale = *state.ui_scale.read().unwrap();
+        state.pointer =
+            Some(Vector2::from((event.client_x().into(), event.client_y().into())) / ui_scale);
+    });
+    window().add_event_listener(move |event: MouseOutEvent| {
+        if event.related_target().is_none() {
+            gfx_out.state.write().unwrap().pointer = None;
+        }
+    });
 
     window().add_event_listener(move |_: ResizeEvent| {
         let state = gfx_resize.state.read().unwrap();
@@ -1018,3 +2428,27 @@ canvas.root {
 
     Box::new(gfx)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fonts_fall_back_after_the_generic_family() {
+        let mut text = Text::new("")
+            .with_family("Inter")
+            .with_fallback("Emoji \"One\"")
+            .with_size(12.);
+        text.font = Font::SansSerif;
+        assert_eq!(
+            css_font(&text, &["Frame".to_owned()]),
+            r#"400 12px "Inter", sans-serif, "Emoji \"One\"", "Frame""#
+        );
+        text.italic = true;
+        text.family.clear();
+        assert_eq!(
+            css_font(&text, &[]),
+            r#"italic 400 12px sans-serif, "Emoji \"One\"""#
+        );
+    }
+}
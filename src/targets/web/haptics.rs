@@ -0,0 +1,31 @@
+use crate::haptics::Haptics as IHaptics;
+
+#[derive(Clone, Copy)]
+pub(crate) struct Haptics;
+
+impl IHaptics for Haptics {
+    fn pulse(&self, duration: f64, intensity: f64) {
+        let intensity = intensity.max(0.).min(1.);
+        js! {
+            let duration = @{duration};
+            let intensity = @{intensity};
+            if (navigator.vibrate) {
+                navigator.vibrate(duration);
+            }
+            if (navigator.getGamepads) {
+                Array.prototype.forEach.call(navigator.getGamepads(), function(gamepad) {
+                    if (gamepad && gamepad.vibrationActuator) {
+                        gamepad.vibrationActuator.playEffect("dual-rumble", {
+                            duration: duration,
+                            strongMagnitude: intensity,
+                            weakMagnitude: intensity
+                        });
+                    }
+                });
+            }
+        };
+    }
+    fn box_clone(&self) -> Box<dyn IHaptics> {
+        Box::new(*self)
+    }
+}
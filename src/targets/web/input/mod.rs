@@ -1,19 +1,24 @@
+use crate::graphics::Vector2;
 use crate::input::{
+    activation,
     keyboard::{self as keyboard_mod, Event as KeyboardEvent},
     mouse::{self, Event as MouseEvent},
+    pointer::{self, Event as PointerEvent},
     windowing::Event as WindowingEvent,
     Event, Input as IInput,
 };
 use crossbeam_channel::{unbounded, Receiver, TryRecvError};
 use futures::{task::AtomicTask, Async, Poll, Stream};
-use std::sync::Arc;
+use std::sync::{Arc, RwLock};
 
 use stdweb::traits::{IEvent, IEventTarget, IKeyboardEvent};
 use stdweb::web::{
     document,
     event::{
-        IMouseEvent, KeyDownEvent, KeyUpEvent, MouseButton, MouseDownEvent, MouseMoveEvent,
-        MouseUpEvent, MouseWheelEvent, ResizeEvent,
+        IMouseEvent, IPointerEvent, KeyDownEvent, KeyUpEvent, MouseButton, MouseDownEvent,
+        MouseMoveEvent, MouseUpEvent, MouseWheelEvent, PointerCancelEvent, PointerDownEvent,
+        PointerEnterEvent, PointerLeaveEvent, PointerMoveEvent, PointerUpEvent, PopStateEvent,
+        ResizeEvent,
     },
     window,
 };
@@ -50,10 +55,39 @@ impl IInput for Input {
     }
 }
 
+fn activation_event() -> Event {
+    Event::Activation(activation::Event {
+        url: window()
+            .location()
+            .and_then(|location| location.href().ok())
+            .unwrap_or_default(),
+    })
+}
+
+/// Returns a pointer event with its position in the logical pixels of the root frame at the
+/// provided UI scale.
+fn pointer_event<T: IPointerEvent>(event: &T, action: pointer::Action, ui_scale: f64) -> Event {
+    Event::Pointer(PointerEvent {
+        action,
+        device: event.pointer_type().as_str().into(),
+        id: event.pointer_id(),
+        position: Vector2::from((event.offset_x(), event.offset_y())) / ui_scale,
+        pressure: event.pressure(),
+    })
+}
+
 impl Input {
-    pub(crate) fn new() -> Box<dyn IInput> {
+    pub(crate) fn new(ui_scale: Arc<RwLock<f64>>) -> Box<dyn IInput> {
         let (sender, receiver) = unbounded();
         let task = Arc::new(AtomicTask::new());
+        sender.send(activation_event());
+        // Navigating to another fragment fires popstate before hashchange, so only the former is
+        // listened to so that each navigation is activated once.
+        let (pop_state_sender, pop_state_task) = (sender.clone(), task.clone());
+        window().add_event_listener(move |_: PopStateEvent| {
+            pop_state_sender.send(activation_event());
+            pop_state_task.notify();
+        });
         let (resize_sender, resize_task) = (sender.clone(), task.clone());
         window().add_event_listener(move |_: ResizeEvent| {
             resize_sender.send(Event::Windowing(WindowingEvent::Resize));
@@ -84,22 +118,88 @@ impl Input {
             })));
             mouse_down_task.notify();
         });
-        let (mouse_move_sender, mouse_move_task) = (sender.clone(), task.clone());
+        let (mouse_move_sender, mouse_move_task, mouse_move_scale) =
+            (sender.clone(), task.clone(), ui_scale.clone());
         body.add_event_listener(move |event: MouseMoveEvent| {
             event.prevent_default();
 
             mouse_move_sender.send(Event::Mouse(MouseEvent::Move(
-                (f64::from(event.movement_x()), f64::from(event.movement_y())).into(),
+                Vector2::from((f64::from(event.movement_x()), f64::from(event.movement_y())))
+                    / *mouse_move_scale.read().unwrap(),
             )));
             mouse_move_task.notify();
         });
-        let (mouse_wheel_sender, mouse_wheel_task) = (sender.clone(), task.clone());
+        let (mouse_wheel_sender, mouse_wheel_task, mouse_wheel_scale) =
+            (sender.clone(), task.clone(), ui_scale.clone());
         body.add_event_listener(move |event: MouseWheelEvent| {
             mouse_wheel_sender.send(Event::Mouse(MouseEvent::Scroll(
-                (event.delta_x(), event.delta_y()).into(),
+                Vector2::from((event.delta_x(), event.delta_y()))
+                    / *mouse_wheel_scale.read().unwrap(),
             )));
             mouse_wheel_task.notify();
         });
+        // Pointer events cover the mouse too, which the native backend matches by reporting its
+        // mouse input on both streams.
+        let (pointer_enter_sender, pointer_enter_task, pointer_enter_scale) =
+            (sender.clone(), task.clone(), ui_scale.clone());
+        body.add_event_listener(move |event: PointerEnterEvent| {
+            pointer_enter_sender.send(pointer_event(
+                &event,
+                pointer::Action::Enter,
+                *pointer_enter_scale.read().unwrap(),
+            ));
+            pointer_enter_task.notify();
+        });
+        let (pointer_down_sender, pointer_down_task, pointer_down_scale) =
+            (sender.clone(), task.clone(), ui_scale.clone());
+        body.add_event_listener(move |event: PointerDownEvent| {
+            pointer_down_sender.send(pointer_event(
+                &event,
+                pointer::Action::Down,
+                *pointer_down_scale.read().unwrap(),
+            ));
+            pointer_down_task.notify();
+        });
+        let (pointer_move_sender, pointer_move_task, pointer_move_scale) =
+            (sender.clone(), task.clone(), ui_scale.clone());
+        body.add_event_listener(move |event: PointerMoveEvent| {
+            pointer_move_sender.send(pointer_event(
+                &event,
+                pointer::Action::Move,
+                *pointer_move_scale.read().unwrap(),
+            ));
+            pointer_move_task.notify();
+        });
+        let (pointer_up_sender, pointer_up_task, pointer_up_scale) =
+            (sender.clone(), task.clone(), ui_scale.clone());
+        body.add_event_listener(move |event: PointerUpEvent| {
+            pointer_up_sender.send(pointer_event(
+                &event,
+                pointer::Action::Up,
+                *pointer_up_scale.read().unwrap(),
+            ));
+            pointer_up_task.notify();
+        });
+        let (pointer_leave_sender, pointer_leave_task, pointer_leave_scale) =
+            (sender.clone(), task.clone(), ui_scale.clone());
+        body.add_event_listener(move |event: PointerLeaveEvent| {
+            pointer_leave_sender.send(pointer_event(
+                &event,
+                pointer::Action::Leave,
+                *pointer_leave_scale.read().unwrap(),
+            ));
+            pointer_leave_task.notify();
+        });
+        let (pointer_cancel_sender, pointer_cancel_task, pointer_cancel_scale) =
+            (sender.clone(), task.clone(), ui_scale.clone());
+        body.add_event_listener(move |event: PointerCancelEvent| {
+            pointer_cancel_sender.send(pointer_event(
+                &event,
+                pointer::Action::Cancel,
+                *pointer_cancel_scale.read().unwrap(),
+            ));
+            pointer_cancel_task.notify();
+        });
         let (key_down_sender, key_down_task) = (sender.clone(), task.clone());
         body.add_event_listener(move |e: KeyDownEvent| {
             e.prevent_default();
@@ -1,3 +1,7 @@
+pub(crate) mod accessibility;
 pub(crate) mod executor;
 pub(crate) mod graphics;
+pub(crate) mod haptics;
 pub(crate) mod input;
+pub(crate) mod net;
+pub(crate) mod storage;
@@ -0,0 +1,186 @@
+use crate::errors::Error;
+use crate::net::{Message, Net as INet, Request, Response, Socket, WebSocket};
+
+use futures::{
+    sync::{mpsc, oneshot},
+    Future,
+};
+use stdweb::unstable::TryInto;
+use stdweb::web::ArrayBuffer;
+use stdweb::{Once, Reference};
+
+#[derive(Clone, Copy)]
+pub(crate) struct Net;
+
+struct BrowserSocket(Reference);
+
+impl Socket for BrowserSocket {
+    fn send(&self, message: Message) -> Result<(), Error> {
+        let socket = &self.0;
+        let open: bool = match message {
+            Message::Text(text) => js! {
+                var socket = @{socket};
+                if (socket.readyState !== 1) {
+                    return false;
+                }
+                socket.send(@{text});
+                return true;
+            },
+            Message::Binary(data) => js! {
+                var socket = @{socket};
+                if (socket.readyState !== 1) {
+                    return false;
+                }
+                socket.send(new Uint8Array(@{data}));
+                return true;
+            },
+        }
+        .try_into()
+        .unwrap();
+        if open {
+            Ok(())
+        } else {
+            Err(Error::network())
+        }
+    }
+    fn close(&self) {
+        js! { @(no_return)
+            @{&self.0}.close();
+        }
+    }
+}
+
+/// Splits the header block returned by `getAllResponseHeaders` into name and value pairs.
+fn parse_headers(headers: &str) -> Vec<(String, String)> {
+    headers
+        .split("\r\n")
+        .filter_map(|line| {
+            let separator = line.find(':')?;
+            Some((
+                line[..separator].trim().to_owned(),
+                line[separator + 1..].trim().to_owned(),
+            ))
+        })
+        .collect()
+}
+
+impl INet for Net {
+    fn fetch(&self, request: Request) -> Box<dyn Future<Item = Response, Error = Error> + Send> {
+        let (sender, receiver) = oneshot::channel();
+        let done = move |status: f64, headers: String, body: Option<ArrayBuffer>| {
+            let _ = sender.send(match body {
+                Some(body) if status > 0. => Ok(Response {
+                    status: status as u16,
+                    headers: parse_headers(&headers),
+                    body: Vec::from(body),
+                }),
+                _ => Err(Error::network()),
+            });
+        };
+        let headers: Vec<Vec<String>> = request
+            .headers
+            .into_iter()
+            .map(|(name, value)| vec![name, value])
+            .collect();
+        js! { @(no_return)
+            var done = @{Once(done)};
+            var settled = false;
+            var settle = function (status, headers, body) {
+                if (!settled) {
+                    settled = true;
+                    done(status, headers, body);
+                }
+            };
+            var request = new XMLHttpRequest();
+            // Malformed URLs, methods and headers throw rather than failing the request.
+            try {
+                request.open(@{request.method.as_str()}, @{request.url});
+                request.responseType = "arraybuffer";
+                @{headers}.forEach(function (header) {
+                    request.setRequestHeader(header[0], header[1]);
+                });
+                request.onload = function () {
+                    settle(request.status, request.getAllResponseHeaders(), request.response);
+                };
+                request.onerror = function () {
+                    settle(0, "", null);
+                };
+                var body = @{request.body};
+                request.send(body.length > 0 ? new Uint8Array(body) : null);
+            } catch (e) {
+                settle(0, "", null);
+            }
+        }
+        Box::new(
+            receiver
+                .map_err(|_| Error::network())
+                .and_then(|response| response),
+        )
+    }
+    fn connect(&self, url: &str) -> Box<dyn Future<Item = WebSocket, Error = Error> + Send> {
+        let (opened, open) = oneshot::channel();
+        let (incoming, incoming_receiver) = mpsc::unbounded();
+        let on_open = move |success: bool| {
+            let _ = opened.send(success);
+        };
+        let on_message = move |text: Option<String>, data: Option<ArrayBuffer>| {
+            let message = match (text, data) {
+                (Some(text), _) => Message::Text(text),
+                (None, Some(data)) => Message::Binary(Vec::from(data)),
+                (None, None) => return,
+            };
+            let _ = incoming.unbounded_send(message);
+        };
+        let socket: Option<Reference> = js! {
+            var open = @{Once(on_open)};
+            var message = @{on_message};
+            var socket;
+            // Malformed URLs and blocked ports throw rather than failing the connection.
+            try {
+                socket = new WebSocket(@{url});
+            } catch (e) {
+                open(false);
+                message.drop();
+                return null;
+            }
+            socket.binaryType = "arraybuffer";
+            var opened = false;
+            var settle = function (success) {
+                if (!opened) {
+                    opened = true;
+                    open(success);
+                }
+            };
+            socket.onopen = function () {
+                settle(true);
+            };
+            socket.onerror = function () {
+                settle(false);
+            };
+            socket.onmessage = function (event) {
+                if (typeof event.data === "string") {
+                    message(event.data, null);
+                } else {
+                    message(null, event.data);
+                }
+            };
+            socket.onclose = function () {
+                settle(false);
+                message.drop();
+            };
+            return socket;
+        }
+        .try_into()
+        .unwrap();
+        Box::new(open.then(move |success| match (success, socket) {
+            (Ok(true), Some(socket)) => Ok(WebSocket::new(
+                Box::new(BrowserSocket(socket)),
+                incoming_receiver,
+            )),
+            _ => Err(Error::network()),
+        }))
+    }
+    fn box_clone(&self) -> Box<dyn INet> {
+        Box::new(*self)
+    }
+}
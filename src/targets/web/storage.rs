@@ -0,0 +1,78 @@
+use crate::errors::Error;
+use crate::storage::Storage as IStorage;
+
+use stdweb::unstable::TryInto;
+
+#[derive(Clone, Copy)]
+pub(crate) struct Storage;
+
+/// Joins a namespace and key into a single local storage key. The namespace is length-prefixed so
+/// that no namespace and key pair can produce the same entry as another.
+fn entry(namespace: &str, key: &str) -> String {
+    format!("vessels:{}:{}:{}", namespace.len(), namespace, key)
+}
+
+impl IStorage for Storage {
+    fn get(&self, namespace: &str, key: &str) -> Option<Vec<u8>> {
+        js! {
+            // Local storage throws where the page isn't allowed to use it.
+            var value = null;
+            try {
+                value = window.localStorage.getItem(@{entry(namespace, key)});
+            } catch (e) {}
+            if (value === null) {
+                return null;
+            }
+            var binary = atob(value);
+            var bytes = new Array(binary.length);
+            for (var i = 0; i < binary.length; i++) {
+                bytes[i] = binary.charCodeAt(i);
+            }
+            return bytes;
+        }
+        .try_into()
+        .unwrap()
+    }
+    fn set(&self, namespace: &str, key: &str, value: &[u8]) -> Result<(), Error> {
+        let stored: bool = js! {
+            var bytes = new Uint8Array(@{value});
+            var binary = "";
+            for (var i = 0; i < bytes.length; i += 8192) {
+                binary += String.fromCharCode.apply(null, bytes.subarray(i, i + 8192));
+            }
+            try {
+                window.localStorage.setItem(@{entry(namespace, key)}, btoa(binary));
+                return true;
+            } catch (e) {
+                return false;
+            }
+        }
+        .try_into()
+        .unwrap();
+        if stored {
+            Ok(())
+        } else {
+            Err(Error::storage())
+        }
+    }
+    fn delete(&self, namespace: &str, key: &str) -> Result<(), Error> {
+        let deleted: bool = js! {
+            try {
+                window.localStorage.removeItem(@{entry(namespace, key)});
+                return true;
+            } catch (e) {
+                return false;
+            }
+        }
+        .try_into()
+        .unwrap();
+        if deleted {
+            Ok(())
+        } else {
+            Err(Error::storage())
+        }
+    }
+    fn box_clone(&self) -> Box<dyn IStorage> {
+        Box::new(*self)
+    }
+}
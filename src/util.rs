@@ -57,4 +57,23 @@ where
         state.content = value;
         state.dirty = true;
     }
+    pub(crate) fn invalidate(&self) {
+        self.state.write().unwrap().dirty = true;
+    }
+}
+
+/// Returns the time in milliseconds since an arbitrary point early in the run of the application,
+/// for measuring durations. Unlike the wall-clock time, it never goes backwards.
+pub(crate) fn now() -> f64 {
+    #[cfg(any(target_os = "linux", target_os = "macos", target_os = "windows"))]
+    static START: std::sync::OnceLock<std::time::Instant> = std::sync::OnceLock::new();
+    #[cfg(any(target_os = "linux", target_os = "macos", target_os = "windows"))]
+    return START
+        .get_or_init(std::time::Instant::now)
+        .elapsed()
+        .as_nanos() as f64
+        / 1_000_000.;
+    // Instant isn't available in browsers, where performance.now is monotonic instead.
+    #[cfg(any(target_arch = "wasm32", target_arch = "asmjs"))]
+    return stdweb::unstable::TryInto::try_into(js! { return performance.now(); }).unwrap();
 }